@@ -0,0 +1,15 @@
+//! With the `grpc` feature, generates prost types from proto/architecture.proto
+//! so the protobuf export can be tested against the schema it documents.
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/architecture.proto");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("No bundled protoc for this platform");
+        prost_build::Config::new()
+            .protoc_executable(protoc)
+            .compile_protos(&["proto/architecture.proto"], &["proto"])
+            .expect("Failed to compile proto/architecture.proto");
+    }
+}
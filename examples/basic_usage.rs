@@ -16,7 +16,7 @@ async fn main() -> Result<()> {
     
     // Scan the project
     println!("📊 Scanning project...");
-    let architecture = scanner.scan().await?;
+    let architecture = scanner.scan_async().await?;
     
     println!("✅ Found {} modules", architecture.total_modules);
     println!("📈 Total lines of code: {}", architecture.total_lines);
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use rust_architecture_visualizer::{
-    ArchitectureScanner, ArchitectureVisualizer, ProjectConfig, VisualizationSettings, 
-    ScanningSettings, ServerSettings, Theme, LayoutType
+    ArchitectureScanner, ArchitectureVisualizer, ProjectConfig, ProjectSettings,
+    VisualizationSettings, ScanningSettings, ServerSettings, Theme, LayoutType, NodeSizeMetric,
 };
 
 #[tokio::main]
@@ -15,7 +15,7 @@ async fn main() -> Result<()> {
 
     // Create custom configuration
     let config = ProjectConfig {
-        project: rust_architecture_visualizer::ProjectSettings {
+        project: ProjectSettings {
             name: Some("My Awesome Rust Project".to_string()),
             description: Some("A fantastic Rust project with custom visualization".to_string()),
             version: Some("1.0.0".to_string()),
@@ -41,8 +41,7 @@ async fn main() -> Result<()> {
             ],
             scan_interval: 10, // Scan every 10 seconds
             max_file_size: Some(5 * 1024 * 1024), // 5MB max file size
-            follow_symlinks: false,
-            ignore_gitignore: true,
+            ..Default::default()
         },
         visualization: VisualizationSettings {
             theme: Theme::Dark,
@@ -57,7 +56,8 @@ async fn main() -> Result<()> {
             filter_complexity: Some(5.0), // Only show modules with complexity > 5
             filter_type: None,
             auto_refresh: true,
-            refresh_interval: 30,
+            node_size_metric: NodeSizeMetric::Complexity, // Big boxes = complex modules
+            ..Default::default()
         },
         server: ServerSettings {
             port: 3000,
@@ -65,9 +65,10 @@ async fn main() -> Result<()> {
             cors_origins: vec!["*".to_string()],
             enable_websocket: true,
             enable_compression: true,
-            max_request_size: Some(10 * 1024 * 1024), // 10MB
             timeout: Some(60), // 60 seconds timeout
+            ..Default::default()
         },
+        ..Default::default()
     };
 
     // Create scanner with custom config
@@ -75,7 +76,7 @@ async fn main() -> Result<()> {
     
     // Scan the project
     println!("📊 Scanning project with custom configuration...");
-    let architecture = scanner.scan().await?;
+    let architecture = scanner.scan_async().await?;
     
     println!("✅ Found {} modules", architecture.total_modules);
     println!("📈 Total lines of code: {}", architecture.total_lines);
@@ -0,0 +1,148 @@
+//! Scanning on a build host and pushing the results to a central `serve` instance.
+//!
+//! The agent connects to the server's `/ws/agent` WebSocket with the shared
+//! token as a bearer credential, pushes a full scan, and pushes again whenever
+//! the sources change. The server answers every push, so a rejected map shows
+//! up on the agent's side. A dropped connection is retried until the agent is
+//! stopped, and the first push after reconnecting is always a full scan.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+use tracing::{info, warn};
+
+use crate::{scanner::ArchitectureScanner, types::ArchitectureMap};
+
+/// Pause before reconnecting to an unreachable server
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Message from an agent to the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentMessage {
+    /// A complete scan of the agent's project
+    Architecture { architecture: Box<ArchitectureMap> },
+}
+
+/// The server's answer to an agent message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentReply {
+    Accepted { last_scan: DateTime<Utc>, total_modules: usize },
+    Rejected { message: String },
+}
+
+/// Where an agent pushes to and how
+#[derive(Debug, Clone)]
+pub struct AgentOptions {
+    /// URL of the project on the server, e.g. `http://viz.internal:8000` or `.../projects/<slug>`
+    pub server: String,
+    pub token: String,
+    /// Push one scan and exit instead of following changes
+    pub once: bool,
+}
+
+/// WebSocket URL of the agent endpoint of the project at `server`
+pub fn endpoint_url(server: &str) -> Result<String> {
+    let server = server.trim_end_matches('/');
+    let base = if let Some(rest) = server.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else if let Some(rest) = server.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if server.starts_with("ws://") || server.starts_with("wss://") {
+        server.to_string()
+    } else {
+        bail!("Server URL must start with http://, https://, ws:// or wss://: {}", server);
+    };
+    Ok(format!("{}/ws/agent", base))
+}
+
+/// Push scans of `scanner`'s project until stopped, or once with `options.once`
+pub async fn run(scanner: &ArchitectureScanner, options: &AgentOptions) -> Result<()> {
+    let url = endpoint_url(&options.server)?;
+    let poll_interval = Duration::from_millis(scanner.config.watch.poll_interval_ms.max(100));
+
+    loop {
+        let mut socket = match connect(&url, &options.token).await {
+            Ok(socket) => socket,
+            Err(e) if options.once => return Err(e),
+            Err(e) => {
+                warn!("{:#}; retrying in {}s", e, RETRY_DELAY.as_secs());
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        info!("📡 Connected to {}", url);
+
+        let mut pushed_fingerprint = None;
+        loop {
+            let fingerprint = scanner.source_fingerprint()?;
+            if pushed_fingerprint != Some(fingerprint) {
+                let architecture = scanner.rescan_async().await?;
+                match push(&mut socket, architecture).await {
+                    Ok(AgentReply::Accepted { total_modules, .. }) => {
+                        info!("📤 Pushed a scan of {} modules", total_modules);
+                        pushed_fingerprint = Some(fingerprint);
+                    }
+                    Ok(AgentReply::Rejected { message }) => bail!("The server rejected the scan: {}", message),
+                    Err(e) if options.once => return Err(e),
+                    Err(e) => {
+                        warn!("{:#}; reconnecting", e);
+                        break;
+                    }
+                }
+                if options.once {
+                    let _ = socket.close(None).await;
+                    return Ok(());
+                }
+            }
+
+            // Reading while idle answers pings and notices when the server goes away
+            tokio::select! {
+                _ = tokio::time::sleep(poll_interval) => {}
+                incoming = socket.next() => match incoming {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                        warn!("Connection to {} closed; reconnecting", url);
+                        break;
+                    }
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+}
+
+async fn connect(url: &str, token: &str) -> Result<Socket> {
+    let mut request = url.into_client_request().with_context(|| format!("Invalid server URL: {}", url))?;
+    request.headers_mut().insert(
+        "Authorization",
+        HeaderValue::from_str(&format!("Bearer {}", token)).context("The agent token contains invalid characters")?,
+    );
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .with_context(|| format!("Failed to connect to {}", url))?;
+    Ok(socket)
+}
+
+/// Send one scan and wait for the server's answer
+async fn push(socket: &mut Socket, architecture: ArchitectureMap) -> Result<AgentReply> {
+    let message = serde_json::to_string(&AgentMessage::Architecture { architecture: Box::new(architecture) })?;
+    socket.send(Message::Text(message)).await.context("Failed to send the scan")?;
+    while let Some(incoming) = socket.next().await {
+        match incoming.context("Failed to read the server's reply")? {
+            Message::Text(text) => return serde_json::from_str(&text).context("Invalid reply from the server"),
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    bail!("The server closed the connection before answering")
+}
@@ -0,0 +1,482 @@
+//! Architecture quality gates for CI.
+//!
+//! A check evaluates absolute project-wide thresholds, the dependency rules of
+//! the `[rules]` section and, when a baseline scan is given, per-module
+//! regressions relative to that baseline. Modules are matched between scans by
+//! their path-derived node id. Cycles the baseline does not have are listed
+//! with the dependencies that closed them, which `attach_origins` can trace to
+//! the commits that added them.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use crate::{
+    config::{CheckSettings, DeltaThresholds, RuleSettings},
+    rules::{self, constraints},
+    scanner::{git_analyzer::LineOrigin, ignored},
+    types::{ArchitectureMap, ArchitectureNode, SourceLocation},
+};
+
+/// What a violation applies to
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ViolationScope {
+    Project,
+    Module {
+        id: String,
+        name: String,
+        file_path: String,
+    },
+}
+
+/// A threshold that was exceeded
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub scope: ViolationScope,
+    pub metric: String,
+    /// Value in the baseline scan, for delta checks
+    pub baseline: Option<f64>,
+    pub current: f64,
+    pub message: String,
+}
+
+/// Result of a check run
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub passed: bool,
+    /// Scan time of the baseline, if one was compared against
+    pub baseline_scan: Option<DateTime<Utc>>,
+    /// Modules present in both scans
+    pub compared_modules: usize,
+    /// Modules that are not in the baseline
+    pub new_modules: usize,
+    pub violations: Vec<Violation>,
+    /// Cycles the baseline does not have
+    pub new_cycles: Vec<NewCycle>,
+}
+
+/// A cycle of the current scan that the baseline does not have
+#[derive(Debug, Clone, Serialize)]
+pub struct NewCycle {
+    /// Member file paths in dependency order; the last depends on the first
+    pub file_paths: Vec<String>,
+    /// Dependencies of the cycle the baseline does not have; after `attach_origins`,
+    /// the one added last, which closed the cycle, comes first
+    pub new_edges: Vec<NewCycleEdge>,
+}
+
+/// A dependency that helped create a new cycle
+#[derive(Debug, Clone, Serialize)]
+pub struct NewCycleEdge {
+    pub from: String,
+    pub to: String,
+    /// First statement that creates the dependency, if known
+    pub location: Option<SourceLocation>,
+    /// Where that statement comes from, once attached
+    pub origin: Option<LineOrigin>,
+}
+
+/// Outcome of draft rules and thresholds, for the rule editor
+#[derive(Debug, Clone, Serialize)]
+pub struct RulePreview {
+    pub report: CheckReport,
+    /// Modules in each layer, in layer order
+    pub layers: Vec<LayerMembership>,
+    /// Modules no layer matches
+    pub unlayered: usize,
+}
+
+/// How many modules a layer gets
+#[derive(Debug, Clone, Serialize)]
+pub struct LayerMembership {
+    pub name: String,
+    pub color: String,
+    pub modules: usize,
+}
+
+/// Evaluate draft `settings` and `rules` against `architecture` without rescanning.
+///
+/// Layers are assigned again from the draft; regressions against a baseline are not checked.
+pub fn preview(architecture: &ArchitectureMap, settings: &CheckSettings, rules: &RuleSettings) -> Result<RulePreview> {
+    let mut draft = architecture.clone();
+    rules::apply_layers(&mut draft.nodes, &mut draft.edges, rules)?;
+    let report = run_check(&draft, None, settings, rules)?;
+
+    let counted: Vec<&ArchitectureNode> = draft.nodes.values().filter(|node| ignored::is_counted(node)).collect();
+    let layers = rules
+        .effective_layers()
+        .iter()
+        .enumerate()
+        .map(|(index, layer)| LayerMembership {
+            name: layer.name.clone(),
+            color: rules::layer_color(layer, index),
+            modules: counted.iter().filter(|node| node.layer.as_ref() == Some(&layer.name)).count(),
+        })
+        .collect();
+    let unlayered = counted.iter().filter(|node| node.layer.is_none()).count();
+    Ok(RulePreview { report, layers, unlayered })
+}
+
+/// Evaluate `current` against the configured thresholds and rules and, if given, the baseline
+pub fn run_check(
+    current: &ArchitectureMap,
+    baseline: Option<&ArchitectureMap>,
+    settings: &CheckSettings,
+    rules: &RuleSettings,
+) -> Result<CheckReport> {
+    let mut violations = Vec::new();
+
+    if let Some(limit) = settings.max_average_complexity {
+        if current.average_complexity > limit {
+            violations.push(Violation {
+                scope: ViolationScope::Project,
+                metric: "average_complexity".to_string(),
+                baseline: None,
+                current: current.average_complexity,
+                message: format!("average complexity {:.2} exceeds {:.2}", current.average_complexity, limit),
+            });
+        }
+    }
+
+    if let Some(limit) = settings.max_circular_dependencies {
+        let cycles = current.circular_dependencies.len();
+        if cycles > limit {
+            violations.push(Violation {
+                scope: ViolationScope::Project,
+                metric: "circular_dependencies".to_string(),
+                baseline: None,
+                current: cycles as f64,
+                message: format!("{} circular dependencies, at most {} allowed", cycles, limit),
+            });
+        }
+    }
+
+    let mut nodes: Vec<&ArchitectureNode> = current.nodes.values().collect();
+    nodes.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    if let Some(limit) = settings.max_module_complexity {
+        for node in nodes.iter().filter(|node| !node.module_type.is_generated()) {
+            if node.metrics.complexity_score > limit {
+                violations.push(Violation {
+                    scope: module_scope(node),
+                    metric: "complexity_score".to_string(),
+                    baseline: None,
+                    current: node.metrics.complexity_score,
+                    message: format!("complexity {:.2} exceeds {:.2}", node.metrics.complexity_score, limit),
+                });
+            }
+        }
+    }
+
+    // Naming rules are evaluated by the scanner; any broken rule fails the check
+    for node in nodes.iter().filter(|node| !node.naming_violations.is_empty()) {
+        violations.push(Violation {
+            scope: module_scope(node),
+            metric: "naming".to_string(),
+            baseline: None,
+            current: node.naming_violations.len() as f64,
+            message: format!("naming: {}", node.naming_violations.join("; ")),
+        });
+    }
+
+    // Likewise any forbidden dependency, and with `enforce_layers` any upward one
+    for violation in constraints::forbidden_dependencies(&current.nodes, &current.edges, rules)? {
+        let (Some(from), Some(to)) = (current.nodes.get(&violation.from), current.nodes.get(&violation.to)) else {
+            continue;
+        };
+        violations.push(Violation {
+            scope: module_scope(from),
+            metric: "forbidden_dependency".to_string(),
+            baseline: None,
+            current: violation.locations.len().max(1) as f64,
+            message: format!(
+                "depends on {} ({}){}, forbidden by rule `{}`",
+                to.name,
+                to.file_path,
+                location_suffix(&violation.locations),
+                violation.rule
+            ),
+        });
+    }
+    if rules.enforce_layers {
+        let mut upward: BTreeMap<(&str, &str), Vec<SourceLocation>> = BTreeMap::new();
+        for edge in current.edges.iter().filter(|edge| edge.layer_violation) {
+            upward.entry((edge.from.as_str(), edge.to.as_str())).or_default().extend(edge.locations.iter().cloned());
+        }
+        for ((from, to), locations) in upward {
+            let (Some(from), Some(to)) = (current.nodes.get(from), current.nodes.get(to)) else {
+                continue;
+            };
+            if from.ignored || to.ignored {
+                continue;
+            }
+            violations.push(Violation {
+                scope: module_scope(from),
+                metric: "layer_violation".to_string(),
+                baseline: None,
+                current: locations.len().max(1) as f64,
+                message: format!(
+                    "layer {} depends on {} ({}) in the higher layer {}{}",
+                    from.layer.as_deref().unwrap_or("-"),
+                    to.name,
+                    to.file_path,
+                    to.layer.as_deref().unwrap_or("-"),
+                    location_suffix(&locations)
+                ),
+            });
+        }
+    }
+
+    if rules.forbid_api_leaks {
+        for node in &nodes {
+            for leak in &node.api_leaks {
+                violations.push(Violation {
+                    scope: module_scope(node),
+                    metric: "api_leak".to_string(),
+                    baseline: None,
+                    current: 1.0,
+                    message: format!(
+                        "public `{}` exposes {} of crate {} (line {}): {}",
+                        leak.item, leak.type_path, leak.crate_name, leak.line, leak.signature
+                    ),
+                });
+            }
+        }
+    }
+
+    let mut compared_modules = 0;
+    let mut new_modules = 0;
+    if let Some(baseline) = baseline {
+        let overrides = settings
+            .overrides
+            .iter()
+            .map(|entry| {
+                glob::Pattern::new(&entry.pattern)
+                    .map(|pattern| (pattern, entry.thresholds.or(&settings.module_deltas)))
+                    .with_context(|| format!("Invalid override pattern: {}", entry.pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Generated code changes whenever its generator does
+        for node in nodes.iter().filter(|node| !node.module_type.is_generated()) {
+            let Some(previous) = baseline.nodes.get(&node.id) else {
+                new_modules += 1;
+                continue;
+            };
+            compared_modules += 1;
+
+            // The first matching override wins
+            let thresholds = overrides
+                .iter()
+                .find(|(pattern, _)| pattern.matches(&node.file_path))
+                .map(|(_, thresholds)| thresholds)
+                .unwrap_or(&settings.module_deltas);
+            check_module_deltas(node, previous, thresholds, &mut violations);
+        }
+    }
+
+    Ok(CheckReport {
+        passed: violations.is_empty(),
+        baseline_scan: baseline.map(|baseline| baseline.last_scan),
+        compared_modules,
+        new_modules,
+        violations,
+        new_cycles: baseline.map(|baseline| new_cycles(current, baseline)).unwrap_or_default(),
+    })
+}
+
+/// Cycles of `current` with a step `baseline` has no dependency for
+fn new_cycles(current: &ArchitectureMap, baseline: &ArchitectureMap) -> Vec<NewCycle> {
+    let baseline_links: HashSet<(&str, &str)> =
+        baseline.edges.iter().map(|edge| (edge.from.as_str(), edge.to.as_str())).collect();
+    let file_path = |id: &str| current.nodes.get(id).map_or_else(|| id.to_string(), |node| node.file_path.clone());
+    current
+        .circular_dependencies
+        .iter()
+        .filter_map(|cycle| {
+            let new_edges: Vec<NewCycleEdge> = cycle
+                .iter()
+                .zip(cycle.iter().cycle().skip(1))
+                .filter(|(from, to)| !baseline_links.contains(&(from.as_str(), to.as_str())))
+                .map(|(from, to)| NewCycleEdge {
+                    from: file_path(from),
+                    to: file_path(to),
+                    location: current
+                        .edges
+                        .iter()
+                        .filter(|edge| &edge.from == from && &edge.to == to)
+                        .find_map(|edge| edge.locations.first().cloned()),
+                    origin: None,
+                })
+                .collect();
+            (!new_edges.is_empty()).then(|| NewCycle {
+                file_paths: cycle.iter().map(|id| file_path(id)).collect(),
+                new_edges,
+            })
+        })
+        .collect()
+}
+
+/// Trace the statement of every new cycle edge with `origin`, e.g. `git_analyzer::line_origin`,
+/// and put the edge added last first
+pub fn attach_origins(report: &mut CheckReport, origin: impl Fn(&SourceLocation) -> Option<LineOrigin>) {
+    for cycle in &mut report.new_cycles {
+        for edge in &mut cycle.new_edges {
+            edge.origin = edge.location.as_ref().and_then(&origin);
+        }
+        cycle.new_edges.sort_by(|a, b| match (&a.origin, &b.origin) {
+            (Some(a), Some(b)) if a.is_newer_than(b) => std::cmp::Ordering::Less,
+            (Some(a), Some(b)) if b.is_newer_than(a) => std::cmp::Ordering::Greater,
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            _ => std::cmp::Ordering::Equal,
+        });
+    }
+}
+
+/// One line on what added `edge`: its statement and commit, as far as known
+pub fn describe_edge(edge: &NewCycleEdge) -> String {
+    let mut line = format!("{} -> {}{}", edge.from, edge.to, location_suffix(edge.location.as_slice()));
+    match &edge.origin {
+        Some(LineOrigin::Committed { commit, committed_at }) => {
+            let _ = write!(
+                line,
+                ", added in {} \"{}\" ({}, {})",
+                &commit.hash[..commit.hash.len().min(8)],
+                commit.subject,
+                commit.author,
+                committed_at.format("%Y-%m-%d")
+            );
+        }
+        Some(LineOrigin::Uncommitted) => line.push_str(", not committed yet"),
+        None => {}
+    }
+    line
+}
+
+/// The report as Markdown, e.g. for a pull request comment
+pub fn render_markdown(report: &CheckReport) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "### Architecture check {}\n",
+        if report.passed { "passed ✅" } else { "failed ❌" }
+    );
+    if report.baseline_scan.is_some() {
+        let _ = writeln!(
+            out,
+            "Compared {} modules against the baseline ({} new).\n",
+            report.compared_modules, report.new_modules
+        );
+    }
+    if !report.violations.is_empty() {
+        out.push_str("| Where | Problem |\n|---|---|\n");
+        for violation in &report.violations {
+            let scope = match &violation.scope {
+                ViolationScope::Project => "project".to_string(),
+                ViolationScope::Module { file_path, .. } => format!("`{}`", file_path),
+            };
+            let _ = writeln!(out, "| {} | {} |", scope, violation.message.replace('|', "\\|"));
+        }
+        out.push('\n');
+    }
+    if !report.new_cycles.is_empty() {
+        let _ = writeln!(out, "#### New circular dependencies ({})\n", report.new_cycles.len());
+        for cycle in &report.new_cycles {
+            let mut members: Vec<String> = cycle.file_paths.iter().map(|path| format!("`{}`", path)).collect();
+            members.push(members[0].clone());
+            let _ = writeln!(out, "- {}", members.join(" → "));
+            for (index, edge) in cycle.new_edges.iter().enumerate() {
+                let role = if index == 0 && edge.origin.is_some() { "closed by" } else { "new" };
+                let _ = writeln!(out, "  - {}: {}", role, describe_edge(edge));
+            }
+        }
+    }
+    out
+}
+
+fn check_module_deltas(
+    node: &ArchitectureNode,
+    previous: &ArchitectureNode,
+    thresholds: &DeltaThresholds,
+    violations: &mut Vec<Violation>,
+) {
+    if let Some(limit) = thresholds.complexity_increase_pct {
+        let before = previous.metrics.complexity_score;
+        let after = node.metrics.complexity_score;
+        if before > 0.0 && before >= thresholds.min_baseline_complexity.unwrap_or(0.0) {
+            let increase = (after - before) / before * 100.0;
+            if increase > limit {
+                violations.push(Violation {
+                    scope: module_scope(node),
+                    metric: "complexity_score".to_string(),
+                    baseline: Some(before),
+                    current: after,
+                    message: format!(
+                        "complexity {:.2} -> {:.2} (+{:.1}%, limit +{:.1}%)",
+                        before, after, increase, limit
+                    ),
+                });
+            }
+        }
+    }
+
+    if let Some(limit) = thresholds.coverage_decrease {
+        let before = previous.metrics.test_coverage;
+        let after = node.metrics.test_coverage;
+        if before - after > limit {
+            violations.push(Violation {
+                scope: module_scope(node),
+                metric: "test_coverage".to_string(),
+                baseline: Some(before),
+                current: after,
+                message: format!(
+                    "test coverage {:.1} -> {:.1} (-{:.1} points, limit -{:.1})",
+                    before,
+                    after,
+                    before - after,
+                    limit
+                ),
+            });
+        }
+    }
+
+    if let Some(limit) = thresholds.lines_increase_pct {
+        let before = previous.metrics.lines_of_code as f64;
+        let after = node.metrics.lines_of_code as f64;
+        if before > 0.0 {
+            let increase = (after - before) / before * 100.0;
+            if increase > limit {
+                violations.push(Violation {
+                    scope: module_scope(node),
+                    metric: "lines_of_code".to_string(),
+                    baseline: Some(before),
+                    current: after,
+                    message: format!(
+                        "lines of code {} -> {} (+{:.1}%, limit +{:.1}%)",
+                        before, after, increase, limit
+                    ),
+                });
+            }
+        }
+    }
+}
+
+/// ` at file:line` of the first place a dependency is made, if known
+fn location_suffix(locations: &[SourceLocation]) -> String {
+    match locations.first() {
+        Some(location) => format!(" at {}:{}", location.file_path, location.line),
+        None => String::new(),
+    }
+}
+
+fn module_scope(node: &ArchitectureNode) -> ViolationScope {
+    ViolationScope::Module {
+        id: node.id.clone(),
+        name: node.name.clone(),
+        file_path: node.file_path.clone(),
+    }
+}
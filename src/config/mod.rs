@@ -3,7 +3,11 @@ use anyhow::Result;
 
 pub mod project_config;
 
-pub use project_config::ProjectConfig;
+pub use project_config::{
+    AnomalySettings, CheckSettings, ChurnSettings, ClassificationRule, ClassificationSettings, ClippySettings, ComplexityWeights, ComponentDefinition, ComponentSettings, ConfigFileEdit, DeltaOverride, DeltaThresholds, DependencyRule, DiagnosticsSettings, DocumentationRule,
+    DocumentationSettings, ExportArtifact, ExportSettings, GroupSettings, HistorySettings, IgnoreSettings, LayerDefinition, MermaidDirection, MermaidGrouping, MermaidSettings, MetricsSettings, ModuleSelector, NamingSettings, OwnershipSettings, PngSettings, ProjectConfig, ProjectSettings, RuleSettings, StaleDataPolicy,
+    ScanVariant, ScanningSettings, ServerSettings, SettingsUpdate, WatchSettings,
+};
 
 /// Default configuration values
 pub const DEFAULT_SCAN_INTERVAL: u64 = 30;
@@ -1,10 +1,13 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fmt,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
+use crate::types::ModuleType;
+
 /// Available themes
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Theme {
@@ -24,17 +27,91 @@ pub enum LayoutType {
     Custom(String),
 }
 
+/// Metric used to size module nodes in every renderer
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NodeSizeMetric {
+    #[default]
+    LinesOfCode,
+    Complexity,
+    FanIn,
+    Churn,
+    /// Recent churn times complexity
+    Hotspot,
+}
+
+/// Which dependency edges carry a label with their relationship
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EdgeLabels {
+    #[default]
+    Always,
+    /// Only the edge under the pointer or selected; a hover tooltip in SVG output
+    OnHover,
+    /// Only circular edges and edges breaking the layering
+    CircularOnly,
+    Never,
+}
+
+/// Aggregate metric shown as a card in the stats section
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StatCard {
+    TotalModules,
+    ActiveModules,
+    LinesOfCode,
+    AverageComplexity,
+    MaxComplexity,
+    Functions,
+    Dependencies,
+    CircularDependencies,
+    Modularity,
+    /// Overall health from 0 (poor) to 100
+    HealthScore,
+    /// Share of public items with a doc comment
+    DocCoverage,
+    /// `unsafe` blocks outside tests
+    UnsafeBlocks,
+    /// `unwrap`, `expect` and panicking macros outside tests
+    PanicPoints,
+}
+
+/// How the scanner reads functions, structs, enums, traits and trait impls from a file
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ParserBackend {
+    /// Fast pattern matching that misses multi-line signatures and nested modules
+    #[default]
+    Regex,
+    /// Full syntax tree from `syn`, with derives, generics, attributes and doc comments
+    Syn,
+}
+
 /// Project configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProjectConfig {
     pub project: ProjectSettings,
     pub scanning: ScanningSettings,
     pub visualization: VisualizationSettings,
     pub server: ServerSettings,
+    pub watch: WatchSettings,
+    pub history: HistorySettings,
+    pub metrics: MetricsSettings,
+    pub check: CheckSettings,
+    pub naming: NamingSettings,
+    pub rules: RuleSettings,
+    pub documentation: DocumentationSettings,
+    pub ownership: OwnershipSettings,
+    pub churn: ChurnSettings,
+    pub diagnostics: DiagnosticsSettings,
+    pub clippy: ClippySettings,
+    pub classification: ClassificationSettings,
+    pub components: ComponentSettings,
+    pub groups: GroupSettings,
+    pub ignore: IgnoreSettings,
+    pub exports: ExportSettings,
 }
 
 /// Project-specific settings
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ProjectSettings {
     pub name: Option<String>,
     pub description: Option<String>,
@@ -45,6 +122,7 @@ pub struct ProjectSettings {
 
 /// Scanning configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ScanningSettings {
     pub include_tests: bool,
     pub include_examples: bool,
@@ -56,10 +134,36 @@ pub struct ScanningSettings {
     pub max_file_size: Option<usize>,
     pub follow_symlinks: bool,
     pub ignore_gitignore: bool,
+    /// Tag tool-written files as Generated and leave test harness macros out of complexity
+    pub detect_generated: bool,
+    /// Backend extracting code elements; files `syn` cannot parse fall back to `Regex`
+    pub parser: ParserBackend,
+    /// Add a node for each `Cargo.toml` dependency that modules `use`, with edges from those modules
+    pub include_external_crates: bool,
+    /// Files parsed at the same time; unset uses one thread per CPU
+    pub max_parallelism: Option<usize>,
+    /// Feature sets and targets to evaluate each scan under, stored as named variants of the map
+    pub variants: Vec<ScanVariant>,
+}
+
+/// A build configuration whose `cfg` attributes decide which modules exist
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanVariant {
+    pub name: String,
+    /// Enabled features, e.g. `serde` for every crate that has it or `storage/postgres` for one crate
+    pub features: Vec<String>,
+    /// Leave each crate's `default` feature off
+    pub no_default_features: bool,
+    /// Target triple, e.g. `x86_64-pc-windows-msvc`; unset keeps modules gated on the target
+    pub target: Option<String>,
+    /// Additional `cfg` options that are set, e.g. `tokio_unstable` or `foo = "bar"`
+    pub cfg: Vec<String>,
 }
 
 /// Visualization settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct VisualizationSettings {
     pub theme: Theme,
     pub layout: LayoutType,
@@ -74,10 +178,151 @@ pub struct VisualizationSettings {
     pub filter_type: Option<String>,
     pub auto_refresh: bool,
     pub refresh_interval: u64,
+    /// Metric that controls how large each module is drawn
+    pub node_size_metric: NodeSizeMetric,
+    /// Which edges are labelled, in the interactive view and SVG output alike
+    pub edge_labels: EdgeLabels,
+    /// Page sections rendered by the HTML UI
+    pub sections: PageSections,
+    /// Cards of the stats section, in the order they are shown
+    pub stat_cards: Vec<StatCard>,
+    /// Whether integration tests and benches are shown initially (they can be toggled in the UI)
+    pub show_test_targets: bool,
+    /// Module count above which the module graph opens with its directories collapsed; 0 never collapses them
+    pub collapse_directories_above: usize,
+    /// File storing pinned positions and manual groups, relative to the project directory
+    pub layout_file: String,
+    /// Link opening a file in an editor, e.g. `vscode://file/{path}:{line}`
+    pub editor_url: Option<String>,
+    /// Load React, React Flow and their stylesheet from `vendor_dir` instead of CDNs
+    pub offline_assets: bool,
+    /// Directory with the vendored libraries, relative to the project directory
+    pub vendor_dir: String,
+}
+
+/// Toggles for the individual sections of the HTML page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageSections {
+    /// Layout, organization and view toolbar
+    pub controls: bool,
+    pub legend: bool,
+    /// Summary stat cards below the graph
+    pub stats: bool,
+    /// Side panel showing the selected module
+    pub details_panel: bool,
+    pub footer: bool,
+}
+
+impl Default for PageSections {
+    fn default() -> Self {
+        Self {
+            controls: true,
+            legend: true,
+            stats: true,
+            details_panel: true,
+            footer: true,
+        }
+    }
+}
+
+/// Artifacts `serve` writes periodically
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// Output directory, relative to the project directory; nothing is written when unset
+    pub directory: Option<String>,
+    /// Seconds between exports; the first is written when the server starts
+    pub interval: u64,
+    pub artifacts: Vec<ExportArtifact>,
+    pub mermaid: MermaidSettings,
+    pub png: PngSettings,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            interval: 24 * 60 * 60,
+            artifacts: vec![ExportArtifact::Json, ExportArtifact::Svg, ExportArtifact::Markdown],
+            mermaid: MermaidSettings::default(),
+            png: PngSettings::default(),
+        }
+    }
+}
+
+/// Shape of the Mermaid flowchart export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MermaidSettings {
+    pub direction: MermaidDirection,
+    pub group_by: MermaidGrouping,
+}
+
+/// Size of the PNG export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PngSettings {
+    /// Width in pixels; the height follows from the layout
+    pub width: u32,
+}
+
+impl Default for PngSettings {
+    fn default() -> Self {
+        Self { width: 1920 }
+    }
+}
+
+/// Direction the Mermaid flowchart flows in
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MermaidDirection {
+    /// `flowchart TD`
+    #[default]
+    TopDown,
+    /// `flowchart LR`
+    LeftRight,
+}
+
+/// Subgraphs the Mermaid flowchart groups modules into
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MermaidGrouping {
+    /// One subgraph per module type
+    #[default]
+    ModuleType,
+    /// One subgraph per directory
+    Directory,
+    /// No subgraphs
+    None,
+}
+
+/// File written by scheduled exports
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ExportArtifact {
+    /// `architecture.json`, the raw scan
+    Json,
+    /// `architecture.svg`, the dependency graph
+    Svg,
+    /// `architecture.md`, a summary report
+    Markdown,
+    /// `architecture.mmd`, a Mermaid flowchart
+    Mermaid,
+}
+
+impl ExportArtifact {
+    /// Name of the file the artifact is written to
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ExportArtifact::Json => "architecture.json",
+            ExportArtifact::Svg => "architecture.svg",
+            ExportArtifact::Markdown => "architecture.md",
+            ExportArtifact::Mermaid => "architecture.mmd",
+        }
+    }
 }
 
 /// Server settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ServerSettings {
     pub port: u16,
     pub host: String,
@@ -86,60 +331,524 @@ pub struct ServerSettings {
     pub enable_compression: bool,
     pub max_request_size: Option<usize>,
     pub timeout: Option<u64>,
+    /// Age in seconds past which a served scan is stale; unset, scans never are
+    pub max_data_age: Option<u64>,
+    /// What `serve` does about a scan older than `max_data_age`
+    pub on_stale_data: StaleDataPolicy,
+}
+
+/// Reaction of `serve` to a scan older than `server.max_data_age`
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StaleDataPolicy {
+    /// Rescan the project
+    #[default]
+    Rescan,
+    /// Keep serving the scan and flag it as stale on the page
+    Warn,
+}
+
+/// Watch mode settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchSettings {
+    /// Command to run after each detected change, before rescanning (e.g. `cargo check`)
+    pub command: Option<String>,
+    /// Seconds before the command is abandoned
+    pub command_timeout: u64,
+    /// Quiet period that ends a burst of file events before the refresh starts, in milliseconds
+    pub debounce_ms: u64,
+    /// How often the project is checked for changes when file events are unavailable, in milliseconds
+    pub poll_interval_ms: u64,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        Self {
+            command: None,
+            command_timeout: 300,
+            debounce_ms: 250,
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+/// Snapshot history settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// Store location, relative to the project directory
+    pub directory: String,
+    /// Number of snapshots `history compact` keeps when no `--keep` is given
+    pub keep_last: Option<usize>,
+    /// Store a PNG preview of the module graph with every snapshot
+    pub thumbnails: bool,
+    /// Width of the previews, in pixels
+    pub thumbnail_width: u32,
+    /// Jumps against the latest snapshot that are reported as anomalies
+    pub anomalies: AnomalySettings,
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            directory: ".rust-arch-viz/history".to_string(),
+            keep_last: None,
+            thumbnails: true,
+            thumbnail_width: 320,
+            anomalies: AnomalySettings::default(),
+        }
+    }
+}
+
+/// Thresholds for flagging a scan as unusual compared with the latest snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalySettings {
+    pub enabled: bool,
+    /// Growth or shrinkage of the module count, in percent
+    pub module_count_change_pct: f64,
+    /// Rise of the average complexity, in percent
+    pub average_complexity_increase_pct: f64,
+    /// Rise of a single module's complexity score, in points
+    pub module_complexity_increase: f64,
 }
 
-impl Default for ProjectConfig {
+impl Default for AnomalySettings {
     fn default() -> Self {
         Self {
-            project: ProjectSettings {
-                name: None,
-                description: None,
-                version: None,
-                authors: Vec::new(),
-                repository: None,
-            },
-            scanning: ScanningSettings {
-                include_tests: true,
-                include_examples: false,
-                include_benches: false,
-                include_docs: false,
-                exclude_patterns: vec![
-                    "target/**".to_string(),
-                    "**/target/**".to_string(),
-                    "**/.git/**".to_string(),
-                    "**/node_modules/**".to_string(),
-                    "**/.*".to_string(),
-                ],
-                include_patterns: vec!["**/*.rs".to_string()],
-                scan_interval: 30,
-                max_file_size: Some(10 * 1024 * 1024), // 10MB
-                follow_symlinks: false,
-                ignore_gitignore: true,
-            },
-            visualization: VisualizationSettings {
-                theme: Theme::Auto,
-                layout: LayoutType::ForceDirected,
-                show_metrics: true,
-                show_dependencies: true,
-                show_errors: true,
-                show_warnings: true,
-                group_by_type: true,
-                show_file_paths: true,
-                show_documentation: true,
-                filter_complexity: None,
-                filter_type: None,
-                auto_refresh: true,
-                refresh_interval: 30,
-            },
-            server: ServerSettings {
-                port: 8000,
-                host: "127.0.0.1".to_string(),
-                cors_origins: vec!["*".to_string()],
-                enable_websocket: true,
-                enable_compression: true,
-                max_request_size: Some(10 * 1024 * 1024), // 10MB
-                timeout: Some(30),
-            },
+            enabled: true,
+            module_count_change_pct: 30.0,
+            average_complexity_increase_pct: 20.0,
+            module_complexity_increase: 10.0,
+        }
+    }
+}
+
+/// Thresholds enforced by the `check` command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckSettings {
+    pub max_average_complexity: Option<f64>,
+    /// Upper bound for the complexity of any single module
+    pub max_module_complexity: Option<f64>,
+    pub max_circular_dependencies: Option<usize>,
+    /// How far a module may regress compared to the baseline
+    pub module_deltas: DeltaThresholds,
+    /// Different delta limits for modules whose path matches a glob
+    pub overrides: Vec<DeltaOverride>,
+}
+
+/// Module naming rules evaluated during scanning; violations are flagged on the nodes and fail `check`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingSettings {
+    /// Require snake_case file and directory names
+    pub snake_case_files: bool,
+    /// Module names that are not allowed, e.g. `utils` or `helpers`
+    pub forbidden_modules: Vec<String>,
+    /// Deepest allowed module nesting below a crate's `src/` (`src/a/b.rs` is 2)
+    pub max_path_depth: Option<usize>,
+}
+
+/// Architecture rules the project is expected to follow
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleSettings {
+    /// Layers from the top (e.g. `api`) to the bottom (e.g. `core`); modules may
+    /// only depend on their own layer and the layers below it
+    pub layers: Vec<LayerDefinition>,
+    /// Module types from the top to the bottom (e.g. `["API", "Core", "Utilities"]`),
+    /// used as one layer per type when `layers` is empty
+    pub module_type_order: Vec<String>,
+    /// Dependencies the project must not have; `check` fails on any of them
+    pub forbidden_dependencies: Vec<DependencyRule>,
+    /// Also fail `check` on dependencies that point up the layering
+    pub enforce_layers: bool,
+    /// Also fail `check` on public signatures naming another workspace crate's types
+    pub forbid_api_leaks: bool,
+}
+
+impl RuleSettings {
+    /// The configured layers, or one layer per entry of `module_type_order`
+    pub fn effective_layers(&self) -> Vec<LayerDefinition> {
+        if !self.layers.is_empty() {
+            return self.layers.clone();
+        }
+        self.module_type_order
+            .iter()
+            .map(|module_type| LayerDefinition {
+                name: module_type.clone(),
+                patterns: Vec::new(),
+                module_types: vec![module_type.clone()],
+                groups: Vec::new(),
+                color: None,
+            })
+            .collect()
+    }
+}
+
+/// A dependency from any module `from` selects to any module `to` selects breaks the rule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DependencyRule {
+    /// Shown with the violations; the selectors are described otherwise
+    pub name: Option<String>,
+    pub from: ModuleSelector,
+    pub to: ModuleSelector,
+}
+
+/// Modules matching any of the patterns, module types or groups
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModuleSelector {
+    /// Globs matched against the module's file path, e.g. `src/core/**`
+    pub patterns: Vec<String>,
+    /// Module types, e.g. `API` or `Database`
+    pub module_types: Vec<String>,
+    /// Groups from the `[groups]` section
+    pub groups: Vec<String>,
+}
+
+/// One architectural layer; a module belongs to the first layer it matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerDefinition {
+    pub name: String,
+    /// Globs matched against the module's file path, e.g. `src/api/**`
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    /// Module types in the layer, e.g. `API` or `Core`
+    #[serde(default)]
+    pub module_types: Vec<String>,
+    /// Groups from the `[groups]` section whose modules are in the layer
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Color of the layer's modules in the color-by-layer mode
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// Manual module types that take precedence over the detected ones
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClassificationSettings {
+    /// Checked in order; the first rule matching a module's file path sets its type
+    pub rules: Vec<ClassificationRule>,
+}
+
+/// Module type for the files matching a glob
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassificationRule {
+    /// Glob matched against file paths relative to the project directory
+    pub pattern: String,
+    pub module_type: ModuleType,
+}
+
+/// Modules kept in the graph but left out of metrics and cycle detection, e.g. generated or vendored code
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IgnoreSettings {
+    /// Globs matched against file paths relative to the project directory
+    pub paths: Vec<String>,
+}
+
+/// Bounded contexts grouping crates and modules into components above the crate level
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComponentSettings {
+    /// Components listed by hand; checked before the conventions below
+    pub definitions: Vec<ComponentDefinition>,
+    /// Directories whose subdirectories are components, e.g. `services` for `services/billing/**`
+    pub folder_prefixes: Vec<String>,
+    /// Crate name prefixes that are components, e.g. `billing` for `billing-api` and `billing_core`
+    pub crate_prefixes: Vec<String>,
+}
+
+/// A component and the crates and files it covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentDefinition {
+    pub name: String,
+    /// Globs matched against crate names, e.g. `billing-*`
+    #[serde(default)]
+    pub crates: Vec<String>,
+    /// Globs matched against the module's file path, e.g. `src/billing/**`
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+/// Named module groups that cut across the directory layout, e.g. `Domain = ["src/billing/**", "src/orders/**"]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct GroupSettings {
+    /// Group name -> globs matched against the module's file path; a module may be in several groups
+    pub definitions: BTreeMap<String, Vec<String>>,
+}
+
+/// Per-module authorship from `git blame`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OwnershipSettings {
+    /// Run `git blame` on every module while scanning; slow on large histories
+    pub enabled: bool,
+    /// Modules with a bus factor of 1 and at least this complexity are flagged as organizational risks
+    pub risk_min_complexity: f64,
+}
+
+impl Default for OwnershipSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            risk_min_complexity: 10.0,
+        }
+    }
+}
+
+/// Per-module change history from `git log`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChurnSettings {
+    /// Read the git history of the project while scanning
+    pub enabled: bool,
+    /// Lines changed within this many days count as recent churn
+    pub recent_days: u32,
+}
+
+impl Default for ChurnSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            recent_days: 90,
+        }
+    }
+}
+
+/// Compiler errors and warnings per module from `cargo check`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsSettings {
+    /// Run `cargo check` while scanning and count its diagnostics instead of `panic!` and `unwrap()`
+    pub enabled: bool,
+    /// Extra arguments to `cargo check`, such as `--all-features`
+    pub args: Vec<String>,
+}
+
+impl Default for DiagnosticsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            args: vec!["--all-targets".to_string()],
+        }
+    }
+}
+
+/// Clippy lint counts per module and category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClippySettings {
+    /// Run `cargo clippy` while scanning, once per category
+    pub enabled: bool,
+    /// Lint groups to count, e.g. `pedantic` in addition to the default ones
+    pub categories: Vec<String>,
+    /// Extra arguments to `cargo clippy`, such as `--all-features`
+    pub args: Vec<String>,
+}
+
+impl Default for ClippySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            categories: ["correctness", "suspicious", "complexity", "perf", "style"].map(String::from).to_vec(),
+            args: vec!["--all-targets".to_string()],
+        }
+    }
+}
+
+/// Documentation linked to modules, in addition to `//! arch-viz:doc=` comments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentationSettings {
+    pub links: Vec<DocumentationRule>,
+}
+
+/// Link every module whose file path matches `pattern` to a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentationRule {
+    /// Glob matched against the module's file path, e.g. `src/scanner/**`
+    pub pattern: String,
+    /// URL, or path of a file relative to the project directory (e.g. an ADR)
+    pub target: String,
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Per-module regression limits relative to the baseline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeltaThresholds {
+    /// Maximum complexity increase, in percent of the baseline value
+    pub complexity_increase_pct: Option<f64>,
+    /// Maximum drop in test coverage, in percentage points
+    pub coverage_decrease: Option<f64>,
+    /// Maximum growth in lines of code, in percent of the baseline value
+    pub lines_increase_pct: Option<f64>,
+    /// Modules with a lower baseline complexity are exempt from the percentage check
+    pub min_baseline_complexity: Option<f64>,
+}
+
+/// Delta limits for the modules matching `pattern`; unset fields fall back to `module_deltas`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeltaOverride {
+    /// Glob matched against the module's file path, e.g. `src/legacy/**`
+    pub pattern: String,
+    #[serde(flatten)]
+    pub thresholds: DeltaThresholds,
+}
+
+impl DeltaThresholds {
+    /// These thresholds with unset fields taken from `fallback`
+    pub fn or(&self, fallback: &DeltaThresholds) -> DeltaThresholds {
+        DeltaThresholds {
+            complexity_increase_pct: self.complexity_increase_pct.or(fallback.complexity_increase_pct),
+            coverage_decrease: self.coverage_decrease.or(fallback.coverage_decrease),
+            lines_increase_pct: self.lines_increase_pct.or(fallback.lines_increase_pct),
+            min_baseline_complexity: self.min_baseline_complexity.or(fallback.min_baseline_complexity),
+        }
+    }
+}
+
+/// Metric calculation settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetricsSettings {
+    pub complexity: ComplexityWeights,
+    /// Modules with at least this many lines of code get a split suggestion
+    pub split_min_lines: usize,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            complexity: ComplexityWeights::default(),
+            split_min_lines: 300,
+        }
+    }
+}
+
+/// Weights of the constructs counted by the complexity score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComplexityWeights {
+    /// Score of a file with no counted constructs
+    pub base: f64,
+    pub if_statement: f64,
+    pub match_expression: f64,
+    pub for_loop: f64,
+    pub while_loop: f64,
+    pub loop_expression: f64,
+    /// Per pair of directly nested opening braces (`{{`)
+    pub nesting: f64,
+    /// Added once when the file contains any async code
+    pub async_code: f64,
+    /// Per `?.` chained try operator
+    pub try_operator: f64,
+    pub unwrap: f64,
+    pub expect: f64,
+}
+
+impl Default for ComplexityWeights {
+    fn default() -> Self {
+        Self {
+            base: 1.0,
+            if_statement: 0.5,
+            match_expression: 0.8,
+            for_loop: 0.6,
+            while_loop: 0.7,
+            loop_expression: 0.8,
+            nesting: 0.1,
+            async_code: 0.5,
+            try_operator: 0.2,
+            unwrap: 0.1,
+            expect: 0.1,
+        }
+    }
+}
+
+impl Default for ScanningSettings {
+    fn default() -> Self {
+        Self {
+            include_tests: true,
+            include_examples: false,
+            include_benches: false,
+            include_docs: false,
+            exclude_patterns: vec![
+                "target/**".to_string(),
+                "**/target/**".to_string(),
+                "**/.git/**".to_string(),
+                "**/node_modules/**".to_string(),
+                "**/.*".to_string(),
+            ],
+            include_patterns: vec!["**/*.rs".to_string()],
+            scan_interval: 30,
+            max_file_size: Some(10 * 1024 * 1024), // 10MB
+            follow_symlinks: false,
+            ignore_gitignore: true,
+            detect_generated: true,
+            parser: ParserBackend::default(),
+            include_external_crates: false,
+            max_parallelism: None,
+            variants: Vec::new(),
+        }
+    }
+}
+
+impl Default for VisualizationSettings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Auto,
+            layout: LayoutType::ForceDirected,
+            show_metrics: true,
+            show_dependencies: true,
+            show_errors: true,
+            show_warnings: true,
+            group_by_type: true,
+            show_file_paths: true,
+            show_documentation: true,
+            filter_complexity: None,
+            filter_type: None,
+            auto_refresh: true,
+            refresh_interval: 30,
+            node_size_metric: NodeSizeMetric::default(),
+            edge_labels: EdgeLabels::default(),
+            sections: PageSections::default(),
+            stat_cards: vec![
+                StatCard::TotalModules,
+                StatCard::ActiveModules,
+                StatCard::LinesOfCode,
+                StatCard::AverageComplexity,
+                StatCard::Dependencies,
+                StatCard::CircularDependencies,
+            ],
+            show_test_targets: true,
+            collapse_directories_above: 150,
+            layout_file: "rust-arch-viz.layout.json".to_string(),
+            editor_url: None,
+            offline_assets: false,
+            vendor_dir: ".rust-arch-viz/vendor".to_string(),
+        }
+    }
+}
+
+impl Default for ServerSettings {
+    fn default() -> Self {
+        Self {
+            port: 8000,
+            host: "127.0.0.1".to_string(),
+            cors_origins: vec!["*".to_string()],
+            enable_websocket: true,
+            enable_compression: true,
+            max_request_size: Some(10 * 1024 * 1024), // 10MB
+            timeout: Some(30),
+            max_data_age: None,
+            on_stale_data: StaleDataPolicy::default(),
         }
     }
 }
@@ -173,12 +882,8 @@ impl ProjectConfig {
     pub fn from_project_dir<P: AsRef<Path>>(dir: P) -> Result<Self> {
         let dir = dir.as_ref();
 
-        // Look for config files in the directory
-        for config_file in crate::config::CONFIG_FILES {
-            let path = dir.join(config_file);
-            if path.exists() {
-                return Self::from_file(&path);
-            }
+        if let Some(path) = Self::find_in_dir(dir) {
+            return Self::from_file(&path);
         }
 
         // If no config file found, try to load from Cargo.toml
@@ -191,6 +896,14 @@ impl ProjectConfig {
         Ok(Self::default())
     }
 
+    /// The config file `from_project_dir` reads in `dir`, if there is one
+    pub fn find_in_dir(dir: &Path) -> Option<PathBuf> {
+        crate::config::CONFIG_FILES
+            .iter()
+            .map(|config_file| dir.join(config_file))
+            .find(|path| path.exists())
+    }
+
     /// Load configuration from Cargo.toml
     pub fn from_cargo_toml<P: AsRef<Path>>(path: P) -> Result<Self> {
         let content = std::fs::read_to_string(path).with_context(|| "Failed to read Cargo.toml")?;
@@ -211,6 +924,13 @@ impl ProjectConfig {
         Ok(config)
     }
 
+    /// Replace one top-level section of the config file at `path`, keeping the rest of the file
+    pub fn save_section_to_file<P: AsRef<Path>, T: Serialize>(path: P, section: &str, value: &T) -> Result<()> {
+        let mut edit = ConfigFileEdit::open(path)?;
+        edit.set_section(section, value)?;
+        edit.save()
+    }
+
     /// Save configuration to a file
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let path = path.as_ref();
@@ -232,6 +952,144 @@ impl ProjectConfig {
     }
 }
 
+/// Sections replaced together through `POST /api/config`; those left out stay as they are
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SettingsUpdate {
+    pub classification: Option<ClassificationSettings>,
+    pub ignore: Option<IgnoreSettings>,
+    pub rules: Option<RuleSettings>,
+    pub check: Option<CheckSettings>,
+}
+
+/// Top-level sections of a config file replaced in memory, then written back at once
+pub struct ConfigFileEdit {
+    path: PathBuf,
+    content: String,
+}
+
+impl ConfigFileEdit {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Ok(Self { path, content })
+    }
+
+    /// Set `section` to `value`, keeping the rest of the file
+    pub fn set_section<T: Serialize>(&mut self, section: &str, value: &T) -> Result<()> {
+        self.content = match self.path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => {
+                let value = toml::Value::try_from(value).with_context(|| "Failed to serialize TOML config")?;
+                replace_toml_section(&self.content, section, value)?
+            }
+            Some("yaml") | Some("yml") => {
+                let mut document: serde_yaml::Mapping = if self.content.trim().is_empty() {
+                    serde_yaml::Mapping::new()
+                } else {
+                    serde_yaml::from_str(&self.content).with_context(|| "Failed to parse YAML config")?
+                };
+                let value = serde_yaml::to_value(value).with_context(|| "Failed to serialize YAML config")?;
+                document.insert(section.into(), value);
+                serde_yaml::to_string(&document).with_context(|| "Failed to serialize YAML config")?
+            }
+            Some("json") => {
+                let mut document: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_str(&self.content).with_context(|| "Failed to parse JSON config")?;
+                let value = serde_json::to_value(value).with_context(|| "Failed to serialize JSON config")?;
+                document.insert(section.to_string(), value);
+                serde_json::to_string_pretty(&document).with_context(|| "Failed to serialize JSON config")?
+            }
+            _ => return Err(anyhow::anyhow!("Unsupported config file format")),
+        };
+        Ok(())
+    }
+
+    pub fn save(self) -> Result<()> {
+        std::fs::write(&self.path, self.content)
+            .with_context(|| format!("Failed to write config file: {}", self.path.display()))
+    }
+}
+
+/// `existing` TOML with `section` set to `value`; comments and formatting outside the section are kept
+fn replace_toml_section(existing: &str, section: &str, value: toml::Value) -> Result<String> {
+    let mut document: toml_edit::DocumentMut =
+        existing.parse().with_context(|| "Failed to parse TOML config")?;
+    let mut wrapper = toml::Table::new();
+    wrapper.insert(section.to_string(), value);
+    let replacement: toml_edit::DocumentMut = toml::to_string_pretty(&wrapper)
+        .with_context(|| "Failed to serialize TOML config")?
+        .parse()
+        .with_context(|| "Failed to serialize TOML config")?;
+    let Some(mut item) = replacement.get(section).cloned() else {
+        return Ok(existing.to_string());
+    };
+
+    // Tables are written in order of their position in the file, so the new ones take the old section's place.
+    // A section without a header of its own starts at its first subtable.
+    let previous = document.get(section).and_then(toml_edit::Item::as_table).and_then(first_table);
+    let position = previous
+        .and_then(toml_edit::Table::position)
+        .unwrap_or_else(|| last_table_position(document.as_table()) + 1);
+    if let Some(table) = item.as_table_mut() {
+        // Keep the header, and with it the comments above it, even when the section only holds subtables
+        table.set_implicit(false);
+        match previous {
+            Some(previous) => *table.decor_mut() = previous.decor().clone(),
+            None if !existing.trim().is_empty() => table.decor_mut().set_prefix("\n"),
+            None => {}
+        }
+        set_table_positions(table, position);
+    }
+    document.insert(section, item);
+    Ok(document.to_string())
+}
+
+/// `table` itself when it has a header, else the subtable written first
+fn first_table(table: &toml_edit::Table) -> Option<&toml_edit::Table> {
+    if !table.is_implicit() && table.position().is_some() {
+        return Some(table);
+    }
+    table
+        .iter()
+        .flat_map(|(_, item)| match item {
+            toml_edit::Item::Table(child) => first_table(child).into_iter().collect(),
+            toml_edit::Item::ArrayOfTables(array) => array.iter().filter_map(first_table).collect(),
+            _ => Vec::new(),
+        })
+        .min_by_key(|table| table.position())
+}
+
+fn last_table_position(table: &toml_edit::Table) -> usize {
+    let mut last = table.position().unwrap_or(0);
+    for (_, item) in table.iter() {
+        match item {
+            toml_edit::Item::Table(child) => last = last.max(last_table_position(child)),
+            toml_edit::Item::ArrayOfTables(array) => {
+                for child in array.iter() {
+                    last = last.max(last_table_position(child));
+                }
+            }
+            _ => {}
+        }
+    }
+    last
+}
+
+fn set_table_positions(table: &mut toml_edit::Table, position: usize) {
+    table.set_position(position);
+    for (_, item) in table.iter_mut() {
+        match item {
+            toml_edit::Item::Table(child) => set_table_positions(child, position),
+            toml_edit::Item::ArrayOfTables(array) => {
+                for child in array.iter_mut() {
+                    set_table_positions(child, position);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 impl fmt::Display for LayoutType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -244,6 +1102,23 @@ impl fmt::Display for LayoutType {
     }
 }
 
+impl std::str::FromStr for LayoutType {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "grid" => Ok(LayoutType::Grid),
+            "force" | "force-directed" => Ok(LayoutType::ForceDirected),
+            "hierarchical" => Ok(LayoutType::Hierarchical),
+            "circular" => Ok(LayoutType::Circular),
+            other => Err(anyhow::anyhow!(
+                "Unknown layout: {} (expected grid, force, hierarchical or circular)",
+                other
+            )),
+        }
+    }
+}
+
 /// Cargo.toml structure for parsing
 #[derive(Debug, Deserialize)]
 struct CargoConfig {
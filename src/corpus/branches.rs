@@ -0,0 +1,222 @@
+//! Headline metrics of several git branches of one project, side by side.
+//!
+//! Each branch is checked out into a temporary detached worktree and scanned
+//! with the configuration of the current checkout, so differences come from
+//! the code rather than from diverging settings. A branch that cannot be
+//! checked out or scanned is reported with its error.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{fill_stats, ProjectStats};
+use crate::{config::ProjectConfig, scanner::ArchitectureScanner, types::ArchitectureMap};
+
+/// Statistics of one branch
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchStats {
+    pub branch: String,
+    /// Commit the branch pointed to when it was scanned
+    pub commit: Option<String>,
+    /// Metrics of the branch; `name` is the branch and `path` the project directory within the repository
+    pub stats: ProjectStats,
+}
+
+/// Change in headline metrics from one branch to another
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchDiff {
+    pub from: String,
+    pub to: String,
+    pub modules: i64,
+    pub lines: i64,
+    pub edges: i64,
+    pub circular_dependencies: i64,
+    pub modularity_score: f64,
+    pub health_score: f64,
+    pub median_complexity: f64,
+    /// Module file paths only `to` has
+    pub added_modules: Vec<String>,
+    /// Module file paths only `from` has
+    pub removed_modules: Vec<String>,
+}
+
+/// Result of a branch comparison
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchReport {
+    pub generated_at: DateTime<Utc>,
+    pub branches: Vec<BranchStats>,
+    /// One entry per pair of successfully scanned branches, in the order they were given
+    pub diffs: Vec<BranchDiff>,
+}
+
+/// Scan every branch of the repository containing `project` and compare them pairwise
+pub async fn compare_branches(project: &Path, config: &ProjectConfig, branches: &[String]) -> Result<BranchReport> {
+    let root = PathBuf::from(git(project, &["rev-parse", "--show-toplevel"]).context("Not inside a git repository")?);
+    let prefix = git(project, &["rev-parse", "--show-prefix"])?;
+    let project_dir = if prefix.is_empty() { ".".to_string() } else { prefix.trim_end_matches('/').to_string() };
+
+    let mut entries = Vec::new();
+    let mut module_sets = Vec::new();
+    for (index, branch) in branches.iter().enumerate() {
+        let mut entry = BranchStats {
+            branch: branch.clone(),
+            commit: None,
+            stats: ProjectStats {
+                name: branch.clone(),
+                path: project_dir.clone(),
+                ..Default::default()
+            },
+        };
+        let mut modules = None;
+        match scan_branch(&root, &prefix, branch, index, config).await {
+            Ok((commit, architecture)) => {
+                entry.commit = Some(commit);
+                fill_stats(&mut entry.stats, &architecture);
+                modules = Some(architecture.nodes.into_values().map(|node| node.file_path).collect::<BTreeSet<_>>());
+            }
+            Err(e) => {
+                tracing::warn!("Failed to scan branch {}: {:#}", branch, e);
+                entry.stats.error = Some(format!("{:#}", e));
+            }
+        }
+        entries.push(entry);
+        module_sets.push(modules);
+    }
+
+    let mut diffs = Vec::new();
+    for from in 0..entries.len() {
+        for to in from + 1..entries.len() {
+            if let (Some(from_modules), Some(to_modules)) = (&module_sets[from], &module_sets[to]) {
+                diffs.push(diff(&entries[from], &entries[to], from_modules, to_modules));
+            }
+        }
+    }
+
+    Ok(BranchReport {
+        generated_at: Utc::now(),
+        branches: entries,
+        diffs,
+    })
+}
+
+async fn scan_branch(
+    root: &Path,
+    prefix: &str,
+    branch: &str,
+    index: usize,
+    config: &ProjectConfig,
+) -> Result<(String, ArchitectureMap)> {
+    let commit = git(root, &["rev-parse", "--verify", &format!("{}^{{commit}}", branch)])
+        .with_context(|| format!("Unknown branch or commit: {}", branch))?;
+    let worktree = std::env::temp_dir().join(format!("rust-arch-viz-branch-{}-{}", std::process::id(), index));
+    let worktree_arg = worktree.to_string_lossy().into_owned();
+    git(root, &["worktree", "add", "--detach", "--quiet", &worktree_arg, &commit])
+        .with_context(|| format!("Failed to check out {}", branch))?;
+
+    let scanned = ArchitectureScanner::new(worktree.join(prefix), config.clone()).scan_async().await;
+    // The worktree goes away whether or not the scan worked
+    if let Err(e) = git(root, &["worktree", "remove", "--force", &worktree_arg]) {
+        tracing::warn!("Failed to remove worktree {}: {:#}", worktree.display(), e);
+    }
+
+    Ok((commit, scanned?))
+}
+
+fn diff(from: &BranchStats, to: &BranchStats, from_modules: &BTreeSet<String>, to_modules: &BTreeSet<String>) -> BranchDiff {
+    let delta = |value: fn(&ProjectStats) -> usize| value(&to.stats) as i64 - value(&from.stats) as i64;
+    BranchDiff {
+        from: from.branch.clone(),
+        to: to.branch.clone(),
+        modules: delta(|stats| stats.total_modules),
+        lines: delta(|stats| stats.total_lines),
+        edges: delta(|stats| stats.total_edges),
+        circular_dependencies: delta(|stats| stats.circular_dependencies),
+        modularity_score: to.stats.modularity_score - from.stats.modularity_score,
+        health_score: to.stats.health_score - from.stats.health_score,
+        median_complexity: to.stats.complexity.median - from.stats.complexity.median,
+        added_modules: to_modules.difference(from_modules).cloned().collect(),
+        removed_modules: from_modules.difference(to_modules).cloned().collect(),
+    }
+}
+
+/// Trimmed stdout of a git command run in `dir`, or its stderr as the error
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!("git {}: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Plain-text table of the branches followed by their pairwise differences
+pub fn render_table(report: &BranchReport) -> String {
+    let name_width = report
+        .branches
+        .iter()
+        .map(|entry| entry.branch.chars().count())
+        .chain(std::iter::once("Branch".len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = format!(
+        "{:<name_width$}  {:>8}  {:>7}  {:>8}  {:>6}  {:>10}  {:>6}  {:>7}  {:>6}\n",
+        "Branch", "Commit", "Modules", "Lines", "Edges", "Modularity", "Cycles", "Cx p50", "Health",
+    );
+    for entry in &report.branches {
+        let stats = &entry.stats;
+        match &stats.error {
+            Some(error) => out.push_str(&format!("{:<name_width$}  scan failed: {}\n", entry.branch, error)),
+            None => out.push_str(&format!(
+                "{:<name_width$}  {:>8}  {:>7}  {:>8}  {:>6}  {:>10.2}  {:>6}  {:>7.1}  {:>6.0}\n",
+                entry.branch,
+                entry.commit.as_deref().map(|commit| &commit[..commit.len().min(8)]).unwrap_or("-"),
+                stats.total_modules,
+                stats.total_lines,
+                stats.total_edges,
+                stats.modularity_score,
+                stats.circular_dependencies,
+                stats.complexity.median,
+                stats.health_score,
+            )),
+        }
+    }
+
+    if report.diffs.is_empty() {
+        return out;
+    }
+    let pair_width = report
+        .diffs
+        .iter()
+        .map(|diff| diff.from.chars().count() + diff.to.chars().count() + 3)
+        .chain(std::iter::once("Branches".len()))
+        .max()
+        .unwrap_or(0);
+    out.push_str(&format!(
+        "\n{:<pair_width$}  {:>7}  {:>8}  {:>6}  {:>10}  {:>6}  {:>7}  {:>6}  {:>5}  {:>7}\n",
+        "Branches", "Modules", "Lines", "Edges", "Modularity", "Cycles", "Cx p50", "Health", "Added", "Removed",
+    ));
+    for diff in &report.diffs {
+        out.push_str(&format!(
+            "{:<pair_width$}  {:>+7}  {:>+8}  {:>+6}  {:>+10.2}  {:>+6}  {:>+7.1}  {:>+6.0}  {:>5}  {:>7}\n",
+            format!("{} → {}", diff.from, diff.to),
+            diff.modules,
+            diff.lines,
+            diff.edges,
+            diff.modularity_score,
+            diff.circular_dependencies,
+            diff.median_complexity,
+            diff.health_score,
+            diff.added_modules.len(),
+            diff.removed_modules.len(),
+        ));
+    }
+    out
+}
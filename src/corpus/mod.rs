@@ -0,0 +1,243 @@
+//! Comparative statistics over many projects.
+//!
+//! Each project is scanned with its own configuration file, if it has one.
+//! A project that fails to scan is reported with its error instead of
+//! aborting the whole run.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::{config::ProjectConfig, scanner::ArchitectureScanner, types::ArchitectureMap};
+
+pub mod branches;
+
+/// Spread of a metric over a set of modules
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Distribution {
+    pub min: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl Distribution {
+    /// Nearest-rank percentiles of `values`; all zero when empty
+    pub fn from_values(mut values: Vec<f64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        let rank = |p: f64| values[((p * values.len() as f64).ceil() as usize).clamp(1, values.len()) - 1];
+
+        Self {
+            min: values[0],
+            p25: rank(0.25),
+            median: rank(0.5),
+            p75: rank(0.75),
+            p90: rank(0.9),
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+}
+
+/// Statistics of one project in the corpus
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectStats {
+    pub name: String,
+    pub path: String,
+    pub total_modules: usize,
+    pub total_lines: usize,
+    pub total_edges: usize,
+    pub modularity_score: f64,
+    pub dependency_density: f64,
+    pub health_score: f64,
+    pub circular_dependencies: usize,
+    /// Share of modules that take part in at least one cycle
+    pub cycle_rate: f64,
+    pub complexity: Distribution,
+    /// Why the project could not be scanned, if it failed
+    pub error: Option<String>,
+}
+
+/// Aggregates over the projects that scanned successfully
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CorpusSummary {
+    pub projects: usize,
+    pub failed: usize,
+    pub average_modularity: f64,
+    pub average_cycle_rate: f64,
+    pub average_health: f64,
+    pub projects_with_cycles: usize,
+    /// Module complexity across every scanned project
+    pub complexity: Distribution,
+}
+
+/// Result of a corpus run
+#[derive(Debug, Clone, Serialize)]
+pub struct CorpusReport {
+    pub generated_at: DateTime<Utc>,
+    pub summary: CorpusSummary,
+    pub projects: Vec<ProjectStats>,
+}
+
+/// Read a project list: one path per line, blank lines and `#` comments ignored.
+///
+/// Relative paths are resolved against the list file's directory.
+pub fn read_project_list(path: &Path) -> Result<Vec<PathBuf>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read project list: {}", path.display()))?;
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect())
+}
+
+/// Scan every project and compare them
+pub async fn scan_corpus(projects: &[PathBuf]) -> CorpusReport {
+    let mut stats = Vec::new();
+    let mut all_complexities = Vec::new();
+
+    for project in projects {
+        let mut entry = ProjectStats {
+            name: project_name(project),
+            path: project.display().to_string(),
+            ..Default::default()
+        };
+        match scan_project(project).await {
+            Ok(architecture) => {
+                all_complexities.extend(architecture.nodes.values().map(|node| node.metrics.complexity_score));
+                fill_stats(&mut entry, &architecture);
+            }
+            Err(e) => {
+                tracing::warn!("Failed to scan {}: {:#}", project.display(), e);
+                entry.error = Some(format!("{:#}", e));
+            }
+        }
+        stats.push(entry);
+    }
+
+    let scanned: Vec<&ProjectStats> = stats.iter().filter(|entry| entry.error.is_none()).collect();
+    let average = |value: fn(&ProjectStats) -> f64| {
+        if scanned.is_empty() {
+            0.0
+        } else {
+            scanned.iter().map(|entry| value(entry)).sum::<f64>() / scanned.len() as f64
+        }
+    };
+    let summary = CorpusSummary {
+        projects: stats.len(),
+        failed: stats.len() - scanned.len(),
+        average_modularity: average(|entry| entry.modularity_score),
+        average_cycle_rate: average(|entry| entry.cycle_rate),
+        average_health: average(|entry| entry.health_score),
+        projects_with_cycles: scanned.iter().filter(|entry| entry.circular_dependencies > 0).count(),
+        complexity: Distribution::from_values(all_complexities),
+    };
+
+    CorpusReport {
+        generated_at: Utc::now(),
+        summary,
+        projects: stats,
+    }
+}
+
+async fn scan_project(project: &Path) -> Result<ArchitectureMap> {
+    if !project.is_dir() {
+        anyhow::bail!("not a directory");
+    }
+    let config = ProjectConfig::from_project_dir(project)?;
+    ArchitectureScanner::new(project, config).scan_async().await
+}
+
+fn fill_stats(entry: &mut ProjectStats, architecture: &ArchitectureMap) {
+    let in_cycles: HashSet<&String> = architecture.circular_dependencies.iter().flatten().collect();
+
+    entry.total_modules = architecture.total_modules;
+    entry.total_lines = architecture.total_lines;
+    entry.total_edges = architecture.edges.len();
+    entry.modularity_score = architecture.metrics.modularity_score;
+    entry.dependency_density = architecture.metrics.dependency_density;
+    entry.health_score = architecture.metrics.health_score;
+    entry.circular_dependencies = architecture.circular_dependencies.len();
+    entry.cycle_rate = if architecture.total_modules > 0 {
+        in_cycles.len() as f64 / architecture.total_modules as f64
+    } else {
+        0.0
+    };
+    entry.complexity = Distribution::from_values(
+        architecture.nodes.values().map(|node| node.metrics.complexity_score).collect(),
+    );
+}
+
+fn project_name(project: &Path) -> String {
+    let path = project.canonicalize().unwrap_or_else(|_| project.to_path_buf());
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| project.display().to_string())
+}
+
+/// Plain-text table of the report, one row per project followed by the corpus summary
+pub fn render_table(report: &CorpusReport) -> String {
+    let name_width = report
+        .projects
+        .iter()
+        .map(|entry| entry.name.chars().count())
+        .chain(std::iter::once("Project".len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = format!(
+        "{:<name_width$}  {:>7}  {:>8}  {:>10}  {:>6}  {:>10}  {:>7}  {:>7}  {:>7}  {:>6}\n",
+        "Project", "Modules", "Lines", "Modularity", "Cycles", "Cycle rate", "Cx p50", "Cx p90", "Cx max", "Health",
+    );
+    for entry in &report.projects {
+        match &entry.error {
+            Some(error) => out.push_str(&format!("{:<name_width$}  scan failed: {}\n", entry.name, error)),
+            None => out.push_str(&format!(
+                "{:<name_width$}  {:>7}  {:>8}  {:>10.2}  {:>6}  {:>9.1}%  {:>7.1}  {:>7.1}  {:>7.1}  {:>6.0}\n",
+                entry.name,
+                entry.total_modules,
+                entry.total_lines,
+                entry.modularity_score,
+                entry.circular_dependencies,
+                entry.cycle_rate * 100.0,
+                entry.complexity.median,
+                entry.complexity.p90,
+                entry.complexity.max,
+                entry.health_score,
+            )),
+        }
+    }
+
+    let summary = &report.summary;
+    out.push_str(&format!(
+        "\n{} projects ({} failed), {} with cycles\n",
+        summary.projects, summary.failed, summary.projects_with_cycles
+    ));
+    out.push_str(&format!(
+        "Average modularity {:.2}, cycle rate {:.1}%, health {:.0}\n",
+        summary.average_modularity,
+        summary.average_cycle_rate * 100.0,
+        summary.average_health
+    ));
+    out.push_str(&format!(
+        "Module complexity across the corpus: p25 {:.1}, median {:.1}, p75 {:.1}, p90 {:.1}, max {:.1}\n",
+        summary.complexity.p25,
+        summary.complexity.median,
+        summary.complexity.p75,
+        summary.complexity.p90,
+        summary.complexity.max
+    ));
+    out
+}
@@ -0,0 +1,100 @@
+//! Manual curation of the graph: pinned node positions and named module groups.
+//!
+//! Curation lives in a JSON layout file in the project (by default
+//! `rust-arch-viz.layout.json`) so it survives rescans and can be committed
+//! and shared. Node ids are derived from file paths, so entries stay attached
+//! to their modules across scans.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::config::ProjectConfig;
+
+/// Pinned positions and manual groups for a project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Curation {
+    /// Node id -> position the node is pinned at
+    pub pinned: BTreeMap<String, PinnedPosition>,
+    pub groups: Vec<ManualGroup>,
+}
+
+/// Canvas position of a pinned node
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PinnedPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A user-defined group of modules, e.g. "Payments domain"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Node ids of the grouped modules
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl Curation {
+    /// Location of the layout file for a project
+    pub fn path_for_project(project_path: &Path, config: &ProjectConfig) -> PathBuf {
+        project_path.join(&config.visualization.layout_file)
+    }
+
+    /// Load curation from `path`; a missing file means nothing has been curated yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read layout file: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse layout file: {}", path.display()))
+    }
+
+    /// Write curation to `path` as pretty-printed JSON so it diffs well in version control
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.validate()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, content)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        std::fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to write layout file: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Check that groups have unique ids and non-empty names and that no module is in two groups
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        let mut members = HashSet::new();
+        for group in &self.groups {
+            if group.id.trim().is_empty() || group.name.trim().is_empty() {
+                bail!("Groups need a non-empty id and name");
+            }
+            if !ids.insert(group.id.as_str()) {
+                bail!("Duplicate group id: {}", group.id);
+            }
+            for member in &group.members {
+                if !members.insert(member.as_str()) {
+                    bail!("Module {} is in more than one group", member);
+                }
+            }
+        }
+        for position in self.pinned.values() {
+            if !position.x.is_finite() || !position.y.is_finite() {
+                bail!("Pinned positions must be finite numbers");
+            }
+        }
+        Ok(())
+    }
+}
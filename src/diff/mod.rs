@@ -0,0 +1,248 @@
+//! Architectural drift between two scans of a project.
+//!
+//! Modules are matched between the scans by their path-derived node id, so a
+//! renamed file shows up as one removed and one added module. Dependencies are
+//! compared per pair of modules and cycles by their member set, since the
+//! reported start of a cycle may differ between scans.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::types::{
+    ArchitectureDiff, ArchitectureMap, ArchitectureNode, DependencyChange, FileCommit, MetricDelta, ModuleChange,
+    ModuleRef,
+};
+
+/// Changes from `before` to `after`
+pub fn compare(before: &ArchitectureMap, after: &ArchitectureMap) -> ArchitectureDiff {
+    let mut added_modules: Vec<ModuleRef> = after
+        .nodes
+        .values()
+        .filter(|node| !before.nodes.contains_key(&node.id))
+        .map(module_ref)
+        .collect();
+    added_modules.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    let mut removed_modules: Vec<ModuleRef> = before
+        .nodes
+        .values()
+        .filter(|node| !after.nodes.contains_key(&node.id))
+        .map(module_ref)
+        .collect();
+    removed_modules.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    let before_links = links(before);
+    let after_links = links(after);
+    let added_dependencies = changed_links(after, &after_links, &before_links);
+    let removed_dependencies = changed_links(before, &before_links, &after_links);
+
+    let before_cycles = cycles(before);
+    let after_cycles = cycles(after);
+    let new_cycles = after_cycles.difference(&before_cycles).map(|cycle| cycle.iter().cloned().collect()).collect();
+    let resolved_cycles = before_cycles.difference(&after_cycles).map(|cycle| cycle.iter().cloned().collect()).collect();
+
+    let mut changed_modules: Vec<ModuleChange> = after
+        .nodes
+        .values()
+        .filter_map(|node| {
+            let previous = before.nodes.get(&node.id)?;
+            let changed = previous.metrics.complexity_score != node.metrics.complexity_score
+                || previous.metrics.lines_of_code != node.metrics.lines_of_code;
+            changed.then(|| ModuleChange {
+                module: module_ref(node),
+                complexity_before: previous.metrics.complexity_score,
+                complexity_after: node.metrics.complexity_score,
+                lines_before: previous.metrics.lines_of_code,
+                lines_after: node.metrics.lines_of_code,
+                commits: Vec::new(),
+            })
+        })
+        .collect();
+    let complexity_change = |change: &ModuleChange| (change.complexity_after - change.complexity_before).abs();
+    changed_modules.sort_by(|a, b| {
+        complexity_change(b)
+            .total_cmp(&complexity_change(a))
+            .then_with(|| a.module.file_path.cmp(&b.module.file_path))
+    });
+
+    ArchitectureDiff {
+        before_scan: before.last_scan,
+        after_scan: after.last_scan,
+        added_modules,
+        removed_modules,
+        added_dependencies,
+        removed_dependencies,
+        new_cycles,
+        resolved_cycles,
+        metrics: metric_deltas(before, after),
+        changed_modules,
+    }
+}
+
+/// Give every changed module the commits of `commits_by_file` that touched its file
+pub fn attach_commits(diff: &mut ArchitectureDiff, commits_by_file: &HashMap<String, Vec<FileCommit>>) {
+    for change in &mut diff.changed_modules {
+        change.commits = commits_by_file.get(&change.module.file_path).cloned().unwrap_or_default();
+    }
+}
+
+fn module_ref(node: &ArchitectureNode) -> ModuleRef {
+    ModuleRef {
+        id: node.id.clone(),
+        name: node.name.clone(),
+        file_path: node.file_path.clone(),
+    }
+}
+
+/// Index of the first edge between each pair of modules
+fn links(architecture: &ArchitectureMap) -> BTreeMap<(&str, &str), usize> {
+    let mut links = BTreeMap::new();
+    for (index, edge) in architecture.edges.iter().enumerate() {
+        links.entry((edge.from.as_str(), edge.to.as_str())).or_insert(index);
+    }
+    links
+}
+
+/// Pairs linked in `links` of `architecture` but not in `other`
+fn changed_links(
+    architecture: &ArchitectureMap,
+    links: &BTreeMap<(&str, &str), usize>,
+    other: &BTreeMap<(&str, &str), usize>,
+) -> Vec<DependencyChange> {
+    let mut changes: Vec<DependencyChange> = links
+        .iter()
+        .filter(|(pair, _)| !other.contains_key(*pair))
+        .filter_map(|((from, to), index)| {
+            Some(DependencyChange {
+                from: module_ref(architecture.nodes.get(*from)?),
+                to: module_ref(architecture.nodes.get(*to)?),
+                relationship: architecture.edges[*index].relationship.clone(),
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| (&a.from.file_path, &a.to.file_path).cmp(&(&b.from.file_path, &b.to.file_path)));
+    changes
+}
+
+/// Cycles as the file paths of their members
+fn cycles(architecture: &ArchitectureMap) -> BTreeSet<BTreeSet<String>> {
+    architecture
+        .circular_dependencies
+        .iter()
+        .map(|cycle| {
+            cycle
+                .iter()
+                .map(|id| architecture.nodes.get(id).map_or_else(|| id.clone(), |node| node.file_path.clone()))
+                .collect()
+        })
+        .collect()
+}
+
+/// Project-wide metric read from a scan
+type Metric = fn(&ArchitectureMap) -> f64;
+
+fn metric_deltas(before: &ArchitectureMap, after: &ArchitectureMap) -> Vec<MetricDelta> {
+    let metrics: [(&str, Metric); 10] = [
+        ("total_modules", |map| map.total_modules as f64),
+        ("total_lines", |map| map.total_lines as f64),
+        ("dependencies", |map| map.edges.len() as f64),
+        ("circular_dependencies", |map| map.circular_dependencies.len() as f64),
+        ("average_complexity", |map| map.average_complexity),
+        ("max_complexity", |map| map.metrics.max_complexity),
+        ("dependency_density", |map| map.metrics.dependency_density),
+        ("modularity_score", |map| map.metrics.modularity_score),
+        ("maintainability_index", |map| map.metrics.maintainability_index),
+        ("health_score", |map| map.metrics.health_score),
+    ];
+    metrics
+        .iter()
+        .map(|(metric, value)| MetricDelta {
+            metric: metric.to_string(),
+            before: value(before),
+            after: value(after),
+            delta: value(after) - value(before),
+        })
+        .collect()
+}
+
+/// Human-readable report of `diff`
+pub fn render_text(diff: &ArchitectureDiff) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Comparing scan of {} with scan of {}\n",
+        diff.before_scan.format("%Y-%m-%d %H:%M"),
+        diff.after_scan.format("%Y-%m-%d %H:%M")
+    );
+
+    let width = diff.metrics.iter().map(|metric| metric.metric.len()).max().unwrap_or(0);
+    for metric in &diff.metrics {
+        let _ = writeln!(
+            out,
+            "{:<width$}  {:>10.2} -> {:>10.2}  ({:+.2})",
+            metric.metric, metric.before, metric.after, metric.delta
+        );
+    }
+
+    let mut section = |title: &str, lines: Vec<String>| {
+        if !lines.is_empty() {
+            let _ = writeln!(out, "\n{} ({}):", title, lines.len());
+            for line in lines {
+                let _ = writeln!(out, "  {}", line);
+            }
+        }
+    };
+    section("Added modules", diff.added_modules.iter().map(|module| format!("+ {}", label(module))).collect());
+    section("Removed modules", diff.removed_modules.iter().map(|module| format!("- {}", label(module))).collect());
+    section(
+        "New dependencies",
+        diff.added_dependencies
+            .iter()
+            .map(|change| format!("+ {} -> {} ({:?})", label(&change.from), label(&change.to), change.relationship))
+            .collect(),
+    );
+    section(
+        "Removed dependencies",
+        diff.removed_dependencies
+            .iter()
+            .map(|change| format!("- {} -> {}", label(&change.from), label(&change.to)))
+            .collect(),
+    );
+    section("New cycles", diff.new_cycles.iter().map(|cycle| format!("! {}", cycle.join(", "))).collect());
+    section("Resolved cycles", diff.resolved_cycles.iter().map(|cycle| format!("✓ {}", cycle.join(", "))).collect());
+    section(
+        "Changed modules",
+        diff.changed_modules
+            .iter()
+            .map(|change| {
+                let mut line = format!(
+                    "~ {}  complexity {:.2} -> {:.2}, lines {} -> {}",
+                    label(&change.module),
+                    change.complexity_before,
+                    change.complexity_after,
+                    change.lines_before,
+                    change.lines_after
+                );
+                for commit in &change.commits {
+                    let _ = write!(
+                        line,
+                        "\n      {} {} ({})",
+                        &commit.hash[..commit.hash.len().min(8)],
+                        commit.subject,
+                        commit.author
+                    );
+                }
+                line
+            })
+            .collect(),
+    );
+    out
+}
+
+/// File path of a module, or the name of one without a file such as an external crate
+fn label(module: &ModuleRef) -> &str {
+    if module.file_path.is_empty() {
+        &module.name
+    } else {
+        &module.file_path
+    }
+}
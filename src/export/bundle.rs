@@ -0,0 +1,75 @@
+use anyhow::Result;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::{
+    types::ArchitectureMap,
+    visualizer::{vendor_assets, ArchitectureVisualizer, HtmlOptions},
+};
+
+/// Directory of the vendored libraries inside the archive
+const VENDOR_PREFIX: &str = "assets/vendor";
+
+/// Build a zip archive that can be dropped onto any static host as a read-only visualizer.
+///
+/// Layout:
+/// - `index.html` – the page, with the architecture data embedded so no extra fetch is needed
+/// - `assets/app.css`, `assets/app.js` – styles and application script
+/// - `data/architecture.json` – the full `ArchitectureMap` for other consumers
+/// - `assets/vendor/` – React and React Flow, so the page loads nothing from CDNs
+pub fn create_bundle(visualizer: &ArchitectureVisualizer, architecture: &ArchitectureMap) -> Result<Vec<u8>> {
+    let vendor_dir = vendored_libraries(visualizer);
+    let options = HtmlOptions {
+        external_assets: true,
+        read_only: true,
+        live_updates: false,
+        asset_base: vendor_dir.as_ref().map(|_| VENDOR_PREFIX.to_string()),
+    };
+
+    let mut files: Vec<(String, Vec<u8>)> = vec![
+        (
+            "index.html".to_string(),
+            visualizer.generate_html_with_options(architecture, &options)?.into_bytes(),
+        ),
+        ("assets/app.css".to_string(), visualizer.stylesheet().into_bytes()),
+        ("assets/app.js".to_string(), visualizer.external_script().into_bytes()),
+        ("data/architecture.json".to_string(), serde_json::to_vec_pretty(architecture)?),
+    ];
+    if let Some(dir) = vendor_dir {
+        for file_name in vendor_assets::file_names() {
+            files.push((format!("{}/{}", VENDOR_PREFIX, file_name), vendor_assets::read(&dir, file_name)?));
+        }
+    }
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let file_options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    for (name, content) in files {
+        zip.start_file(name, file_options)?;
+        zip.write_all(&content)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+/// Directory of the vendored libraries to pack, downloading them into `vendor_dir` when missing.
+///
+/// With `offline_assets` the directory is used as configured and nothing is downloaded.
+/// When the download fails, or builds without the `cli` feature cannot download, the page falls back to the CDNs.
+fn vendored_libraries(visualizer: &ArchitectureVisualizer) -> Option<PathBuf> {
+    if let Some(dir) = visualizer.vendor_dir() {
+        return Some(dir);
+    }
+    let dir = visualizer.project_path().join(&visualizer.get_config().visualization.vendor_dir);
+    if vendor_assets::is_complete(&dir) {
+        return Some(dir);
+    }
+
+    #[cfg(feature = "cli")]
+    match vendor_assets::download(&dir) {
+        Ok(()) => return Some(dir),
+        Err(e) => tracing::warn!("Bundle loads React and React Flow from CDNs: {:#}", e),
+    }
+    None
+}
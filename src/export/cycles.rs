@@ -0,0 +1,78 @@
+//! The cycles analysis on its own, for attaching to refactoring tickets.
+
+use anyhow::Result;
+use serde_json::json;
+use std::fmt::Write;
+
+use crate::{
+    rules::cycles::{self, CycleEdge},
+    types::ArchitectureMap,
+};
+
+/// Groups of mutually dependent modules with their dependencies and suggested cuts, as Markdown
+pub fn render_markdown(architecture: &ArchitectureMap, project_name: &str) -> String {
+    let tangles = cycles::analyze(architecture);
+    let mut report = String::new();
+
+    let _ = writeln!(report, "# {} circular dependencies\n", project_name);
+    let _ = write!(report, "Scanned {}", architecture.last_scan.format("%Y-%m-%d %H:%M UTC"));
+    if let Some(commit) = git_commit(architecture) {
+        let _ = write!(report, " at commit `{}`", &commit[..commit.len().min(12)]);
+    }
+    report.push_str(".\n\n");
+    if tangles.is_empty() {
+        report.push_str("No circular dependencies.\n");
+        return report;
+    }
+    let cuts: usize = tangles.iter().map(|tangle| tangle.break_edges.len()).sum();
+    let _ = writeln!(
+        report,
+        "{} group{} of mutually dependent modules. Removing the {} dependenc{} under \"Suggested cuts\" breaks every cycle.",
+        tangles.len(),
+        if tangles.len() == 1 { "" } else { "s" },
+        cuts,
+        if cuts == 1 { "y" } else { "ies" }
+    );
+
+    for (index, tangle) in tangles.iter().enumerate() {
+        let names: Vec<&str> = tangle.modules.iter().map(|module| module.name.as_str()).collect();
+        let _ = writeln!(report, "\n## {}. {}\n", index + 1, names.join(", "));
+        report.push_str("| Module | File |\n|---|---|\n");
+        for module in &tangle.modules {
+            let _ = writeln!(report, "| {} | `{}` |", module.name, module.file_path);
+        }
+        report.push_str("\n### Dependencies\n\n");
+        for edge in &tangle.edges {
+            write_edge(&mut report, edge);
+        }
+        report.push_str("\n### Suggested cuts\n\n");
+        for edge in &tangle.break_edges {
+            write_edge(&mut report, edge);
+        }
+    }
+    report
+}
+
+/// The same analysis as `GET /api/cycles`, with the project and scan it belongs to
+pub fn render_json(architecture: &ArchitectureMap, project_name: &str) -> Result<Vec<u8>> {
+    let tangles = cycles::analyze(architecture);
+    Ok(serde_json::to_vec_pretty(&json!({
+        "project": project_name,
+        "last_scan": architecture.last_scan,
+        "git_commit": git_commit(architecture),
+        "total": tangles.len(),
+        "cycles": tangles,
+    }))?)
+}
+
+/// A dependency and every statement behind it
+fn write_edge(report: &mut String, edge: &CycleEdge) {
+    let _ = writeln!(report, "- {} → {}", edge.from_name, edge.to_name);
+    for location in &edge.locations {
+        let _ = writeln!(report, "  - `{}:{}` `{}`", location.file_path, location.line, location.snippet);
+    }
+}
+
+fn git_commit(architecture: &ArchitectureMap) -> Option<&str> {
+    architecture.provenance.as_ref().and_then(|provenance| provenance.git_commit.as_deref())
+}
@@ -0,0 +1,131 @@
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::Path;
+
+use crate::{
+    scanner::metrics_calculator::MetricsCalculator,
+    types::{ArchitectureMap, ArchitectureNode},
+};
+
+/// Restrict `architecture` to the modules within `depth` edges of `focus`, in either direction.
+///
+/// Node metrics keep their whole-project values; totals and cycles are limited
+/// to the retained modules.
+pub fn focus_subgraph(architecture: &ArchitectureMap, focus: &str, depth: usize) -> Result<ArchitectureMap> {
+    let focus_id = resolve_focus(architecture, focus)?;
+
+    let mut neighbours: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &architecture.edges {
+        neighbours.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+        neighbours.entry(edge.to.as_str()).or_default().push(edge.from.as_str());
+    }
+
+    let mut kept: HashSet<&str> = HashSet::from([focus_id.as_str()]);
+    let mut queue = VecDeque::from([(focus_id.as_str(), 0)]);
+    while let Some((id, distance)) = queue.pop_front() {
+        if distance == depth {
+            continue;
+        }
+        for neighbour in neighbours.get(id).into_iter().flatten() {
+            if kept.insert(neighbour) {
+                queue.push_back((neighbour, distance + 1));
+            }
+        }
+    }
+
+    let mut focused = architecture.clone();
+    focused.nodes.retain(|id, _| kept.contains(id.as_str()));
+    focused
+        .edges
+        .retain(|edge| kept.contains(edge.from.as_str()) && kept.contains(edge.to.as_str()));
+    focused
+        .circular_dependencies
+        .retain(|cycle| cycle.iter().all(|id| kept.contains(id.as_str())));
+
+    focused.total_modules = focused.nodes.len();
+    focused.total_lines = focused.nodes.values().map(|n| n.metrics.lines_of_code).sum();
+    focused.average_complexity = MetricsCalculator::average_complexity(&focused.nodes);
+
+    Ok(focused)
+}
+
+/// Find the node a focus reference means.
+///
+/// Accepts a node id, file path or module name, or a module path such as
+/// `crate::scanner` or `my_crate::scanner::metrics`.
+fn resolve_focus(architecture: &ArchitectureMap, focus: &str) -> Result<String> {
+    if architecture.nodes.contains_key(focus) {
+        return Ok(focus.to_string());
+    }
+
+    let direct: Vec<&ArchitectureNode> = architecture
+        .nodes
+        .values()
+        .filter(|node| node.file_path == focus || node.name == focus)
+        .collect();
+    if let [node] = direct.as_slice() {
+        return Ok(node.id.clone());
+    }
+
+    let mut segments: Vec<&str> = focus.split("::").filter(|segment| !segment.is_empty()).collect();
+    let crate_filter = match segments.first() {
+        Some(&"crate") => {
+            segments.remove(0);
+            None
+        }
+        Some(first) if segments.len() > 1 && is_crate_name(architecture, first) => {
+            let name = segments.remove(0);
+            Some(name)
+        }
+        _ => None,
+    };
+
+    let matches: Vec<&ArchitectureNode> = architecture
+        .nodes
+        .values()
+        .filter(|node| {
+            crate_filter.is_none_or(|name| {
+                node.crate_name.as_deref().is_some_and(|crate_name| crate_name.replace('-', "_") == name)
+            })
+        })
+        .filter(|node| matches_module_path(&node.file_path, &segments))
+        .collect();
+
+    match matches.as_slice() {
+        [node] => Ok(node.id.clone()),
+        [] if direct.len() > 1 => Err(anyhow!(
+            "Ambiguous focus '{}' matches {} modules; use a module path or file path",
+            focus,
+            direct.len()
+        )),
+        [] => Err(anyhow!("Unknown focus module: {}", focus)),
+        _ => Err(anyhow!(
+            "Ambiguous focus '{}' matches {} modules; prefix it with the crate name or use a file path",
+            focus,
+            matches.len()
+        )),
+    }
+}
+
+fn is_crate_name(architecture: &ArchitectureMap, name: &str) -> bool {
+    architecture
+        .nodes
+        .values()
+        .any(|node| node.crate_name.as_deref().is_some_and(|crate_name| crate_name.replace('-', "_") == name))
+}
+
+/// Whether `file_path` is the file of the module at `segments` below `src/`
+fn matches_module_path(file_path: &str, segments: &[&str]) -> bool {
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+    let mut module_file = Path::new("src").to_path_buf();
+    for parent in parents {
+        module_file.push(parent);
+    }
+    let module_dir = module_file.join(last).join("mod.rs");
+    module_file.push(format!("{}.rs", last));
+
+    let path = Path::new(file_path);
+    path.ends_with(&module_file) || path.ends_with(&module_dir)
+}
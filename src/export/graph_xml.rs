@@ -0,0 +1,210 @@
+//! GraphML and GEXF documents of the module graph, for Gephi, yEd and other
+//! graph-analysis tools.
+//!
+//! Both formats carry the same attributes: size, complexity and type of every
+//! module, and the relationship, strength and cycle membership of every edge.
+//! Nodes are keyed by their scan ids, so files of the same scan can be joined
+//! with the JSON export.
+
+use std::fmt::Write;
+
+use crate::{
+    types::{ArchitectureMap, ArchitectureNode, DependencyEdge},
+    visualizer::static_page::escape_html,
+};
+
+/// Value types the formats declare for an attribute column
+#[derive(Clone, Copy)]
+enum AttributeType {
+    Text,
+    Integer,
+    Real,
+    Flag,
+}
+
+impl AttributeType {
+    fn graphml(self) -> &'static str {
+        match self {
+            AttributeType::Text => "string",
+            AttributeType::Integer => "int",
+            AttributeType::Real => "double",
+            AttributeType::Flag => "boolean",
+        }
+    }
+
+    fn gexf(self) -> &'static str {
+        match self {
+            AttributeType::Text => "string",
+            AttributeType::Integer => "integer",
+            AttributeType::Real => "double",
+            AttributeType::Flag => "boolean",
+        }
+    }
+}
+
+/// Node attribute columns, in the order of [`node_values`]; empty values are left out
+const NODE_ATTRIBUTES: [(&str, AttributeType); 11] = [
+    ("module_type", AttributeType::Text),
+    ("crate", AttributeType::Text),
+    ("module_path", AttributeType::Text),
+    ("file_path", AttributeType::Text),
+    ("lines_of_code", AttributeType::Integer),
+    ("complexity", AttributeType::Real),
+    ("cyclomatic_complexity", AttributeType::Real),
+    ("function_count", AttributeType::Integer),
+    ("dependency_count", AttributeType::Integer),
+    ("dependent_count", AttributeType::Integer),
+    ("layer", AttributeType::Text),
+];
+
+/// Edge attribute columns, in the order of [`edge_values`]
+const EDGE_ATTRIBUTES: [(&str, AttributeType); 4] = [
+    ("relationship", AttributeType::Text),
+    ("strength", AttributeType::Real),
+    ("is_circular", AttributeType::Flag),
+    ("layer_violation", AttributeType::Flag),
+];
+
+fn node_values(node: &ArchitectureNode) -> [String; 11] {
+    [
+        node.module_type.display_name(),
+        node.crate_name.clone().unwrap_or_default(),
+        node.module_path.clone(),
+        node.file_path.clone(),
+        node.metrics.lines_of_code.to_string(),
+        node.metrics.complexity_score.to_string(),
+        node.metrics.cyclomatic_complexity.to_string(),
+        node.metrics.function_count.to_string(),
+        node.metrics.dependency_count.to_string(),
+        node.metrics.dependent_count.to_string(),
+        node.layer.clone().unwrap_or_default(),
+    ]
+}
+
+fn edge_values(edge: &DependencyEdge) -> [String; 4] {
+    [
+        format!("{:?}", edge.relationship),
+        edge.strength.to_string(),
+        edge.is_circular.to_string(),
+        edge.layer_violation.to_string(),
+    ]
+}
+
+/// Modules sorted by id, and the edges between them in a stable order
+fn graph(architecture: &ArchitectureMap) -> (Vec<&ArchitectureNode>, Vec<&DependencyEdge>) {
+    let mut nodes: Vec<&ArchitectureNode> = architecture.nodes.values().collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    let mut edges: Vec<&DependencyEdge> = architecture
+        .edges
+        .iter()
+        .filter(|edge| architecture.nodes.contains_key(&edge.from) && architecture.nodes.contains_key(&edge.to))
+        .collect();
+    edges.sort_by(|a, b| (&a.from, &a.to, &a.relationship).cmp(&(&b.from, &b.to, &b.relationship)));
+    (nodes, edges)
+}
+
+/// GraphML document with a `label` and the attribute columns above as `<data>` keys
+pub fn render_graphml(architecture: &ArchitectureMap, project_name: &str) -> String {
+    let (nodes, edges) = graph(architecture);
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n",
+    );
+    xml.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    for (name, kind) in NODE_ATTRIBUTES {
+        let _ = writeln!(xml, "  <key id=\"{}\" for=\"node\" attr.name=\"{}\" attr.type=\"{}\"/>", name, name, kind.graphml());
+    }
+    for (name, kind) in EDGE_ATTRIBUTES {
+        let _ = writeln!(xml, "  <key id=\"{}\" for=\"edge\" attr.name=\"{}\" attr.type=\"{}\"/>", name, name, kind.graphml());
+    }
+    let _ = writeln!(xml, "  <graph id=\"{}\" edgedefault=\"directed\">", escape_html(project_name));
+
+    for node in nodes {
+        let _ = writeln!(xml, "    <node id=\"{}\">", escape_html(&node.id));
+        let _ = writeln!(xml, "      <data key=\"label\">{}</data>", escape_html(&node.name));
+        for ((name, _), value) in NODE_ATTRIBUTES.iter().zip(node_values(node)).filter(|(_, value)| !value.is_empty()) {
+            let _ = writeln!(xml, "      <data key=\"{}\">{}</data>", name, escape_html(&value));
+        }
+        xml.push_str("    </node>\n");
+    }
+    for (index, edge) in edges.into_iter().enumerate() {
+        let _ = writeln!(
+            xml,
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">",
+            index,
+            escape_html(&edge.from),
+            escape_html(&edge.to)
+        );
+        for ((name, _), value) in EDGE_ATTRIBUTES.iter().zip(edge_values(edge)) {
+            let _ = writeln!(xml, "      <data key=\"{}\">{}</data>", name, escape_html(&value));
+        }
+        xml.push_str("    </edge>\n");
+    }
+
+    xml.push_str("  </graph>\n</graphml>\n");
+    xml
+}
+
+/// GEXF 1.3 document; edge strength is also the edge weight, and modules are colored by type
+pub fn render_gexf(architecture: &ArchitectureMap, project_name: &str) -> String {
+    let (nodes, edges) = graph(architecture);
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n");
+    let _ = writeln!(xml, "  <meta lastmodifieddate=\"{}\">", architecture.last_scan.format("%Y-%m-%d"));
+    xml.push_str("    <creator>rust-arch-viz</creator>\n");
+    let _ = writeln!(xml, "    <description>Module graph of {}</description>", escape_html(project_name));
+    xml.push_str("  </meta>\n");
+    xml.push_str("  <graph mode=\"static\" defaultedgetype=\"directed\">\n");
+    for (class, attributes) in [("node", &NODE_ATTRIBUTES[..]), ("edge", &EDGE_ATTRIBUTES[..])] {
+        let _ = writeln!(xml, "    <attributes class=\"{}\">", class);
+        for (name, kind) in attributes {
+            let _ = writeln!(xml, "      <attribute id=\"{}\" title=\"{}\" type=\"{}\"/>", name, name, kind.gexf());
+        }
+        xml.push_str("    </attributes>\n");
+    }
+
+    xml.push_str("    <nodes>\n");
+    for node in nodes {
+        let _ = writeln!(xml, "      <node id=\"{}\" label=\"{}\">", escape_html(&node.id), escape_html(&node.name));
+        write_attvalues(&mut xml, &NODE_ATTRIBUTES, node_values(node));
+        if let Some([red, green, blue]) = rgb(node.module_type.color()) {
+            let _ = writeln!(xml, "        <viz:color r=\"{}\" g=\"{}\" b=\"{}\"/>", red, green, blue);
+        }
+        xml.push_str("      </node>\n");
+    }
+    xml.push_str("    </nodes>\n    <edges>\n");
+    for (index, edge) in edges.into_iter().enumerate() {
+        let _ = writeln!(
+            xml,
+            "      <edge id=\"e{}\" source=\"{}\" target=\"{}\" weight=\"{}\" label=\"{:?}\">",
+            index,
+            escape_html(&edge.from),
+            escape_html(&edge.to),
+            edge.strength,
+            edge.relationship
+        );
+        write_attvalues(&mut xml, &EDGE_ATTRIBUTES, edge_values(edge));
+        xml.push_str("      </edge>\n");
+    }
+    xml.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    xml
+}
+
+fn write_attvalues<const N: usize>(xml: &mut String, attributes: &[(&str, AttributeType); N], values: [String; N]) {
+    xml.push_str("        <attvalues>\n");
+    for ((name, _), value) in attributes.iter().zip(values).filter(|(_, value)| !value.is_empty()) {
+        let _ = writeln!(xml, "          <attvalue for=\"{}\" value=\"{}\"/>", name, escape_html(&value));
+    }
+    xml.push_str("        </attvalues>\n");
+}
+
+/// Channels of a `#rrggbb` color
+fn rgb(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.strip_prefix('#')?;
+    let channel = |range| u8::from_str_radix(hex.get(range)?, 16).ok();
+    Some([channel(0..2)?, channel(2..4)?, channel(4..6)?])
+}
@@ -0,0 +1,194 @@
+//! Markdown summary of a scan, for documentation sites and wikis.
+
+use std::fmt::Write;
+
+use crate::{
+    rules::cycles,
+    types::ArchitectureMap,
+    visualizer::crate_view::{build_crate_view, toolchain_inconsistencies},
+};
+
+/// Summary report: headline metrics, crates, modules, cycles and layering problems
+pub fn render_report(architecture: &ArchitectureMap, project_name: &str) -> String {
+    let mut report = String::new();
+    let name_of = |id: &String| architecture.nodes.get(id).map_or_else(|| id.clone(), |node| node.name.clone());
+
+    let _ = writeln!(report, "# {} architecture\n", project_name);
+    let _ = write!(report, "Scanned {}", architecture.last_scan.format("%Y-%m-%d %H:%M UTC"));
+    if let Some(commit) = architecture.provenance.as_ref().and_then(|provenance| provenance.git_commit.as_deref()) {
+        let _ = write!(report, " at commit `{}`", &commit[..commit.len().min(12)]);
+    }
+    report.push_str(".\n\n");
+
+    report.push_str("| Metric | Value |\n|---|---:|\n");
+    let _ = writeln!(report, "| Modules | {} |", architecture.total_modules);
+    let _ = writeln!(report, "| Lines of code | {} |", architecture.total_lines);
+    let _ = writeln!(report, "| Average complexity | {:.2} |", architecture.average_complexity);
+    let _ = writeln!(report, "| Dependencies | {} |", architecture.edges.len());
+    let _ = writeln!(report, "| Circular dependencies | {} |", architecture.circular_dependencies.len());
+    let _ = writeln!(report, "| Health score | {:.0} |", architecture.metrics.health_score);
+
+    if !architecture.variants.is_empty() {
+        report.push_str("\n## Variants\n\n| Variant | Features | Target | Modules | Lines of code | Average complexity | Circular dependencies |\n|---|---|---|---:|---:|---:|---:|\n");
+        for variant in &architecture.variants {
+            let _ = writeln!(
+                report,
+                "| {} | {} | {} | {} | {} | {:.2} | {} |",
+                cell(&variant.name),
+                cell(&variant.features.join(", ")),
+                cell(variant.target.as_deref().unwrap_or("")),
+                variant.total_modules,
+                variant.total_lines,
+                variant.average_complexity,
+                variant.circular_dependencies
+            );
+        }
+    }
+
+    if architecture.crates.len() > 1 {
+        let inconsistencies = toolchain_inconsistencies(&architecture.crates);
+        let view = build_crate_view(architecture);
+        report.push_str("\n## Crates\n\n| Crate | Version | Edition | MSRV | Dead code | Inconsistencies |\n|---|---|---|---|---:|---|\n");
+        for metadata in &architecture.crates {
+            let dead_code = view.crates.iter().find(|summary| summary.name == metadata.name).map_or(0.0, |summary| summary.dead_code_percentage);
+            let _ = writeln!(
+                report,
+                "| {} | {} | {} | {} | {:.0}% | {} |",
+                cell(&metadata.name),
+                cell(metadata.version.as_deref().unwrap_or("")),
+                cell(&metadata.edition),
+                cell(metadata.rust_version.as_deref().unwrap_or("")),
+                dead_code,
+                cell(&inconsistencies.get(&metadata.name).map(|messages| messages.join("; ")).unwrap_or_default())
+            );
+        }
+    }
+
+    let mut nodes: Vec<_> = architecture.nodes.values().collect();
+    nodes.sort_by(|a, b| {
+        b.metrics
+            .complexity_score
+            .total_cmp(&a.metrics.complexity_score)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    report.push_str("\n## Modules\n\n| Module | Type | File | Lines | Complexity | Fan-in | Fan-out |\n|---|---|---|---:|---:|---:|---:|\n");
+    for node in nodes {
+        let _ = writeln!(
+            report,
+            "| {} | {} | `{}` | {} | {:.1} | {} | {} |",
+            cell(&node.name),
+            cell(&node.module_type.display_name()),
+            cell(&node.file_path),
+            node.metrics.lines_of_code,
+            node.metrics.complexity_score,
+            node.metrics.dependent_count,
+            node.metrics.dependency_count
+        );
+    }
+
+    report.push_str("\n## Circular dependencies\n\n");
+    if architecture.circular_dependencies.is_empty() {
+        report.push_str("None.\n");
+    }
+    for cycle in &architecture.circular_dependencies {
+        let mut path: Vec<String> = cycle.iter().map(name_of).collect();
+        if let Some(first) = cycle.first() {
+            path.push(name_of(first));
+        }
+        let _ = writeln!(report, "- {}", path.join(" → "));
+    }
+    let tangles = cycles::analyze(architecture);
+    if !tangles.is_empty() {
+        report.push_str("\n### Breaking the cycles\n\nRemoving these dependencies leaves each group of mutually dependent modules without cycles.\n\n");
+    }
+    for tangle in &tangles {
+        let names: Vec<&str> = tangle.modules.iter().map(|module| module.name.as_str()).collect();
+        let _ = writeln!(report, "- {} ({} modules):", names.join(", "), names.len());
+        for edge in &tangle.break_edges {
+            let _ = write!(report, "  - {} → {}", edge.from_name, edge.to_name);
+            if let Some(location) = edge.locations.first() {
+                let _ = write!(report, ": `{}` at `{}:{}`", location.snippet, location.file_path, location.line);
+            }
+            report.push('\n');
+        }
+    }
+
+    let mut upward: Vec<_> = architecture.edges.iter().filter(|edge| edge.layer_violation).collect();
+    if !upward.is_empty() {
+        upward.sort_by_key(|edge| (name_of(&edge.from), name_of(&edge.to)));
+        let layer_of = |id: &String| {
+            architecture
+                .nodes
+                .get(id)
+                .and_then(|node| node.layer.clone())
+                .unwrap_or_default()
+        };
+        report.push_str("\n## Inversion candidates\n\nDependencies pointing up the layering, from a lower layer to a higher one.\n\n");
+        for edge in upward {
+            let _ = writeln!(
+                report,
+                "- {} ({}) → {} ({})",
+                name_of(&edge.from),
+                layer_of(&edge.from),
+                name_of(&edge.to),
+                layer_of(&edge.to)
+            );
+            for suggestion in &edge.suggestions {
+                let _ = writeln!(report, "  - {}", suggestion);
+            }
+        }
+    }
+
+    let mut routes: Vec<_> = architecture
+        .nodes
+        .values()
+        .flat_map(|node| node.routes.iter().map(move |route| (node, route)))
+        .collect();
+    if !routes.is_empty() {
+        routes.sort_by(|(a, first), (b, second)| {
+            first
+                .path
+                .cmp(&second.path)
+                .then_with(|| first.method.cmp(&second.method))
+                .then_with(|| a.file_path.cmp(&b.file_path))
+        });
+        report.push_str("\n## HTTP surface\n\n| Method | Path | Handler | Registered at |\n|---|---|---|---|\n");
+        for (node, route) in routes {
+            let _ = writeln!(
+                report,
+                "| {} | `{}` | {} | `{}:{}` |",
+                route.method,
+                cell(&route.path),
+                cell(route.handler.as_deref().unwrap_or("(closure)")),
+                cell(&node.file_path),
+                route.line
+            );
+        }
+    }
+
+    let mut risks: Vec<_> = architecture
+        .nodes
+        .values()
+        .filter_map(|node| Some((node, node.ownership.as_ref().filter(|ownership| ownership.organizational_risk)?)))
+        .collect();
+    if !risks.is_empty() {
+        risks.sort_by(|(a, _), (b, _)| a.file_path.cmp(&b.file_path));
+        report.push_str("\n## Organizational risks\n\n");
+        for (node, ownership) in risks {
+            let _ = writeln!(
+                report,
+                "- {}: {:.0}% written by {}",
+                node.name,
+                ownership.top_author_share * 100.0,
+                ownership.top_author
+            );
+        }
+    }
+
+    report
+}
+
+/// `value` made safe for a table cell
+fn cell(value: &str) -> String {
+    value.replace('|', "\\|").replace('\n', " ")
+}
@@ -0,0 +1,106 @@
+//! Mermaid flowchart of the module graph, for embedding in READMEs and mdBook pages.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+use crate::{
+    config::{MermaidDirection, MermaidGrouping, MermaidSettings},
+    types::{ArchitectureMap, ArchitectureNode},
+};
+
+/// Stroke of dependencies that are part of a cycle
+const CIRCULAR_STROKE: &str = "#ef4444";
+
+/// `flowchart` definition with one node per module, grouped into subgraphs as configured
+pub fn render(architecture: &ArchitectureMap, settings: &MermaidSettings) -> String {
+    let mut nodes: Vec<&ArchitectureNode> = architecture.nodes.values().collect();
+    nodes.sort_by(|a, b| a.file_path.cmp(&b.file_path).then_with(|| a.id.cmp(&b.id)));
+    // Scan ids are UUIDs; short positional ids keep the diagram readable
+    let short_ids: HashMap<&str, String> = nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id.as_str(), format!("m{}", index)))
+        .collect();
+
+    let mut diagram = String::new();
+    let direction = match settings.direction {
+        MermaidDirection::TopDown => "TD",
+        MermaidDirection::LeftRight => "LR",
+    };
+    let _ = writeln!(diagram, "flowchart {}", direction);
+
+    let node_line = |node: &ArchitectureNode| format!("{}[\"{}\"]", short_ids[node.id.as_str()], label(&node.name));
+    match settings.group_by {
+        MermaidGrouping::None => {
+            for node in &nodes {
+                let _ = writeln!(diagram, "    {}", node_line(node));
+            }
+        }
+        grouping => {
+            let mut groups: BTreeMap<String, Vec<&ArchitectureNode>> = BTreeMap::new();
+            for node in &nodes {
+                groups.entry(group_of(node, grouping)).or_default().push(node);
+            }
+            for (index, (group, members)) in groups.iter().enumerate() {
+                let _ = writeln!(diagram, "    subgraph g{}[\"{}\"]", index, label(group));
+                for node in members {
+                    let _ = writeln!(diagram, "        {}", node_line(node));
+                }
+                diagram.push_str("    end\n");
+            }
+        }
+    }
+
+    // Repeated edges between the same modules are drawn once, and self references not at all
+    let mut links: BTreeMap<(&str, &str), bool> = BTreeMap::new();
+    for edge in &architecture.edges {
+        let (Some(from), Some(to)) = (short_ids.get(edge.from.as_str()), short_ids.get(edge.to.as_str())) else {
+            continue;
+        };
+        if from == to {
+            continue;
+        }
+        *links.entry((from.as_str(), to.as_str())).or_default() |= edge.is_circular;
+    }
+    let mut circular = Vec::new();
+    for (index, ((from, to), is_circular)) in links.iter().enumerate() {
+        let _ = writeln!(diagram, "    {} --> {}", from, to);
+        if *is_circular {
+            circular.push(index.to_string());
+        }
+    }
+    if !circular.is_empty() {
+        let _ = writeln!(diagram, "    linkStyle {} stroke:{},stroke-width:2px", circular.join(","), CIRCULAR_STROKE);
+    }
+
+    // One class per module type, colored as in the interactive graph
+    let mut types: BTreeMap<String, (&str, BTreeSet<&str>)> = BTreeMap::new();
+    for node in &nodes {
+        types
+            .entry(node.module_type.display_name())
+            .or_insert_with(|| (node.module_type.color(), BTreeSet::new()))
+            .1
+            .insert(short_ids[node.id.as_str()].as_str());
+    }
+    for (index, (color, members)) in types.values().enumerate() {
+        let _ = writeln!(diagram, "    classDef t{} fill:{},stroke:{},color:#fff", index, color, color);
+        let _ = writeln!(diagram, "    class {} t{}", members.iter().copied().collect::<Vec<_>>().join(","), index);
+    }
+
+    diagram
+}
+
+fn group_of(node: &ArchitectureNode, grouping: MermaidGrouping) -> String {
+    match grouping {
+        MermaidGrouping::Directory => match node.file_path.rsplit_once('/') {
+            Some((directory, _)) => directory.to_string(),
+            None => ".".to_string(),
+        },
+        _ => node.module_type.display_name(),
+    }
+}
+
+/// Text safe inside a quoted Mermaid label
+fn label(text: &str) -> String {
+    text.replace('"', "#quot;").replace('<', "#lt;").replace('>', "#gt;")
+}
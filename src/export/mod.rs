@@ -0,0 +1,165 @@
+//! Exporting architecture data to files in various formats.
+
+pub mod bundle;
+pub mod cycles;
+pub mod focus;
+pub mod graph_xml;
+pub mod markdown;
+pub mod mermaid;
+#[cfg(feature = "png")]
+pub mod png;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+pub mod redact;
+pub mod schedule;
+
+pub use focus::focus_subgraph;
+pub use redact::Redactor;
+
+use anyhow::Result;
+use std::{fmt, str::FromStr};
+
+use crate::{
+    types::ArchitectureMap,
+    visualizer::{static_page, ArchitectureVisualizer},
+};
+
+/// Supported export formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Raw `ArchitectureMap` JSON
+    Json,
+    /// The interactive page as a single HTML file
+    Html,
+    /// Zip archive for static hosting: HTML, assets and JSON data
+    Bundle,
+    /// Summary report of metrics, modules and cycles
+    Markdown,
+    /// Groups of mutually dependent modules with the dependencies to cut, as Markdown
+    CyclesMarkdown,
+    /// The same cycles analysis as JSON
+    CyclesJson,
+    /// Mermaid flowchart of the module graph
+    Mermaid,
+    /// The module graph as a vector diagram, laid out on a grid or in layers
+    Svg,
+    /// GraphML of the module graph with metrics as node and edge attributes, for yEd
+    Graphml,
+    /// GEXF of the module graph with metrics as node and edge attributes, for Gephi
+    Gexf,
+    /// The SVG diagram rasterized, `exports.png.width` pixels wide
+    #[cfg(feature = "png")]
+    Png,
+    /// `ArchitectureMap` message of `proto/architecture.proto`
+    #[cfg(feature = "protobuf")]
+    Protobuf,
+}
+
+impl ExportFormat {
+    /// Conventional file name for this format
+    pub fn default_file_name(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "architecture.json",
+            ExportFormat::Html => "architecture.html",
+            ExportFormat::Bundle => "architecture-bundle.zip",
+            ExportFormat::Markdown => "architecture.md",
+            ExportFormat::CyclesMarkdown => "cycles.md",
+            ExportFormat::CyclesJson => "cycles.json",
+            ExportFormat::Mermaid => "architecture.mmd",
+            ExportFormat::Svg => "architecture.svg",
+            ExportFormat::Graphml => "architecture.graphml",
+            ExportFormat::Gexf => "architecture.gexf",
+            #[cfg(feature = "png")]
+            ExportFormat::Png => "architecture.png",
+            #[cfg(feature = "protobuf")]
+            ExportFormat::Protobuf => "architecture.pb",
+        }
+    }
+
+    /// Whether the output is binary (and so should not be written to stdout)
+    pub fn is_binary(&self) -> bool {
+        match self {
+            ExportFormat::Bundle => true,
+            #[cfg(feature = "png")]
+            ExportFormat::Png => true,
+            #[cfg(feature = "protobuf")]
+            ExportFormat::Protobuf => true,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "json" => Ok(ExportFormat::Json),
+            "html" => Ok(ExportFormat::Html),
+            "bundle" | "zip" => Ok(ExportFormat::Bundle),
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "cycles-md" | "cycles-markdown" => Ok(ExportFormat::CyclesMarkdown),
+            "cycles-json" => Ok(ExportFormat::CyclesJson),
+            "mermaid" | "mmd" => Ok(ExportFormat::Mermaid),
+            "svg" => Ok(ExportFormat::Svg),
+            "graphml" => Ok(ExportFormat::Graphml),
+            "gexf" => Ok(ExportFormat::Gexf),
+            #[cfg(feature = "png")]
+            "png" => Ok(ExportFormat::Png),
+            #[cfg(feature = "protobuf")]
+            "protobuf" | "proto" | "pb" => Ok(ExportFormat::Protobuf),
+            other => Err(anyhow::anyhow!(
+                "Unsupported export format: {} (expected json, html, bundle, markdown, cycles-md, cycles-json, mermaid, svg, graphml, gexf or png)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Json => write!(f, "json"),
+            ExportFormat::Html => write!(f, "html"),
+            ExportFormat::Bundle => write!(f, "bundle"),
+            ExportFormat::Markdown => write!(f, "markdown"),
+            ExportFormat::CyclesMarkdown => write!(f, "cycles-md"),
+            ExportFormat::CyclesJson => write!(f, "cycles-json"),
+            ExportFormat::Mermaid => write!(f, "mermaid"),
+            ExportFormat::Svg => write!(f, "svg"),
+            ExportFormat::Graphml => write!(f, "graphml"),
+            ExportFormat::Gexf => write!(f, "gexf"),
+            #[cfg(feature = "png")]
+            ExportFormat::Png => write!(f, "png"),
+            #[cfg(feature = "protobuf")]
+            ExportFormat::Protobuf => write!(f, "protobuf"),
+        }
+    }
+}
+
+/// Render the architecture in the requested format
+pub fn export(
+    visualizer: &ArchitectureVisualizer,
+    architecture: &ArchitectureMap,
+    format: ExportFormat,
+) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_vec_pretty(architecture)?),
+        ExportFormat::Html => Ok(visualizer.generate_html(architecture)?.into_bytes()),
+        ExportFormat::Bundle => bundle::create_bundle(visualizer, architecture),
+        ExportFormat::Markdown => Ok(markdown::render_report(architecture, visualizer.project_name()).into_bytes()),
+        ExportFormat::CyclesMarkdown => Ok(cycles::render_markdown(architecture, visualizer.project_name()).into_bytes()),
+        ExportFormat::CyclesJson => cycles::render_json(architecture, visualizer.project_name()),
+        ExportFormat::Mermaid => Ok(visualizer.to_mermaid(architecture).into_bytes()),
+        ExportFormat::Svg => Ok(static_page::render_graph_svg(architecture, &visualizer.get_config().visualization)?.into_bytes()),
+        ExportFormat::Graphml => Ok(graph_xml::render_graphml(architecture, visualizer.project_name()).into_bytes()),
+        ExportFormat::Gexf => Ok(graph_xml::render_gexf(architecture, visualizer.project_name()).into_bytes()),
+        #[cfg(feature = "png")]
+        ExportFormat::Png => {
+            let config = visualizer.get_config();
+            png::render(architecture, &config.visualization, config.exports.png.width)
+        }
+        #[cfg(feature = "protobuf")]
+        ExportFormat::Protobuf => Ok(protobuf::encode(architecture)),
+    }
+}
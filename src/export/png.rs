@@ -0,0 +1,89 @@
+//! PNG image of the module graph, for slide decks and documents.
+//!
+//! The SVG export is rasterized with resvg, so both show the same drawing. Text
+//! uses the fonts installed on the system. The background follows the
+//! configured theme.
+
+use anyhow::{Context, Result};
+use resvg::{
+    tiny_skia::{Color, Pixmap, Transform},
+    usvg::{
+        self,
+        fontdb::{Database, Family, Query, Stretch, Style, Weight},
+    },
+};
+use std::sync::{Arc, OnceLock};
+
+use crate::{
+    types::{ArchitectureMap, Theme, VisualizationSettings},
+    visualizer::static_page,
+};
+
+/// Largest image drawn, in pixels; wider requests are scaled down to fit
+const MAX_PIXELS: f64 = 64.0 * 1024.0 * 1024.0;
+
+/// The module graph as a PNG file `width` pixels wide
+pub fn render(architecture: &ArchitectureMap, settings: &VisualizationSettings, width: u32) -> Result<Vec<u8>> {
+    let svg = static_page::render_graph_svg(architecture, settings)?;
+    let fonts = system_fonts();
+    let mut options = usvg::Options::default();
+    if let Some(family) = fonts.query(&SANS_SERIF).and_then(|id| fonts.face(id)).and_then(|face| face.families.first()) {
+        options.font_family = family.0.clone();
+    }
+    options.fontdb = fonts;
+    let tree = usvg::Tree::from_str(&svg, &options).context("Failed to read the SVG export")?;
+
+    let size = tree.size();
+    let (scene_width, scene_height) = (size.width() as f64, size.height() as f64);
+    let mut scale = width.max(1) as f64 / scene_width;
+    if scene_width * scene_height * scale * scale > MAX_PIXELS {
+        scale = (MAX_PIXELS / (scene_width * scene_height)).sqrt();
+    }
+    let mut pixmap = Pixmap::new(
+        (scene_width * scale).round().max(1.0) as u32,
+        (scene_height * scale).round().max(1.0) as u32,
+    )
+    .context("Failed to allocate the PNG image")?;
+    pixmap.fill(background(&settings.theme));
+
+    resvg::render(&tree, Transform::from_scale(scale as f32, scale as f32), &mut pixmap.as_mut());
+    pixmap.encode_png().context("Failed to encode PNG")
+}
+
+fn background(theme: &Theme) -> Color {
+    match theme {
+        Theme::Dark => Color::from_rgba8(0x0f, 0x17, 0x2a, 0xff),
+        Theme::Light | Theme::Auto | Theme::Custom(_) => Color::WHITE,
+    }
+}
+
+const SANS_SERIF: Query<'static> = Query {
+    families: &[Family::SansSerif],
+    weight: Weight::NORMAL,
+    stretch: Stretch::Normal,
+    style: Style::Normal,
+};
+
+/// Installed fonts, looked up once per process. When the system has no Arial,
+/// `sans-serif` falls back to the first installed sans face so labels still show.
+fn system_fonts() -> Arc<Database> {
+    static FONTS: OnceLock<Arc<Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut fonts = Database::new();
+            fonts.load_system_fonts();
+            if fonts.query(&SANS_SERIF).is_none() {
+                let fallback = fonts
+                    .faces()
+                    .flat_map(|face| face.families.iter().map(|(name, _)| name))
+                    .find(|name| name.contains("Sans") && !name.contains("Mono"))
+                    .or_else(|| fonts.faces().flat_map(|face| face.families.iter().map(|(name, _)| name)).next())
+                    .cloned();
+                if let Some(family) = fallback {
+                    fonts.set_sans_serif_family(family);
+                }
+            }
+            Arc::new(fonts)
+        })
+        .clone()
+}
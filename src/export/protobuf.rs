@@ -0,0 +1,457 @@
+//! Protocol Buffers encoding of `ArchitectureMap`, following `proto/architecture.proto`.
+//!
+//! The wire format is written directly, so the feature pulls in no code
+//! generator; consumers generate their types from the published schema. With
+//! the `grpc` feature the tests decode the output with types prost generates
+//! from that schema.
+
+use crate::types::{
+    ArchitectureDiff, ArchitectureMap, ArchitectureMetrics, ArchitectureNode, CrateMetadata, DependencyChange, DependencyEdge,
+    FileCommit, ModuleChange, ModuleRef, NodeMetrics, Robustness, SourceLocation,
+};
+
+/// The schema the encoding follows
+pub const SCHEMA: &str = include_str!("../../proto/architecture.proto");
+/// The gRPC service, whose diff messages `encode_diff` writes
+pub const SERVICE_SCHEMA: &str = include_str!("../../proto/service.proto");
+
+const VARINT: u64 = 0;
+const FIXED64: u64 = 1;
+const LENGTH_DELIMITED: u64 = 2;
+
+/// Encode `architecture` as a `rust_arch_viz.v1.ArchitectureMap` message
+pub fn encode(architecture: &ArchitectureMap) -> Vec<u8> {
+    let mut message = Encoder::default();
+
+    // Sorted so the same scan always encodes to the same bytes
+    let mut nodes: Vec<&ArchitectureNode> = architecture.nodes.values().collect();
+    nodes.sort_by(|a, b| a.id.cmp(&b.id));
+    for node in nodes {
+        message.message(1, |encoder| encode_node(encoder, node));
+    }
+    for edge in &architecture.edges {
+        message.message(2, |encoder| encode_edge(encoder, edge));
+    }
+    message.string(3, &architecture.last_scan.to_rfc3339());
+    message.uint(4, architecture.total_modules as u64);
+    message.uint(5, architecture.total_lines as u64);
+    message.double(6, architecture.average_complexity);
+    for cycle in &architecture.circular_dependencies {
+        message.message(7, |encoder| {
+            for id in cycle {
+                encoder.string(1, id);
+            }
+        });
+    }
+    message.message(8, |encoder| encode_architecture_metrics(encoder, &architecture.metrics));
+    for metadata in &architecture.crates {
+        message.message(9, |encoder| encode_crate(encoder, metadata));
+    }
+
+    message.buffer
+}
+
+/// Encode `diff` as a `rust_arch_viz.v1.ArchitectureDiff` message of `proto/service.proto`
+pub fn encode_diff(diff: &ArchitectureDiff) -> Vec<u8> {
+    let mut message = Encoder::default();
+    message.string(1, &diff.before_scan.to_rfc3339());
+    message.string(2, &diff.after_scan.to_rfc3339());
+    for module in &diff.added_modules {
+        message.message(3, |encoder| encode_module_ref(encoder, module));
+    }
+    for module in &diff.removed_modules {
+        message.message(4, |encoder| encode_module_ref(encoder, module));
+    }
+    for change in &diff.added_dependencies {
+        message.message(5, |encoder| encode_dependency_change(encoder, change));
+    }
+    for change in &diff.removed_dependencies {
+        message.message(6, |encoder| encode_dependency_change(encoder, change));
+    }
+    for (field, cycles) in [(7, &diff.new_cycles), (8, &diff.resolved_cycles)] {
+        for cycle in cycles {
+            message.message(field, |encoder| {
+                for file_path in cycle {
+                    encoder.string(1, file_path);
+                }
+            });
+        }
+    }
+    for metric in &diff.metrics {
+        message.message(9, |encoder| {
+            encoder.string(1, &metric.metric);
+            encoder.double(2, metric.before);
+            encoder.double(3, metric.after);
+            encoder.double(4, metric.delta);
+        });
+    }
+    for change in &diff.changed_modules {
+        message.message(10, |encoder| encode_module_change(encoder, change));
+    }
+    message.buffer
+}
+
+fn encode_module_ref(encoder: &mut Encoder, module: &ModuleRef) {
+    encoder.string(1, &module.id);
+    encoder.string(2, &module.name);
+    encoder.string(3, &module.file_path);
+}
+
+fn encode_dependency_change(encoder: &mut Encoder, change: &DependencyChange) {
+    encoder.message(1, |encoder| encode_module_ref(encoder, &change.from));
+    encoder.message(2, |encoder| encode_module_ref(encoder, &change.to));
+    encoder.string(3, &format!("{:?}", change.relationship));
+}
+
+fn encode_module_change(encoder: &mut Encoder, change: &ModuleChange) {
+    encoder.message(1, |encoder| encode_module_ref(encoder, &change.module));
+    encoder.double(2, change.complexity_before);
+    encoder.double(3, change.complexity_after);
+    encoder.uint(4, change.lines_before as u64);
+    encoder.uint(5, change.lines_after as u64);
+    for commit in &change.commits {
+        encoder.message(6, |encoder| encode_file_commit(encoder, commit));
+    }
+}
+
+fn encode_file_commit(encoder: &mut Encoder, commit: &FileCommit) {
+    encoder.string(1, &commit.hash);
+    encoder.string(2, &commit.author);
+    encoder.string(3, &commit.subject);
+}
+
+fn encode_node(encoder: &mut Encoder, node: &ArchitectureNode) {
+    encoder.string(1, &node.id);
+    encoder.string(2, &node.name);
+    encoder.string(3, &node.module_type.display_name());
+    encoder.string(4, &node.file_path);
+    encoder.string(5, node.crate_name.as_deref().unwrap_or(""));
+    for dependency in &node.dependencies {
+        encoder.string(6, dependency);
+    }
+    for dependent in &node.dependents {
+        encoder.string(7, dependent);
+    }
+    encoder.message(8, |encoder| encode_node_metrics(encoder, &node.metrics));
+    encoder.string(9, node.layer.as_deref().unwrap_or(""));
+    for feature in &node.features {
+        encoder.string(10, feature);
+    }
+    encoder.message(11, |encoder| encode_robustness(encoder, &node.robustness));
+    encoder.string(12, &format!("{:?}", node.status));
+    encoder.string(13, &node.entry_point.map(|kind| format!("{:?}", kind)).unwrap_or_default());
+    encoder.string(14, &node.module_path);
+}
+
+fn encode_node_metrics(encoder: &mut Encoder, metrics: &NodeMetrics) {
+    encoder.uint(1, metrics.lines_of_code as u64);
+    encoder.double(2, metrics.complexity_score);
+    encoder.double(3, metrics.test_coverage);
+    encoder.uint(4, metrics.function_count as u64);
+    encoder.uint(5, metrics.struct_count as u64);
+    encoder.uint(6, metrics.enum_count as u64);
+    encoder.uint(7, metrics.trait_count as u64);
+    encoder.uint(8, metrics.error_count as u64);
+    encoder.uint(9, metrics.warning_count as u64);
+    encoder.uint(10, metrics.dependency_count as u64);
+    encoder.uint(11, metrics.dependent_count as u64);
+    encoder.double(12, metrics.cyclomatic_complexity);
+    encoder.double(13, metrics.cognitive_complexity);
+    encoder.uint(14, metrics.recent_churn as u64);
+    encoder.uint(15, metrics.commit_count as u64);
+    encoder.string(16, metrics.last_author.as_deref().unwrap_or(""));
+}
+
+fn encode_robustness(encoder: &mut Encoder, robustness: &Robustness) {
+    encoder.uint(1, robustness.unsafe_blocks as u64);
+    encoder.uint(2, robustness.unwraps as u64);
+    encoder.uint(3, robustness.expects as u64);
+    encoder.uint(4, robustness.panics as u64);
+    encoder.uint(5, robustness.handles_input as u64);
+    encoder.double(6, robustness.score);
+}
+
+fn encode_edge(encoder: &mut Encoder, edge: &DependencyEdge) {
+    encoder.string(1, &edge.from);
+    encoder.string(2, &edge.to);
+    encoder.string(3, &format!("{:?}", edge.relationship));
+    encoder.double(4, edge.strength);
+    encoder.uint(5, edge.is_circular as u64);
+    encoder.uint(6, edge.layer_violation as u64);
+    for location in &edge.locations {
+        encoder.message(7, |encoder| encode_location(encoder, location));
+    }
+}
+
+fn encode_location(encoder: &mut Encoder, location: &SourceLocation) {
+    encoder.string(1, &location.file_path);
+    encoder.uint(2, location.line as u64);
+    encoder.string(3, &location.snippet);
+}
+
+fn encode_architecture_metrics(encoder: &mut Encoder, metrics: &ArchitectureMetrics) {
+    encoder.uint(1, metrics.total_functions as u64);
+    encoder.uint(2, metrics.total_structs as u64);
+    encoder.uint(3, metrics.total_enums as u64);
+    encoder.uint(4, metrics.total_traits as u64);
+    encoder.double(5, metrics.max_complexity);
+    encoder.double(6, metrics.min_complexity);
+    encoder.double(7, metrics.dependency_density);
+    encoder.double(8, metrics.modularity_score);
+    encoder.double(9, metrics.maintainability_index);
+    encoder.double(10, metrics.health_score);
+}
+
+fn encode_crate(encoder: &mut Encoder, metadata: &CrateMetadata) {
+    encoder.string(1, &metadata.name);
+    encoder.string(2, metadata.version.as_deref().unwrap_or(""));
+    encoder.string(3, &metadata.edition);
+    encoder.string(4, metadata.rust_version.as_deref().unwrap_or(""));
+}
+
+/// Writes proto3 fields, leaving out scalars that hold their default value
+#[derive(Default)]
+struct Encoder {
+    buffer: Vec<u8>,
+}
+
+impl Encoder {
+    fn key(&mut self, field: u64, wire_type: u64) {
+        self.varint(field << 3 | wire_type);
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buffer.push((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.buffer.push(value as u8);
+    }
+
+    fn uint(&mut self, field: u64, value: u64) {
+        if value != 0 {
+            self.key(field, VARINT);
+            self.varint(value);
+        }
+    }
+
+    fn double(&mut self, field: u64, value: f64) {
+        if value != 0.0 {
+            self.key(field, FIXED64);
+            self.buffer.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    fn string(&mut self, field: u64, value: &str) {
+        if !value.is_empty() {
+            self.bytes(field, value.as_bytes());
+        }
+    }
+
+    fn bytes(&mut self, field: u64, value: &[u8]) {
+        self.key(field, LENGTH_DELIMITED);
+        self.varint(value.len() as u64);
+        self.buffer.extend_from_slice(value);
+    }
+
+    /// Embedded message; always written, so present-but-empty messages survive
+    fn message(&mut self, field: u64, encode: impl FnOnce(&mut Encoder)) {
+        let mut nested = Encoder::default();
+        encode(&mut nested);
+        self.bytes(field, &nested.buffer);
+    }
+}
+
+#[cfg(all(test, feature = "grpc"))]
+mod tests {
+    use super::*;
+    use crate::{config::ProjectConfig, scanner::ArchitectureScanner};
+    use prost::Message;
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    /// Types prost generates from `proto/architecture.proto` in `build.rs`
+    #[allow(clippy::all)]
+    mod proto {
+        include!(concat!(env!("OUT_DIR"), "/rust_arch_viz.v1.rs"));
+    }
+
+    const MANIFEST: &str = r#"
+[package]
+name = "shop"
+version = "0.3.1"
+edition = "2021"
+rust-version = "1.74"
+
+[features]
+audit = []
+"#;
+
+    const LIB: &str = r#"
+pub mod orders;
+pub mod billing;
+#[cfg(feature = "audit")]
+pub mod audit;
+"#;
+
+    const ORDERS: &str = r#"
+use crate::billing::Invoice;
+
+pub struct Order {
+    pub invoice: Invoice,
+}
+
+pub fn place(input: &str) -> Order {
+    let total: u32 = input.parse().unwrap();
+    if total > 10 {
+        panic!("too large");
+    }
+    Order { invoice: crate::billing::issue(total) }
+}
+"#;
+
+    const BILLING: &str = r#"
+use crate::orders::Order;
+
+pub struct Invoice {
+    pub total: u32,
+}
+
+pub enum Status {
+    Open,
+    Paid,
+}
+
+pub trait Payable {
+    fn pay(&self, order: &Order);
+}
+
+pub fn issue(total: u32) -> Invoice {
+    Invoice { total }
+}
+"#;
+
+    const AUDIT: &str = r#"
+pub fn record() {}
+"#;
+
+    fn architecture() -> ArchitectureMap {
+        let files: BTreeMap<PathBuf, String> = [
+            ("Cargo.toml", MANIFEST),
+            ("src/lib.rs", LIB),
+            ("src/orders.rs", ORDERS),
+            ("src/billing.rs", BILLING),
+            ("src/audit.rs", AUDIT),
+        ]
+        .into_iter()
+        .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+        .collect();
+        let mut architecture = ArchitectureScanner::new("", ProjectConfig::default()).scan_sources(&files).unwrap();
+        // Filled from git and coverage data, which an in-memory scan has none of
+        for node in architecture.nodes.values_mut() {
+            node.layer = Some("domain".to_string());
+            node.metrics.test_coverage = 0.75;
+            node.metrics.recent_churn = 4;
+            node.metrics.commit_count = 9;
+            node.metrics.last_author = Some("Ada".to_string());
+        }
+        architecture
+    }
+
+    #[test]
+    fn encoding_decodes_with_the_published_schema() {
+        let architecture = architecture();
+        assert!(!architecture.circular_dependencies.is_empty(), "the sample should contain a cycle");
+        let decoded = proto::ArchitectureMap::decode(encode(&architecture).as_slice()).unwrap();
+
+        assert_eq!(decoded.last_scan, architecture.last_scan.to_rfc3339());
+        assert_eq!(decoded.total_modules, architecture.total_modules as u64);
+        assert_eq!(decoded.total_lines, architecture.total_lines as u64);
+        assert_eq!(decoded.average_complexity, architecture.average_complexity);
+        assert_eq!(
+            decoded.circular_dependencies.iter().map(|cycle| cycle.node_ids.clone()).collect::<Vec<_>>(),
+            architecture.circular_dependencies
+        );
+
+        let metrics = decoded.metrics.unwrap();
+        let expected = &architecture.metrics;
+        assert_eq!(
+            (metrics.total_functions, metrics.total_structs, metrics.total_enums, metrics.total_traits),
+            (expected.total_functions as u64, expected.total_structs as u64, expected.total_enums as u64, expected.total_traits as u64)
+        );
+        assert_eq!(
+            [metrics.max_complexity, metrics.min_complexity, metrics.dependency_density, metrics.modularity_score, metrics.maintainability_index, metrics.health_score],
+            [expected.max_complexity, expected.min_complexity, expected.dependency_density, expected.modularity_score, expected.maintainability_index, expected.health_score]
+        );
+
+        let crates: Vec<_> = decoded.crates.iter().map(|metadata| (metadata.name.as_str(), metadata.version.as_str(), metadata.edition.as_str(), metadata.rust_version.as_str())).collect();
+        assert_eq!(crates, [("shop", "0.3.1", "2021", "1.74")]);
+
+        assert_eq!(decoded.nodes.len(), architecture.nodes.len());
+        for node in &decoded.nodes {
+            let expected = &architecture.nodes[&node.id];
+            assert_eq!(node.name, expected.name);
+            assert_eq!(node.module_type, expected.module_type.display_name());
+            assert_eq!(node.file_path, expected.file_path);
+            assert_eq!(node.crate_name, expected.crate_name.clone().unwrap_or_default());
+            assert_eq!(node.dependencies, expected.dependencies);
+            assert_eq!(node.dependents, expected.dependents);
+            assert_eq!(node.layer, "domain");
+            assert_eq!(node.features, expected.features);
+            assert_eq!(node.status, format!("{:?}", expected.status));
+            assert_eq!(node.entry_point, expected.entry_point.map(|kind| format!("{:?}", kind)).unwrap_or_default());
+            assert_eq!(node.module_path, expected.module_path);
+
+            let metrics = node.metrics.as_ref().unwrap();
+            let counts = [
+                metrics.lines_of_code,
+                metrics.function_count,
+                metrics.struct_count,
+                metrics.enum_count,
+                metrics.trait_count,
+                metrics.error_count,
+                metrics.warning_count,
+                metrics.dependency_count,
+                metrics.dependent_count,
+                metrics.recent_churn,
+                metrics.commit_count,
+            ];
+            let expected_counts = [
+                expected.metrics.lines_of_code,
+                expected.metrics.function_count,
+                expected.metrics.struct_count,
+                expected.metrics.enum_count,
+                expected.metrics.trait_count,
+                expected.metrics.error_count,
+                expected.metrics.warning_count,
+                expected.metrics.dependency_count,
+                expected.metrics.dependent_count,
+                expected.metrics.recent_churn,
+                expected.metrics.commit_count,
+            ];
+            assert_eq!(counts, expected_counts.map(|count| count as u64));
+            assert_eq!(
+                [metrics.complexity_score, metrics.test_coverage, metrics.cyclomatic_complexity, metrics.cognitive_complexity],
+                [expected.metrics.complexity_score, expected.metrics.test_coverage, expected.metrics.cyclomatic_complexity, expected.metrics.cognitive_complexity]
+            );
+            assert_eq!(metrics.last_author, "Ada");
+
+            let robustness = node.robustness.as_ref().unwrap();
+            assert_eq!(
+                [robustness.unsafe_blocks, robustness.unwraps, robustness.expects, robustness.panics],
+                [expected.robustness.unsafe_blocks, expected.robustness.unwraps, expected.robustness.expects, expected.robustness.panics].map(|count| count as u64)
+            );
+            assert_eq!((robustness.handles_input, robustness.score), (expected.robustness.handles_input, expected.robustness.score));
+        }
+
+        assert_eq!(decoded.edges.len(), architecture.edges.len());
+        for (edge, expected) in decoded.edges.iter().zip(&architecture.edges) {
+            assert_eq!((edge.from.as_str(), edge.to.as_str()), (expected.from.as_str(), expected.to.as_str()));
+            assert_eq!(edge.relationship, format!("{:?}", expected.relationship));
+            assert_eq!(edge.strength, expected.strength);
+            assert_eq!((edge.is_circular, edge.layer_violation), (expected.is_circular, expected.layer_violation));
+            let locations: Vec<_> = edge.locations.iter().map(|location| (location.file_path.clone(), location.line as usize, location.snippet.clone())).collect();
+            let expected_locations: Vec<_> = expected.locations.iter().map(|location| (location.file_path.clone(), location.line, location.snippet.clone())).collect();
+            assert_eq!(locations, expected_locations);
+        }
+    }
+}
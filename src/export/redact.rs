@@ -0,0 +1,425 @@
+//! Redacted copies of an architecture for sharing outside the team.
+//!
+//! Names, paths and ids are replaced by salted hashes, so the same name maps
+//! to the same pseudonym everywhere in one export and the graph keeps its
+//! shape. Documentation, source locations, attributes and free-text messages
+//! are dropped; metrics and counts are kept as they are.
+
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::{
+    config::ProjectConfig,
+    scanner::{directories, ArchitectureScanner},
+    types::*,
+    visualizer::ArchitectureVisualizer,
+};
+
+/// Path segments that follow Cargo conventions and say nothing about the project
+const CONVENTIONAL_SEGMENTS: &[&str] = &["src", "tests", "benches", "examples", "bin", "lib.rs", "main.rs", "mod.rs", "build.rs"];
+
+/// Hashes names with a salt, so short or common names cannot be looked up
+pub struct Redactor {
+    salt: String,
+}
+
+impl Redactor {
+    pub fn new(salt: impl Into<String>) -> Self {
+        Self { salt: salt.into() }
+    }
+
+    /// A redactor with a salt that differs on every run
+    pub fn with_random_salt() -> Self {
+        let seed = format!(
+            "{:?}-{}",
+            std::time::SystemTime::now(),
+            std::process::id()
+        );
+        Self::new(format!("{:x}", Sha256::digest(seed.as_bytes())))
+    }
+
+    /// A redactor with `salt`, or a random one when it is not given
+    pub fn from_salt(salt: Option<String>) -> Self {
+        match salt {
+            Some(salt) => Self::new(salt),
+            None => Self::with_random_salt(),
+        }
+    }
+
+    /// Visualizer rendering redacted maps of the project at `project` without naming it
+    pub fn visualizer(&self, project: &Path, config: &ProjectConfig) -> ArchitectureVisualizer {
+        // Manual groups are named after the modules they hold
+        ArchitectureVisualizer::new(ArchitectureScanner::new(project, self.config(config))).with_curation(false)
+    }
+
+    /// Pseudonym of `value`, e.g. `m_3fa2c09d1e`
+    pub fn name(&self, value: &str) -> String {
+        format!("m_{}", &self.digest(value)[..10])
+    }
+
+    /// `path` with every unconventional segment hashed, keeping its depth and extension
+    pub fn path(&self, path: &str) -> String {
+        path.split('/')
+            .map(|segment| {
+                if CONVENTIONAL_SEGMENTS.contains(&segment) || segment.is_empty() {
+                    segment.to_string()
+                } else if let Some(stem) = segment.strip_suffix(".rs") {
+                    format!("{}.rs", self.name(stem))
+                } else {
+                    self.name(segment)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn names(&self, values: &[String]) -> Vec<String> {
+        values.iter().map(|value| self.name(value)).collect()
+    }
+
+    fn id(&self, id: &str) -> String {
+        format!("node-{}", &self.digest(id)[..16])
+    }
+
+    fn digest(&self, value: &str) -> String {
+        format!("{:x}", Sha256::digest(format!("{}:{}", self.salt, value).as_bytes()))
+    }
+
+    /// Redacted copy of `architecture`
+    pub fn architecture(&self, architecture: &ArchitectureMap) -> ArchitectureMap {
+        let ids: HashMap<&str, String> = architecture
+            .nodes
+            .keys()
+            .map(|id| (id.as_str(), self.id(id)))
+            .collect();
+        let id = |value: &String| ids.get(value.as_str()).cloned().unwrap_or_else(|| self.id(value));
+
+        let nodes = architecture
+            .nodes
+            .values()
+            .map(|node| {
+                let node = self.node(node, &id);
+                (node.id.clone(), node)
+            })
+            .collect();
+
+        let module_groups = directories::build(&nodes);
+        let edges = architecture
+            .edges
+            .iter()
+            .map(|edge| DependencyEdge {
+                from: id(&edge.from),
+                to: id(&edge.to),
+                locations: Vec::new(),
+                suggestions: Vec::new(),
+                ..edge.clone()
+            })
+            .collect();
+
+        ArchitectureMap {
+            nodes,
+            edges,
+            circular_dependencies: architecture
+                .circular_dependencies
+                .iter()
+                .map(|cycle| cycle.iter().map(&id).collect())
+                .collect(),
+            // Command output quotes file paths and source
+            hook_outcome: None,
+            cargo_features: architecture
+                .cargo_features
+                .iter()
+                .map(|crate_features| CrateFeatures {
+                    crate_name: self.name(&crate_features.crate_name),
+                    features: crate_features
+                        .features
+                        .iter()
+                        .map(|feature| FeatureDefinition {
+                            name: self.name(&feature.name),
+                            enables: feature.enables.iter().map(|value| self.name(value)).collect(),
+                        })
+                        .collect(),
+                    optional_dependencies: crate_features.optional_dependencies.iter().map(|name| self.name(name)).collect(),
+                })
+                .collect(),
+            crates: architecture
+                .crates
+                .iter()
+                .map(|metadata| CrateMetadata {
+                    name: self.name(&metadata.name),
+                    ..metadata.clone()
+                })
+                .collect(),
+            provenance: architecture.provenance.as_ref().map(|provenance| ScanProvenance {
+                git_commit: None,
+                host: None,
+                ..provenance.clone()
+            }),
+            anomalies: architecture
+                .anomalies
+                .iter()
+                .map(|anomaly| Anomaly {
+                    kind: anomaly.kind,
+                    message: format!("{:?}", anomaly.kind),
+                    node_id: anomaly.node_id.as_ref().map(&id),
+                })
+                .collect(),
+            previous_crate_health: architecture
+                .previous_crate_health
+                .iter()
+                .map(|(name, score)| (self.name(name), *score))
+                .collect(),
+            orphans: architecture.orphans.iter().map(&id).collect(),
+            variants: architecture
+                .variants
+                .iter()
+                .map(|variant| ArchitectureVariant {
+                    name: self.name(&variant.name),
+                    features: self.names(&variant.features),
+                    target: variant.target.as_deref().map(|target| self.name(target)),
+                    excluded: variant.excluded.iter().map(&id).collect(),
+                    ..variant.clone()
+                })
+                .collect(),
+            module_groups,
+            ..architecture.clone()
+        }
+    }
+
+    fn node(&self, node: &ArchitectureNode, id: &impl Fn(&String) -> String) -> ArchitectureNode {
+        ArchitectureNode {
+            id: id(&node.id),
+            name: self.name(&node.name),
+            file_path: self.path(&node.file_path),
+            crate_name: node.crate_name.as_deref().map(|name| self.name(name)),
+            entry_point: node.entry_point,
+            module_path: node
+                .module_path
+                .split("::")
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| self.name(segment))
+                .collect::<Vec<_>>()
+                .join("::"),
+            dependencies: node.dependencies.iter().map(|dependency| self.name(dependency)).collect(),
+            dependents: node.dependents.iter().map(id).collect(),
+            functions: node
+                .functions
+                .iter()
+                .map(|function| FunctionInfo {
+                    name: self.name(&function.name),
+                    documentation: None,
+                    attributes: Vec::new(),
+                    line: 0,
+                    ..function.clone()
+                })
+                .collect(),
+            structs: node
+                .structs
+                .iter()
+                .map(|item| StructInfo {
+                    name: self.name(&item.name),
+                    derives: self.names(&item.derives),
+                    documentation: None,
+                    attributes: Vec::new(),
+                    generics: self.names(&item.generics),
+                    ..item.clone()
+                })
+                .collect(),
+            enums: node
+                .enums
+                .iter()
+                .map(|item| EnumInfo {
+                    name: self.name(&item.name),
+                    derives: self.names(&item.derives),
+                    documentation: None,
+                    attributes: Vec::new(),
+                    generics: self.names(&item.generics),
+                    ..item.clone()
+                })
+                .collect(),
+            traits: node
+                .traits
+                .iter()
+                .map(|item| TraitInfo {
+                    name: self.name(&item.name),
+                    documentation: None,
+                    attributes: Vec::new(),
+                    generics: self.names(&item.generics),
+                    supertraits: self.names(&item.supertraits),
+                    ..item.clone()
+                })
+                .collect(),
+            impls: node
+                .impls
+                .iter()
+                .map(|item| ImplInfo {
+                    trait_path: self.name(&item.trait_path),
+                    self_type: self.name(&item.self_type),
+                    line: 0,
+                    trait_module: item.trait_module.as_ref().map(id),
+                })
+                .collect(),
+            features: node.features.iter().map(|feature| self.name(feature)).collect(),
+            // The messages quote the offending names
+            naming_violations: vec!["naming rule broken".to_string(); node.naming_violations.len()],
+            layer: node.layer.as_deref().map(|layer| self.name(layer)),
+            component: node.component.as_deref().map(|component| self.name(component)),
+            documentation: Vec::new(),
+            doc_coverage: DocCoverage {
+                undocumented: node
+                    .doc_coverage
+                    .undocumented
+                    .iter()
+                    .map(|item| UndocumentedItem {
+                        name: self.name(&item.name),
+                        line: 0,
+                        ..item.clone()
+                    })
+                    .collect(),
+                ..node.doc_coverage.clone()
+            },
+            routes: node
+                .routes
+                .iter()
+                .map(|route| HttpRoute {
+                    path: self.path(&route.path),
+                    handler: route.handler.as_deref().map(|handler| self.name(handler)),
+                    line: 0,
+                    ..route.clone()
+                })
+                .collect(),
+            split_suggestion: node.split_suggestion.as_ref().map(|suggestion| SplitSuggestion {
+                stay: suggestion.stay.iter().map(|item| self.name(item)).collect(),
+                moved: suggestion.moved.iter().map(|item| self.name(item)).collect(),
+                cross_references: suggestion.cross_references,
+            }),
+            dead_code: DeadCode {
+                unreachable_items: node.dead_code.unreachable_items.iter().map(|item| self.name(item)).collect(),
+                ..node.dead_code.clone()
+            },
+            api_leaks: node
+                .api_leaks
+                .iter()
+                .map(|leak| ApiLeak {
+                    item: self.name(&leak.item),
+                    signature: String::new(),
+                    crate_name: self.name(&leak.crate_name),
+                    type_path: leak.type_path.split("::").map(|segment| self.name(segment)).collect::<Vec<_>>().join("::"),
+                    line: 0,
+                })
+                .collect(),
+            metrics: NodeMetrics {
+                last_author: node.metrics.last_author.as_deref().map(|author| self.name(author)),
+                ..node.metrics.clone()
+            },
+            ownership: node.ownership.as_ref().map(|ownership| Ownership {
+                top_author: self.name(&ownership.top_author),
+                ..ownership.clone()
+            }),
+            // Hook data is the embedder's own and may name internal systems
+            annotations: BTreeMap::new(),
+            dependency_sites: HashMap::new(),
+            external_uses: BTreeMap::new(),
+            imported_items: HashMap::new(),
+            module_root: String::new(),
+            item_references: ItemReferences::default(),
+            public_signatures: Vec::new(),
+            cfg: node.cfg.iter().map(|predicate| self.name(predicate)).collect(),
+            groups: node.groups.iter().map(|group| self.name(group)).collect(),
+            cfg_gates: CfgGates::default(),
+            ..node.clone()
+        }
+    }
+
+    /// Copy of `config` without the project's name, description, authors and repository.
+    ///
+    /// Layer and group names are hashed so redacted modules still match their layers' colors
+    /// and their groups; the globs naming the project's paths are dropped.
+    pub fn config(&self, config: &ProjectConfig) -> ProjectConfig {
+        let mut config = config.clone();
+        config.project.name = Some("Redacted project".to_string());
+        config.project.description = None;
+        config.project.authors.clear();
+        config.project.repository = None;
+        // Ordered module types become layers, so their names get hashed like the nodes' layers
+        config.rules.layers = config.rules.effective_layers();
+        config.rules.module_type_order.clear();
+        for layer in &mut config.rules.layers {
+            layer.name = self.name(&layer.name);
+            layer.patterns.clear();
+            layer.groups = layer.groups.iter().map(|group| self.name(group)).collect();
+        }
+        config.groups.definitions = config.groups.definitions.keys().map(|group| (self.name(group), Vec::new())).collect();
+        config.rules.forbidden_dependencies.clear();
+        config.documentation.links.clear();
+        config.visualization.editor_url = None;
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{project_config::ParserBackend, ScanVariant};
+    use std::path::PathBuf;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "ledger_vault"
+version = "0.1.0"
+
+[features]
+ledger_audit = []
+"#;
+
+    const LIB: &str = r#"
+pub mod ledger_books;
+
+pub trait LedgerStore<LedgerKey>: LedgerBase {
+    fn ledger_lookup(&self);
+}
+"#;
+
+    const BOOKS: &str = r#"
+use crate::LedgerStore;
+
+#[derive(LedgerMacro, Clone)]
+pub struct LedgerEntry<LedgerAmount> {
+    pub ledger_amount: LedgerAmount,
+}
+
+#[derive(LedgerMacro)]
+pub enum LedgerState<LedgerReason> {
+    Open,
+    Closed(LedgerReason),
+}
+
+pub fn ledger_post() {}
+"#;
+
+    #[test]
+    fn redacted_architecture_names_nothing_from_the_project() {
+        let mut config = ProjectConfig::default();
+        // The syn parser reads derives, generics and supertraits
+        config.scanning.parser = ParserBackend::Syn;
+        config.scanning.variants = vec![ScanVariant {
+            name: "ledger_release".to_string(),
+            features: vec!["ledger_audit".to_string()],
+            target: Some("ledger-target".to_string()),
+            ..ScanVariant::default()
+        }];
+        let files: BTreeMap<PathBuf, String> = [("Cargo.toml", MANIFEST), ("src/lib.rs", LIB), ("src/ledger_books.rs", BOOKS)]
+            .into_iter()
+            .map(|(path, content)| (PathBuf::from(path), content.to_string()))
+            .collect();
+        let architecture = ArchitectureScanner::new("", config).scan_sources(&files).unwrap();
+        let original = serde_json::to_string(&architecture).unwrap();
+        for name in ["LedgerMacro", "LedgerAmount", "LedgerBase", "ledger-target"] {
+            assert!(original.contains(name), "scan lacks {}", name);
+        }
+
+        let redacted = serde_json::to_string(&Redactor::new("salt").architecture(&architecture)).unwrap().to_lowercase();
+        assert!(!redacted.contains("ledger"), "{}", redacted);
+    }
+}
@@ -0,0 +1,50 @@
+//! Export artifacts `serve` writes periodically, so a long-running server doubles as a documentation generator.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    config::ExportArtifact,
+    export::markdown,
+    types::ArchitectureMap,
+    visualizer::{static_page, ArchitectureVisualizer},
+};
+
+/// Directory scheduled exports are written to, if the `[exports]` section sets one
+pub fn export_directory(visualizer: &ArchitectureVisualizer) -> Option<PathBuf> {
+    let directory = visualizer.get_config().exports.directory.as_ref()?;
+    Some(visualizer.project_path().join(directory))
+}
+
+/// Write every configured artifact for `architecture`, returning the written paths
+pub fn write_artifacts(visualizer: &ArchitectureVisualizer, architecture: &ArchitectureMap) -> Result<Vec<PathBuf>> {
+    let Some(directory) = export_directory(visualizer) else {
+        return Ok(Vec::new());
+    };
+    std::fs::create_dir_all(&directory)
+        .with_context(|| format!("Failed to create export directory: {}", directory.display()))?;
+
+    let config = visualizer.get_config();
+    let mut written = Vec::new();
+    for artifact in &config.exports.artifacts {
+        let content = match artifact {
+            ExportArtifact::Json => serde_json::to_string_pretty(architecture)?,
+            ExportArtifact::Svg => static_page::render_graph_svg(architecture, &config.visualization)?,
+            ExportArtifact::Markdown => markdown::render_report(architecture, visualizer.project_name()),
+            ExportArtifact::Mermaid => visualizer.to_mermaid(architecture),
+        };
+        let path = directory.join(artifact.file_name());
+        write_atomically(&path, &content)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Readers such as static file servers never see a half-written file
+fn write_atomically(path: &Path, content: &str) -> Result<()> {
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, content)
+        .with_context(|| format!("Failed to write export: {}", temporary.display()))?;
+    std::fs::rename(&temporary, path)
+        .with_context(|| format!("Failed to write export: {}", path.display()))
+}
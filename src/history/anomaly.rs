@@ -0,0 +1,85 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    config::AnomalySettings,
+    types::{Anomaly, AnomalyKind, ArchitectureMap},
+};
+
+/// Unusual changes from `previous` to `current`, largest module spikes first
+pub fn detect_anomalies(previous: &ArchitectureMap, current: &ArchitectureMap, settings: &AnomalySettings) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    if !settings.enabled {
+        return anomalies;
+    }
+
+    if let Some(change) = percent_change(previous.total_modules as f64, current.total_modules as f64) {
+        if change.abs() >= settings.module_count_change_pct {
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::ModuleCountJump,
+                message: format!(
+                    "Module count went from {} to {} ({:+.0}%)",
+                    previous.total_modules, current.total_modules, change
+                ),
+                node_id: None,
+            });
+        }
+    }
+
+    let crates = |architecture: &ArchitectureMap| -> BTreeSet<String> {
+        architecture.nodes.values().filter_map(|node| node.crate_name.clone()).collect()
+    };
+    let previous_crates = crates(previous);
+    // A first snapshot without crate names would report every crate as new
+    if !previous_crates.is_empty() {
+        for name in crates(current).difference(&previous_crates) {
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::NewCrate,
+                message: format!("New crate `{}` appeared", name),
+                node_id: None,
+            });
+        }
+    }
+
+    if let Some(change) = percent_change(previous.average_complexity, current.average_complexity) {
+        if change >= settings.average_complexity_increase_pct {
+            anomalies.push(Anomaly {
+                kind: AnomalyKind::ComplexitySpike,
+                message: format!(
+                    "Average complexity rose from {:.2} to {:.2} ({:+.0}%)",
+                    previous.average_complexity, current.average_complexity, change
+                ),
+                node_id: None,
+            });
+        }
+    }
+
+    let mut spikes: Vec<(f64, Anomaly)> = current
+        .nodes
+        .values()
+        .filter_map(|node| {
+            let before = previous.nodes.get(&node.id)?.metrics.complexity_score;
+            let increase = node.metrics.complexity_score - before;
+            (increase >= settings.module_complexity_increase).then(|| {
+                (
+                    increase,
+                    Anomaly {
+                        kind: AnomalyKind::ModuleComplexitySpike,
+                        message: format!(
+                            "Complexity of {} jumped from {:.1} to {:.1}",
+                            node.file_path, before, node.metrics.complexity_score
+                        ),
+                        node_id: Some(node.id.clone()),
+                    },
+                )
+            })
+        })
+        .collect();
+    spikes.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.message.cmp(&b.1.message)));
+    anomalies.extend(spikes.into_iter().map(|(_, anomaly)| anomaly));
+
+    anomalies
+}
+
+fn percent_change(previous: f64, current: f64) -> Option<f64> {
+    (previous > 0.0).then(|| (current - previous) / previous * 100.0)
+}
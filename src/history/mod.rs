@@ -0,0 +1,14 @@
+//! Timestamped storage of `ArchitectureMap` snapshots.
+//!
+//! Snapshots are stored content-addressed: every node is serialized once into
+//! `objects/` under the SHA-256 of its JSON, and each snapshot manifest only
+//! records which object every node id points at. Unchanged modules are shared
+//! between snapshots, so daily snapshots of a large repository stay small.
+
+pub mod anomaly;
+pub mod snapshot_store;
+pub mod window;
+
+pub use anomaly::detect_anomalies;
+pub use snapshot_store::{CompactionReport, HistoryStore, SaveReport, SnapshotMeta};
+pub use window::{MetricTrend, MetricWindow};
@@ -0,0 +1,339 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::{schema, types::ArchitectureMap};
+
+const OBJECTS_DIR: &str = "objects";
+const SNAPSHOTS_DIR: &str = "snapshots";
+const THUMBNAILS_DIR: &str = "thumbnails";
+
+/// On-disk store of architecture snapshots
+pub struct HistoryStore {
+    root: PathBuf,
+}
+
+/// Summary of a stored snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMeta {
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    pub last_scan: DateTime<Utc>,
+    pub total_modules: usize,
+    pub total_lines: usize,
+    pub average_complexity: f64,
+    /// Git commit the scan was taken at, from its provenance
+    #[serde(default)]
+    pub git_commit: Option<String>,
+    /// Whether a preview image is stored; filled in when listing
+    #[serde(default)]
+    pub has_thumbnail: bool,
+}
+
+/// Outcome of saving a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveReport {
+    pub snapshot: SnapshotMeta,
+    pub new_objects: usize,
+    pub reused_objects: usize,
+}
+
+/// Outcome of compacting the store
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompactionReport {
+    pub snapshots_removed: usize,
+    pub snapshots_kept: usize,
+    pub objects_removed: usize,
+    pub bytes_reclaimed: u64,
+}
+
+/// Snapshot manifest: the map without its nodes, plus node id → object hash references.
+///
+/// The map stays JSON until it is loaded, so snapshots of older schema versions
+/// can be upgraded as a whole.
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotManifest {
+    meta: SnapshotMeta,
+    nodes: BTreeMap<String, String>,
+    architecture: serde_json::Value,
+}
+
+impl HistoryStore {
+    /// Open (and create if needed) a store rooted at `root`
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        for dir in [OBJECTS_DIR, SNAPSHOTS_DIR] {
+            std::fs::create_dir_all(root.join(dir))
+                .with_context(|| format!("Failed to create history directory: {}", root.join(dir).display()))?;
+        }
+        Ok(Self { root })
+    }
+
+    /// Open the store configured for a project
+    pub fn for_project<P: AsRef<Path>>(project_path: P, config: &crate::config::ProjectConfig) -> Result<Self> {
+        Self::open(project_path.as_ref().join(&config.history.directory))
+    }
+
+    /// The project's store, if any snapshot has ever been saved; unlike
+    /// [`HistoryStore::for_project`] this does not create it
+    pub fn existing_for_project<P: AsRef<Path>>(project_path: P, config: &crate::config::ProjectConfig) -> Option<Self> {
+        let root = project_path.as_ref().join(&config.history.directory);
+        root.join(SNAPSHOTS_DIR).is_dir().then_some(Self { root })
+    }
+
+    /// Most recent snapshot, if the store holds any
+    pub fn latest(&self) -> Result<Option<ArchitectureMap>> {
+        match self.list()?.pop() {
+            Some(meta) => self.load(&meta.id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Directory the store lives in
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Store a snapshot, writing only nodes whose content has not been seen before
+    pub fn save(&self, architecture: &ArchitectureMap) -> Result<SaveReport> {
+        let created_at = Utc::now();
+        let mut id = created_at.format("%Y%m%dT%H%M%S%3fZ").to_string();
+        while self.manifest_path(&id).exists() {
+            id.push('_');
+        }
+
+        let mut node_refs = BTreeMap::new();
+        let mut new_objects = 0;
+        let mut reused_objects = 0;
+
+        for (node_id, node) in &architecture.nodes {
+            let content = serde_json::to_vec(node)?;
+            let hash = content_hash(&content);
+            let object_path = self.object_path(&hash)?;
+
+            if object_path.exists() {
+                reused_objects += 1;
+            } else {
+                write_atomically(&object_path, &content)?;
+                new_objects += 1;
+            }
+            node_refs.insert(node_id.clone(), hash);
+        }
+
+        let meta = SnapshotMeta {
+            id: id.clone(),
+            created_at,
+            last_scan: architecture.last_scan,
+            total_modules: architecture.total_modules,
+            total_lines: architecture.total_lines,
+            average_complexity: architecture.average_complexity,
+            git_commit: architecture.provenance.as_ref().and_then(|provenance| provenance.git_commit.clone()),
+            has_thumbnail: false,
+        };
+
+        let mut skeleton = architecture.clone();
+        skeleton.nodes.clear();
+
+        let manifest = SnapshotManifest {
+            meta: meta.clone(),
+            nodes: node_refs,
+            architecture: serde_json::to_value(&skeleton)?,
+        };
+        write_atomically(&self.manifest_path(&id), &serde_json::to_vec(&manifest)?)?;
+
+        tracing::info!(
+            "Saved snapshot {} ({} new node objects, {} reused)",
+            id,
+            new_objects,
+            reused_objects
+        );
+
+        Ok(SaveReport {
+            snapshot: meta,
+            new_objects,
+            reused_objects,
+        })
+    }
+
+    /// List stored snapshots, oldest first
+    pub fn list(&self) -> Result<Vec<SnapshotMeta>> {
+        let mut snapshots = self
+            .manifest_ids()?
+            .iter()
+            .map(|id| {
+                self.read_manifest(id).map(|manifest| SnapshotMeta {
+                    has_thumbnail: self.thumbnail_path(id).is_file(),
+                    ..manifest.meta
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        snapshots.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        Ok(snapshots)
+    }
+
+    /// Whether a snapshot with `id` is stored
+    pub fn contains(&self, id: &str) -> bool {
+        // Ids are timestamps, so anything else cannot name a snapshot
+        !id.is_empty()
+            && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            && self.manifest_path(id).is_file()
+    }
+
+    /// Store the PNG preview of snapshot `id`, replacing any earlier one
+    pub fn save_thumbnail(&self, id: &str, image: &[u8]) -> Result<()> {
+        write_atomically(&self.thumbnail_path(id), image)
+    }
+
+    /// The PNG preview of snapshot `id`, if one was stored
+    pub fn thumbnail(&self, id: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.thumbnail_path(id);
+        if !self.contains(id) || !path.is_file() {
+            return Ok(None);
+        }
+        std::fs::read(&path)
+            .map(Some)
+            .with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    /// Load a snapshot back into a full architecture map, upgrading older schema versions
+    pub fn load(&self, id: &str) -> Result<ArchitectureMap> {
+        let manifest = self.read_manifest(id)?;
+        let mut architecture = manifest.architecture;
+
+        let mut nodes = serde_json::Map::new();
+        for (node_id, hash) in manifest.nodes {
+            let path = self.object_path(&hash).with_context(|| format!("Snapshot {} is corrupt", id))?;
+            let content = std::fs::read(path)
+                .with_context(|| format!("Snapshot {} references missing object {}", id, hash))?;
+            let node: serde_json::Value = serde_json::from_slice(&content)
+                .with_context(|| format!("Failed to parse object {}", hash))?;
+            nodes.insert(node_id, node);
+        }
+        if let Some(object) = architecture.as_object_mut() {
+            object.insert("nodes".to_string(), serde_json::Value::Object(nodes));
+        }
+
+        schema::upgrade(architecture).with_context(|| format!("Failed to read snapshot {}", id))
+    }
+
+    /// Drop all but the newest `keep_last` snapshots (if given) and delete unreferenced objects
+    pub fn compact(&self, keep_last: Option<usize>) -> Result<CompactionReport> {
+        let mut report = CompactionReport::default();
+        let mut snapshots = self.list()?;
+
+        if let Some(keep_last) = keep_last {
+            let excess = snapshots.len().saturating_sub(keep_last);
+            for meta in snapshots.drain(..excess) {
+                let path = self.manifest_path(&meta.id);
+                report.bytes_reclaimed += file_size(&path);
+                std::fs::remove_file(&path)
+                    .with_context(|| format!("Failed to remove snapshot {}", meta.id))?;
+                let thumbnail = self.thumbnail_path(&meta.id);
+                if thumbnail.is_file() {
+                    report.bytes_reclaimed += file_size(&thumbnail);
+                    std::fs::remove_file(&thumbnail)
+                        .with_context(|| format!("Failed to remove thumbnail of snapshot {}", meta.id))?;
+                }
+                report.snapshots_removed += 1;
+            }
+        }
+        report.snapshots_kept = snapshots.len();
+
+        let mut referenced = HashSet::new();
+        for meta in &snapshots {
+            referenced.extend(self.read_manifest(&meta.id)?.nodes.into_values());
+        }
+
+        for shard in std::fs::read_dir(self.root.join(OBJECTS_DIR))? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for object in std::fs::read_dir(&shard)? {
+                let object = object?.path();
+                let hash = object
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                if !referenced.contains(&hash) {
+                    report.bytes_reclaimed += file_size(&object);
+                    std::fs::remove_file(&object)?;
+                    report.objects_removed += 1;
+                }
+            }
+            if std::fs::read_dir(&shard)?.next().is_none() {
+                std::fs::remove_dir(&shard)?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn manifest_ids(&self) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(self.root.join(SNAPSHOTS_DIR))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    ids.push(stem.to_string());
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    fn read_manifest(&self, id: &str) -> Result<SnapshotManifest> {
+        let path = self.manifest_path(id);
+        let content = std::fs::read(&path)
+            .with_context(|| format!("Snapshot not found: {}", id))?;
+        serde_json::from_slice(&content).with_context(|| format!("Failed to parse snapshot {}", id))
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.root.join(SNAPSHOTS_DIR).join(format!("{}.json", id))
+    }
+
+    fn thumbnail_path(&self, id: &str) -> PathBuf {
+        self.root.join(THUMBNAILS_DIR).join(format!("{}.png", id))
+    }
+
+    /// Path of the object with `hash`, which must be a hex-encoded SHA-256
+    fn object_path(&self, hash: &str) -> Result<PathBuf> {
+        if hash.len() != 64 || !hash.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+            anyhow::bail!("Invalid object hash: {:?}", hash);
+        }
+        Ok(self.root
+            .join(OBJECTS_DIR)
+            .join(&hash[..2])
+            .join(format!("{}.json", hash)))
+    }
+}
+
+/// Hex-encoded SHA-256 of the given bytes
+fn content_hash(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Write a file via a temporary sibling so readers never see partial content
+fn write_atomically(path: &Path, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, content)
+        .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+    std::fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
@@ -0,0 +1,121 @@
+//! Aggregates of stored snapshots over a time window, for `/api/metrics?since=`.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::{history::SnapshotMeta, types::ArchitectureMap};
+
+/// How a metric moved over the window
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MetricTrend {
+    pub start: f64,
+    pub end: f64,
+    pub change: f64,
+    /// Relative change; `None` when the window starts at zero
+    pub change_pct: Option<f64>,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl MetricTrend {
+    fn from_values(values: &[f64]) -> Self {
+        let (Some(&start), Some(&end)) = (values.first(), values.last()) else {
+            return Self::default();
+        };
+        Self {
+            start,
+            end,
+            change: end - start,
+            change_pct: (start != 0.0).then(|| (end - start) / start * 100.0),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+        }
+    }
+}
+
+/// Snapshot aggregates since a point in time
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricWindow {
+    pub since: DateTime<Utc>,
+    /// Stored snapshots taken inside the window
+    pub snapshots: usize,
+    /// Scan time of the first and last data points
+    pub first_scan: Option<DateTime<Utc>>,
+    pub last_scan: Option<DateTime<Utc>>,
+    pub average_complexity: MetricTrend,
+    pub total_modules: MetricTrend,
+    pub total_lines: MetricTrend,
+}
+
+/// Parse `since`: an RFC 3339 timestamp, a `YYYY-MM-DD` date or a duration back from `now` such as `30d`, `12h` or `6w`
+pub fn parse_since(since: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let since = since.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(since) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(since, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    let invalid = || anyhow!("Invalid since value: {} (expected e.g. 30d, 12h, 2024-05-01 or an RFC 3339 timestamp)", since);
+    let split = since.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let amount: i64 = since[..split].parse().map_err(|_| invalid())?;
+    let duration = match &since[split..] {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    Ok(now - duration)
+}
+
+/// Aggregate the snapshots taken since `since`, ending with the `current` scan if given
+pub fn aggregate(snapshots: &[SnapshotMeta], since: DateTime<Utc>, current: Option<&ArchitectureMap>) -> MetricWindow {
+    let in_window: Vec<&SnapshotMeta> = snapshots.iter().filter(|meta| meta.last_scan >= since).collect();
+
+    let mut points: Vec<Point> = in_window
+        .iter()
+        .map(|meta| Point {
+            scan: meta.last_scan,
+            average_complexity: meta.average_complexity,
+            total_modules: meta.total_modules as f64,
+            total_lines: meta.total_lines as f64,
+        })
+        .collect();
+    if let Some(current) = current {
+        // The live scan is usually stored as the latest snapshot as well
+        if points.last().is_none_or(|point| point.scan != current.last_scan) {
+            points.push(Point {
+                scan: current.last_scan,
+                average_complexity: current.average_complexity,
+                total_modules: current.total_modules as f64,
+                total_lines: current.total_lines as f64,
+            });
+        }
+    }
+    points.sort_by_key(|point| point.scan);
+
+    let series = |pick: fn(&Point) -> f64| points.iter().map(pick).collect::<Vec<_>>();
+    MetricWindow {
+        since,
+        snapshots: in_window.len(),
+        first_scan: points.first().map(|point| point.scan),
+        last_scan: points.last().map(|point| point.scan),
+        average_complexity: MetricTrend::from_values(&series(|point| point.average_complexity)),
+        total_modules: MetricTrend::from_values(&series(|point| point.total_modules)),
+        total_lines: MetricTrend::from_values(&series(|point| point.total_lines)),
+    }
+}
+
+/// Metrics of one scan in the window
+struct Point {
+    scan: DateTime<Utc>,
+    average_complexity: f64,
+    total_modules: f64,
+    total_lines: f64,
+}
@@ -5,31 +5,57 @@
 //!
 //! ## Quick Start
 //!
-//! ```rust
-//! use rust_architecture_visualizer::{ArchitectureScanner, Visualizer};
+//! ```rust,no_run
+//! use rust_architecture_visualizer::{ArchitectureScanner, ArchitectureVisualizer, WebServer};
 //!
 //! #[tokio::main]
 //! async fn main() -> anyhow::Result<()> {
 //!     let scanner = ArchitectureScanner::new("./my-project", Default::default());
-//!     let architecture = scanner.scan().await?;
-//!     
-//!     let visualizer = Visualizer::new(scanner);
-//!     visualizer.start_server(8080).await?;
-//!     
+//!     let architecture = scanner.scan_async().await?;
+//!     println!("Found {} modules", architecture.total_modules);
+//!
+//!     let visualizer = ArchitectureVisualizer::new(scanner);
+//!     WebServer::new(visualizer).serve("127.0.0.1", 8080).await?;
+//!
 //!     Ok(())
 //! }
 //! ```
 
+#[cfg(feature = "web")]
+pub mod agent;
+pub mod check;
 pub mod config;
+pub mod corpus;
+pub mod curation;
+pub mod diff;
+#[cfg(feature = "web")]
+pub mod export;
+pub mod history;
+#[cfg(feature = "web")]
+pub mod pipeline;
+pub mod rules;
 pub mod scanner;
+pub mod schema;
+#[cfg(feature = "web")]
+pub mod simulation;
+#[cfg(feature = "web")]
 pub mod web;
+#[cfg(feature = "web")]
 pub mod visualizer;
 pub mod types;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Re-export main types for convenience
-pub use config::ProjectConfig;
-pub use scanner::ArchitectureScanner;
+pub use config::{
+    AnomalySettings, CheckSettings, ChurnSettings, ClassificationRule, ClassificationSettings, ClippySettings, ComplexityWeights, DeltaOverride, DeltaThresholds, DependencyRule, DiagnosticsSettings, DocumentationRule,
+    DocumentationSettings, ExportArtifact, ExportSettings, GroupSettings, HistorySettings, IgnoreSettings, LayerDefinition, MermaidDirection, MermaidGrouping, MermaidSettings, MetricsSettings, ModuleSelector, NamingSettings, OwnershipSettings, PngSettings, ProjectConfig, ProjectSettings, RuleSettings, StaleDataPolicy,
+    ScanVariant, ScanningSettings, ServerSettings, WatchSettings,
+};
+pub use scanner::{ArchitectureScanner, ScanHook};
+#[cfg(feature = "web")]
 pub use visualizer::ArchitectureVisualizer;
+#[cfg(feature = "web")]
 pub use web::WebServer;
 pub use types::*;
 
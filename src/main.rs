@@ -1,13 +1,28 @@
 use clap::{Parser, Subcommand};
 use rust_architecture_visualizer::{
+    agent,
+    check::{self, ViolationScope},
     config::ProjectConfig,
-    scanner::ArchitectureScanner,
+    corpus,
+    diff,
+    export::{self, ExportFormat},
+    history::{HistoryStore, SaveReport},
+    pipeline::{self, StepOutcome, TaskManifest},
+    scanner::{git_analyzer, ArchitectureScanner, ScanProgress},
+    schema,
+    types::{ArchitectureMap, LayoutType},
     web::WebServer,
     visualizer::ArchitectureVisualizer,
 };
-use std::path::PathBuf;
+use anyhow::Context;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use tracing::info;
 
+/// Environment variable holding the token agents authenticate with
+const AGENT_TOKEN_VAR: &str = "RUST_ARCH_VIZ_AGENT_TOKEN";
+
 #[derive(Parser)]
 #[command(name = "rust-arch-viz")]
 #[command(about = "A beautiful, real-time architecture visualizer for Rust projects")]
@@ -32,6 +47,22 @@ enum Commands {
         /// Configuration file path
         #[arg(short, long)]
         config: Option<PathBuf>,
+        
+        /// Hash module names and paths and strip docs and source references, for sharing externally
+        #[arg(long)]
+        redact: bool,
+        
+        /// Salt of the redaction hashes; reuse it to get the same pseudonyms across exports (random by default)
+        #[arg(long, requires = "redact")]
+        redact_salt: Option<String>,
+        
+        /// Print the effective configuration and the files a scan would read, with the reason for each one left out, without scanning
+        #[arg(long, conflicts_with_all = ["output", "redact"])]
+        dry_run: bool,
+        
+        /// Evaluate only this configured `scanning.variants` entry; repeat for several (default: all of them)
+        #[arg(long = "variant", value_name = "NAME")]
+        variants: Vec<String>,
     },
     
     /// Start the web server for interactive visualization
@@ -44,13 +75,21 @@ enum Commands {
         #[arg(long, default_value = "127.0.0.1")]
         host: String,
         
-        /// Path to the Rust project directory
+        /// Path to the Rust project directory; repeat to serve several projects behind a landing page
         #[arg(short, long, default_value = ".")]
-        project: PathBuf,
+        project: Vec<PathBuf>,
         
         /// Configuration file path
         #[arg(short, long)]
         config: Option<PathBuf>,
+        
+        /// Accept scans pushed by `agent` commands presenting this token (default: $RUST_ARCH_VIZ_AGENT_TOKEN)
+        #[arg(long)]
+        agent_token: Option<String>,
+        
+        /// Also serve the gRPC API on this port (builds with the `grpc` feature, single project only)
+        #[arg(long)]
+        grpc_port: Option<u16>,
     },
     
     /// Watch a project for changes and auto-refresh
@@ -67,6 +106,327 @@ enum Commands {
         #[arg(short, long)]
         config: Option<PathBuf>,
     },
+    
+    /// Export the architecture in a given format
+    Export {
+        /// Path to the Rust project directory
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Output format: json, html, bundle (zip for static hosting), markdown, cycles-md, cycles-json, mermaid, svg, graphml, gexf or png
+        #[arg(short, long, default_value = "json")]
+        format: ExportFormat,
+        
+        /// Layout of the svg and png formats: hierarchical, or grid for any other (default: the configured layout)
+        #[arg(long)]
+        layout: Option<LayoutType>,
+        
+        /// Width of the png format in pixels (default: `exports.png.width`, 1920)
+        #[arg(long)]
+        width: Option<u32>,
+        
+        /// Output file (defaults to stdout for text formats)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        
+        /// Configuration file path
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        
+        /// Only export this module's neighborhood (module path like `crate::scanner`, name, file path or id)
+        #[arg(long)]
+        focus: Option<String>,
+        
+        /// How many edges away from the focus module to include
+        #[arg(long, default_value = "1", requires = "focus")]
+        depth: usize,
+        
+        /// Hash module names and paths and strip docs and source references, for sharing externally
+        #[arg(long)]
+        redact: bool,
+        
+        /// Salt of the redaction hashes; reuse it to get the same pseudonyms across exports (random by default)
+        #[arg(long, requires = "redact")]
+        redact_salt: Option<String>,
+    },
+    
+    /// Check the project against the `[check]` thresholds and the `[rules]` dependency rules
+    Check {
+        /// Path to the Rust project directory
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Configuration file path
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        
+        /// Architecture JSON (from `scan --output`) to compare per-module metrics against
+        #[arg(long, conflicts_with = "baseline_snapshot")]
+        baseline: Option<PathBuf>,
+        
+        /// Stored history snapshot to compare against, by id or `latest`
+        #[arg(long)]
+        baseline_snapshot: Option<String>,
+        
+        /// Fail when the baseline is internally inconsistent, e.g. after hand edits
+        #[arg(long)]
+        validate: bool,
+        
+        /// Print the report as JSON
+        #[arg(long, conflicts_with = "markdown")]
+        json: bool,
+        
+        /// Print the report as Markdown, e.g. for a pull request comment
+        #[arg(long)]
+        markdown: bool,
+    },
+    
+    /// Scan several projects and compare their architectures
+    Corpus {
+        /// Project directories to scan
+        projects: Vec<PathBuf>,
+        
+        /// File listing project directories, one per line
+        #[arg(long)]
+        list: Option<PathBuf>,
+        
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Scan several git branches of a project and compare their architectures
+    CompareBranches {
+        /// Branches (or other commits) to compare, e.g. `main develop feature/x`
+        #[arg(required = true)]
+        branches: Vec<String>,
+        
+        /// Path to the Rust project directory inside the git repository
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Configuration file path; every branch is scanned with it
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Report architectural drift between two scans
+    Diff {
+        /// Architecture JSON (from `scan --output`) of the earlier scan
+        #[arg(required_unless_present = "baseline_snapshot")]
+        before: Option<PathBuf>,
+        
+        /// Architecture JSON of the later scan; the project is scanned when omitted
+        after: Option<PathBuf>,
+        
+        /// Stored history snapshot to use as the earlier scan, by id or `latest`
+        #[arg(long, conflicts_with = "before")]
+        baseline_snapshot: Option<String>,
+        
+        /// Fail when a loaded scan is internally inconsistent, e.g. after hand edits
+        #[arg(long)]
+        validate: bool,
+        
+        /// Path to the Rust project directory, scanned when no later scan is given and
+        /// searched for the commits between the two scans
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Configuration file path
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+        
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Scan here and push the results to a central `serve` instance, again after every change
+    Agent {
+        /// URL of the project on the server, e.g. http://viz.internal:8000 or http://viz.internal:8000/projects/<slug>
+        #[arg(long)]
+        server: String,
+        
+        /// Token the server was started with (default: $RUST_ARCH_VIZ_AGENT_TOKEN)
+        #[arg(long)]
+        token: Option<String>,
+        
+        /// Path to the Rust project directory
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Configuration file path
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        
+        /// Push one scan and exit instead of following changes
+        #[arg(long)]
+        once: bool,
+    },
+    
+    /// Manage stored architecture snapshots
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    
+    /// Run the steps of a task manifest (scan, check, export, history-save) over a single scan
+    Run {
+        /// Task manifest; relative paths in it are resolved against its directory
+        #[arg(default_value = "tasks.toml")]
+        manifest: PathBuf,
+    },
+    
+    /// Print the JSON Schema of the architecture JSON written by `scan --output`
+    Schema {
+        /// Write the schema to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryAction {
+    /// Scan the project and store the result as a snapshot
+    Save {
+        /// Path to the Rust project directory
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Configuration file path
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+    
+    /// List stored snapshots
+    List {
+        /// Path to the Rust project directory
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Configuration file path
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+    },
+    
+    /// Prune old snapshots and delete node objects no snapshot references
+    Compact {
+        /// Path to the Rust project directory
+        #[arg(short, long, default_value = ".")]
+        project: PathBuf,
+        
+        /// Configuration file path
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        
+        /// Number of most recent snapshots to keep (defaults to history.keep_last)
+        #[arg(long)]
+        keep: Option<usize>,
+    },
+}
+
+/// Load the configuration from an explicit file or the project directory
+fn load_config(project: &Path, config: Option<PathBuf>) -> anyhow::Result<ProjectConfig> {
+    if let Some(config_path) = config {
+        ProjectConfig::from_file(&config_path)
+    } else {
+        ProjectConfig::from_project_dir(project)
+    }
+}
+
+/// The configuration `scan` would use and every Rust file it would read or leave out
+fn print_scan_plan(project: &Path, config: Option<PathBuf>) -> anyhow::Result<()> {
+    let source = match &config {
+        Some(path) => path.display().to_string(),
+        None => match ProjectConfig::find_in_dir(project) {
+            Some(path) => path.display().to_string(),
+            None if project.join("Cargo.toml").exists() => format!("{} (package metadata only)", project.join("Cargo.toml").display()),
+            None => "built-in defaults".to_string(),
+        },
+    };
+    let config = load_config(project, config)?;
+    println!("# Configuration from {}", source);
+    println!("{}", toml::to_string_pretty(&config).context("Failed to serialize the configuration")?);
+    
+    let scanner = ArchitectureScanner::new(project, config);
+    let plan = scanner.file_plan()?;
+    let scanned = plan.iter().filter(|(_, exclusion)| exclusion.is_none()).count();
+    println!("# Files: {} scanned, {} excluded", scanned, plan.len() - scanned);
+    for (path, exclusion) in &plan {
+        let path = path.strip_prefix(project).unwrap_or(path).display();
+        match exclusion {
+            None => println!("scan  {}", path),
+            Some(reason) => println!("skip  {}  ({})", path, reason),
+        }
+    }
+    Ok(())
+}
+
+/// Parsing progress on stderr, redrawn in place whenever another percent is done
+fn print_progress(progress: ScanProgress) {
+    static PRINTED_PERCENT: AtomicUsize = AtomicUsize::new(0);
+    let percent = progress.processed_files * 100 / progress.total_files.max(1);
+    if percent > PRINTED_PERCENT.fetch_max(percent, Ordering::Relaxed) {
+        eprint!("\rParsing files: {}/{} ({}%)", progress.processed_files, progress.total_files, percent);
+        if percent == 100 {
+            eprintln!();
+        }
+    }
+}
+
+/// Violations and new cycles of a check, one per line, then whether it passed
+fn print_check_report(report: &check::CheckReport) {
+    for violation in &report.violations {
+        match &violation.scope {
+            ViolationScope::Project => println!("FAIL  project: {}", violation.message),
+            ViolationScope::Module { file_path, .. } => {
+                println!("FAIL  {}: {}", file_path, violation.message)
+            }
+        }
+    }
+    for cycle in &report.new_cycles {
+        println!("CYCLE {} -> {} (new)", cycle.file_paths.join(" -> "), cycle.file_paths[0]);
+        for edge in &cycle.new_edges {
+            println!("        {}", check::describe_edge(edge));
+        }
+    }
+    if report.baseline_scan.is_some() {
+        println!(
+            "Compared {} modules against the baseline ({} new)",
+            report.compared_modules, report.new_modules
+        );
+    }
+    if report.passed {
+        println!("Check passed");
+    } else {
+        println!("Check failed with {} violation(s)", report.violations.len());
+    }
+}
+
+fn print_saved_snapshot(report: &SaveReport) {
+    println!(
+        "Saved snapshot {} ({} modules, {} new node objects, {} unchanged)",
+        report.snapshot.id,
+        report.snapshot.total_modules,
+        report.new_objects,
+        report.reused_objects
+    );
 }
 
 #[tokio::main]
@@ -79,17 +439,28 @@ async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Scan { project, output, config } => {
+        Commands::Scan { project, output, config, redact, redact_salt, dry_run, variants } => {
+            if dry_run {
+                return print_scan_plan(&project, config);
+            }
             info!("Scanning project at: {:?}", project);
             
-            let config = if let Some(config_path) = config {
-                ProjectConfig::from_file(&config_path)?
-            } else {
-                ProjectConfig::from_project_dir(&project)?
-            };
+            let mut config = load_config(&project, config)?;
+            if !variants.is_empty() {
+                if let Some(unknown) = variants.iter().find(|name| !config.scanning.variants.iter().any(|variant| &variant.name == *name)) {
+                    anyhow::bail!("No variant named {} in scanning.variants", unknown);
+                }
+                config.scanning.variants.retain(|variant| variants.contains(&variant.name));
+            }
             
-            let scanner = ArchitectureScanner::new(&project, config);
-            let architecture = scanner.scan_async().await?;
+            let mut scanner = ArchitectureScanner::new(&project, config);
+            if std::io::stderr().is_terminal() {
+                scanner = scanner.with_progress(print_progress);
+            }
+            let mut architecture = scanner.scan_async().await?;
+            if redact {
+                architecture = export::Redactor::from_salt(redact_salt).architecture(&architecture);
+            }
             
             if let Some(output_path) = output {
                 std::fs::write(&output_path, serde_json::to_string_pretty(&architecture)?)?;
@@ -99,19 +470,25 @@ async fn main() -> anyhow::Result<()> {
             }
         }
         
-        Commands::Serve { port, host, project, config } => {
+        Commands::Serve { port, host, project, config, agent_token, grpc_port } => {
             info!("Starting web server on {}:{}", host, port);
-            info!("Project directory: {:?}", project);
+            info!("Project directories: {:?}", project);
             
-            let config = if let Some(config_path) = config {
-                ProjectConfig::from_file(&config_path)?
-            } else {
-                ProjectConfig::from_project_dir(&project)?
-            };
+            let mut visualizers = Vec::new();
+            for project in &project {
+                let scanner = ArchitectureScanner::new(project, load_config(project, config.clone())?);
+                let visualizer = ArchitectureVisualizer::new(scanner);
+                visualizers.push(match &config {
+                    Some(path) => visualizer.with_config_path(path),
+                    None => visualizer,
+                });
+            }
             
-            let scanner = ArchitectureScanner::new(&project, config);
-            let visualizer = ArchitectureVisualizer::new(scanner);
-            let server = WebServer::new(visualizer);
+            let mut visualizers = visualizers.into_iter();
+            let first = visualizers.next().ok_or_else(|| anyhow::anyhow!("No project given"))?;
+            let server = visualizers.fold(WebServer::new(first), WebServer::with_project)
+                .agent_token(agent_token.or_else(|| std::env::var(AGENT_TOKEN_VAR).ok()).filter(|token| !token.is_empty()))
+                .grpc_port(grpc_port);
             
             server.serve(&host, port).await?;
         }
@@ -119,19 +496,257 @@ async fn main() -> anyhow::Result<()> {
         Commands::Watch { project, port, config } => {
             info!("Starting watch mode for project: {:?}", project);
             
-            let config = if let Some(config_path) = config {
-                ProjectConfig::from_file(&config_path)?
-            } else {
-                ProjectConfig::from_project_dir(&project)?
-            };
-            
-            let scanner = ArchitectureScanner::new(&project, config);
+            let scanner = ArchitectureScanner::new(&project, load_config(&project, config.clone())?);
             let visualizer = ArchitectureVisualizer::new(scanner);
+            let visualizer = match config {
+                Some(path) => visualizer.with_config_path(path),
+                None => visualizer,
+            };
             let server = WebServer::new(visualizer);
             
             // Enable watch mode and serve
             server.watch_mode(true).serve("127.0.0.1", port).await?;
         }
+        
+        Commands::Export { project, format, output, config, focus, depth, redact, redact_salt, layout, width } => {
+            let mut config = load_config(&project, config)?;
+            if let Some(layout) = layout {
+                config.visualization.layout = layout;
+            }
+            if let Some(width) = width {
+                config.exports.png.width = width;
+            }
+            let scanner = ArchitectureScanner::new(&project, config);
+            let mut architecture = scanner.scan_async().await?;
+            if let Some(focus) = focus {
+                architecture = export::focus_subgraph(&architecture, &focus, depth)?;
+            }
+            let visualizer = if redact {
+                let redactor = export::Redactor::from_salt(redact_salt);
+                architecture = redactor.architecture(&architecture);
+                redactor.visualizer(&project, &scanner.config)
+            } else {
+                ArchitectureVisualizer::new(scanner)
+            };
+            
+            let content = export::export(&visualizer, &architecture, format)?;
+            
+            match output {
+                Some(output_path) => {
+                    std::fs::write(&output_path, content)?;
+                    info!("Exported {} to: {:?}", format, output_path);
+                }
+                None if format.is_binary() => {
+                    let output_path = PathBuf::from(format.default_file_name());
+                    std::fs::write(&output_path, content)?;
+                    println!("Exported {} to {}", format, output_path.display());
+                }
+                None => {
+                    use std::io::Write;
+                    std::io::stdout().write_all(&content)?;
+                }
+            }
+        }
+        
+        Commands::Check { project, config, baseline, baseline_snapshot, validate, json, markdown } => {
+            let config = load_config(&project, config)?;
+            
+            let baseline = match (baseline, baseline_snapshot) {
+                (Some(path), _) => Some(pipeline::read_architecture(&path, "baseline", validate)?),
+                (None, Some(id)) => Some(pipeline::load_snapshot(&project, &config, &id, validate)?),
+                (None, None) => None,
+            };
+            
+            let settings = config.check.clone();
+            let rules = config.rules.clone();
+            let scanner = ArchitectureScanner::new(&project, config);
+            let architecture = scanner.scan_async().await?;
+            let mut report = check::run_check(&architecture, baseline.as_ref(), &settings, &rules)?;
+            check::attach_origins(&mut report, |location| {
+                git_analyzer::line_origin(&project, &location.file_path, location.line)
+            });
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else if markdown {
+                print!("{}", check::render_markdown(&report));
+            } else {
+                print_check_report(&report);
+            }
+            
+            if !report.passed {
+                std::process::exit(1);
+            }
+        }
+        
+        Commands::Corpus { mut projects, list, json, output } => {
+            if let Some(list) = list {
+                projects.extend(corpus::read_project_list(&list)?);
+            }
+            if projects.is_empty() {
+                anyhow::bail!("No projects given; pass project directories or --list");
+            }
+            
+            let report = corpus::scan_corpus(&projects).await;
+            let content = if json {
+                serde_json::to_string_pretty(&report)?
+            } else {
+                corpus::render_table(&report)
+            };
+            
+            match output {
+                Some(output_path) => {
+                    std::fs::write(&output_path, content)?;
+                    println!("Corpus report written to {:?}", output_path);
+                }
+                None => print!("{}", content),
+            }
+        }
+        
+        Commands::CompareBranches { branches, project, config, json, output } => {
+            let config = load_config(&project, config)?;
+            let report = corpus::branches::compare_branches(&project, &config, &branches).await?;
+            let content = if json {
+                serde_json::to_string_pretty(&report)?
+            } else {
+                corpus::branches::render_table(&report)
+            };
+            
+            match output {
+                Some(output_path) => {
+                    std::fs::write(&output_path, content)?;
+                    println!("Branch comparison written to {:?}", output_path);
+                }
+                None => print!("{}", content),
+            }
+        }
+        
+        Commands::Diff { before, after, baseline_snapshot, validate, project, config, json, output } => {
+            let config = load_config(&project, config)?;
+            let before = match (before, baseline_snapshot) {
+                (Some(path), _) => pipeline::read_architecture(&path, "earlier scan", validate)?,
+                (None, Some(id)) => pipeline::load_snapshot(&project, &config, &id, validate)?,
+                (None, None) => anyhow::bail!("Pass the earlier scan's JSON or --baseline-snapshot"),
+            };
+            let after = match after {
+                Some(path) => pipeline::read_architecture(&path, "later scan", validate)?,
+                None => ArchitectureScanner::new(&project, config).scan_async().await?,
+            };
+            
+            let mut report = diff::compare(&before, &after);
+            let git_commit = |architecture: &ArchitectureMap| {
+                architecture.provenance.as_ref().and_then(|provenance| provenance.git_commit.clone())
+            };
+            if let (Some(from), Some(to)) = (git_commit(&before), git_commit(&after)) {
+                if from != to {
+                    diff::attach_commits(&mut report, &git_analyzer::commits_between(&project, &from, &to));
+                }
+            }
+            let content = if json {
+                serde_json::to_string_pretty(&report)?
+            } else {
+                diff::render_text(&report)
+            };
+            
+            match output {
+                Some(output_path) => {
+                    std::fs::write(&output_path, content)?;
+                    println!("Architecture diff written to {:?}", output_path);
+                }
+                None => print!("{}", content),
+            }
+        }
+        
+        Commands::Agent { server, token, project, config, once } => {
+            let token = token
+                .or_else(|| std::env::var(AGENT_TOKEN_VAR).ok())
+                .filter(|token| !token.is_empty())
+                .with_context(|| format!("No agent token; pass --token or set {}", AGENT_TOKEN_VAR))?;
+            let scanner = ArchitectureScanner::new(&project, load_config(&project, config)?);
+            info!("Pushing scans of {:?} to {}", project, server);
+            agent::run(&scanner, &agent::AgentOptions { server, token, once }).await?;
+        }
+        
+        Commands::History { action } => match action {
+            HistoryAction::Save { project, config } => {
+                let config = load_config(&project, config)?;
+                
+                let scanner = ArchitectureScanner::new(&project, config.clone());
+                let architecture = scanner.scan_async().await?;
+                print_saved_snapshot(&pipeline::save_snapshot(&project, &config, &architecture)?);
+            }
+            
+            HistoryAction::List { project, config } => {
+                let config = load_config(&project, config)?;
+                let store = HistoryStore::for_project(&project, &config)?;
+                
+                for snapshot in store.list()? {
+                    println!(
+                        "{}  {}  {} modules  {} lines  {:.1} avg complexity{}",
+                        snapshot.id,
+                        snapshot.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                        snapshot.total_modules,
+                        snapshot.total_lines,
+                        snapshot.average_complexity,
+                        snapshot.git_commit
+                            .map(|commit| format!("  @ {}", &commit[..commit.len().min(12)]))
+                            .unwrap_or_default()
+                    );
+                }
+            }
+            
+            HistoryAction::Compact { project, config, keep } => {
+                let config = load_config(&project, config)?;
+                let store = HistoryStore::for_project(&project, &config)?;
+                let report = store.compact(keep.or(config.history.keep_last))?;
+                
+                println!(
+                    "Removed {} snapshots and {} unreferenced objects ({} bytes reclaimed); {} snapshots kept",
+                    report.snapshots_removed,
+                    report.objects_removed,
+                    report.bytes_reclaimed,
+                    report.snapshots_kept
+                );
+            }
+        },
+        
+        Commands::Run { manifest } => {
+            let manifest = TaskManifest::from_file(&manifest)?;
+            let config = load_config(&manifest.project, manifest.config.clone())?;
+            let mut scanner = ArchitectureScanner::new(&manifest.project, config);
+            if std::io::stderr().is_terminal() {
+                scanner = scanner.with_progress(print_progress);
+            }
+
+            let mut passed = true;
+            pipeline::run(&manifest, scanner, |outcome| match outcome {
+                StepOutcome::Scanned { output } => println!("Architecture data saved to {}", output.display()),
+                StepOutcome::Checked { report, written } => {
+                    print_check_report(report);
+                    for path in written {
+                        println!("Check report written to {}", path.display());
+                    }
+                    passed &= report.passed;
+                }
+                StepOutcome::Exported { format, output } => println!("Exported {} to {}", format, output.display()),
+                StepOutcome::Saved(report) => print_saved_snapshot(report),
+            }).await?;
+
+            if !passed {
+                std::process::exit(1);
+            }
+        }
+        
+        Commands::Schema { output } => {
+            let content = serde_json::to_string_pretty(&schema::json_schema())?;
+            match output {
+                Some(output_path) => {
+                    std::fs::write(&output_path, content)?;
+                    println!("Schema written to {:?}", output_path);
+                }
+                None => println!("{}", content),
+            }
+        }
     }
 
     Ok(())
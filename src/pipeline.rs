@@ -0,0 +1,243 @@
+//! Task manifests of `rust-arch-viz run`: several commands over one scan.
+//!
+//! A manifest names a project and lists steps, run in order against a single
+//! scan so a CI job that checks, exports and snapshots a project parses its
+//! sources once. Relative paths in the manifest are resolved against the
+//! manifest's directory. A failing check does not stop the later steps, so
+//! its reports and exports are still written.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    check::{self, CheckReport},
+    config::ProjectConfig,
+    export::{self, ExportFormat},
+    history::{HistoryStore, SaveReport},
+    scanner::{git_analyzer, ArchitectureScanner},
+    schema,
+    types::ArchitectureMap,
+    visualizer::ArchitectureVisualizer,
+};
+
+/// A parsed task manifest
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskManifest {
+    /// Project directory, `.` by default
+    #[serde(default = "default_project")]
+    pub project: PathBuf,
+    /// Configuration file; the project's own one when unset
+    #[serde(default)]
+    pub config: Option<PathBuf>,
+    #[serde(default)]
+    pub steps: Vec<TaskStep>,
+}
+
+/// One step of a manifest, selected by its `task` key
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "task", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum TaskStep {
+    /// Write the scan as architecture JSON, like `scan --output`
+    Scan {
+        output: PathBuf,
+    },
+    /// Check the scan against `[check]` and `[rules]`, optionally writing the report
+    Check {
+        /// Architecture JSON to compare against
+        #[serde(default)]
+        baseline: Option<PathBuf>,
+        /// Stored snapshot to compare against, or `latest`
+        #[serde(default)]
+        baseline_snapshot: Option<String>,
+        /// Fail when the baseline is internally inconsistent, like `check --validate`
+        #[serde(default)]
+        validate: bool,
+        #[serde(default)]
+        json: Option<PathBuf>,
+        #[serde(default)]
+        markdown: Option<PathBuf>,
+    },
+    /// Write one export format, like `export --output`
+    Export {
+        #[serde(deserialize_with = "deserialize_format")]
+        format: ExportFormat,
+        output: PathBuf,
+        #[serde(default)]
+        focus: Option<String>,
+        #[serde(default = "default_depth")]
+        depth: usize,
+        #[serde(default)]
+        redact: bool,
+        #[serde(default)]
+        redact_salt: Option<String>,
+    },
+    /// Store the scan as a snapshot, like `history save`
+    #[serde(alias = "snapshot")]
+    HistorySave {},
+}
+
+/// What a step did, reported as soon as it is done
+#[derive(Debug)]
+pub enum StepOutcome {
+    Scanned { output: PathBuf },
+    Checked { report: CheckReport, written: Vec<PathBuf> },
+    Exported { format: ExportFormat, output: PathBuf },
+    Saved(SaveReport),
+}
+
+fn default_project() -> PathBuf {
+    PathBuf::from(".")
+}
+
+fn default_depth() -> usize {
+    1
+}
+
+fn deserialize_format<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ExportFormat, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(|e: anyhow::Error| serde::de::Error::custom(e.to_string()))
+}
+
+impl TaskManifest {
+    /// Read a manifest, resolving its paths against the manifest's directory
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read task manifest: {}", path.display()))?;
+        let mut manifest: TaskManifest = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse task manifest: {}", path.display()))?;
+        if manifest.steps.is_empty() {
+            anyhow::bail!("Task manifest {} has no steps", path.display());
+        }
+
+        let base = path.parent().unwrap_or(Path::new(""));
+        let resolve = |path: &mut PathBuf| *path = base.join(&*path);
+        resolve(&mut manifest.project);
+        if let Some(config) = &mut manifest.config {
+            resolve(config);
+        }
+        for step in &mut manifest.steps {
+            match step {
+                TaskStep::Scan { output } | TaskStep::Export { output, .. } => resolve(output),
+                TaskStep::Check { baseline, baseline_snapshot, json, markdown, .. } => {
+                    if baseline.is_some() && baseline_snapshot.is_some() {
+                        anyhow::bail!("A check step of {} sets both `baseline` and `baseline_snapshot`", path.display());
+                    }
+                    baseline.iter_mut().chain(json).chain(markdown).for_each(resolve);
+                }
+                TaskStep::HistorySave {} => {}
+            }
+        }
+        Ok(manifest)
+    }
+}
+
+/// Scan once with `scanner` and run every step of `manifest` on the result, in order
+pub async fn run(
+    manifest: &TaskManifest,
+    scanner: ArchitectureScanner,
+    mut on_step: impl FnMut(&StepOutcome),
+) -> Result<()> {
+    let project = &manifest.project;
+    let architecture = scanner.scan_async().await?;
+    let config = scanner.config.clone();
+    let visualizer = ArchitectureVisualizer::new(scanner);
+
+    for step in &manifest.steps {
+        let outcome = match step {
+            TaskStep::Scan { output } => {
+                write(output, serde_json::to_vec_pretty(&architecture)?)?;
+                StepOutcome::Scanned { output: output.clone() }
+            }
+            TaskStep::Check { baseline, baseline_snapshot, validate, json, markdown } => {
+                let baseline = match (baseline, baseline_snapshot) {
+                    (Some(path), _) => Some(read_architecture(path, "baseline", *validate)?),
+                    (None, Some(id)) => Some(load_snapshot(project, &config, id, *validate)?),
+                    (None, None) => None,
+                };
+                let mut report = check::run_check(&architecture, baseline.as_ref(), &config.check, &config.rules)?;
+                check::attach_origins(&mut report, |location| {
+                    git_analyzer::line_origin(project, &location.file_path, location.line)
+                });
+
+                let mut written = Vec::new();
+                if let Some(path) = json {
+                    write(path, serde_json::to_vec_pretty(&report)?)?;
+                    written.push(path.clone());
+                }
+                if let Some(path) = markdown {
+                    write(path, check::render_markdown(&report).into_bytes())?;
+                    written.push(path.clone());
+                }
+                StepOutcome::Checked { report, written }
+            }
+            TaskStep::Export { format, output, focus, depth, redact, redact_salt } => {
+                let mut exported = match focus {
+                    Some(focus) => export::focus_subgraph(&architecture, focus, *depth)?,
+                    None => architecture.clone(),
+                };
+                let content = if *redact {
+                    let redactor = export::Redactor::from_salt(redact_salt.clone());
+                    exported = redactor.architecture(&exported);
+                    export::export(&redactor.visualizer(project, &config), &exported, *format)?
+                } else {
+                    export::export(&visualizer, &exported, *format)?
+                };
+                write(output, content)?;
+                StepOutcome::Exported { format: *format, output: output.clone() }
+            }
+            TaskStep::HistorySave {} => StepOutcome::Saved(save_snapshot(project, &config, &architecture)?),
+        };
+        on_step(&outcome);
+    }
+    Ok(())
+}
+
+/// Store `architecture` as a snapshot of `project`, with a thumbnail when `history.thumbnails` is on
+pub fn save_snapshot(project: &Path, config: &ProjectConfig, architecture: &ArchitectureMap) -> Result<SaveReport> {
+    let store = HistoryStore::for_project(project, config)?;
+    let report = store.save(architecture)?;
+
+    #[cfg(feature = "png")]
+    if config.history.thumbnails {
+        let thumbnail = export::png::render(architecture, &config.visualization, config.history.thumbnail_width)
+            .and_then(|image| store.save_thumbnail(&report.snapshot.id, &image));
+        if let Err(e) = thumbnail {
+            tracing::warn!("Failed to store a thumbnail of snapshot {}: {:#}", report.snapshot.id, e);
+        }
+    }
+    Ok(report)
+}
+
+fn write(path: &Path, content: Vec<u8>) -> Result<()> {
+    std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Architecture JSON written by `scan --output`, checked with `ArchitectureMap::validate` when `validate` is set
+pub fn read_architecture(path: &Path, what: &str, validate: bool) -> Result<ArchitectureMap> {
+    let content = std::fs::read(path).with_context(|| format!("Failed to read {}: {}", what, path.display()))?;
+    let architecture = schema::parse_architecture(&content)
+        .with_context(|| format!("Failed to parse {}: {}", what, path.display()))?;
+    if validate {
+        architecture.validate().with_context(|| format!("Invalid {}: {}", what, path.display()))?;
+    }
+    Ok(architecture)
+}
+
+/// Stored snapshot `id` of `project`, or the newest one for `latest`
+pub fn load_snapshot(project: &Path, config: &ProjectConfig, id: &str, validate: bool) -> Result<ArchitectureMap> {
+    let store = HistoryStore::for_project(project, config)?;
+    let id = if id == "latest" {
+        store.list()?.pop().map(|snapshot| snapshot.id)
+            .context("No snapshots stored yet")?
+    } else {
+        id.to_string()
+    };
+    let architecture = store.load(&id)?;
+    if validate {
+        architecture.validate().with_context(|| format!("Invalid snapshot {}", id))?;
+    }
+    Ok(architecture)
+}
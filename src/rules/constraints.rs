@@ -0,0 +1,132 @@
+//! Forbidden dependencies from the `[[rules.forbidden_dependencies]]` config entries.
+//!
+//! Each rule selects the modules on both ends of a dependency by file path
+//! globs, module types or `[groups]` groups; every edge from a `from` module to a `to` module
+//! breaks it. Edges touching ignored modules are left out.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+use super::{matches_group, matches_module_type};
+use crate::{
+    config::{DependencyRule, ModuleSelector, RuleSettings},
+    types::{ArchitectureNode, DependencyEdge, SourceLocation},
+};
+
+/// A dependency one of the configured rules forbids
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleViolation {
+    /// The rule's name, or a description of its selectors
+    pub rule: String,
+    /// Id of the depending module
+    pub from: String,
+    /// Id of the module depended on
+    pub to: String,
+    /// Where the dependency is made, from every edge between the two modules
+    pub locations: Vec<SourceLocation>,
+}
+
+struct CompiledSelector<'a> {
+    patterns: Vec<glob::Pattern>,
+    module_types: &'a [String],
+    groups: &'a [String],
+}
+
+impl CompiledSelector<'_> {
+    fn matches(&self, node: &ArchitectureNode) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(&node.file_path))
+            || matches_module_type(self.module_types, node)
+            || matches_group(self.groups, node)
+    }
+}
+
+/// Every dependency between two modules that a configured rule forbids, once per rule
+pub fn forbidden_dependencies(
+    nodes: &HashMap<String, ArchitectureNode>,
+    edges: &[DependencyEdge],
+    settings: &RuleSettings,
+) -> Result<Vec<RuleViolation>> {
+    let mut violations = Vec::new();
+    for rule in &settings.forbidden_dependencies {
+        let (from, to) = compile(rule)?;
+        let name = rule_name(rule);
+
+        // Several edges between the same modules make one violation
+        let mut pairs: BTreeMap<(&str, &str), Vec<SourceLocation>> = BTreeMap::new();
+        for edge in edges {
+            let (Some(source), Some(target)) = (nodes.get(&edge.from), nodes.get(&edge.to)) else {
+                continue;
+            };
+            if source.ignored || target.ignored || !from.matches(source) || !to.matches(target) {
+                continue;
+            }
+            let locations = pairs.entry((edge.from.as_str(), edge.to.as_str())).or_default();
+            for location in &edge.locations {
+                if !locations.contains(location) {
+                    locations.push(location.clone());
+                }
+            }
+        }
+
+        let mut broken: Vec<RuleViolation> = pairs
+            .into_iter()
+            .map(|((from, to), locations)| RuleViolation {
+                rule: name.clone(),
+                from: from.to_string(),
+                to: to.to_string(),
+                locations,
+            })
+            .collect();
+        broken.sort_by(|a, b| {
+            (&nodes[&a.from].file_path, &nodes[&a.to].file_path).cmp(&(&nodes[&b.from].file_path, &nodes[&b.to].file_path))
+        });
+        violations.extend(broken);
+    }
+    Ok(violations)
+}
+
+fn compile(rule: &DependencyRule) -> Result<(CompiledSelector<'_>, CompiledSelector<'_>)> {
+    Ok((compile_selector(rule, "from", &rule.from)?, compile_selector(rule, "to", &rule.to)?))
+}
+
+fn compile_selector<'a>(rule: &DependencyRule, side: &str, selector: &'a ModuleSelector) -> Result<CompiledSelector<'a>> {
+    if selector.patterns.is_empty() && selector.module_types.is_empty() && selector.groups.is_empty() {
+        bail!("Rule `{}` selects no `{}` modules; give patterns, module types or groups", rule_name(rule), side);
+    }
+    let patterns = selector
+        .patterns
+        .iter()
+        .map(|pattern| {
+            glob::Pattern::new(pattern)
+                .with_context(|| format!("Invalid pattern in rule `{}`: {}", rule_name(rule), pattern))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(CompiledSelector {
+        patterns,
+        module_types: &selector.module_types,
+        groups: &selector.groups,
+    })
+}
+
+/// Name of `rule` for reports
+pub fn rule_name(rule: &DependencyRule) -> String {
+    match &rule.name {
+        Some(name) => name.clone(),
+        None => format!("{} must not depend on {}", describe(&rule.from), describe(&rule.to)),
+    }
+}
+
+fn describe(selector: &ModuleSelector) -> String {
+    let parts: Vec<String> = selector
+        .module_types
+        .iter()
+        .cloned()
+        .chain(selector.groups.iter().map(|group| format!("group {}", group)))
+        .chain(selector.patterns.iter().cloned())
+        .collect();
+    if parts.is_empty() {
+        return "no modules".to_string();
+    }
+    parts.join(" | ")
+}
@@ -0,0 +1,192 @@
+//! Strongly connected components of the module graph and the dependencies to remove to break them.
+//!
+//! Each component of two or more modules is one tangle of cycles. The edges to
+//! break are a feedback arc set: the modules are ordered with the greedy
+//! heuristic of Eades, Lin and Smyth, every edge pointing backwards in that
+//! order is removed, and removed edges are put back while that creates no
+//! cycle. The result is minimal, not necessarily minimum. Edges weigh the
+//! number of `use` statements behind them, so cheap edges are preferred.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::{
+    scanner::{dependency_analyzer::strongly_connected, ignored},
+    types::{ArchitectureMap, DependencyEdge, SourceLocation},
+};
+
+/// One strongly connected component and how to break it
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleReport {
+    /// Modules of the component, sorted by file path
+    pub modules: Vec<CycleModule>,
+    /// Dependencies between modules of the component
+    pub edges: Vec<CycleEdge>,
+    /// Dependencies whose removal leaves the component without cycles
+    pub break_edges: Vec<CycleEdge>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleModule {
+    pub id: String,
+    pub name: String,
+    pub file_path: String,
+}
+
+/// A dependency inside a component, with the statements that create it
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleEdge {
+    pub from: String,
+    pub to: String,
+    pub from_name: String,
+    pub to_name: String,
+    pub locations: Vec<SourceLocation>,
+}
+
+/// Every cycle-forming component of the counted modules, largest first
+pub fn analyze(architecture: &ArchitectureMap) -> Vec<CycleReport> {
+    let edges = ignored::counted_edges(&architecture.nodes, &architecture.edges);
+    // Parallel edges between two modules are one dependency with all their statements
+    let mut links: BTreeMap<(&str, &str), Vec<&DependencyEdge>> = BTreeMap::new();
+    for edge in edges.iter().filter(|edge| edge.from != edge.to) {
+        links.entry((edge.from.as_str(), edge.to.as_str())).or_default().push(edge);
+    }
+    let mut successors: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for &(from, to) in links.keys() {
+        successors.entry(from).or_default().push(to);
+        successors.entry(to).or_default();
+    }
+
+    let mut reports: Vec<CycleReport> = strongly_connected(&successors)
+        .into_iter()
+        .filter(|component| component.len() > 1)
+        .map(|component| {
+            let members: HashSet<&str> = component.iter().copied().collect();
+            let weighted: Vec<(&str, &str, usize)> = links
+                .iter()
+                .filter(|((from, to), _)| members.contains(from) && members.contains(to))
+                .map(|(&(from, to), parallel)| {
+                    let statements = parallel.iter().map(|edge| edge.locations.len()).sum::<usize>();
+                    (from, to, statements.max(1))
+                })
+                .collect();
+            let name_of = |id: &str| architecture.nodes.get(id).map_or_else(|| id.to_string(), |node| node.name.clone());
+
+            let mut modules: Vec<CycleModule> = component
+                .iter()
+                .map(|&id| CycleModule {
+                    id: id.to_string(),
+                    name: name_of(id),
+                    file_path: architecture.nodes.get(id).map(|node| node.file_path.clone()).unwrap_or_default(),
+                })
+                .collect();
+            modules.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+            let edge = |from: &str, to: &str| CycleEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                from_name: name_of(from),
+                to_name: name_of(to),
+                locations: links[&(from, to)].iter().flat_map(|edge| edge.locations.iter().cloned()).collect(),
+            };
+            let break_edges = feedback_arcs(&component, &weighted).into_iter().map(|(from, to)| edge(from, to)).collect();
+            let mut edges: Vec<CycleEdge> = weighted.iter().map(|&(from, to, _)| edge(from, to)).collect();
+            edges.sort_by(|a, b| (&a.from_name, &a.to_name).cmp(&(&b.from_name, &b.to_name)));
+            CycleReport { modules, edges, break_edges }
+        })
+        .collect();
+    reports.sort_by(|a, b| b.modules.len().cmp(&a.modules.len()).then_with(|| a.modules[0].file_path.cmp(&b.modules[0].file_path)));
+    reports
+}
+
+/// A minimal set of `edges` (from, to, weight) whose removal makes the component acyclic, lightest first
+fn feedback_arcs<'a>(component: &[&'a str], edges: &[(&'a str, &'a str, usize)]) -> Vec<(&'a str, &'a str)> {
+    let order = greedy_order(component, edges);
+    let position: HashMap<&str, usize> = order.iter().enumerate().map(|(position, &id)| (id, position)).collect();
+    let (mut removed, kept): (Vec<_>, Vec<_>) = edges.iter().copied().partition(|(from, to, _)| position[from] > position[to]);
+
+    let mut successors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for &(from, to, _) in &kept {
+        successors.entry(from).or_default().push(to);
+    }
+    // Heaviest first, since restoring those saves the most work
+    removed.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| (a.0, a.1).cmp(&(b.0, b.1))));
+    let mut arcs = Vec::new();
+    for (from, to, weight) in removed {
+        if reaches(&successors, to, from) {
+            arcs.push((from, to, weight));
+        } else {
+            successors.entry(from).or_default().push(to);
+        }
+    }
+    arcs.sort_by(|a, b| a.2.cmp(&b.2).then_with(|| (a.0, a.1).cmp(&(b.0, b.1))));
+    arcs.into_iter().map(|(from, to, _)| (from, to)).collect()
+}
+
+/// Eades-Lin-Smyth ordering: sinks go last, sources first, and otherwise the
+/// module with the most outgoing over incoming weight comes next
+fn greedy_order<'a>(component: &[&'a str], edges: &[(&'a str, &'a str, usize)]) -> Vec<&'a str> {
+    let mut incoming: BTreeMap<&str, usize> = component.iter().map(|&id| (id, 0)).collect();
+    let mut outgoing = incoming.clone();
+    let mut neighbours: HashMap<&str, Vec<(&str, &str, usize)>> = HashMap::new();
+    for &(from, to, weight) in edges {
+        *outgoing.get_mut(from).unwrap() += weight;
+        *incoming.get_mut(to).unwrap() += weight;
+        neighbours.entry(from).or_default().push((from, to, weight));
+        neighbours.entry(to).or_default().push((from, to, weight));
+    }
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+
+    while !incoming.is_empty() {
+        let sink = outgoing.iter().find(|(_, &weight)| weight == 0).map(|(&id, _)| id);
+        let source = || incoming.iter().find(|(_, &weight)| weight == 0).map(|(&id, _)| id);
+        let next = match sink {
+            Some(sink) => {
+                back.push(sink);
+                sink
+            }
+            None => {
+                let next = source().unwrap_or_else(|| {
+                    incoming
+                        .iter()
+                        .max_by_key(|&(&id, &weight)| (outgoing[id] as i64 - weight as i64, std::cmp::Reverse(id)))
+                        .map(|(&id, _)| id)
+                        .unwrap()
+                });
+                front.push(next);
+                next
+            }
+        };
+        incoming.remove(next);
+        outgoing.remove(next);
+        for &(from, to, weight) in neighbours.get(next).into_iter().flatten() {
+            if from == next {
+                if let Some(remaining) = incoming.get_mut(to) {
+                    *remaining -= weight;
+                }
+            } else if let Some(remaining) = outgoing.get_mut(from) {
+                *remaining -= weight;
+            }
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    front
+}
+
+/// Whether `target` can be reached from `start`
+fn reaches(successors: &HashMap<&str, Vec<&str>>, start: &str, target: &str) -> bool {
+    let mut pending = vec![start];
+    let mut seen: HashSet<&str> = HashSet::from([start]);
+    while let Some(id) = pending.pop() {
+        if id == target {
+            return true;
+        }
+        for &next in successors.get(id).into_iter().flatten() {
+            if seen.insert(next) {
+                pending.push(next);
+            }
+        }
+    }
+    false
+}
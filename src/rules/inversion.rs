@@ -0,0 +1,108 @@
+//! Suggestions for removing an edge that points up the layering.
+//!
+//! The items an upward edge imports are read from its `use` statements. A
+//! trait can move down into the lower layer; a type or function can be hidden
+//! behind a trait the lower layer owns. A module whose other neighbours allow
+//! it can instead move to the other layer.
+
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::types::{ArchitectureNode, DependencyEdge};
+
+/// How to remove the upward `edge`; `layer_of` maps node ids to layer indexes, top-down
+pub fn suggest_inversions(
+    edge: &DependencyEdge,
+    nodes: &HashMap<String, ArchitectureNode>,
+    edges: &[DependencyEdge],
+    layer_of: &HashMap<String, usize>,
+    layer_names: &[String],
+) -> Vec<String> {
+    let (Some(from), Some(to)) = (nodes.get(&edge.from), nodes.get(&edge.to)) else {
+        return Vec::new();
+    };
+    let (Some(&from_layer), Some(&to_layer)) = (layer_of.get(&from.id), layer_of.get(&to.id)) else {
+        return Vec::new();
+    };
+    let lower = &layer_names[from_layer];
+    let upper = &layer_names[to_layer];
+    let mut suggestions = Vec::new();
+
+    for item in imported_items(edge) {
+        if to.traits.iter().any(|candidate| candidate.name == item) {
+            suggestions.push(format!(
+                "Move trait `{}` from `{}` down into the `{}` layer; `{}` keeps implementing it and `{}` no longer depends upward",
+                item, to.name, lower, to.name, from.name
+            ));
+        } else if to.structs.iter().any(|candidate| candidate.name == item)
+            || to.enums.iter().any(|candidate| candidate.name == item)
+        {
+            suggestions.push(format!(
+                "Invert the dependency: declare a trait in `{}` for what it needs from `{}`, implement it for `{}` in `{}` and inject it",
+                from.name, item, item, to.name
+            ));
+        } else if to.functions.iter().any(|candidate| candidate.name == item) {
+            suggestions.push(format!(
+                "Pass `{}` into `{}` as a closure or trait object, or move it down into the `{}` layer",
+                item, from.name, lower
+            ));
+        }
+    }
+
+    // `to` can move down if nothing it depends on sits above the lower layer
+    let to_fits_lower = edges
+        .iter()
+        .filter(|other| other.from == to.id && other.to != to.id)
+        .all(|other| layer_of.get(&other.to).is_none_or(|&layer| layer >= from_layer));
+    if to_fits_lower {
+        suggestions.push(format!(
+            "Move module `{}` down into the `{}` layer; it only depends on modules at or below that layer",
+            to.name, lower
+        ));
+    }
+
+    // `from` can move up if only modules at or above the upper layer use it
+    let from_fits_upper = from
+        .dependents
+        .iter()
+        .filter(|dependent| **dependent != from.id)
+        .all(|dependent| layer_of.get(dependent).is_none_or(|&layer| layer <= to_layer));
+    if from_fits_upper {
+        suggestions.push(format!(
+            "Move module `{}` up into the `{}` layer; only modules at or above that layer depend on it",
+            from.name, upper
+        ));
+    }
+
+    if suggestions.is_empty() {
+        suggestions.push(format!(
+            "Extract what `{}` needs from `{}` into a trait in the `{}` layer and let `{}` implement it",
+            from.name, to.name, lower, to.name
+        ));
+    }
+    suggestions
+}
+
+/// Item names imported by the `use` statements behind `edge`
+fn imported_items(edge: &DependencyEdge) -> Vec<String> {
+    let identifier = Regex::new(r"\b([A-Za-z_]\w*)\b").unwrap();
+    let mut items = Vec::new();
+
+    for location in &edge.locations {
+        let Some(path) = location.snippet.trim().strip_prefix("use ").or_else(|| location.snippet.trim().strip_prefix("pub use ")) else {
+            continue;
+        };
+        // The last segment of a path, or every name inside braces
+        let names = match path.find('{') {
+            Some(brace) => &path[brace..],
+            None => path.rsplit("::").next().unwrap_or(""),
+        };
+        for captures in identifier.captures_iter(names) {
+            let name = captures[1].to_string();
+            if !matches!(name.as_str(), "self" | "super" | "crate" | "as") && !items.contains(&name) {
+                items.push(name);
+            }
+        }
+    }
+    items
+}
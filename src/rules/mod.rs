@@ -0,0 +1,118 @@
+//! Architecture rules from the `[rules]` config section.
+//!
+//! Layers are listed from the top of the architecture to the bottom, either
+//! explicitly or as an ordering of module types. A module belongs to the
+//! first layer whose patterns, module types or groups match it, and an edge from a
+//! module to one in a higher layer breaks the layering.
+
+pub mod constraints;
+pub mod cycles;
+pub mod inversion;
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+use crate::{
+    config::{LayerDefinition, RuleSettings},
+    types::{ArchitectureNode, DependencyEdge},
+};
+
+/// Colors of layers that do not set one, in layer order
+const LAYER_PALETTE: &[&str] = &["#6366f1", "#0ea5e9", "#10b981", "#f59e0b", "#ec4899", "#8b5cf6", "#14b8a6", "#ef4444"];
+
+/// Color used for the layer at `index`
+pub fn layer_color(layer: &LayerDefinition, index: usize) -> String {
+    layer
+        .color
+        .clone()
+        .unwrap_or_else(|| LAYER_PALETTE[index % LAYER_PALETTE.len()].to_string())
+}
+
+/// Whether `names` lists the module's type, by variant or display name and ignoring case
+pub(crate) fn matches_module_type(names: &[String], node: &ArchitectureNode) -> bool {
+    if names.is_empty() {
+        return false;
+    }
+    let module_type = format!("{:?}", node.module_type);
+    let display_name = node.module_type.display_name();
+    names
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(&module_type) || name.eq_ignore_ascii_case(&display_name))
+}
+
+/// Whether the module is in one of the `[groups]` groups `names` lists
+pub(crate) fn matches_group(names: &[String], node: &ArchitectureNode) -> bool {
+    node.groups.iter().any(|group| names.contains(group))
+}
+
+/// Assign every module its layer and flag the edges that point up the layering
+pub fn apply_layers(
+    nodes: &mut HashMap<String, ArchitectureNode>,
+    edges: &mut [DependencyEdge],
+    settings: &RuleSettings,
+) -> Result<()> {
+    let layers = settings.effective_layers();
+    if layers.is_empty() {
+        // Nothing to assign, but a previous assignment may have to go
+        for node in nodes.values_mut() {
+            node.layer = None;
+        }
+        for edge in edges.iter_mut() {
+            edge.layer_violation = false;
+            edge.suggestions.clear();
+        }
+        return Ok(());
+    }
+
+    let patterns = layers
+        .iter()
+        .map(|layer| {
+            layer
+                .patterns
+                .iter()
+                .map(|pattern| {
+                    glob::Pattern::new(pattern)
+                        .with_context(|| format!("Invalid pattern in layer {}: {}", layer.name, pattern))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut layer_of: HashMap<String, usize> = HashMap::new();
+    for node in nodes.values_mut() {
+        let index = layers.iter().zip(&patterns).position(|(layer, patterns)| {
+            patterns.iter().any(|pattern| pattern.matches(&node.file_path))
+                || matches_module_type(&layer.module_types, node)
+                || matches_group(&layer.groups, node)
+        });
+        node.layer = index.map(|index| layers[index].name.clone());
+        if let Some(index) = index {
+            layer_of.insert(node.id.clone(), index);
+        }
+    }
+
+    for edge in edges.iter_mut() {
+        // Layers are listed top-down, so a lower layer has a larger index
+        edge.layer_violation = matches!(
+            (layer_of.get(&edge.from), layer_of.get(&edge.to)),
+            (Some(from), Some(to)) if from > to
+        );
+    }
+
+    let layer_names: Vec<String> = layers.iter().map(|layer| layer.name.clone()).collect();
+    let suggestions: Vec<Vec<String>> = edges
+        .iter()
+        .map(|edge| {
+            if edge.layer_violation {
+                inversion::suggest_inversions(edge, nodes, edges, &layer_of, &layer_names)
+            } else {
+                Vec::new()
+            }
+        })
+        .collect();
+    for (edge, suggestions) in edges.iter_mut().zip(suggestions) {
+        edge.suggestions = suggestions;
+    }
+
+    Ok(())
+}
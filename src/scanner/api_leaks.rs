@@ -0,0 +1,286 @@
+//! Public APIs that expose types of another crate of the workspace.
+//!
+//! A library's public signatures (functions and methods, fields of public
+//! structs, enum variants, trait methods, type aliases, constants and statics)
+//! should name its own types, std and external crates. A type from a sibling
+//! crate in one of them ties the library's users to that crate as well. Paths
+//! are resolved through the file's top-level `use` declarations; a type the
+//! file also re-exports with `pub use` is taken as a deliberate part of the API.
+
+use proc_macro2::Span;
+use quote::ToTokens;
+use std::collections::{HashMap, HashSet};
+use syn::{
+    spanned::Spanned,
+    visit::{self, Visit},
+    Fields, ImplItem, Item, TraitItem, UseTree, Visibility,
+};
+
+use crate::{
+    scanner::reachability,
+    types::{ApiLeak, ArchitectureNode, EntryPoint, PublicSignature},
+};
+
+/// Signatures of the public items of `content`; empty when it does not parse
+pub fn collect(content: &str) -> Vec<PublicSignature> {
+    let Ok(file) = syn::parse_file(content) else {
+        return Vec::new();
+    };
+    let mut imports = Imports::default();
+    for item in &file.items {
+        match item {
+            Item::Use(item) => imports.add(&item.tree, Vec::new(), is_public(&item.vis)),
+            Item::Mod(module) => {
+                imports.local.insert(module.ident.to_string());
+            }
+            _ => {}
+        }
+    }
+    let mut signatures = Vec::new();
+    collect_items(&file.items, &imports, &mut signatures);
+    signatures
+}
+
+/// What the names a file imports stand for
+#[derive(Default)]
+struct Imports {
+    /// Name in scope -> full path, and whether the file re-exports it
+    paths: HashMap<String, (Vec<String>, bool)>,
+    /// Child modules, which shadow crates of the same name
+    local: HashSet<String>,
+}
+
+impl Imports {
+    fn add(&mut self, tree: &UseTree, mut prefix: Vec<String>, public: bool) {
+        match tree {
+            UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.add(&path.tree, prefix, public);
+            }
+            UseTree::Name(name) => {
+                let ident = name.ident.to_string();
+                if ident == "self" {
+                    if let Some(last) = prefix.last().cloned() {
+                        self.paths.insert(last, (prefix, public));
+                    }
+                } else {
+                    prefix.push(ident.clone());
+                    self.paths.insert(ident, (prefix, public));
+                }
+            }
+            UseTree::Rename(rename) => {
+                prefix.push(rename.ident.to_string());
+                self.paths.insert(rename.rename.to_string(), (prefix, public));
+            }
+            UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.add(tree, prefix.clone(), public);
+                }
+            }
+            UseTree::Glob(_) => {}
+        }
+    }
+
+    /// `segments` with its first one replaced by what it was imported as; `None` for the
+    /// file's own modules and for names it re-exports
+    fn resolve(&self, segments: &[String]) -> Option<String> {
+        let (first, rest) = segments.split_first()?;
+        if self.local.contains(first) {
+            return None;
+        }
+        let mut path = match self.paths.get(first) {
+            Some((_, true)) => return None,
+            Some((imported, false)) => imported.clone(),
+            None => vec![first.clone()],
+        };
+        path.extend(rest.iter().cloned());
+        Some(path.join("::"))
+    }
+}
+
+fn collect_items(items: &[Item], imports: &Imports, signatures: &mut Vec<PublicSignature>) {
+    let mut add = |item: String, signature: String, span: Span, visit: &dyn Fn(&mut PathCollector)| {
+        let mut paths = PathCollector::default();
+        visit(&mut paths);
+        signatures.push(PublicSignature {
+            item,
+            signature: tidy(&signature),
+            line: span.start().line,
+            paths: paths.0.iter().filter_map(|segments| imports.resolve(segments)).collect(),
+        });
+    };
+    let mut modules = Vec::new();
+
+    for item in items {
+        match item {
+            Item::Fn(item) if is_public(&item.vis) => add(
+                format!("fn {}", item.sig.ident),
+                item.sig.to_token_stream().to_string(),
+                item.sig.ident.span(),
+                &|paths| paths.visit_signature(&item.sig),
+            ),
+            Item::Struct(item) if is_public(&item.vis) => {
+                for (index, field) in item.fields.iter().enumerate().filter(|(_, field)| is_public(&field.vis)) {
+                    let name = field.ident.as_ref().map(ToString::to_string).unwrap_or_else(|| index.to_string());
+                    add(
+                        format!("struct {}", item.ident),
+                        format!("{}.{}: {}", item.ident, name, field.ty.to_token_stream()),
+                        field.span(),
+                        &|paths| paths.visit_type(&field.ty),
+                    );
+                }
+            }
+            Item::Enum(item) if is_public(&item.vis) => {
+                for variant in item.variants.iter().filter(|variant| !matches!(variant.fields, Fields::Unit)) {
+                    add(
+                        format!("enum {}", item.ident),
+                        format!("{}::{}", item.ident, variant.to_token_stream()),
+                        variant.ident.span(),
+                        &|paths| paths.visit_variant(variant),
+                    );
+                }
+            }
+            Item::Trait(item) if is_public(&item.vis) => {
+                for trait_item in &item.items {
+                    if let TraitItem::Fn(method) = trait_item {
+                        add(
+                            format!("trait {}", item.ident),
+                            method.sig.to_token_stream().to_string(),
+                            method.sig.ident.span(),
+                            &|paths| paths.visit_signature(&method.sig),
+                        );
+                    }
+                }
+            }
+            Item::Type(item) if is_public(&item.vis) => add(
+                format!("type {}", item.ident),
+                format!("type {} = {}", item.ident, item.ty.to_token_stream()),
+                item.ident.span(),
+                &|paths| paths.visit_type(&item.ty),
+            ),
+            Item::Const(item) if is_public(&item.vis) => add(
+                format!("const {}", item.ident),
+                format!("const {}: {}", item.ident, item.ty.to_token_stream()),
+                item.ident.span(),
+                &|paths| paths.visit_type(&item.ty),
+            ),
+            Item::Static(item) if is_public(&item.vis) => add(
+                format!("static {}", item.ident),
+                format!("static {}: {}", item.ident, item.ty.to_token_stream()),
+                item.ident.span(),
+                &|paths| paths.visit_type(&item.ty),
+            ),
+            // Trait impls are part of the trait's API, not the type's
+            Item::Impl(item) if item.trait_.is_none() => {
+                let type_name = tidy(&item.self_ty.to_token_stream().to_string());
+                for impl_item in &item.items {
+                    if let ImplItem::Fn(method) = impl_item {
+                        if is_public(&method.vis) {
+                            add(
+                                format!("{}::{}", type_name, method.sig.ident),
+                                method.sig.to_token_stream().to_string(),
+                                method.sig.ident.span(),
+                                &|paths| paths.visit_signature(&method.sig),
+                            );
+                        }
+                    }
+                }
+            }
+            Item::Mod(module) if is_public(&module.vis) => {
+                if let Some((_, items)) = &module.content {
+                    modules.push(items);
+                }
+            }
+            _ => {}
+        }
+    }
+    for items in modules {
+        collect_items(items, imports, signatures);
+    }
+}
+
+/// Every path in a signature, as its segments
+#[derive(Default)]
+struct PathCollector(Vec<Vec<String>>);
+
+impl<'ast> Visit<'ast> for PathCollector {
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        self.0.push(path.segments.iter().map(|segment| segment.ident.to_string()).collect());
+        visit::visit_path(self, path);
+    }
+
+    // Argument names and patterns are not part of the API
+    fn visit_pat(&mut self, _: &'ast syn::Pat) {}
+}
+
+/// Record on every module of a library's public API the signatures that name a
+/// type of another library crate of the workspace
+pub fn detect(nodes: &mut HashMap<String, ArchitectureNode>) {
+    // Crates are named by their library identifier in paths
+    let libraries: HashSet<String> = nodes
+        .values()
+        .filter(|node| node.entry_point == Some(EntryPoint::Library))
+        .filter_map(|node| node.crate_name.as_deref())
+        .map(|name| name.replace('-', "_"))
+        .collect();
+    let public_api: HashSet<String> = reachability::public_api(nodes).into_iter().map(str::to_string).collect();
+
+    for node in nodes.values_mut() {
+        let own_crate = node.crate_name.as_deref().map(|name| name.replace('-', "_"));
+        node.api_leaks = if public_api.contains(&node.id) && !node.ignored {
+            node.public_signatures
+                .iter()
+                .flat_map(|signature| {
+                    signature
+                        .paths
+                        .iter()
+                        .filter_map(|path| {
+                            let crate_name = path.split("::").next()?;
+                            (path.contains("::")
+                                && libraries.contains(crate_name)
+                                && own_crate.as_deref() != Some(crate_name))
+                            .then(|| ApiLeak {
+                                item: signature.item.clone(),
+                                signature: signature.signature.clone(),
+                                crate_name: crate_name.to_string(),
+                                type_path: path.clone(),
+                                line: signature.line,
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+    }
+}
+
+/// Token text with the spacing `to_string` puts around punctuation removed
+fn tidy(tokens: &str) -> String {
+    let mut text = tokens.to_string();
+    for (from, to) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" . ", "."),
+        ("& ", "&"),
+        (" ,", ","),
+        ("( ", "("),
+        (" )", ")"),
+        (" (", "("),
+        ("< ", "<"),
+        (" <", "<"),
+        (" >", ">"),
+        (" :", ":"),
+        ("[ ", "["),
+        (" ]", "]"),
+        (" ;", ";"),
+    ] {
+        text = text.replace(from, to);
+    }
+    text
+}
+
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
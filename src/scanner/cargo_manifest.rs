@@ -0,0 +1,300 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::{scanner::source_tree::SourceTree, types::EntryPoint};
+
+/// A crate discovered from a `Cargo.toml` with a `[package]` section
+#[derive(Debug, Clone)]
+pub struct CrateInfo {
+    pub name: String,
+    /// Directory containing the crate's `Cargo.toml`
+    pub root: PathBuf,
+    /// The `[features]` table: feature name -> what it enables
+    pub features: BTreeMap<String, Vec<String>>,
+    /// Dependencies declared with `optional = true`
+    pub optional_dependencies: Vec<String>,
+    /// Every dependency the manifest declares, including dev, build and target-specific ones
+    pub dependencies: Vec<String>,
+    pub version: Option<String>,
+    /// Rust edition; Cargo defaults to 2015 when it is not set
+    pub edition: String,
+    /// The `rust-version` (MSRV) the crate declares
+    pub rust_version: Option<String>,
+    /// False for packages under a workspace root that its `members` leave out,
+    /// such as test fixtures or vendored crates
+    pub workspace_member: bool,
+    /// Files the library and binary targets are compiled from
+    pub entry_points: Vec<(PathBuf, EntryPoint)>,
+}
+
+/// Kind of cargo target a source file belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    /// `src/`
+    Source,
+    /// `tests/`
+    IntegrationTest,
+    /// `benches/`
+    Bench,
+    /// `examples/`
+    Example,
+    Other,
+}
+
+/// Subset of `Cargo.toml` the scanner cares about
+#[derive(Debug, Deserialize)]
+struct CargoManifest {
+    package: Option<ManifestPackage>,
+    workspace: Option<ManifestWorkspace>,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, rename = "build-dependencies")]
+    build_dependencies: BTreeMap<String, toml::Value>,
+    /// `[target.'cfg(...)'.dependencies]` and friends
+    #[serde(default)]
+    target: BTreeMap<String, CargoManifestTarget>,
+    lib: Option<ManifestBuildTarget>,
+    #[serde(default)]
+    bin: Vec<ManifestBuildTarget>,
+}
+
+/// A `[lib]` or `[[bin]]` table
+#[derive(Debug, Deserialize)]
+struct ManifestBuildTarget {
+    path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CargoManifestTarget {
+    #[serde(default)]
+    dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, rename = "dev-dependencies")]
+    dev_dependencies: BTreeMap<String, toml::Value>,
+    #[serde(default, rename = "build-dependencies")]
+    build_dependencies: BTreeMap<String, toml::Value>,
+}
+
+impl CargoManifest {
+    /// Every declared dependency by the name code refers to it with, renamed ones by their new name
+    fn dependency_names(&self) -> Vec<String> {
+        let tables = [&self.dependencies, &self.dev_dependencies, &self.build_dependencies].into_iter().chain(
+            self.target
+                .values()
+                .flat_map(|target| [&target.dependencies, &target.dev_dependencies, &target.build_dependencies]),
+        );
+        let names: BTreeSet<&String> = tables.flat_map(BTreeMap::keys).collect();
+        names.into_iter().cloned().collect()
+    }
+
+    /// Existing source files of the library and binary targets, declared or found by Cargo's conventions
+    fn entry_points(&self, root: &Path, tree: SourceTree) -> Vec<(PathBuf, EntryPoint)> {
+        let library = self
+            .lib
+            .as_ref()
+            .and_then(|lib| lib.path.as_deref())
+            .map_or_else(|| root.join("src").join("lib.rs"), |path| root.join(path));
+        let mut binaries: BTreeSet<PathBuf> = self.bin.iter().filter_map(|bin| bin.path.as_deref()).map(|path| root.join(path)).collect();
+        binaries.insert(root.join("src").join("main.rs"));
+        // `src/bin/name.rs` and `src/bin/name/main.rs`
+        for (path, is_dir) in tree.entries(&root.join("src").join("bin")) {
+            binaries.insert(if is_dir { path.join("main.rs") } else { path });
+        }
+
+        std::iter::once((library, EntryPoint::Library))
+            .chain(binaries.into_iter().map(|path| (path, EntryPoint::Binary)))
+            .filter(|(path, _)| tree.is_file(path) && path.extension().is_some_and(|extension| extension == "rs"))
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ManifestPackage {
+    name: String,
+    version: Option<toml::Value>,
+    edition: Option<toml::Value>,
+    rust_version: Option<toml::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestWorkspace {
+    /// Globs of member directories, relative to the workspace root
+    #[serde(default)]
+    members: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    /// Values members inherit with `field.workspace = true`
+    #[serde(default)]
+    package: BTreeMap<String, toml::Value>,
+}
+
+impl ManifestWorkspace {
+    /// Whether the package at `root` belongs to the workspace rooted at `workspace_root`.
+    ///
+    /// The root package always does; without `members` every package below counts.
+    fn includes(&self, workspace_root: &Path, root: &Path) -> bool {
+        let Ok(relative) = root.strip_prefix(workspace_root) else {
+            return false;
+        };
+        if relative.as_os_str().is_empty() {
+            return true;
+        }
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let matches = |patterns: &[String]| {
+            patterns.iter().any(|pattern| {
+                glob::Pattern::new(pattern.trim_end_matches('/')).is_ok_and(|pattern| pattern.matches(&relative))
+            })
+        };
+        (self.members.is_empty() || matches(&self.members)) && !matches(&self.exclude)
+    }
+}
+
+/// A package field, or the workspace's value when the field is inherited
+fn package_field(
+    value: Option<&toml::Value>,
+    key: &str,
+    workspace: Option<&BTreeMap<String, toml::Value>>,
+) -> Option<String> {
+    let inherited = value
+        .and_then(|value| value.get("workspace"))
+        .and_then(toml::Value::as_bool)
+        == Some(true);
+    let value = if inherited {
+        workspace.and_then(|package| package.get(key))
+    } else {
+        value
+    };
+    value.and_then(toml::Value::as_str).map(str::to_string)
+}
+
+/// Find every crate under `project_path`, skipping build output and hidden directories
+pub fn discover_crates(project_path: &Path, tree: SourceTree) -> Result<Vec<CrateInfo>> {
+    let mut crates = Vec::new();
+    let mut packages = Vec::new();
+    // Workspace roots with their `[workspace]` tables
+    let mut workspaces: Vec<(PathBuf, ManifestWorkspace)> = Vec::new();
+
+    for manifest_path in manifest_paths(project_path, tree) {
+        let manifest_path = manifest_path.as_path();
+        match read_manifest(manifest_path, tree) {
+            Ok(manifest) => {
+                let dependencies = manifest.dependency_names();
+                let root = manifest_path.parent().unwrap_or(project_path).to_path_buf();
+                let entry_points = manifest.entry_points(&root, tree);
+                if let Some(workspace) = manifest.workspace {
+                    workspaces.push((root.clone(), workspace));
+                }
+                if let Some(package) = manifest.package {
+                    let optional_dependencies = manifest
+                        .dependencies
+                        .iter()
+                        .filter(|(_, spec)| spec.get("optional").and_then(toml::Value::as_bool) == Some(true))
+                        .map(|(name, _)| name.clone())
+                        .collect();
+                    packages.push((root, package, manifest.features, optional_dependencies, dependencies, entry_points));
+                }
+            }
+            Err(e) => tracing::warn!("Skipping unreadable manifest {}: {:#}", manifest_path.display(), e),
+        }
+    }
+
+    // Members inherit from the nearest workspace root above them
+    workspaces.sort_by_key(|(root, _)| std::cmp::Reverse(root.components().count()));
+    for (root, package, features, optional_dependencies, dependencies, entry_points) in packages {
+        let workspace = workspaces.iter().find(|(workspace_root, _)| root.starts_with(workspace_root));
+        let workspace_member = workspace.is_none_or(|(workspace_root, workspace)| workspace.includes(workspace_root, &root));
+        let workspace = workspace.map(|(_, workspace)| &workspace.package);
+        crates.push(CrateInfo {
+            version: package_field(package.version.as_ref(), "version", workspace),
+            edition: package_field(package.edition.as_ref(), "edition", workspace).unwrap_or_else(|| "2015".to_string()),
+            rust_version: package_field(package.rust_version.as_ref(), "rust-version", workspace),
+            name: package.name,
+            root,
+            features,
+            optional_dependencies,
+            dependencies,
+            workspace_member,
+            entry_points,
+        });
+    }
+
+    // Deepest roots first so nested crates win over their parents
+    crates.sort_by_key(|info| std::cmp::Reverse(info.root.components().count()));
+    Ok(crates)
+}
+
+/// The crate that owns `file_path`, i.e. the one with the deepest root containing it
+pub fn owning_crate<'a>(crates: &'a [CrateInfo], file_path: &Path) -> Option<&'a CrateInfo> {
+    crates.iter().find(|info| file_path.starts_with(&info.root))
+}
+
+/// Whether `file_path` is the `src/lib.rs` or `src/main.rs` the crate is compiled from
+pub fn is_crate_root(info: &CrateInfo, file_path: &Path) -> bool {
+    ["lib.rs", "main.rs"].iter().any(|root| file_path == info.root.join("src").join(root))
+}
+
+/// Kind of target `file_path` is the root of, if it is one
+pub fn entry_point(info: &CrateInfo, file_path: &Path) -> Option<EntryPoint> {
+    info.entry_points.iter().find(|(path, _)| path == file_path).map(|(_, kind)| *kind)
+}
+
+/// Every `Cargo.toml` under `project_path`, outside build output and hidden directories
+fn manifest_paths(project_path: &Path, tree: SourceTree) -> Vec<PathBuf> {
+    let skipped = |name: &str| name == "target" || name == "node_modules" || name.starts_with('.');
+    match tree {
+        SourceTree::Disk => WalkDir::new(project_path)
+            .into_iter()
+            .filter_entry(|entry| entry.depth() == 0 || !(entry.file_type().is_dir() && skipped(&entry.file_name().to_string_lossy())))
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.file_name() == "Cargo.toml")
+            .map(|entry| entry.into_path())
+            .collect(),
+        SourceTree::Memory(files) => files
+            .keys()
+            .filter(|path| path.file_name().is_some_and(|name| name == "Cargo.toml"))
+            .filter(|path| {
+                let dirs = path.parent().and_then(|dir| dir.strip_prefix(project_path).ok());
+                dirs.is_some_and(|dirs| !dirs.iter().any(|name| skipped(&name.to_string_lossy())))
+            })
+            .cloned()
+            .collect(),
+    }
+}
+
+fn read_manifest(path: &Path, tree: SourceTree) -> Result<CargoManifest> {
+    let content = tree.read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Classify `file_path` by the directory it sits in under its crate root.
+///
+/// The crate root is the nearest ancestor with a `Cargo.toml`, or `project_root`.
+pub fn target_kind(project_root: &Path, file_path: &Path, tree: SourceTree) -> TargetKind {
+    for dir in file_path.ancestors().skip(1) {
+        if dir == project_root || tree.is_file(&dir.join("Cargo.toml")) {
+            let first = file_path
+                .strip_prefix(dir)
+                .ok()
+                .and_then(|relative| relative.components().next())
+                .and_then(|component| component.as_os_str().to_str());
+            return match first {
+                Some("src") => TargetKind::Source,
+                Some("tests") => TargetKind::IntegrationTest,
+                Some("benches") => TargetKind::Bench,
+                Some("examples") => TargetKind::Example,
+                _ => TargetKind::Other,
+            };
+        }
+        if !dir.starts_with(project_root) {
+            break;
+        }
+    }
+    TargetKind::Other
+}
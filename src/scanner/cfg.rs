@@ -0,0 +1,208 @@
+//! `cfg` attributes that decide whether a module is compiled, and their evaluation.
+//!
+//! A module exists when the predicates on its `mod` declaration, its own
+//! `#![cfg]` attributes and those of every module above it hold. Predicates
+//! are kept as written and evaluated per variant; an option whose value is
+//! not known, such as `unix` when no target is given, leaves the module in.
+
+use regex::Regex;
+use std::collections::HashMap;
+
+use crate::types::{ArchitectureNode, CfgGates};
+
+/// The `#![cfg]` predicates of a file and the `#[cfg]` predicates on each of its `mod name;` declarations
+pub fn collect(content: &str) -> CfgGates {
+    let inner = Regex::new(r"(?m)^\s*#!\[cfg\((.*)\)\]").unwrap();
+    let declaration = Regex::new(r"^(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;").unwrap();
+    let outer = Regex::new(r"^#\[cfg\((.*)\)\]$").unwrap();
+
+    let own = inner.captures_iter(content).map(|captures| captures[1].trim().to_string()).collect();
+    let lines: Vec<&str> = content.lines().map(str::trim).collect();
+    let mut children: HashMap<String, Vec<String>> = HashMap::new();
+    for (index, line) in lines.iter().enumerate() {
+        let Some(captures) = declaration.captures(line) else {
+            continue;
+        };
+        // Attributes and doc comments stacked right above the declaration
+        let predicates: Vec<String> = lines[..index]
+            .iter()
+            .rev()
+            .take_while(|line| line.starts_with("#[") || line.starts_with("///"))
+            .filter_map(|line| outer.captures(line).map(|captures| captures[1].trim().to_string()))
+            .collect();
+        if !predicates.is_empty() {
+            children.entry(captures[1].to_string()).or_default().extend(predicates);
+        }
+    }
+    CfgGates { own, children }
+}
+
+/// Give every module the predicates it is compiled under, its parents' included
+pub fn assign(nodes: &mut HashMap<String, ArchitectureNode>) {
+    let mut by_path: Vec<(String, String, String)> = nodes
+        .values()
+        .map(|node| (node.module_root.clone(), node.module_path.clone(), node.id.clone()))
+        .collect();
+    // Parents come before their children
+    by_path.sort_by_key(|(_, module_path, _)| module_path.matches("::").count() + usize::from(!module_path.is_empty()));
+    let ids: HashMap<(&str, &str), &str> = by_path
+        .iter()
+        .map(|(root, module_path, id)| ((root.as_str(), module_path.as_str()), id.as_str()))
+        .collect();
+
+    for (root, module_path, id) in &by_path {
+        let mut predicates = Vec::new();
+        if !module_path.is_empty() && !root.is_empty() {
+            let (parent_path, name) = module_path.rsplit_once("::").unwrap_or(("", module_path.as_str()));
+            if let Some(parent) = ids.get(&(root.as_str(), parent_path)).and_then(|parent| nodes.get(*parent)) {
+                predicates.extend(parent.cfg.iter().cloned());
+                predicates.extend(parent.cfg_gates.children.get(name).into_iter().flatten().cloned());
+            }
+        }
+        if let Some(node) = nodes.get_mut(id) {
+            predicates.extend(node.cfg_gates.own.iter().cloned());
+            predicates.dedup();
+            node.cfg = predicates;
+        }
+    }
+}
+
+/// The options set in one build configuration
+pub trait CfgOptions {
+    /// Whether a bare option such as `unix` or `test` is set; `None` when unknown
+    fn is_set(&self, name: &str) -> Option<bool>;
+    /// Whether a `key = "value"` option such as `feature = "serde"` is set; `None` when unknown
+    fn has_value(&self, key: &str, value: &str) -> Option<bool>;
+}
+
+/// Whether `predicate` holds, or `None` when it depends on options whose value is unknown.
+///
+/// Predicates that do not parse are unknown too.
+pub fn evaluate(predicate: &str, options: &impl CfgOptions) -> Option<bool> {
+    let tokens = tokenize(predicate)?;
+    let mut parser = Parser { tokens: &tokens, position: 0 };
+    let expression = parser.expression()?;
+    (parser.position == tokens.len()).then_some(())?;
+    expression.evaluate(options)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Open,
+    Close,
+    Comma,
+    Equals,
+}
+
+fn tokenize(predicate: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = predicate.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '(' | ')' | ',' | '=' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    ',' => Token::Comma,
+                    _ => Token::Equals,
+                });
+            }
+            '"' => {
+                chars.next();
+                let value: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                tokens.push(Token::Str(value));
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    ident.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Ident(ident));
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+enum Expression {
+    Option(String),
+    Value(String, String),
+    All(Vec<Expression>),
+    Any(Vec<Expression>),
+    Not(Box<Expression>),
+}
+
+impl Expression {
+    fn evaluate(&self, options: &impl CfgOptions) -> Option<bool> {
+        match self {
+            Expression::Option(name) => options.is_set(name),
+            Expression::Value(key, value) => options.has_value(key, value),
+            Expression::Not(inner) => inner.evaluate(options).map(|value| !value),
+            // False wins over unknown in `all`, true in `any`
+            Expression::All(list) => list.iter().try_fold(Some(true), |result, item| match item.evaluate(options) {
+                Some(false) => Err(()),
+                value => Ok(result.and(value)),
+            }).unwrap_or(Some(false)),
+            Expression::Any(list) => list.iter().try_fold(Some(false), |result, item| match item.evaluate(options) {
+                Some(true) => Err(()),
+                value => Ok(result.and(value)),
+            }).unwrap_or(Some(true)),
+        }
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position)?;
+        self.position += 1;
+        Some(token)
+    }
+
+    fn expression(&mut self) -> Option<Expression> {
+        let Token::Ident(name) = self.next()?.clone() else {
+            return None;
+        };
+        match self.tokens.get(self.position) {
+            Some(Token::Equals) => {
+                self.position += 1;
+                let Token::Str(value) = self.next()?.clone() else {
+                    return None;
+                };
+                Some(Expression::Value(name, value))
+            }
+            Some(Token::Open) if matches!(name.as_str(), "all" | "any" | "not") => {
+                self.position += 1;
+                let mut list = Vec::new();
+                while self.tokens.get(self.position) != Some(&Token::Close) {
+                    list.push(self.expression()?);
+                    match self.tokens.get(self.position) {
+                        Some(Token::Comma) => self.position += 1,
+                        Some(Token::Close) => {}
+                        _ => return None,
+                    }
+                }
+                self.position += 1;
+                match name.as_str() {
+                    "all" => Some(Expression::All(list)),
+                    "any" => Some(Expression::Any(list)),
+                    _ if list.len() == 1 => list.pop().map(|inner| Expression::Not(Box::new(inner))),
+                    _ => None,
+                }
+            }
+            _ => Some(Expression::Option(name)),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Module types assigned by hand through `[classification]` rules.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+use crate::{config::ClassificationSettings, types::ArchitectureNode};
+
+/// Give every module matched by a `[classification]` rule the rule's type
+pub fn apply_rules(nodes: &mut HashMap<String, ArchitectureNode>, settings: &ClassificationSettings) -> Result<()> {
+    let patterns = compile(settings)?;
+    if patterns.is_empty() {
+        return Ok(());
+    }
+
+    for node in nodes.values_mut() {
+        if let Some((_, rule)) = patterns.iter().find(|(pattern, _)| pattern.matches(&node.file_path)) {
+            node.module_type = settings.rules[*rule].module_type.clone();
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that every rule's pattern is a valid glob
+pub fn validate(settings: &ClassificationSettings) -> Result<()> {
+    compile(settings).map(|_| ())
+}
+
+fn compile(settings: &ClassificationSettings) -> Result<Vec<(glob::Pattern, usize)>> {
+    settings
+        .rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            glob::Pattern::new(&rule.pattern)
+                .map(|pattern| (pattern, index))
+                .with_context(|| format!("Invalid classification pattern: {}", rule.pattern))
+        })
+        .collect()
+}
@@ -0,0 +1,78 @@
+//! Clippy lint counts per module, by lint category.
+//!
+//! Clippy's JSON output names each lint but not its category, so `cargo clippy`
+//! runs once per configured category with every other clippy lint allowed.
+//! A lint a source attribute such as `#![warn(clippy::pedantic)]` switches on
+//! shows up in every run; its category is then unknown and it counts as
+//! `other`. Lints are attributed to the module holding their primary span.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+use std::process::Command;
+
+use crate::{
+    config::ClippySettings,
+    scanner::diagnostics::{self, SourceRoots},
+    types::ArchitectureNode,
+};
+
+/// Category of lints whose category the runs could not tell apart
+const OTHER_CATEGORY: &str = "other";
+
+/// Lint counts by category for each file under `project_path`; `None` when clippy could not be run
+pub fn lint_counts(project_path: &Path, settings: &ClippySettings) -> Option<HashMap<String, BTreeMap<String, usize>>> {
+    let roots = SourceRoots::new(project_path);
+    // Every lint occurrence, with the categories whose run reported it
+    let mut occurrences: BTreeMap<(String, String, usize), BTreeSet<&str>> = BTreeMap::new();
+    for category in &settings.categories {
+        let output = Command::new("cargo")
+            .current_dir(project_path)
+            .args(["clippy", "--message-format=json"])
+            .args(&settings.args)
+            .args(["--", "-A", "clippy::all", "-W"])
+            .arg(format!("clippy::{}", category))
+            .output();
+        let output = match output {
+            Ok(output) if output.status.success() || !output.stdout.is_empty() => output,
+            Ok(output) => {
+                tracing::warn!("cargo clippy failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+                return None;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to run cargo clippy: {}", e);
+                return None;
+            }
+        };
+        for message in diagnostics::compiler_messages(&String::from_utf8_lossy(&output.stdout)) {
+            let Some(lint) = message.code.as_ref().and_then(|code| code.code.strip_prefix("clippy::")) else {
+                continue;
+            };
+            if let Some((file, line)) = message.location(&roots) {
+                occurrences.entry((lint.to_string(), file, line)).or_default().insert(category);
+            }
+        }
+    }
+
+    let mut counts: HashMap<String, BTreeMap<String, usize>> = HashMap::new();
+    for ((_, file, _), categories) in occurrences {
+        let category = match categories.len() {
+            1 => categories.into_iter().next().unwrap_or(OTHER_CATEGORY),
+            _ => OTHER_CATEGORY,
+        };
+        *counts.entry(file).or_default().entry(category.to_string()).or_default() += 1;
+    }
+    Some(counts)
+}
+
+/// Set the lint counts of every module; modules clippy found nothing in get empty counts
+pub fn apply(nodes: &mut HashMap<String, ArchitectureNode>, counts: &HashMap<String, BTreeMap<String, usize>>) {
+    for node in nodes.values_mut() {
+        node.metrics.clippy_lints = Some(counts.get(&node.file_path).cloned().unwrap_or_default());
+    }
+}
+
+/// Lints per 100 lines of code, or `None` when clippy did not run on the module
+pub fn lint_density(node: &ArchitectureNode) -> Option<f64> {
+    let lints: usize = node.metrics.clippy_lints.as_ref()?.values().sum();
+    Some(lints as f64 * 100.0 / node.metrics.lines_of_code.max(1) as f64)
+}
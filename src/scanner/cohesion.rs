@@ -0,0 +1,146 @@
+//! Internal cohesion of a module, used to suggest where a large module could be split.
+//!
+//! Every top-level item (function, type, trait, constant) is a vertex; `impl`
+//! blocks belong to the type they implement. Two items are linked when the
+//! text of one names the other. A module whose item graph falls apart into two
+//! sizeable groups, or into two groups joined by a single weak link, is a
+//! candidate for a split.
+
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use crate::types::SplitSuggestion;
+
+/// Fewest items on each side of a suggested split
+const MIN_PART_SIZE: usize = 3;
+
+/// Most references allowed across a split of an otherwise connected module
+const MAX_CUT_WEIGHT: usize = 2;
+
+/// Suggest moving a weakly connected group of items out of the module, if it has one
+pub fn suggest_split(content: &str) -> Option<SplitSuggestion> {
+    let items = top_level_items(content);
+    if items.len() < MIN_PART_SIZE * 2 {
+        return None;
+    }
+    let names: Vec<&String> = items.keys().collect();
+    let weights = reference_weights(&items, &names);
+
+    let neighbours: Vec<Vec<usize>> = weights
+        .iter()
+        .map(|row| (0..row.len()).filter(|&other| row[other] > 0).collect())
+        .collect();
+
+    let mut groups = components(&neighbours, None);
+    groups.sort_by_key(|group| std::cmp::Reverse(group.len()));
+    if groups.len() >= 2 && groups[1].len() >= MIN_PART_SIZE {
+        return Some(suggestion(&names, &groups[0], &groups[1], 0));
+    }
+
+    // The largest group is connected: look for the weak link whose removal
+    // balances its halves best. Items outside it stand alone and are left be.
+    let main: HashSet<usize> = groups[0].iter().copied().collect();
+    let mut best: Option<(usize, usize, Vec<usize>, Vec<usize>)> = None;
+    for &a in &groups[0] {
+        for &b in neighbours[a].iter().filter(|&&b| b > a) {
+            let weight = weights[a][b];
+            if weight > MAX_CUT_WEIGHT {
+                continue;
+            }
+            let mut halves: Vec<Vec<usize>> = components(&neighbours, Some((a, b)))
+                .into_iter()
+                .filter(|half| main.contains(&half[0]))
+                .collect();
+            if halves.len() != 2 {
+                continue;
+            }
+            halves.sort_by_key(|half| std::cmp::Reverse(half.len()));
+            let smaller = halves[1].len();
+            if smaller >= MIN_PART_SIZE && best.as_ref().is_none_or(|(size, ..)| smaller > *size) {
+                let moved = halves.pop().unwrap_or_default();
+                let stay = halves.pop().unwrap_or_default();
+                best = Some((smaller, weight, stay, moved));
+            }
+        }
+    }
+
+    best.map(|(_, weight, stay, moved)| suggestion(&names, &stay, &moved, weight))
+}
+
+/// Text of each top-level item, keyed by name; `impl` blocks are added to their type
+fn top_level_items(content: &str) -> BTreeMap<String, String> {
+    let item_regex = Regex::new(
+        r"(?m)^(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern\s+\S+)\s+)*(?:fn|struct|enum|trait|type|const|static|union)\s+(\w+)",
+    )
+    .unwrap();
+    let impl_regex = Regex::new(r"(?m)^(?:unsafe\s+)?impl(?:\s*<[^{]*?>)?\s+(?:[\w:]+(?:<[^{]*?>)?\s+for\s+)?(?:[\w]+::)*(\w+)").unwrap();
+
+    let mut starts: Vec<(usize, String)> = item_regex
+        .captures_iter(content)
+        .chain(impl_regex.captures_iter(content))
+        .filter_map(|captures| Some((captures.get(0)?.start(), captures.get(1)?.as_str().to_string())))
+        .collect();
+    starts.sort_by_key(|(start, _)| *start);
+
+    let mut items: BTreeMap<String, String> = BTreeMap::new();
+    for (index, (start, name)) in starts.iter().enumerate() {
+        let end = starts.get(index + 1).map_or(content.len(), |(next, _)| *next);
+        items.entry(name.clone()).or_default().push_str(&content[*start..end]);
+    }
+    items
+}
+
+/// Symmetric matrix of how often two items name each other
+fn reference_weights(items: &BTreeMap<String, String>, names: &[&String]) -> Vec<Vec<usize>> {
+    let patterns: Vec<Regex> = names
+        .iter()
+        .map(|name| Regex::new(&format!(r"\b{}\b", regex::escape(name))).unwrap())
+        .collect();
+    let mut weights = vec![vec![0; names.len()]; names.len()];
+
+    for (a, name) in names.iter().enumerate() {
+        let text = &items[*name];
+        for (b, pattern) in patterns.iter().enumerate() {
+            if a != b {
+                let count = pattern.find_iter(text).count();
+                weights[a][b] += count;
+                weights[b][a] += count;
+            }
+        }
+    }
+    weights
+}
+
+/// Connected groups of items, optionally ignoring the link between one pair
+fn components(neighbours: &[Vec<usize>], without: Option<(usize, usize)>) -> Vec<Vec<usize>> {
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+
+    for start in 0..neighbours.len() {
+        if !seen.insert(start) {
+            continue;
+        }
+        let mut component = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(current) = queue.pop_front() {
+            for &next in &neighbours[current] {
+                let removed = without.is_some_and(|(a, b)| (current, next) == (a, b) || (current, next) == (b, a));
+                if !removed && seen.insert(next) {
+                    component.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+        component.sort();
+        components.push(component);
+    }
+    components
+}
+
+fn suggestion(names: &[&String], stay: &[usize], moved: &[usize], cross_references: usize) -> SplitSuggestion {
+    SplitSuggestion {
+        stay: stay.iter().map(|&index| names[index].clone()).collect(),
+        moved: moved.iter().map(|&index| names[index].clone()).collect(),
+        cross_references,
+    }
+}
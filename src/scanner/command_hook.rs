@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::{
+    config::project_config::WatchSettings,
+    types::{ArchitectureNode, DiagnosticLevel, HookDiagnostic, HookOutcome, NodeStatus},
+};
+
+/// Runs a user-configured command (e.g. `cargo check`) before each watch-triggered rescan
+pub struct CommandHook {
+    command: String,
+    timeout: Duration,
+}
+
+impl CommandHook {
+    pub fn new(command: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            command: command.into(),
+            timeout,
+        }
+    }
+
+    /// Build a hook from the watch settings, if a command is configured
+    pub fn from_settings(settings: &WatchSettings) -> Option<Self> {
+        settings
+            .command
+            .as_ref()
+            .filter(|command| !command.trim().is_empty())
+            .map(|command| Self::new(command.clone(), Duration::from_secs(settings.command_timeout)))
+    }
+
+    /// The command line as configured
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// Run the command in the project directory and collect its diagnostics
+    pub async fn run(&self, project_path: &Path) -> Result<HookOutcome> {
+        let start_time = Instant::now();
+        let ran_at = Utc::now();
+
+        let mut command = shell_command(&self.command);
+        command.current_dir(project_path).kill_on_drop(true);
+
+        let output = tokio::time::timeout(self.timeout, command.output())
+            .await
+            .with_context(|| format!("Watch command timed out after {:?}: {}", self.timeout, self.command))?
+            .with_context(|| format!("Failed to run watch command: {}", self.command))?;
+
+        let mut combined = String::from_utf8_lossy(&output.stderr).to_string();
+        combined.push('\n');
+        combined.push_str(&String::from_utf8_lossy(&output.stdout));
+
+        let diagnostics = parse_diagnostics(&combined);
+        let error_count = diagnostics.iter().filter(|d| d.level == DiagnosticLevel::Error).count();
+        let warning_count = diagnostics.iter().filter(|d| d.level == DiagnosticLevel::Warning).count();
+
+        let outcome = HookOutcome {
+            command: self.command.clone(),
+            success: output.status.success(),
+            exit_code: output.status.code(),
+            duration_ms: start_time.elapsed().as_millis() as u64,
+            error_count,
+            warning_count,
+            diagnostics,
+            ran_at,
+        };
+
+        tracing::info!(
+            "Watch command `{}` finished in {}ms ({} errors, {} warnings)",
+            outcome.command,
+            outcome.duration_ms,
+            outcome.error_count,
+            outcome.warning_count
+        );
+
+        Ok(outcome)
+    }
+}
+
+/// Attribute the diagnostics of a hook run to the nodes whose files they point at
+pub fn apply_outcome(outcome: &HookOutcome, nodes: &mut HashMap<String, ArchitectureNode>) {
+    let mut by_file: HashMap<PathBuf, (usize, usize)> = HashMap::new();
+    for diagnostic in &outcome.diagnostics {
+        if let Some(file) = &diagnostic.file {
+            let counts = by_file.entry(PathBuf::from(file)).or_default();
+            match diagnostic.level {
+                DiagnosticLevel::Error => counts.0 += 1,
+                DiagnosticLevel::Warning => counts.1 += 1,
+            }
+        }
+    }
+
+    for node in nodes.values_mut() {
+        let node_path = Path::new(&node.file_path);
+        let counts = by_file
+            .iter()
+            .filter(|(file, _)| file.as_path() == node_path || file.ends_with(node_path))
+            .fold((0, 0), |acc, (_, counts)| (acc.0 + counts.0, acc.1 + counts.1));
+
+        if counts.0 > 0 {
+            node.status = NodeStatus::Error;
+        }
+        node.metrics.error_count = counts.0;
+        node.metrics.warning_count = counts.1;
+    }
+}
+
+/// Build a platform shell invocation for the configured command line
+fn shell_command(command_line: &str) -> tokio::process::Command {
+    if cfg!(windows) {
+        let mut command = tokio::process::Command::new("cmd");
+        command.args(["/C", command_line]);
+        command
+    } else {
+        let mut command = tokio::process::Command::new("sh");
+        command.args(["-c", command_line]);
+        command
+    }
+}
+
+/// Parse rustc-style human-readable diagnostics (`error[E0308]: ...` followed by `--> file:line:col`)
+fn parse_diagnostics(output: &str) -> Vec<HookDiagnostic> {
+    let header_regex = Regex::new(r"^(error|warning)(?:\[\w+\])?:\s*(.+)$").unwrap();
+    let location_regex = Regex::new(r"^\s*-->\s*([^:]+):(\d+):\d+").unwrap();
+
+    let mut diagnostics: Vec<HookDiagnostic> = Vec::new();
+    let mut awaiting_location = false;
+
+    for line in output.lines() {
+        if let Some(captures) = header_regex.captures(line) {
+            let message = captures[2].trim().to_string();
+            // Summary lines such as "warning: `crate` generated 3 warnings" are not diagnostics
+            if message.contains("generated") || message.starts_with("could not compile") || message.starts_with("aborting") {
+                awaiting_location = false;
+                continue;
+            }
+            let level = if &captures[1] == "error" {
+                DiagnosticLevel::Error
+            } else {
+                DiagnosticLevel::Warning
+            };
+            diagnostics.push(HookDiagnostic {
+                level,
+                message,
+                file: None,
+                line: None,
+            });
+            awaiting_location = true;
+        } else if awaiting_location {
+            if let Some(captures) = location_regex.captures(line) {
+                if let Some(last) = diagnostics.last_mut() {
+                    last.file = Some(captures[1].trim().to_string());
+                    last.line = captures[2].parse().ok();
+                }
+                awaiting_location = false;
+            }
+        }
+    }
+
+    diagnostics
+}
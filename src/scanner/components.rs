@@ -0,0 +1,83 @@
+//! Bounded contexts from the `[components]` config section.
+//!
+//! A module belongs to the first listed component whose crate or file globs
+//! match it, otherwise to the component its folder or crate name follows the
+//! conventions for: the directory below a configured folder prefix, or the
+//! longest configured prefix of its crate name.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+use crate::{config::ComponentSettings, types::ArchitectureNode};
+
+/// Globs of one listed component
+struct CompiledDefinition {
+    crates: Vec<glob::Pattern>,
+    patterns: Vec<glob::Pattern>,
+}
+
+/// Give every module the component its crate or location places it in
+pub fn apply_conventions(nodes: &mut HashMap<String, ArchitectureNode>, settings: &ComponentSettings) -> Result<()> {
+    let definitions = compile(settings)?;
+    if definitions.is_empty() && settings.folder_prefixes.is_empty() && settings.crate_prefixes.is_empty() {
+        return Ok(());
+    }
+
+    for node in nodes.values_mut() {
+        let crate_name = node.crate_name.as_deref();
+        let listed = definitions.iter().position(|definition| {
+            definition.patterns.iter().any(|pattern| pattern.matches(&node.file_path))
+                || crate_name.is_some_and(|name| definition.crates.iter().any(|pattern| pattern.matches(name)))
+        });
+        node.component = listed
+            .map(|index| settings.definitions[index].name.clone())
+            .or_else(|| folder_component(&node.file_path, &settings.folder_prefixes))
+            .or_else(|| crate_name.and_then(|name| crate_component(name, &settings.crate_prefixes)));
+    }
+
+    Ok(())
+}
+
+fn compile(settings: &ComponentSettings) -> Result<Vec<CompiledDefinition>> {
+    let compile_all = |name: &str, globs: &[String]| {
+        globs
+            .iter()
+            .map(|glob| {
+                glob::Pattern::new(glob).with_context(|| format!("Invalid pattern in component {}: {}", name, glob))
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+    settings
+        .definitions
+        .iter()
+        .map(|definition| {
+            Ok(CompiledDefinition {
+                crates: compile_all(&definition.name, &definition.crates)?,
+                patterns: compile_all(&definition.name, &definition.patterns)?,
+            })
+        })
+        .collect()
+}
+
+/// The directory right below the first prefix `file_path` lies under
+fn folder_component(file_path: &str, prefixes: &[String]) -> Option<String> {
+    prefixes.iter().find_map(|prefix| {
+        let prefix = prefix.trim_matches('/');
+        let rest = if prefix.is_empty() { Some(file_path) } else { file_path.strip_prefix(prefix)?.strip_prefix('/') };
+        // Files directly in the prefix directory belong to no component
+        rest?.split_once('/').map(|(directory, _)| directory.to_string())
+    })
+}
+
+/// The longest prefix naming `crate_name` whole or followed by `-` or `_`
+fn crate_component(crate_name: &str, prefixes: &[String]) -> Option<String> {
+    prefixes
+        .iter()
+        .filter(|prefix| {
+            crate_name
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', '_']))
+        })
+        .max_by_key(|prefix| prefix.len())
+        .cloned()
+}
@@ -0,0 +1,99 @@
+//! Database access of a module through sqlx, diesel or SeaORM.
+//!
+//! A module touches the database when it runs queries; modules that only
+//! declare tables or row types hold the schema. Modules whose path marks
+//! them as a repository (`repository`, `repo`, `dao`, `store`, `db`, ...)
+//! are where queries are expected; queries anywhere else bypass that layer.
+
+use regex::Regex;
+use std::collections::HashSet;
+
+use crate::types::{ArchitectureMap, ArchitectureNode, DataAccess};
+
+/// Crates detected, with the path prefix that gives each away
+const LIBRARIES: &[(&str, &str)] = &[("sqlx", "sqlx::"), ("diesel", "diesel::"), ("sea-orm", "sea_orm::")];
+
+/// Path segments of modules meant to hold the queries
+const REPOSITORY_SEGMENTS: &[&str] = &[
+    "repository",
+    "repositories",
+    "repo",
+    "repos",
+    "dao",
+    "store",
+    "persistence",
+    "db",
+    "database",
+];
+
+/// Queries and schema references of the database crates `content` uses, if any
+pub fn detect(content: &str) -> Option<DataAccess> {
+    let libraries: Vec<String> = LIBRARIES
+        .iter()
+        .filter(|(_, prefix)| content.contains(prefix))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    if libraries.is_empty() {
+        return None;
+    }
+
+    let uses = |library: &str| libraries.iter().any(|name| name == library);
+    let mut query_patterns = Vec::new();
+    let mut schema_patterns = Vec::new();
+    if uses("sqlx") {
+        query_patterns.push(r"\bquery(?:_as|_scalar|_file|_file_as)?(?:_unchecked)?!\s*\(|\bsqlx::query(?:_as|_scalar|_with)?\s*(?:::<[^>]*>)?\s*\(");
+        schema_patterns.push(r"\bFromRow\b|\bsqlx::migrate!");
+    }
+    if uses("diesel") {
+        query_patterns.push(r"\.(?:load|get_result|get_results|first|execute)\s*(?:::<[^>]*>)?\s*\(\s*(?:&\s*mut\s+)?\w*conn");
+        schema_patterns.push(r"\btable!\s*\{|\bschema::\w+|#\[diesel\(table_name");
+    }
+    if uses("sea-orm") {
+        query_patterns.push(r"::find(?:_by_id)?\s*\(|::(?:insert|update|delete)(?:_many)?\s*\(|\.(?:one|all|exec)\s*\(\s*&?\s*\w*(?:db|conn)");
+        schema_patterns.push(r"\bDeriveEntityModel\b|#\[sea_orm\(table_name");
+    }
+    let count = |patterns: &[&str]| {
+        patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern).unwrap().find_iter(content).count())
+            .sum()
+    };
+
+    Some(DataAccess {
+        queries: count(&query_patterns),
+        schema_references: count(&schema_patterns),
+        libraries,
+    })
+}
+
+/// Whether `node` is where queries belong
+pub fn is_repository(node: &ArchitectureNode) -> bool {
+    node.file_path
+        .split('/')
+        .map(|segment| segment.trim_end_matches(".rs").to_lowercase())
+        .any(|segment| REPOSITORY_SEGMENTS.contains(&segment.as_str()))
+}
+
+/// How a module reaches the database: `repository`, `direct`, `schema` or `via_repository`
+pub fn role(node: &ArchitectureNode, architecture: &ArchitectureMap, querying: &HashSet<&str>) -> Option<&'static str> {
+    match &node.data_access {
+        Some(access) if access.queries > 0 && is_repository(node) => Some("repository"),
+        Some(access) if access.queries > 0 => Some("direct"),
+        Some(_) => Some("schema"),
+        None => architecture
+            .edges
+            .iter()
+            .any(|edge| edge.from == node.id && querying.contains(edge.to.as_str()))
+            .then_some("via_repository"),
+    }
+}
+
+/// Ids of the modules that run queries
+pub fn querying_modules(architecture: &ArchitectureMap) -> HashSet<&str> {
+    architecture
+        .nodes
+        .values()
+        .filter(|node| node.data_access.as_ref().is_some_and(|access| access.queries > 0))
+        .map(|node| node.id.as_str())
+        .collect()
+}
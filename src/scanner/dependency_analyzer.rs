@@ -1,12 +1,93 @@
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
-use crate::types::{ArchitectureNode, DependencyEdge, DependencyType, ModuleType};
+use rayon::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use crate::{
+    scanner::{module_tree, relationships},
+    types::{ArchitectureNode, DependencyEdge, ImplInfo, ImportedItem, ItemUsage, ModuleType, SourceLocation},
+};
+
+/// Modules by their place in the module tree, for resolving dependencies without scanning every node
+pub struct NodeIndex<'a> {
+    /// By crate root file and module path
+    by_path: HashMap<(&'a str, &'a str), &'a ArchitectureNode>,
+}
+
+impl<'a> NodeIndex<'a> {
+    /// Index `nodes` that belong to a module tree
+    pub fn new(nodes: &'a HashMap<String, ArchitectureNode>) -> Self {
+        let by_path = nodes
+            .values()
+            .filter(|node| !node.module_root.is_empty())
+            .map(|node| ((node.module_root.as_str(), node.module_path.as_str()), node))
+            .collect();
+        Self { by_path }
+    }
+
+    /// The module a `use` or `mod` path of `source` leads to
+    pub fn resolve(&self, source: &ArchitectureNode, path: &str) -> Option<&'a ArchitectureNode> {
+        let root = source.module_root.as_str();
+        let module_path = module_tree::resolve(&source.module_path, path, |candidate| {
+            self.by_path.contains_key(&(root, candidate))
+        })?;
+        self.by_path.get(&(root, module_path.as_str())).copied()
+    }
+
+    /// The module defining the trait `implementation` of `source` implements.
+    ///
+    /// A qualified trait path resolves like a `use` path. A bare name is looked up
+    /// in the modules `source` imports from, preferring one that defines a trait of
+    /// that name over one that only re-exports it, and then in `source` itself.
+    pub fn trait_module(&self, source: &ArchitectureNode, implementation: &ImplInfo) -> Option<&'a ArchitectureNode> {
+        let name = implementation.trait_path.rsplit("::").next()?;
+        if implementation.trait_path.contains("::") {
+            return self.resolve(source, &implementation.trait_path);
+        }
+        let defines = |node: &ArchitectureNode| node.traits.iter().any(|item| item.name == name);
+        let imported: Vec<(&String, &'a ArchitectureNode)> = source
+            .dependencies
+            .iter()
+            .filter_map(|path| Some((path, self.resolve(source, path)?)))
+            .collect();
+        imported
+            .iter()
+            .find(|(_, target)| defines(target))
+            .or_else(|| imported.iter().find(|(path, _)| path.rsplit("::").next() == Some(name)))
+            .map(|(_, target)| *target)
+            .or_else(|| {
+                self.by_path
+                    .get(&(source.module_root.as_str(), source.module_path.as_str()))
+                    .copied()
+                    .filter(|node| defines(node))
+            })
+    }
+}
+
+/// A module depended on, with the items taken from it and where
+type Target<'a> = (&'a ArchitectureNode, Vec<ImportedItem>, Vec<SourceLocation>);
+
+/// The entry of `targets` for `target_node`, added when there is none yet
+fn target_entry<'a, 'b>(targets: &'b mut Vec<Target<'a>>, target_node: &'a ArchitectureNode) -> &'b mut Target<'a> {
+    let position = match targets.iter().position(|(target, ..)| target.id == target_node.id) {
+        Some(position) => position,
+        None => {
+            targets.push((target_node, Vec::new(), Vec::new()));
+            targets.len() - 1
+        }
+    };
+    &mut targets[position]
+}
 
 /// Analyzes dependencies between modules
 pub struct DependencyAnalyzer {
     // Add any state needed for dependency analysis
 }
 
+impl Default for DependencyAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DependencyAnalyzer {
     pub fn new() -> Self {
         Self {}
@@ -17,53 +98,116 @@ impl DependencyAnalyzer {
         &self,
         nodes: &HashMap<String, ArchitectureNode>,
     ) -> Result<Vec<DependencyEdge>> {
-        let mut edges = Vec::new();
-        
-        for (source_id, source_node) in nodes {
-            for dep_name in &source_node.dependencies {
-                // Find the target node by name
-                if let Some(target_node) = self.find_node_by_name(nodes, dep_name) {
-                    let edge = DependencyEdge {
-                        from: source_id.clone(),
-                        to: target_node.id.clone(),
-                        relationship: self.determine_relationship_type(source_node, target_node),
-                        strength: self.calculate_dependency_strength(source_node, target_node),
-                        is_circular: false, // Will be updated later
-                    };
-                    edges.push(edge);
-                }
-            }
-        }
+        let index = NodeIndex::new(nodes);
+        let mut edges: Vec<DependencyEdge> = nodes
+            .par_iter()
+            .flat_map_iter(|(_, source_node)| self.edges_from(&index, source_node))
+            .collect();
         
         // Update circular dependency flags
-        self.update_circular_dependencies(&mut edges, nodes);
+        self.update_circular_dependencies(&mut edges);
         
         Ok(edges)
     }
 
-    /// Find a node by its name
-    fn find_node_by_name<'a>(
+    /// Set `trait_module` on every trait impl whose trait the project defines
+    pub fn resolve_impls(&self, nodes: &mut HashMap<String, ArchitectureNode>) {
+        let index = NodeIndex::new(nodes);
+        let resolved: Vec<(String, Vec<Option<String>>)> = nodes
+            .values()
+            .filter(|node| !node.impls.is_empty())
+            .map(|node| {
+                let modules = node
+                    .impls
+                    .iter()
+                    .map(|implementation| index.trait_module(node, implementation).map(|target| target.id.clone()))
+                    .collect();
+                (node.id.clone(), modules)
+            })
+            .collect();
+        for (node_id, modules) in resolved {
+            if let Some(node) = nodes.get_mut(&node_id) {
+                for (implementation, module) in node.impls.iter_mut().zip(modules) {
+                    implementation.trait_module = module;
+                }
+            }
+        }
+    }
+
+    /// Edges out of `source_node`, before the circular dependency flags are set
+    pub fn edges_from(
         &self,
-        nodes: &'a HashMap<String, ArchitectureNode>,
-        name: &str,
-    ) -> Option<&'a ArchitectureNode> {
-        nodes.values().find(|node| node.name == name)
+        index: &NodeIndex,
+        source_node: &ArchitectureNode,
+    ) -> Vec<DependencyEdge> {
+        // Every path leading to the same module makes one edge
+        let mut targets: Vec<Target> = Vec::new();
+        for path in &source_node.dependencies {
+            let Some(target_node) = index.resolve(source_node, path) else {
+                continue;
+            };
+            let (_, items, locations) = target_entry(&mut targets, target_node);
+            items.extend(source_node.imported_items.get(path).into_iter().flatten().cloned());
+            for location in source_node.dependency_sites.get(path).into_iter().flatten() {
+                if !locations.contains(location) {
+                    locations.push(location.clone());
+                }
+            }
+        }
+        // Implementing a trait of another module, however it is named, makes an `Implements` edge
+        for implementation in &source_node.impls {
+            let Some(target_node) = index.trait_module(source_node, implementation).filter(|target| target.id != source_node.id) else {
+                continue;
+            };
+            let (_, items, locations) = target_entry(&mut targets, target_node);
+            items.push(ImportedItem {
+                name: implementation.trait_path.rsplit("::").next().unwrap_or_default().to_string(),
+                usage: ItemUsage::Implemented,
+            });
+            locations.push(SourceLocation {
+                file_path: source_node.file_path.clone(),
+                line: implementation.line,
+                snippet: format!("impl {} for {}", implementation.trait_path, implementation.self_type),
+            });
+        }
+        
+        targets
+            .into_iter()
+            .map(|(target_node, items, locations)| DependencyEdge {
+                from: source_node.id.clone(),
+                to: target_node.id.clone(),
+                relationship: relationships::classify(&items, target_node),
+                strength: self.calculate_dependency_strength(source_node, target_node),
+                is_circular: false, // Will be updated later
+                layer_violation: false,
+                locations,
+                suggestions: Vec::new(),
+            })
+            .collect()
     }
 
-    /// Determine the type of relationship between two nodes
-    fn determine_relationship_type(
+    /// Fill in `dependents` and the dependency counts on each node from the analyzed edges
+    pub fn update_node_links(
         &self,
-        source: &ArchitectureNode,
-        target: &ArchitectureNode,
-    ) -> DependencyType {
-        // Simple heuristic based on module types
-        match (&source.module_type, &target.module_type) {
-            (ModuleType::API, ModuleType::Core) => DependencyType::Uses,
-            (ModuleType::Core, ModuleType::DataProcessing) => DependencyType::Uses,
-            (ModuleType::Execution, ModuleType::Core) => DependencyType::Uses,
-            (ModuleType::Integration, ModuleType::API) => DependencyType::Uses,
-            (ModuleType::Testing, _) => DependencyType::Uses,
-            _ => DependencyType::DependsOn,
+        nodes: &mut HashMap<String, ArchitectureNode>,
+        edges: &[DependencyEdge],
+    ) {
+        let mut dependents: HashMap<&str, Vec<String>> = HashMap::new();
+        let mut dependency_counts: HashMap<&str, usize> = HashMap::new();
+        
+        for edge in edges {
+            let entry = dependents.entry(edge.to.as_str()).or_default();
+            if !entry.contains(&edge.from) {
+                entry.push(edge.from.clone());
+            }
+            *dependency_counts.entry(edge.from.as_str()).or_insert(0) += 1;
+        }
+        
+        for (node_id, node) in nodes.iter_mut() {
+            node.dependents = dependents.remove(node_id.as_str()).unwrap_or_default();
+            node.dependents.sort();
+            node.metrics.dependent_count = node.dependents.len();
+            node.metrics.dependency_count = dependency_counts.get(node_id.as_str()).copied().unwrap_or(0);
         }
     }
 
@@ -94,86 +238,54 @@ impl DependencyAnalyzer {
     }
 
     /// Update circular dependency flags
-    fn update_circular_dependencies(
-        &self,
-        edges: &mut Vec<DependencyEdge>,
-        nodes: &HashMap<String, ArchitectureNode>,
-    ) {
+    pub fn update_circular_dependencies(&self, edges: &mut [DependencyEdge]) {
         let circular_deps = self.find_circular_dependencies(edges);
+        self.mark_circular(edges, &circular_deps);
+    }
+
+    /// Flag the edges that close a step of any of `cycles`, and clear the flag on all others
+    pub fn mark_circular(&self, edges: &mut [DependencyEdge], cycles: &[Vec<String>]) {
+        let mut circular_pairs: HashSet<(&str, &str)> = HashSet::new();
+        for cycle in cycles {
+            for (index, from) in cycle.iter().enumerate() {
+                circular_pairs.insert((from, &cycle[(index + 1) % cycle.len()]));
+            }
+        }
         
         for edge in edges.iter_mut() {
-            let edge_pair = (edge.from.clone(), edge.to.clone());
-            edge.is_circular = circular_deps.iter().any(|cycle| {
-                cycle.windows(2).any(|pair| {
-                    (pair[0] == edge_pair.0 && pair[1] == edge_pair.1) ||
-                    (pair[0] == edge_pair.1 && pair[1] == edge_pair.0)
-                })
-            });
+            edge.is_circular = circular_pairs.contains(&(edge.from.as_str(), edge.to.as_str()));
         }
     }
 
-    /// Find circular dependencies using DFS
+    /// Find circular dependencies from the strongly connected components of the graph.
+    ///
+    /// Every edge inside a component lies on a cycle; each edge no earlier cycle
+    /// went through is reported with the shortest way back to where it starts,
+    /// so together the cycles cover every circular edge. Cycles start at their smallest id and each is
+    /// reported once, shortest first. Components share no edges and are
+    /// searched in parallel.
     pub fn find_circular_dependencies(&self, edges: &[DependencyEdge]) -> Vec<Vec<String>> {
-        let mut graph = HashMap::new();
-        
-        // Build adjacency list
+        let mut successors: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
         for edge in edges {
-            graph.entry(edge.from.clone())
-                .or_insert_with(Vec::new)
-                .push(edge.to.clone());
-        }
-        
-        let mut visited = HashSet::new();
-        let mut rec_stack = HashSet::new();
-        let mut cycles = Vec::new();
-        
-        for node in graph.keys() {
-            if !visited.contains(node) {
-                let mut path = Vec::new();
-                self.dfs_find_cycles(
-                    node,
-                    &graph,
-                    &mut visited,
-                    &mut rec_stack,
-                    &mut path,
-                    &mut cycles,
-                );
-            }
+            successors.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+            successors.entry(edge.to.as_str()).or_default();
         }
-        
-        cycles
-    }
-
-    /// DFS helper to find cycles
-    fn dfs_find_cycles(
-        &self,
-        node: &String,
-        graph: &HashMap<String, Vec<String>>,
-        visited: &mut HashSet<String>,
-        rec_stack: &mut HashSet<String>,
-        path: &mut Vec<String>,
-        cycles: &mut Vec<Vec<String>>,
-    ) {
-        visited.insert(node.clone());
-        rec_stack.insert(node.clone());
-        path.push(node.clone());
-        
-        if let Some(neighbors) = graph.get(node) {
-            for neighbor in neighbors {
-                if !visited.contains(neighbor) {
-                    self.dfs_find_cycles(neighbor, graph, visited, rec_stack, path, cycles);
-                } else if rec_stack.contains(neighbor) {
-                    // Found a cycle
-                    if let Some(cycle_start) = path.iter().position(|n| n == neighbor) {
-                        let cycle = path[cycle_start..].to_vec();
-                        cycles.push(cycle);
-                    }
-                }
-            }
+        for targets in successors.values_mut() {
+            targets.sort_unstable();
+            targets.dedup();
         }
         
-        rec_stack.remove(node);
-        path.pop();
+        let components: Vec<Vec<&str>> = strongly_connected(&successors)
+            .into_iter()
+            .filter(|component| component.len() > 1 || successors[component[0]].contains(&component[0]))
+            .collect();
+        let mut cycles: Vec<Vec<String>> = components
+            .par_iter()
+            .flat_map_iter(|component| cycles_covering(component, &successors))
+            .map(|cycle| cycle.into_iter().map(str::to_string).collect())
+            .collect();
+        cycles.sort_by(|a: &Vec<String>, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)));
+        cycles
     }
 
     /// Calculate dependency metrics
@@ -232,3 +344,205 @@ pub struct DependencyMetrics {
     pub average_dependencies_per_node: f64,
     pub most_connected_node: Option<String>,
 }
+
+/// Strongly connected components with Tarjan's algorithm, iterative so deep graphs cannot overflow the stack.
+///
+/// Every node must have an entry in `successors`; components come out in reverse topological order.
+pub fn strongly_connected<'a>(successors: &BTreeMap<&'a str, Vec<&'a str>>) -> Vec<Vec<&'a str>> {
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    let mut low_link: HashMap<&str, usize> = HashMap::new();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut on_stack: HashSet<&str> = HashSet::new();
+    let mut components = Vec::new();
+
+    for &start in successors.keys() {
+        if index.contains_key(start) {
+            continue;
+        }
+        // Nodes being visited, with the position of the next successor to look at
+        let mut path: Vec<(&str, usize)> = vec![(start, 0)];
+        index.insert(start, index.len());
+        low_link.insert(start, index[start]);
+        stack.push(start);
+        on_stack.insert(start);
+
+        while let Some(&mut (node, ref mut next)) = path.last_mut() {
+            if let Some(&successor) = successors[node].get(*next) {
+                *next += 1;
+                if !index.contains_key(successor) {
+                    index.insert(successor, index.len());
+                    low_link.insert(successor, index[successor]);
+                    stack.push(successor);
+                    on_stack.insert(successor);
+                    path.push((successor, 0));
+                } else if on_stack.contains(successor) {
+                    low_link.insert(node, low_link[node].min(index[successor]));
+                }
+                continue;
+            }
+
+            path.pop();
+            if let Some(&(parent, _)) = path.last() {
+                low_link.insert(parent, low_link[parent].min(low_link[node]));
+            }
+            if low_link[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+/// A cycle through each edge of `component`, starting at its smallest id and without repeats.
+///
+/// Edges already on an earlier cycle get none of their own.
+fn cycles_covering<'a>(component: &[&'a str], successors: &BTreeMap<&'a str, Vec<&'a str>>) -> BTreeSet<Vec<&'a str>> {
+    let mut members: Vec<&str> = component.to_vec();
+    members.sort_unstable();
+    let member_set: HashSet<&str> = members.iter().copied().collect();
+    let mut covered: HashSet<(&str, &str)> = HashSet::new();
+    let mut cycles = BTreeSet::new();
+    for &from in &members {
+        for &to in successors[from].iter().filter(|to| member_set.contains(*to)) {
+            if covered.contains(&(from, to)) {
+                continue;
+            }
+            let mut cycle = vec![from];
+            if to != from {
+                cycle.extend(shortest_path(to, from, &member_set, successors));
+                cycle.pop();
+            }
+            covered.extend((0..cycle.len()).map(|position| (cycle[position], cycle[(position + 1) % cycle.len()])));
+            let smallest = (0..cycle.len()).min_by_key(|&position| cycle[position]).unwrap_or_default();
+            cycle.rotate_left(smallest);
+            cycles.insert(cycle);
+        }
+    }
+    cycles
+}
+
+/// The nodes from `start` to `target` along the fewest edges inside `members`, both ends included
+fn shortest_path<'a>(start: &'a str, target: &'a str, members: &HashSet<&str>, successors: &BTreeMap<&'a str, Vec<&'a str>>) -> Vec<&'a str> {
+    let mut previous: HashMap<&str, &str> = HashMap::new();
+    let mut pending = VecDeque::from([start]);
+    while let Some(node) = pending.pop_front() {
+        if node == target {
+            break;
+        }
+        for &next in successors[node].iter().filter(|next| members.contains(*next)) {
+            if next != start && !previous.contains_key(next) {
+                previous.insert(next, node);
+                pending.push_back(next);
+            }
+        }
+    }
+    let mut path = vec![target];
+    while let Some(&node) = path.last().and_then(|node| previous.get(node)) {
+        path.push(node);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::DependencyType;
+
+    fn edges(pairs: &[(&str, &str)]) -> Vec<DependencyEdge> {
+        pairs
+            .iter()
+            .map(|&(from, to)| DependencyEdge {
+                from: from.to_string(),
+                to: to.to_string(),
+                relationship: DependencyType::Uses,
+                strength: 1.0,
+                is_circular: false,
+                layer_violation: false,
+                locations: Vec::new(),
+                suggestions: Vec::new(),
+            })
+            .collect()
+    }
+
+    fn cycles(pairs: &[(&str, &str)]) -> Vec<Vec<String>> {
+        DependencyAnalyzer::new().find_circular_dependencies(&edges(pairs))
+    }
+
+    fn ids(cycle: &[&str]) -> Vec<String> {
+        cycle.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn acyclic_graph_has_no_cycles() {
+        assert!(cycles(&[("a", "b"), ("b", "c"), ("a", "c"), ("c", "d")]).is_empty());
+    }
+
+    #[test]
+    fn cycle_starts_at_its_smallest_id() {
+        assert_eq!(cycles(&[("c", "a"), ("b", "c"), ("a", "b"), ("c", "d")]), vec![ids(&["a", "b", "c"])]);
+    }
+
+    #[test]
+    fn parallel_edges_report_one_cycle() {
+        assert_eq!(cycles(&[("a", "b"), ("a", "b"), ("b", "a"), ("b", "a")]), vec![ids(&["a", "b"])]);
+    }
+
+    #[test]
+    fn self_loop_is_a_cycle() {
+        assert_eq!(cycles(&[("a", "a"), ("a", "b")]), vec![ids(&["a"])]);
+    }
+
+    #[test]
+    fn separate_components_are_reported_shortest_first() {
+        let found = cycles(&[("x", "y"), ("y", "z"), ("z", "x"), ("d", "e"), ("e", "d"), ("z", "d")]);
+        assert_eq!(found, vec![ids(&["d", "e"]), ids(&["x", "y", "z"])]);
+    }
+
+    #[test]
+    fn cycles_cover_every_edge_of_a_component() {
+        // Two cycles sharing a → b: a → b → a and a → b → c → a
+        let pairs = [("a", "b"), ("b", "a"), ("b", "c"), ("c", "a")];
+        let found = cycles(&pairs);
+        assert_eq!(found, vec![ids(&["a", "b"]), ids(&["a", "b", "c"])]);
+
+        let mut marked = edges(&pairs);
+        marked.extend(edges(&[("c", "d")]));
+        DependencyAnalyzer::new().mark_circular(&mut marked, &found);
+        let circular: Vec<bool> = marked.iter().map(|edge| edge.is_circular).collect();
+        assert_eq!(circular, vec![true, true, true, true, false]);
+    }
+
+    #[test]
+    fn nodes_only_reached_as_targets_are_components() {
+        let successors = BTreeMap::from([("a", vec!["b"]), ("b", vec!["a", "c"]), ("c", vec![])]);
+        let mut components: Vec<Vec<&str>> = strongly_connected(&successors)
+            .into_iter()
+            .map(|mut component| {
+                component.sort_unstable();
+                component
+            })
+            .collect();
+        components.sort();
+        assert_eq!(components, vec![vec!["a", "b"], vec!["c"]]);
+    }
+
+    #[test]
+    fn long_chains_do_not_overflow_the_stack() {
+        let names: Vec<String> = (0..100_000).map(|index| format!("m{:06}", index)).collect();
+        let mut pairs: Vec<(&str, &str)> = names.windows(2).map(|pair| (pair[0].as_str(), pair[1].as_str())).collect();
+        pairs.push((names[names.len() - 1].as_str(), names[0].as_str()));
+        let found = cycles(&pairs);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].len(), names.len());
+        assert_eq!(found[0][0], names[0]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Compiler errors and warnings per module, from `cargo check --message-format=json`.
+//!
+//! Without this pass `error_count` and `warning_count` only estimate trouble from
+//! `panic!`, `unwrap()` and `#[warn(` in the source. Each diagnostic is
+//! attributed to the module holding its primary span; diagnostics in files
+//! outside the project, such as dependencies or macro expansions, are dropped.
+
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{
+    config::DiagnosticsSettings,
+    types::{ArchitectureNode, DiagnosticLevel, HookDiagnostic, NodeStatus},
+};
+
+/// One line of cargo's JSON output; only compiler messages are of interest
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<CompilerMessage>,
+}
+
+/// A diagnostic rustc or clippy reported through cargo
+#[derive(Deserialize)]
+pub(crate) struct CompilerMessage {
+    pub message: String,
+    pub level: String,
+    /// The lint or error code, e.g. `clippy::needless_return` or `E0308`
+    pub code: Option<DiagnosticCode>,
+    spans: Vec<Span>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DiagnosticCode {
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+struct Span {
+    file_name: String,
+    line_start: usize,
+    is_primary: bool,
+}
+
+/// Where span file names are resolved from, and the project they are made relative to
+pub(crate) struct SourceRoots {
+    workspace: PathBuf,
+    project: PathBuf,
+}
+
+impl SourceRoots {
+    pub fn new(project_path: &Path) -> Self {
+        let project = project_path.canonicalize().unwrap_or_else(|_| project_path.to_path_buf());
+        let workspace = workspace_root(project_path).unwrap_or_else(|| project.clone());
+        Self { workspace, project }
+    }
+}
+
+impl CompilerMessage {
+    /// File and line of the primary span, with the file relative to the project; `None` outside it
+    pub fn location(&self, roots: &SourceRoots) -> Option<(String, usize)> {
+        let span = self.spans.iter().find(|span| span.is_primary)?;
+        let path = roots.workspace.join(&span.file_name);
+        let relative = path.strip_prefix(&roots.project).ok()?;
+        Some((relative.to_string_lossy().replace('\\', "/"), span.line_start))
+    }
+}
+
+/// The compiler messages in cargo's JSON `output`
+pub(crate) fn compiler_messages(output: &str) -> impl Iterator<Item = CompilerMessage> + '_ {
+    output
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .filter_map(|line| serde_json::from_str::<CargoMessage>(line).ok())
+        .filter(|cargo_message| cargo_message.reason == "compiler-message")
+        .filter_map(|cargo_message| cargo_message.message)
+}
+
+/// Run `cargo check` in `project_path` and collect its diagnostics, with files relative to the project.
+///
+/// `None` when cargo could not be run; a failing build still returns its diagnostics.
+pub fn cargo_check(project_path: &Path, settings: &DiagnosticsSettings) -> Option<Vec<HookDiagnostic>> {
+    let output = Command::new("cargo")
+        .current_dir(project_path)
+        .args(["check", "--message-format=json"])
+        .args(&settings.args)
+        .output();
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            tracing::warn!("Failed to run cargo check for diagnostics: {}", e);
+            return None;
+        }
+    };
+    if !output.status.success() && output.stdout.is_empty() {
+        tracing::warn!("cargo check failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+        return None;
+    }
+
+    Some(parse_messages(&String::from_utf8_lossy(&output.stdout), &SourceRoots::new(project_path)))
+}
+
+/// Directory of the workspace manifest, which span file names are relative to
+fn workspace_root(project_path: &Path) -> Option<PathBuf> {
+    let output = Command::new("cargo")
+        .current_dir(project_path)
+        .args(["locate-project", "--workspace", "--message-format", "plain"])
+        .output()
+        .ok()?;
+    let manifest = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    output.status.success().then(|| manifest.parent().map(Path::to_path_buf)).flatten()
+}
+
+/// Errors and warnings of cargo's JSON `output`, each once even when several targets report it
+fn parse_messages(output: &str, roots: &SourceRoots) -> Vec<HookDiagnostic> {
+    let mut seen: HashSet<(bool, String, Option<String>, Option<usize>)> = HashSet::new();
+    let mut diagnostics = Vec::new();
+    for message in compiler_messages(output) {
+        // Also `error: internal compiler error`; notes and help are attached to their diagnostic
+        let level = if message.level.starts_with("error") {
+            DiagnosticLevel::Error
+        } else if message.level == "warning" {
+            DiagnosticLevel::Warning
+        } else {
+            continue;
+        };
+        let location = message.location(roots);
+        let (file, line) = (location.as_ref().map(|(file, _)| file.clone()), location.map(|(_, line)| line));
+        if !seen.insert((level == DiagnosticLevel::Error, message.message.clone(), file.clone(), line)) {
+            continue;
+        }
+        diagnostics.push(HookDiagnostic { level, message: message.message, file, line });
+    }
+    diagnostics
+}
+
+/// Replace the estimated error and warning counts of every module with those of `diagnostics`
+pub fn apply(nodes: &mut HashMap<String, ArchitectureNode>, diagnostics: &[HookDiagnostic]) {
+    let mut by_file: HashMap<&str, (usize, usize)> = HashMap::new();
+    for diagnostic in diagnostics {
+        if let Some(file) = &diagnostic.file {
+            let counts = by_file.entry(file.as_str()).or_default();
+            match diagnostic.level {
+                DiagnosticLevel::Error => counts.0 += 1,
+                DiagnosticLevel::Warning => counts.1 += 1,
+            }
+        }
+    }
+
+    for node in nodes.values_mut() {
+        let (errors, warnings) = by_file.get(node.file_path.as_str()).copied().unwrap_or_default();
+        if errors > 0 {
+            node.status = NodeStatus::Error;
+        }
+        node.metrics.error_count = errors;
+        node.metrics.warning_count = warnings;
+    }
+}
@@ -0,0 +1,54 @@
+//! Directory tree of the scanned modules, for exploring a large map top-down.
+//!
+//! Every directory holding a module, and every directory above one, becomes a
+//! [`ModuleGroup`] listing the modules directly inside it and its
+//! subdirectories. Modules at the project root and external crates have no
+//! directory and belong to no group.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::types::{ArchitectureNode, ModuleGroup};
+
+/// Groups of every directory with modules below it, each parent before its children
+pub fn build(nodes: &HashMap<String, ArchitectureNode>) -> Vec<ModuleGroup> {
+    let mut sorted: Vec<&ArchitectureNode> = nodes.values().collect();
+    sorted.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    let mut groups: BTreeMap<&str, ModuleGroup> = BTreeMap::new();
+    for node in sorted {
+        let mut directory = parent(&node.file_path);
+        let mut directly_inside = true;
+        while let Some(path) = directory {
+            let group = groups.entry(path).or_insert_with(|| ModuleGroup {
+                id: path.to_string(),
+                name: path.rsplit('/').next().unwrap_or(path).to_string(),
+                parent: parent(path).map(str::to_string),
+                children: Vec::new(),
+                modules: Vec::new(),
+                module_count: 0,
+                lines_of_code: 0,
+            });
+            if directly_inside {
+                group.modules.push(node.id.clone());
+            }
+            group.module_count += 1;
+            group.lines_of_code += node.metrics.lines_of_code;
+            directly_inside = false;
+            directory = parent(path);
+        }
+    }
+
+    // Every ancestor was added above, so each parent is there to take its children
+    let paths: Vec<&str> = groups.keys().copied().collect();
+    for path in paths {
+        if let Some(group) = parent(path).and_then(|parent| groups.get_mut(parent)) {
+            group.children.push(path.to_string());
+        }
+    }
+    groups.into_values().collect()
+}
+
+/// Directory of a `/`-separated path relative to the project; `None` at the project root
+fn parent(path: &str) -> Option<&str> {
+    path.rsplit_once('/').map(|(parent, _)| parent).filter(|parent| !parent.is_empty())
+}
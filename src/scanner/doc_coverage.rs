@@ -0,0 +1,71 @@
+//! Which public items of a module have a doc comment.
+//!
+//! Items count as public when declared `pub` without a visibility
+//! restriction; `pub mod name;` declarations are documented by the module
+//! file's own `//!` comment and are left out, as are `#[cfg(test)]` modules.
+
+use regex::Regex;
+
+use crate::types::{DocCoverage, UndocumentedItem};
+
+/// Count the public items of `content` and list those without a doc comment
+pub fn assess(content: &str) -> DocCoverage {
+    let item_regex = Regex::new(
+        r#"^pub\s+(?:(?:async|unsafe|const|extern(?:\s+"[^"]*")?)\s+)*(fn|struct|enum|trait|type|mod|union|static|const)\s+(?:mut\s+)?(?:r#)?(\w+)"#,
+    )
+    .unwrap();
+
+    let mut coverage = DocCoverage::default();
+    let mut documented = false;
+    let mut test_attribute = false;
+    // Brace depth at which a skipped `#[cfg(test)]` module closes
+    let mut skipping: Option<usize> = None;
+    let mut depth = 0usize;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        let depth_before = depth;
+        depth += trimmed.matches('{').count();
+        depth = depth.saturating_sub(trimmed.matches('}').count());
+
+        if let Some(closing) = skipping {
+            if depth <= closing {
+                skipping = None;
+            }
+            continue;
+        }
+
+        if trimmed.starts_with("///") || trimmed.starts_with("/**") || trimmed.starts_with("#[doc") {
+            documented = true;
+            continue;
+        }
+        if trimmed.starts_with("#[") {
+            test_attribute |= trimmed.starts_with("#[cfg(test)]");
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with("//") {
+            continue;
+        }
+
+        if test_attribute && trimmed.contains("mod ") && depth > depth_before {
+            skipping = Some(depth_before);
+        } else if let Some(captures) = item_regex.captures(trimmed) {
+            let kind = &captures[1];
+            let declaration_only = kind == "mod" && trimmed.ends_with(';');
+            if !declaration_only {
+                coverage.public_items += 1;
+                if !documented {
+                    coverage.undocumented.push(UndocumentedItem {
+                        name: captures[2].to_string(),
+                        kind: kind.to_string(),
+                        line: index + 1,
+                    });
+                }
+            }
+        }
+        documented = false;
+        test_attribute = false;
+    }
+
+    coverage
+}
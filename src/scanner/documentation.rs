@@ -0,0 +1,101 @@
+//! Documentation linked to modules.
+//!
+//! A module links a document with an inner doc comment such as
+//! `//! arch-viz:doc=docs/adr/0004-scanner.md Scanner ADR`; the optional text
+//! after the target is the link title. Relative targets are resolved against
+//! the module's directory. The `[documentation]` config section links
+//! modules by file path pattern instead.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{
+    config::DocumentationSettings,
+    types::{ArchitectureNode, DocumentationLink},
+};
+
+/// Links declared by `//! arch-viz:doc=` comments of the module at `relative_path`
+pub fn extract_doc_comments(relative_path: &Path, content: &str) -> Vec<DocumentationLink> {
+    let doc_regex = Regex::new(r"(?m)^\s*//!\s*arch-viz:doc=(\S+)[ \t]*(.*)$").unwrap();
+    let module_dir = relative_path.parent().unwrap_or(Path::new(""));
+
+    doc_regex
+        .captures_iter(content)
+        .map(|captures| {
+            let target = &captures[1];
+            let title = captures[2].trim();
+            let target = if is_url(target) {
+                target.to_string()
+            } else {
+                normalize(&module_dir.join(target))
+            };
+            link(target, (!title.is_empty()).then(|| title.to_string()))
+        })
+        .collect()
+}
+
+/// Add the links of every `[documentation]` rule matching a module's file path
+pub fn apply_rules(nodes: &mut HashMap<String, ArchitectureNode>, settings: &DocumentationSettings) -> Result<()> {
+    for rule in &settings.links {
+        let pattern = glob::Pattern::new(&rule.pattern)
+            .with_context(|| format!("Invalid documentation pattern: {}", rule.pattern))?;
+        let target = if is_url(&rule.target) {
+            rule.target.clone()
+        } else {
+            normalize(Path::new(&rule.target))
+        };
+
+        for node in nodes.values_mut().filter(|node| pattern.matches(&node.file_path)) {
+            if !node.documentation.iter().any(|existing| existing.target == target) {
+                node.documentation.push(link(target.clone(), rule.title.clone()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether some module links the project file at `path`
+pub fn is_linked_file<'a>(mut nodes: impl Iterator<Item = &'a ArchitectureNode>, path: &str) -> bool {
+    nodes.any(|node| node.documentation.iter().any(|link| !link.external && link.target == path))
+}
+
+fn link(target: String, title: Option<String>) -> DocumentationLink {
+    let external = is_url(&target);
+    let title = title.unwrap_or_else(|| {
+        if external {
+            target.clone()
+        } else {
+            Path::new(&target)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| target.clone())
+        }
+    });
+    DocumentationLink { title, target, external }
+}
+
+fn is_url(target: &str) -> bool {
+    target.starts_with("http://") || target.starts_with("https://")
+}
+
+/// Project-relative path with `.` and `..` resolved and `/` separators
+fn normalize(path: &Path) -> String {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Normal(part) => normalized.push(part),
+            _ => {}
+        }
+    }
+    normalized
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,145 @@
+//! Nodes for the `Cargo.toml` dependencies modules `use`.
+//!
+//! Enabled by `include_external_crates`. Each dependency some module uses
+//! becomes one `ExternalCrate` node, with an edge from every module whose
+//! `use` or `extern crate` items name it. Only dependencies the module's own
+//! crate declares count, so a loose tree without a manifest gets none, and
+//! workspace crates depending on each other stay internal.
+
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use uuid::Uuid;
+
+use crate::{
+    scanner::cargo_manifest::CrateInfo,
+    types::{
+        ArchitectureNode, CfgGates, DeadCode, DependencyEdge, DependencyType, ItemReferences, ModuleType, NodeMetrics, NodeStatus,
+        SourceLocation,
+    },
+};
+
+/// Path roots that never name a dependency
+const BUILTIN_ROOTS: &[&str] = &["std", "core", "alloc", "proc_macro", "test", "crate", "self", "super"];
+
+/// Root crates of the `use` and `extern crate` items in `content`, with where they appear
+pub fn extract_uses(file_path: &str, content: &str) -> BTreeMap<String, Vec<SourceLocation>> {
+    let use_regex =
+        Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?(?:use\s+(?:::)?|extern\s+crate\s+)([A-Za-z_]\w*)").unwrap();
+    let mut uses: BTreeMap<String, Vec<SourceLocation>> = BTreeMap::new();
+
+    for captures in use_regex.captures_iter(content) {
+        let (Some(statement), Some(root)) = (captures.get(0), captures.get(1)) else {
+            continue;
+        };
+        if BUILTIN_ROOTS.contains(&root.as_str()) {
+            continue;
+        }
+        // The match starts at the line break before any indentation
+        let offset = statement.start() + statement.as_str().len() - statement.as_str().trim_start().len();
+        uses.entry(root.as_str().to_string())
+            .or_default()
+            .push(SourceLocation::at_offset(file_path, content, offset));
+    }
+
+    uses
+}
+
+/// Add a node for every declared dependency some module uses, and an edge from each of those modules
+pub fn attach(nodes: &mut HashMap<String, ArchitectureNode>, edges: &mut Vec<DependencyEdge>, crates: &[CrateInfo]) {
+    let ident = |name: &str| name.replace('-', "_");
+    let workspace: HashSet<String> = crates.iter().map(|info| ident(&info.name)).collect();
+    // Per crate, the dependency behind each identifier its code can use
+    let declared: HashMap<&str, HashMap<String, &str>> = crates
+        .iter()
+        .map(|info| {
+            let dependencies = info
+                .dependencies
+                .iter()
+                .map(|name| (ident(name), name.as_str()))
+                .filter(|(ident, _)| !workspace.contains(ident))
+                .collect();
+            (info.name.as_str(), dependencies)
+        })
+        .collect();
+
+    let mut used: BTreeMap<&str, BTreeMap<&str, Vec<SourceLocation>>> = BTreeMap::new();
+    for node in nodes.values() {
+        let Some(dependencies) = node.crate_name.as_deref().and_then(|name| declared.get(name)) else {
+            continue;
+        };
+        for (root, sites) in &node.external_uses {
+            if let Some(dependency) = dependencies.get(root) {
+                used.entry(dependency).or_default().insert(node.id.as_str(), sites.clone());
+            }
+        }
+    }
+
+    let mut external = Vec::new();
+    for (dependency, users) in used {
+        let node = external_node(dependency);
+        for (from, locations) in users {
+            edges.push(DependencyEdge {
+                from: from.to_string(),
+                to: node.id.clone(),
+                relationship: DependencyType::Imports,
+                strength: 0.5,
+                is_circular: false,
+                layer_violation: false,
+                locations,
+                suggestions: Vec::new(),
+            });
+        }
+        external.push(node);
+    }
+    nodes.extend(external.into_iter().map(|node| (node.id.clone(), node)));
+}
+
+fn external_node(dependency: &str) -> ArchitectureNode {
+    ArchitectureNode {
+        // Namespaced so no file path can produce the same id
+        id: Uuid::new_v5(&Uuid::NAMESPACE_URL, format!("external-crate:{}", dependency).as_bytes()).to_string(),
+        name: dependency.to_string(),
+        module_type: ModuleType::ExternalCrate,
+        file_path: String::new(),
+        crate_name: Some(dependency.to_string()),
+        entry_point: None,
+        module_path: String::new(),
+        dependencies: Vec::new(),
+        dependents: Vec::new(),
+        status: NodeStatus::Active,
+        metrics: NodeMetrics::default(),
+        // Fixed, so snapshots of unchanged dependencies stay identical
+        last_modified: DateTime::<Utc>::UNIX_EPOCH,
+        functions: Vec::new(),
+        structs: Vec::new(),
+        enums: Vec::new(),
+        traits: Vec::new(),
+        impls: Vec::new(),
+        position: None,
+        features: Vec::new(),
+        cfg: Vec::new(),
+        naming_violations: Vec::new(),
+        layer: None,
+        component: None,
+        groups: Vec::new(),
+        ignored: false,
+        documentation: Vec::new(),
+        robustness: Default::default(),
+        doc_coverage: Default::default(),
+        routes: Vec::new(),
+        data_access: None,
+        ownership: None,
+        split_suggestion: None,
+        dead_code: DeadCode::default(),
+        api_leaks: Vec::new(),
+        annotations: BTreeMap::new(),
+        dependency_sites: HashMap::new(),
+        external_uses: BTreeMap::new(),
+        imported_items: HashMap::new(),
+        module_root: String::new(),
+        item_references: ItemReferences::default(),
+        public_signatures: Vec::new(),
+        cfg_gates: CfgGates::default(),
+    }
+}
@@ -0,0 +1,111 @@
+//! Generated code and macro-expanded harnesses, which should not count as hand-written complexity.
+//!
+//! Files whose header says a tool wrote them, and glue modules that only pull
+//! in build-script output (`tonic::include_proto!`, `include!(concat!(env!("OUT_DIR"), ..))`),
+//! become `Generated` modules. Test and bench harness macros such as
+//! `proptest!` or `criterion_group!` stay in their module but are left out of
+//! its complexity scores.
+
+use regex::Regex;
+use std::borrow::Cow;
+
+/// Comments code generators put at the top of their output
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "code generated by",
+    "do not edit",
+    "automatically generated",
+    "auto-generated",
+    "prost-build",
+    "tonic-build",
+];
+
+/// Lines at the top of a file searched for a generator marker
+const HEADER_LINES: usize = 10;
+
+/// Whether a tool rather than a person wrote `content`
+pub fn is_generated(content: &str) -> bool {
+    let header_says_generated = content
+        .lines()
+        .take(HEADER_LINES)
+        .filter(|line| line.trim_start().starts_with("//") || line.trim_start().starts_with("/*"))
+        .any(|line| {
+            let line = line.to_lowercase();
+            GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+        });
+    if header_says_generated {
+        return true;
+    }
+
+    // A module that only includes build-script output is the generated code's stand-in
+    let includes = include_regex();
+    includes.is_match(content) && is_blank(&includes.replace_all(content, ""))
+}
+
+/// `content` with harness macro invocations blanked out, keeping its line numbers
+pub fn strip_harness_macros(content: &str) -> Cow<'_, str> {
+    let harness = harness_regex();
+    let mut spans = Vec::new();
+    let mut searched = 0;
+    while let Some(found) = harness.find_at(content, searched) {
+        let end = matching_delimiter(content, found.end() - 1).unwrap_or(content.len());
+        spans.push(found.start()..end);
+        searched = end;
+    }
+    if spans.is_empty() {
+        return Cow::Borrowed(content);
+    }
+
+    let mut stripped = String::with_capacity(content.len());
+    let mut copied = 0;
+    for span in spans {
+        stripped.push_str(&content[copied..span.start]);
+        stripped.extend(content[span.clone()].chars().filter(|&c| c == '\n'));
+        copied = span.end;
+    }
+    stripped.push_str(&content[copied..]);
+    Cow::Owned(stripped)
+}
+
+fn harness_regex() -> Regex {
+    Regex::new(r"\b(?:[\w:]+::)?(?:proptest|prop_compose|quickcheck|criterion_group|criterion_main)!\s*[({\[]").unwrap()
+}
+
+fn include_regex() -> Regex {
+    Regex::new(r#"(?:\b(?:tonic::)?include_proto!\s*\([^)]*\)|\binclude!\s*\(\s*concat!\s*\(\s*env!\s*\(\s*"OUT_DIR"\s*\)[^;]*)\s*;?"#).unwrap()
+}
+
+/// Offset just past the delimiter closing the one at `open`
+fn matching_delimiter(content: &str, open: usize) -> Option<usize> {
+    let bytes = content.as_bytes();
+    let (opening, closing) = match bytes[open] {
+        b'(' => (b'(', b')'),
+        b'{' => (b'{', b'}'),
+        _ => (b'[', b']'),
+    };
+    let mut depth = 0usize;
+    for (offset, &byte) in bytes.iter().enumerate().skip(open) {
+        if byte == opening {
+            depth += 1;
+        } else if byte == closing {
+            depth -= 1;
+            if depth == 0 {
+                return Some(offset + 1);
+            }
+        }
+    }
+    None
+}
+
+/// Whether only comments, attributes and `use`/`mod` glue are left
+fn is_blank(content: &str) -> bool {
+    content.lines().map(str::trim).all(|line| {
+        line.is_empty()
+            || line.starts_with("//")
+            || line.starts_with("#")
+            || line.starts_with("use ")
+            || line.starts_with("pub use ")
+            || line == "}"
+            || (line.contains("mod ") && line.ends_with('{'))
+    })
+}
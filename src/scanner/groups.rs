@@ -0,0 +1,36 @@
+//! Virtual groups from the `[groups]` config section.
+//!
+//! A group names the modules its file path globs match, wherever they live in
+//! the directory tree, so conceptual parts of the architecture such as a
+//! domain spread over several folders can be drawn and ruled on as one. A
+//! module may belong to several groups.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+
+use crate::{config::GroupSettings, types::ArchitectureNode};
+
+/// Give every module the names of the groups whose globs match it, in name order
+pub fn apply(nodes: &mut HashMap<String, ArchitectureNode>, settings: &GroupSettings) -> Result<()> {
+    let groups = settings
+        .definitions
+        .iter()
+        .map(|(name, globs)| {
+            let patterns = globs
+                .iter()
+                .map(|glob| glob::Pattern::new(glob).with_context(|| format!("Invalid pattern in group {}: {}", name, glob)))
+                .collect::<Result<Vec<_>>>()?;
+            Ok((name, patterns))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for node in nodes.values_mut() {
+        node.groups = groups
+            .iter()
+            .filter(|(_, patterns)| patterns.iter().any(|pattern| pattern.matches(&node.file_path)))
+            .map(|(name, _)| name.to_string())
+            .collect();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Modules marked as ignored through the `[ignore]` config section.
+//!
+//! Ignored modules, typically generated or vendored code, stay in the graph so
+//! the UI can still show them, but take no part in metrics, totals or cycles.
+//! Nodes of external crates are left out of those the same way.
+
+use anyhow::{Context, Result};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    config::IgnoreSettings,
+    types::{ArchitectureNode, DependencyEdge},
+};
+
+/// Flag every module whose file path matches an `[ignore]` glob
+pub fn apply_rules(nodes: &mut HashMap<String, ArchitectureNode>, settings: &IgnoreSettings) -> Result<()> {
+    let patterns = compile(settings)?;
+    for node in nodes.values_mut() {
+        node.ignored = patterns.iter().any(|pattern| pattern.matches(&node.file_path));
+    }
+
+    Ok(())
+}
+
+/// Check that every ignored path is a valid glob
+pub fn validate(settings: &IgnoreSettings) -> Result<()> {
+    compile(settings).map(|_| ())
+}
+
+/// Modules that count toward metrics; borrowed when every node does
+pub fn counted_nodes(nodes: &HashMap<String, ArchitectureNode>) -> Cow<'_, HashMap<String, ArchitectureNode>> {
+    if nodes.values().all(is_counted) {
+        return Cow::Borrowed(nodes);
+    }
+    Cow::Owned(
+        nodes
+            .iter()
+            .filter(|(_, node)| is_counted(node))
+            .map(|(id, node)| (id.clone(), node.clone()))
+            .collect(),
+    )
+}
+
+/// Edges between two counted modules; borrowed when every node counts
+pub fn counted_edges<'a>(nodes: &HashMap<String, ArchitectureNode>, edges: &'a [DependencyEdge]) -> Cow<'a, [DependencyEdge]> {
+    let ignored: HashSet<&str> = nodes.values().filter(|node| !is_counted(node)).map(|node| node.id.as_str()).collect();
+    if ignored.is_empty() {
+        return Cow::Borrowed(edges);
+    }
+    Cow::Owned(
+        edges
+            .iter()
+            .filter(|edge| !ignored.contains(edge.from.as_str()) && !ignored.contains(edge.to.as_str()))
+            .cloned()
+            .collect(),
+    )
+}
+
+/// Whether `node` counts towards metrics: neither ignored nor an external crate
+pub(crate) fn is_counted(node: &ArchitectureNode) -> bool {
+    !node.ignored && !node.module_type.is_external()
+}
+
+fn compile(settings: &IgnoreSettings) -> Result<Vec<glob::Pattern>> {
+    settings
+        .paths
+        .iter()
+        .map(|path| glob::Pattern::new(path).with_context(|| format!("Invalid ignore pattern: {}", path)))
+        .collect()
+}
@@ -0,0 +1,135 @@
+//! Parse results kept between scans, so a rescan only re-parses changed files.
+//!
+//! A file is unchanged when its size and modification time match the previous
+//! scan, or failing that when its content hashes the same. Dependency edges are
+//! kept per source module and resolved again only for modules that changed or
+//! whose paths may lead to a module that moved, appeared, disappeared or changed type.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::types::{ArchitectureNode, DependencyEdge, ModuleType};
+
+/// Size, modification time and content hash of a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFingerprint {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+    pub content_hash: u64,
+}
+
+impl FileFingerprint {
+    pub fn new(size: u64, modified: Option<SystemTime>, content: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Self {
+            size,
+            modified,
+            content_hash: hasher.finish(),
+        }
+    }
+
+    /// Whether the file metadata alone shows the file did not change
+    pub fn matches_metadata(&self, size: u64, modified: Option<SystemTime>) -> bool {
+        self.size == size && self.modified.is_some() && self.modified == modified
+    }
+}
+
+/// A parsed file as the scanner produced it, before crate attribution and classification
+#[derive(Debug, Clone)]
+pub struct CachedFile {
+    pub fingerprint: FileFingerprint,
+    pub node: ArchitectureNode,
+}
+
+/// Crate root file and module path of a module
+type ModulePlace = (String, String);
+
+/// What one scan leaves for the next
+#[derive(Debug, Default)]
+pub struct ScanCache {
+    files: HashMap<PathBuf, CachedFile>,
+    /// Resolved edges by source module id, before the cycle and layer flags
+    edges: HashMap<String, Vec<DependencyEdge>>,
+    /// Place in the module tree and type of each module when the edges were resolved
+    resolved: HashMap<String, (ModulePlace, ModuleType)>,
+}
+
+impl ScanCache {
+    /// Take the cached parse of `path` out of the cache
+    pub fn take_file(&mut self, path: &Path) -> Option<CachedFile> {
+        self.files.remove(path)
+    }
+
+    pub fn insert_file(&mut self, path: PathBuf, file: CachedFile) {
+        self.files.insert(path, file);
+    }
+
+    /// Take the cached edges of `id` out of the cache
+    pub fn take_edges(&mut self, id: &str) -> Option<Vec<DependencyEdge>> {
+        self.edges.remove(id)
+    }
+
+    pub fn insert_edges(&mut self, id: String, edges: Vec<DependencyEdge>) {
+        self.edges.insert(id, edges);
+    }
+
+    /// Remember the module paths and types the cached edges were resolved against
+    pub fn record_resolution(&mut self, nodes: &HashMap<String, ArchitectureNode>) {
+        self.resolved = nodes
+            .iter()
+            .map(|(id, node)| (id.clone(), (place(node), node.module_type.clone())))
+            .collect();
+    }
+
+    /// Ids of the modules whose edges must be resolved again: those in `reparsed`,
+    /// new ones, moved or retyped ones, and those with a path naming a module
+    /// that appeared, disappeared or moved
+    pub fn stale_sources<'a>(&'a self, nodes: &'a HashMap<String, ArchitectureNode>, reparsed: &HashSet<String>) -> HashSet<String> {
+        let mut stale = reparsed.clone();
+        // Last segments of the module paths involved; dependency paths name modules by them
+        let mut touched_names: HashSet<&str> = HashSet::new();
+        let last_segment = |module_path: &'a str| module_path.rsplit("::").next().unwrap_or_default();
+
+        for (id, ((root, module_path), module_type)) in &self.resolved {
+            match nodes.get(id) {
+                Some(node) if (&node.module_root, &node.module_path) == (root, module_path) && node.module_type == *module_type => {}
+                Some(node) => {
+                    touched_names.insert(last_segment(module_path));
+                    touched_names.insert(last_segment(&node.module_path));
+                    stale.insert(id.clone());
+                }
+                None => {
+                    touched_names.insert(last_segment(module_path));
+                }
+            }
+        }
+        for (id, node) in nodes {
+            if !self.resolved.contains_key(id) {
+                touched_names.insert(last_segment(&node.module_path));
+                stale.insert(id.clone());
+            }
+        }
+
+        if !touched_names.is_empty() {
+            stale.extend(
+                nodes
+                    .iter()
+                    .filter(|(_, node)| {
+                        node.dependencies
+                            .iter()
+                            .any(|path| path.split("::").any(|segment| touched_names.contains(segment)))
+                    })
+                    .map(|(id, _)| id.clone()),
+            );
+        }
+        stale
+    }
+}
+
+fn place(node: &ArchitectureNode) -> ModulePlace {
+    (node.module_root.clone(), node.module_path.clone())
+}
@@ -0,0 +1,200 @@
+use regex::Regex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::{
+    scanner::source_tree::SourceTree,
+    types::{ArchitectureNode, EntryPoint},
+};
+
+/// Module a file of a loose source tree (no `Cargo.toml`) was inferred to be
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredModule {
+    /// Last segment of the module path, or the root's name for a root file
+    pub name: String,
+    /// Path below the root, e.g. `utils::strings`; empty for a root file
+    pub module_path: String,
+    /// Name of the root file the module hangs off, used as its crate
+    pub root: String,
+    /// The root file itself
+    pub root_file: PathBuf,
+}
+
+/// Infer the module tree of `files` from their `mod name;` declarations.
+///
+/// Files that no other file declares are treated as crate roots, the way
+/// `rustc file.rs` would compile them. A root named `main.rs` or `lib.rs`
+/// below the project directory is named after its directory so that several
+/// small programs in one tree stay distinguishable.
+pub fn infer_modules(project_path: &Path, files: &[PathBuf], tree: SourceTree) -> HashMap<PathBuf, InferredModule> {
+    let file_set: HashSet<&Path> = files.iter().map(PathBuf::as_path).collect();
+    let declarations: HashMap<&Path, Vec<ModDeclaration>> = files
+        .iter()
+        .map(|file| {
+            let content = tree.read(file).unwrap_or_default();
+            (file.as_path(), mod_declarations(&content))
+        })
+        .collect();
+
+    // Files declared by some other file are submodules; everything else is a root.
+    // Roots own their directory, which changes where their submodules resolve, so
+    // iterate until the set of declared files settles.
+    let mut declared: HashSet<PathBuf> = HashSet::new();
+    let file_set = &file_set;
+    for _ in 0..=files.len() {
+        let next: HashSet<PathBuf> = files
+            .iter()
+            .flat_map(|file| {
+                let owns_directory = is_directory_owner(file) || !declared.contains(file);
+                declarations[file.as_path()]
+                    .iter()
+                    .filter_map(move |declaration| resolve_child(file, owns_directory, declaration, file_set))
+            })
+            .collect();
+        if next == declared {
+            break;
+        }
+        declared = next;
+    }
+
+    let mut modules = HashMap::new();
+    let mut queue = VecDeque::new();
+    let mut roots: Vec<&PathBuf> = files.iter().filter(|file| !declared.contains(*file)).collect();
+    roots.sort();
+    for root in roots {
+        let name = root_name(project_path, root);
+        modules.insert(
+            root.clone(),
+            InferredModule {
+                name: name.clone(),
+                module_path: String::new(),
+                root: name,
+                root_file: root.clone(),
+            },
+        );
+        queue.push_back((root.clone(), true));
+    }
+
+    while let Some((file, is_root)) = queue.pop_front() {
+        let parent = modules[&file].clone();
+        for declaration in &declarations[file.as_path()] {
+            let Some(child) = resolve_child(&file, is_root || is_directory_owner(&file), declaration, file_set)
+            else {
+                continue;
+            };
+            if modules.contains_key(&child) {
+                continue;
+            }
+            let module_path = if parent.module_path.is_empty() {
+                declaration.name.clone()
+            } else {
+                format!("{}::{}", parent.module_path, declaration.name)
+            };
+            modules.insert(
+                child.clone(),
+                InferredModule {
+                    name: declaration.name.clone(),
+                    module_path,
+                    root: parent.root.clone(),
+                    root_file: parent.root_file.clone(),
+                },
+            );
+            queue.push_back((child, false));
+        }
+    }
+
+    // Files only reachable through a declaration cycle become roots of their own
+    for file in files {
+        if !modules.contains_key(file) {
+            let name = root_name(project_path, file);
+            modules.insert(
+                file.clone(),
+                InferredModule {
+                    name: name.clone(),
+                    module_path: String::new(),
+                    root: name,
+                    root_file: file.clone(),
+                },
+            );
+        }
+    }
+
+    modules
+}
+
+/// Target a root file compiles into: a library for `lib.rs`, a binary for other roots with a `main` function
+pub fn entry_point(module: &InferredModule, node: &ArchitectureNode) -> Option<EntryPoint> {
+    if !module.module_path.is_empty() {
+        None
+    } else if Path::new(&node.file_path).file_name().is_some_and(|name| name == "lib.rs") {
+        Some(EntryPoint::Library)
+    } else if node.functions.iter().any(|function| function.name == "main") {
+        Some(EntryPoint::Binary)
+    } else {
+        None
+    }
+}
+
+/// An out-of-line `mod name;` declaration
+#[derive(Debug)]
+struct ModDeclaration {
+    name: String,
+    /// Value of a preceding `#[path = "..."]` attribute
+    path: Option<String>,
+}
+
+fn mod_declarations(content: &str) -> Vec<ModDeclaration> {
+    let mod_regex = Regex::new(
+        r#"(?m)^\s*(?:#\[path\s*=\s*"([^"]+)"\]\s*)?(?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;"#,
+    )
+    .unwrap();
+    mod_regex
+        .captures_iter(content)
+        .map(|captures| ModDeclaration {
+            name: captures[2].to_string(),
+            path: captures.get(1).map(|path| path.as_str().to_string()),
+        })
+        .collect()
+}
+
+/// Whether a file's submodules live next to it rather than in a directory named after it
+fn is_directory_owner(file: &Path) -> bool {
+    matches!(
+        file.file_name().and_then(|name| name.to_str()),
+        Some("mod.rs" | "main.rs" | "lib.rs")
+    )
+}
+
+fn resolve_child(
+    file: &Path,
+    owns_directory: bool,
+    declaration: &ModDeclaration,
+    files: &HashSet<&Path>,
+) -> Option<PathBuf> {
+    let parent_dir = file.parent()?;
+    if let Some(path) = &declaration.path {
+        let candidate = parent_dir.join(path);
+        return files.contains(candidate.as_path()).then_some(candidate);
+    }
+
+    let module_dir = if owns_directory {
+        parent_dir.to_path_buf()
+    } else {
+        parent_dir.join(file.file_stem()?)
+    };
+    [
+        module_dir.join(format!("{}.rs", declaration.name)),
+        module_dir.join(&declaration.name).join("mod.rs"),
+    ]
+    .into_iter()
+    .find(|candidate| files.contains(candidate.as_path()))
+}
+
+fn root_name(project_path: &Path, file: &Path) -> String {
+    let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or("unknown");
+    let parent = file.parent().filter(|parent| *parent != project_path);
+    match (stem, parent.and_then(|parent| parent.file_name()).and_then(|name| name.to_str())) {
+        ("main" | "lib", Some(directory)) => directory.to_string(),
+        _ => stem.to_string(),
+    }
+}
@@ -1,14 +1,80 @@
-use std::collections::HashMap;
-use crate::types::{ArchitectureNode, DependencyEdge, NodeMetrics, ArchitectureMetrics};
+use std::collections::{BTreeMap, HashMap};
+use crate::{
+    config::ComplexityWeights,
+    types::{ArchitectureNode, DependencyEdge, NodeMetrics, ArchitectureMetrics},
+};
 
 /// Calculates various metrics for architecture analysis
 pub struct MetricsCalculator {
-    // Add any state needed for metrics calculation
+    complexity_weights: ComplexityWeights,
+}
+
+impl Default for MetricsCalculator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl MetricsCalculator {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            complexity_weights: ComplexityWeights::default(),
+        }
+    }
+
+    /// Use custom weights for the complexity score
+    pub fn with_complexity_weights(mut self, weights: ComplexityWeights) -> Self {
+        self.complexity_weights = weights;
+        self
+    }
+
+    /// Human-readable formula of every reported metric, with the configured weights filled in
+    pub fn metric_formulas(&self) -> BTreeMap<&'static str, String> {
+        let w = &self.complexity_weights;
+        BTreeMap::from([
+            ("lines_of_code", "non-empty lines that do not start with `//` or `/*`".to_string()),
+            (
+                "complexity_score",
+                format!(
+                    "{} + {}·if + {}·match + {}·for + {}·while + {}·loop + {}·`{{{{` + {}·[contains async] + {}·`?.` + {}·unwrap() + {}·expect(",
+                    w.base,
+                    w.if_statement,
+                    w.match_expression,
+                    w.for_loop,
+                    w.while_loop,
+                    w.loop_expression,
+                    w.nesting,
+                    w.async_code,
+                    w.try_operator,
+                    w.unwrap,
+                    w.expect
+                ),
+            ),
+            (
+                "cyclomatic_complexity",
+                "1 + if + match + for + while + loop + `&&` + `||`".to_string(),
+            ),
+            (
+                "cognitive_complexity",
+                "sum over control-flow lines of (1 for if/for/while, 1.5 for loop, 2 for match) + brace nesting depth".to_string(),
+            ),
+            ("error_count", "panic! + unwrap(), replaced by compiler errors when [diagnostics] is enabled or a watch command runs".to_string()),
+            ("warning_count", "#[warn( attributes, replaced by compiler warnings when [diagnostics] is enabled or a watch command runs".to_string()),
+            ("dependency_density", "edges / (modules · (modules - 1))".to_string()),
+            (
+                "modularity_score",
+                "Shannon entropy of the module type distribution / log2(number of module types)".to_string(),
+            ),
+            (
+                "maintainability_index",
+                "(min(1, 1000 / total lines) + min(1, 10 / average complexity)) / 2".to_string(),
+            ),
+            ("robustness_score", crate::scanner::robustness::formula()),
+            (
+                "health_score",
+                "100 · (0.5·maintainability_index + 0.3·(1 - circular edges / edges) + 0.2·(1 - modules with errors / modules))".to_string(),
+            ),
+        ])
     }
 
     /// Calculate metrics for a single node
@@ -37,6 +103,10 @@ impl MetricsCalculator {
             dependent_count: 0,  // Will be updated by dependency analyzer
             cyclomatic_complexity: self.calculate_cyclomatic_complexity(content),
             cognitive_complexity: self.calculate_cognitive_complexity(content),
+            recent_churn: 0, // Populated by git analysis when available
+            commit_count: 0,
+            last_author: None,
+            clippy_lints: None, // Populated by the clippy pass when enabled
         }
     }
 
@@ -51,13 +121,22 @@ impl MetricsCalculator {
         let total_enums = nodes.values().map(|n| n.metrics.enum_count).sum();
         let total_traits = nodes.values().map(|n| n.metrics.trait_count).sum();
         
-        let complexities: Vec<f64> = nodes.values().map(|n| n.metrics.complexity_score).collect();
+        let complexities: Vec<f64> = nodes
+            .values()
+            .filter(|n| !n.module_type.is_generated())
+            .map(|n| n.metrics.complexity_score)
+            .collect();
         let max_complexity = complexities.iter().cloned().fold(0.0, f64::max);
         let min_complexity = complexities.iter().cloned().fold(f64::INFINITY, f64::min);
         
         let dependency_density = self.calculate_dependency_density(nodes, edges);
         let modularity_score = self.calculate_modularity_score(nodes, edges);
         let maintainability_index = self.calculate_maintainability_index(nodes);
+        let health_score = self.calculate_health_score(nodes, edges, maintainability_index);
+        let mut edges_by_relationship = BTreeMap::new();
+        for edge in edges {
+            *edges_by_relationship.entry(edge.relationship.clone()).or_insert(0) += 1;
+        }
         
         ArchitectureMetrics {
             total_functions,
@@ -69,9 +148,17 @@ impl MetricsCalculator {
             dependency_density,
             modularity_score,
             maintainability_index,
+            health_score,
+            edges_by_relationship,
         }
     }
 
+    /// Health score of any set of modules, such as the ones of a single crate
+    pub fn health_score(&self, nodes: &HashMap<String, ArchitectureNode>, edges: &[DependencyEdge]) -> f64 {
+        let maintainability_index = self.calculate_maintainability_index(nodes);
+        self.calculate_health_score(nodes, edges, maintainability_index)
+    }
+
     /// Count lines of code (excluding comments and empty lines)
     fn count_lines_of_code(&self, content: &str) -> usize {
         content
@@ -85,27 +172,28 @@ impl MetricsCalculator {
 
     /// Calculate complexity score based on various factors
     fn calculate_complexity(&self, content: &str) -> f64 {
-        let mut complexity = 1.0;
+        let w = &self.complexity_weights;
+        let mut complexity = w.base;
         
         // Add complexity for control structures
-        complexity += content.matches("if ").count() as f64 * 0.5;
-        complexity += content.matches("match ").count() as f64 * 0.8;
-        complexity += content.matches("for ").count() as f64 * 0.6;
-        complexity += content.matches("while ").count() as f64 * 0.7;
-        complexity += content.matches("loop ").count() as f64 * 0.8;
+        complexity += content.matches("if ").count() as f64 * w.if_statement;
+        complexity += content.matches("match ").count() as f64 * w.match_expression;
+        complexity += content.matches("for ").count() as f64 * w.for_loop;
+        complexity += content.matches("while ").count() as f64 * w.while_loop;
+        complexity += content.matches("loop ").count() as f64 * w.loop_expression;
         
         // Add complexity for nested structures
-        complexity += content.matches("{{").count() as f64 * 0.1;
+        complexity += content.matches("{{").count() as f64 * w.nesting;
         
         // Add complexity for async/await
         if content.contains("async") {
-            complexity += 0.5;
+            complexity += w.async_code;
         }
         
         // Add complexity for error handling
-        complexity += content.matches("?.").count() as f64 * 0.2;
-        complexity += content.matches("unwrap()").count() as f64 * 0.1;
-        complexity += content.matches("expect(").count() as f64 * 0.1;
+        complexity += content.matches("?.").count() as f64 * w.try_operator;
+        complexity += content.matches("unwrap()").count() as f64 * w.unwrap;
+        complexity += content.matches("expect(").count() as f64 * w.expect;
         
         complexity
     }
@@ -208,7 +296,7 @@ impl MetricsCalculator {
     fn calculate_modularity_score(
         &self,
         nodes: &HashMap<String, ArchitectureNode>,
-        edges: &[DependencyEdge],
+        _edges: &[DependencyEdge],
     ) -> f64 {
         if nodes.is_empty() {
             return 0.0;
@@ -234,6 +322,19 @@ impl MetricsCalculator {
         entropy / (type_counts.len() as f64).log2().max(1.0)
     }
 
+    /// Mean complexity of the hand-written modules
+    pub fn average_complexity(nodes: &HashMap<String, ArchitectureNode>) -> f64 {
+        let complexities: Vec<f64> = nodes
+            .values()
+            .filter(|n| !n.module_type.is_generated())
+            .map(|n| n.metrics.complexity_score)
+            .collect();
+        if complexities.is_empty() {
+            return 0.0;
+        }
+        complexities.iter().sum::<f64>() / complexities.len() as f64
+    }
+
     /// Calculate maintainability index
     fn calculate_maintainability_index(&self, nodes: &HashMap<String, ArchitectureNode>) -> f64 {
         if nodes.is_empty() {
@@ -241,9 +342,7 @@ impl MetricsCalculator {
         }
         
         let total_lines = nodes.values().map(|n| n.metrics.lines_of_code).sum::<usize>() as f64;
-        let avg_complexity = nodes.values()
-            .map(|n| n.metrics.complexity_score)
-            .sum::<f64>() / nodes.len() as f64;
+        let avg_complexity = Self::average_complexity(nodes);
         
         // Simple maintainability index (higher is better)
         let lines_factor = (1000.0 / total_lines.max(1.0)).min(1.0);
@@ -251,4 +350,25 @@ impl MetricsCalculator {
         
         (lines_factor + complexity_factor) / 2.0
     }
+
+    /// Blend maintainability, cycle freedom and error freedom into a 0-100 score
+    fn calculate_health_score(
+        &self,
+        nodes: &HashMap<String, ArchitectureNode>,
+        edges: &[DependencyEdge],
+        maintainability_index: f64,
+    ) -> f64 {
+        if nodes.is_empty() {
+            return 0.0;
+        }
+        
+        let circular_ratio = if edges.is_empty() {
+            0.0
+        } else {
+            edges.iter().filter(|edge| edge.is_circular).count() as f64 / edges.len() as f64
+        };
+        let error_ratio = nodes.values().filter(|n| n.metrics.error_count > 0).count() as f64 / nodes.len() as f64;
+        
+        100.0 * (0.5 * maintainability_index + 0.3 * (1.0 - circular_ratio) + 0.2 * (1.0 - error_ratio))
+    }
 }
@@ -1,13 +1,53 @@
 pub mod rust_scanner;
 pub mod dependency_analyzer;
 pub mod metrics_calculator;
+#[cfg(feature = "web")]
+pub mod command_hook;
+pub mod api_leaks;
+pub mod cargo_manifest;
+pub mod cfg;
+pub mod classification;
+pub mod clippy_analyzer;
+pub mod cohesion;
+pub mod components;
+pub mod data_access;
+pub mod diagnostics;
+pub mod directories;
+pub mod loose_tree;
+pub mod module_tree;
+pub mod documentation;
+pub mod doc_coverage;
+pub mod external_crates;
+pub mod generated;
+pub mod git_analyzer;
+pub mod groups;
+pub mod ignored;
+pub mod incremental;
+pub mod naming;
+pub mod orphans;
+pub mod ownership;
+pub mod provenance;
+pub mod reachability;
+pub mod relationships;
+pub mod robustness;
+pub mod routes;
+pub mod scan_hook;
+pub mod source_tree;
+pub mod syn_parser;
+pub mod test_targets;
+pub mod variants;
+#[cfg(feature = "watch")]
+pub mod watcher;
 
 use anyhow::Result;
 use std::path::Path;
 use crate::types::ArchitectureMap;
 use crate::config::ProjectConfig;
 
-pub use rust_scanner::ArchitectureScanner;
+pub use rust_scanner::{ArchitectureScanner, FileExclusion, ScanProgress};
+#[cfg(feature = "web")]
+pub use command_hook::CommandHook;
+pub use scan_hook::ScanHook;
 
 /// Trait for different types of project scanners
 #[async_trait::async_trait]
@@ -23,10 +63,17 @@ pub fn create_scanner<P: AsRef<Path>>(
 ) -> Result<Box<dyn ProjectScanner + Send + Sync>> {
     let project_path = project_path.as_ref();
     
-    // Check if it's a Rust project
-    if project_path.join("Cargo.toml").exists() {
+    // Cargo projects, or loose trees of .rs files whose modules are inferred from `mod` declarations
+    if project_path.join("Cargo.toml").exists() || contains_rust_files(project_path) {
         Ok(Box::new(ArchitectureScanner::new(project_path, config)))
     } else {
-        Err(anyhow::anyhow!("Unsupported project type. Only Rust projects are currently supported."))
+        Err(anyhow::anyhow!("Unsupported project type. No Cargo.toml or .rs files found."))
     }
 }
+
+fn contains_rust_files(path: &Path) -> bool {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "rs"))
+}
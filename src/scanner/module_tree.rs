@@ -0,0 +1,116 @@
+//! Canonical module paths, and resolution of `use` and `mod` paths against them.
+//!
+//! Each file gets the path its crate root reaches it by, following `mod`
+//! declarations the way rustc does: `foo.rs` and `foo/mod.rs` are both `foo`,
+//! the submodules of a non-`mod.rs` file live in a directory named after it,
+//! and `#[path]` attributes win over both. Every crate root (`lib.rs`,
+//! `main.rs`, each binary, test and bench) starts a tree of its own.
+//!
+//! A path resolves to the deepest module of the source's tree it names, so
+//! `crate::scanner::rust_scanner::Scanner` and `super::rust_scanner` both point at
+//! `scanner::rust_scanner`. Paths that only reach the crate root, name nothing
+//! in the tree, or start at another crate resolve to nothing.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::{scanner::loose_tree::InferredModule, types::ArchitectureNode};
+
+/// Record each module's place in the module tree inferred by [`loose_tree::infer_modules`](crate::scanner::loose_tree::infer_modules)
+pub fn assign(nodes: &mut HashMap<String, ArchitectureNode>, modules: &HashMap<PathBuf, InferredModule>, project_path: &Path) {
+    for node in nodes.values_mut() {
+        let module = modules.get(&project_path.join(&node.file_path));
+        node.module_path = module.map(|module| module.module_path.clone()).unwrap_or_default();
+        node.module_root = module
+            .map(|module| module.root_file.strip_prefix(project_path).unwrap_or(&module.root_file).to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+}
+
+/// The module `path` names, seen from the module at `module_path`.
+///
+/// `is_module` tells whether a canonical path exists in the source's tree. Bare
+/// paths must start with a child module, the way 2018-edition paths do.
+pub fn resolve(module_path: &str, path: &str, is_module: impl Fn(&str) -> bool) -> Option<String> {
+    let mut segments = path.split("::").map(str::trim).filter(|segment| !segment.is_empty()).peekable();
+    let mut resolved: Vec<&str> = module_path.split("::").filter(|segment| !segment.is_empty()).collect();
+    let mut deepest = None;
+
+    match segments.peek().copied()? {
+        "crate" => {
+            segments.next();
+            resolved.clear();
+        }
+        "self" | "super" => {
+            let mut ascended = false;
+            while let Some(&segment) = segments.peek() {
+                match segment {
+                    "self" => {}
+                    "super" => {
+                        resolved.pop()?;
+                        ascended = true;
+                    }
+                    _ => break,
+                }
+                segments.next();
+            }
+            // `use super::*` depends on the parent itself, unless that is the crate root
+            if ascended && !resolved.is_empty() {
+                deepest = Some(resolved.join("::"));
+            }
+        }
+        _ => {}
+    }
+
+    for segment in segments {
+        resolved.push(segment);
+        let candidate = resolved.join("::");
+        if !is_module(&candidate) {
+            break;
+        }
+        deepest = Some(candidate);
+    }
+    deepest.filter(|deepest| deepest != module_path)
+}
+
+/// Byte ranges of the bodies of inline `mod name { .. }` items in `content`
+pub fn inline_module_spans(content: &str) -> Vec<Range<usize>> {
+    let inline_regex = Regex::new(r"\bmod\s+\w+\s*\{").unwrap();
+    inline_regex
+        .find_iter(content)
+        .filter_map(|item| {
+            let body = item.end() - 1;
+            let mut depth = 0usize;
+            for (index, c) in content[body..].char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' if depth == 1 => return Some(item.end()..body + index),
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+/// `path` from a `use` item `depth` inline modules deep, seen from the module of
+/// the whole file instead; `None` when it points into those inline modules
+pub fn file_relative(path: &str, depth: usize) -> Option<String> {
+    if depth == 0 || path.split("::").next() == Some("crate") {
+        return Some(path.to_string());
+    }
+    let segments: Vec<&str> = path.split("::").collect();
+    let supers = segments.iter().take_while(|segment| **segment == "super").count();
+    if supers < depth {
+        return None;
+    }
+    match &segments[depth..] {
+        // Back at the file's own module
+        [] => None,
+        rest if rest[0] == "super" => Some(rest.join("::")),
+        rest => Some(format!("self::{}", rest.join("::"))),
+    }
+}
@@ -0,0 +1,64 @@
+//! Module naming rules from the `[naming]` config section.
+
+use regex::Regex;
+use std::path::{Component, Path};
+
+use crate::config::NamingSettings;
+
+/// File stems that name their parent module rather than a module of their own
+const MODULE_ROOT_FILES: &[&str] = &["mod", "lib", "main"];
+
+/// Rules from `settings` that the module at `relative_path` breaks, as messages
+pub fn check_module_path(relative_path: &Path, settings: &NamingSettings) -> Vec<String> {
+    let segments = module_segments(relative_path);
+    let mut violations = Vec::new();
+
+    if settings.snake_case_files {
+        let snake_case = Regex::new(r"^[a-z_][a-z0-9_]*$").unwrap();
+        for segment in segments.iter().filter(|segment| !snake_case.is_match(segment)) {
+            violations.push(format!("`{}` is not snake_case", segment));
+        }
+    }
+
+    if let Some(name) = segments.last() {
+        if settings.forbidden_modules.iter().any(|forbidden| forbidden == name) {
+            violations.push(format!("module name `{}` is not allowed", name));
+        }
+    }
+
+    if let Some(limit) = settings.max_path_depth {
+        if segments.len() > limit {
+            violations.push(format!("module depth {} exceeds {}", segments.len(), limit));
+        }
+    }
+
+    violations
+}
+
+/// Module path of a file below its crate's `src/`, e.g. `["scanner", "naming"]`.
+///
+/// Files outside any `src/` directory (tests, benches, loose trees) only
+/// contribute their own module name.
+fn module_segments(relative_path: &Path) -> Vec<String> {
+    let components: Vec<String> = relative_path
+        .with_extension("")
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+
+    let within_src = components.iter().rposition(|part| part == "src");
+    let mut segments = match within_src {
+        Some(src) => components[src + 1..].to_vec(),
+        None => components[components.len().saturating_sub(2)..].to_vec(),
+    };
+    if segments.last().is_some_and(|last| MODULE_ROOT_FILES.contains(&last.as_str())) {
+        // `mod.rs` is named after its directory, `src/lib.rs` and `src/main.rs` are crate roots
+        segments.pop();
+    } else if within_src.is_none() {
+        segments.drain(..segments.len().saturating_sub(1));
+    }
+    segments
+}
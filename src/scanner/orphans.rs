@@ -0,0 +1,57 @@
+//! Modules no compiled target reaches, for finding dead code at the module level.
+//!
+//! The walk starts at every library and binary root, example and build script,
+//! and follows the dependency edges, `mod` declarations included. A file no
+//! `mod` declares, and any module only such files use, is left over. Test
+//! targets are not starting points, but they and ignored, generated and
+//! external modules are never reported.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::types::{ArchitectureNode, DependencyEdge, NodeStatus};
+
+/// Mark every module no target reaches `Inactive` and return their ids, sorted by file path
+pub fn mark(nodes: &mut HashMap<String, ArchitectureNode>, edges: &[DependencyEdge]) -> Vec<String> {
+    let mut dependencies: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in edges {
+        dependencies.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+    }
+    let mut pending: Vec<&str> = nodes.values().filter(|node| is_target_root(node)).map(|node| node.id.as_str()).collect();
+    let mut reached: HashSet<&str> = pending.iter().copied().collect();
+    while let Some(id) = pending.pop() {
+        for &dependency in dependencies.get(id).into_iter().flatten() {
+            if reached.insert(dependency) {
+                pending.push(dependency);
+            }
+        }
+    }
+
+    let mut orphans: Vec<&ArchitectureNode> = nodes
+        .values()
+        .filter(|node| !reached.contains(node.id.as_str()) && !is_exempt(node))
+        .collect();
+    orphans.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    let orphans: Vec<String> = orphans.into_iter().map(|node| node.id.clone()).collect();
+    for id in &orphans {
+        if let Some(node) = nodes.get_mut(id) {
+            node.status = NodeStatus::Inactive;
+        }
+    }
+    orphans
+}
+
+/// A library or binary root, an example or a build script
+fn is_target_root(node: &ArchitectureNode) -> bool {
+    if node.entry_point.is_some() {
+        return true;
+    }
+    let path = Path::new(&node.file_path);
+    node.module_path.is_empty()
+        && !node.module_root.is_empty()
+        && (path.file_name().is_some_and(|name| name == "build.rs") || path.components().any(|part| part.as_os_str() == "examples"))
+}
+
+fn is_exempt(node: &ArchitectureNode) -> bool {
+    node.ignored || node.module_type.is_test_target() || node.module_type.is_generated() || node.module_type.is_external()
+}
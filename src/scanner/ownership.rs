@@ -0,0 +1,60 @@
+//! Module authorship from `git blame`, used to spot modules only one person knows.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::{config::OwnershipSettings, types::Ownership};
+
+/// Authorship of the file at `relative_path`, or `None` outside git or for untracked files
+pub fn blame(project_path: &Path, relative_path: &str) -> Option<Ownership> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_path)
+        .args(["blame", "--line-porcelain", "--", relative_path])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Every line of `--line-porcelain` output repeats its commit's author
+    let mut lines_by_author: HashMap<String, usize> = HashMap::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(author) = line.strip_prefix("author ") {
+            *lines_by_author.entry(author.to_string()).or_default() += 1;
+        }
+    }
+    summarize(lines_by_author)
+}
+
+/// Flag modules only one person knows that are also hard to change
+pub fn mark_risk(ownership: &mut Ownership, complexity: f64, settings: &OwnershipSettings) {
+    ownership.organizational_risk = ownership.bus_factor == 1 && complexity >= settings.risk_min_complexity;
+}
+
+fn summarize(lines_by_author: HashMap<String, usize>) -> Option<Ownership> {
+    let total: usize = lines_by_author.values().sum();
+    let mut authors: Vec<(String, usize)> = lines_by_author.into_iter().collect();
+    // Most lines first; ties by name so results do not depend on hash order
+    authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let (top_author, top_lines) = authors.first().cloned()?;
+
+    let mut covered = 0;
+    let bus_factor = authors
+        .iter()
+        .take_while(|(_, lines)| {
+            let below_half = covered * 2 <= total;
+            covered += lines;
+            below_half
+        })
+        .count();
+
+    Some(Ownership {
+        top_author,
+        top_author_share: top_lines as f64 / total as f64,
+        authors: authors.len(),
+        bus_factor,
+        organizational_risk: false,
+    })
+}
@@ -0,0 +1,63 @@
+//! Where and how a scan was produced, so stored results can be traced back.
+
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::{config::ProjectConfig, types::ScanProvenance};
+
+/// Collect provenance for a scan of `project_path` that took `duration`
+pub fn collect(project_path: &Path, config: &ProjectConfig, duration: Duration) -> ScanProvenance {
+    let git_commit = git(project_path, &["rev-parse", "HEAD"]);
+    // Only meaningful inside a work tree, where the commit is known
+    let git_dirty = git_commit
+        .as_ref()
+        .and_then(|_| git(project_path, &["status", "--porcelain"]))
+        .map(|status| !status.is_empty());
+
+    ScanProvenance {
+        tool_version: crate::VERSION.to_string(),
+        git_commit,
+        git_dirty,
+        scan_duration_ms: duration.as_millis() as u64,
+        config_hash: config_hash(config),
+        host: hostname(),
+    }
+}
+
+/// Provenance for a scan of files held in memory, which belong to no repository or host
+pub fn in_memory(config: &ProjectConfig, duration: Duration) -> ScanProvenance {
+    ScanProvenance {
+        tool_version: crate::VERSION.to_string(),
+        git_commit: None,
+        git_dirty: None,
+        scan_duration_ms: duration.as_millis() as u64,
+        config_hash: config_hash(config),
+        host: None,
+    }
+}
+
+/// SHA-256 of the effective configuration, defaults included
+pub fn config_hash(config: &ProjectConfig) -> String {
+    let serialized = serde_json::to_vec(config).unwrap_or_default();
+    format!("{:x}", Sha256::digest(&serialized))
+}
+
+/// Trimmed stdout of a successful git command run in `dir`
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
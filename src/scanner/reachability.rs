@@ -0,0 +1,249 @@
+//! Items no entry point reaches, for the dead code percentage of each crate.
+//!
+//! The analysis starts from `main`, tests and benches, and the `pub` items of a
+//! library's public modules. Every name a reached item mentions reaches all
+//! items of that name, in whichever module they are. Names are not resolved
+//! through paths, so items sharing a name are reached together and the result
+//! errs on the side of live code. A trait impl is reached with its type, and
+//! items produced by macros stay invisible.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use syn::{Attribute, ImplItem, Item, Visibility};
+
+use crate::types::{ArchitectureNode, DeadCode, EntryPoint, ItemReferences, ModuleType, ReferencingItem};
+
+/// Items of `content` and the names each of them mentions; empty when it does not parse
+pub fn collect(content: &str) -> ItemReferences {
+    let mut references = ItemReferences::default();
+    if let Ok(file) = syn::parse_file(content) {
+        collect_items(&file.items, Scope { public: true, test: false, top_level: true }, &mut references);
+    }
+    references
+}
+
+/// Where a list of items sits in the file
+#[derive(Clone, Copy)]
+struct Scope {
+    /// Only inside `pub` inline modules
+    public: bool,
+    /// Inside a `#[cfg(test)]` module
+    test: bool,
+    top_level: bool,
+}
+
+fn collect_items(items: &[Item], scope: Scope, references: &mut ItemReferences) {
+    for item in items {
+        let (name, vis, attrs, counted) = match item {
+            Item::Fn(item) => (item.sig.ident.to_string(), &item.vis, &item.attrs, true),
+            Item::Struct(item) => (item.ident.to_string(), &item.vis, &item.attrs, true),
+            Item::Enum(item) => (item.ident.to_string(), &item.vis, &item.attrs, true),
+            Item::Union(item) => (item.ident.to_string(), &item.vis, &item.attrs, true),
+            Item::Trait(item) => (item.ident.to_string(), &item.vis, &item.attrs, true),
+            Item::Const(item) => (item.ident.to_string(), &item.vis, &item.attrs, false),
+            Item::Static(item) => (item.ident.to_string(), &item.vis, &item.attrs, false),
+            Item::Type(item) => (item.ident.to_string(), &item.vis, &item.attrs, false),
+            // Re-exports are reached through the public API, or not at all
+            Item::Use(item) => (String::new(), &item.vis, &item.attrs, false),
+            Item::Macro(item) => (
+                item.ident.as_ref().map(ToString::to_string).unwrap_or_default(),
+                &Visibility::Inherited,
+                &item.attrs,
+                false,
+            ),
+            Item::Impl(item) => {
+                let test = scope.test || is_cfg_test(&item.attrs);
+                if item.trait_.is_some() {
+                    // Called through the trait, so live as long as the type is
+                    references.items.push(ReferencingItem {
+                        name: type_name(&item.self_ty).unwrap_or_default(),
+                        counted: false,
+                        is_public: false,
+                        is_test: test,
+                        mentions: mentions(item.to_token_stream()),
+                    });
+                    continue;
+                }
+                for impl_item in &item.items {
+                    if let ImplItem::Fn(method) = impl_item {
+                        references.items.push(ReferencingItem {
+                            name: method.sig.ident.to_string(),
+                            counted: true,
+                            is_public: scope.public && is_public(&method.vis),
+                            is_test: test || is_test(&method.attrs),
+                            mentions: mentions(method.to_token_stream()),
+                        });
+                    }
+                }
+                continue;
+            }
+            Item::Mod(module) => {
+                match &module.content {
+                    Some((_, items)) => collect_items(
+                        items,
+                        Scope {
+                            public: scope.public && is_public(&module.vis),
+                            test: scope.test || is_cfg_test(&module.attrs),
+                            top_level: false,
+                        },
+                        references,
+                    ),
+                    None if scope.top_level && is_public(&module.vis) => {
+                        references.public_modules.push(module.ident.to_string());
+                    }
+                    None => {}
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        references.items.push(ReferencingItem {
+            name,
+            counted,
+            is_public: scope.public && is_public(vis),
+            is_test: scope.test || is_test(attrs),
+            mentions: mentions(item.to_token_stream()),
+        });
+    }
+}
+
+/// Set `dead_code` on every module from the items reachable from the project's entry points
+pub fn mark_unreachable(nodes: &mut HashMap<String, ArchitectureNode>) {
+    let public_api = public_api(nodes);
+
+    // Test targets, generated and ignored modules are neither counted nor dead
+    let exempt = |node: &ArchitectureNode| {
+        node.ignored || node.module_type.is_test_target() || node.module_type == ModuleType::Generated
+    };
+    let is_root = |node: &ArchitectureNode, item: &ReferencingItem| {
+        exempt(node)
+            || item.is_test
+            || (item.is_public && public_api.contains(node.id.as_str()))
+            || (item.name == "main" && node.module_path.is_empty())
+    };
+
+    let mut by_name: HashMap<&str, Vec<(&str, usize)>> = HashMap::new();
+    let mut reached: HashSet<(&str, usize)> = HashSet::new();
+    let mut pending: Vec<&str> = Vec::new();
+    for node in nodes.values() {
+        for (index, item) in node.item_references.items.iter().enumerate() {
+            by_name.entry(item.name.as_str()).or_default().push((node.id.as_str(), index));
+            if is_root(node, item) {
+                reached.insert((node.id.as_str(), index));
+                pending.extend(item.mentions.iter().map(String::as_str));
+            }
+        }
+    }
+    let mut followed: HashSet<&str> = HashSet::new();
+    while let Some(name) = pending.pop() {
+        if !followed.insert(name) {
+            continue;
+        }
+        for &(node_id, index) in by_name.get(name).into_iter().flatten() {
+            if reached.insert((node_id, index)) {
+                pending.extend(nodes[node_id].item_references.items[index].mentions.iter().map(String::as_str));
+            }
+        }
+    }
+
+    let dead_code: Vec<(String, DeadCode)> = nodes
+        .values()
+        .map(|node| {
+            let counted: Vec<(usize, &ReferencingItem)> = node
+                .item_references
+                .items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.counted && !item.is_test && !exempt(node))
+                .collect();
+            let unreachable_items = counted
+                .iter()
+                .filter(|(index, _)| !reached.contains(&(node.id.as_str(), *index)))
+                .map(|(_, item)| item.name.clone())
+                .collect();
+            (node.id.clone(), DeadCode { item_count: counted.len(), unreachable_items })
+        })
+        .collect();
+    for (node_id, dead_code) in dead_code {
+        if let Some(node) = nodes.get_mut(&node_id) {
+            node.dead_code = dead_code;
+        }
+    }
+}
+
+/// Ids of the modules other crates can name
+pub(crate) fn public_api(nodes: &HashMap<String, ArchitectureNode>) -> HashSet<&str> {
+    let places: HashMap<(&str, &str), &ArchitectureNode> = nodes
+        .values()
+        .map(|node| ((node.module_root.as_str(), node.module_path.as_str()), node))
+        .collect();
+    nodes
+        .values()
+        .filter(|node| is_public_module(node, &places))
+        .map(|node| node.id.as_str())
+        .collect()
+}
+
+/// Whether other crates can name `node`: a library root, or reached from one through `pub mod`s
+fn is_public_module(node: &ArchitectureNode, places: &HashMap<(&str, &str), &ArchitectureNode>) -> bool {
+    let root = places.get(&(node.module_root.as_str(), ""));
+    if root.and_then(|root| root.entry_point) != Some(EntryPoint::Library) {
+        return false;
+    }
+    let segments: Vec<&str> = node.module_path.split("::").filter(|segment| !segment.is_empty()).collect();
+    (0..segments.len()).all(|depth| {
+        places
+            .get(&(node.module_root.as_str(), segments[..depth].join("::").as_str()))
+            .is_some_and(|parent| parent.item_references.public_modules.iter().any(|child| child == segments[depth]))
+    })
+}
+
+/// Every identifier in `tokens`, including those inside macro invocations
+fn mentions(tokens: TokenStream) -> Vec<String> {
+    fn walk(tokens: TokenStream, found: &mut BTreeSet<String>) {
+        for token in tokens {
+            match token {
+                TokenTree::Ident(ident) => {
+                    found.insert(ident.to_string());
+                }
+                TokenTree::Group(group) => walk(group.stream(), found),
+                _ => {}
+            }
+        }
+    }
+    let mut found = BTreeSet::new();
+    walk(tokens, &mut found);
+    found.into_iter().collect()
+}
+
+/// Last path segment of an impl's self type, e.g. `Scanner` for `&mut crate::Scanner<T>`
+fn type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()),
+        syn::Type::Reference(reference) => type_name(&reference.elem),
+        _ => None,
+    }
+}
+
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+/// `#[test]`, `#[bench]` or a runtime's `#[tokio::test]`
+fn is_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path()
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "test" || segment.ident == "bench")
+    }) || is_cfg_test(attrs)
+}
+
+/// `#[cfg(test)]`, also inside `any(..)` or `all(..)`
+fn is_cfg_test(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .any(|attr| mentions(attr.meta.to_token_stream()).iter().any(|ident| ident == "test"))
+}
@@ -0,0 +1,139 @@
+//! What a module takes from the modules it depends on, and the kind of edge that makes.
+//!
+//! Every path of a `use` tree leads through the module the dependency resolves
+//! to and ends at the name it brings in. How the source then uses that
+//! name decides the relationship: implementing it makes `Implements`, calling it
+//! or invoking it as a macro makes `Calls`, and anything else `Imports`. A
+//! `mod` declaration makes `Contains`.
+
+use regex::Regex;
+
+use crate::types::{ArchitectureNode, DependencyType, ImportedItem, ItemUsage};
+
+/// Paths a `use` tree imports, each with the name it brings in and how `content` uses it.
+///
+/// `crate::scanner::{rust_scanner::Scanner, watch}` imports `Scanner` by
+/// `crate::scanner::rust_scanner::Scanner` and `watch` by `crate::scanner::watch`;
+/// a glob imports its module's path and nothing by name.
+pub fn imported_paths(tree: &str, content: &str) -> Vec<(String, Option<ImportedItem>)> {
+    let mut paths = Vec::new();
+    collect_paths(tree, &mut Vec::new(), &mut paths);
+
+    paths
+        .into_iter()
+        .map(|(segments, local_name)| {
+            // Usage is looked up under the name the importing module sees
+            let item = segments.last().zip(local_name).map(|(name, local_name)| ImportedItem {
+                name: name.clone(),
+                usage: usage(content, &local_name),
+            });
+            (segments.join("::"), item)
+        })
+        .collect()
+}
+
+/// Relationship of an edge to `target` made by `items`
+pub fn classify(items: &[ImportedItem], target: &ArchitectureNode) -> DependencyType {
+    let has = |usage: ItemUsage| items.iter().any(|item| item.usage == usage);
+    if has(ItemUsage::Declared) {
+        DependencyType::Contains
+    } else if has(ItemUsage::Implemented) {
+        DependencyType::Implements
+    } else if has(ItemUsage::Invoked)
+        || has(ItemUsage::Called)
+        || items.iter().any(|item| target.functions.iter().any(|function| function.name == item.name))
+    {
+        DependencyType::Calls
+    } else {
+        DependencyType::Imports
+    }
+}
+
+fn usage(content: &str, local_name: &str) -> ItemUsage {
+    let name = regex::escape(local_name);
+    let implemented = Regex::new(&format!(r"\bimpl\b(?:\s*<[^{{;]*?>)?\s+(?:\w+::)*{}\b[^{{;]*\bfor\b", name)).unwrap();
+    let invoked = Regex::new(&format!(r"\b{}!\s*[(\[{{]", name)).unwrap();
+    let called = Regex::new(&format!(r"(?:^|[^\w.:]){}\s*(?:::<[^>]*>\s*)?\(", name)).unwrap();
+
+    if implemented.is_match(content) {
+        return ItemUsage::Implemented;
+    }
+    if invoked.is_match(content) {
+        return ItemUsage::Invoked;
+    }
+    let is_module = local_name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_');
+    let module_call = is_module
+        && Regex::new(&format!(r"(?:^|[^\w.:]){}::[a-z_]\w*\s*\(", name)).unwrap().is_match(content);
+    if called.is_match(content) || module_call {
+        ItemUsage::Called
+    } else {
+        ItemUsage::Named
+    }
+}
+
+/// Paths of a use tree as their segments and the name they are imported under,
+/// which is `None` for globs
+fn collect_paths(tree: &str, prefix: &mut Vec<String>, paths: &mut Vec<(Vec<String>, Option<String>)>) {
+    let tree = tree.trim();
+    if let Some(group) = tree.strip_prefix('{') {
+        let group = group.strip_suffix('}').unwrap_or(group);
+        for item in split_top_level(group) {
+            collect_paths(item, prefix, paths);
+        }
+        return;
+    }
+
+    // Everything up to a nested group is a path prefix
+    let (path, rest) = match tree.find('{') {
+        Some(index) => (&tree[..index], Some(&tree[index..])),
+        None => (tree, None),
+    };
+    let (path, alias) = match path.split_once(" as ") {
+        Some((path, alias)) => (path.trim(), Some(alias.trim().to_string())),
+        None => (path.trim(), None),
+    };
+    let depth = prefix.len();
+    for segment in path.split("::").map(str::trim).filter(|segment| !segment.is_empty()) {
+        match segment {
+            "self" => {}
+            "*" => {
+                if !prefix.is_empty() {
+                    paths.push((prefix.clone(), None));
+                }
+                prefix.truncate(depth);
+                return;
+            }
+            _ => prefix.push(segment.to_string()),
+        }
+    }
+    match rest {
+        Some(group) => collect_paths(group, prefix, paths),
+        None if prefix.len() > depth || path.ends_with("self") => {
+            if let Some(last) = prefix.last() {
+                let local_name = alias.unwrap_or_else(|| last.clone());
+                paths.push((prefix.clone(), Some(local_name)));
+            }
+        }
+        None => {}
+    }
+    prefix.truncate(depth);
+}
+
+/// Split on the commas outside nested groups
+fn split_top_level(group: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (index, c) in group.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                items.push(&group[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&group[start..]);
+    items.into_iter().filter(|item| !item.trim().is_empty()).collect()
+}
@@ -0,0 +1,103 @@
+//! Robustness of a module: unsafe code and panic points, weighted up where external input is handled.
+//!
+//! `#[cfg(test)]` modules and comments are left out, since unwrapping in tests
+//! is expected and doc comments often name the macros they warn about.
+
+use regex::Regex;
+
+use crate::types::Robustness;
+
+const UNSAFE_WEIGHT: f64 = 5.0;
+const PANIC_WEIGHT: f64 = 3.0;
+const UNWRAP_WEIGHT: f64 = 2.0;
+const EXPECT_WEIGHT: f64 = 1.0;
+/// Panics on a path that handles external input can be triggered from outside
+const INPUT_FACTOR: f64 = 1.5;
+
+/// Calls and types that bring data from outside the process into a module
+const INPUT_MARKERS: &[&str] = &[
+    "std::env::",
+    "env::var",
+    "env::args",
+    "stdin()",
+    "fs::read",
+    "File::open",
+    "TcpStream",
+    "UdpSocket",
+    "Json<",
+    "Query<",
+    "Form<",
+    "extract::",
+    "from_slice(",
+    "from_reader(",
+];
+
+/// Count the panic points and unsafe blocks of `content` and score them
+pub fn assess(content: &str) -> Robustness {
+    let content: String = without_test_modules(content)
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("//"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let unsafe_regex = Regex::new(r"\bunsafe\s*(?:\{|fn\b|impl\b)").unwrap();
+    let panic_regex = Regex::new(r"\b(?:panic|unreachable|todo|unimplemented)!").unwrap();
+
+    let mut robustness = Robustness {
+        unsafe_blocks: unsafe_regex.find_iter(&content).count(),
+        unwraps: content.matches(".unwrap()").count(),
+        expects: content.matches(".expect(").count(),
+        panics: panic_regex.find_iter(&content).count(),
+        handles_input: INPUT_MARKERS.iter().any(|marker| content.contains(marker)),
+        score: 0.0,
+    };
+    robustness.score = score(&robustness);
+    robustness
+}
+
+/// 100 minus the weighted panic points, never below 0
+fn score(robustness: &Robustness) -> f64 {
+    let penalty = UNSAFE_WEIGHT * robustness.unsafe_blocks as f64
+        + PANIC_WEIGHT * robustness.panics as f64
+        + UNWRAP_WEIGHT * robustness.unwraps as f64
+        + EXPECT_WEIGHT * robustness.expects as f64;
+    let factor = if robustness.handles_input { INPUT_FACTOR } else { 1.0 };
+    (100.0 - penalty * factor).max(0.0)
+}
+
+/// Human-readable formula of `score`
+pub fn formula() -> String {
+    format!(
+        "max(0, 100 - ({}·unsafe + {}·panic-like macros + {}·unwrap() + {}·expect()) · ({} if the module reads external input, else 1)); tests excluded",
+        UNSAFE_WEIGHT, PANIC_WEIGHT, UNWRAP_WEIGHT, EXPECT_WEIGHT, INPUT_FACTOR
+    )
+}
+
+/// `content` without the bodies of `#[cfg(test)]` modules
+fn without_test_modules(content: &str) -> String {
+    let test_module = Regex::new(r"#\[cfg\(test\)\]\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+\w+\s*\{").unwrap();
+    let mut kept = String::with_capacity(content.len());
+    let mut copied = 0;
+    for found in test_module.find_iter(content) {
+        if found.start() < copied {
+            continue;
+        }
+        kept.push_str(&content[copied..found.start()]);
+        let mut depth = 0usize;
+        copied = content.len();
+        for (offset, byte) in content.bytes().enumerate().skip(found.end() - 1) {
+            match byte {
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        copied = offset + 1;
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    kept.push_str(&content[copied.min(content.len())..]);
+    kept
+}
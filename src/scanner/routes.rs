@@ -0,0 +1,114 @@
+//! HTTP endpoints registered by a module with axum or actix-web.
+//!
+//! Recognized forms are axum's `.route("/path", get(handler).post(other))`,
+//! actix's `#[get("/path")]` attributes on handlers and its
+//! `.route("/path", web::get().to(handler))` and
+//! `web::resource("/path").route(web::post().to(handler))` builders. Prefixes
+//! added by `nest` or `scope` are not followed, so paths are as registered.
+
+use regex::Regex;
+
+use crate::types::HttpRoute;
+
+const METHODS: &str = "get|post|put|delete|patch|head|options|trace|any";
+
+/// Endpoints registered in `content`, in source order
+pub fn extract_routes(content: &str) -> Vec<HttpRoute> {
+    let content = without_line_comments(content);
+    let mut routes = Vec::new();
+
+    // axum and actix `.route("/path", ...)`
+    let route_call = Regex::new(r#"\.route\(\s*"([^"]*)"\s*,"#).unwrap();
+    for captures in route_call.captures_iter(&content) {
+        let whole = captures.get(0).unwrap();
+        let arguments = &content[whole.end()..closing_paren(&content, whole.start() + ".route".len())];
+        routes.extend(method_handlers(arguments).into_iter().map(|(method, handler)| HttpRoute {
+            method,
+            path: captures[1].to_string(),
+            handler,
+            line: line_of(&content, whole.start()),
+        }));
+    }
+
+    // actix `web::resource("/path").route(web::get().to(handler))...`
+    let resource = Regex::new(r#"\bresource\(\s*"([^"]*)"\s*\)"#).unwrap();
+    for captures in resource.captures_iter(&content) {
+        let whole = captures.get(0).unwrap();
+        let rest = &content[whole.end()..];
+        let end = [rest.find(';'), rest.find("resource(")].into_iter().flatten().min().unwrap_or(rest.len());
+        let chain = &rest[..end];
+        routes.extend(method_handlers(chain).into_iter().map(|(method, handler)| HttpRoute {
+            method,
+            path: captures[1].to_string(),
+            handler,
+            line: line_of(&content, whole.start()),
+        }));
+    }
+
+    // actix `#[get("/path")] async fn handler`
+    let attribute = Regex::new(&format!(
+        r#"#\[\s*(?:actix_web::)?({})\(\s*"([^"]*)"[^\]]*\]\s*(?:#\[[^\]]*\]\s*)*(?:pub(?:\([^)]*\))?\s+)?(?:async\s+)?fn\s+(\w+)"#,
+        METHODS
+    ))
+    .unwrap();
+    for captures in attribute.captures_iter(&content) {
+        routes.push(HttpRoute {
+            method: captures[1].to_uppercase(),
+            path: captures[2].to_string(),
+            handler: Some(captures[3].to_string()),
+            line: line_of(&content, captures.get(0).unwrap().start()),
+        });
+    }
+
+    routes.sort_by_key(|route| route.line);
+    routes
+}
+
+/// Methods and handlers of an axum method router or actix `web::<method>().to(handler)` calls
+fn method_handlers(arguments: &str) -> Vec<(String, Option<String>)> {
+    let actix = Regex::new(&format!(r"\bweb::({})\(\)\s*\.to\(\s*([\w:]+)", METHODS)).unwrap();
+    let found: Vec<_> = actix
+        .captures_iter(arguments)
+        .map(|captures| (captures[1].to_uppercase(), Some(captures[2].to_string())))
+        .collect();
+    if !found.is_empty() {
+        return found;
+    }
+
+    // Closures leave the handler unnamed
+    let axum = Regex::new(&format!(r"(?:^|[\s.(:])({})(?:_service)?\(\s*([\w:]*)", METHODS)).unwrap();
+    axum.captures_iter(arguments)
+        .map(|captures| (captures[1].to_uppercase(), Some(captures[2].to_string()).filter(|handler| !handler.is_empty())))
+        .collect()
+}
+
+/// Offset of the `)` closing the `(` at `open`, or the end of `content`
+fn closing_paren(content: &str, open: usize) -> usize {
+    let mut depth = 0usize;
+    for (offset, byte) in content.bytes().enumerate().skip(open) {
+        match byte {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return offset;
+                }
+            }
+            _ => {}
+        }
+    }
+    content.len()
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
+/// `content` with `//` comment lines blanked, keeping line numbers
+fn without_line_comments(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| if line.trim_start().starts_with("//") { "" } else { line })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
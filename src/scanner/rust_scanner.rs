@@ -1,23 +1,141 @@
 use anyhow::{Result, Context};
 use std::path::{Path, PathBuf};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use walkdir::WalkDir;
 use regex::Regex;
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
+use rayon::prelude::*;
 
 use crate::{
     types::*,
     config::ProjectConfig,
-    scanner::{ProjectScanner, dependency_analyzer::DependencyAnalyzer, metrics_calculator::MetricsCalculator},
+    rules,
+    schema::SCHEMA_VERSION,
+    scanner::{
+        ProjectScanner,
+        ScanHook,
+        api_leaks,
+        cargo_manifest::{self, TargetKind},
+        cfg,
+        classification,
+        clippy_analyzer,
+        cohesion,
+        components,
+        data_access,
+        diagnostics,
+        directories,
+        dependency_analyzer::{DependencyAnalyzer, NodeIndex},
+        doc_coverage,
+        documentation,
+        external_crates,
+        generated,
+        git_analyzer,
+        groups,
+        ignored,
+        incremental::{CachedFile, FileFingerprint, ScanCache},
+        loose_tree,
+        module_tree,
+        ownership,
+        metrics_calculator::MetricsCalculator,
+        naming,
+        orphans,
+        provenance,
+        reachability,
+        relationships,
+        robustness,
+        routes,
+        source_tree::SourceTree,
+        syn_parser::{self, ParsedItems},
+        test_targets,
+        variants,
+    },
 };
 
+/// Modules a file's `use crate::` and `mod` items depend on
+struct ModuleDependencies {
+    names: Vec<String>,
+    /// Where each dependency is made
+    sites: HashMap<String, Vec<SourceLocation>>,
+    /// What the file takes from each dependency
+    imported_items: HashMap<String, Vec<ImportedItem>>,
+}
+
+/// How far a scan has got through the project's files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanProgress {
+    /// Files parsed or taken from the previous scan so far
+    pub processed_files: usize,
+    pub total_files: usize,
+}
+
+/// Why a Rust file under the project is left out of the scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileExclusion {
+    /// Matched this `scanning.exclude_patterns` glob
+    ExcludePattern(String),
+    /// Matched none of `scanning.include_patterns`
+    NotIncluded,
+    /// Not inside any crate of the workspace
+    OutsideCrates,
+    /// Belongs to the package at this directory, which the workspace leaves out
+    OtherPackage(PathBuf),
+    /// Inside a crate but outside its `src/`, `tests/`, `benches/` and `examples/` targets
+    NotATarget,
+    /// An integration test, with `scanning.include_tests` off
+    Tests,
+    /// A benchmark, with `scanning.include_benches` off
+    Benches,
+    /// Larger than `scanning.max_file_size`
+    TooLarge { size: u64, max_size: usize },
+    /// The file at this path, reached again through a symlink
+    SameFileAs(PathBuf),
+}
+
+impl std::fmt::Display for FileExclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ExcludePattern(pattern) => write!(f, "matches exclude pattern `{}`", pattern),
+            Self::NotIncluded => write!(f, "matches no include pattern"),
+            Self::OutsideCrates => write!(f, "not inside a workspace crate"),
+            Self::OtherPackage(dir) => write!(f, "belongs to the package at {}, outside the workspace", dir.display()),
+            Self::NotATarget => write!(f, "not under a crate target directory or build.rs"),
+            Self::Tests => write!(f, "integration test (include_tests is off)"),
+            Self::Benches => write!(f, "benchmark (include_benches is off)"),
+            Self::TooLarge { size, max_size } => write!(f, "{} bytes, over max_file_size of {}", size, max_size),
+            Self::SameFileAs(path) => write!(f, "same file as {}", path.display()),
+        }
+    }
+}
+
+/// Run `work` on a thread of its own, so a scan does not hold up the async runtime's other tasks
+async fn run_blocking<T: Send + 'static>(work: impl FnOnce() -> T + Send + 'static) -> Result<T> {
+    #[cfg(any(feature = "web", feature = "watch"))]
+    if tokio::runtime::Handle::try_current().is_ok() {
+        return tokio::task::spawn_blocking(work).await.context("The parser threads panicked");
+    }
+    Ok(work())
+}
+
+/// Receives the progress of every scan, from the parser threads
+type ProgressCallback = dyn Fn(ScanProgress) + Send + Sync;
+
 /// Scanner for Rust projects
 pub struct ArchitectureScanner {
     project_path: PathBuf,
     pub config: ProjectConfig,
     dependency_analyzer: DependencyAnalyzer,
     metrics_calculator: MetricsCalculator,
+    /// Parse results of the previous scan, reused by `rescan_async`
+    cache: Mutex<ScanCache>,
+    /// Post-processing registered by embedders, run after every scan
+    hooks: Vec<Arc<dyn ScanHook>>,
+    progress: Option<Arc<ProgressCallback>>,
+    /// Threads parsing the files with `scanning.max_parallelism`; rayon's global pool without it
+    parser_threads: Option<Arc<rayon::ThreadPool>>,
 }
 
 impl ArchitectureScanner {
@@ -25,49 +143,296 @@ impl ArchitectureScanner {
         let project_path = project_path.as_ref().to_path_buf();
         Self {
             dependency_analyzer: DependencyAnalyzer::new(),
-            metrics_calculator: MetricsCalculator::new(),
+            metrics_calculator: MetricsCalculator::new()
+                .with_complexity_weights(config.metrics.complexity.clone()),
             project_path,
+            parser_threads: config.scanning.max_parallelism.and_then(|threads| {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .map_err(|e| tracing::warn!("Failed to start {} parser threads, parsing on the global pool: {}", threads, e))
+                    .ok()
+                    .map(Arc::new)
+            }),
             config,
+            cache: Mutex::default(),
+            hooks: Vec::new(),
+            progress: None,
         }
     }
 
+    /// Copy of this scanner's parsing setup, to move to the parser threads
+    fn parser(&self) -> Self {
+        Self {
+            dependency_analyzer: DependencyAnalyzer::new(),
+            metrics_calculator: MetricsCalculator::new()
+                .with_complexity_weights(self.config.metrics.complexity.clone()),
+            project_path: self.project_path.clone(),
+            config: self.config.clone(),
+            cache: Mutex::default(),
+            hooks: Vec::new(),
+            progress: None,
+            parser_threads: None,
+        }
+    }
+
+    /// Call `progress` after each file a scan parses or reuses
+    pub fn with_progress(mut self, progress: impl Fn(ScanProgress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Run `hook` on the result of every scan, after the hooks registered before it
+    pub fn with_hook(mut self, hook: impl ScanHook + 'static) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
     /// Scan the project and return architecture map
     pub async fn scan_async(&self) -> Result<ArchitectureMap> {
+        self.scan_with(ScanCache::default()).await
+    }
+
+    /// Scan again, re-parsing only the files that changed since the previous scan
+    /// of this scanner and resolving dependencies only where they may have changed
+    pub async fn rescan_async(&self) -> Result<ArchitectureMap> {
+        let previous = std::mem::take(&mut *self.cache.lock().unwrap_or_else(PoisonError::into_inner));
+        self.scan_with(previous).await
+    }
+
+    async fn scan_with(&self, mut previous: ScanCache) -> Result<ArchitectureMap> {
         let start_time = std::time::Instant::now();
+        let mut cache = ScanCache::default();
         
         // Find all Rust files
-        let rust_files = self.find_rust_files()?;
+        let crates = self.workspace_crates(SourceTree::Disk)?;
+        let rust_files = self.find_rust_files_in(&crates, SourceTree::Disk);
         
-        // Parse each file
+        // Parse each file, or take it from the previous scan when it did not change
         let mut nodes = HashMap::new();
-        let all_dependencies: Vec<DependencyEdge> = Vec::new();
+        let mut reparsed = HashSet::new();
+        let cached: Vec<(PathBuf, Option<CachedFile>)> =
+            rust_files.iter().map(|file_path| (file_path.clone(), previous.take_file(file_path))).collect();
+        let parser = self.parser();
+        let progress = self.progress.clone();
+        let pool = self.parser_threads.clone();
+        let loaded = run_blocking(move || {
+            let total_files = cached.len();
+            let processed_files = AtomicUsize::new(0);
+            let load = || {
+                cached
+                    .into_par_iter()
+                    .map(|(file_path, cached)| {
+                        let loaded = parser.load_module(&file_path, cached);
+                        if let Some(progress) = &progress {
+                            progress(ScanProgress {
+                                processed_files: processed_files.fetch_add(1, Ordering::Relaxed) + 1,
+                                total_files,
+                            });
+                        }
+                        (file_path, loaded)
+                    })
+                    .collect::<Vec<_>>()
+            };
+            match pool {
+                Some(pool) => pool.install(load),
+                None => load(),
+            }
+        })
+        .await?;
         
-        for file_path in &rust_files {
-            if let Ok(node) = self.parse_rust_file(file_path).await {
-                let node_id = node.id.clone();
-                nodes.insert(node_id.clone(), node);
+        for (file_path, loaded) in loaded {
+            if let Ok((file, parsed)) = loaded {
+                let node_id = file.node.id.clone();
+                if parsed {
+                    reparsed.insert(node_id.clone());
+                }
+                nodes.insert(node_id, file.node.clone());
+                cache.insert_file(file_path, file);
             }
         }
         
-        // Analyze dependencies
-        let edges = self.dependency_analyzer.analyze_dependencies(&nodes)?;
+        let (mut architecture, cache) = self.build_architecture(nodes, &reparsed, &crates, &rust_files, previous, cache, SourceTree::Disk)?;
+        *self.cache.lock().unwrap_or_else(PoisonError::into_inner) = cache;
+        let duration = start_time.elapsed();
+        architecture.provenance = Some(provenance::collect(&self.project_path, &self.config, duration));
         
-        // Calculate metrics
-        let metrics = self.metrics_calculator.calculate_architecture_metrics(&nodes, &edges);
+        for hook in &self.hooks {
+            hook.post_scan(&mut architecture);
+        }
         
-        // Find circular dependencies
-        let circular_dependencies = self.dependency_analyzer.find_circular_dependencies(&edges);
+        tracing::info!("Scan completed in {:?} ({} of {} files parsed)", duration, reparsed.len(), rust_files.len());
         
-        // Calculate totals
-        let total_modules = nodes.len();
-        let total_lines = nodes.values().map(|n| n.metrics.lines_of_code).sum();
-        let average_complexity = if total_modules > 0 {
-            nodes.values().map(|n| n.metrics.complexity_score).sum::<f64>() / total_modules as f64
+        Ok(architecture)
+    }
+
+    /// Scan `files`, contents by path below the project directory, instead of the directory itself.
+    ///
+    /// Nothing is read from disk and no git or cargo command runs, so churn, ownership,
+    /// compiler diagnostics and clippy lints are left out and the provenance names no commit.
+    pub fn scan_sources(&self, files: &BTreeMap<PathBuf, String>) -> Result<ArchitectureMap> {
+        // `Instant` is not available in the browser
+        let started = Utc::now();
+        let files: BTreeMap<PathBuf, String> = files
+            .iter()
+            .map(|(path, content)| (self.project_path.join(path), content.clone()))
+            .collect();
+        let tree = SourceTree::Memory(&files);
+        let crates = self.workspace_crates(tree)?;
+        let rust_files = self.find_rust_files_in(&crates, tree);
+        
+        let nodes: HashMap<String, ArchitectureNode> = rust_files
+            .par_iter()
+            .filter_map(|file_path| self.parse_rust_file(file_path, &files[file_path], started, tree).ok())
+            .map(|node| (node.id.clone(), node))
+            .collect();
+        let reparsed: HashSet<String> = nodes.keys().cloned().collect();
+        
+        let (mut architecture, _) =
+            self.build_architecture(nodes, &reparsed, &crates, &rust_files, ScanCache::default(), ScanCache::default(), tree)?;
+        let duration = (Utc::now() - started).to_std().unwrap_or_default();
+        architecture.provenance = Some(provenance::in_memory(&self.config, duration));
+        
+        for hook in &self.hooks {
+            hook.post_scan(&mut architecture);
+        }
+        
+        Ok(architecture)
+    }
+
+    /// The architecture of the parsed modules of `rust_files`, with the edge cache for the next scan.
+    ///
+    /// Edges of modules outside `reparsed` are taken from `previous` where nothing changed around them.
+    #[allow(clippy::too_many_arguments)]
+    fn build_architecture(
+        &self,
+        mut nodes: HashMap<String, ArchitectureNode>,
+        reparsed: &HashSet<String>,
+        crates: &[cargo_manifest::CrateInfo],
+        rust_files: &[PathBuf],
+        mut previous: ScanCache,
+        mut cache: ScanCache,
+        tree: SourceTree,
+    ) -> Result<(ArchitectureMap, ScanCache)> {
+        // Attribute each module to its owning crate
+        let mut cargo_features: Vec<CrateFeatures> = crates
+            .iter()
+            .filter(|info| !info.features.is_empty() || !info.optional_dependencies.is_empty())
+            .map(|info| CrateFeatures {
+                crate_name: info.name.clone(),
+                features: info
+                    .features
+                    .iter()
+                    .map(|(name, enables)| FeatureDefinition { name: name.clone(), enables: enables.clone() })
+                    .collect(),
+                optional_dependencies: info.optional_dependencies.clone(),
+            })
+            .collect();
+        cargo_features.sort_by(|a, b| a.crate_name.cmp(&b.crate_name));
+        let mut crate_metadata: Vec<CrateMetadata> = crates
+            .iter()
+            .map(|info| CrateMetadata {
+                name: info.name.clone(),
+                version: info.version.clone(),
+                edition: info.edition.clone(),
+                rust_version: info.rust_version.clone(),
+            })
+            .collect();
+        crate_metadata.sort_by(|a, b| a.name.cmp(&b.name));
+        let modules = loose_tree::infer_modules(&self.project_path, rust_files, tree);
+        module_tree::assign(&mut nodes, &modules, &self.project_path);
+        cfg::assign(&mut nodes);
+        if crates.is_empty() {
+            // Loose tree without a manifest: derive names and roots from `mod` declarations
+            for node in nodes.values_mut() {
+                if let Some(module) = modules.get(&self.project_path.join(&node.file_path)) {
+                    node.name = module.name.clone();
+                    node.crate_name = Some(module.root.clone());
+                    node.entry_point = loose_tree::entry_point(module, node);
+                }
+            }
         } else {
-            0.0
-        };
+            for node in nodes.values_mut() {
+                let absolute_path = self.project_path.join(&node.file_path);
+                let info = cargo_manifest::owning_crate(crates, &absolute_path);
+                if let Some(info) = info.filter(|info| cargo_manifest::is_crate_root(info, &absolute_path)) {
+                    node.name = info.name.clone();
+                }
+                node.crate_name = info.map(|info| info.name.clone());
+                node.entry_point = info.and_then(|info| cargo_manifest::entry_point(info, &absolute_path));
+            }
+        }
+        
+        if self.config.churn.enabled && tree.is_disk() {
+            let histories = git_analyzer::file_histories(&self.project_path, &self.config.churn, Utc::now());
+            git_analyzer::apply(&mut nodes, &histories);
+        }
+        if self.config.diagnostics.enabled && tree.is_disk() {
+            if let Some(diagnostics) = diagnostics::cargo_check(&self.project_path, &self.config.diagnostics) {
+                diagnostics::apply(&mut nodes, &diagnostics);
+            }
+        }
+        if self.config.clippy.enabled && tree.is_disk() {
+            if let Some(lints) = clippy_analyzer::lint_counts(&self.project_path, &self.config.clippy) {
+                clippy_analyzer::apply(&mut nodes, &lints);
+            }
+        }
+        
+        classification::apply_rules(&mut nodes, &self.config.classification)?;
+        components::apply_conventions(&mut nodes, &self.config.components)?;
+        groups::apply(&mut nodes, &self.config.groups)?;
+        ignored::apply_rules(&mut nodes, &self.config.ignore)?;
+        reachability::mark_unreachable(&mut nodes);
+        api_leaks::detect(&mut nodes);
+        self.dependency_analyzer.resolve_impls(&mut nodes);
+        
+        // Analyze dependencies, reusing the edges of modules nothing changed around
+        let stale = previous.stale_sources(&nodes, reparsed);
+        let index = NodeIndex::new(&nodes);
+        let cached: Vec<(&String, Option<Vec<DependencyEdge>>)> = nodes
+            .keys()
+            .map(|node_id| (node_id, previous.take_edges(node_id).filter(|_| !stale.contains(node_id))))
+            .collect();
+        let resolved: Vec<(&String, Vec<DependencyEdge>)> = cached
+            .into_par_iter()
+            .map(|(node_id, cached)| {
+                let resolved = cached.unwrap_or_else(|| self.dependency_analyzer.edges_from(&index, &nodes[node_id]));
+                (node_id, resolved)
+            })
+            .collect();
+        let mut edges = Vec::new();
+        for (node_id, resolved) in resolved {
+            edges.extend(resolved.iter().cloned());
+            cache.insert_edges(node_id.clone(), resolved);
+        }
+        cache.record_resolution(&nodes);
+        // Ignored modules keep their edges, but none of them is part of a cycle. The edges added
+        // below start at test targets or end at external crates, so they close no cycle either
+        let circular_dependencies = self.dependency_analyzer.find_circular_dependencies(&ignored::counted_edges(&nodes, &edges));
+        self.dependency_analyzer.mark_circular(&mut edges, &circular_dependencies);
+        edges.extend(test_targets::exercise_edges(&nodes));
+        if self.config.scanning.include_external_crates {
+            external_crates::attach(&mut nodes, &mut edges, crates);
+        }
+        self.dependency_analyzer.update_node_links(&mut nodes, &edges);
+        let orphans = orphans::mark(&mut nodes, &edges);
+        rules::apply_layers(&mut nodes, &mut edges, &self.config.rules)?;
+        documentation::apply_rules(&mut nodes, &self.config.documentation)?;
+        
+        // Calculate metrics over the modules that are not ignored
+        let counted_nodes = ignored::counted_nodes(&nodes);
+        let counted_edges = ignored::counted_edges(&nodes, &edges);
+        let metrics = self.metrics_calculator.calculate_architecture_metrics(&counted_nodes, &counted_edges);
+        let variants = variants::evaluate(&nodes, &edges, &cargo_features, &self.config.scanning.variants, &self.dependency_analyzer);
+        
+        // Calculate totals
+        let total_modules = counted_nodes.len();
+        let total_lines = counted_nodes.values().map(|n| n.metrics.lines_of_code).sum();
+        let average_complexity = MetricsCalculator::average_complexity(&counted_nodes);
+        let module_groups = directories::build(&nodes);
         
         let architecture = ArchitectureMap {
+            schema_version: SCHEMA_VERSION,
             nodes,
             edges,
             last_scan: Utc::now(),
@@ -76,72 +441,236 @@ impl ArchitectureScanner {
             average_complexity,
             circular_dependencies,
             metrics,
+            hook_outcome: None,
+            cargo_features,
+            crates: crate_metadata,
+            provenance: None, // Filled by the caller, which knows where the files came from
+            anomalies: Vec::new(), // Filled by callers that can compare with the stored history
+            previous_crate_health: BTreeMap::new(),
+            orphans,
+            variants,
+            module_groups,
         };
         
-        let duration = start_time.elapsed();
-        tracing::info!("Scan completed in {:?}", duration);
+        Ok((architecture, cache))
+    }
+
+    /// Root directory being scanned
+    pub fn project_path(&self) -> &Path {
+        &self.project_path
+    }
+
+    /// Formulas of the metrics this scanner reports
+    pub fn metric_formulas(&self) -> std::collections::BTreeMap<&'static str, String> {
+        self.metrics_calculator.metric_formulas()
+    }
+
+    /// Cheap fingerprint of the scanned sources (paths, sizes and mtimes) used to detect changes
+    pub fn source_fingerprint(&self) -> Result<u64> {
+        use std::hash::{Hash, Hasher};
         
-        Ok(architecture)
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        for file_path in self.find_rust_files()? {
+            file_path.hash(&mut hasher);
+            if let Ok(metadata) = std::fs::metadata(&file_path) {
+                metadata.len().hash(&mut hasher);
+                if let Ok(modified) = metadata.modified() {
+                    modified.hash(&mut hasher);
+                }
+            }
+        }
+        
+        Ok(hasher.finish())
+    }
+
+    /// Crates of the project, without packages its workspaces leave out
+    fn workspace_crates(&self, tree: SourceTree) -> Result<Vec<cargo_manifest::CrateInfo>> {
+        let mut crates = cargo_manifest::discover_crates(&self.project_path, tree)?;
+        let skipped: Vec<PathBuf> = crates
+            .iter()
+            .filter(|info| !info.workspace_member)
+            .map(|info| info.root.clone())
+            .collect();
+        // Crates nested in a skipped package go with it
+        crates.retain(|info| info.workspace_member && !skipped.iter().any(|root| info.root.starts_with(root)));
+        for root in &skipped {
+            tracing::debug!("Skipping {}: not a workspace member", root.display());
+        }
+        Ok(crates)
     }
 
     /// Find all Rust files in the project
     fn find_rust_files(&self) -> Result<Vec<PathBuf>> {
+        Ok(self.find_rust_files_in(&self.workspace_crates(SourceTree::Disk)?, SourceTree::Disk))
+    }
+
+    /// Find the Rust files of `crates`: their `src/`, `tests/`, `benches/` and
+    /// `examples/` targets and build scripts. Without any crate manifest every
+    /// Rust file under the project counts.
+    fn find_rust_files_in(&self, crates: &[cargo_manifest::CrateInfo], tree: SourceTree) -> Vec<PathBuf> {
+        self.file_plan_in(crates, tree)
+            .into_iter()
+            .filter(|(_, exclusion)| exclusion.is_none())
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Every Rust file under the project, with the reason a scan would leave it out
+    pub fn file_plan(&self) -> Result<Vec<(PathBuf, Option<FileExclusion>)>> {
+        Ok(self.file_plan_in(&self.workspace_crates(SourceTree::Disk)?, SourceTree::Disk))
+    }
+
+    fn file_plan_in(&self, crates: &[cargo_manifest::CrateInfo], tree: SourceTree) -> Vec<(PathBuf, Option<FileExclusion>)> {
+        let is_rust = |path: &Path| path.extension().is_some_and(|ext| ext == "rs");
+        let files: Vec<PathBuf> = match tree {
+            SourceTree::Disk => self.walk_rust_files(),
+            SourceTree::Memory(files) => {
+                files.keys().filter(|path| path.starts_with(&self.project_path) && is_rust(path)).cloned().collect()
+            }
+        };
+
+        let mut plan: Vec<(PathBuf, Option<FileExclusion>)> = files
+            .into_iter()
+            .map(|path| {
+                let exclusion = self.exclusion(crates, &path, tree);
+                (path, exclusion)
+            })
+            .collect();
+        if tree.is_disk() {
+            self.exclude_duplicates(&mut plan);
+        }
+        plan
+    }
+
+    /// Every Rust file under the project directory, in file name order
+    fn walk_rust_files(&self) -> Vec<PathBuf> {
         let mut files = Vec::new();
-        
-        for entry in WalkDir::new(&self.project_path)
+        let walker = WalkDir::new(&self.project_path)
             .follow_links(self.config.scanning.follow_symlinks)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            
-            // Check if it's a Rust file
-            if path.extension().map_or(false, |ext| ext == "rs") {
-                // Check exclude patterns
-                if self.should_exclude_file(path) {
-                    continue;
-                }
-                
-                // Check include patterns
-                if !self.should_include_file(path) {
-                    continue;
-                }
-                
-                // Check file size
-                if let Some(max_size) = self.config.scanning.max_file_size {
-                    if let Ok(metadata) = std::fs::metadata(path) {
-                        if metadata.len() > max_size as u64 {
-                            continue;
-                        }
+            .sort_by_file_name();
+        for entry in walker {
+            match entry {
+                Ok(entry) if entry.path().extension().is_some_and(|ext| ext == "rs") => files.push(entry.into_path()),
+                Ok(_) => {}
+                // walkdir does not descend into a link back to one of the directories it is in
+                Err(e) => match (e.path(), e.loop_ancestor()) {
+                    (Some(path), Some(ancestor)) => {
+                        tracing::warn!("Skipping symlink loop: {} leads back to {}", path.display(), ancestor.display())
+                    }
+                    _ => tracing::debug!("Skipping unreadable entry: {}", e),
+                },
+            }
+        }
+        files
+    }
+
+    /// Leave out all but one of the paths symlinks give to the same file: the
+    /// file's own location when it is inside the project, otherwise the first path
+    fn exclude_duplicates(&self, plan: &mut [(PathBuf, Option<FileExclusion>)]) {
+        let canonical_root = self.project_path.canonicalize().ok();
+        let canonical: Vec<Option<PathBuf>> = plan
+            .iter()
+            .map(|(path, exclusion)| exclusion.is_none().then(|| path.canonicalize().ok()).flatten())
+            .collect();
+        let is_own_location = |path: &Path, canonical: &Path| {
+            let relative = path.strip_prefix(&self.project_path).unwrap_or(path);
+            canonical_root.as_ref().is_some_and(|root| root.join(relative) == canonical)
+        };
+
+        let mut kept: HashMap<&Path, usize> = HashMap::new();
+        for own_location in [true, false] {
+            for (index, canonical) in canonical.iter().enumerate() {
+                if let Some(canonical) = canonical {
+                    if is_own_location(&plan[index].0, canonical) == own_location {
+                        kept.entry(canonical.as_path()).or_insert(index);
                     }
                 }
-                
-                files.push(path.to_path_buf());
             }
         }
-        
-        Ok(files)
+        let duplicates: Vec<(usize, PathBuf)> = canonical
+            .iter()
+            .enumerate()
+            .filter_map(|(index, canonical)| {
+                let kept = kept[canonical.as_deref()?];
+                (kept != index).then(|| (index, plan[kept].0.clone()))
+            })
+            .collect();
+        for (index, original) in duplicates {
+            plan[index].1 = Some(FileExclusion::SameFileAs(original));
+        }
     }
 
-    /// Check if a file should be excluded
-    fn should_exclude_file(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
+    /// Why `path`, a Rust file, is left out of the scan; `None` when it is scanned
+    fn exclusion(&self, crates: &[cargo_manifest::CrateInfo], path: &Path, tree: SourceTree) -> Option<FileExclusion> {
+        if let Some(pattern) = self.exclude_pattern(path) {
+            return Some(FileExclusion::ExcludePattern(pattern.to_string()));
+        }
         
-        for pattern in &self.config.scanning.exclude_patterns {
-            if glob::Pattern::new(pattern)
-                .map(|p| p.matches(&path_str))
-                .unwrap_or(false)
-            {
-                return true;
+        if !self.should_include_file(path) {
+            return Some(FileExclusion::NotIncluded);
+        }
+        
+        // Only files inside a crate's target directories
+        if !crates.is_empty() {
+            let Some(info) = cargo_manifest::owning_crate(crates, path) else {
+                return Some(FileExclusion::OutsideCrates);
+            };
+            // A nearer manifest means a package left out of the workspace
+            let manifest_dir = path.ancestors().skip(1).find(|dir| tree.is_file(&dir.join("Cargo.toml")));
+            if let Some(manifest_dir) = manifest_dir.filter(|dir| *dir != info.root.as_path()) {
+                return Some(FileExclusion::OtherPackage(manifest_dir.to_path_buf()));
+            }
+            let target = cargo_manifest::target_kind(&info.root, path, tree);
+            if target == TargetKind::Other && path != info.root.join("build.rs") {
+                return Some(FileExclusion::NotATarget);
             }
         }
         
-        false
+        // Test and bench targets are opt-in/out via the scanning settings
+        match cargo_manifest::target_kind(&self.project_path, path, tree) {
+            TargetKind::IntegrationTest if !self.config.scanning.include_tests => return Some(FileExclusion::Tests),
+            TargetKind::Bench if !self.config.scanning.include_benches => return Some(FileExclusion::Benches),
+            _ => {}
+        }
+        
+        // Check file size
+        if let Some(max_size) = self.config.scanning.max_file_size {
+            if let Some(size) = tree.size(path) {
+                if size > max_size as u64 {
+                    return Some(FileExclusion::TooLarge { size, max_size });
+                }
+            }
+        }
+        
+        None
+    }
+
+    /// Path used for include/exclude matching, relative to the project root so that
+    /// patterns like `**/.*` are not tripped by the location of the project itself
+    fn pattern_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.project_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// The first exclude pattern matching `path`, if any
+    fn exclude_pattern(&self, path: &Path) -> Option<&str> {
+        let path_str = self.pattern_path(path);
+        
+        self.config.scanning.exclude_patterns
+            .iter()
+            .find(|pattern| {
+                glob::Pattern::new(pattern)
+                    .map(|p| p.matches(&path_str))
+                    .unwrap_or(false)
+            })
+            .map(String::as_str)
     }
 
     /// Check if a file should be included
     fn should_include_file(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy();
+        let path_str = self.pattern_path(path);
         
         if self.config.scanning.include_patterns.is_empty() {
             return true;
@@ -159,42 +688,107 @@ impl ArchitectureScanner {
         false
     }
 
-    /// Parse a single Rust file
-    async fn parse_rust_file(&self, file_path: &Path) -> Result<ArchitectureNode> {
-        let content = tokio::fs::read_to_string(file_path).await
-            .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+    /// The module of `file_path` and whether it had to be parsed; the parse is
+    /// taken from `cached`, the previous scan's entry, when the file did not change
+    fn load_module(&self, file_path: &Path, cached: Option<CachedFile>) -> Result<(CachedFile, bool)> {
+        let metadata = std::fs::metadata(file_path)
+            .with_context(|| format!("Failed to read metadata: {}", file_path.display()))?;
+        let (size, modified) = (metadata.len(), metadata.modified().ok());
+        
+        let (fingerprint, node, parsed) = match cached {
+            Some(cached) if cached.fingerprint.matches_metadata(size, modified) => (cached.fingerprint, cached.node, false),
+            cached => {
+                let content = std::fs::read_to_string(file_path)
+                    .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
+                let fingerprint = FileFingerprint::new(size, modified, &content);
+                match cached {
+                    // Touched but not edited: only the timestamp moves
+                    Some(cached) if cached.fingerprint.content_hash == fingerprint.content_hash => {
+                        let mut node = cached.node;
+                        if let Some(modified) = modified {
+                            node.last_modified = last_modified(modified);
+                        }
+                        (fingerprint, node, false)
+                    }
+                    _ => {
+                        let modified = modified.with_context(|| format!("Failed to read modification time: {}", file_path.display()))?;
+                        let node = self.parse_rust_file(file_path, &content, last_modified(modified), SourceTree::Disk)?;
+                        (fingerprint, node, true)
+                    }
+                }
+            }
+        };
         
+        Ok((CachedFile { fingerprint, node }, parsed))
+    }
+
+    fn parse_rust_file(&self, file_path: &Path, content: &str, last_modified: DateTime<Utc>, tree: SourceTree) -> Result<ArchitectureNode> {
         let relative_path = file_path.strip_prefix(&self.project_path)
             .unwrap_or(file_path);
         
-        let name = self.extract_module_name(file_path, &content);
-        let module_type = self.determine_module_type(file_path, &content);
-        let dependencies = self.extract_dependencies(&content);
+        let name = self.extract_module_name(relative_path);
+        let target_kind = cargo_manifest::target_kind(&self.project_path, file_path, tree);
+        let module_type = match target_kind {
+            TargetKind::IntegrationTest => ModuleType::IntegrationTest,
+            TargetKind::Bench => ModuleType::Bench,
+            _ if self.config.scanning.detect_generated && generated::is_generated(content) => ModuleType::Generated,
+            _ => self.determine_module_type(file_path, content),
+        };
+        let file_path_str = relative_path.to_string_lossy().to_string();
+        let ModuleDependencies { names: mut dependencies, sites: mut dependency_sites, imported_items } =
+            self.extract_dependencies(&file_path_str, content);
+        if module_type.is_test_target() {
+            // Tests and benches reach the library through its crate name
+            for (reference, offsets) in test_targets::extract_crate_uses(content) {
+                dependency_sites.insert(
+                    reference.clone(),
+                    offsets.into_iter().map(|offset| SourceLocation::at_offset(&file_path_str, content, offset)).collect(),
+                );
+                dependencies.push(reference);
+            }
+        }
+        let external_uses = if self.config.scanning.include_external_crates {
+            external_crates::extract_uses(&file_path_str, content)
+        } else {
+            BTreeMap::new()
+        };
         
         // Calculate metrics
-        let metrics = self.metrics_calculator.calculate_node_metrics(&content);
+        let mut metrics = self.metrics_calculator.calculate_node_metrics(content);
+        if self.config.scanning.detect_generated {
+            // Harness macros expand to boilerplate the author never sees
+            if let Cow::Owned(hand_written) = generated::strip_harness_macros(content) {
+                let hand_written = self.metrics_calculator.calculate_node_metrics(&hand_written);
+                metrics.complexity_score = hand_written.complexity_score;
+                metrics.cyclomatic_complexity = hand_written.cyclomatic_complexity;
+                metrics.cognitive_complexity = hand_written.cognitive_complexity;
+            }
+        }
+        
+        let split_suggestion = (metrics.lines_of_code >= self.config.metrics.split_min_lines)
+            .then(|| cohesion::suggest_split(content))
+            .flatten();
+        
+        let ownership = (self.config.ownership.enabled && tree.is_disk())
+            .then(|| ownership::blame(&self.project_path, &file_path_str))
+            .flatten()
+            .map(|mut ownership| {
+                ownership::mark_risk(&mut ownership, metrics.complexity_score, &self.config.ownership);
+                ownership
+            });
         
         // Extract code elements
-        let functions = self.extract_functions(&content);
-        let structs = self.extract_structs(&content);
-        let enums = self.extract_enums(&content);
-        let traits = self.extract_traits(&content);
-        
-        // Get file metadata
-        let metadata = std::fs::metadata(file_path)?;
-        let last_modified = metadata.modified()?
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default();
-        let last_modified = DateTime::from_timestamp(
-            last_modified.as_secs() as i64,
-            last_modified.subsec_nanos(),
-        ).unwrap_or_else(Utc::now);
+        let ParsedItems { functions, structs, enums, traits, impls } = self.extract_items(relative_path, content);
         
         Ok(ArchitectureNode {
-            id: Uuid::new_v4().to_string(),
+            // Derived from the file path so ids stay stable across scans
+            id: Uuid::new_v5(&Uuid::NAMESPACE_URL, relative_path.to_string_lossy().as_bytes()).to_string(),
             name,
             module_type,
-            file_path: relative_path.to_string_lossy().to_string(),
+            file_path: file_path_str,
+            crate_name: None, // Filled from the discovered crate manifests
+            entry_point: None,
+            module_path: String::new(), // Filled from the module tree of the whole project
             dependencies,
             dependents: Vec::new(), // Will be filled by dependency analyzer
             status: NodeStatus::Active,
@@ -204,24 +798,76 @@ impl ArchitectureScanner {
             structs,
             enums,
             traits,
+            impls,
             position: None,
+            features: self.extract_cfg_features(content),
+            cfg: Vec::new(), // Needs the module tree
+            naming_violations: naming::check_module_path(relative_path, &self.config.naming),
+            layer: None, // Assigned from the configured rules once all modules are known
+            component: None, // Assigned from the configured conventions once crates are attributed
+            groups: Vec::new(), // Assigned from the `[groups]` section once all modules are known
+            ignored: false, // Set from the `[ignore]` section once all modules are known
+            documentation: documentation::extract_doc_comments(relative_path, content),
+            robustness: robustness::assess(content),
+            doc_coverage: doc_coverage::assess(content),
+            routes: routes::extract_routes(content),
+            data_access: data_access::detect(content),
+            ownership,
+            split_suggestion,
+            dead_code: DeadCode::default(), // Needs the items of every module
+            api_leaks: Vec::new(), // Needs the crates of every module
+            annotations: BTreeMap::new(), // Filled by the registered scan hooks
+            dependency_sites,
+            external_uses,
+            imported_items,
+            module_root: String::new(),
+            item_references: reachability::collect(content),
+            public_signatures: api_leaks::collect(content),
+            cfg_gates: cfg::collect(content),
         })
     }
 
-    /// Extract module name from file path and content
-    fn extract_module_name(&self, file_path: &Path, content: &str) -> String {
-        // Try to find module declaration
-        let mod_regex = Regex::new(r"pub\s+mod\s+(\w+)|mod\s+(\w+)").unwrap();
-        if let Some(captures) = mod_regex.captures(content) {
-            return captures.get(1).or_else(|| captures.get(2))
-                .unwrap().as_str().to_string();
-        }
-        
-        // Fall back to file name without extension
-        file_path.file_stem()
+    /// Name of the module in `relative_path`, from its place in the module tree:
+    /// `foo/mod.rs` is `foo`, and a `lib.rs` or `main.rs` crate root is named after
+    /// its crate directory until the crate's manifest names it
+    fn extract_module_name(&self, relative_path: &Path) -> String {
+        let stem = relative_path.file_stem()
             .and_then(|name| name.to_str())
-            .unwrap_or("unknown")
-            .to_string()
+            .unwrap_or("unknown");
+        let mut directories = relative_path
+            .parent()
+            .into_iter()
+            .flat_map(|parent| parent.iter().rev())
+            .filter_map(|directory| directory.to_str());
+        
+        let name = match stem {
+            "mod" => directories.next(),
+            "lib" | "main" => match directories.next() {
+                Some("src") => directories.next().or(Some("crate")),
+                directory => directory.or(Some("crate")),
+            },
+            _ => None,
+        };
+        name.unwrap_or(stem).to_string()
+    }
+
+    /// Collect the Cargo features named in `cfg(...)` and `cfg_attr(...)` attributes
+    fn extract_cfg_features(&self, content: &str) -> Vec<String> {
+        let cfg_regex = Regex::new(r"#!?\[cfg(?:_attr)?\((.*)\)\]").unwrap();
+        let feature_regex = Regex::new(r#"feature\s*=\s*"([^"]+)""#).unwrap();
+        
+        let mut features: Vec<String> = cfg_regex
+            .captures_iter(content)
+            .flat_map(|captures| {
+                feature_regex
+                    .captures_iter(captures.get(1).map_or("", |m| m.as_str()))
+                    .map(|feature| feature[1].to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        features.sort();
+        features.dedup();
+        features
     }
 
     /// Determine module type based on file path and content
@@ -290,27 +936,81 @@ impl ArchitectureScanner {
         ModuleType::Core
     }
 
-    /// Extract dependencies from file content
-    fn extract_dependencies(&self, content: &str) -> Vec<String> {
+    /// Paths of the file's `mod` and `use` items that can lead to other modules of the project
+    fn extract_dependencies(
+        &self,
+        file_path: &str,
+        content: &str,
+    ) -> ModuleDependencies {
         let mut dependencies = Vec::new();
+        let mut sites: HashMap<String, Vec<SourceLocation>> = HashMap::new();
+        let mut imported: HashMap<String, Vec<ImportedItem>> = HashMap::new();
+        let mut add = |path: String, item: Option<ImportedItem>, offset: usize| {
+            let locations = sites.entry(path.clone()).or_default();
+            if locations.is_empty() {
+                dependencies.push(path.clone());
+            }
+            locations.push(SourceLocation::at_offset(file_path, content, offset));
+            imported.entry(path).or_default().extend(item);
+        };
         
-        // Match use statements
-        let use_regex = Regex::new(r"use\s+crate::([^;]+)").unwrap();
-        for captures in use_regex.captures_iter(content) {
-            if let Some(dep) = captures.get(1) {
-                dependencies.push(dep.as_str().to_string());
+        // Out-of-line `mod` declarations of the file's own module
+        let inline_modules = module_tree::inline_module_spans(content);
+        let depth = |offset: usize| inline_modules.iter().filter(|span| span.contains(&offset)).count();
+        let mod_regex = Regex::new(r"(?m)^[ \t]*((?:pub(?:\([^)]*\))?\s+)?mod\s+(\w+)\s*;)").unwrap();
+        let mut children = HashSet::new();
+        for captures in mod_regex.captures_iter(content) {
+            let (Some(statement), Some(name)) = (captures.get(1), captures.get(2)) else {
+                continue;
+            };
+            if depth(statement.start()) == 0 {
+                children.insert(name.as_str());
+                let item = ImportedItem { name: name.as_str().to_string(), usage: ItemUsage::Declared };
+                add(format!("self::{}", name.as_str()), Some(item), statement.start());
             }
         }
         
-        // Match mod declarations
-        let mod_regex = Regex::new(r"mod\s+(\w+)").unwrap();
-        for captures in mod_regex.captures_iter(content) {
-            if let Some(dep) = captures.get(1) {
-                dependencies.push(dep.as_str().to_string());
+        // `use` paths that can lead into the project: from `crate`, `self`, `super` or a child module
+        let use_regex = Regex::new(r"(?m)^[ \t]*((?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);)").unwrap();
+        for captures in use_regex.captures_iter(content) {
+            let (Some(statement), Some(tree)) = (captures.get(1), captures.get(2)) else {
+                continue;
+            };
+            for (path, item) in relationships::imported_paths(tree.as_str(), content) {
+                let Some(path) = module_tree::file_relative(&path, depth(statement.start())) else {
+                    continue;
+                };
+                let path = match path.split("::").next() {
+                    Some("crate" | "self" | "super") => path,
+                    Some(child) if children.contains(child) => format!("self::{}", path),
+                    _ => continue,
+                };
+                add(path, item, statement.start());
             }
         }
         
-        dependencies
+        ModuleDependencies {
+            names: dependencies,
+            sites,
+            imported_items: imported,
+        }
+    }
+
+    /// Code elements read with the configured parser backend
+    fn extract_items(&self, relative_path: &Path, content: &str) -> ParsedItems {
+        if self.config.scanning.parser == ParserBackend::Syn {
+            match syn_parser::parse_items(content) {
+                Ok(items) => return items,
+                Err(e) => tracing::debug!("Falling back to regex parsing for {}: {:#}", relative_path.display(), e),
+            }
+        }
+        ParsedItems {
+            functions: self.extract_functions(content),
+            structs: self.extract_structs(content),
+            enums: self.extract_enums(content),
+            traits: self.extract_traits(content),
+            impls: self.extract_impls(content),
+        }
     }
 
     /// Extract function information
@@ -339,6 +1039,7 @@ impl ArchitectureScanner {
                     lines_of_code: 1, // Simplified
                     documentation: None,
                     attributes: Vec::new(),
+                    line: content[..name.start()].matches('\n').count() + 1,
                 });
             }
         }
@@ -432,6 +1133,20 @@ impl ArchitectureScanner {
         
         traits
     }
+
+    /// Extract trait implementations
+    fn extract_impls(&self, content: &str) -> Vec<ImplInfo> {
+        let impl_regex = Regex::new(r"\bimpl\b(?:\s*<[^{;]*?>)?\s+((?:\w+::)*\w+)(?:<[^{;]*?>)?\s+for\s+(?:&\s*(?:'\w+\s+)?(?:mut\s+)?)?(?:\w+::)*(\w+)").unwrap();
+        impl_regex
+            .captures_iter(content)
+            .map(|captures| ImplInfo {
+                trait_path: captures[1].to_string(),
+                self_type: captures[2].to_string(),
+                line: content[..captures.get(0).map_or(0, |m| m.start())].matches('\n').count() + 1,
+                trait_module: None,
+            })
+            .collect()
+    }
 }
 
 #[async_trait::async_trait]
@@ -440,9 +1155,16 @@ impl ProjectScanner for ArchitectureScanner {
         self.scan_async().await
     }
 
+    /// Without a previous scan everything is parsed; otherwise see `rescan_async`
     async fn scan_incremental(&self, last_scan: Option<ArchitectureMap>) -> Result<ArchitectureMap> {
-        // For now, just do a full scan
-        // TODO: Implement incremental scanning
-        self.scan_async().await
+        match last_scan {
+            Some(_) => self.rescan_async().await,
+            None => self.scan_async().await,
+        }
     }
 }
+
+fn last_modified(modified: std::time::SystemTime) -> DateTime<Utc> {
+    let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+    DateTime::from_timestamp(since_epoch.as_secs() as i64, since_epoch.subsec_nanos()).unwrap_or_else(Utc::now)
+}
@@ -0,0 +1,30 @@
+//! Post-processing of scan results by code embedding the library.
+
+use crate::types::ArchitectureMap;
+
+/// Changes a finished scan before it is visualized, stored or exported.
+///
+/// Hooks run in registration order after every scan and rescan, once all
+/// built-in analysis is done. Closures taking `&mut ArchitectureMap` are hooks too.
+///
+/// ```rust,no_run
+/// use rust_architecture_visualizer::{ArchitectureMap, ArchitectureScanner};
+///
+/// let scanner = ArchitectureScanner::new("./my-project", Default::default()).with_hook(|map: &mut ArchitectureMap| {
+///     for node in map.nodes.values_mut() {
+///         node.annotations.insert("catalog_id".to_string(), format!("svc-{}", node.name));
+///     }
+/// });
+/// ```
+pub trait ScanHook: Send + Sync {
+    fn post_scan(&self, map: &mut ArchitectureMap);
+}
+
+impl<F> ScanHook for F
+where
+    F: Fn(&mut ArchitectureMap) + Send + Sync,
+{
+    fn post_scan(&self, map: &mut ArchitectureMap) {
+        self(map)
+    }
+}
@@ -0,0 +1,76 @@
+//! Where a scan reads the project's files from.
+//!
+//! Scans normally walk the project directory. A tree held in memory, such as
+//! files pasted or dropped into a browser page, is scanned the same way
+//! without touching the file system. In-memory files are keyed by the project
+//! path joined with their path below it, so every path a scan compares is the
+//! same for both.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy)]
+pub enum SourceTree<'a> {
+    /// The file system
+    Disk,
+    /// These files and nothing else
+    Memory(&'a BTreeMap<PathBuf, String>),
+}
+
+impl<'a> SourceTree<'a> {
+    pub fn is_disk(&self) -> bool {
+        matches!(self, Self::Disk)
+    }
+
+    /// Content of the file at `path`, or `None` when there is no readable file
+    pub fn read(&self, path: &Path) -> Option<Cow<'a, str>> {
+        match self {
+            Self::Disk => std::fs::read_to_string(path).ok().map(Cow::Owned),
+            Self::Memory(files) => files.get(path).map(|content| Cow::Borrowed(content.as_str())),
+        }
+    }
+
+    pub fn is_file(&self, path: &Path) -> bool {
+        match self {
+            Self::Disk => path.is_file(),
+            Self::Memory(files) => files.contains_key(path),
+        }
+    }
+
+    /// Size of the file at `path` in bytes
+    pub fn size(&self, path: &Path) -> Option<u64> {
+        match self {
+            Self::Disk => std::fs::metadata(path).ok().map(|metadata| metadata.len()),
+            Self::Memory(files) => files.get(path).map(|content| content.len() as u64),
+        }
+    }
+
+    /// Files and directories directly inside `dir`, each with whether it is a directory
+    pub fn entries(&self, dir: &Path) -> Vec<(PathBuf, bool)> {
+        match self {
+            Self::Disk => std::fs::read_dir(dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let path = entry.path();
+                    let is_dir = path.is_dir();
+                    (path, is_dir)
+                })
+                .collect(),
+            Self::Memory(files) => {
+                let mut entries: Vec<(PathBuf, bool)> = files
+                    .keys()
+                    .filter_map(|path| {
+                        let mut components = path.strip_prefix(dir).ok()?.components();
+                        let first = components.next()?;
+                        Some((dir.join(first), components.next().is_some()))
+                    })
+                    .collect();
+                entries.dedup();
+                entries
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+//! Functions, structs, enums, traits and trait impls read from the syntax tree with `syn`.
+//!
+//! Unlike the regex extraction this follows multi-line signatures and nested
+//! modules, and reads derives, generics, attributes and doc comments. Items
+//! produced by macro invocations are not expanded and stay invisible.
+
+use anyhow::{Context, Result};
+use quote::ToTokens;
+use syn::{
+    spanned::Spanned,
+    visit::{self, Visit},
+    Attribute, Block, Expr, Fields, GenericParam, Generics, ImplItemFn, ItemEnum, ItemFn, ItemImpl, ItemStruct,
+    ItemTrait, Signature, TraitItem, Type, TypeParamBound, Visibility,
+};
+
+use crate::types::{EnumInfo, FunctionInfo, ImplInfo, StructInfo, TraitInfo};
+
+/// Code elements of one file
+#[derive(Debug, Default)]
+pub struct ParsedItems {
+    pub functions: Vec<FunctionInfo>,
+    pub structs: Vec<StructInfo>,
+    pub enums: Vec<EnumInfo>,
+    pub traits: Vec<TraitInfo>,
+    pub impls: Vec<ImplInfo>,
+}
+
+/// Parse `content` and collect its items, including those of inline modules and impl blocks
+pub fn parse_items(content: &str) -> Result<ParsedItems> {
+    let file = syn::parse_file(content).context("Failed to parse Rust source")?;
+    let mut collector = ParsedItems::default();
+    collector.visit_file(&file);
+    Ok(collector)
+}
+
+impl<'ast> Visit<'ast> for ParsedItems {
+    fn visit_item_fn(&mut self, item: &'ast ItemFn) {
+        self.functions.push(function(&item.attrs, &item.vis, &item.sig, &item.block));
+        visit::visit_item_fn(self, item);
+    }
+
+    fn visit_impl_item_fn(&mut self, item: &'ast ImplItemFn) {
+        self.functions.push(function(&item.attrs, &item.vis, &item.sig, &item.block));
+        visit::visit_impl_item_fn(self, item);
+    }
+
+    fn visit_item_struct(&mut self, item: &'ast ItemStruct) {
+        self.structs.push(StructInfo {
+            name: item.ident.to_string(),
+            is_public: is_public(&item.vis),
+            field_count: match &item.fields {
+                Fields::Named(fields) => fields.named.len(),
+                Fields::Unnamed(fields) => fields.unnamed.len(),
+                Fields::Unit => 0,
+            },
+            derives: derives(&item.attrs),
+            documentation: documentation(&item.attrs),
+            attributes: attributes(&item.attrs),
+            generics: generics(&item.generics),
+        });
+        visit::visit_item_struct(self, item);
+    }
+
+    fn visit_item_enum(&mut self, item: &'ast ItemEnum) {
+        self.enums.push(EnumInfo {
+            name: item.ident.to_string(),
+            is_public: is_public(&item.vis),
+            variant_count: item.variants.len(),
+            derives: derives(&item.attrs),
+            documentation: documentation(&item.attrs),
+            attributes: attributes(&item.attrs),
+            generics: generics(&item.generics),
+        });
+        visit::visit_item_enum(self, item);
+    }
+
+    fn visit_item_trait(&mut self, item: &'ast ItemTrait) {
+        self.traits.push(TraitInfo {
+            name: item.ident.to_string(),
+            is_public: is_public(&item.vis),
+            method_count: item.items.iter().filter(|item| matches!(item, TraitItem::Fn(_))).count(),
+            documentation: documentation(&item.attrs),
+            attributes: attributes(&item.attrs),
+            generics: generics(&item.generics),
+            supertraits: item
+                .supertraits
+                .iter()
+                .filter_map(|bound| match bound {
+                    TypeParamBound::Trait(bound) => Some(path_string(&bound.path)),
+                    _ => None,
+                })
+                .collect(),
+        });
+        visit::visit_item_trait(self, item);
+    }
+
+    fn visit_item_impl(&mut self, item: &'ast ItemImpl) {
+        if let Some((_, path, _)) = &item.trait_ {
+            self.impls.push(ImplInfo {
+                trait_path: path_string(path),
+                self_type: type_name(&item.self_ty),
+                line: item.impl_token.span.start().line,
+                trait_module: None, // Resolved once all modules are known
+            });
+        }
+        visit::visit_item_impl(self, item);
+    }
+}
+
+fn function(attrs: &[Attribute], vis: &Visibility, sig: &Signature, block: &Block) -> FunctionInfo {
+    let span = block.span();
+    let first_line = sig.fn_token.span.start().line;
+    let mut branches = BranchCounter::default();
+    branches.visit_block(block);
+
+    FunctionInfo {
+        name: sig.ident.to_string(),
+        is_public: is_public(vis),
+        is_async: sig.asyncness.is_some(),
+        parameter_count: sig.inputs.len(),
+        complexity: 1.0 + branches.count as f64,
+        lines_of_code: span.end().line.saturating_sub(first_line) + 1,
+        documentation: documentation(attrs),
+        attributes: attributes(attrs),
+        line: first_line,
+    }
+}
+
+/// Decision points of a function body, for its cyclomatic complexity
+#[derive(Default)]
+struct BranchCounter {
+    count: usize,
+}
+
+impl<'ast> Visit<'ast> for BranchCounter {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match expr {
+            Expr::If(_) | Expr::While(_) | Expr::ForLoop(_) | Expr::Loop(_) | Expr::Try(_) => self.count += 1,
+            Expr::Match(expr_match) => self.count += expr_match.arms.len().saturating_sub(1),
+            Expr::Binary(binary) if matches!(binary.op, syn::BinOp::And(_) | syn::BinOp::Or(_)) => self.count += 1,
+            _ => {}
+        }
+        visit::visit_expr(self, expr);
+    }
+
+    // Nested items are counted on their own
+    fn visit_item(&mut self, _item: &'ast syn::Item) {}
+}
+
+fn is_public(vis: &Visibility) -> bool {
+    matches!(vis, Visibility::Public(_))
+}
+
+/// Text of the `///` and `#[doc]` attributes, one line each
+fn documentation(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(text), .. }) => Some(text.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Attributes other than doc comments and derives, as written
+fn attributes(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc") && !attr.path().is_ident("derive"))
+        .map(|attr| format!("#[{}]", attr.meta.to_token_stream()))
+        .collect()
+}
+
+fn derives(attrs: &[Attribute]) -> Vec<String> {
+    let mut derives = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            derives.push(path_string(&meta.path));
+            Ok(())
+        });
+    }
+    derives
+}
+
+/// Names of the generic parameters: `'a`, `T`, `N`
+fn generics(generics: &Generics) -> Vec<String> {
+    generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Lifetime(param) => param.lifetime.to_string(),
+            GenericParam::Type(param) => param.ident.to_string(),
+            GenericParam::Const(param) => param.ident.to_string(),
+        })
+        .collect()
+}
+
+/// Last path segment of a type, e.g. `Scanner` for `&mut crate::Scanner<T>`
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default(),
+        Type::Reference(reference) => type_name(&reference.elem),
+        _ => ty.to_token_stream().to_string(),
+    }
+}
+
+fn path_string(path: &syn::Path) -> String {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
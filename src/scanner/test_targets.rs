@@ -0,0 +1,112 @@
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::types::{ArchitectureNode, DependencyEdge, DependencyType, SourceLocation};
+
+/// Crates whose paths never point at project modules
+const EXTERNAL_ROOTS: &[&str] = &["std", "core", "alloc", "crate", "self", "super"];
+
+/// Collect `crate_ident::module` references from the `use` statements of a test or bench,
+/// with the byte offsets of the statements that make them.
+pub fn extract_crate_uses(content: &str) -> BTreeMap<String, Vec<usize>> {
+    let use_regex = Regex::new(r"(?m)^\s*(?:pub\s+)?use\s+([A-Za-z_]\w*)::([^;]+);").unwrap();
+    let mut uses: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+
+    for captures in use_regex.captures_iter(content) {
+        let crate_ident = &captures[1];
+        if EXTERNAL_ROOTS.contains(&crate_ident) {
+            continue;
+        }
+
+        // `use my_crate::{a, b::c}` exercises both `a` and `b`
+        for item in captures[2].split(',') {
+            let module = item
+                .trim()
+                .trim_start_matches('{')
+                .split("::")
+                .next()
+                .unwrap_or("")
+                .trim_matches(|c: char| c == '{' || c == '}' || c.is_whitespace());
+            if !module.is_empty() && module != "*" && module != "self" {
+                let offsets = uses.entry(format!("{}::{}", crate_ident, module)).or_default();
+                let offset = captures.get(1).map_or(0, |ident| ident.start());
+                if !offsets.contains(&offset) {
+                    offsets.push(offset);
+                }
+            }
+        }
+    }
+
+    uses
+}
+
+/// Edges from integration tests and benches to the library modules they exercise.
+///
+/// A reference `my_crate::parser` resolves to `src/parser.rs` or `src/parser/mod.rs`
+/// of the crate named `my_crate` (or `my-crate`); anything else at the crate root
+/// resolves to its `src/lib.rs`.
+pub fn exercise_edges(nodes: &HashMap<String, ArchitectureNode>) -> Vec<DependencyEdge> {
+    let mut pairs: BTreeMap<(String, String), Vec<SourceLocation>> = BTreeMap::new();
+
+    for test in nodes.values().filter(|node| node.module_type.is_test_target()) {
+        for dependency in &test.dependencies {
+            // Paths within the test's own module tree are resolved like any other module's
+            let Some((crate_ident, module)) = dependency.split_once("::").filter(|(root, _)| !EXTERNAL_ROOTS.contains(root))
+            else {
+                continue;
+            };
+            if let Some(target) = find_library_module(nodes, crate_ident, module) {
+                let locations = pairs.entry((test.id.clone(), target.id.clone())).or_default();
+                for site in test.dependency_sites.get(dependency).into_iter().flatten() {
+                    if !locations.contains(site) {
+                        locations.push(site.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    pairs
+        .into_iter()
+        .map(|((from, to), locations)| DependencyEdge {
+            from,
+            to,
+            relationship: DependencyType::Exercises,
+            strength: 0.5,
+            is_circular: false,
+            layer_violation: false,
+            locations,
+            suggestions: Vec::new(),
+        })
+        .collect()
+}
+
+fn find_library_module<'a>(
+    nodes: &'a HashMap<String, ArchitectureNode>,
+    crate_ident: &str,
+    module: &str,
+) -> Option<&'a ArchitectureNode> {
+    let library: Vec<&ArchitectureNode> = nodes
+        .values()
+        .filter(|node| !node.module_type.is_test_target())
+        .filter(|node| {
+            node.crate_name
+                .as_deref()
+                .is_some_and(|name| name.replace('-', "_") == crate_ident)
+        })
+        .collect();
+
+    let module_file = Path::new("src").join(format!("{}.rs", module));
+    let module_dir = Path::new("src").join(module).join("mod.rs");
+    let lib_file = Path::new("src").join("lib.rs");
+
+    library
+        .iter()
+        .find(|node| {
+            let path = Path::new(&node.file_path);
+            path.ends_with(&module_file) || path.ends_with(&module_dir)
+        })
+        .or_else(|| library.iter().find(|node| Path::new(&node.file_path).ends_with(&lib_file)))
+        .copied()
+}
@@ -0,0 +1,241 @@
+//! The scan evaluated under each configured feature set and target.
+//!
+//! Every module keeps the `cfg` predicates it is compiled under, so a variant
+//! needs no rescan: the modules whose predicates fail are left out and the
+//! totals are computed again over the rest, for all variants in parallel.
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    config::ScanVariant,
+    scanner::{cfg, dependency_analyzer::DependencyAnalyzer, ignored, metrics_calculator::MetricsCalculator},
+    types::{ArchitectureNode, ArchitectureVariant, CrateFeatures, DependencyEdge},
+};
+
+/// Options without a target-independent value, left unknown when the variant names no target
+const TARGET_OPTIONS: &[&str] = &["unix", "windows"];
+
+/// Evaluate `variants` over a finished scan
+pub fn evaluate(
+    nodes: &HashMap<String, ArchitectureNode>,
+    edges: &[DependencyEdge],
+    cargo_features: &[CrateFeatures],
+    variants: &[ScanVariant],
+    dependency_analyzer: &DependencyAnalyzer,
+) -> Vec<ArchitectureVariant> {
+    variants
+        .par_iter()
+        .map(|variant| {
+            let target = variant.target.as_deref().map(Target::parse);
+            let mut enabled: HashMap<&str, HashSet<String>> = HashMap::new();
+            let mut excluded: Vec<&ArchitectureNode> = nodes
+                .values()
+                .filter(|node| !node.cfg.is_empty())
+                .filter(|node| {
+                    let crate_name = node.crate_name.as_deref().unwrap_or_default();
+                    let features = enabled
+                        .entry(crate_name)
+                        .or_insert_with(|| enabled_features(variant, crate_name, cargo_features));
+                    let options = VariantOptions { variant, target: target.as_ref(), features };
+                    node.cfg.iter().any(|predicate| cfg::evaluate(predicate, &options) == Some(false))
+                })
+                .collect();
+            excluded.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+            let excluded: Vec<String> = excluded.into_iter().map(|node| node.id.clone()).collect();
+
+            let left_out: HashSet<&str> = excluded.iter().map(String::as_str).collect();
+            let remaining: HashMap<String, ArchitectureNode> = nodes
+                .iter()
+                .filter(|(id, _)| !left_out.contains(id.as_str()))
+                .map(|(id, node)| (id.clone(), node.clone()))
+                .collect();
+            let remaining_edges: Vec<DependencyEdge> = edges
+                .iter()
+                .filter(|edge| !left_out.contains(edge.from.as_str()) && !left_out.contains(edge.to.as_str()))
+                .cloned()
+                .collect();
+            let counted = ignored::counted_nodes(&remaining);
+            let counted_edges = ignored::counted_edges(&remaining, &remaining_edges);
+
+            ArchitectureVariant {
+                name: variant.name.clone(),
+                features: variant.features.clone(),
+                target: variant.target.clone(),
+                excluded,
+                total_modules: counted.len(),
+                total_lines: counted.values().map(|node| node.metrics.lines_of_code).sum(),
+                average_complexity: MetricsCalculator::average_complexity(&counted),
+                circular_dependencies: dependency_analyzer.find_circular_dependencies(&counted_edges).len(),
+            }
+        })
+        .collect()
+}
+
+/// Features of `crate_name` the variant turns on, with everything they enable in turn
+fn enabled_features(variant: &ScanVariant, crate_name: &str, cargo_features: &[CrateFeatures]) -> HashSet<String> {
+    let mut pending: Vec<String> = variant
+        .features
+        .iter()
+        .filter_map(|feature| match feature.split_once('/') {
+            Some((owner, feature)) => (owner == crate_name).then(|| feature.to_string()),
+            None => Some(feature.clone()),
+        })
+        .collect();
+    if !variant.no_default_features {
+        pending.push("default".to_string());
+    }
+
+    let definitions = cargo_features.iter().find(|features| features.crate_name == crate_name);
+    let mut enabled = HashSet::new();
+    while let Some(feature) = pending.pop() {
+        if !enabled.insert(feature.clone()) {
+            continue;
+        }
+        let enables = definitions
+            .and_then(|definitions| definitions.features.iter().find(|definition| definition.name == feature))
+            .map(|definition| definition.enables.as_slice())
+            .unwrap_or_default();
+        for value in enables {
+            // `dep:name` only turns the dependency on; `name/feature` also turns on the optional dependency's own feature
+            if let Some((dependency, _)) = value.split_once('/') {
+                if !dependency.ends_with('?') && definitions.is_some_and(|definitions| definitions.optional_dependencies.iter().any(|name| name == dependency)) {
+                    pending.push(dependency.to_string());
+                }
+            } else if !value.starts_with("dep:") {
+                pending.push(value.clone());
+            }
+        }
+    }
+    enabled
+}
+
+struct VariantOptions<'a> {
+    variant: &'a ScanVariant,
+    target: Option<&'a Target>,
+    features: &'a HashSet<String>,
+}
+
+impl VariantOptions<'_> {
+    /// Options the variant sets explicitly, as `name` or `key = "value"`
+    fn configured(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.variant.cfg.iter().map(|option| match option.split_once('=') {
+            Some((key, value)) => (key.trim(), Some(value.trim().trim_matches('"'))),
+            None => (option.trim(), None),
+        })
+    }
+}
+
+impl cfg::CfgOptions for VariantOptions<'_> {
+    fn is_set(&self, name: &str) -> Option<bool> {
+        if self.configured().any(|option| option == (name, None)) {
+            return Some(true);
+        }
+        match name {
+            "test" | "doc" | "doctest" | "miri" => Some(false),
+            "debug_assertions" => Some(true),
+            _ if TARGET_OPTIONS.contains(&name) => self.target.map(|target| target.families.contains(&name)),
+            // Options nobody sets are off, the way rustc sees them
+            _ => Some(false),
+        }
+    }
+
+    fn has_value(&self, key: &str, value: &str) -> Option<bool> {
+        if self.configured().any(|option| option == (key, Some(value))) {
+            return Some(true);
+        }
+        match key {
+            "feature" => Some(self.features.contains(value)),
+            "target_os" => self.target.map(|target| target.os == value),
+            "target_family" => self.target.map(|target| target.families.contains(&value)),
+            "target_arch" => self.target.map(|target| target.arch == value),
+            "target_env" => self.target.map(|target| target.env == value),
+            "target_vendor" => self.target.map(|target| target.vendor == value),
+            "target_pointer_width" => self.target.map(|target| target.pointer_width == value),
+            "target_endian" => self.target.map(|target| target.endian == value),
+            // e.g. `target_feature` or `panic`: depends on flags the variant does not describe
+            _ if key.starts_with("target_") || key == "panic" => None,
+            _ => Some(false),
+        }
+    }
+}
+
+/// The `cfg` values of a target triple, e.g. `aarch64-apple-darwin`
+struct Target {
+    arch: String,
+    vendor: String,
+    os: String,
+    env: String,
+    families: Vec<&'static str>,
+    pointer_width: &'static str,
+    endian: &'static str,
+}
+
+impl Target {
+    fn parse(triple: &str) -> Self {
+        let parts: Vec<&str> = triple.split('-').collect();
+        let raw_arch = parts.first().copied().unwrap_or_default();
+        let arch = match raw_arch {
+            "i386" | "i586" | "i686" => "x86",
+            arch if arch.starts_with("armv") || arch.starts_with("thumbv") || arch == "arm" => "arm",
+            arch if arch.starts_with("riscv64") => "riscv64",
+            arch if arch.starts_with("riscv32") => "riscv32",
+            "powerpc64le" => "powerpc64",
+            "mipsel" => "mips",
+            "mips64el" => "mips64",
+            arch => arch,
+        };
+        let has = |name: &str| parts.iter().skip(1).any(|part| part.starts_with(name));
+        let os = if has("android") {
+            "android"
+        } else if has("linux") {
+            "linux"
+        } else if has("darwin") {
+            "macos"
+        } else if has("ios") {
+            "ios"
+        } else if has("windows") {
+            "windows"
+        } else {
+            ["freebsd", "netbsd", "openbsd", "dragonfly", "solaris", "illumos", "fuchsia", "redox", "wasi", "emscripten", "none"]
+                .into_iter()
+                .find(|os| has(os))
+                .unwrap_or("unknown")
+        };
+        let env = ["gnu", "musl", "msvc", "sgx", "uclibc"]
+            .into_iter()
+            .find(|env| parts.len() > 2 && parts.last().is_some_and(|last| last.starts_with(env)))
+            .unwrap_or_default();
+
+        let mut families = Vec::new();
+        match os {
+            "windows" => families.push("windows"),
+            "none" | "unknown" | "wasi" => {}
+            _ => families.push("unix"),
+        }
+        if arch.starts_with("wasm") {
+            families.push("wasm");
+        }
+        let pointer_width = match arch {
+            "x86_64" | "aarch64" | "powerpc64" | "riscv64" | "s390x" | "mips64" | "sparc64" | "loongarch64" | "wasm64" => "64",
+            "avr" | "msp430" => "16",
+            _ => "32",
+        };
+        let big_endian = matches!(arch, "s390x" | "sparc64" | "sparc" | "m68k") || matches!(raw_arch, "powerpc" | "powerpc64" | "mips" | "mips64");
+
+        Self {
+            arch: arch.to_string(),
+            vendor: parts
+                .get(1)
+                .filter(|vendor| ["pc", "apple", "unknown", "uwp", "fortanix", "nvidia", "sun", "wrs", "esp"].contains(vendor))
+                .copied()
+                .unwrap_or("unknown")
+                .to_string(),
+            os: os.to_string(),
+            env: env.to_string(),
+            families,
+            pointer_width,
+            endian: if big_endian { "big" } else { "little" },
+        }
+    }
+}
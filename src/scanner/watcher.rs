@@ -0,0 +1,76 @@
+//! Filesystem events for watch mode, debounced into one notification per burst of edits.
+//!
+//! Only Rust sources and `Cargo.toml` files count. Anything under `target/` or a
+//! hidden directory is ignored, so a watch command writing build output does not
+//! trigger the next refresh.
+
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Recursive watch over a project directory
+pub struct SourceWatcher {
+    // Dropping the watcher stops the events
+    _watcher: RecommendedWatcher,
+    events: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    debounce: Duration,
+}
+
+impl SourceWatcher {
+    /// Start watching `project_path`; a burst of events ends after `debounce` without new ones
+    pub fn new(project_path: &Path, debounce: Duration) -> Result<Self> {
+        let (sender, events) = mpsc::unbounded_channel();
+        let root = project_path.to_path_buf();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+            Ok(event) if is_change(&event.kind) => {
+                let paths: Vec<PathBuf> = event.paths.into_iter().filter(|path| is_source(&root, path)).collect();
+                if !paths.is_empty() {
+                    let _ = sender.send(paths);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("File watcher error: {}", e),
+        })
+        .context("Failed to create file watcher")?;
+        watcher
+            .watch(project_path, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", project_path.display()))?;
+
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            debounce,
+        })
+    }
+
+    /// Wait for the next burst of changes and return the files it touched
+    pub async fn changed(&mut self) -> Option<Vec<PathBuf>> {
+        let mut paths = self.events.recv().await?;
+        while let Ok(Some(more)) = tokio::time::timeout(self.debounce, self.events.recv()).await {
+            paths.extend(more);
+        }
+        paths.sort();
+        paths.dedup();
+        Some(paths)
+    }
+}
+
+fn is_change(kind: &EventKind) -> bool {
+    matches!(kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+}
+
+/// Whether `path` is a Rust source or manifest outside build output and hidden directories
+fn is_source(root: &Path, path: &Path) -> bool {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    let ignored = relative.parent().is_some_and(|parent| {
+        parent.components().any(|component| match component {
+            Component::Normal(name) => name == "target" || name.to_string_lossy().starts_with('.'),
+            _ => false,
+        })
+    });
+    !ignored
+        && (path.extension().is_some_and(|ext| ext == "rs")
+            || path.file_name().is_some_and(|name| name == "Cargo.toml"))
+}
@@ -0,0 +1,47 @@
+//! Version and JSON Schema of the architecture JSON written by `scan --output`,
+//! `export --format json` and the history store.
+//!
+//! Every map records the `schema_version` it was written with. Maps without one
+//! predate versioning (version 0); every field added since then defaults when
+//! missing, so they are read as they are. When a field is renamed, removed or
+//! changes meaning, bump [`SCHEMA_VERSION`] and add a step to [`upgrade`] that
+//! rewrites the older JSON into the new shape.
+
+use anyhow::{bail, Context, Result};
+use schemars::schema::RootSchema;
+use serde_json::Value;
+
+use crate::types::ArchitectureMap;
+
+/// Version of the `ArchitectureMap` JSON this build writes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema (draft 7) of the current `ArchitectureMap` JSON
+pub fn json_schema() -> RootSchema {
+    let mut schema = schemars::schema_for!(ArchitectureMap);
+    let metadata = schema.schema.metadata();
+    metadata.title = Some(format!("rust-arch-viz ArchitectureMap, schema version {}", SCHEMA_VERSION));
+    schema
+}
+
+/// An `ArchitectureMap` from JSON of the current or an older schema version
+pub fn parse_architecture(json: &[u8]) -> Result<ArchitectureMap> {
+    upgrade(serde_json::from_slice(json)?)
+}
+
+/// Read a map of any schema version up to [`SCHEMA_VERSION`], upgrading older ones
+pub fn upgrade(mut value: Value) -> Result<ArchitectureMap> {
+    let version = value.get("schema_version").and_then(Value::as_u64).unwrap_or(0);
+    if version > u64::from(SCHEMA_VERSION) {
+        bail!(
+            "Architecture data has schema version {}, newer than the {} this rust-arch-viz reads; upgrade rust-arch-viz",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    // Version 0 only lacks fields that default when missing, so there is nothing to rewrite yet
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema_version".to_string(), SCHEMA_VERSION.into());
+    }
+    serde_json::from_value(value).context("Invalid architecture data")
+}
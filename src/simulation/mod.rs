@@ -0,0 +1,211 @@
+//! Refactor simulation: apply hypothetical changes to a scanned architecture.
+//!
+//! Changes are applied to a copy of the map, after which links, cycles and
+//! totals are recomputed and a set of checks compares the projected graph
+//! with the current one. Nothing on disk is touched.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+use crate::{
+    scanner::{dependency_analyzer::DependencyAnalyzer, ignored, metrics_calculator::MetricsCalculator},
+    types::ArchitectureMap,
+    visualizer::{build_crate_view, CrateView},
+};
+
+/// A hypothetical change to the module graph.
+///
+/// Modules are referenced by node id, name or file path.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum SimulatedChange {
+    /// Move a module into another (possibly new) crate
+    Move { module: String, to_crate: String },
+    /// Give a module a new name
+    Rename { module: String, new_name: String },
+    /// Remove a module; anything depending on it is reported as broken
+    Delete { module: String },
+}
+
+/// Request body of a simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationRequest {
+    pub changes: Vec<SimulatedChange>,
+}
+
+/// A dependency whose target no longer exists in the projected graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokenDependency {
+    pub from: String,
+    pub from_name: String,
+    pub missing: String,
+    pub missing_name: String,
+}
+
+/// Outcome of one check against the projected graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub passed: bool,
+    pub details: Vec<String>,
+}
+
+/// Projected graph and check results of a simulation
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    pub architecture: ArchitectureMap,
+    pub crate_view: CrateView,
+    pub broken_dependencies: Vec<BrokenDependency>,
+    pub checks: Vec<CheckResult>,
+}
+
+/// Apply `changes` to a copy of `architecture` and check the result
+pub fn simulate(architecture: &ArchitectureMap, changes: &[SimulatedChange]) -> Result<SimulationReport> {
+    let mut projected = architecture.clone();
+    let mut broken_dependencies = Vec::new();
+
+    for change in changes {
+        match change {
+            SimulatedChange::Move { module, to_crate } => {
+                let id = resolve_module(&projected, module)?;
+                if let Some(node) = projected.nodes.get_mut(&id) {
+                    node.crate_name = Some(to_crate.clone());
+                }
+            }
+            SimulatedChange::Rename { module, new_name } => {
+                let id = resolve_module(&projected, module)?;
+                if let Some(node) = projected.nodes.get_mut(&id) {
+                    node.name = new_name.clone();
+                }
+            }
+            SimulatedChange::Delete { module } => {
+                let id = resolve_module(&projected, module)?;
+                let removed = projected.nodes.remove(&id);
+                let missing_name = removed.map(|node| node.name).unwrap_or_else(|| id.clone());
+                for edge in projected.edges.iter().filter(|edge| edge.to == id && edge.from != id) {
+                    if let Some(source) = projected.nodes.get(&edge.from) {
+                        broken_dependencies.push(BrokenDependency {
+                            from: edge.from.clone(),
+                            from_name: source.name.clone(),
+                            missing: id.clone(),
+                            missing_name: missing_name.clone(),
+                        });
+                    }
+                }
+                projected.edges.retain(|edge| edge.from != id && edge.to != id);
+            }
+        }
+    }
+
+    recompute(&mut projected);
+
+    let crate_view = build_crate_view(&projected);
+    let checks = vec![
+        CheckResult {
+            name: "no_broken_dependencies".to_string(),
+            passed: broken_dependencies.is_empty(),
+            details: broken_dependencies
+                .iter()
+                .map(|broken| format!("{} depends on deleted module {}", broken.from_name, broken.missing_name))
+                .collect(),
+        },
+        check_new_module_cycles(architecture, &projected),
+        check_new_crate_cycles(&build_crate_view(architecture), &crate_view),
+    ];
+
+    Ok(SimulationReport {
+        architecture: projected,
+        crate_view,
+        broken_dependencies,
+        checks,
+    })
+}
+
+/// Find the node id a change refers to
+fn resolve_module(architecture: &ArchitectureMap, reference: &str) -> Result<String> {
+    if architecture.nodes.contains_key(reference) {
+        return Ok(reference.to_string());
+    }
+
+    let matches: Vec<&String> = architecture
+        .nodes
+        .iter()
+        .filter(|(_, node)| node.name == reference || node.file_path == reference)
+        .map(|(id, _)| id)
+        .collect();
+
+    match matches.as_slice() {
+        [id] => Ok((*id).clone()),
+        [] => Err(anyhow!("Unknown module: {}", reference)),
+        _ => Err(anyhow!(
+            "Ambiguous module reference '{}' matches {} modules; use the node id or file path",
+            reference,
+            matches.len()
+        )),
+    }
+}
+
+/// Refresh links, cycles and totals after the graph was edited
+fn recompute(architecture: &mut ArchitectureMap) {
+    let analyzer = DependencyAnalyzer::new();
+    analyzer.update_node_links(&mut architecture.nodes, &architecture.edges);
+    let nodes = ignored::counted_nodes(&architecture.nodes);
+    let edges = ignored::counted_edges(&architecture.nodes, &architecture.edges);
+    let circular_dependencies = analyzer.find_circular_dependencies(&edges);
+
+    architecture.total_modules = nodes.len();
+    architecture.total_lines = nodes.values().map(|n| n.metrics.lines_of_code).sum();
+    architecture.average_complexity = MetricsCalculator::average_complexity(&nodes);
+    architecture.metrics = MetricsCalculator::new().calculate_architecture_metrics(&nodes, &edges);
+    analyzer.mark_circular(&mut architecture.edges, &circular_dependencies);
+    architecture.circular_dependencies = circular_dependencies;
+}
+
+fn check_new_module_cycles(current: &ArchitectureMap, projected: &ArchitectureMap) -> CheckResult {
+    // Cycles are compared by their member set since the reported start node may differ
+    let cycle_key = |cycle: &Vec<String>| cycle.iter().cloned().collect::<BTreeSet<_>>();
+    let existing: HashSet<BTreeSet<String>> = current.circular_dependencies.iter().map(cycle_key).collect();
+
+    let details = projected
+        .circular_dependencies
+        .iter()
+        .filter(|cycle| !existing.contains(&cycle_key(cycle)))
+        .map(|cycle| {
+            cycle
+                .iter()
+                .map(|id| projected.nodes.get(id).map_or(id.as_str(), |node| node.name.as_str()))
+                .collect::<Vec<_>>()
+                .join(" -> ")
+        })
+        .collect::<Vec<_>>();
+
+    CheckResult {
+        name: "no_new_module_cycles".to_string(),
+        passed: details.is_empty(),
+        details,
+    }
+}
+
+fn check_new_crate_cycles(current: &CrateView, projected: &CrateView) -> CheckResult {
+    let existing: HashSet<(&str, &str)> = current
+        .edges
+        .iter()
+        .filter(|edge| edge.is_circular)
+        .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+        .collect();
+
+    let details = projected
+        .edges
+        .iter()
+        .filter(|edge| edge.is_circular && edge.from < edge.to)
+        .filter(|edge| !existing.contains(&(edge.from.as_str(), edge.to.as_str())))
+        .map(|edge| format!("{} <-> {}", edge.from, edge.to))
+        .collect::<Vec<_>>();
+
+    CheckResult {
+        name: "no_new_crate_cycles".to_string(),
+        passed: details.is_empty(),
+        details,
+    }
+}
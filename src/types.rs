@@ -1,14 +1,24 @@
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 /// Represents a module in the architecture
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ArchitectureNode {
     pub id: String,
     pub name: String,
     pub module_type: ModuleType,
     pub file_path: String,
+    /// Package name of the crate that owns this module
+    #[serde(default)]
+    pub crate_name: Option<String>,
+    /// Set on the files a library or binary target is compiled from
+    #[serde(default)]
+    pub entry_point: Option<EntryPoint>,
+    /// Path from the crate root, e.g. `scanner::rust_scanner`; empty for crate roots
+    #[serde(default)]
+    pub module_path: String,
     pub dependencies: Vec<String>,
     pub dependents: Vec<String>,
     pub status: NodeStatus,
@@ -18,19 +28,324 @@ pub struct ArchitectureNode {
     pub structs: Vec<StructInfo>,
     pub enums: Vec<EnumInfo>,
     pub traits: Vec<TraitInfo>,
+    /// Trait implementations for the module's types
+    #[serde(default)]
+    pub impls: Vec<ImplInfo>,
     pub position: Option<Position>,
+    /// Cargo features named in this module's `cfg` attributes
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// `cfg` predicates the module only exists under: on its `mod` declaration, its `#![cfg]` attributes and its parents'
+    #[serde(default)]
+    pub cfg: Vec<String>,
+    /// Broken rules from the `[naming]` config section
+    #[serde(default)]
+    pub naming_violations: Vec<String>,
+    /// Layer from the `[rules]` config section the module belongs to
+    #[serde(default)]
+    pub layer: Option<String>,
+    /// Bounded context from the `[components]` config section the module belongs to
+    #[serde(default)]
+    pub component: Option<String>,
+    /// Groups from the `[groups]` config section the module belongs to, by name
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Matched by the `[ignore]` config section; shown on request but not counted
+    #[serde(default)]
+    pub ignored: bool,
+    /// ADRs, READMEs and wiki pages describing the module
+    #[serde(default)]
+    pub documentation: Vec<DocumentationLink>,
+    /// Panic points and unsafe code outside tests
+    #[serde(default)]
+    pub robustness: Robustness,
+    /// Public items and which of them lack a doc comment
+    #[serde(default)]
+    pub doc_coverage: DocCoverage,
+    /// HTTP endpoints the module registers with axum or actix-web
+    #[serde(default)]
+    pub routes: Vec<HttpRoute>,
+    /// Database crates the module uses, when it uses any
+    #[serde(default)]
+    pub data_access: Option<DataAccess>,
+    /// Authorship from `git blame`, when the `[ownership]` section enables it
+    #[serde(default)]
+    pub ownership: Option<Ownership>,
+    /// Weakly connected items that could move to a module of their own
+    #[serde(default)]
+    pub split_suggestion: Option<SplitSuggestion>,
+    /// Items no entry point, test or public API reaches
+    #[serde(default)]
+    pub dead_code: DeadCode,
+    /// Public signatures naming a type of another crate of the workspace
+    #[serde(default)]
+    pub api_leaks: Vec<ApiLeak>,
+    /// Data attached by [`ScanHook`](crate::scanner::ScanHook)s, e.g. a service catalog id
+    #[serde(default)]
+    pub annotations: BTreeMap<String, String>,
+    /// Where each entry of `dependencies` is referenced; copied onto the edges it produces
+    #[serde(skip)]
+    pub dependency_sites: HashMap<String, Vec<SourceLocation>>,
+    /// Root crates of the module's `use` paths outside `crate`, with where they are used
+    #[serde(skip)]
+    pub external_uses: BTreeMap<String, Vec<SourceLocation>>,
+    /// What the module takes from each entry of `dependencies`; decides the relationship of its edges
+    #[serde(skip)]
+    pub imported_items: HashMap<String, Vec<ImportedItem>>,
+    /// Crate root file of the module's tree, relative to the project; `module_path` is within it
+    #[serde(skip)]
+    pub module_root: String,
+    /// Items and the names they mention, for the reachability analysis
+    #[serde(skip)]
+    pub item_references: ItemReferences,
+    /// Signatures of the module's public items, for the API leak analysis
+    #[serde(skip)]
+    pub public_signatures: Vec<PublicSignature>,
+    /// `cfg` predicates on the file itself and on each of its `mod name;` declarations
+    #[serde(skip)]
+    pub cfg_gates: CfgGates,
+}
+
+/// `cfg` attributes a file puts on itself and on the modules it declares
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CfgGates {
+    /// Predicates of `#![cfg(...)]` attributes
+    pub own: Vec<String>,
+    /// Predicates of the `#[cfg(...)]` attributes on each `mod name;`, by module name
+    pub children: HashMap<String, Vec<String>>,
+}
+
+/// The items of one file and the names each of them mentions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemReferences {
+    pub items: Vec<ReferencingItem>,
+    /// Child modules declared `pub mod`
+    pub public_modules: Vec<String>,
+}
+
+/// A function, type, trait or other item, as the reachability analysis sees it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferencingItem {
+    /// Name the item is reached by; a trait impl goes by the name of its type
+    pub name: String,
+    /// Counted towards dead code; trait impls, constants and test code are not
+    pub counted: bool,
+    /// `pub` and only inside `pub` inline modules
+    pub is_public: bool,
+    /// A test or bench function, or inside a `#[cfg(test)]` module
+    pub is_test: bool,
+    /// Identifiers in the item's tokens
+    pub mentions: Vec<String>,
+}
+
+/// A public item's signature and the paths it names, resolved through the file's `use` declarations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicSignature {
+    /// e.g. `fn load`, `struct Settings` or `Settings::new`
+    pub item: String,
+    pub signature: String,
+    pub line: usize,
+    pub paths: Vec<String>,
+}
+
+/// A type of another workspace crate in a library's public API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ApiLeak {
+    pub item: String,
+    pub signature: String,
+    /// The crate the type is defined in
+    pub crate_name: String,
+    /// Path of the type, e.g. `storage::model::Record`
+    pub type_path: String,
+    pub line: usize,
+}
+
+/// Functions and types of a module no entry point reaches
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DeadCode {
+    /// Items the analysis counted
+    pub item_count: usize,
+    pub unreachable_items: Vec<String>,
+}
+
+/// How the depending module uses an imported name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemUsage {
+    /// `mod name;` or `mod name { .. }`
+    Declared,
+    /// `impl Name for ..`
+    Implemented,
+    /// `name!(..)`
+    Invoked,
+    /// `name(..)`, or `name::function(..)` for an imported module
+    Called,
+    /// Only named, e.g. as a type
+    Named,
+}
+
+/// A name one module imports from another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedItem {
+    /// Name in the module depended on
+    pub name: String,
+    pub usage: ItemUsage,
+}
+
+/// How likely a module is to crash or misbehave on bad input
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Robustness {
+    pub unsafe_blocks: usize,
+    pub unwraps: usize,
+    pub expects: usize,
+    /// `panic!`, `unreachable!`, `todo!` and `unimplemented!`
+    pub panics: usize,
+    /// Reads from the environment, stdin, files, sockets or request extractors
+    pub handles_input: bool,
+    /// From 0 (fragile) to 100, see `MetricsCalculator::metric_formulas`
+    pub score: f64,
+}
+
+impl Robustness {
+    /// Band of the score: `robust`, `fair` or `fragile`
+    pub fn level(&self) -> &'static str {
+        if self.score >= 80.0 {
+            "robust"
+        } else if self.score >= 50.0 {
+            "fair"
+        } else {
+            "fragile"
+        }
+    }
+}
+
+/// A module's use of sqlx, diesel or SeaORM
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DataAccess {
+    pub libraries: Vec<String>,
+    /// Query calls and macros
+    pub queries: usize,
+    /// Table declarations, entity derives and row types
+    pub schema_references: usize,
+}
+
+/// An HTTP endpoint registered in a module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HttpRoute {
+    /// Upper-case method, or `ANY`
+    pub method: String,
+    /// Path as registered, without prefixes added by `nest` or `scope`
+    pub path: String,
+    pub handler: Option<String>,
+    pub line: usize,
+}
+
+/// Documentation of a module's public items
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DocCoverage {
+    pub public_items: usize,
+    pub undocumented: Vec<UndocumentedItem>,
+}
+
+impl DocCoverage {
+    pub fn documented_items(&self) -> usize {
+        self.public_items - self.undocumented.len()
+    }
+
+    /// Share of documented public items from 0 to 1; modules without public items count as documented
+    pub fn ratio(&self) -> f64 {
+        if self.public_items == 0 {
+            1.0
+        } else {
+            self.documented_items() as f64 / self.public_items as f64
+        }
+    }
+}
+
+/// A public item without a doc comment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct UndocumentedItem {
+    pub name: String,
+    /// `fn`, `struct`, `enum`, `trait`, ...
+    pub kind: String,
+    pub line: usize,
+}
+
+/// Who wrote a module's current lines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Ownership {
+    pub top_author: String,
+    /// Share of lines last changed by `top_author`, from 0 to 1
+    pub top_author_share: f64,
+    pub authors: usize,
+    /// Fewest authors who together wrote more than half of the lines
+    pub bus_factor: usize,
+    /// Bus factor of 1 on a complex module
+    pub organizational_risk: bool,
+}
+
+/// Items of a module that barely reference the rest of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SplitSuggestion {
+    /// Items that stay in the module
+    pub stay: Vec<String>,
+    /// Items that would move to a new module
+    pub moved: Vec<String>,
+    /// References between the two groups
+    pub cross_references: usize,
+}
+
+/// A line of source code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SourceLocation {
+    pub file_path: String,
+    /// 1-based line number
+    pub line: usize,
+    /// The trimmed source line
+    pub snippet: String,
+}
+
+impl SourceLocation {
+    /// Location of the byte `offset` into the `content` of the file at `file_path`
+    pub fn at_offset(file_path: &str, content: &str, offset: usize) -> Self {
+        let line_start = content[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = content[offset..].find('\n').map_or(content.len(), |index| offset + index);
+        Self {
+            file_path: file_path.to_string(),
+            line: content[..offset].matches('\n').count() + 1,
+            snippet: content[line_start..line_end].trim().to_string(),
+        }
+    }
+}
+
+/// A document linked to a module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DocumentationLink {
+    pub title: String,
+    /// URL, or file path relative to the project directory
+    pub target: String,
+    /// Whether `target` is a URL rather than a project file
+    pub external: bool,
 }
 
 /// Position of a node in the visualization
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
     pub z: f64,
 }
 
+/// Kind of target a crate root module is compiled into
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum EntryPoint {
+    /// `src/lib.rs` or the `[lib]` path: the crate's exported API
+    Library,
+    /// `src/main.rs`, `src/bin/*` or a `[[bin]]` path
+    Binary,
+}
+
 /// Types of modules in the architecture
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub enum ModuleType {
     Core,
     DataProcessing,
@@ -43,6 +358,12 @@ pub enum ModuleType {
     Processing,
     Scaffold,
     Testing,
+    /// File under a crate's `tests/` directory
+    IntegrationTest,
+    /// File under a crate's `benches/` directory
+    Bench,
+    /// Code written by a tool, e.g. prost or tonic output
+    Generated,
     Utilities,
     Configuration,
     Database,
@@ -50,10 +371,37 @@ pub enum ModuleType {
     Security,
     Logging,
     Monitoring,
+    /// Dependency from a `Cargo.toml`, added when `include_external_crates` is set
+    ExternalCrate,
     Other(String),
 }
 
 impl ModuleType {
+    /// Every type a module file can be given (all but `ExternalCrate` and `Other`), in the order the UI offers them
+    pub const BUILTIN: &'static [ModuleType] = &[
+        ModuleType::Core,
+        ModuleType::DataProcessing,
+        ModuleType::AI,
+        ModuleType::Performance,
+        ModuleType::Validation,
+        ModuleType::Execution,
+        ModuleType::Integration,
+        ModuleType::API,
+        ModuleType::Processing,
+        ModuleType::Scaffold,
+        ModuleType::Testing,
+        ModuleType::IntegrationTest,
+        ModuleType::Bench,
+        ModuleType::Generated,
+        ModuleType::Utilities,
+        ModuleType::Configuration,
+        ModuleType::Database,
+        ModuleType::Network,
+        ModuleType::Security,
+        ModuleType::Logging,
+        ModuleType::Monitoring,
+    ];
+
     pub fn color(&self) -> &'static str {
         match self {
             ModuleType::Core => "#e74c3c",
@@ -67,6 +415,9 @@ impl ModuleType {
             ModuleType::Processing => "#8e44ad",
             ModuleType::Scaffold => "#16a085",
             ModuleType::Testing => "#f1c40f",
+            ModuleType::IntegrationTest => "#d4ac0d",
+            ModuleType::Bench => "#ca6f1e",
+            ModuleType::Generated => "#aab7b8",
             ModuleType::Utilities => "#95a5a6",
             ModuleType::Configuration => "#7f8c8d",
             ModuleType::Database => "#27ae60",
@@ -74,10 +425,26 @@ impl ModuleType {
             ModuleType::Security => "#c0392b",
             ModuleType::Logging => "#8e44ad",
             ModuleType::Monitoring => "#d35400",
+            ModuleType::ExternalCrate => "#64748b",
             ModuleType::Other(_) => "#bdc3c7",
         }
     }
 
+    /// Whether a tool wrote this module; it is left out of complexity aggregates
+    pub fn is_generated(&self) -> bool {
+        matches!(self, ModuleType::Generated)
+    }
+
+    /// Whether this node stands for a dependency rather than a source file
+    pub fn is_external(&self) -> bool {
+        matches!(self, ModuleType::ExternalCrate)
+    }
+
+    /// Whether this module is an integration test or bench target rather than library code
+    pub fn is_test_target(&self) -> bool {
+        matches!(self, ModuleType::IntegrationTest | ModuleType::Bench)
+    }
+
     pub fn icon(&self) -> &'static str {
         match self {
             ModuleType::Core => "⚙️",
@@ -91,6 +458,9 @@ impl ModuleType {
             ModuleType::Processing => "⚙️",
             ModuleType::Scaffold => "🏗️",
             ModuleType::Testing => "🧪",
+            ModuleType::IntegrationTest => "🧪",
+            ModuleType::Bench => "⏱️",
+            ModuleType::Generated => "🏭",
             ModuleType::Utilities => "🛠️",
             ModuleType::Configuration => "⚙️",
             ModuleType::Database => "🗄️",
@@ -98,6 +468,7 @@ impl ModuleType {
             ModuleType::Security => "🔒",
             ModuleType::Logging => "📝",
             ModuleType::Monitoring => "📈",
+            ModuleType::ExternalCrate => "📚",
             ModuleType::Other(_) => "📦",
         }
     }
@@ -116,6 +487,9 @@ impl ModuleType {
             ModuleType::Processing => "Processing".to_string(),
             ModuleType::Scaffold => "Scaffold".to_string(),
             ModuleType::Testing => "Testing".to_string(),
+            ModuleType::IntegrationTest => "Integration Test".to_string(),
+            ModuleType::Bench => "Bench".to_string(),
+            ModuleType::Generated => "Generated".to_string(),
             ModuleType::Utilities => "Utilities".to_string(),
             ModuleType::Configuration => "Configuration".to_string(),
             ModuleType::Database => "Database".to_string(),
@@ -123,13 +497,14 @@ impl ModuleType {
             ModuleType::Security => "Security".to_string(),
             ModuleType::Logging => "Logging".to_string(),
             ModuleType::Monitoring => "Monitoring".to_string(),
+            ModuleType::ExternalCrate => "External Crate".to_string(),
             ModuleType::Other(label) => label.clone(),
         }
     }
 }
 
 /// Status of a module
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub enum NodeStatus {
     Active,
     Inactive,
@@ -140,7 +515,7 @@ pub enum NodeStatus {
 }
 
 /// Metrics for a module
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct NodeMetrics {
     pub lines_of_code: usize,
     pub complexity_score: f64,
@@ -156,10 +531,22 @@ pub struct NodeMetrics {
     pub dependent_count: usize,
     pub cyclomatic_complexity: f64,
     pub cognitive_complexity: f64,
+    /// Lines changed in recent history (populated by git analysis when available)
+    #[serde(default)]
+    pub recent_churn: usize,
+    /// Commits that touched the file (populated by git analysis when available)
+    #[serde(default)]
+    pub commit_count: usize,
+    /// Author of the newest commit that touched the file
+    #[serde(default)]
+    pub last_author: Option<String>,
+    /// Clippy lints in the file by category, e.g. `perf`; `None` unless `[clippy]` is enabled
+    #[serde(default)]
+    pub clippy_lints: Option<BTreeMap<String, usize>>,
 }
 
 /// Information about a function
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct FunctionInfo {
     pub name: String,
     pub is_public: bool,
@@ -169,10 +556,13 @@ pub struct FunctionInfo {
     pub lines_of_code: usize,
     pub documentation: Option<String>,
     pub attributes: Vec<String>,
+    /// 1-based line of the `fn` keyword
+    #[serde(default)]
+    pub line: usize,
 }
 
 /// Information about a struct
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct StructInfo {
     pub name: String,
     pub is_public: bool,
@@ -184,7 +574,7 @@ pub struct StructInfo {
 }
 
 /// Information about an enum
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct EnumInfo {
     pub name: String,
     pub is_public: bool,
@@ -196,7 +586,7 @@ pub struct EnumInfo {
 }
 
 /// Information about a trait
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TraitInfo {
     pub name: String,
     pub is_public: bool,
@@ -207,18 +597,40 @@ pub struct TraitInfo {
     pub supertraits: Vec<String>,
 }
 
+/// A trait implemented for a type, e.g. `impl Display for Config`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ImplInfo {
+    /// The trait as written, e.g. `Display` or `crate::scanner::ScanHook`
+    pub trait_path: String,
+    /// Name of the implementing type, without generics or references
+    pub self_type: String,
+    pub line: usize,
+    /// Id of the module defining the trait, when it is part of the project
+    #[serde(default)]
+    pub trait_module: Option<String>,
+}
+
 /// A dependency relationship between modules
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct DependencyEdge {
     pub from: String,
     pub to: String,
     pub relationship: DependencyType,
     pub strength: f64,
     pub is_circular: bool,
+    /// The edge points from a lower layer up to a higher one
+    #[serde(default)]
+    pub layer_violation: bool,
+    /// The `use` statements and declarations that create the dependency
+    #[serde(default)]
+    pub locations: Vec<SourceLocation>,
+    /// Ways to remove the edge, for edges that break the layering
+    #[serde(default)]
+    pub suggestions: Vec<String>,
 }
 
 /// Types of dependencies
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, PartialOrd, Ord)]
 pub enum DependencyType {
     Uses,
     Implements,
@@ -228,11 +640,16 @@ pub enum DependencyType {
     Calls,
     References,
     Contains,
+    /// An integration test or bench exercising a library module
+    Exercises,
 }
 
 /// Complete architecture map
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ArchitectureMap {
+    /// Version of this JSON's shape, see `schema::SCHEMA_VERSION`; 0 for maps written before versioning
+    #[serde(default)]
+    pub schema_version: u32,
     pub nodes: HashMap<String, ArchitectureNode>,
     pub edges: Vec<DependencyEdge>,
     pub last_scan: DateTime<Utc>,
@@ -241,10 +658,311 @@ pub struct ArchitectureMap {
     pub average_complexity: f64,
     pub circular_dependencies: Vec<Vec<String>>,
     pub metrics: ArchitectureMetrics,
+    /// Result of the watch command that ran before this scan, if any
+    #[serde(default)]
+    pub hook_outcome: Option<HookOutcome>,
+    /// Features declared by each scanned crate
+    #[serde(default)]
+    pub cargo_features: Vec<CrateFeatures>,
+    /// Version, edition and MSRV of each scanned crate
+    #[serde(default)]
+    pub crates: Vec<CrateMetadata>,
+    /// How this scan was produced
+    #[serde(default)]
+    pub provenance: Option<ScanProvenance>,
+    /// Unusual changes since the latest stored snapshot
+    #[serde(default)]
+    pub anomalies: Vec<Anomaly>,
+    /// Health score of each crate in the latest stored snapshot, by crate name
+    #[serde(default)]
+    pub previous_crate_health: BTreeMap<String, f64>,
+    /// Ids of the modules no library, binary, example or build script reaches
+    #[serde(default)]
+    pub orphans: Vec<String>,
+    /// The scan evaluated under each configured feature set and target
+    #[serde(default)]
+    pub variants: Vec<ArchitectureVariant>,
+    /// Directory tree of the modules, each directory before its subdirectories
+    #[serde(default)]
+    pub module_groups: Vec<ModuleGroup>,
+}
+
+/// A directory of the project with the modules and subdirectories inside it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ModuleGroup {
+    /// Path of the directory relative to the project, such as `src/web`
+    pub id: String,
+    /// Last component of the path
+    pub name: String,
+    /// Id of the enclosing directory's group; `None` for the top-level directories
+    pub parent: Option<String>,
+    /// Ids of the groups of the directories directly inside
+    pub children: Vec<String>,
+    /// Ids of the modules directly inside
+    pub modules: Vec<String>,
+    /// Modules anywhere below the directory
+    pub module_count: usize,
+    pub lines_of_code: usize,
+}
+
+/// The modules that exist under one of the configured `scanning.variants`, and their totals
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ArchitectureVariant {
+    pub name: String,
+    /// Requested features, as configured
+    pub features: Vec<String>,
+    pub target: Option<String>,
+    /// Ids of the modules whose `cfg` rules them out
+    pub excluded: Vec<String>,
+    pub total_modules: usize,
+    pub total_lines: usize,
+    pub average_complexity: f64,
+    pub circular_dependencies: usize,
+}
+
+impl ArchitectureMap {
+    /// Id of every edge, in edge order.
+    ///
+    /// Ids count repeats of the same pair so they stay stable between scans.
+    pub fn edge_ids(&self) -> Vec<String> {
+        let mut pair_counts: HashMap<(&str, &str), usize> = HashMap::new();
+        self.edges
+            .iter()
+            .map(|edge| {
+                let occurrence = pair_counts.entry((edge.from.as_str(), edge.to.as_str())).or_default();
+                let id = format!("edge-{}-{}-{}", edge.from, edge.to, occurrence);
+                *occurrence += 1;
+                id
+            })
+            .collect()
+    }
+
+    /// Check that the map is internally consistent, failing with every problem found.
+    ///
+    /// Edges, cycles and orphans must name existing modules, each module's
+    /// `dependents` and link counts must match the edges, every step of a cycle
+    /// must be an edge, and the totals must add up over the counted modules.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut problems = Vec::new();
+        for (key, node) in &self.nodes {
+            if *key != node.id {
+                problems.push(format!("module {} is stored under the id {}", node.id, key));
+            }
+        }
+
+        let mut dependents: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+        let mut dependency_counts: HashMap<&str, usize> = HashMap::new();
+        let mut linked: HashSet<(&str, &str)> = HashSet::new();
+        for edge in &self.edges {
+            for end in [&edge.from, &edge.to] {
+                if !self.nodes.contains_key(end) {
+                    problems.push(format!("edge {} -> {} points at the missing module {}", edge.from, edge.to, end));
+                }
+            }
+            dependents.entry(edge.to.as_str()).or_default().insert(edge.from.as_str());
+            *dependency_counts.entry(edge.from.as_str()).or_default() += 1;
+            linked.insert((edge.from.as_str(), edge.to.as_str()));
+        }
+        for node in self.nodes.values() {
+            let expected = dependents.remove(node.id.as_str()).unwrap_or_default();
+            let actual: BTreeSet<&str> = node.dependents.iter().map(String::as_str).collect();
+            if actual != expected {
+                problems.push(format!(
+                    "module {} lists dependents {:?}, but its incoming edges come from {:?}",
+                    node.id, actual, expected
+                ));
+            }
+            if node.metrics.dependent_count != node.dependents.len() {
+                problems.push(format!(
+                    "module {} has dependent_count {} for {} dependents",
+                    node.id,
+                    node.metrics.dependent_count,
+                    node.dependents.len()
+                ));
+            }
+            let outgoing = dependency_counts.get(node.id.as_str()).copied().unwrap_or_default();
+            if node.metrics.dependency_count != outgoing {
+                problems.push(format!(
+                    "module {} has dependency_count {} for {} outgoing edges",
+                    node.id, node.metrics.dependency_count, outgoing
+                ));
+            }
+        }
+
+        for cycle in &self.circular_dependencies {
+            for (index, from) in cycle.iter().enumerate() {
+                let to = &cycle[(index + 1) % cycle.len()];
+                if !self.nodes.contains_key(from) {
+                    problems.push(format!("cycle {:?} names the missing module {}", cycle, from));
+                } else if !linked.contains(&(from.as_str(), to.as_str())) {
+                    problems.push(format!("cycle {:?} steps from {} to {} without an edge", cycle, from, to));
+                }
+            }
+        }
+        for orphan in self.orphans.iter().filter(|orphan| !self.nodes.contains_key(*orphan)) {
+            problems.push(format!("orphan {} is not a module", orphan));
+        }
+
+        let counted = crate::scanner::ignored::counted_nodes(&self.nodes);
+        let total_lines: usize = counted.values().map(|node| node.metrics.lines_of_code).sum();
+        let average_complexity = crate::scanner::metrics_calculator::MetricsCalculator::average_complexity(&counted);
+        if self.total_modules != counted.len() {
+            problems.push(format!("total_modules is {}, but {} modules are counted", self.total_modules, counted.len()));
+        }
+        if self.total_lines != total_lines {
+            problems.push(format!("total_lines is {}, but the counted modules have {} lines", self.total_lines, total_lines));
+        }
+        if (self.average_complexity - average_complexity).abs() > 1e-6 {
+            problems.push(format!(
+                "average_complexity is {}, but the counted modules average {}",
+                self.average_complexity, average_complexity
+            ));
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        const SHOWN: usize = 20;
+        let mut message = format!("Architecture data is inconsistent ({} problems):", problems.len());
+        for problem in problems.iter().take(SHOWN) {
+            message.push_str("\n  - ");
+            message.push_str(problem);
+        }
+        if problems.len() > SHOWN {
+            message.push_str(&format!("\n  ... and {} more", problems.len() - SHOWN));
+        }
+        anyhow::bail!(message)
+    }
+}
+
+/// What kind of jump an anomaly reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    ModuleCountJump,
+    NewCrate,
+    ComplexitySpike,
+    ModuleComplexitySpike,
+}
+
+/// A statistically unusual change between two consecutive scans
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub message: String,
+    /// Module the anomaly is about, if any
+    pub node_id: Option<String>,
+}
+
+/// Tool, source revision and environment a scan came from
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ScanProvenance {
+    pub tool_version: String,
+    /// `HEAD` of the project's git repository, if it is one
+    pub git_commit: Option<String>,
+    /// Whether the work tree had uncommitted changes
+    pub git_dirty: Option<bool>,
+    pub scan_duration_ms: u64,
+    /// SHA-256 of the effective configuration
+    pub config_hash: String,
+    pub host: Option<String>,
+}
+
+/// Package metadata of one crate from its `Cargo.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CrateMetadata {
+    pub name: String,
+    pub version: Option<String>,
+    pub edition: String,
+    /// Declared minimum supported Rust version
+    pub rust_version: Option<String>,
+}
+
+/// The `[features]` of one crate
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CrateFeatures {
+    pub crate_name: String,
+    pub features: Vec<FeatureDefinition>,
+    pub optional_dependencies: Vec<String>,
+}
+
+/// A single entry of a `[features]` table
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FeatureDefinition {
+    pub name: String,
+    /// Raw values, e.g. `other-feature`, `dep:serde` or `serde/derive`
+    pub enables: Vec<String>,
+}
+
+/// Result of running the configured watch command
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HookOutcome {
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub diagnostics: Vec<HookDiagnostic>,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// Progress of the watch command, shown in the page's build strip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum BuildStatus {
+    /// The command started after a detected change
+    Building { command: String },
+    /// The command finished; `failing_files` hold its errors
+    Finished {
+        command: String,
+        success: bool,
+        error_count: usize,
+        warning_count: usize,
+        duration_ms: u64,
+        failing_files: Vec<String>,
+    },
+}
+
+impl BuildStatus {
+    pub fn from_outcome(outcome: &HookOutcome) -> Self {
+        let mut failing_files: Vec<String> = outcome
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.level == DiagnosticLevel::Error)
+            .filter_map(|diagnostic| diagnostic.file.clone())
+            .collect();
+        failing_files.sort();
+        failing_files.dedup();
+        BuildStatus::Finished {
+            command: outcome.command.clone(),
+            success: outcome.success,
+            error_count: outcome.error_count,
+            warning_count: outcome.warning_count,
+            duration_ms: outcome.duration_ms,
+            failing_files,
+        }
+    }
+}
+
+/// A compiler-style diagnostic parsed from command output
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct HookDiagnostic {
+    pub level: DiagnosticLevel,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
+
+/// Severity of a parsed diagnostic
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
 }
 
 /// Overall architecture metrics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct ArchitectureMetrics {
     pub total_functions: usize,
     pub total_structs: usize,
@@ -255,10 +973,85 @@ pub struct ArchitectureMetrics {
     pub dependency_density: f64,
     pub modularity_score: f64,
     pub maintainability_index: f64,
+    /// Overall health from 0 (poor) to 100, see `MetricsCalculator::metric_formulas`
+    #[serde(default)]
+    pub health_score: f64,
+    /// Number of edges of each relationship
+    #[serde(default)]
+    pub edges_by_relationship: BTreeMap<DependencyType, usize>,
+}
+
+/// Changes from one scan of a project to a later one, see `diff::compare`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchitectureDiff {
+    pub before_scan: DateTime<Utc>,
+    pub after_scan: DateTime<Utc>,
+    /// Modules only the later scan has, by file path
+    pub added_modules: Vec<ModuleRef>,
+    /// Modules only the earlier scan has, by file path
+    pub removed_modules: Vec<ModuleRef>,
+    /// Pairs of modules linked only in the later scan
+    pub added_dependencies: Vec<DependencyChange>,
+    /// Pairs of modules linked only in the earlier scan
+    pub removed_dependencies: Vec<DependencyChange>,
+    /// Cycles of the later scan, as member file paths, that the earlier one does not have
+    pub new_cycles: Vec<Vec<String>>,
+    /// Cycles of the earlier scan that are gone
+    pub resolved_cycles: Vec<Vec<String>>,
+    /// Project-wide metrics in both scans
+    pub metrics: Vec<MetricDelta>,
+    /// Modules in both scans whose complexity or size changed, largest complexity change first
+    pub changed_modules: Vec<ModuleChange>,
+}
+
+/// A module named in a diff
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleRef {
+    pub id: String,
+    pub name: String,
+    pub file_path: String,
+}
+
+/// A dependency between two modules that appeared or disappeared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyChange {
+    pub from: ModuleRef,
+    pub to: ModuleRef,
+    pub relationship: DependencyType,
+}
+
+/// One metric in two scans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub metric: String,
+    pub before: f64,
+    pub after: f64,
+    pub delta: f64,
+}
+
+/// Complexity and size of a module in two scans
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleChange {
+    pub module: ModuleRef,
+    pub complexity_before: f64,
+    pub complexity_after: f64,
+    pub lines_before: usize,
+    pub lines_after: usize,
+    /// Commits between the two scans' git commits that touched the file, newest first
+    #[serde(default)]
+    pub commits: Vec<FileCommit>,
+}
+
+/// A commit touching a module's file, see `git_analyzer::commits_between`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileCommit {
+    pub hash: String,
+    pub author: String,
+    pub subject: String,
 }
 
 // Re-export VisualizationSettings from config
 pub use crate::config::project_config::VisualizationSettings;
 
 // Re-export Theme and LayoutType from config
-pub use crate::config::project_config::{LayoutType, Theme};
+pub use crate::config::project_config::{EdgeLabels, LayoutType, NodeSizeMetric, PageSections, ParserBackend, StatCard, Theme};
@@ -0,0 +1,137 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::types::ArchitectureMap;
+
+/// Bucket for modules no `[components]` convention places anywhere
+pub const UNASSIGNED_COMPONENT: &str = "(no component)";
+
+/// Aggregated metrics of all modules in one bounded context
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentSummary {
+    pub name: String,
+    /// Crates with at least one module in the component
+    pub crates: Vec<String>,
+    /// `[groups]` groups any of the component's modules belong to
+    pub groups: Vec<String>,
+    pub module_count: usize,
+    pub lines_of_code: usize,
+    pub function_count: usize,
+    pub average_complexity: f64,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub dependency_count: usize,
+    pub dependent_count: usize,
+}
+
+/// Module edges between two components folded into one
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentEdge {
+    pub from: String,
+    pub to: String,
+    pub edge_count: usize,
+    pub is_circular: bool,
+}
+
+/// Component-level projection of an architecture map, one level above crates
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentView {
+    pub components: Vec<ComponentSummary>,
+    pub edges: Vec<ComponentEdge>,
+}
+
+/// Collapse module nodes into their components; empty when no module has one
+pub fn build_component_view(architecture: &ArchitectureMap) -> ComponentView {
+    if architecture.nodes.values().all(|node| node.component.is_none()) {
+        return ComponentView { components: Vec::new(), edges: Vec::new() };
+    }
+    let component_of = |node_id: &str| -> &str {
+        architecture
+            .nodes
+            .get(node_id)
+            .and_then(|node| node.component.as_deref())
+            .unwrap_or(UNASSIGNED_COMPONENT)
+    };
+
+    let mut components: BTreeMap<&str, ComponentSummary> = BTreeMap::new();
+    let mut crates: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    let mut groups: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for node in architecture.nodes.values() {
+        let name = node.component.as_deref().unwrap_or(UNASSIGNED_COMPONENT);
+        let summary = components.entry(name).or_insert_with(|| ComponentSummary {
+            name: name.to_string(),
+            crates: Vec::new(),
+            groups: Vec::new(),
+            module_count: 0,
+            lines_of_code: 0,
+            function_count: 0,
+            average_complexity: 0.0,
+            error_count: 0,
+            warning_count: 0,
+            dependency_count: 0,
+            dependent_count: 0,
+        });
+        summary.module_count += 1;
+        summary.lines_of_code += node.metrics.lines_of_code;
+        summary.function_count += node.metrics.function_count;
+        // Holds the running sum until the averages are taken below
+        summary.average_complexity += node.metrics.complexity_score;
+        summary.error_count += node.metrics.error_count;
+        summary.warning_count += node.metrics.warning_count;
+        if let Some(crate_name) = node.crate_name.as_deref() {
+            crates.entry(name).or_default().insert(crate_name);
+        }
+        groups.entry(name).or_default().extend(node.groups.iter().map(String::as_str));
+    }
+    for (name, summary) in components.iter_mut() {
+        summary.average_complexity /= summary.module_count as f64;
+        summary.crates = crates
+            .remove(name)
+            .unwrap_or_default()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        summary.groups = groups.remove(name).unwrap_or_default().into_iter().map(str::to_string).collect();
+    }
+
+    let mut edges: BTreeMap<(&str, &str), ComponentEdge> = BTreeMap::new();
+    for edge in &architecture.edges {
+        let (from, to) = (component_of(&edge.from), component_of(&edge.to));
+        if from == to {
+            continue;
+        }
+        let entry = edges.entry((from, to)).or_insert_with(|| ComponentEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            edge_count: 0,
+            is_circular: false,
+        });
+        entry.edge_count += 1;
+    }
+
+    // Components that depend on each other in both directions are not separate contexts
+    let pairs: Vec<(&str, &str)> = edges.keys().copied().collect();
+    for (from, to) in pairs {
+        if edges.contains_key(&(to, from)) {
+            if let Some(edge) = edges.get_mut(&(from, to)) {
+                edge.is_circular = true;
+            }
+        }
+    }
+
+    let mut degree: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (from, to) in edges.keys() {
+        degree.entry(from).or_default().0 += 1;
+        degree.entry(to).or_default().1 += 1;
+    }
+    for (name, summary) in components.iter_mut() {
+        let (out_degree, in_degree) = degree.get(name).copied().unwrap_or_default();
+        summary.dependency_count = out_degree;
+        summary.dependent_count = in_degree;
+    }
+
+    ComponentView {
+        components: components.into_values().collect(),
+        edges: edges.into_values().collect(),
+    }
+}
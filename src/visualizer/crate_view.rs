@@ -0,0 +1,244 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    scanner::metrics_calculator::MetricsCalculator,
+    types::{ArchitectureMap, ArchitectureNode, CrateMetadata, DependencyEdge},
+};
+
+/// Bucket for modules that could not be attributed to any crate manifest
+pub const UNOWNED_CRATE: &str = "(no crate)";
+
+/// Aggregated metrics of all modules owned by one crate
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateSummary {
+    pub name: String,
+    pub module_count: usize,
+    pub lines_of_code: usize,
+    pub function_count: usize,
+    pub average_complexity: f64,
+    pub max_complexity: f64,
+    pub error_count: usize,
+    pub warning_count: usize,
+    pub dependency_count: usize,
+    pub dependent_count: usize,
+    pub version: Option<String>,
+    pub edition: Option<String>,
+    pub rust_version: Option<String>,
+    /// Where the edition or MSRV differs from the rest of the workspace
+    pub inconsistencies: Vec<String>,
+    /// Component most of the crate's modules belong to
+    pub component: Option<String>,
+    /// `[groups]` groups any of the crate's modules belong to
+    pub groups: Vec<String>,
+    /// Health score of the crate's own modules and the edges leaving them
+    pub health_score: f64,
+    /// Change of the health score since the latest stored snapshot
+    pub health_trend: Option<f64>,
+    /// Share of the crate's functions and types no entry point reaches, in percent
+    pub dead_code_percentage: f64,
+}
+
+/// Module edges between two crates folded into one
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateEdge {
+    pub from: String,
+    pub to: String,
+    pub edge_count: usize,
+    pub is_circular: bool,
+}
+
+/// Crate-level projection of an architecture map
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateView {
+    pub crates: Vec<CrateSummary>,
+    pub edges: Vec<CrateEdge>,
+}
+
+/// Collapse module nodes into their owning crates, aggregating metrics and edges
+pub fn build_crate_view(architecture: &ArchitectureMap) -> CrateView {
+    let crate_of = |node_id: &str| -> &str {
+        architecture
+            .nodes
+            .get(node_id)
+            .and_then(|node| node.crate_name.as_deref())
+            .unwrap_or(UNOWNED_CRATE)
+    };
+
+    let mut crates: BTreeMap<&str, CrateSummary> = BTreeMap::new();
+    let mut components: HashMap<&str, Vec<Option<&str>>> = HashMap::new();
+    let mut groups: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    let mut items: HashMap<&str, (usize, usize)> = HashMap::new();
+    for node in architecture.nodes.values() {
+        let name = node.crate_name.as_deref().unwrap_or(UNOWNED_CRATE);
+        let summary = crates.entry(name).or_insert_with(|| CrateSummary {
+            name: name.to_string(),
+            module_count: 0,
+            lines_of_code: 0,
+            function_count: 0,
+            average_complexity: 0.0,
+            max_complexity: 0.0,
+            error_count: 0,
+            warning_count: 0,
+            dependency_count: 0,
+            dependent_count: 0,
+            version: None,
+            edition: None,
+            rust_version: None,
+            inconsistencies: Vec::new(),
+            component: None,
+            groups: Vec::new(),
+            health_score: 0.0,
+            health_trend: None,
+            dead_code_percentage: 0.0,
+        });
+        components.entry(name).or_default().push(node.component.as_deref());
+        groups.entry(name).or_default().extend(node.groups.iter().map(String::as_str));
+        summary.module_count += 1;
+        summary.lines_of_code += node.metrics.lines_of_code;
+        summary.function_count += node.metrics.function_count;
+        // Holds the running sum until the averages are taken below
+        summary.average_complexity += node.metrics.complexity_score;
+        summary.max_complexity = summary.max_complexity.max(node.metrics.complexity_score);
+        summary.error_count += node.metrics.error_count;
+        summary.warning_count += node.metrics.warning_count;
+        let (unreachable, total) = items.entry(name).or_default();
+        *unreachable += node.dead_code.unreachable_items.len();
+        *total += node.dead_code.item_count;
+    }
+    for (name, summary) in crates.iter_mut() {
+        summary.average_complexity /= summary.module_count as f64;
+        if let Some(&(unreachable, total)) = items.get(name).filter(|(_, total)| *total > 0) {
+            summary.dead_code_percentage = unreachable as f64 * 100.0 / total as f64;
+        }
+        summary.component = components
+            .remove(name)
+            .and_then(|components| most_common(components.into_iter()).flatten())
+            .map(str::to_string);
+        summary.groups = groups.remove(name).unwrap_or_default().into_iter().map(str::to_string).collect();
+    }
+
+    let mut edges: BTreeMap<(&str, &str), CrateEdge> = BTreeMap::new();
+    for edge in &architecture.edges {
+        let (from, to) = (crate_of(&edge.from), crate_of(&edge.to));
+        if from == to {
+            continue;
+        }
+        let entry = edges.entry((from, to)).or_insert_with(|| CrateEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            edge_count: 0,
+            is_circular: false,
+        });
+        entry.edge_count += 1;
+    }
+
+    // Crates that depend on each other in both directions form a cycle at this level
+    let pairs: Vec<(&str, &str)> = edges.keys().copied().collect();
+    for (from, to) in pairs {
+        if edges.contains_key(&(to, from)) {
+            if let Some(edge) = edges.get_mut(&(from, to)) {
+                edge.is_circular = true;
+            }
+        }
+    }
+
+    let mut degree: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (from, to) in edges.keys() {
+        degree.entry(from).or_default().0 += 1;
+        degree.entry(to).or_default().1 += 1;
+    }
+    for (name, summary) in crates.iter_mut() {
+        let (out_degree, in_degree) = degree.get(name).copied().unwrap_or_default();
+        summary.dependency_count = out_degree;
+        summary.dependent_count = in_degree;
+    }
+
+    for (name, score) in crate_health(architecture) {
+        if let Some(summary) = crates.get_mut(name.as_str()) {
+            summary.health_score = score;
+            summary.health_trend = architecture.previous_crate_health.get(&name).map(|previous| score - previous);
+        }
+    }
+
+    let mut inconsistencies = toolchain_inconsistencies(&architecture.crates);
+    for metadata in &architecture.crates {
+        if let Some(summary) = crates.get_mut(metadata.name.as_str()) {
+            summary.version = metadata.version.clone();
+            summary.edition = Some(metadata.edition.clone());
+            summary.rust_version = metadata.rust_version.clone();
+            summary.inconsistencies = inconsistencies.remove(&metadata.name).unwrap_or_default();
+        }
+    }
+
+    CrateView {
+        crates: crates.into_values().collect(),
+        edges: edges.into_values().collect(),
+    }
+}
+
+/// Health score of every crate, from its modules and the edges that start in them
+pub fn crate_health(architecture: &ArchitectureMap) -> BTreeMap<String, f64> {
+    let crate_of = |node: &ArchitectureNode| node.crate_name.clone().unwrap_or_else(|| UNOWNED_CRATE.to_string());
+    let mut modules: BTreeMap<String, (HashMap<String, ArchitectureNode>, Vec<DependencyEdge>)> = BTreeMap::new();
+    for node in architecture.nodes.values() {
+        modules.entry(crate_of(node)).or_default().0.insert(node.id.clone(), node.clone());
+    }
+    for edge in &architecture.edges {
+        if let Some(node) = architecture.nodes.get(&edge.from) {
+            modules.entry(crate_of(node)).or_default().1.push(edge.clone());
+        }
+    }
+
+    let calculator = MetricsCalculator::new();
+    modules
+        .into_iter()
+        .map(|(name, (nodes, edges))| (name, calculator.health_score(&nodes, &edges)))
+        .collect()
+}
+
+/// Crates whose edition or MSRV differs from the one most of the workspace uses, with the reasons
+pub fn toolchain_inconsistencies(crates: &[CrateMetadata]) -> HashMap<String, Vec<String>> {
+    let mut found: HashMap<String, Vec<String>> = HashMap::new();
+    if crates.len() < 2 {
+        return found;
+    }
+
+    let edition = most_common(crates.iter().map(|metadata| Some(metadata.edition.as_str())));
+    let rust_version = most_common(crates.iter().map(|metadata| metadata.rust_version.as_deref()));
+    for metadata in crates {
+        if let Some(Some(edition)) = edition {
+            if metadata.edition != edition {
+                found.entry(metadata.name.clone()).or_default().push(format!(
+                    "edition {} while most crates use {}",
+                    metadata.edition, edition
+                ));
+            }
+        }
+        if let Some(expected) = rust_version {
+            let message = match (metadata.rust_version.as_deref(), expected) {
+                (actual, expected) if actual == expected => None,
+                (Some(actual), Some(expected)) => Some(format!("rust-version {} while most crates declare {}", actual, expected)),
+                (None, Some(expected)) => Some(format!("no rust-version while most crates declare {}", expected)),
+                (Some(actual), None) => Some(format!("rust-version {} while most crates declare none", actual)),
+                (None, None) => None,
+            };
+            if let Some(message) = message {
+                found.entry(metadata.name.clone()).or_default().push(message);
+            }
+        }
+    }
+    found
+}
+
+/// The most frequent value, ties going to the smallest; `None` if there are no values
+fn most_common<'a>(values: impl Iterator<Item = Option<&'a str>>) -> Option<Option<&'a str>> {
+    let mut counts: BTreeMap<Option<&str>, usize> = BTreeMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by(|(a_value, a_count), (b_value, b_count)| a_count.cmp(b_count).then(b_value.cmp(a_value)))
+        .map(|(value, _)| value)
+}
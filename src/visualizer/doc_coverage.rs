@@ -0,0 +1,127 @@
+//! Documentation coverage of public items: a badge and a table of the modules missing the most docs.
+
+use crate::{
+    types::{ArchitectureMap, ArchitectureNode},
+    visualizer::static_page::escape_html,
+};
+
+/// Modules listed on the coverage page
+const TOP_OFFENDERS: usize = 25;
+/// Undocumented items listed per module before the rest are summarized
+const LISTED_ITEMS: usize = 12;
+/// Rough width of one character of the badge font, in pixels
+const BADGE_CHAR_WIDTH: f64 = 6.5;
+
+/// Documented and total public items of hand-written modules
+pub fn project_totals(architecture: &ArchitectureMap) -> (usize, usize) {
+    hand_written(architecture).fold((0, 0), |(documented, public), node| {
+        (documented + node.doc_coverage.documented_items(), public + node.doc_coverage.public_items)
+    })
+}
+
+/// Share of documented public items from 0 to 1
+pub fn project_ratio(architecture: &ArchitectureMap) -> f64 {
+    match project_totals(architecture) {
+        (_, 0) => 1.0,
+        (documented, public) => documented as f64 / public as f64,
+    }
+}
+
+/// Shields-style SVG badge with the project's documentation coverage
+pub fn render_badge(architecture: &ArchitectureMap) -> String {
+    let ratio = project_ratio(architecture);
+    let color = match ratio {
+        r if r >= 0.9 => "#4c1",
+        r if r >= 0.75 => "#97ca00",
+        r if r >= 0.5 => "#dfb317",
+        _ => "#e05d44",
+    };
+    let label = "docs";
+    let value = format!("{:.0}%", ratio * 100.0);
+    let label_width = (label.len() as f64 * BADGE_CHAR_WIDTH + 12.0).round();
+    let value_width = (value.len() as f64 * BADGE_CHAR_WIDTH + 12.0).round();
+    let width = label_width + value_width;
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {value}">
+  <title>{label}: {value}</title>
+  <rect width="{label_width}" height="20" fill="#555"/>
+  <rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{value_x}" y="14">{value}</text>
+  </g>
+</svg>
+"##,
+        label_x = label_width / 2.0,
+        value_x = label_width + value_width / 2.0,
+    )
+}
+
+/// Table of the modules with the most undocumented public items.
+///
+/// `link` turns a file path and line into an editor link, when one is configured.
+pub fn render_offender_table(architecture: &ArchitectureMap, link: impl Fn(&str, usize) -> Option<String>) -> String {
+    let mut offenders: Vec<_> = hand_written(architecture)
+        .filter(|node| !node.doc_coverage.undocumented.is_empty())
+        .collect();
+    if offenders.is_empty() {
+        return r#"<p class="static-note">Every public item is documented.</p>"#.to_string();
+    }
+    offenders.sort_by(|a, b| {
+        b.doc_coverage
+            .undocumented
+            .len()
+            .cmp(&a.doc_coverage.undocumented.len())
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    let total = offenders.len();
+    offenders.truncate(TOP_OFFENDERS);
+
+    let linked = |file_path: &str, line: usize, label: &str| match link(file_path, line) {
+        Some(href) => format!("<a href=\"{}\">{}</a>", escape_html(&href), escape_html(label)),
+        None => escape_html(label),
+    };
+    let rows: String = offenders
+        .iter()
+        .map(|node| {
+            let coverage = &node.doc_coverage;
+            let mut items: Vec<String> = coverage
+                .undocumented
+                .iter()
+                .take(LISTED_ITEMS)
+                .map(|item| linked(&node.file_path, item.line, &format!("{} {} (line {})", item.kind, item.name, item.line)))
+                .collect();
+            if coverage.undocumented.len() > LISTED_ITEMS {
+                items.push(format!("and {} more", coverage.undocumented.len() - LISTED_ITEMS));
+            }
+            format!(
+                "<tr><th scope=\"row\">{}</th><td><code>{}</code></td><td>{}</td><td>{} of {}</td><td>{:.0}%</td><td>{}</td></tr>\n",
+                escape_html(&node.name),
+                linked(&node.file_path, 1, &node.file_path),
+                escape_html(node.crate_name.as_deref().unwrap_or("")),
+                coverage.undocumented.len(),
+                coverage.public_items,
+                coverage.ratio() * 100.0,
+                items.join("<br>")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>Top offenders ({} of {} modules missing docs)</caption>
+            <thead><tr><th scope="col">Module</th><th scope="col">File</th><th scope="col">Crate</th><th scope="col">Undocumented</th><th scope="col">Coverage</th><th scope="col">Missing docs</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        offenders.len(),
+        total,
+        rows
+    )
+}
+
+/// Generated code is not expected to carry docs
+fn hand_written(architecture: &ArchitectureMap) -> impl Iterator<Item = &ArchitectureNode> {
+    architecture.nodes.values().filter(|node| !node.module_type.is_generated())
+}
@@ -0,0 +1,165 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::types::{ArchitectureMap, CrateFeatures};
+
+/// A feature of one crate
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureSummary {
+    /// Unique key, see [`feature_key`]
+    pub key: String,
+    pub crate_name: String,
+    pub name: String,
+    /// Node ids of the modules whose `cfg` attributes name this feature
+    pub gated_modules: Vec<String>,
+}
+
+/// An optional dependency that features can switch on
+#[derive(Debug, Clone, Serialize)]
+pub struct OptionalDependency {
+    /// Unique key, see [`dependency_key`]
+    pub key: String,
+    pub crate_name: String,
+    pub name: String,
+}
+
+/// What a feature edge expresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureEdgeKind {
+    /// The feature turns on another feature of the same crate
+    Enables,
+    /// The feature activates an optional dependency (or one of its features)
+    Activates,
+    /// The module is compiled differently depending on the feature
+    Gates,
+}
+
+/// Edge from a feature to a feature, dependency or module
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct FeatureEdge {
+    pub from: String,
+    /// Feature key, dependency key or module node id
+    pub to: String,
+    pub kind: FeatureEdgeKind,
+    /// Dependency features turned on by the edge, e.g. `derive` for `serde/derive`
+    pub detail: Option<String>,
+}
+
+/// Cargo features of all scanned crates and what they switch on
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureView {
+    pub features: Vec<FeatureSummary>,
+    pub dependencies: Vec<OptionalDependency>,
+    pub edges: Vec<FeatureEdge>,
+}
+
+/// Key identifying a feature across crates
+pub fn feature_key(crate_name: &str, feature: &str) -> String {
+    format!("feature:{}/{}", crate_name, feature)
+}
+
+/// Key identifying an optional dependency across crates
+pub fn dependency_key(crate_name: &str, dependency: &str) -> String {
+    format!("dependency:{}/{}", crate_name, dependency)
+}
+
+/// Build the feature graph from the scanned manifests and the modules' `cfg` attributes
+pub fn build_feature_view(architecture: &ArchitectureMap) -> FeatureView {
+    let mut features = Vec::new();
+    let mut dependencies: BTreeMap<String, OptionalDependency> = BTreeMap::new();
+    let mut edges = BTreeSet::new();
+
+    for crate_features in &architecture.cargo_features {
+        let crate_name = crate_features.crate_name.as_str();
+        for name in &crate_features.optional_dependencies {
+            add_dependency(&mut dependencies, crate_name, name);
+        }
+
+        for feature in &crate_features.features {
+            let key = feature_key(crate_name, &feature.name);
+            for value in &feature.enables {
+                if let Some((to, kind, detail)) = resolve_enabled(crate_features, value) {
+                    // `dep:` and `name/feature` may also refer to dependencies that are not optional
+                    if let Some(dependency) = to.strip_prefix(&dependency_key(crate_name, "")) {
+                        add_dependency(&mut dependencies, crate_name, dependency);
+                    }
+                    edges.insert(FeatureEdge {
+                        from: key.clone(),
+                        to,
+                        kind,
+                        detail,
+                    });
+                }
+            }
+
+            let mut gated_modules: Vec<String> = architecture
+                .nodes
+                .values()
+                .filter(|node| node.crate_name.as_deref() == Some(crate_name))
+                .filter(|node| node.features.contains(&feature.name))
+                .map(|node| node.id.clone())
+                .collect();
+            gated_modules.sort();
+            for module in &gated_modules {
+                edges.insert(FeatureEdge {
+                    from: key.clone(),
+                    to: module.clone(),
+                    kind: FeatureEdgeKind::Gates,
+                    detail: None,
+                });
+            }
+
+            features.push(FeatureSummary {
+                key,
+                crate_name: crate_name.to_string(),
+                name: feature.name.clone(),
+                gated_modules,
+            });
+        }
+    }
+
+    FeatureView {
+        features,
+        dependencies: dependencies.into_values().collect(),
+        edges: edges.into_iter().collect(),
+    }
+}
+
+fn add_dependency(dependencies: &mut BTreeMap<String, OptionalDependency>, crate_name: &str, name: &str) {
+    let key = dependency_key(crate_name, name);
+    dependencies.entry(key.clone()).or_insert_with(|| OptionalDependency {
+        key,
+        crate_name: crate_name.to_string(),
+        name: name.to_string(),
+    });
+}
+
+/// Interpret one value of a `[features]` entry
+fn resolve_enabled(crate_features: &CrateFeatures, value: &str) -> Option<(String, FeatureEdgeKind, Option<String>)> {
+    let crate_name = crate_features.crate_name.as_str();
+
+    if let Some(dependency) = value.strip_prefix("dep:") {
+        return Some((dependency_key(crate_name, dependency), FeatureEdgeKind::Activates, None));
+    }
+
+    if let Some((dependency, feature)) = value.split_once('/') {
+        // `dep?/feature` enables the feature without activating the dependency itself
+        let (dependency, detail) = match dependency.strip_suffix('?') {
+            Some(dependency) => (dependency, format!("{} (weak)", feature)),
+            None => (dependency, feature.to_string()),
+        };
+        return Some((dependency_key(crate_name, dependency), FeatureEdgeKind::Activates, Some(detail)));
+    }
+
+    if crate_features.features.iter().any(|feature| feature.name == value) {
+        return Some((feature_key(crate_name, value), FeatureEdgeKind::Enables, None));
+    }
+
+    // Optional dependencies double as implicit features
+    crate_features
+        .optional_dependencies
+        .iter()
+        .any(|dependency| dependency == value)
+        .then(|| (dependency_key(crate_name, value), FeatureEdgeKind::Activates, None))
+}
@@ -1,16 +1,103 @@
 use crate::{
-    config::ProjectConfig,
-    scanner::ArchitectureScanner,
-    types::{ArchitectureMap, ModuleType, NodeStatus, Theme, VisualizationSettings},
+    config::{self, ConfigFileEdit, ProjectConfig, SettingsUpdate, StaleDataPolicy},
+    curation::Curation,
+    export,
+    history::{detect_anomalies, HistoryStore},
+    rules::{self, cycles},
+    scanner::{
+        classification, clippy_analyzer, command_hook, data_access, git_analyzer::hotspot_score, ignored, ArchitectureScanner, CommandHook,
+    },
+    types::{
+        ArchitectureMap, ArchitectureNode, BuildStatus, HookOutcome, ModuleType, NodeStatus, PageSections, StatCard,
+        Theme, VisualizationSettings,
+    },
+    visualizer::{
+        component_view::build_component_view,
+        crate_view::{build_crate_view, crate_health, UNOWNED_CRATE},
+        doc_coverage,
+        settings_page,
+        static_page,
+        feature_view::{build_feature_view, FeatureEdgeKind},
+        metric_percentiles,
+        node_size_factor,
+        vendor_assets::{self, AssetSource},
+        Snapshot,
+    },
 };
 use anyhow::Result;
 use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use tokio::sync::broadcast;
+
+/// Colors of the robustness overlay by `Robustness::level`, with legend labels
+const ROBUSTNESS_COLORS: &[(&str, &str, &str)] = &[
+    ("robust", "#22c55e", "Robust (80+)"),
+    ("fair", "#f59e0b", "Fair (50–79)"),
+    ("fragile", "#ef4444", "Fragile (under 50)"),
+];
+
+/// Colors of the data access overlay by `data_access::role`, with legend labels
+const DATA_ACCESS_COLORS: &[(&str, &str, &str)] = &[
+    ("repository", "#3b82f6", "Repository (runs queries)"),
+    ("direct", "#ef4444", "Direct queries outside a repository"),
+    ("via_repository", "#22c55e", "Uses a module that runs queries"),
+    ("schema", "#a855f7", "Schema and row types only"),
+];
+
+/// Colors of the clippy lint overlay by `lint_level`, with legend labels
+const LINT_DENSITY_COLORS: &[(&str, &str, &str)] = &[
+    ("clean", "#22c55e", "No clippy lints"),
+    ("low", "#f59e0b", "Under 1 lint per 100 lines"),
+    ("high", "#ef4444", "1 or more lints per 100 lines"),
+];
+
+/// Band of a module's clippy lint density, when clippy ran
+fn lint_level(node: &ArchitectureNode) -> Option<&'static str> {
+    let density = clippy_analyzer::lint_density(node)?;
+    Some(if density == 0.0 {
+        "clean"
+    } else if density < 1.0 {
+        "low"
+    } else {
+        "high"
+    })
+}
+
+/// Options controlling how the HTML page is assembled
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+    /// Reference `assets/app.css` and `assets/app.js` instead of inlining them
+    pub external_assets: bool,
+    /// Hide actions that need a running server (for static hosting)
+    pub read_only: bool,
+    /// Apply node patches pushed over the server's `/ws` socket (watch mode)
+    pub live_updates: bool,
+    /// URL path vendored libraries are served under; unset, they are embedded with `offline_assets` and loaded from CDNs without
+    pub asset_base: Option<String>,
+}
+
+/// Refreshed maps kept per subscriber; each one is complete, so skipping is harmless
+const REFRESH_BUFFER: usize = 4;
 
 /// Main architecture visualizer that generates HTML and handles data
 pub struct ArchitectureVisualizer {
     scanner: ArchitectureScanner,
     config: ProjectConfig,
-    cached_architecture: Option<ArchitectureMap>,
+    /// Rescans build the next map off to the side and swap it in here
+    cached_architecture: Snapshot<ArchitectureMap>,
+    /// Every map a refresh swaps in, for clients streaming the scan data
+    refreshes: broadcast::Sender<Arc<ArchitectureMap>>,
+    /// Held by a refresh from its rescan to the swap, so an older scan never replaces a newer one
+    refreshing: tokio::sync::Mutex<()>,
+    include_curation: bool,
+    config_path: Option<PathBuf>,
+    /// Set once a remote agent pushed a scan; local refreshes then keep the pushed map
+    remote_scans: AtomicBool,
 }
 
 impl ArchitectureVisualizer {
@@ -19,24 +106,219 @@ impl ArchitectureVisualizer {
         Self {
             scanner,
             config,
-            cached_architecture: None,
+            cached_architecture: Snapshot::default(),
+            refreshes: broadcast::channel(REFRESH_BUFFER).0,
+            refreshing: tokio::sync::Mutex::new(()),
+            include_curation: true,
+            config_path: None,
+            remote_scans: AtomicBool::new(false),
+        }
+    }
+
+    /// Project directory as an absolute path with `/` separators, for editor links
+    fn absolute_project_path(&self) -> String {
+        let path = self.project_path();
+        path.canonicalize()
+            .unwrap_or_else(|_| path.to_path_buf())
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// Whether generated pages carry the pinned positions and manual groups of the layout file
+    pub fn with_curation(mut self, include_curation: bool) -> Self {
+        self.include_curation = include_curation;
+        self
+    }
+
+    /// Config file that changes made in the UI are written to, instead of the one in the project directory
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Config file the project's settings are persisted to; `rust-arch-viz.toml` when there is none yet
+    pub fn config_path(&self) -> PathBuf {
+        self.config_path
+            .clone()
+            .or_else(|| config::find_config_file(self.project_path()))
+            .unwrap_or_else(|| self.project_path().join(config::CONFIG_FILES[0]))
+    }
+
+    /// Replace the sections `update` sets and persist them with one write of the config file;
+    /// they apply from the next refresh. Nothing changes when a section is invalid or the write fails.
+    pub fn update_settings(&mut self, update: SettingsUpdate) -> Result<()> {
+        if let Some(classification) = &update.classification {
+            classification::validate(classification)?;
+        }
+        if let Some(ignore) = &update.ignore {
+            ignored::validate(ignore)?;
         }
+
+        let mut config = self.config.clone();
+        config.classification = update.classification.clone().unwrap_or(config.classification);
+        config.ignore = update.ignore.clone().unwrap_or(config.ignore);
+        config.rules = update.rules.clone().unwrap_or(config.rules);
+        config.check = update.check.clone().unwrap_or(config.check);
+
+        let path = self.config_path();
+        if path.exists() {
+            let mut edit = ConfigFileEdit::open(&path)?;
+            if let Some(classification) = &update.classification {
+                edit.set_section("classification", classification)?;
+            }
+            if let Some(ignore) = &update.ignore {
+                edit.set_section("ignore", ignore)?;
+            }
+            if let Some(rules) = &update.rules {
+                edit.set_section("rules", rules)?;
+            }
+            if let Some(check) = &update.check {
+                edit.set_section("check", check)?;
+            }
+            edit.save()?;
+        } else {
+            // Keep what was read from Cargo.toml, which a new config file would hide
+            config.save_to_file(&path)?;
+        }
+
+        self.scanner.config.classification = config.classification.clone();
+        self.scanner.config.ignore = config.ignore.clone();
+        self.scanner.config.rules = config.rules.clone();
+        self.scanner.config.check = config.check.clone();
+        self.config = config;
+        Ok(())
     }
 
-    /// Get the current architecture data
-    pub async fn get_architecture(&self) -> Result<ArchitectureMap> {
-        if let Some(ref cached) = self.cached_architecture {
-            Ok(cached.clone())
+    /// Get the current architecture data, shared with every other reader
+    pub async fn get_architecture(&self) -> Result<Arc<ArchitectureMap>> {
+        if let Some(cached) = self.cached_architecture.load() {
+            Ok(cached)
         } else {
-            self.scanner.scan_async().await
+            let mut architecture = self.scanner.scan_async().await?;
+            self.attach_anomalies(&mut architecture);
+            Ok(Arc::new(architecture))
+        }
+    }
+
+    /// Compare a fresh scan with the latest stored snapshot, if there is one, and keep the
+    /// snapshot's crate health scores for the crate view's trend arrows
+    fn attach_anomalies(&self, architecture: &mut ArchitectureMap) {
+        let Some(store) = HistoryStore::existing_for_project(self.scanner.project_path(), &self.config) else {
+            return;
+        };
+        match store.latest() {
+            Ok(Some(previous)) => {
+                architecture.anomalies = detect_anomalies(&previous, architecture, &self.config.history.anomalies);
+                architecture.previous_crate_health = crate_health(&previous);
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load the latest snapshot: {:#}", e),
+        }
+    }
+
+    /// Rescan and swap the result in; readers keep seeing the previous scan until it is done
+    pub async fn refresh(&self) -> Result<Arc<ArchitectureMap>> {
+        self.refresh_with(None, |_, _| {}).await
+    }
+
+    /// Rescan, attaching the watch command's `outcome` to this scan only.
+    ///
+    /// `on_swap` gets the replaced map and the new one before any later swap, so what it
+    /// publishes goes out in the order the maps were swapped in.
+    pub async fn refresh_with(
+        &self,
+        outcome: Option<HookOutcome>,
+        on_swap: impl FnOnce(Option<&ArchitectureMap>, &ArchitectureMap),
+    ) -> Result<Arc<ArchitectureMap>> {
+        let _refreshing = self.refreshing.lock().await;
+        if let Some(pushed) = self.pushed_architecture() {
+            return Ok(pushed);
+        }
+        let mut architecture = self.scanner.rescan_async().await?;
+        self.attach_anomalies(&mut architecture);
+        if let Some(outcome) = outcome {
+            command_hook::apply_outcome(&outcome, &mut architecture.nodes);
+            architecture.hook_outcome = Some(outcome);
+        }
+        // A map pushed while this scan ran is newer than it
+        if let Some(pushed) = self.pushed_architecture() {
+            return Ok(pushed);
         }
+        let previous = self.cached_architecture.load();
+        let stored = self.cached_architecture.store(architecture);
+        on_swap(previous.as_deref(), &stored);
+        // Nobody listening is not an error
+        let _ = self.refreshes.send(stored.clone());
+        Ok(stored)
+    }
+
+    /// Swap in a map scanned elsewhere, such as one pushed by a remote agent.
+    ///
+    /// From then on the project is fed remotely: refreshes keep the latest pushed map
+    /// instead of rescanning the local directory. `on_swap` is called as in `refresh_with`.
+    pub async fn accept_remote_scan(
+        &self,
+        mut architecture: ArchitectureMap,
+        on_swap: impl FnOnce(Option<&ArchitectureMap>, &ArchitectureMap),
+    ) -> Arc<ArchitectureMap> {
+        let _refreshing = self.refreshing.lock().await;
+        self.remote_scans.store(true, Ordering::Relaxed);
+        self.attach_anomalies(&mut architecture);
+        let previous = self.cached_architecture.load();
+        let stored = self.cached_architecture.store(architecture);
+        on_swap(previous.as_deref(), &stored);
+        let _ = self.refreshes.send(stored.clone());
+        stored
+    }
+
+    /// The latest map a remote agent pushed, which refreshes keep instead of rescanning
+    fn pushed_architecture(&self) -> Option<Arc<ArchitectureMap>> {
+        self.remote_scans.load(Ordering::Relaxed).then(|| self.cached_architecture.load()).flatten()
+    }
+
+    /// Whether the map comes from remote agents, so refreshes do not rescan the local directory
+    pub fn serves_remote_scans(&self) -> bool {
+        self.remote_scans.load(Ordering::Relaxed)
+    }
+
+    /// Receive each map a refresh swaps in; a receiver that falls behind skips to the newest
+    pub fn subscribe_refreshes(&self) -> broadcast::Receiver<Arc<ArchitectureMap>> {
+        self.refreshes.subscribe()
+    }
+
+    /// The configured watch command, run before each refresh after a source change
+    pub fn watch_command(&self) -> Option<CommandHook> {
+        CommandHook::from_settings(&self.config.watch)
+    }
+
+    /// Fingerprint of the project sources, used by watch mode to detect changes
+    pub fn source_fingerprint(&self) -> Result<u64> {
+        self.scanner.source_fingerprint()
+    }
+
+    /// Path of the layout file holding pinned positions and manual groups
+    pub fn curation_path(&self) -> PathBuf {
+        Curation::path_for_project(self.scanner.project_path(), &self.config)
     }
 
-    /// Refresh the architecture data
-    pub async fn refresh(&mut self) -> Result<ArchitectureMap> {
-        let architecture = self.scanner.scan_async().await?;
-        self.cached_architecture = Some(architecture.clone());
-        Ok(architecture)
+    /// Load the project's pinned positions and manual groups
+    pub fn load_curation(&self) -> Result<Curation> {
+        Curation::load(&self.curation_path())
+    }
+
+    /// Architecture from the last refresh, if any
+    pub fn cached_architecture(&self) -> Option<Arc<ArchitectureMap>> {
+        self.cached_architecture.load()
+    }
+
+    /// Project name shown in page titles and reports
+    pub fn project_name(&self) -> &str {
+        self.config.project.name.as_deref().unwrap_or("Rust Project")
+    }
+
+    /// Root directory of the visualized project
+    pub fn project_path(&self) -> &std::path::Path {
+        self.scanner.project_path()
     }
 
     /// Get the current configuration
@@ -44,22 +326,124 @@ impl ArchitectureVisualizer {
         &self.config
     }
 
+    /// Formulas of the reported metrics, as configured for this project
+    pub fn metric_formulas(&self) -> std::collections::BTreeMap<&'static str, String> {
+        self.scanner.metric_formulas()
+    }
+
     /// Generate HTML for the architecture visualization
     pub fn generate_html(&self, architecture: &ArchitectureMap) -> Result<String> {
+        self.generate_html_with_options(architecture, &HtmlOptions::default())
+    }
+
+    /// Generate HTML for the architecture visualization with explicit page options
+    pub fn generate_html_with_options(
+        &self,
+        architecture: &ArchitectureMap,
+        options: &HtmlOptions,
+    ) -> Result<String> {
         let settings = &self.config.visualization;
-        let project_name = self
-            .config
-            .project
-            .name
-            .as_deref()
-            .unwrap_or("Rust Project");
+        let project_name = self.project_name();
 
-        let javascript = self.generate_javascript(architecture, settings)?;
+        let data = self.page_data(architecture, options)?;
+        let vendor_dir = self.vendor_dir();
+        let libraries = vendor_assets::head_html(match (&options.asset_base, &vendor_dir) {
+            (Some(base), _) => AssetSource::Served(base),
+            (None, Some(dir)) => AssetSource::Embedded(dir),
+            (None, None) => AssetSource::Cdn,
+        })?;
+        let (styles, scripts) = if options.external_assets {
+            (
+                r#"<link rel="stylesheet" href="assets/app.css">"#.to_string(),
+                format!(
+                    r#"<script type="application/json" id="architecture-data">{}</script>
+    <script type="module" src="assets/app.js"></script>"#,
+                    data
+                ),
+            )
+        } else {
+            (
+                format!("<style>\n        {}\n    </style>", self.generate_css(settings)),
+                format!(
+                    "<script type=\"module\">\n        {}\n    </script>",
+                    self.render_javascript(&data)
+                ),
+            )
+        };
         
+        let sections = &settings.sections;
+
         Ok(format!(
             r#"
 <!DOCTYPE html>
 <html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{} - Architecture Visualizer</title>
+    {}
+    {}
+</head>
+<body>
+    <noscript><p class="noscript-notice">JavaScript is disabled. <a href="?nojs=1">Open the static view</a>.</p></noscript>
+    <div class="container">
+        {}
+        {}
+        <div class="build-status" id="build-status" role="status" hidden></div>
+        <div class="notices" id="anomaly-notices" hidden></div>
+        <details class="notices notices--orphans" id="orphan-panel" hidden></details>
+        <details class="notices notices--orphans" id="cycle-panel" hidden></details>
+        <div class="timeline" id="history-timeline" hidden>
+            <label for="history-slider">🕰️ History</label>
+            <input type="range" id="history-slider" min="0" max="0" step="1" value="0">
+            <span class="timeline-label" id="history-label">Live</span>
+            <img class="timeline-preview" id="history-preview" alt="" hidden>
+        </div>
+        <div class="visualization-container">
+            <div class="visualization-panel">
+                {}
+            </div>
+            {}
+        </div>
+        {}
+        {}
+        {}
+    </div>
+    
+    {}
+</body>
+</html>
+        "#,
+            project_name,
+            styles,
+            libraries,
+            self.generate_header_html(),
+            section(sections.controls, || self.generate_controls_html(sections)),
+            self.generate_architecture_html(architecture, settings),
+            section(sections.details_panel, || self.generate_details_panel_html()),
+            section(sections.legend, || self.generate_legend_html()),
+            section(sections.stats, || self.generate_stats_html(architecture)),
+            section(sections.footer, || self.generate_footer_html(architecture)),
+            scripts
+        ))
+    }
+
+    /// Mermaid flowchart of the module graph, shaped by the `[exports.mermaid]` settings
+    pub fn to_mermaid(&self, architecture: &ArchitectureMap) -> String {
+        export::mermaid::render(architecture, &self.config.exports.mermaid)
+    }
+
+    /// Script-free page: the graph as SVG plus tables of modules and cycles.
+    ///
+    /// Served instead of the interactive page to text browsers and for `?nojs=1`.
+    pub fn generate_static_html(&self, architecture: &ArchitectureMap) -> Result<String> {
+        let settings = &self.config.visualization;
+        let project_name = self.project_name();
+        let svg = static_page::render_graph_svg(architecture, settings)?;
+
+        Ok(format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
 <head>
     <meta charset="UTF-8">
     <meta name="viewport" content="width=device-width, initial-scale=1.0">
@@ -67,82 +451,187 @@ impl ArchitectureVisualizer {
     <style>
         {}
     </style>
-    <link rel="stylesheet" href="https://unpkg.com/reactflow@11.7.4/dist/style.css">
 </head>
 <body>
     <div class="container">
         <div class="header">
             <h1>🏗️ Architecture Visualizer</h1>
-            <p>Real-time view of your Rust project architecture</p>
-            <div class="controls">
-                <button id="refresh-btn" class="btn btn-primary">🔄 Refresh</button>
-                <button id="theme-btn" class="btn btn-secondary">🎨 Theme</button>
-                <button id="fullscreen-btn" class="btn btn-secondary">⛶ Fullscreen</button>
-            </div>
-        </div>
-        
-        <div class="visualization-controls">
-            <div class="control-group">
-                <h4>Layout</h4>
-                <button id="layout-grid" class="btn btn-secondary active">Grid</button>
-                <button id="layout-circular" class="btn btn-secondary">Circular</button>
-                <button id="layout-hierarchical" class="btn btn-secondary">Hierarchical</button>
-            </div>
-            <div class="control-group">
-                <h4>Organization</h4>
-                <button id="reorder-hierarchical" class="btn btn-secondary active">Hierarchical</button>
-                <button id="reorder-grouped" class="btn btn-secondary">Grouped by Type</button>
-                <button id="reorder-dependency" class="btn btn-secondary">Dependency Driven</button>
-                <button id="reorder-alphabetical" class="btn btn-secondary">Alphabetical</button>
-            </div>
-            <div class="control-group">
-                <button id="legend-toggle" class="btn btn-secondary">📋 Legend</button>
-            </div>
+            <p>{} · static view · <a href="?nojs=0">interactive version</a></p>
         </div>
-        
-        <div class="visualization-container">
-            <div class="visualization-panel">
-                <div class="architecture-canvas" id="react-flow-root">
+        {}
+        <main class="static-content">
+            <figure class="static-graph">
+                <div class="static-graph__scroll" role="img" aria-label="Dependency graph of {} modules; the same information is listed in the tables below">
                     {}
                 </div>
-            </div>
-        </div>
-        
-        <div class="legend">
+                <figcaption>Dependency graph. Red arrows are part of a circular dependency.</figcaption>
+            </figure>
             {}
-        </div>
-        
-        <div class="stats">
             {}
-        </div>
-        
-        <div class="footer">
-            <div class="info">
-                <span>Last updated: {}</span>
-                <span>Total modules: {}</span>
-                <span>Dependencies: {}</span>
-            </div>
-        </div>
-    </div>
-    
-    <script type="module">
+            {}
+            {}
+            {}
+            {}
+            {}
+        </main>
         {}
-    </script>
+    </div>
 </body>
 </html>
-        "#,
-            project_name,
+"#,
+            static_page::escape_html(project_name),
             self.generate_css(settings),
+            static_page::escape_html(project_name),
             self.generate_stats_html(architecture),
-            self.generate_legend_html(),
-            self.generate_architecture_html(architecture, settings),
-            architecture.last_scan.format("%Y-%m-%d %H:%M:%S UTC"),
-            architecture.total_modules,
-            architecture.edges.len(),
-            javascript
+            architecture.nodes.len(),
+            svg,
+            static_page::render_cycle_table(architecture),
+            static_page::render_inversion_table(architecture),
+            static_page::render_orphan_table(architecture),
+            static_page::render_crate_table(architecture),
+            static_page::render_risk_table(architecture),
+            static_page::render_route_table(architecture),
+            static_page::render_module_table(architecture),
+            self.generate_footer_html(architecture)
         ))
     }
 
+    /// Documentation coverage page listing the modules with the most undocumented public items
+    pub fn generate_doc_coverage_html(&self, architecture: &ArchitectureMap) -> String {
+        let settings = &self.config.visualization;
+        let project_name = self.project_name();
+        let (documented, public) = doc_coverage::project_totals(architecture);
+        let project_root = self.absolute_project_path();
+        let editor_link = |file_path: &str, line: usize| {
+            settings.editor_url.as_ref().map(|template| {
+                template
+                    .replace("{path}", &format!("{}/{}", project_root, file_path))
+                    .replace("{relative_path}", file_path)
+                    .replace("{line}", &line.to_string())
+            })
+        };
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{} - Documentation coverage</title>
+    <style>
+        {}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>📚 Documentation coverage</h1>
+            <p>{} · <a href="./">architecture</a></p>
+        </div>
+        <main class="static-content">
+            <p><img src="docs-coverage/badge.svg" alt="docs {:.0}%"> {} of {} public items documented.</p>
+            {}
+        </main>
+    </div>
+</body>
+</html>
+"#,
+            static_page::escape_html(project_name),
+            self.generate_css(settings),
+            static_page::escape_html(project_name),
+            doc_coverage::project_ratio(architecture) * 100.0,
+            documented,
+            public,
+            doc_coverage::render_offender_table(architecture, editor_link),
+        )
+    }
+
+    /// Rule editor page for the `[rules]` and `[check]` sections
+    pub fn generate_settings_html(&self) -> String {
+        let settings = &self.config.visualization;
+        let project_name = self.project_name();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{} - Rules</title>
+    <style>
+        {}
+        {}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>⚙️ Rules</h1>
+            <p>{} · <a href="./">architecture</a></p>
+        </div>
+        {}
+    </div>
+</body>
+</html>
+"#,
+            static_page::escape_html(project_name),
+            self.generate_css(settings),
+            settings_page::STYLE,
+            static_page::escape_html(project_name),
+            settings_page::render_body(),
+        )
+    }
+
+    /// Directory of the vendored libraries when `offline_assets` is enabled
+    pub fn vendor_dir(&self) -> Option<PathBuf> {
+        let settings = &self.config.visualization;
+        settings.offline_assets.then(|| self.project_path().join(&settings.vendor_dir))
+    }
+
+    /// Stylesheet used by the page (also written as `assets/app.css` in bundles)
+    pub fn stylesheet(&self) -> String {
+        self.generate_css(&self.config.visualization)
+    }
+
+    /// Script used by pages with external assets; reads its data from the `architecture-data` element
+    pub fn external_script(&self) -> String {
+        self.render_javascript("JSON.parse(document.getElementById('architecture-data')?.textContent || '{}')")
+    }
+
+    /// Serialized page data, safe to embed inside a `<script>` element
+    /// Graph data the page renders, without the per-request settings
+    pub fn flow_data(&self, architecture: &ArchitectureMap) -> Value {
+        self.build_react_flow_data(architecture, &self.config.visualization)
+    }
+
+    fn page_data(&self, architecture: &ArchitectureMap, options: &HtmlOptions) -> Result<String> {
+        let mut data = self.flow_data(architecture);
+        data["settings"]["readOnly"] = json!(options.read_only);
+        data["settings"]["liveUpdates"] = json!(options.live_updates);
+        data["settings"]["maxDataAge"] = json!(self.config.server.max_data_age);
+        data["settings"]["rescansStaleData"] = json!(options.live_updates && self.config.server.on_stale_data == StaleDataPolicy::Rescan);
+        if !options.read_only {
+            data["settings"]["classificationRules"] = serde_json::to_value(&self.config.classification.rules)?;
+            data["settings"]["ignoredPaths"] = json!(self.config.ignore.paths);
+            data["settings"]["moduleTypes"] = ModuleType::BUILTIN
+                .iter()
+                .map(|module_type| json!({ "key": format!("{:?}", module_type), "label": module_type.display_name() }))
+                .collect();
+        }
+        data["curation"] = if !self.include_curation {
+            json!({})
+        } else {
+            match self.load_curation() {
+                Ok(curation) => serde_json::to_value(curation)?,
+                Err(e) => {
+                    tracing::warn!("Ignoring layout file: {:#}", e);
+                    json!({})
+                }
+            }
+        };
+        Ok(serde_json::to_string(&data)?.replace("</", "<\\/"))
+    }
+
     /// Generate CSS styles
     fn generate_css(&self, _settings: &VisualizationSettings) -> String {
         String::from(
@@ -169,6 +658,15 @@ body.theme-dark .stats{background:rgba(15,23,42,.72);}
 body.theme-dark .stat-card{background:rgba(30,41,59,.92);color:#e2e8f0;}
 .stat-number{font-size:2.2rem;font-weight:700;color:#667eea;}
 .stat-label{text-transform:uppercase;font-size:.78rem;letter-spacing:.08em;color:#64748b;}
+.noscript-notice{max-width:1320px;margin:0 auto 1rem;padding:1rem 1.4rem;border-radius:12px;background:#fef3c7;color:#92400e;}
+.static-content{padding:1.8rem;display:flex;flex-direction:column;gap:1.8rem;}
+.static-graph__scroll{overflow:auto;max-height:80vh;border:1px solid rgba(148,163,184,.25);border-radius:12px;}
+.static-graph figcaption,.static-note{margin-top:.5rem;font-size:.85rem;color:#64748b;}
+.static-table{width:100%;border-collapse:collapse;font-size:.9rem;}
+.static-table caption{text-align:left;font-weight:700;font-size:1.1rem;margin-bottom:.6rem;}
+.static-table th,.static-table td{padding:.45rem .6rem;border-bottom:1px solid rgba(148,163,184,.25);text-align:left;}
+.static-table thead th{text-transform:uppercase;font-size:.75rem;letter-spacing:.06em;color:#64748b;}
+.header p a{color:#fff;}
 .visualization-controls{display:flex;flex-wrap:wrap;gap:1rem;padding:1.5rem;background:rgba(248,250,252,.9);border-bottom:1px solid rgba(148,163,184,.25);}
 body.theme-dark .visualization-controls{background:rgba(15,23,42,.72);}
 .control-group{display:flex;flex-wrap:wrap;gap:.5rem;align-items:center;}
@@ -177,8 +675,8 @@ body.theme-dark .visualization-controls{background:rgba(15,23,42,.72);}
 .control-group .btn:hover{background:rgba(102,126,234,.2);border-color:rgba(102,126,234,.5);}
 body.theme-dark .control-group .btn{background:rgba(30,41,59,.7);border-color:rgba(148,163,184,.4);color:#e2e8f0;}
 body.theme-dark .control-group .btn:hover{background:rgba(30,41,59,.9);border-color:rgba(148,163,184,.6);}
-.visualization-container{display:grid;grid-template-columns:1fr;min-height:600px;}
-.visualization-panel{position:relative;padding:1.5rem;background:linear-gradient(135deg,rgba(102,126,234,.08),rgba(118,75,162,.08));}
+.visualization-container{display:flex;min-height:600px;}
+.visualization-panel{flex:1;min-width:0;position:relative;padding:1.5rem;background:linear-gradient(135deg,rgba(102,126,234,.08),rgba(118,75,162,.08));}
 .legend{position:fixed;top:50%;right:2rem;transform:translateY(-50%);background:#fff;border-radius:12px;padding:1.5rem;box-shadow:0 20px 40px rgba(15,23,42,.15);z-index:1000;display:none;max-width:280px;max-height:80vh;overflow-y:auto;}
 .legend.visible{display:block;}
 body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px solid rgba(148,163,184,.3);}
@@ -194,9 +692,71 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
 .react-flow__node-module{width:210px;border-radius:16px;border:2px solid rgba(102,126,234,.25);background:#fff;box-shadow:0 10px 22px rgba(15,23,42,.12);transition:transform .2s ease,opacity .2s ease;}
 .react-flow__node-module.is-selected{transform:translateY(-3px);border-color:#667eea;}
 .react-flow__node-module.is-dimmed{opacity:.35;}
+.react-flow__node-module.is-entry-point{border-radius:4px;border:4px double #0f766e;}
+.flow-wrapper{position:relative;width:100%;height:100%;}
+.breadcrumb{position:absolute;top:12px;left:12px;z-index:5;display:flex;align-items:center;gap:.4rem;padding:.4rem .75rem;background:rgba(255,255,255,.95);border:1px solid rgba(148,163,184,.35);border-radius:999px;box-shadow:0 6px 16px rgba(15,23,42,.1);font-size:.85rem;}
+.breadcrumb__link{background:none;border:none;padding:0;color:#4f46e5;font:inherit;cursor:pointer;}
+.breadcrumb__link:hover{text-decoration:underline;}
+.breadcrumb__link--aside{margin-left:.6rem;color:#64748b;}
+.breadcrumb__separator{color:#94a3b8;}
+.breadcrumb__current{font-weight:600;color:#1e293b;}
 .rf-module-card{position:relative;padding:.9rem 1rem;display:flex;flex-direction:column;gap:.75rem;}
 .rf-module-card__header{display:flex;align-items:center;gap:.65rem;border-bottom:1px solid rgba(15,23,42,.1);padding-bottom:.4rem;}
 .rf-module-card__icon{font-size:1.45rem;}
+.rf-module-card__pin{margin-left:auto;font-size:.9rem;}
+.rf-module-card--ignored{opacity:.5;border:1px dashed #94a3b8;}
+.notices{margin:1.2rem 1.8rem 0;padding:.9rem 1.1rem;border-radius:12px;background:#fffbeb;border:1px solid #fcd34d;color:#92400e;font-size:.9rem;}
+.notices h4{font-size:.8rem;text-transform:uppercase;letter-spacing:.08em;margin-bottom:.4rem;}
+.notices ul{margin-left:1.1rem;}
+.notices--orphans{background:#f8fafc;border-color:#cbd5e1;color:#334155;}
+.notices--orphans summary{cursor:pointer;font-weight:600;}
+.notices--orphans ul{margin-top:.5rem;}
+.variant-summary{display:block;margin-top:.3rem;font-size:.75rem;color:#64748b;}
+.search-controls{flex-direction:column;align-items:stretch;}
+.search-results{list-style:none;margin:0;padding:0;max-height:220px;overflow-y:auto;}
+.search-result{display:flex;flex-direction:column;align-items:flex-start;width:100%;padding:.35rem .5rem;border:none;border-radius:6px;background:none;font:inherit;text-align:left;color:#1f2937;cursor:pointer;}
+.search-result:hover{background:rgba(99,102,241,.12);}
+.search-result span{font-size:.72rem;color:#64748b;}
+body.theme-dark .search-result{color:#e2e8f0;}
+.react-flow__node-module.is-search-match{box-shadow:0 0 0 4px rgba(245,158,11,.85);}
+.react-flow__node-groupContainer{padding:0;border:none;background:none;}
+.group-container{position:relative;width:100%;height:100%;box-sizing:border-box;border:2px dashed;border-radius:22px;}
+.group-container__label{position:absolute;top:10px;left:16px;font-size:12px;font-weight:700;letter-spacing:.04em;text-transform:uppercase;}
+.group-container__collapse{pointer-events:auto;cursor:pointer;border:none;background:none;padding:0;font-family:inherit;text-transform:none;}
+.notices__cycle{margin-top:.6rem;}
+.notices__cycle ul{margin-top:.25rem;}
+.notices__link{background:none;border:none;padding:0;color:inherit;font:inherit;text-decoration:underline;cursor:pointer;}
+body.theme-dark .notices--orphans{background:rgba(30,41,59,.6);border-color:#475569;color:#e2e8f0;}
+body.theme-dark .notices{background:rgba(120,53,15,.35);border-color:#b45309;color:#fde68a;}
+.timeline{position:relative;display:flex;align-items:center;gap:.8rem;margin:1.2rem 1.8rem 0;padding:.7rem 1.1rem;border-radius:12px;background:rgba(255,255,255,.85);border:1px solid #e2e8f0;font-size:.9rem;}
+.timeline[hidden]{display:none;}
+.timeline input{flex:1;}
+.timeline-label{min-width:16rem;text-align:right;color:#475569;font-variant-numeric:tabular-nums;}
+.timeline-preview{position:absolute;right:1.1rem;top:calc(100% + .4rem);z-index:20;width:320px;max-width:60vw;border-radius:8px;border:1px solid #e2e8f0;background:#fff;box-shadow:0 8px 24px rgba(15,23,42,.18);}
+.timeline-preview[hidden]{display:none;}
+body.theme-dark .timeline{background:rgba(30,41,59,.85);border-color:#334155;}
+body.theme-dark .timeline-label{color:#cbd5e1;}
+body.theme-dark .timeline-preview{border-color:#334155;background:#0f172a;}
+.data-age{display:inline-block;margin:-1.2rem 0 1.4rem;padding:.25rem .8rem;border-radius:999px;background:rgba(255,255,255,.18);font-size:.85rem;font-weight:600;}
+.data-age[hidden]{display:none;}
+.data-age--stale{background:#fef3c7;color:#92400e;}
+.build-status{margin:1.2rem 1.8rem 0;padding:.55rem 1.1rem;border-radius:12px;font-size:.88rem;font-weight:600;border:1px solid transparent;}
+.build-status--building{background:#eff6ff;border-color:#93c5fd;color:#1e40af;}
+.build-status--ok{background:#ecfdf5;border-color:#6ee7b7;color:#065f46;}
+.build-status--failed{background:#fef2f2;border-color:#fca5a5;color:#991b1b;}
+.build-status__files{font-weight:400;}
+.rf-module-card__naming{align-self:flex-start;padding:.15rem .55rem;border-radius:999px;background:#fef3c7;color:#92400e;font-size:.66rem;font-weight:600;}
+.rf-module-card__health{margin-left:auto;padding:.15rem .5rem;border-radius:999px;font-size:.7rem;font-weight:700;font-variant-numeric:tabular-nums;white-space:nowrap;}
+.rf-module-card__health--good{background:#dcfce7;color:#166534;}
+.rf-module-card__health--fair{background:#fef3c7;color:#92400e;}
+.rf-module-card__health--poor{background:#fee2e2;color:#991b1b;}
+.rf-module-card__toolchain{font-size:.68rem;color:#64748b;font-family:'Fira Code','Source Code Pro',monospace;}
+.rf-module-card__entry{align-self:flex-start;padding:.15rem .55rem;border-radius:4px;background:#ccfbf1;color:#115e59;font-size:.66rem;font-weight:600;}
+.rf-module-card__group{align-self:flex-start;padding:.15rem .55rem;border-radius:999px;color:#fff;font-size:.66rem;font-weight:600;letter-spacing:.04em;}
+.curation-actions{display:flex;flex-wrap:wrap;gap:.4rem;margin-bottom:.5rem;}
+.curation-label{display:flex;flex-direction:column;gap:.3rem;font-size:.75rem;color:#64748b;margin-bottom:.5rem;}
+.curation-select{padding:.4rem .5rem;border-radius:8px;border:1px solid rgba(148,163,184,.5);font:inherit;color:#1f2937;background:#fff;}
+.btn-small{padding:.35rem .8rem;font-size:.75rem;background:rgba(102,126,234,.12);border:1px solid rgba(102,126,234,.35);color:#1f2937;}
 .rf-module-card__name{font-weight:600;font-size:1rem;color:#1f2937;}
 .rf-module-card__type{font-size:.7rem;text-transform:uppercase;letter-spacing:.08em;color:#64748b;}
 .rf-module-card__metrics{display:grid;grid-template-columns:repeat(2,minmax(0,1fr));gap:.65rem;}
@@ -204,7 +764,7 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
 .rf-metric__value{font-weight:600;color:#1f2937;}
 .rf-metric__label{font-size:.66rem;text-transform:uppercase;letter-spacing:.07em;color:#64748b;}
 .empty-architecture{height:100%;display:flex;flex-direction:column;align-items:center;justify-content:center;text-align:center;gap:.8rem;color:#475569;}
-.details-panel{background:#fff;border-left:1px solid rgba(148,163,184,.25);display:none;flex-direction:column;padding:1.2rem;gap:.95rem;}
+.details-panel{width:340px;flex-shrink:0;background:#fff;border-left:1px solid rgba(148,163,184,.25);display:none;flex-direction:column;padding:1.2rem;gap:.95rem;}
 .details-panel.open{display:flex;}
 .details-header{display:flex;justify-content:space-between;align-items:center;}
 .details-content{flex:1;overflow-y:auto;display:flex;flex-direction:column;gap:.9rem;}
@@ -218,62 +778,186 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
 .metric-item{background:rgba(248,250,252,.95);border-radius:9px;padding:.65rem;display:flex;flex-direction:column;gap:.28rem;}
 .metric-item__label{text-transform:uppercase;font-size:.62rem;letter-spacing:.07em;color:#64748b;}
 .metric-item__value{font-weight:600;color:#1f2937;}
+.metric-item__percentile{font-weight:400;font-size:.78rem;color:#64748b;}
 .chip-row{display:flex;flex-wrap:wrap;gap:.4rem;}
 .chip{padding:.36rem .62rem;border-radius:999px;background:rgba(102,126,234,.16);color:#1f2937;font-size:.7rem;font-weight:600;}
 .empty-state{font-size:.82rem;color:#94a3b8;font-style:italic;}
 .details-list{list-style:none;display:flex;flex-direction:column;gap:.4rem;color:#475569;}
 .details-placeholder{color:#94a3b8;font-size:.85rem;}
+.editor-link{color:inherit;text-decoration:underline dotted;}
+.editor-link:hover{color:var(--primary);}
+.suggestion-list li{font-size:.8rem;line-height:1.45;padding-left:.9rem;position:relative;}
+.suggestion-list li::before{content:'→';position:absolute;left:0;color:var(--primary);}
+.details-note{font-size:.8rem;color:#475569;line-height:1.45;}
+.source-location{display:flex;flex-direction:column;gap:.2rem;}
+.source-location code{font-family:'Fira Code','Source Code Pro',monospace;font-size:.75rem;background:#f1f5f9;border-radius:6px;padding:.3rem .45rem;color:#0f172a;white-space:pre-wrap;word-break:break-word;}
 .footer{background:rgba(248,250,252,.95);padding:1rem 2rem;border-top:1px solid rgba(148,163,184,.28);}
 .info{display:flex;gap:1.3rem;flex-wrap:wrap;font-size:.84rem;color:#64748b;}
-@media(max-width:760px){body{padding:1rem;}.header h1{font-size:2rem;}.visualization-panel{padding:1.1rem;}.legend{position:relative;top:auto;right:auto;margin-bottom:1.1rem;}.architecture-canvas{height:520px;}.details-panel{width:100%;position:relative;}.controls{flex-direction:column;}}
+@media(max-width:760px){body{padding:1rem;}.header h1{font-size:2rem;}.visualization-panel{padding:1.1rem;}.legend{position:relative;top:auto;right:auto;margin-bottom:1.1rem;}.architecture-canvas{height:520px;}.visualization-container{flex-direction:column;}.details-panel{width:100%;position:relative;border-left:none;}.controls{flex-direction:column;}}
 "#,
         )
     }
 
-    /// Generate stats HTML
-    fn generate_stats_html(&self, architecture: &ArchitectureMap) -> String {
-        let active_modules = architecture
-            .nodes
-            .values()
-            .filter(|n| matches!(n.status, NodeStatus::Active))
-            .count();
-        
+    /// Generate the page header with the global actions
+    fn generate_header_html(&self) -> String {
+        r#"<div class="header">
+            <h1>🏗️ Architecture Visualizer</h1>
+            <p>Real-time view of your Rust project architecture</p>
+            <div class="data-age" id="data-age" role="status" hidden></div>
+            <div class="controls">
+                <button id="refresh-btn" class="btn btn-primary">🔄 Refresh</button>
+                <button id="theme-btn" class="btn btn-secondary">🎨 Theme</button>
+                <button id="fullscreen-btn" class="btn btn-secondary">⛶ Fullscreen</button>
+                <a id="rules-link" class="btn btn-secondary" href="settings">⚙️ Rules</a>
+            </div>
+        </div>"#
+            .to_string()
+    }
+
+    /// Generate the layout / organization / view toolbar
+    fn generate_controls_html(&self, sections: &PageSections) -> String {
+        let legend_toggle = if sections.legend {
+            r#"<div class="control-group">
+                <button id="legend-toggle" class="btn btn-secondary">📋 Legend</button>
+            </div>"#
+        } else {
+            ""
+        };
+
         format!(
-            r#"
-            <div class="stat-card">
-                <div class="stat-number">{}</div>
-                <div class="stat-label">Total Modules</div>
+            r#"<div class="visualization-controls">
+            <div class="control-group">
+                <h4>Layout</h4>
+                <button id="layout-grid" class="btn btn-secondary active">Grid</button>
+                <button id="layout-circular" class="btn btn-secondary">Circular</button>
+                <button id="layout-hierarchical" class="btn btn-secondary">Hierarchical</button>
             </div>
-            <div class="stat-card">
-                <div class="stat-number">{}</div>
-                <div class="stat-label">Active Modules</div>
+            <div class="control-group">
+                <h4>Organization</h4>
+                <button id="reorder-hierarchical" class="btn btn-secondary active">Hierarchical</button>
+                <button id="reorder-grouped" class="btn btn-secondary">Grouped by Type</button>
+                <button id="reorder-dependency" class="btn btn-secondary">Dependency Driven</button>
+                <button id="reorder-alphabetical" class="btn btn-secondary">Alphabetical</button>
             </div>
-            <div class="stat-card">
-                <div class="stat-number">{}</div>
-                <div class="stat-label">Lines of Code</div>
+            <div class="control-group" id="view-controls">
+                <h4>View</h4>
+                <button id="view-modules" class="btn btn-secondary active">Modules</button>
+                <button id="view-components" class="btn btn-secondary">🧩 Components</button>
+                <button id="view-crates" class="btn btn-secondary">📦 Crates</button>
+                <button id="view-features" class="btn btn-secondary">🚩 Features</button>
+                <button id="toggle-test-targets" class="btn btn-secondary">🧪 Tests &amp; Benches</button>
+                <button id="toggle-ignored" class="btn btn-secondary">👻 Show Ignored</button>
+                <button id="toggle-trait-impls" class="btn btn-secondary" title="Only show edges of modules implementing traits of other modules">🔗 Trait Impls Only</button>
+                <button id="toggle-directories" class="btn btn-secondary" title="Collapse the module graph to its top-level directories, or expand them all">📁 Collapse Directories</button>
+                <button id="toggle-color-mode" class="btn btn-secondary">🧱 Color by Layer</button>
+                <button id="toggle-robustness" class="btn btn-secondary">🛡️ Robustness</button>
+                <button id="toggle-data-access" class="btn btn-secondary">🗄️ Data Access</button>
+                <button id="toggle-lints" class="btn btn-secondary" title="Color modules by clippy lints per line">🧹 Lints</button>
             </div>
-            <div class="stat-card">
-                <div class="stat-number">{:.1}</div>
-                <div class="stat-label">Avg Complexity</div>
+            <div class="control-group search-controls" id="search-controls">
+                <h4>Find</h4>
+                <input type="search" id="module-search" class="curation-select" placeholder="Module, file or item…" aria-label="Find modules" autocomplete="off">
+                <ul class="search-results" id="search-results"></ul>
             </div>
-            <div class="stat-card">
-                <div class="stat-number">{}</div>
-                <div class="stat-label">Dependencies</div>
+            <div class="control-group" id="trace-controls">
+                <h4>Trace</h4>
+                <select id="trace-entry-point" class="curation-select" aria-label="Trace from entry point">
+                    <option value="">Trace from entry point…</option>
+                </select>
             </div>
-            <div class="stat-card">
-                <div class="stat-number">{}</div>
-                <div class="stat-label">Circular Deps</div>
+            <div class="control-group" id="variant-controls">
+                <h4>Variant</h4>
+                <select id="variant-select" class="curation-select" aria-label="Build variant">
+                    <option value="">Every cfg combination</option>
+                </select>
+                <span class="variant-summary" id="variant-summary"></span>
             </div>
-            "#,
+            {}
+        </div>"#,
+            legend_toggle
+        )
+    }
+
+    /// Generate the side panel that shows the selected module
+    fn generate_details_panel_html(&self) -> String {
+        r#"<aside class="details-panel" id="details-panel">
+                <div class="details-header">
+                    <h3>Module Details</h3>
+                    <button id="close-details" class="btn btn-close" aria-label="Close details">✕</button>
+                </div>
+                <div class="details-content" id="details-content">
+                    <p class="details-placeholder">Click on a module to see details</p>
+                </div>
+            </aside>"#
+            .to_string()
+    }
+
+    /// Generate the footer with scan information
+    fn generate_footer_html(&self, architecture: &ArchitectureMap) -> String {
+        let provenance = architecture
+            .provenance
+            .as_ref()
+            .map(|provenance| {
+                let revision = match (&provenance.git_commit, provenance.git_dirty) {
+                    (Some(commit), Some(true)) => format!("{} (dirty)", &commit[..commit.len().min(12)]),
+                    (Some(commit), _) => commit[..commit.len().min(12)].to_string(),
+                    (None, _) => "no git".to_string(),
+                };
+                format!(
+                    r#"
+                <span title="Config hash {}">rust-arch-viz {} · {} · {} ms{}</span>"#,
+                    static_page::escape_html(&provenance.config_hash),
+                    static_page::escape_html(&provenance.tool_version),
+                    static_page::escape_html(&revision),
+                    provenance.scan_duration_ms,
+                    provenance
+                        .host
+                        .as_ref()
+                        .map(|host| format!(" · {}", static_page::escape_html(host)))
+                        .unwrap_or_default()
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            r#"<div class="footer">
+            <div class="info">
+                <span>Last updated: {}</span>
+                <span>Total modules: {}</span>
+                <span>Dependencies: {}</span>{}
+            </div>
+        </div>"#,
+            architecture.last_scan.format("%Y-%m-%d %H:%M:%S UTC"),
             architecture.total_modules,
-            active_modules,
-            architecture.total_lines,
-            architecture.average_complexity,
             architecture.edges.len(),
-            architecture.circular_dependencies.len()
+            provenance
         )
     }
 
+    /// Generate stats HTML
+    fn generate_stats_html(&self, architecture: &ArchitectureMap) -> String {
+        let counted = ignored::counted_nodes(&architecture.nodes);
+        let cards: String = self
+            .config
+            .visualization
+            .stat_cards
+            .iter()
+            .map(|card| {
+                let (value, label) = stat_card(*card, architecture, &counted);
+                format!(
+                    r#"
+            <div class="stat-card">
+                <div class="stat-number">{}</div>
+                <div class="stat-label">{}</div>
+            </div>"#,
+                    value, label
+                )
+            })
+            .collect();
+        
+        format!("<div class=\"stats\">{}\n        </div>", cards)
+    }
+
     /// Generate legend HTML
     fn generate_legend_html(&self) -> String {
         let module_types = [
@@ -286,29 +970,114 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
             (ModuleType::Execution, "Execution"),
             (ModuleType::Integration, "Integration"),
             (ModuleType::Testing, "Testing"),
+            (ModuleType::IntegrationTest, "Integration Tests"),
+            (ModuleType::Bench, "Benches"),
+            (ModuleType::Generated, "Generated"),
             (ModuleType::Utilities, "Utilities"),
         ];
 
         let legend_items = module_types
             .iter()
-            .map(|(module_type, label)| {
+            .map(|(module_type, label)| {
+                format!(
+                    r#"<div class="legend-item">
+                        <div class="legend-color" style="background-color: {};"></div>
+                        <span>{}</span>
+                    </div>"#,
+                    module_type.color(),
+                    label
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("");
+
+        let layer_items: String = self
+            .config
+            .rules
+            .effective_layers()
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| {
+                format!(
+                    r#"<div class="legend-item">
+                        <div class="legend-color" style="background-color: {};"></div>
+                        <span>{}</span>
+                    </div>"#,
+                    static_page::escape_html(&rules::layer_color(layer, index)),
+                    static_page::escape_html(&layer.name)
+                )
+            })
+            .collect();
+        let robustness_items: String = ROBUSTNESS_COLORS
+            .iter()
+            .map(|(_, color, label)| {
+                format!(
+                    r#"<div class="legend-item">
+                        <div class="legend-color" style="background-color: {};"></div>
+                        <span>{}</span>
+                    </div>"#,
+                    color, label
+                )
+            })
+            .collect();
+        let data_access_items: String = DATA_ACCESS_COLORS
+            .iter()
+            .map(|(_, color, label)| {
                 format!(
                     r#"<div class="legend-item">
                         <div class="legend-color" style="background-color: {};"></div>
                         <span>{}</span>
                     </div>"#,
-                    module_type.color(),
-                    label
+                    color, label
                 )
             })
-            .collect::<Vec<_>>()
-            .join("");
+            .collect();
+        let lint_legend = if self.config.clippy.enabled {
+            let items: String = LINT_DENSITY_COLORS
+                .iter()
+                .map(|(_, color, label)| {
+                    format!(
+                        r#"<div class="legend-item">
+                        <div class="legend-color" style="background-color: {};"></div>
+                        <span>{}</span>
+                    </div>"#,
+                        color, label
+                    )
+                })
+                .collect();
+            format!(
+                r#"<h4 style="margin-top: 1rem;">Clippy lint overlay</h4>
+                {}"#,
+                items
+            )
+        } else {
+            String::new()
+        };
+
+        let layer_legend = if layer_items.is_empty() {
+            String::new()
+        } else {
+            format!(
+                r#"<h4 style="margin-top: 1rem;">Layers (top to bottom)</h4>
+                {}
+                <div class="legend-item">
+                    <div class="legend-color" style="background-color: #dc2626;"></div>
+                    <span>Upward dependency</span>
+                </div>"#,
+                layer_items
+            )
+        };
 
         format!(
-            r#"
-            <div class="legend">
+            r#"<div class="legend">
                 <h4>Module Types</h4>
                 {}
+                {}
+                <h4 style="margin-top: 1rem;">Robustness overlay</h4>
+                {}
+                <h4 style="margin-top: 1rem;">Data access overlay</h4>
+                {}
+                {}
                 <h4 style="margin-top: 1rem;">Dependency Types</h4>
                 <div class="legend-item">
                     <div class="legend-color" style="background-color: #10b981;"></div>
@@ -326,9 +1095,12 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
                     <div class="legend-color" style="background-color: #ef4444;"></div>
                     <span>Circular Deps</span>
                 </div>
-            </div>
-            "#,
-            legend_items
+            </div>"#,
+            legend_items,
+            layer_legend,
+            robustness_items,
+            data_access_items,
+            lint_legend
         )
     }
 
@@ -339,7 +1111,7 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
         _settings: &VisualizationSettings,
     ) -> String {
         format!(
-            r#"<div id="react-flow-root" data-node-count="{}"></div>"#,
+            r#"<div class="architecture-canvas" id="react-flow-root" data-node-count="{}"></div>"#,
             architecture.nodes.len()
         )
     }
@@ -352,18 +1124,44 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
         let mut ordered_nodes: Vec<_> = architecture.nodes.values().collect();
         ordered_nodes.sort_by(|a, b| a.name.cmp(&b.name));
 
+        let layers: Vec<Value> = self
+            .config
+            .rules
+            .effective_layers()
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| json!({ "name": layer.name, "color": rules::layer_color(layer, index) }))
+            .collect();
+        let layer_color = |name: &Option<String>| {
+            name.as_ref().and_then(|name| {
+                layers
+                    .iter()
+                    .find(|layer| layer["name"] == name.as_str())
+                    .map(|layer| layer["color"].clone())
+            })
+        };
+
         let mut node_entries = Vec::new();
+        let querying = data_access::querying_modules(architecture);
+        let percentiles = metric_percentiles(architecture);
         for (index, node) in ordered_nodes.iter().enumerate() {
+            let data_access_role = data_access::role(node, architecture, &querying);
             let mut entry = json!({
                 "id": node.id,
                 "name": node.name,
                 "icon": node.module_type.icon(),
                 "moduleType": node.module_type.display_name(),
+                "moduleTypeKey": format!("{:?}", node.module_type),
                 "color": node.module_type.color(),
                 "status": format!("{:?}", node.status),
                 "filePath": node.file_path,
+                "crateName": node.crate_name.as_deref().unwrap_or(UNOWNED_CRATE),
+                "entryPoint": node.entry_point,
+                "modulePath": node.module_path,
+                "testTarget": node.module_type.is_test_target(),
                 "order": index,
                 "hierarchyLevel": node.dependencies.len(),
+                "sizeFactor": node_size_factor(settings.node_size_metric, node),
                 "dependencies": node.dependencies,
                 "dependents": node.dependents,
                 "metrics": {
@@ -378,11 +1176,55 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
                     "dependent_count": node.metrics.dependent_count,
                     "error_count": node.metrics.error_count,
                     "warning_count": node.metrics.warning_count,
+                    "recent_churn": node.metrics.recent_churn,
+                    "commit_count": node.metrics.commit_count,
+                    "last_author": node.metrics.last_author,
+                    "hotspot_score": hotspot_score(&node.metrics),
                     "last_build_time": node.metrics.last_build_time.map(|time| time.to_rfc3339()),
                 },
+                "percentiles": percentiles.get(&node.id),
                 "lastModified": node.last_modified.to_rfc3339(),
+                "namingViolations": node.naming_violations,
+                "layer": node.layer,
+                "component": node.component,
+                "ignored": node.ignored,
+                "layerColor": layer_color(&node.layer),
+                "documentation": node.documentation,
+                "annotations": node.annotations,
+                "routes": node.routes,
+                "splitSuggestion": node.split_suggestion,
+                "deadCode": node.dead_code,
+                "ownership": node.ownership,
+                "robustness": node.robustness,
+                "robustnessColor": ROBUSTNESS_COLORS
+                    .iter()
+                    .find(|(level, ..)| *level == node.robustness.level())
+                    .map(|(_, color, _)| *color),
+                "dataAccess": node.data_access,
+                "dataAccessRole": data_access_role,
+                "dataAccessColor": DATA_ACCESS_COLORS
+                    .iter()
+                    .find(|(role, ..)| Some(*role) == data_access_role)
+                    .map(|(_, color, _)| *color),
+                "functions": node
+                    .functions
+                    .iter()
+                    .map(|function| json!({ "name": function.name, "line": function.line, "isPublic": function.is_public }))
+                    .collect::<Vec<_>>(),
             });
 
+            // Outside the literal above, which is at the `json!` recursion limit
+            if let Some(obj) = entry.as_object_mut() {
+                obj.insert("groups".to_string(), json!(node.groups));
+                obj.insert("apiLeaks".to_string(), json!(node.api_leaks));
+                obj.insert("impls".to_string(), json!(node.impls));
+                obj.insert("clippyLints".to_string(), json!(node.metrics.clippy_lints));
+                obj.insert("lintDensity".to_string(), json!(clippy_analyzer::lint_density(node)));
+                obj.insert(
+                    "lintColor".to_string(),
+                    json!(LINT_DENSITY_COLORS.iter().find(|(level, ..)| Some(*level) == lint_level(node)).map(|(_, color, _)| *color)),
+                );
+            }
             if let Some(position) = node.position.as_ref() {
                 if let Some(obj) = entry.as_object_mut() {
                     obj.insert(
@@ -399,14 +1241,14 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
         }
 
         let mut edge_entries = Vec::new();
-        for (index, edge) in architecture.edges.iter().enumerate() {
+        for (edge, id) in architecture.edges.iter().zip(architecture.edge_ids()) {
             let color = if edge.is_circular {
                 "#ef4444"
             } else {
                 "#94a3b8"
             };
             edge_entries.push(json!({
-                "id": format!("edge-{}-{}-{}", edge.from, edge.to, index),
+                "id": id,
                 "source": edge.from,
                 "target": edge.to,
                 "type": "smoothstep",
@@ -416,6 +1258,9 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
                     "relationship": format!("{:?}", edge.relationship),
                     "strength": edge.strength,
                     "isCircular": edge.is_circular,
+                    "layerViolation": edge.layer_violation,
+                    "locations": edge.locations,
+                    "suggestions": edge.suggestions,
                     "color": color,
                 },
                 "style": {
@@ -436,27 +1281,315 @@ body.theme-dark .legend{background:rgba(30,41,59,.95);color:#e2e8f0;border:1px s
         json!({
             "nodes": node_entries,
             "edges": edge_entries,
+            "crates": self.build_crate_flow_data(architecture),
+            "components": self.build_component_flow_data(architecture),
+            "features": self.build_feature_flow_data(architecture),
+            "layers": layers,
+            "anomalies": architecture.anomalies,
+            "orphans": architecture.orphans,
+            "variants": architecture.variants,
+            "moduleGroups": architecture.module_groups,
+            "lastScan": architecture.last_scan,
+            "cycles": cycles::analyze(architecture),
+            "build": architecture.hook_outcome.as_ref().map(BuildStatus::from_outcome),
             "layout": settings.layout.to_string(),
             "settings": {
                 "showMetrics": settings.show_metrics,
                 "showDependencies": settings.show_dependencies,
                 "theme": theme,
+                "nodeSizeMetric": format!("{:?}", settings.node_size_metric),
+                "edgeLabels": format!("{:?}", settings.edge_labels),
+                "showTestTargets": settings.show_test_targets,
+                "collapseDirectoriesAbove": settings.collapse_directories_above,
+                "editorUrl": settings.editor_url,
+                "projectRoot": settings.editor_url.as_ref().map(|_| self.absolute_project_path()),
             }
         })
     }
 
-    /// Generate JavaScript for interactivity
-    fn generate_javascript(
-        &self,
-        architecture: &ArchitectureMap,
-        settings: &VisualizationSettings,
-    ) -> Result<String> {
-        let data = self.build_react_flow_data(architecture, settings);
-        let serialized = serde_json::to_string(&data)?;
+    /// Crate-level nodes and edges in the same shape as the module graph
+    fn build_crate_flow_data(&self, architecture: &ArchitectureMap) -> Value {
+        let view = build_crate_view(architecture);
+        let crate_id = |name: &str| format!("crate:{}", name);
+
+        let node_entries: Vec<Value> = view
+            .crates
+            .iter()
+            .enumerate()
+            .map(|(index, summary)| {
+                let dependencies: Vec<String> = view
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.from == summary.name)
+                    .map(|edge| crate_id(&edge.to))
+                    .collect();
+                let dependents: Vec<String> = view
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.to == summary.name)
+                    .map(|edge| crate_id(&edge.from))
+                    .collect();
+                json!({
+                    "id": crate_id(&summary.name),
+                    "kind": "crate",
+                    "name": summary.name,
+                    "icon": "📦",
+                    "moduleType": format!(
+                        "Crate · {} module{}",
+                        summary.module_count,
+                        if summary.module_count == 1 { "" } else { "s" }
+                    ),
+                    "color": "#0ea5e9",
+                    "status": if summary.error_count > 0 { "Error" } else { "Active" },
+                    "crateName": summary.name,
+                    "component": summary.component,
+                    "groups": summary.groups,
+                    "order": index,
+                    "hierarchyLevel": dependencies.len(),
+                    "sizeFactor": (summary.lines_of_code as f64 / 10000.0).clamp(0.0, 1.0),
+                    "dependencies": dependencies,
+                    "dependents": dependents,
+                    "version": summary.version,
+                    "edition": summary.edition,
+                    "rustVersion": summary.rust_version,
+                    "inconsistencies": summary.inconsistencies,
+                    "healthScore": summary.health_score,
+                    "healthTrend": summary.health_trend,
+                    "deadCodePercentage": summary.dead_code_percentage,
+                    "metrics": {
+                        "module_count": summary.module_count,
+                        "lines_of_code": summary.lines_of_code,
+                        "function_count": summary.function_count,
+                        "complexity_score": summary.average_complexity,
+                        "max_complexity": summary.max_complexity,
+                        "dependency_count": summary.dependency_count,
+                        "dependent_count": summary.dependent_count,
+                        "error_count": summary.error_count,
+                        "warning_count": summary.warning_count,
+                    },
+                })
+            })
+            .collect();
+
+        let edge_entries: Vec<Value> = view
+            .edges
+            .iter()
+            .map(|edge| {
+                let color = if edge.is_circular { "#ef4444" } else { "#94a3b8" };
+                json!({
+                    "id": format!("crate-edge-{}-{}", edge.from, edge.to),
+                    "source": crate_id(&edge.from),
+                    "target": crate_id(&edge.to),
+                    "label": format!(
+                        "{} module edge{}",
+                        edge.edge_count,
+                        if edge.edge_count == 1 { "" } else { "s" }
+                    ),
+                    "is_circular": edge.is_circular,
+                    "strength": (1.0 + edge.edge_count as f64).log2().min(4.0) / 2.0,
+                    "data": {
+                        "edgeCount": edge.edge_count,
+                        "isCircular": edge.is_circular,
+                        "color": color,
+                    },
+                })
+            })
+            .collect();
+
+        json!({
+            "nodes": node_entries,
+            "edges": edge_entries,
+        })
+    }
+
+    /// Component-level nodes and edges, one aggregation level above the crate graph
+    fn build_component_flow_data(&self, architecture: &ArchitectureMap) -> Value {
+        let view = build_component_view(architecture);
+        let component_id = |name: &str| format!("component:{}", name);
+
+        let node_entries: Vec<Value> = view
+            .components
+            .iter()
+            .enumerate()
+            .map(|(index, summary)| {
+                let dependencies: Vec<String> = view
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.from == summary.name)
+                    .map(|edge| component_id(&edge.to))
+                    .collect();
+                let dependents: Vec<String> = view
+                    .edges
+                    .iter()
+                    .filter(|edge| edge.to == summary.name)
+                    .map(|edge| component_id(&edge.from))
+                    .collect();
+                json!({
+                    "id": component_id(&summary.name),
+                    "kind": "component",
+                    "name": summary.name,
+                    "icon": "🧩",
+                    "moduleType": format!(
+                        "Component · {} crate{}",
+                        summary.crates.len(),
+                        if summary.crates.len() == 1 { "" } else { "s" }
+                    ),
+                    "color": "#8b5cf6",
+                    "status": if summary.error_count > 0 { "Error" } else { "Active" },
+                    "crates": summary.crates,
+                    "groups": summary.groups,
+                    "order": index,
+                    "hierarchyLevel": dependencies.len(),
+                    "sizeFactor": (summary.lines_of_code as f64 / 50000.0).clamp(0.0, 1.0),
+                    "dependencies": dependencies,
+                    "dependents": dependents,
+                    "metrics": {
+                        "module_count": summary.module_count,
+                        "lines_of_code": summary.lines_of_code,
+                        "function_count": summary.function_count,
+                        "complexity_score": summary.average_complexity,
+                        "dependency_count": summary.dependency_count,
+                        "dependent_count": summary.dependent_count,
+                        "error_count": summary.error_count,
+                        "warning_count": summary.warning_count,
+                    },
+                })
+            })
+            .collect();
+
+        let edge_entries: Vec<Value> = view
+            .edges
+            .iter()
+            .map(|edge| {
+                let color = if edge.is_circular { "#ef4444" } else { "#94a3b8" };
+                json!({
+                    "id": format!("component-edge-{}-{}", edge.from, edge.to),
+                    "source": component_id(&edge.from),
+                    "target": component_id(&edge.to),
+                    "label": format!(
+                        "{} module edge{}",
+                        edge.edge_count,
+                        if edge.edge_count == 1 { "" } else { "s" }
+                    ),
+                    "is_circular": edge.is_circular,
+                    "strength": (1.0 + edge.edge_count as f64).log2().min(4.0) / 2.0,
+                    "data": {
+                        "edgeCount": edge.edge_count,
+                        "isCircular": edge.is_circular,
+                        "color": color,
+                    },
+                })
+            })
+            .collect();
+
+        json!({
+            "nodes": node_entries,
+            "edges": edge_entries,
+        })
+    }
+
+    /// Feature and optional dependency nodes plus the edges to what they switch on.
+    ///
+    /// Gated modules are referenced by node id and drawn from the module graph.
+    fn build_feature_flow_data(&self, architecture: &ArchitectureMap) -> Value {
+        let view = build_feature_view(architecture);
+        let outgoing = |key: &str| -> Vec<&str> {
+            view.edges.iter().filter(|edge| edge.from == key).map(|edge| edge.to.as_str()).collect()
+        };
+        let incoming = |key: &str| -> Vec<&str> {
+            view.edges.iter().filter(|edge| edge.to == key).map(|edge| edge.from.as_str()).collect()
+        };
+
+        let mut node_entries: Vec<Value> = Vec::new();
+        for feature in &view.features {
+            let dependencies = outgoing(&feature.key);
+            let module_count = feature.gated_modules.len();
+            node_entries.push(json!({
+                "id": feature.key,
+                "kind": "feature",
+                "name": feature.name,
+                "icon": "🚩",
+                "moduleType": format!(
+                    "Feature · {} · {} module{}",
+                    feature.crate_name,
+                    module_count,
+                    if module_count == 1 { "" } else { "s" }
+                ),
+                "color": "#a855f7",
+                "status": "Active",
+                "crateName": feature.crate_name,
+                "order": node_entries.len(),
+                "hierarchyLevel": 0,
+                "sizeFactor": (module_count as f64 / 10.0).clamp(0.0, 1.0),
+                "dependencies": dependencies,
+                "dependents": incoming(&feature.key),
+                "gatedModules": feature.gated_modules,
+                "metrics": {
+                    "gated_module_count": module_count,
+                    "dependency_count": dependencies.len(),
+                },
+            }));
+        }
+        for dependency in &view.dependencies {
+            node_entries.push(json!({
+                "id": dependency.key,
+                "kind": "dependency",
+                "name": dependency.name,
+                "icon": "🔌",
+                "moduleType": format!("Optional dependency · {}", dependency.crate_name),
+                "color": "#64748b",
+                "status": "Active",
+                "crateName": dependency.crate_name,
+                "order": node_entries.len(),
+                "hierarchyLevel": 1,
+                "sizeFactor": 0.0,
+                "dependencies": [],
+                "dependents": incoming(&dependency.key),
+                "metrics": {},
+            }));
+        }
+
+        let edge_entries: Vec<Value> = view
+            .edges
+            .iter()
+            .map(|edge| {
+                let (label, color) = match edge.kind {
+                    FeatureEdgeKind::Enables => ("enables".to_string(), "#a855f7"),
+                    FeatureEdgeKind::Activates => match &edge.detail {
+                        Some(detail) => (format!("activates {}", detail), "#64748b"),
+                        None => ("activates".to_string(), "#64748b"),
+                    },
+                    FeatureEdgeKind::Gates => ("gates".to_string(), "#94a3b8"),
+                };
+                json!({
+                    "id": format!("feature-edge-{}-{}", edge.from, edge.to),
+                    "source": edge.from,
+                    "target": edge.to,
+                    "label": label,
+                    "strength": 0.75,
+                    "data": {
+                        "kind": edge.kind,
+                        "color": color,
+                    },
+                    "style": {
+                        "stroke": color,
+                    },
+                })
+            })
+            .collect();
+
+        json!({
+            "nodes": node_entries,
+            "edges": edge_entries,
+        })
+    }
+
+    /// Generate JavaScript for interactivity, with `data_source` as the expression yielding the page data
+    fn render_javascript(&self, data_source: &str) -> String {
         let template = r#"
-import * as React from 'https://esm.sh/react@18.2.0';
-import * as ReactDOMClient from 'https://esm.sh/react-dom@18.2.0/client';
-import ReactFlow, { Background, Controls, MiniMap, MarkerType, ReactFlowProvider, applyEdgeChanges, applyNodeChanges, Handle, Position } from 'https://esm.sh/reactflow@11.6.0?deps=react@18.2.0,react-dom@18.2.0';
+import * as React from 'react';
+import * as ReactDOMClient from 'react-dom/client';
+import ReactFlow, { Background, Controls, MiniMap, MarkerType, ReactFlowProvider, applyEdgeChanges, applyNodeChanges, Handle, Position, useReactFlow } from 'reactflow';
 
 const { createRoot } = ReactDOMClient;
 const globalObj = typeof globalThis !== 'undefined' ? globalThis : (typeof window !== 'undefined' ? window : {});
@@ -478,10 +1611,84 @@ const rawEdges = Array.isArray(architectureData.edges)
     : Object.values(architectureData.edges || {});
 const edgesData = shouldShowDependencies ? rawEdges : [];
 const nodeLookup = new Map(nodesData.map((node, index) => [node.id, { ...node, order: node.order ?? index }]));
+const crateNodesData = architectureData.crates?.nodes || [];
+const crateEdgesData = shouldShowDependencies ? (architectureData.crates?.edges || []) : [];
+const componentNodesData = architectureData.components?.nodes || [];
+const componentEdgesData = shouldShowDependencies ? (architectureData.components?.edges || []) : [];
+const featureNodesData = architectureData.features?.nodes || [];
+const featureEdgesData = architectureData.features?.edges || [];
+const featureModuleIds = new Set(featureNodesData.flatMap((node) => node.gatedModules || []));
+// The configured feature sets and targets, each with the modules its cfg leaves out
+const variantsData = architectureData.variants || [];
+const viewModes = ['modules', 'components', 'crates', 'features'];
+// Which views have anything to show; the module view is always there
+const availableViews = {
+    modules: true,
+    components: componentNodesData.length >= 2,
+    crates: crateNodesData.length >= 2,
+    features: featureNodesData.length > 0,
+};
+const hasAlternateViews = availableViews.components || availableViews.crates || availableViews.features;
+const hasTestTargets = nodesData.some((node) => node.testTarget);
+const hasIgnored = nodesData.some((node) => node.ignored);
+const isTraitImpl = (edge) => edge.data?.relationship === 'Implements';
+const hasTraitImpls = edgesData.some(isTraitImpl);
+const hasLayers = Array.isArray(architectureData.layers) && architectureData.layers.length > 0;
+
+// Layout, ordering, filters, overlay, theme and the selected module live in the
+// query string, so reloading or sharing the link reproduces the view
+const urlState = new URLSearchParams(window.location.search);
+const urlChoice = (key, options, fallback) => {
+    const value = urlState.get(key);
+    return options.includes(value) ? value : fallback;
+};
+// Defaults are left out to keep links short
+const setUrlParam = (key, value, fallback = null) => {
+    if (value === null || value === undefined || value === fallback) {
+        urlState.delete(key);
+    } else {
+        urlState.set(key, String(value));
+    }
+    const query = urlState.toString();
+    try {
+        window.history.replaceState(window.history.state, '', `${window.location.pathname}${query ? `?${query}` : ''}${window.location.hash}`);
+    } catch (error) {
+        // Some browsers refuse history changes on file:// pages
+    }
+};
+
+// Modules are colored by type, or by configured layer when the rules define layers
+const defaultColorMode = hasLayers ? 'layer' : 'type';
+let currentColorMode = urlChoice('color', hasLayers ? ['type', 'layer', 'robustness', 'dataAccess', 'lints'] : ['type', 'robustness', 'dataAccess', 'lints'], defaultColorMode);
+const UNLAYERED_COLOR = '#94a3b8';
+// While the watch command fails, modules with errors are marked whatever the overlay
+const BUILD_ERROR_COLOR = '#dc2626';
+const isBuildFailure = (status) => status?.state === 'finished' && !status.success;
+let buildFailing = isBuildFailure(architectureData.build);
+// Test targets may unwrap freely, so the robustness overlay greys them out
+const accentColorFor = (data) => {
+    if (buildFailing && data.status === 'Error') return BUILD_ERROR_COLOR;
+    if (currentColorMode === 'robustness') return data.testTarget ? UNLAYERED_COLOR : (data.robustnessColor || UNLAYERED_COLOR);
+    if (currentColorMode === 'dataAccess') return data.dataAccessColor || UNLAYERED_COLOR;
+    if (currentColorMode === 'lints') return data.lintColor || UNLAYERED_COLOR;
+    if (currentColorMode === 'layer') return data.layerColor || UNLAYERED_COLOR;
+    return data.color;
+};
+const isReadOnly = architectureData?.settings?.readOnly === true;
+// The page may be mounted below a prefix such as /projects/<slug> when several projects are served
+const apiBase = window.location.pathname.replace(/\/$/, '');
+const initialCuration = {
+    pinned: architectureData.curation?.pinned || {},
+    groups: architectureData.curation?.groups || []
+};
+const liveUpdates = architectureData?.settings?.liveUpdates === true;
+const groupPalette = ['#6366f1', '#0ea5e9', '#10b981', '#f59e0b', '#ec4899', '#8b5cf6', '#14b8a6', '#ef4444'];
 
 const layouts = ['grid', 'circular', 'hierarchical'];
 const reorderOptions = ['hierarchical', 'grouped-by-type', 'dependency-driven', 'alphabetical'];
-let currentLayoutIndex = Math.max(layouts.indexOf((architectureData.layout || 'grid').toLowerCase()), 0);
+const defaultLayout = layouts[Math.max(layouts.indexOf((architectureData.layout || 'grid').toLowerCase()), 0)];
+let currentLayoutIndex = layouts.indexOf(urlChoice('layout', layouts, defaultLayout));
+const defaultShowTestTargets = architectureData?.settings?.showTestTargets !== false;
 
 // Utility functions
 const escapeHtml = (value) => value === null || value === undefined ? '' : String(value).replace(/&/g,'&amp;').replace(/</g,'&lt;').replace(/>/g,'&gt;').replace(/"/g,'&quot;').replace(/'/g,'&#39;');
@@ -689,20 +1896,388 @@ const computePositions = (layout, nodes, reorderType = 'hierarchical') => {
     return resolveCollisions(positions);
 };
 
-const buildNodes = (layout, nodes, reorderType = 'hierarchical') => {
+const baseNodeWidth = 210;
+const nodeWidthRange = 90;
+const nodeWidthFor = (node) => baseNodeWidth + Math.max(0, Math.min(1, Number(node?.sizeFactor) || 0)) * nodeWidthRange;
+
+// Pinned positions and manual groups, persisted through /api/layout
+const groupOf = (curation, nodeId) => curation.groups.find((group) => (group.members || []).includes(nodeId)) || null;
+const withoutMember = (groups, nodeId) => groups.map((group) => ({ ...group, members: (group.members || []).filter((member) => member !== nodeId) }));
+const decorateNodeData = (data, curation) => {
+    const group = groupOf(curation, data.id);
+    return {
+        ...data,
+        accentColor: accentColorFor(data),
+        pinned: Boolean(curation.pinned[data.id]),
+        groupName: group ? group.name : null,
+        groupColor: group ? (group.color || groupPalette[0]) : null
+    };
+};
+const saveCuration = (curation) => {
+    if (isReadOnly) return;
+    fetch(`${apiBase}/api/layout`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(curation)
+    })
+        .then((response) => {
+            if (!response.ok) response.text().then((message) => console.warn('[Layout] Save failed:', message));
+        })
+        .catch((error) => console.warn('[Layout] Save failed:', error));
+};
+
+// Manual module types, persisted as `[classification]` rules through /api/config
+const classificationRules = architectureData?.settings?.classificationRules || [];
+const moduleTypeOptions = architectureData?.settings?.moduleTypes || [];
+const manualModuleType = (filePath) => classificationRules.find((rule) => rule.pattern === filePath)?.module_type ?? null;
+const saveModuleType = (filePath, moduleType) => {
+    if (isReadOnly) return;
+    // An exact path goes first so it wins over broader patterns
+    const rules = classificationRules.filter((rule) => rule.pattern !== filePath);
+    if (moduleType) rules.unshift({ pattern: filePath, module_type: moduleType });
+    fetch(`${apiBase}/api/config`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ classification: { rules } })
+    })
+        .then((response) => {
+            if (response.ok) {
+                window.location.reload();
+            } else {
+                response.text().then((message) => console.warn('[Classification] Save failed:', message));
+            }
+        })
+        .catch((error) => console.warn('[Classification] Save failed:', error));
+};
+
+// Modules left out of metrics and cycles, persisted as `[ignore]` paths through /api/config
+const ignoredPaths = architectureData?.settings?.ignoredPaths || [];
+const saveIgnored = (filePath, ignore) => {
+    if (isReadOnly) return;
+    const paths = ignoredPaths.filter((path) => path !== filePath);
+    if (ignore) paths.push(filePath);
+    fetch(`${apiBase}/api/config`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ ignore: { paths } })
+    })
+        .then((response) => {
+            if (response.ok) {
+                window.location.reload();
+            } else {
+                response.text().then((message) => console.warn('[Ignore] Save failed:', message));
+            }
+        })
+        .catch((error) => console.warn('[Ignore] Save failed:', error));
+};
+
+const toFlowNode = (node, position, curation) => ({
+    id: node.id,
+    type: 'module',
+    position: curation.pinned[node.id] || position || { x: 0, y: 0 },
+    data: decorateNodeData(node, curation),
+    style: { width: nodeWidthFor(node) },
+    className: '',
+    sourcePosition: Position.Right,
+    targetPosition: Position.Left,
+    draggable: true
+});
+
+const replaceContents = (target, items) => target.splice(0, target.length, ...items);
+
+// Modules no library, binary, example or build script reaches, listed above the graph
+const renderOrphans = (orphans) => {
+    const panel = document.getElementById('orphan-panel');
+    if (!panel) return;
+    const modules = orphans.map((id) => nodeLookup.get(id)).filter(Boolean);
+    panel.hidden = !modules.length;
+    panel.innerHTML = modules.length
+        ? `<summary>🪦 ${modules.length} orphan module${modules.length === 1 ? '' : 's'}: nothing compiled reaches ${modules.length === 1 ? 'it' : 'them'}</summary><ul>${modules.map((node) => `<li><button type="button" class="notices__link" data-orphan-id="${escapeHtml(node.id)}">${escapeHtml(node.name)}</button> <span class="details-path">${escapeHtml(node.filePath)}</span></li>`).join('')}</ul>`
+        : '';
+};
+
+// Groups of mutually dependent modules and the dependencies to remove to untangle each, listed above the graph
+const renderCycles = (cycles) => {
+    const panel = document.getElementById('cycle-panel');
+    if (!panel) return;
+    panel.hidden = !cycles.length;
+    if (!cycles.length) {
+        panel.innerHTML = '';
+        return;
+    }
+    const moduleLink = (id, name) => `<button type="button" class="notices__link" data-cycle-module="${escapeHtml(id)}">${escapeHtml(name)}</button>`;
+    const groups = cycles.map((cycle, index) => {
+        const breaks = cycle.break_edges.map((edge) => {
+            const statements = edge.locations.map((location) => `<li>${editorLink(location.file_path, location.line, `${location.file_path}:${location.line}`)} <code>${escapeHtml(location.snippet)}</code></li>`).join('');
+            return `<li>${moduleLink(edge.from, edge.from_name)} → ${moduleLink(edge.to, edge.to_name)}${statements ? `<ul>${statements}</ul>` : ''}</li>`;
+        }).join('');
+        return `<div class="notices__cycle"><strong>${cycle.modules.length} modules</strong>, ${cycle.edges.length} dependencies: ${cycle.modules.map((module) => moduleLink(module.id, module.name)).join(', ')} · <button type="button" class="notices__link" data-cycle-focus="${index}">show in graph</button>
+            <div>Remove ${cycle.break_edges.length === 1 ? 'this dependency' : `these ${cycle.break_edges.length} dependencies`} to break every cycle:</div><ul>${breaks}</ul></div>`;
+    }).join('');
+    panel.innerHTML = `<summary>🔁 ${cycles.length} group${cycles.length === 1 ? '' : 's'} of circular dependencies</summary>${groups}`;
+    panel.cycles = cycles;
+};
+
+// Unusual jumps since the latest stored snapshot, listed above the graph
+const renderAnomalies = (anomalies) => {
+    const container = document.getElementById('anomaly-notices');
+    if (!container) return;
+    container.hidden = !anomalies.length;
+    container.innerHTML = anomalies.length
+        ? `<h4>⚠️ Unusual changes since the last snapshot</h4><ul>${anomalies.map((anomaly) => `<li>${escapeHtml(anomaly.message)}</li>`).join('')}</ul>`
+        : '';
+};
+
+// Build strip fed by the watch command: building, ok, or the errors and the files they are in
+const renderBuildStatus = (status) => {
+    const strip = document.getElementById('build-status');
+    if (!strip || !status) return;
+    const seconds = `${(status.duration_ms / 1000).toFixed(1)}s`;
+    const warnings = status.warning_count ? ` · ${status.warning_count} warning${status.warning_count === 1 ? '' : 's'}` : '';
+    let state = 'building';
+    let text = `⏳ Building: ${escapeHtml(status.command)}…`;
+    if (status.state === 'finished' && status.success) {
+        state = 'ok';
+        text = `✅ Build OK${warnings} · ${seconds}`;
+    } else if (status.state === 'finished') {
+        const files = status.failing_files || [];
+        state = 'failed';
+        text = status.error_count
+            ? `❌ ${status.error_count} error${status.error_count === 1 ? '' : 's'}${warnings} · ${seconds}`
+            : `❌ ${escapeHtml(status.command)} failed · ${seconds}`;
+        if (files.length) text += ` <span class="build-status__files">in ${files.map(escapeHtml).join(', ')}</span>`;
+    }
+    strip.hidden = false;
+    strip.className = `build-status build-status--${state}`;
+    strip.innerHTML = text;
+};
+
+// How old the shown scan is; past `server.max_data_age` it is flagged as stale
+const maxDataAge = architectureData?.settings?.maxDataAge ?? null;
+let shownScanTime = architectureData.lastScan ? new Date(architectureData.lastScan) : null;
+const describeAge = (seconds) => {
+    if (seconds < 60) return 'less than a minute';
+    if (seconds < 3600) return `${Math.floor(seconds / 60)} min`;
+    if (seconds < 86400) return `${Math.floor(seconds / 3600)} h`;
+    return `${Math.floor(seconds / 86400)} d`;
+};
+const renderDataAge = () => {
+    const element = document.getElementById('data-age');
+    if (!element || !shownScanTime) return;
+    const age = Math.max(0, (Date.now() - shownScanTime.getTime()) / 1000);
+    // A past snapshot is old on purpose
+    const stale = maxDataAge !== null && !viewingSnapshot && age > maxDataAge;
+    element.hidden = false;
+    element.classList.toggle('data-age--stale', stale);
+    element.title = `Scanned ${shownScanTime.toLocaleString()}`;
+    element.textContent = stale
+        ? `⚠ Scanned ${describeAge(age)} ago, over the ${describeAge(maxDataAge)} limit${architectureData.settings.rescansStaleData ? ' · rescanning' : ''}`
+        : `🕒 Scanned ${describeAge(age)} ago`;
+};
+const setShownScanTime = (lastScan) => {
+    if (!lastScan) return;
+    shownScanTime = new Date(lastScan);
+    renderDataAge();
+};
+
+// Fold a patch from the server into the page data; FlowApp picks it up on 'architecturePatch'
+const applyArchitecturePatch = (patch) => {
+    const removedNodes = new Set(patch.nodes?.removed || []);
+    const updatedNodes = new Map((patch.nodes?.updated || []).map((node) => [node.id, node]));
+    replaceContents(nodesData, nodesData
+        .filter((node) => !removedNodes.has(node.id))
+        .map((node) => updatedNodes.get(node.id) || node)
+        .concat(patch.nodes?.added || []));
+    nodeLookup.clear();
+    nodesData.forEach((node, index) => nodeLookup.set(node.id, { ...node, order: node.order ?? index }));
+
+    const removedEdges = new Set(patch.edges?.removed || []);
+    replaceContents(rawEdges, rawEdges.filter((edge) => !removedEdges.has(edge.id)).concat(patch.edges?.added || []));
+
+    if (patch.crates) {
+        replaceContents(crateNodesData, patch.crates.nodes || []);
+        if (shouldShowDependencies) replaceContents(crateEdgesData, patch.crates.edges || []);
+    }
+    if (patch.components) {
+        replaceContents(componentNodesData, patch.components.nodes || []);
+        if (shouldShowDependencies) replaceContents(componentEdgesData, patch.components.edges || []);
+    }
+    if (patch.anomalies) renderAnomalies(patch.anomalies);
+    if (patch.orphans) renderOrphans(patch.orphans);
+    if (patch.cycles) renderCycles(patch.cycles);
+    if (patch.variants) replaceContents(variantsData, patch.variants);
+    setShownScanTime(patch.lastScan);
+    if (patch.features) {
+        replaceContents(featureNodesData, patch.features.nodes || []);
+        replaceContents(featureEdgesData, patch.features.edges || []);
+        featureModuleIds.clear();
+        featureNodesData.forEach((node) => (node.gatedModules || []).forEach((id) => featureModuleIds.add(id)));
+    }
+    window.dispatchEvent(new CustomEvent('architecturePatch', { detail: patch }));
+};
+
+// Stored snapshots for the history timeline, oldest first, and the one shown instead of the live scan
+let historySnapshots = [];
+let viewingSnapshot = null;
+
+// Swap the whole graph for `flow`; modules in both keep their place so the timeline reads as one picture
+const replaceArchitecture = (flow) => {
+    const incoming = new Set((flow.nodes || []).map((node) => node.id));
+    const current = new Set(nodesData.map((node) => node.id));
+    applyArchitecturePatch({
+        nodes: {
+            removed: nodesData.filter((node) => !incoming.has(node.id)).map((node) => node.id),
+            updated: (flow.nodes || []).filter((node) => current.has(node.id)),
+            added: (flow.nodes || []).filter((node) => !current.has(node.id)),
+        },
+        edges: { removed: rawEdges.map((edge) => edge.id), added: flow.edges || [] },
+        crates: flow.crates,
+        components: flow.components,
+        features: flow.features,
+        lastScan: flow.lastScan,
+    });
+};
+
+const describeSnapshot = (snapshot) => {
+    const commit = snapshot.git_commit ? ` · ${snapshot.git_commit.slice(0, 8)}` : '';
+    return `${new Date(snapshot.created_at).toLocaleString()} · ${snapshot.total_modules} modules${commit}`;
+};
+
+// The last slider position is the live scan, the others the stored snapshots
+const showSnapshot = async (index) => {
+    const label = document.getElementById('history-label');
+    const snapshot = historySnapshots[index] || null;
+    const query = snapshot ? `?snapshot=${encodeURIComponent(snapshot.id)}` : '';
+    try {
+        const response = await fetch(`${apiBase}/api/flow${query}`);
+        if (!response.ok) throw new Error(await response.text());
+        const flow = await response.json();
+        viewingSnapshot = snapshot;
+        replaceArchitecture(flow);
+        if (label) label.textContent = snapshot ? describeSnapshot(snapshot) : 'Live';
+    } catch (error) {
+        console.warn('[History] Failed to load snapshot:', error);
+        if (label) label.textContent = 'Failed to load snapshot';
+    }
+};
+
+const initHistoryTimeline = async () => {
+    const timeline = document.getElementById('history-timeline');
+    const slider = document.getElementById('history-slider');
+    if (isReadOnly || !timeline || !slider) return;
+    try {
+        const response = await fetch(`${apiBase}/api/history`);
+        if (!response.ok) return;
+        historySnapshots = (await response.json()).snapshots || [];
+    } catch (error) {
+        // Exported pages have no server to ask
+        return;
+    }
+    if (!historySnapshots.length) return;
+    slider.max = String(historySnapshots.length);
+    slider.value = slider.max;
+    // While dragging, the stored preview shows how the graph looked without loading the snapshot
+    const preview = document.getElementById('history-preview');
+    const showPreview = (snapshot) => {
+        if (!preview) return;
+        preview.hidden = !snapshot?.has_thumbnail;
+        if (!preview.hidden) {
+            preview.src = `${apiBase}/api/history/${encodeURIComponent(snapshot.id)}/thumbnail`;
+            preview.alt = `Module graph of ${describeSnapshot(snapshot)}`;
+        }
+    };
+    slider.addEventListener('input', () => {
+        const snapshot = historySnapshots[Number(slider.value)];
+        document.getElementById('history-label').textContent = snapshot ? describeSnapshot(snapshot) : 'Live';
+        showPreview(snapshot);
+    });
+    slider.addEventListener('change', () => {
+        showPreview(null);
+        showSnapshot(Number(slider.value));
+    });
+    timeline.hidden = false;
+};
+
+const connectLiveUpdates = () => {
+    if (!liveUpdates || !window.WebSocket) return;
+    const scheme = window.location.protocol === 'https:' ? 'wss' : 'ws';
+    const socket = new WebSocket(`${scheme}://${window.location.host}${apiBase}/ws`);
+    socket.onopen = () => socket.send(JSON.stringify({ type: 'subscribe', topics: ['graph', 'build'] }));
+    socket.onmessage = (event) => {
+        let message;
+        try {
+            message = JSON.parse(event.data);
+        } catch (error) {
+            console.warn('[Live] Ignoring malformed update:', error);
+            return;
+        }
+        if (message.type === 'patch') {
+            // A past snapshot stays on screen; going back to Live fetches the latest scan
+            if (!viewingSnapshot) applyArchitecturePatch(message);
+        } else if (message.type === 'build') {
+            renderBuildStatus(message);
+            if (message.state === 'finished' && buildFailing !== isBuildFailure(message)) {
+                buildFailing = isBuildFailure(message);
+                window.dispatchEvent(new CustomEvent('buildStatusChange'));
+            }
+        } else if (message.type === 'scanned') {
+            if (!viewingSnapshot) setShownScanTime(message.last_scan);
+        } else if (message.type === 'resync') {
+            window.location.reload();
+        }
+    };
+    socket.onclose = () => setTimeout(connectLiveUpdates, 3000);
+};
+
+const buildNodes = (layout, nodes, reorderType = 'hierarchical', curation = initialCuration) => {
     const positions = computePositions(layout, nodes, reorderType);
-    return nodes.map((node) => ({
-        id: node.id,
-        type: 'module',
-        position: positions.get(node.id) || { x: 0, y: 0 },
-        data: { ...node },
-        className: '',
-        sourcePosition: Position.Right,
-        targetPosition: Position.Left,
-        draggable: true
-    }));
+    return nodes.map((node) => toFlowNode(node, positions.get(node.id), curation));
+};
+
+// Apply patched data without re-running the layout: existing nodes keep their position
+// and selection, new ones are stacked to the right of the graph
+const mergeNodes = (current, nodes, curation) => {
+    const existing = new Map(current.map((node) => [node.id, node]));
+    const right = current.reduce((max, node) => Math.max(max, node.position.x + (node.style?.width || 0)), 0);
+    let added = 0;
+    return nodes.map((node) => {
+        const previous = existing.get(node.id);
+        if (previous) {
+            return { ...previous, data: decorateNodeData(node, curation), style: { ...previous.style, width: nodeWidthFor(node) } };
+        }
+        return toFlowNode(node, { x: right + 120, y: 80 + 160 * added++ }, curation);
+    });
+};
+
+const ENTRY_POINT_LABELS = {
+    Library: 'Library root: the API the crate exports',
+    Binary: 'Binary target: a program the crate builds'
+};
+
+// Modules reachable from `rootId` along dependency edges, the root included
+const reachableFrom = (rootId, edges) => {
+    const targets = new Map();
+    edges.forEach((edge) => {
+        const source = edge?.source ?? edge?.from;
+        if (!targets.has(source)) targets.set(source, []);
+        targets.get(source).push(edge?.target ?? edge?.to);
+    });
+    const reached = new Set([rootId]);
+    const queue = [rootId];
+    while (queue.length) {
+        (targets.get(queue.shift()) || []).forEach((target) => {
+            if (reached.has(target)) return;
+            reached.add(target);
+            queue.push(target);
+        });
+    }
+    return reached;
 };
 
+const edgeLabelMode = architectureData?.settings?.edgeLabels || 'Always';
+// Whether an edge shows its label without being hovered or selected
+const edgeLabelVisible = (flagged) => edgeLabelMode === 'Always' || (edgeLabelMode === 'CircularOnly' && flagged);
+
 const buildEdges = (edges) => edges
     .map((edge, index) => {
         const source = edge?.source ?? edge?.from;
@@ -727,15 +2302,25 @@ const buildEdges = (edges) => edges
         };
         
         // Color coding based on relationship type
-        if (relationship.includes('import') || relationship.includes('use')) {
+        const relationshipKey = relationship.toLowerCase();
+        if (relationshipKey.includes('import') || relationshipKey.includes('use')) {
             edgeStyle.stroke = '#10b981'; // Green for imports
-        } else if (relationship.includes('trait') || relationship.includes('impl')) {
+        } else if (relationshipKey.includes('trait') || relationshipKey.includes('impl')) {
             edgeStyle.stroke = '#f59e0b'; // Orange for traits
-        } else if (relationship.includes('struct') || relationship.includes('enum')) {
+        } else if (relationshipKey.includes('call')) {
+            edgeStyle.stroke = '#0ea5e9'; // Blue for calls
+        } else if (relationshipKey.includes('struct') || relationshipKey.includes('enum')) {
             edgeStyle.stroke = '#8b5cf6'; // Purple for types
         } else if (isCircular) {
             edgeStyle.stroke = '#ef4444'; // Red for circular dependencies
         }
+        const layerViolation = currentColorMode === 'layer' && edge?.data?.layerViolation === true;
+        if (layerViolation) {
+            edgeStyle.stroke = '#dc2626'; // Thick red for dependencies pointing up the layering
+            edgeStyle.strokeWidth = 4;
+            edgeStyle.strokeDasharray = '0';
+        }
+        const labelText = layerViolation ? `${relationship} ↑ layer` : relationship;
         
         return {
             ...edge,
@@ -745,7 +2330,8 @@ const buildEdges = (edges) => edges
             type: 'smoothstep',
             animated: isCircular,
             style: edgeStyle,
-            label: relationship,
+            label: edgeLabelVisible(isCircular || layerViolation) ? labelText : undefined,
+            data: { ...(edge.data || {}), labelText },
             labelStyle: { 
                 fill: '#1f2937', 
                 fontSize: 10, 
@@ -766,10 +2352,14 @@ const buildEdges = (edges) => edges
 // React components
 const e = React.createElement;
 
+// Crate health badges: colour by score, arrow by the change since the last snapshot
+const healthBand = (score) => score >= 75 ? 'good' : score >= 50 ? 'fair' : 'poor';
+const healthArrow = (trend) => typeof trend !== 'number' ? '' : trend > 0.5 ? ' ▲' : trend < -0.5 ? ' ▼' : ' ▶';
+
 const ModuleNode = ({ data }) => {
     const metrics = data?.metrics || {};
     const showMetrics = architectureData?.settings?.showMetrics === true;
-    const accentColor = data?.color || '#4b5563';
+    const accentColor = data?.accentColor || data?.color || '#4b5563';
     const handleStyle = {
         width: 12,
         height: 12,
@@ -780,7 +2370,7 @@ const ModuleNode = ({ data }) => {
         zIndex: 10
     };
 
-    return e('div', { className: 'rf-module-card' },
+    return e('div', { className: data?.ignored ? 'rf-module-card rf-module-card--ignored' : 'rf-module-card', style: { boxShadow: `inset 4px 0 0 ${accentColor}` } },
         e(Handle, { type: 'target', position: Position.Left, style: handleStyle, isConnectable: false }),
         e(Handle, { type: 'source', position: Position.Right, style: handleStyle, isConnectable: false }),
         e('div', { className: 'rf-module-card__header' },
@@ -788,8 +2378,23 @@ const ModuleNode = ({ data }) => {
             e('div', null,
                 e('div', { className: 'rf-module-card__name' }, data?.name || ''),
                 e('div', { className: 'rf-module-card__type' }, data?.moduleType || '')
-            )
+            ),
+            data?.kind === 'crate' && typeof data.healthScore === 'number' ? e('div', {
+                className: `rf-module-card__health rf-module-card__health--${healthBand(data.healthScore)}`,
+                title: `Health ${data.healthScore.toFixed(1)}${typeof data.healthTrend === 'number' ? ` (${data.healthTrend >= 0 ? '+' : ''}${data.healthTrend.toFixed(1)} since the last snapshot)` : ''}`
+            }, `${Math.round(data.healthScore)}${healthArrow(data.healthTrend)}`) : null,
+            data?.pinned ? e('div', { className: 'rf-module-card__pin', title: 'Pinned' }, '📌') : null
         ),
+        data?.entryPoint ? e('div', { className: 'rf-module-card__entry', title: ENTRY_POINT_LABELS[data.entryPoint] }, data.entryPoint === 'Library' ? '📚 Library root' : '▶ Binary') : null,
+        data?.groupName ? e('div', { className: 'rf-module-card__group', style: { background: data.groupColor } }, data.groupName) : null,
+        (data?.namingViolations || []).length ? e('div', { className: 'rf-module-card__naming', title: data.namingViolations.join('\n') }, '⚠ Naming') : null,
+        data?.kind === 'crate' && data.edition ? e('div', { className: 'rf-module-card__toolchain' },
+            [data.version ? `v${data.version}` : null, `edition ${data.edition}`, data.rustVersion ? `MSRV ${data.rustVersion}` : null].filter(Boolean).join(' · ')
+        ) : null,
+        (data?.inconsistencies || []).length ? e('div', { className: 'rf-module-card__naming', title: data.inconsistencies.join('\n') }, '⚠ Toolchain') : null,
+        data?.kind === 'crate' && data.deadCodePercentage >= 0.5 ? e('div', { className: 'rf-module-card__toolchain', title: 'Functions and types no binary, test or public API reaches' }, `${Math.round(data.deadCodePercentage)}% dead code`) : null,
+        data?.dataAccessRole === 'direct' ? e('div', { className: 'rf-module-card__naming', title: `${data.dataAccess.queries} ${data.dataAccess.libraries.join('/')} queries outside a repository module` }, '🗄️ Direct DB') : null,
+        data?.ownership?.organizational_risk ? e('div', { className: 'rf-module-card__naming', title: `${Math.round(data.ownership.top_author_share * 100)}% written by ${data.ownership.top_author}` }, '👤 Bus factor 1') : null,
         showMetrics ? e('div', { className: 'rf-module-card__metrics' },
             e('div', { className: 'rf-metric' },
                 e('div', { className: 'rf-metric__value' }, formatNumber(metrics.lines_of_code)),
@@ -811,20 +2416,835 @@ const ModuleNode = ({ data }) => {
     );
 };
 
+const renderCurationSection = (data, curation) => {
+    const group = groupOf(curation, data.id);
+    if (isReadOnly) {
+        return group ? `<div class="details-section"><h4>Group</h4><span class="chip">${escapeHtml(group.name)}</span></div>` : '';
+    }
+    const pinned = Boolean(curation.pinned[data.id]);
+    const options = curation.groups
+        .map((candidate) => `<option value="${escapeHtml(candidate.id)}"${group && group.id === candidate.id ? ' selected' : ''}>${escapeHtml(candidate.name)}</option>`)
+        .join('');
+    return `
+            <div class="details-section">
+                <h4>Curation</h4>
+                <div class="curation-actions">
+                    <button class="btn btn-small" data-curation-action="toggle-pin" data-node-id="${escapeHtml(data.id)}">${pinned ? '📌 Unpin' : '📌 Pin position'}</button>
+                </div>
+                <label class="curation-label">Group
+                    <select class="curation-select" data-curation-action="set-group" data-node-id="${escapeHtml(data.id)}">
+                        <option value=""${group ? '' : ' selected'}>No group</option>
+                        ${options}
+                        <option value="__new__">+ New group…</option>
+                    </select>
+                </label>
+                ${group ? `<div class="curation-actions">
+                    <button class="btn btn-small" data-curation-action="rename-group" data-group-id="${escapeHtml(group.id)}">Rename group</button>
+                    <button class="btn btn-small" data-curation-action="delete-group" data-group-id="${escapeHtml(group.id)}">Delete group</button>
+                </div>` : ''}
+            </div>`;
+};
+
+const renderModuleTypeSection = (data) => {
+    // External crates have no file a rule could match
+    if (isReadOnly || !moduleTypeOptions.length || !data.filePath) return '';
+    const manual = manualModuleType(data.filePath);
+    const options = moduleTypeOptions
+        .map((option) => `<option value="${escapeHtml(option.key)}"${manual === option.key ? ' selected' : ''}>${escapeHtml(option.label)}</option>`)
+        .join('');
+    return `
+            <div class="details-section">
+                <h4>Module type</h4>
+                <label class="curation-label">Type
+                    <select class="curation-select" data-curation-action="set-type" data-file-path="${escapeHtml(data.filePath)}">
+                        <option value=""${manual ? '' : ' selected'}>Detected${manual ? '' : ` (${escapeHtml(data.moduleType)})`}</option>
+                        ${options}
+                    </select>
+                </label>
+                <p class="details-note">Saved to the config file, so later scans keep it.</p>
+            </div>`;
+};
+
+const renderIgnoreSection = (data) => {
+    if (!data.filePath) return '';
+    if (isReadOnly) return data.ignored ? '<div class="details-section"><h4>Ignored</h4><p class="details-note">Left out of metrics and cycle detection.</p></div>' : '';
+    // Modules matched by a hand-written glob can only be released in the config file
+    const byPattern = data.ignored && !ignoredPaths.includes(data.filePath);
+    return `
+            <div class="details-section">
+                <h4>Ignored</h4>
+                ${byPattern
+                    ? '<p class="details-note">Ignored by a pattern in the config file.</p>'
+                    : `<div class="curation-actions">
+                    <button class="btn btn-small" data-curation-action="toggle-ignore" data-file-path="${escapeHtml(data.filePath)}" data-ignored="${data.ignored ? '1' : '0'}">${data.ignored ? '👁️ Stop ignoring' : '👻 Ignore module'}</button>
+                </div>`}
+                <p class="details-note">Ignored modules, such as generated or vendored code, are left out of metrics and cycle detection.</p>
+            </div>`;
+};
+
+const editorUrlTemplate = architectureData?.settings?.editorUrl || null;
+const projectRoot = architectureData?.settings?.projectRoot || '';
+const MAX_LISTED_FUNCTIONS = 25;
+
+// Wraps `label` in a link opening the file in the configured editor
+const editorLink = (filePath, line, label) => {
+    if (!editorUrlTemplate || !filePath) return escapeHtml(label);
+    const href = editorUrlTemplate
+        .replaceAll('{path}', `${projectRoot}/${filePath}`)
+        .replaceAll('{relative_path}', filePath)
+        .replaceAll('{line}', String(line || 1));
+    return `<a class="editor-link" href="${escapeHtml(href)}" title="Open in editor">${escapeHtml(label)}</a>`;
+};
+
+const renderFunctionList = (data) => {
+    const functions = data.functions || [];
+    if (!functions.length) return '';
+    const listed = functions.slice(0, MAX_LISTED_FUNCTIONS).map((fn) => `<li>${editorLink(data.filePath, fn.line, `${fn.isPublic ? 'pub ' : ''}fn ${fn.name}`)}${fn.line ? ` <span class="details-meta">:${fn.line}</span>` : ''}</li>`).join('');
+    const more = functions.length > MAX_LISTED_FUNCTIONS ? `<li class="empty-state">+${functions.length - MAX_LISTED_FUNCTIONS} more</li>` : '';
+    return `<div class="details-section">
+                <h4>Functions</h4>
+                <ul class="details-list">${listed}${more}</ul>
+            </div>`;
+};
+
+const renderDocumentationLink = (link) => {
+    if (link.external) {
+        return `<li><a href="${escapeHtml(link.target)}" target="_blank" rel="noopener">${escapeHtml(link.title)}</a></li>`;
+    }
+    // Exported pages have no server to read project files from
+    if (isReadOnly) {
+        return `<li>${escapeHtml(link.title)} <span class="details-path">${escapeHtml(link.target)}</span></li>`;
+    }
+    const href = `${apiBase}/api/docs/${link.target.split('/').map(encodeURIComponent).join('/')}`;
+    return `<li><a href="${escapeHtml(href)}" target="_blank" rel="noopener">${escapeHtml(link.title)}</a></li>`;
+};
+
+const DATA_ACCESS_ROLES = {
+    repository: 'Repository: runs queries where they are expected',
+    direct: 'Runs queries directly, bypassing a repository module',
+    via_repository: 'Reaches the database through a module that runs queries',
+    schema: 'Declares tables or row types without running queries',
+};
+
+// "(p95)" after a metric: where the module ranks among the project's modules
+const percentileOf = (data, key) => {
+    const percentile = data.percentiles?.[key];
+    if (typeof percentile !== 'number') return '';
+    return ` <span class="metric-item__percentile" title="Percentile among the project's modules">(p${percentile})</span>`;
+};
+
+const renderModuleDetails = (data, curation) => {
+    const metrics = data.metrics || {};
+    return `
+            <div class="details-heading">
+                <div class="details-icon">${escapeHtml(data.icon)}</div>
+                <div class="details-title">
+                    <h3>${escapeHtml(data.name)}</h3>
+                    <div class="details-meta">${escapeHtml(data.moduleType)} · ${escapeHtml(data.status)}</div>
+                    </div>
+                            </div>
+            <div class="details-section">
+                <h4>Summary</h4>
+                <p class="details-path">${editorLink(data.filePath, 1, data.filePath)}</p>
+                ${data.modulePath ? `<p class="details-meta">crate::${escapeHtml(data.modulePath)}</p>` : ''}
+                            </div>
+            ${data.entryPoint ? `<div class="details-section">
+                <h4>Entry point</h4>
+                <p class="details-meta">${escapeHtml(ENTRY_POINT_LABELS[data.entryPoint] || data.entryPoint)}</p>
+                <button class="btn btn-small" data-trace-from="${escapeHtml(data.id)}">🔦 Trace from here</button>
+            </div>` : ''}
+            ${(data.namingViolations || []).length ? `<div class="details-section">
+                <h4>Naming</h4>
+                <ul class="details-list">${data.namingViolations.map((item) => `<li>${escapeHtml(item)}</li>`).join('')}</ul>
+            </div>` : ''}
+            ${(data.documentation || []).length ? `<div class="details-section">
+                <h4>Documentation</h4>
+                <ul class="details-list">${data.documentation.map(renderDocumentationLink).join('')}</ul>
+            </div>` : ''}
+            ${Object.keys(data.annotations || {}).length ? `<div class="details-section">
+                <h4>Annotations</h4>
+                <ul class="details-list">${Object.entries(data.annotations).map(([key, value]) => `<li><span class="chip">${escapeHtml(key)}</span> ${escapeHtml(value)}</li>`).join('')}</ul>
+            </div>` : ''}
+            ${(data.routes || []).length ? `<div class="details-section">
+                <h4>HTTP endpoints</h4>
+                <ul class="details-list">${data.routes.map((route) => `<li><span class="chip">${escapeHtml(route.method)}</span> ${editorLink(data.filePath, route.line, route.path)}${route.handler ? ` <span class="details-path">${escapeHtml(route.handler)}</span>` : ''}</li>`).join('')}</ul>
+            </div>` : ''}
+            ${data.dataAccess || data.dataAccessRole ? `<div class="details-section">
+                <h4>Data access</h4>
+                <p class="details-meta">${escapeHtml(DATA_ACCESS_ROLES[data.dataAccessRole] || '')}</p>
+                ${data.dataAccess ? `<div class="metric-grid">
+                    <div class="metric-item"><span class="metric-item__label">Crates</span><span class="metric-item__value">${escapeHtml(data.dataAccess.libraries.join(', '))}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">Queries</span><span class="metric-item__value">${formatNumber(data.dataAccess.queries)}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">Schema refs</span><span class="metric-item__value">${formatNumber(data.dataAccess.schema_references)}</span></div>
+                </div>` : ''}
+            </div>` : ''}
+            ${data.clippyLints ? `<div class="details-section">
+                <h4>Clippy</h4>
+                <p class="details-meta">${formatNumber(data.lintDensity)} lints per 100 lines</p>
+                ${Object.keys(data.clippyLints).length ? `<div class="metric-grid">
+                    ${Object.entries(data.clippyLints).map(([category, count]) => `<div class="metric-item"><span class="metric-item__label">${escapeHtml(category)}</span><span class="metric-item__value">${formatNumber(count)}</span></div>`).join('')}
+                </div>` : ''}
+            </div>` : ''}
+            ${data.robustness ? `<div class="details-section">
+                <h4>Robustness</h4>
+                <p class="details-meta">Score ${formatNumber(data.robustness.score)} / 100${data.robustness.handles_input ? ' · handles external input' : ''}</p>
+                <div class="metric-grid">
+                    <div class="metric-item"><span class="metric-item__label">unsafe</span><span class="metric-item__value">${formatNumber(data.robustness.unsafe_blocks)}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">unwrap()</span><span class="metric-item__value">${formatNumber(data.robustness.unwraps)}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">expect()</span><span class="metric-item__value">${formatNumber(data.robustness.expects)}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">Panics</span><span class="metric-item__value">${formatNumber(data.robustness.panics)}</span></div>
+                </div>
+            </div>` : ''}
+            ${data.ownership ? `<div class="details-section">
+                <h4>Ownership</h4>
+                <p class="details-meta">${formatNumber(data.ownership.top_author_share * 100)}% of lines by ${escapeHtml(data.ownership.top_author)} · ${formatNumber(data.ownership.authors)} author${data.ownership.authors === 1 ? '' : 's'} · bus factor ${formatNumber(data.ownership.bus_factor)}</p>
+                ${data.ownership.organizational_risk ? '<p class="details-note">Organizational risk: one person wrote most of this complex module.</p>' : ''}
+            </div>` : ''}
+            ${data.metrics?.commit_count ? `<div class="details-section">
+                <h4>History</h4>
+                <p class="details-meta">${formatNumber(data.metrics.commit_count)} commit${data.metrics.commit_count === 1 ? '' : 's'}${data.metrics.last_author ? ` · last changed by ${escapeHtml(data.metrics.last_author)}` : ''}</p>
+                <div class="metric-grid">
+                    <div class="metric-item"><span class="metric-item__label">Recent churn</span><span class="metric-item__value">${formatNumber(data.metrics.recent_churn)} lines${percentileOf(data, 'recent_churn')}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">Hotspot</span><span class="metric-item__value">${formatNumber(data.metrics.hotspot_score)}</span></div>
+                </div>
+            </div>` : ''}
+            ${(data.deadCode?.unreachable_items || []).length ? `<div class="details-section">
+                <h4>Unreachable items</h4>
+                <p class="details-note">${formatNumber(data.deadCode.unreachable_items.length)} of ${formatNumber(data.deadCode.item_count)} items are not reached from any binary, test or public API.</p>
+                <div class="chip-row">${data.deadCode.unreachable_items.map((item) => `<span class="chip">${escapeHtml(item)}</span>`).join('')}</div>
+            </div>` : ''}
+            ${(data.impls || []).length ? `<div class="details-section">
+                <h4>Trait implementations</h4>
+                <ul class="details-list">${data.impls.map((item) => `<li>${editorLink(data.filePath, item.line, `impl ${item.trait_path} for ${item.self_type}`)}${item.trait_module && nodeLookup.get(item.trait_module) ? ` <span class="details-path">${escapeHtml(nodeLookup.get(item.trait_module).filePath)}</span>` : ''}</li>`).join('')}</ul>
+            </div>` : ''}
+            ${(data.apiLeaks || []).length ? `<div class="details-section">
+                <h4>API leaks</h4>
+                <p class="details-note">These public signatures expose types of other workspace crates, so users of this crate depend on those crates too.</p>
+                <ul class="details-list">${data.apiLeaks.map((leak) => `<li>${editorLink(data.filePath, leak.line, leak.signature || leak.item)} <span class="details-path">${escapeHtml(leak.type_path)}</span></li>`).join('')}</ul>
+            </div>` : ''}
+            ${data.splitSuggestion ? `<div class="details-section">
+                <h4>Split suggestion</h4>
+                <p class="details-note">${data.splitSuggestion.cross_references
+                    ? `These items are linked to the rest of the module by only ${data.splitSuggestion.cross_references} reference${data.splitSuggestion.cross_references === 1 ? '' : 's'}; they could move to a module of their own.`
+                    : 'These items never reference the rest of the module; they could move to a module of their own.'}</p>
+                <div class="chip-row">${data.splitSuggestion.moved.map((item) => `<span class="chip">${escapeHtml(item)}</span>`).join('')}</div>
+            </div>` : ''}
+            <div class="details-section">
+                <h4>Metrics</h4>
+                <div class="metric-grid">
+                    <div class="metric-item"><span class="metric-item__label">Lines</span><span class="metric-item__value">${formatNumber(metrics.lines_of_code)}${percentileOf(data, 'lines_of_code')}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">Functions</span><span class="metric-item__value">${formatNumber(metrics.function_count)}${percentileOf(data, 'function_count')}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">Complexity</span><span class="metric-item__value">${formatNumber(metrics.complexity_score,1)}${percentileOf(data, 'complexity_score')}</span></div>
+                    <div class="metric-item"><span class="metric-item__label">Deps</span><span class="metric-item__value">${formatNumber(metrics.dependency_count)}${percentileOf(data, 'dependency_count')}</span></div>
+                            </div>
+                            </div>
+            <div class="details-section">
+                <h4>Dependencies</h4>
+                <div class="chip-row">${(data.dependencies || []).map((item) => `<span class="chip">${escapeHtml(item)}</span>`).join('') || '<span class="empty-state">None</span>'}</div>
+                        </div>
+            <div class="details-section">
+                <h4>Dependents</h4>
+                <div class="chip-row">${(data.dependents || []).map((item) => `<span class="chip">${escapeHtml(nodeLookup.get(item)?.name || item)}</span>`).join('') || '<span class="empty-state">None</span>'}</div>
+                    </div>
+            ${renderFunctionList(data)}
+            ${renderCurationSection(data, curation)}
+            ${renderModuleTypeSection(data)}
+            ${renderIgnoreSection(data)}
+        `;
+};
+
+const showModuleDetails = (nodeId, curation) => {
+    const detailsPanel = document.getElementById('details-panel');
+    const detailsContent = document.getElementById('details-content');
+    if (!detailsPanel || !detailsContent) return;
+
+    const data = nodeLookup.get(nodeId);
+    if (!data) return;
+
+    detailsPanel.classList.add('open');
+    detailsContent.innerHTML = renderModuleDetails(data, curation);
+    setUrlParam('node', nodeId);
+};
+
+const closeDetails = () => {
+    const detailsPanel = document.getElementById('details-panel');
+    const detailsContent = document.getElementById('details-content');
+    if (detailsPanel) detailsPanel.classList.remove('open');
+    if (detailsContent) detailsContent.innerHTML = '<p class="details-placeholder">Click on a module to see details</p>';
+    setUrlParam('node', null);
+};
+
+const renderEdgeDetails = (data, sourceName, targetName) => {
+    const locations = data.locations || [];
+    return `
+            <div class="details-heading">
+                <div class="details-icon">🔗</div>
+                <div class="details-title">
+                    <h3>${escapeHtml(sourceName)} → ${escapeHtml(targetName)}</h3>
+                    <div class="details-meta">${escapeHtml(data.relationship)}${data.isCircular ? ' · circular' : ''}${data.layerViolation ? ' · upward dependency' : ''}</div>
+                </div>
+            </div>
+            <div class="details-section">
+                <h4>Source locations</h4>
+                ${locations.length ? `<ul class="details-list">${locations.map((location) => `<li class="source-location">
+                    <span class="details-path">${editorLink(location.file_path, location.line, `${location.file_path}:${location.line}`)}</span>
+                    <code>${escapeHtml(location.snippet)}</code>
+                </li>`).join('')}</ul>` : '<span class="empty-state">No recorded locations</span>'}
+            </div>
+            ${(data.suggestions || []).length ? `<div class="details-section">
+                <h4>How to remove this dependency</h4>
+                <ul class="details-list suggestion-list">${data.suggestions.map((item) => `<li>${escapeHtml(item)}</li>`).join('')}</ul>
+            </div>` : ''}
+        `;
+};
+
+const showEdgeDetails = (edge) => {
+    const detailsPanel = document.getElementById('details-panel');
+    const detailsContent = document.getElementById('details-content');
+    if (!detailsPanel || !detailsContent || !edge?.data?.relationship) return;
+
+    const sourceName = nodeLookup.get(edge.source)?.name || edge.source;
+    const targetName = nodeLookup.get(edge.target)?.name || edge.target;
+    detailsPanel.classList.add('open');
+    detailsContent.innerHTML = renderEdgeDetails(edge.data, sourceName, targetName);
+    setUrlParam('node', null);
+};
+
+// `[groups]` groups from the config, framing the nodes of the current view they contain
+const groupContainerPadding = 28;
+const GroupContainer = ({ data }) => e('div', {
+    className: 'group-container',
+    style: { borderColor: data.color, background: `${data.color}12` }
+}, data.directory
+    ? e('button', {
+        className: 'group-container__label group-container__collapse',
+        style: { color: data.color },
+        title: 'Collapse this directory',
+        onClick: () => window.dispatchEvent(new CustomEvent('directoryCollapse', { detail: data.directory }))
+    }, `▾ ${data.name}`)
+    : e('span', { className: 'group-container__label', style: { color: data.color } }, data.name));
+const frameNode = (id, members, data) => {
+    const left = Math.min(...members.map((node) => node.position.x));
+    const top = Math.min(...members.map((node) => node.position.y));
+    const right = Math.max(...members.map((node) => node.position.x + (node.width || nodeWidthFor(node.data))));
+    const bottom = Math.max(...members.map((node) => node.position.y + (node.height || 120)));
+    return {
+        id,
+        type: 'groupContainer',
+        // Room above the members for the label
+        position: { x: left - groupContainerPadding, y: top - groupContainerPadding * 2 },
+        data: { kind: 'group', ...data },
+        style: {
+            width: right - left + groupContainerPadding * 2,
+            height: bottom - top + groupContainerPadding * 3,
+            pointerEvents: 'none'
+        },
+        draggable: false,
+        selectable: false,
+        connectable: false,
+        focusable: false,
+        zIndex: -1
+    };
+};
+const groupContainers = (nodes) => {
+    const names = [...new Set(nodes.flatMap((node) => node.data?.groups || []))].sort();
+    return names.map((name, index) => frameNode(
+        `group-container-${name}`,
+        nodes.filter((node) => node.data.groups?.includes(name)),
+        { name, color: groupPalette[index % groupPalette.length] }
+    ));
+};
+
+// Directory tree of the modules; a collapsed directory is drawn as one card standing in for everything below it
+const moduleGroupsData = architectureData.moduleGroups || [];
+const moduleGroupLookup = new Map(moduleGroupsData.map((group) => [group.id, group]));
+const directoryColor = '#64748b';
+// Directories of the tree above a file, outermost first
+const directoriesOf = (filePath) => {
+    const parts = (filePath || '').split('/').slice(0, -1);
+    return parts.map((_, index) => parts.slice(0, index + 1).join('/')).filter((path) => moduleGroupLookup.has(path));
+};
+// Subdirectories to collapse when opening a directory, going through directories that only hold one
+const subdirectoriesToCollapse = (groupId) => {
+    let group = moduleGroupLookup.get(groupId);
+    while (group && group.children.length === 1 && !group.modules.length) group = moduleGroupLookup.get(group.children[0]);
+    return group ? group.children : [];
+};
+const topLevelDirectories = () => {
+    const roots = moduleGroupsData.filter((group) => !group.parent).map((group) => group.id);
+    return new Set(roots.length === 1 ? subdirectoriesToCollapse(roots[0]) : roots);
+};
+const initialDirectories = () => {
+    const threshold = architectureData?.settings?.collapseDirectoriesAbove ?? 150;
+    const large = threshold > 0 && nodesData.length > threshold;
+    return { collapsed: large ? topLevelDirectories() : new Set(), opened: new Set() };
+};
+const openDirectory = (directories, groupId) => {
+    const collapsed = new Set(directories.collapsed);
+    collapsed.delete(groupId);
+    subdirectoriesToCollapse(groupId).forEach((child) => collapsed.add(child));
+    return { collapsed, opened: new Set(directories.opened).add(groupId) };
+};
+const collapseDirectory = (directories, groupId) => {
+    const below = (path) => path === groupId || path.startsWith(`${groupId}/`);
+    return {
+        collapsed: new Set([...[...directories.collapsed].filter((path) => !below(path)), groupId]),
+        opened: new Set([...directories.opened].filter((path) => !below(path)))
+    };
+};
+// Open the collapsed directories above each module, one level at a time, until it shows
+const revealModules = (directories, moduleIds) => moduleIds.reduce((current, moduleId) =>
+    directoriesOf(nodeLookup.get(moduleId)?.filePath).reduce((next, path) =>
+        next.collapsed.has(path) ? openDirectory(next, path) : next, current), directories);
+
+const directoryNodeData = (groupId, members) => {
+    const group = moduleGroupLookup.get(groupId);
+    const sum = (metric) => members.reduce((total, member) => total + (Number(member.metrics?.[metric]) || 0), 0);
+    const lines = sum('lines_of_code');
+    return {
+        id: `dir:${groupId}`,
+        kind: 'directory',
+        directory: groupId,
+        moduleIds: members.map((member) => member.id),
+        name: `${group.name}/`,
+        icon: '📁',
+        moduleType: `Directory · ${members.length} module${members.length === 1 ? '' : 's'}`,
+        color: directoryColor,
+        status: members.some((member) => member.status === 'Error') ? 'Error' : 'Active',
+        groups: [...new Set(members.flatMap((member) => member.groups || []))],
+        order: Math.min(...members.map((member) => member.order)),
+        hierarchyLevel: Math.min(...members.map((member) => member.hierarchyLevel)),
+        sizeFactor: Math.min(1, lines / 20000),
+        dependencies: [],
+        dependents: [],
+        metrics: {
+            lines_of_code: lines,
+            function_count: sum('function_count'),
+            complexity_score: sum('complexity_score') / members.length,
+            dependency_count: sum('dependency_count')
+        }
+    };
+};
+// Modules inside a collapsed directory become its card, and their edges its edges
+const foldDirectories = (nodes, collapsed) => {
+    const representative = new Map();
+    if (!collapsed.size) return { nodes, representative };
+    const members = new Map();
+    const shown = [];
+    nodes.forEach((node) => {
+        const directory = directoriesOf(node.filePath).find((path) => collapsed.has(path));
+        if (!directory) {
+            shown.push(node);
+            return;
+        }
+        representative.set(node.id, `dir:${directory}`);
+        if (!members.has(directory)) members.set(directory, []);
+        members.get(directory).push(node);
+    });
+    members.forEach((group, directory) => shown.push(directoryNodeData(directory, group)));
+    return { nodes: shown, representative };
+};
+const foldEdges = (edges, representative) => {
+    if (!representative.size) return edges;
+    const kept = [];
+    const folded = new Map();
+    edges.forEach((edge) => {
+        const from = edge.source ?? edge.from;
+        const to = edge.target ?? edge.to;
+        const source = representative.get(from) || from;
+        const target = representative.get(to) || to;
+        if (source === target) return;
+        if (source === from && target === to) {
+            kept.push(edge);
+            return;
+        }
+        const key = `${source}->${target}`;
+        if (!folded.has(key)) folded.set(key, { id: `dir-edge-${key}`, source, target, is_circular: false, data: { edgeCount: 0, color: '#94a3b8' } });
+        const entry = folded.get(key);
+        entry.data.edgeCount += 1;
+        entry.is_circular = entry.is_circular || Boolean(edge.data?.isCircular);
+    });
+    return kept.concat([...folded.values()].map((edge) => ({
+        ...edge,
+        label: `${edge.data.edgeCount} module edge${edge.data.edgeCount === 1 ? '' : 's'}`,
+        strength: Math.min(4, Math.log2(1 + edge.data.edgeCount)) / 2,
+        data: { ...edge.data, isCircular: edge.is_circular }
+    })));
+};
+
+const Breadcrumb = ({ viewMode, componentFilter, crateFilter, onSelectComponents, onSelectCrates, onSelectAll }) => {
+    if (viewMode === 'features') {
+        return e('nav', { className: 'breadcrumb', 'aria-label': 'Graph level' },
+            e('span', { key: 'features', className: 'breadcrumb__current' }, '🚩 Cargo features'),
+            e('button', { key: 'all', className: 'breadcrumb__link breadcrumb__link--aside', onClick: onSelectAll }, 'Show all modules')
+        );
+    }
+    const hasComponents = componentNodesData.length >= 2;
+    const hasCrates = crateNodesData.length >= 2;
+    if (!hasComponents && !hasCrates) return null;
+    const link = (key, label, onClick) => e('button', { key, className: 'breadcrumb__link', onClick }, label);
+    const current = (key, label) => e('span', { key, className: 'breadcrumb__current' }, label);
+    const separator = (key) => e('span', { key, className: 'breadcrumb__separator' }, '›');
+    const crumbs = [];
+    if (viewMode === 'components') {
+        crumbs.push(current('components', '🧩 All components'));
+    } else {
+        if (hasComponents) crumbs.push(link('components', '🧩 All components', onSelectComponents), separator('sep-components'));
+        // Within a component the crate level lists only the component's crates
+        const cratesLabel = componentFilter ? `🧩 ${componentFilter}` : '📦 All crates';
+        if (viewMode === 'crates') {
+            crumbs.push(current('crates', cratesLabel));
+        } else {
+            if (hasCrates) crumbs.push(link('crates', cratesLabel, onSelectCrates), separator('sep-crates'));
+            const scope = crateFilter || (!hasCrates && componentFilter);
+            crumbs.push(current('modules', scope || 'All modules'));
+            if (crateFilter || componentFilter) {
+                crumbs.push(e('button', { key: 'all', className: 'breadcrumb__link breadcrumb__link--aside', onClick: onSelectAll }, 'Show all modules'));
+            }
+        }
+    }
+    return e('nav', { className: 'breadcrumb', 'aria-label': 'Graph level' }, ...crumbs);
+};
+
+const syncViewButtons = (viewMode) => {
+    viewModes.forEach((mode) => {
+        const button = document.getElementById(`view-${mode}`);
+        if (button) button.classList.toggle('active', mode === viewMode);
+    });
+};
+
 const FlowApp = () => {
     const [layout, setLayout] = React.useState(layouts[currentLayoutIndex] || 'grid');
-    const [reorderType, setReorderType] = React.useState('hierarchical');
+    const [reorderType, setReorderType] = React.useState(() => urlChoice('order', reorderOptions, 'hierarchical'));
+    const [viewMode, setViewMode] = React.useState(() => urlChoice('view', viewModes.filter((mode) => availableViews[mode]), 'modules'));
+    const [componentFilter, setComponentFilter] = React.useState(() => {
+        const componentName = urlState.get('component');
+        return componentNodesData.some((node) => node.name === componentName) ? componentName : null;
+    });
+    const [crateFilter, setCrateFilter] = React.useState(() => {
+        const crateName = urlState.get('crate');
+        return nodesData.some((node) => node.crateName === crateName) ? crateName : null;
+    });
+    const [showTestTargets, setShowTestTargets] = React.useState(() =>
+        urlState.has('tests') ? urlState.get('tests') === '1' : defaultShowTestTargets
+    );
+    const [showIgnored, setShowIgnored] = React.useState(() => urlState.get('ignored') === '1');
+    const [traitImplsOnly, setTraitImplsOnly] = React.useState(() => hasTraitImpls && urlState.get('impls') === '1');
+    const [traceRootId, setTraceRootId] = React.useState(() => {
+        const traceId = urlState.get('trace');
+        return nodeLookup.get(traceId)?.entryPoint ? traceId : null;
+    });
+    const [cycleFocusIds, setCycleFocusIds] = React.useState(null);
+    const [variantName, setVariantName] = React.useState(() =>
+        variantsData.some((variant) => variant.name === urlState.get('variant')) ? urlState.get('variant') : null
+    );
+    const [curation, setCuration] = React.useState(initialCuration);
+    const curationRef = React.useRef(initialCuration);
+    const selectedIdRef = React.useRef(null);
+    const [dataVersion, setDataVersion] = React.useState(0);
+    const [colorMode, setColorMode] = React.useState(currentColorMode);
+    const patchPendingRef = React.useRef(false);
+    const [searchIds, setSearchIds] = React.useState(null);
+    const [directories, setDirectories] = React.useState(initialDirectories);
+    const searchFitPendingRef = React.useRef(false);
+    const { getNode, fitView, setCenter } = useReactFlow();
     const [nodes, setNodes] = React.useState(() =>
         nodesData.length ? buildNodes(layouts[currentLayoutIndex] || 'grid', nodesData, 'hierarchical') : []
     );
     const [edges, setEdges] = React.useState(() =>
         edgesData.length ? buildEdges(edgesData) : []
     );
-    const nodeTypes = React.useMemo(() => ({ module: ModuleNode }), []);
+    const nodeTypes = React.useMemo(() => ({ module: ModuleNode, groupContainer: GroupContainer }), []);
+
+    // The component graph, the crate graph optionally narrowed to one component, the
+    // feature graph with the modules it gates, or the module graph optionally narrowed
+    // to one component and crate
+    const filteredNodes = React.useMemo(() => {
+        if (viewMode === 'components') return componentNodesData;
+        if (viewMode === 'crates') {
+            return componentFilter ? crateNodesData.filter((node) => node.component === componentFilter) : crateNodesData;
+        }
+        if (viewMode === 'features') {
+            return featureNodesData.concat(nodesData.filter((node) => featureModuleIds.has(node.id)));
+        }
+        const excluded = new Set(variantsData.find((variant) => variant.name === variantName)?.excluded || []);
+        return nodesData.filter((node) =>
+            (!componentFilter || node.component === componentFilter) &&
+            (!crateFilter || node.crateName === crateFilter) &&
+            (showTestTargets || !node.testTarget) &&
+            (showIgnored || !node.ignored) &&
+            !excluded.has(node.id)
+        );
+    }, [viewMode, componentFilter, crateFilter, showTestTargets, showIgnored, variantName, dataVersion]);
+    const folding = React.useMemo(
+        () => foldDirectories(filteredNodes, viewMode === 'modules' ? directories.collapsed : new Set()),
+        [filteredNodes, viewMode, directories]
+    );
+    const visibleNodes = folding.nodes;
+    const visibleEdges = React.useMemo(() => {
+        if (viewMode === 'components') return componentEdgesData;
+        if (viewMode === 'features') return featureEdgesData;
+        const moduleEdges = traitImplsOnly ? edgesData.filter(isTraitImpl) : edgesData;
+        const allEdges = viewMode === 'crates' ? crateEdgesData : moduleEdges;
+        const allNodes = viewMode === 'crates' ? crateNodesData : nodesData;
+        const visibleIds = new Set(filteredNodes.map((node) => node.id));
+        const edges = filteredNodes.length === allNodes.length
+            ? allEdges
+            : allEdges.filter((edge) => visibleIds.has(edge.source ?? edge.from) && visibleIds.has(edge.target ?? edge.to));
+        return foldEdges(edges, folding.representative);
+    }, [viewMode, filteredNodes, folding, traitImplsOnly, dataVersion]);
+
+    React.useEffect(() => {
+        if (patchPendingRef.current) {
+            patchPendingRef.current = false;
+            setNodes((current) => mergeNodes(current, visibleNodes, curationRef.current));
+            return;
+        }
+        setNodes(visibleNodes.length ? buildNodes(layout, visibleNodes, reorderType, curationRef.current) : []);
+    }, [layout, reorderType, visibleNodes]);
+
+    // Live patches update the data in place; the zoom, selection and node positions stay
+    React.useEffect(() => {
+        const patchHandler = (event) => {
+            patchPendingRef.current = true;
+            setDataVersion((version) => version + 1);
+            const selectedId = selectedIdRef.current;
+            const detailsPanel = document.getElementById('details-panel');
+            if (selectedId && nodeLookup.has(selectedId) && detailsPanel && detailsPanel.classList.contains('open')) {
+                showModuleDetails(selectedId, curationRef.current);
+            }
+        };
+        window.addEventListener('architecturePatch', patchHandler);
+        return () => window.removeEventListener('architecturePatch', patchHandler);
+    }, []);
+
+    // Mark or clear the modules with build errors in place
+    React.useEffect(() => {
+        const buildHandler = () => {
+            setNodes((current) => current.map((node) => ({ ...node, data: decorateNodeData(node.data, curationRef.current) })));
+        };
+        window.addEventListener('buildStatusChange', buildHandler);
+        return () => window.removeEventListener('buildStatusChange', buildHandler);
+    }, []);
+
+    const updateCuration = React.useCallback((update) => {
+        const next = update(curationRef.current);
+        curationRef.current = next;
+        setCuration(next);
+        saveCuration(next);
+    }, []);
+
+    // Reflect curation changes on the cards and in the open details panel without re-running the layout
+    React.useEffect(() => {
+        setNodes((current) => current.map((node) => ({ ...node, data: decorateNodeData(node.data, curation) })));
+        const detailsPanel = document.getElementById('details-panel');
+        if (selectedIdRef.current && detailsPanel && detailsPanel.classList.contains('open')) {
+            showModuleDetails(selectedIdRef.current, curation);
+        }
+    }, [curation]);
+
+    // Curation controls live in the details panel markup, so listen by delegation
+    React.useEffect(() => {
+        const handler = (event) => {
+            const control = event.target?.closest ? event.target.closest('[data-curation-action]') : null;
+            if (!control) return;
+            const action = control.dataset.curationAction;
+            const onChange = action === 'set-group' || action === 'set-type';
+            if (onChange !== (event.type === 'change')) return;
+            const nodeId = control.dataset.nodeId;
+            const groupId = control.dataset.groupId;
+
+            if (action === 'toggle-pin') {
+                const node = getNode(nodeId);
+                updateCuration((current) => {
+                    const pinned = { ...current.pinned };
+                    if (pinned[nodeId]) {
+                        delete pinned[nodeId];
+                    } else if (node) {
+                        pinned[nodeId] = { x: node.position.x, y: node.position.y };
+                    }
+                    return { ...current, pinned };
+                });
+            } else if (action === 'set-group') {
+                const value = control.value;
+                if (value === '__new__') {
+                    const name = (window.prompt('Name of the new group', '') || '').trim();
+                    if (!name) {
+                        showModuleDetails(nodeId, curationRef.current);
+                        return;
+                    }
+                    updateCuration((current) => ({
+                        ...current,
+                        groups: [...withoutMember(current.groups, nodeId), {
+                            id: `group-${Date.now().toString(36)}`,
+                            name,
+                            color: groupPalette[current.groups.length % groupPalette.length],
+                            members: [nodeId]
+                        }]
+                    }));
+                } else {
+                    updateCuration((current) => ({
+                        ...current,
+                        groups: withoutMember(current.groups, nodeId).map((group) =>
+                            group.id === value ? { ...group, members: [...group.members, nodeId] } : group
+                        )
+                    }));
+                }
+            } else if (action === 'set-type') {
+                saveModuleType(control.dataset.filePath, control.value);
+            } else if (action === 'toggle-ignore') {
+                saveIgnored(control.dataset.filePath, control.dataset.ignored !== '1');
+            } else if (action === 'rename-group') {
+                const group = curationRef.current.groups.find((candidate) => candidate.id === groupId);
+                const name = (window.prompt('Group name', group ? group.name : '') || '').trim();
+                if (!name) return;
+                updateCuration((current) => ({
+                    ...current,
+                    groups: current.groups.map((candidate) => candidate.id === groupId ? { ...candidate, name } : candidate)
+                }));
+            } else if (action === 'delete-group') {
+                if (!window.confirm('Delete this group? Its modules are kept.')) return;
+                updateCuration((current) => ({
+                    ...current,
+                    groups: current.groups.filter((candidate) => candidate.id !== groupId)
+                }));
+            }
+        };
+        document.addEventListener('click', handler);
+        document.addEventListener('change', handler);
+        return () => {
+            document.removeEventListener('click', handler);
+            document.removeEventListener('change', handler);
+        };
+    }, [getNode, updateCuration]);
+
+    React.useEffect(() => {
+        setEdges(visibleEdges.length ? buildEdges(visibleEdges) : []);
+    }, [visibleEdges]);
+
+    React.useEffect(() => {
+        setUrlParam('layout', layout, defaultLayout);
+        setUrlParam('order', reorderType, 'hierarchical');
+        setUrlParam('view', viewMode, 'modules');
+        setUrlParam('component', componentFilter);
+        setUrlParam('crate', crateFilter);
+        setUrlParam('tests', showTestTargets ? '1' : '0', defaultShowTestTargets ? '1' : '0');
+        setUrlParam('ignored', showIgnored ? '1' : null);
+        setUrlParam('impls', traitImplsOnly ? '1' : null);
+        setUrlParam('color', colorMode, defaultColorMode);
+        setUrlParam('trace', traceRootId);
+        setUrlParam('variant', variantName);
+    }, [layout, reorderType, viewMode, componentFilter, crateFilter, showTestTargets, showIgnored, traitImplsOnly, colorMode, traceRootId, variantName]);
+
+    // Tracing works on the module graph; picking an entry point switches to it
+    React.useEffect(() => {
+        const startTrace = (nodeId) => {
+            setTraceRootId(nodeId || null);
+            setCycleFocusIds(null);
+            if (nodeId) setViewMode('modules');
+        };
+        const traceHandler = (event) => startTrace(event?.detail);
+        const clickHandler = (event) => {
+            const control = event.target?.closest ? event.target.closest('[data-trace-from]') : null;
+            if (control) startTrace(control.dataset.traceFrom);
+        };
+        window.addEventListener('traceChange', traceHandler);
+        document.addEventListener('click', clickHandler);
+        return () => {
+            window.removeEventListener('traceChange', traceHandler);
+            document.removeEventListener('click', clickHandler);
+        };
+    }, []);
+
+    React.useEffect(() => {
+        const select = document.getElementById('trace-entry-point');
+        if (select) select.value = traceRootId || '';
+    }, [traceRootId]);
+
+    // Switching the variant hides the modules its cfg leaves out and shows its totals
+    React.useEffect(() => {
+        const variantHandler = (event) => setVariantName(event.detail || null);
+        window.addEventListener('variantChange', variantHandler);
+        return () => window.removeEventListener('variantChange', variantHandler);
+    }, []);
+
+    React.useEffect(() => {
+        const select = document.getElementById('variant-select');
+        if (select) select.value = variantName || '';
+        const summary = document.getElementById('variant-summary');
+        const variant = variantsData.find((candidate) => candidate.name === variantName);
+        if (summary) {
+            summary.textContent = variant
+                ? `${variant.total_modules} modules · ${variant.total_lines} lines · ${variant.circular_dependencies} cycles · ${variant.excluded.length} left out`
+                : '';
+        }
+    }, [variantName, dataVersion]);
+
+    // Reopen the module selected in the link
+    React.useEffect(() => {
+        const selectedId = urlState.get('node');
+        if (selectedId && nodeLookup.has(selectedId)) {
+            selectedIdRef.current = selectedId;
+            showModuleDetails(selectedId, curationRef.current);
+        }
+    }, []);
+
+    React.useEffect(() => {
+        syncViewButtons(viewMode);
+    }, [viewMode]);
+
+    React.useEffect(() => {
+        const button = document.getElementById('toggle-test-targets');
+        if (button) button.classList.toggle('active', showTestTargets);
+    }, [showTestTargets]);
+
+    React.useEffect(() => {
+        const button = document.getElementById('toggle-ignored');
+        if (button) button.classList.toggle('active', showIgnored);
+    }, [showIgnored]);
+
+    React.useEffect(() => {
+        const button = document.getElementById('toggle-trait-impls');
+        if (button) button.classList.toggle('active', traitImplsOnly);
+    }, [traitImplsOnly]);
 
     React.useEffect(() => {
-        setNodes(nodesData.length ? buildNodes(layout, nodesData, reorderType) : []);
-    }, [layout, reorderType]);
+        const button = document.getElementById('toggle-directories');
+        if (button) button.classList.toggle('active', directories.collapsed.size > 0);
+    }, [directories]);
+
+    // Recolor in place; positions and selection stay as they are
+    React.useEffect(() => {
+        const button = document.getElementById('toggle-color-mode');
+        if (button) button.classList.toggle('active', colorMode === 'layer');
+        const robustnessButton = document.getElementById('toggle-robustness');
+        if (robustnessButton) robustnessButton.classList.toggle('active', colorMode === 'robustness');
+        const dataAccessButton = document.getElementById('toggle-data-access');
+        if (dataAccessButton) dataAccessButton.classList.toggle('active', colorMode === 'dataAccess');
+        const lintsButton = document.getElementById('toggle-lints');
+        if (lintsButton) lintsButton.classList.toggle('active', colorMode === 'lints');
+        if (currentColorMode === colorMode) return;
+        currentColorMode = colorMode;
+        setNodes((current) => current.map((node) => ({ ...node, data: decorateNodeData(node.data, curationRef.current) })));
+        setEdges(visibleEdges.length ? buildEdges(visibleEdges) : []);
+    }, [colorMode]);
+
+    const showComponents = React.useCallback(() => {
+        setComponentFilter(null);
+        setCrateFilter(null);
+        setViewMode('components');
+    }, []);
+
+    const showCrates = React.useCallback(() => {
+        setCrateFilter(null);
+        setViewMode('crates');
+    }, []);
+
+    const showAllModules = React.useCallback(() => {
+        setComponentFilter(null);
+        setCrateFilter(null);
+        setViewMode('modules');
+    }, []);
 
     React.useEffect(() => {
         const layoutHandler = (event) => {
@@ -833,6 +3253,14 @@ const FlowApp = () => {
                 setLayout(nextLayout);
             }
         };
+        const viewModeHandler = (event) => {
+            const nextMode = (event?.detail || '').toString().toLowerCase();
+            if (nextMode && viewModes.includes(nextMode) && availableViews[nextMode]) {
+                setComponentFilter(null);
+                setCrateFilter(null);
+                setViewMode(nextMode);
+            }
+        };
         const reorderHandler = (event) => {
             const nextReorder = (event?.detail || '').toString().toLowerCase();
             if (nextReorder && reorderOptions.includes(nextReorder)) {
@@ -841,59 +3269,151 @@ const FlowApp = () => {
         };
         window.addEventListener('layoutChange', layoutHandler);
         window.addEventListener('reorderChange', reorderHandler);
+        window.addEventListener('viewModeChange', viewModeHandler);
+        const testTargetsHandler = () => setShowTestTargets((shown) => !shown);
+        window.addEventListener('testTargetsToggle', testTargetsHandler);
+        const ignoredHandler = () => setShowIgnored((shown) => !shown);
+        window.addEventListener('ignoredToggle', ignoredHandler);
+        const traitImplsHandler = () => setTraitImplsOnly((only) => !only);
+        window.addEventListener('traitImplsToggle', traitImplsHandler);
+        const directoriesHandler = () => setDirectories((current) => ({
+            collapsed: current.collapsed.size ? new Set() : topLevelDirectories(),
+            opened: new Set()
+        }));
+        window.addEventListener('directoriesToggle', directoriesHandler);
+        const directoryCollapseHandler = (event) => setDirectories((current) => collapseDirectory(current, event.detail));
+        window.addEventListener('directoryCollapse', directoryCollapseHandler);
+        const colorModeHandler = () => setColorMode((mode) => (mode === 'layer' ? 'type' : 'layer'));
+        window.addEventListener('colorModeToggle', colorModeHandler);
+        const robustnessHandler = () => setColorMode((mode) => (mode === 'robustness' ? defaultColorMode : 'robustness'));
+        window.addEventListener('robustnessToggle', robustnessHandler);
+        const dataAccessHandler = () => setColorMode((mode) => (mode === 'dataAccess' ? defaultColorMode : 'dataAccess'));
+        window.addEventListener('dataAccessToggle', dataAccessHandler);
+        const lintsHandler = () => setColorMode((mode) => (mode === 'lints' ? defaultColorMode : 'lints'));
+        window.addEventListener('lintsToggle', lintsHandler);
         return () => {
+            window.removeEventListener('lintsToggle', lintsHandler);
+            window.removeEventListener('dataAccessToggle', dataAccessHandler);
+            window.removeEventListener('robustnessToggle', robustnessHandler);
+            window.removeEventListener('colorModeToggle', colorModeHandler);
+            window.removeEventListener('directoryCollapse', directoryCollapseHandler);
+            window.removeEventListener('directoriesToggle', directoriesHandler);
+            window.removeEventListener('traitImplsToggle', traitImplsHandler);
+            window.removeEventListener('ignoredToggle', ignoredHandler);
+            window.removeEventListener('testTargetsToggle', testTargetsHandler);
+            window.removeEventListener('viewModeChange', viewModeHandler);
             window.removeEventListener('layoutChange', layoutHandler);
             window.removeEventListener('reorderChange', reorderHandler);
         };
     }, []);
 
     const onNodeClick = React.useCallback((_, node) => {
-            const detailsPanel = document.getElementById('details-panel');
-            const detailsContent = document.getElementById('details-content');
-        if (!detailsPanel || !detailsContent) return;
-        
-        const data = nodeLookup.get(node.id);
-        if (!data) return;
-        
-        const metrics = data.metrics || {};
-        detailsPanel.classList.add('open');
-            detailsContent.innerHTML = `
-            <div class="details-heading">
-                <div class="details-icon">${escapeHtml(data.icon)}</div>
-                <div class="details-title">
-                    <h3>${escapeHtml(data.name)}</h3>
-                    <div class="details-meta">${escapeHtml(data.moduleType)} · ${escapeHtml(data.status)}</div>
-                    </div>
-                            </div>
-            <div class="details-section">
-                <h4>Summary</h4>
-                <p class="details-path">${escapeHtml(data.filePath)}</p>
-                            </div>
-            <div class="details-section">
-                <h4>Metrics</h4>
-                <div class="metric-grid">
-                    <div class="metric-item"><span class="metric-item__label">Lines</span><span class="metric-item__value">${formatNumber(metrics.lines_of_code)}</span></div>
-                    <div class="metric-item"><span class="metric-item__label">Functions</span><span class="metric-item__value">${formatNumber(metrics.function_count)}</span></div>
-                    <div class="metric-item"><span class="metric-item__label">Complexity</span><span class="metric-item__value">${formatNumber(metrics.complexity_score,1)}</span></div>
-                    <div class="metric-item"><span class="metric-item__label">Deps</span><span class="metric-item__value">${formatNumber(metrics.dependency_count)}</span></div>
-                            </div>
-                            </div>
-            <div class="details-section">
-                <h4>Dependencies</h4>
-                <div class="chip-row">${(data.dependencies || []).map((item) => `<span class="chip">${escapeHtml(item)}</span>`).join('') || '<span class="empty-state">None</span>'}</div>
-                        </div>
-            <div class="details-section">
-                <h4>Dependents</h4>
-                <div class="chip-row">${(data.dependents || []).map((item) => `<span class="chip">${escapeHtml(item)}</span>`).join('') || '<span class="empty-state">None</span>'}</div>
-                    </div>
-        `;
+        // Clicking a component drills into its crates, or its modules when there is one crate
+        if (node?.data?.kind === 'component') {
+            setComponentFilter(node.data.name);
+            setCrateFilter(null);
+            setViewMode(crateNodesData.length >= 2 ? 'crates' : 'modules');
+            return;
+        }
+        // Clicking a crate drills into its module view
+        if (node?.data?.kind === 'crate') {
+            setCrateFilter(node.data.name);
+            setViewMode('modules');
+            return;
+        }
+        // Clicking a collapsed directory opens it one level
+        if (node?.data?.kind === 'directory') {
+            setDirectories((current) => openDirectory(current, node.data.directory));
+            return;
+        }
+        if (['feature', 'dependency', 'group'].includes(node?.data?.kind)) return;
+        selectedIdRef.current = node.id;
+        showModuleDetails(node.id, curationRef.current);
+    }, []);
+
+    // Showing a group of circular dependencies dims every module outside it; picking it again clears the focus
+    React.useEffect(() => {
+        const cycleHandler = (event) => {
+            const ids = new Set(event.detail || []);
+            setCycleFocusIds((current) => (current && current.size === ids.size && [...ids].every((id) => current.has(id)) ? null : ids));
+            setDirectories((current) => revealModules(current, [...ids]));
+            setTraceRootId(null);
+            setViewMode('modules');
+            setComponentFilter(null);
+            setCrateFilter(null);
+        };
+        window.addEventListener('cycleFocus', cycleHandler);
+        return () => window.removeEventListener('cycleFocus', cycleHandler);
+    }, []);
+
+    // Search matches are outlined and framed in the module view; picking one centers it
+    React.useEffect(() => {
+        const matchHandler = (event) => {
+            const ids = (event.detail || []).filter((id) => nodeLookup.has(id));
+            setSearchIds(ids.length ? new Set(ids) : null);
+            if (!ids.length) return;
+            setDirectories((current) => revealModules(current, ids));
+            searchFitPendingRef.current = true;
+            setViewMode('modules');
+            setComponentFilter(null);
+            setCrateFilter(null);
+        };
+        const focusHandler = (event) => {
+            // Wait for a view switch to render the module
+            setTimeout(() => {
+                const node = getNode(event.detail);
+                if (!node) return;
+                setCenter(node.position.x + (node.width || 0) / 2, node.position.y + (node.height || 0) / 2, { zoom: 1.2, duration: 400 });
+            }, 50);
+        };
+        window.addEventListener('searchMatches', matchHandler);
+        window.addEventListener('searchFocus', focusHandler);
+        return () => {
+            window.removeEventListener('searchMatches', matchHandler);
+            window.removeEventListener('searchFocus', focusHandler);
+        };
+    }, []);
+
+    React.useEffect(() => {
+        if (!searchFitPendingRef.current || !searchIds) return;
+        searchFitPendingRef.current = false;
+        // Nodes are measured after they render, and fitting needs their size
+        setTimeout(() => fitView({ nodes: [...searchIds].map((id) => ({ id })), padding: 0.3, maxZoom: 1.2, duration: 400 }), 50);
+    }, [nodes, searchIds]);
+
+    // Picking a module outside the graph, e.g. in the orphan panel, shows it in the module view
+    React.useEffect(() => {
+        const selectHandler = (event) => {
+            const nodeId = event.detail;
+            if (!nodeLookup.has(nodeId)) return;
+            setViewMode('modules');
+            setComponentFilter(null);
+            setCrateFilter(null);
+            if (nodeLookup.get(nodeId).ignored) setShowIgnored(true);
+            setDirectories((current) => revealModules(current, [nodeId]));
+            selectedIdRef.current = nodeId;
+            showModuleDetails(nodeId, curationRef.current);
+        };
+        window.addEventListener('moduleSelect', selectHandler);
+        return () => window.removeEventListener('moduleSelect', selectHandler);
     }, []);
 
+    const onEdgeClick = React.useCallback((_, edge) => {
+        selectedIdRef.current = null;
+        showEdgeDetails(edge);
+    }, []);
+
+    const onNodeDragStop = React.useCallback((_, node) => {
+        if (!curationRef.current.pinned[node.id]) return;
+        updateCuration((current) => ({
+            ...current,
+            pinned: { ...current.pinned, [node.id]: { x: node.position.x, y: node.position.y } }
+        }));
+    }, [updateCuration]);
+
     const onPaneClick = React.useCallback(() => {
-        const detailsPanel = document.getElementById('details-panel');
-        const detailsContent = document.getElementById('details-content');
-        if (detailsPanel) detailsPanel.classList.remove('open');
-        if (detailsContent) detailsContent.innerHTML = '<p class="details-placeholder">Click on a module to see details</p>';
+        selectedIdRef.current = null;
+        closeDetails();
     }, []);
 
     const onNodesChange = React.useCallback(
@@ -906,17 +3426,71 @@ const FlowApp = () => {
         []
     );
 
+    // Entry points get their own frame; a trace dims everything its entry point cannot reach, a focused cycle everything outside it
+    const tracedIds = React.useMemo(() => {
+        if (viewMode !== 'modules') return null;
+        if (traceRootId && nodeLookup.has(traceRootId)) return reachableFrom(traceRootId, edgesData);
+        return cycleFocusIds;
+    }, [traceRootId, cycleFocusIds, viewMode, dataVersion]);
+    const matchedIds = viewMode === 'modules' ? searchIds : null;
+    // A directory card stands for the modules inside it
+    const includes = (ids, node) => ids.has(node.id) || (node.data?.moduleIds || []).some((id) => ids.has(id));
+    const renderedNodes = React.useMemo(() => {
+        const below = (path, node) => `${node.data?.filePath || node.data?.directory || ''}/`.startsWith(`${path}/`) && node.data?.directory !== path;
+        const directoryFrames = viewMode !== 'modules' ? [] : [...directories.opened]
+            .map((path) => [path, nodes.filter((node) => below(path, node))])
+            .filter(([, members]) => members.length)
+            .map(([path, members]) => frameNode(`directory-frame-${path}`, members, { name: `${path}/`, color: directoryColor, directory: path }));
+        return [...directoryFrames, ...groupContainers(nodes), ...nodes.map((node) => {
+            const className = [
+                node.data?.entryPoint ? 'is-entry-point' : '',
+                matchedIds && includes(matchedIds, node) ? 'is-search-match' : '',
+                (tracedIds && !includes(tracedIds, node)) || (matchedIds && !includes(matchedIds, node)) ? 'is-dimmed' : ''
+            ].filter(Boolean).join(' ');
+            return className === (node.className || '') ? node : { ...node, className };
+        })];
+    }, [nodes, tracedIds, matchedIds, directories, viewMode]);
+
+    // With labels on hover, only the hovered and selected edges show theirs
+    const [hoveredEdgeId, setHoveredEdgeId] = React.useState(null);
+    const renderedEdges = React.useMemo(() => {
+        if (edgeLabelMode !== 'OnHover' && !tracedIds) return edges;
+        return edges.map((edge) => {
+            let rendered = edge;
+            if (edgeLabelMode === 'OnHover' && (edge.id === hoveredEdgeId || edge.selected)) {
+                rendered = { ...rendered, label: edge.data?.labelText };
+            }
+            if (tracedIds && !(tracedIds.has(edge.source) && tracedIds.has(edge.target))) {
+                rendered = { ...rendered, style: { ...rendered.style, opacity: 0.15 } };
+            }
+            return rendered;
+        });
+    }, [edges, hoveredEdgeId, tracedIds]);
+
     if (!nodesData.length) {
         return e('div', { className: 'empty-architecture' }, 'No modules found');
     }
 
-    return e(ReactFlow, {
-        nodes,
-        edges,
+    return e('div', { className: 'flow-wrapper' },
+        e(Breadcrumb, {
+            viewMode,
+            componentFilter,
+            crateFilter,
+            onSelectComponents: showComponents,
+            onSelectCrates: showCrates,
+            onSelectAll: showAllModules,
+        }),
+        e(ReactFlow, {
+        nodes: renderedNodes,
+        edges: renderedEdges,
         nodeTypes,
         onNodesChange,
         onEdgesChange,
         onNodeClick,
+        onEdgeClick,
+        onEdgeMouseEnter: (_, edge) => setHoveredEdgeId(edge.id),
+        onEdgeMouseLeave: () => setHoveredEdgeId(null),
+        onNodeDragStop,
         onPaneClick,
         fitView: true,
         defaultEdgeOptions: { type: 'smoothstep', markerEnd: { type: MarkerType.ArrowClosed, width: 20, height: 20 } },
@@ -925,8 +3499,9 @@ const FlowApp = () => {
         proOptions: { hideAttribution: true }
     },
         e(Background, { gap: 32, size: 1, color: '#dce2f2' }),
-        e(MiniMap, { nodeColor: (node) => node?.data?.color || '#9ca3af' }),
+        e(MiniMap, { nodeColor: (node) => node?.data?.kind === 'group' ? 'transparent' : node?.data?.accentColor || node?.data?.color || '#9ca3af' }),
         e(Controls, null)
+        )
     );
 };
 
@@ -934,28 +3509,34 @@ const FlowApp = () => {
 document.addEventListener('DOMContentLoaded', () => {
     // Setup non-React event handlers
     const themeButton = document.getElementById('theme-btn');
-    if ((architectureData?.settings?.theme || '').toLowerCase() === 'dark') {
+    const defaultTheme = (architectureData?.settings?.theme || '').toLowerCase() === 'dark' ? 'dark' : 'light';
+    if (urlChoice('theme', ['light', 'dark'], defaultTheme) === 'dark') {
         document.body.classList.add('theme-dark');
     }
     if (themeButton) {
         themeButton.addEventListener('click', () => {
-            document.body.classList.toggle('theme-dark');
+            const dark = document.body.classList.toggle('theme-dark');
+            setUrlParam('theme', dark ? 'dark' : 'light', defaultTheme);
         });
     }
 
+    const rulesLink = document.getElementById('rules-link');
+    if (rulesLink && isReadOnly) {
+        rulesLink.style.display = 'none';
+    } else if (rulesLink) {
+        rulesLink.href = `${apiBase}/settings`;
+    }
+
     const refreshButton = document.getElementById('refresh-btn');
-    if (refreshButton) {
+    if (refreshButton && architectureData?.settings?.readOnly) {
+        refreshButton.style.display = 'none';
+    } else if (refreshButton) {
         refreshButton.addEventListener('click', () => window.location.reload());
     }
 
     const closeButton = document.getElementById('close-details');
     if (closeButton) {
-        closeButton.addEventListener('click', () => {
-            const detailsPanel = document.getElementById('details-panel');
-            const detailsContent = document.getElementById('details-content');
-            if (detailsPanel) detailsPanel.classList.remove('open');
-            if (detailsContent) detailsContent.innerHTML = '<p class="details-placeholder">Click on a module to see details</p>';
-        });
+        closeButton.addEventListener('click', closeDetails);
     }
 
            // Layout button handlers
@@ -979,6 +3560,7 @@ document.addEventListener('DOMContentLoaded', () => {
                        // Trigger layout change
                        window.dispatchEvent(new CustomEvent('layoutChange', { detail: layout }));
                    });
+                   button.classList.toggle('active', layout === layouts[currentLayoutIndex]);
                }
            });
 
@@ -1004,9 +3586,193 @@ document.addEventListener('DOMContentLoaded', () => {
                        // Trigger reorder change
                        window.dispatchEvent(new CustomEvent('reorderChange', { detail: reorderType }));
                    });
+                   button.classList.toggle('active', reorderType === urlChoice('order', reorderOptions, 'hierarchical'));
+               }
+           });
+
+           // View (module / crate level) button handlers
+           const viewGroup = document.getElementById('view-controls');
+           if (viewGroup && !hasAlternateViews && !hasTestTargets && !hasIgnored && !hasLayers) {
+               viewGroup.style.display = 'none';
+           }
+           viewModes.forEach((mode) => {
+               const button = document.getElementById(`view-${mode}`);
+               if (button && (!availableViews[mode] || !hasAlternateViews)) {
+                   button.style.display = 'none';
+               } else if (button) {
+                   button.addEventListener('click', () => {
+                       window.dispatchEvent(new CustomEvent('viewModeChange', { detail: mode }));
+                   });
                }
            });
 
+           const testTargetsButton = document.getElementById('toggle-test-targets');
+           if (testTargetsButton && !hasTestTargets) {
+               testTargetsButton.style.display = 'none';
+           } else if (testTargetsButton) {
+               testTargetsButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('testTargetsToggle'));
+               });
+           }
+
+           const ignoredButton = document.getElementById('toggle-ignored');
+           if (ignoredButton && !hasIgnored) {
+               ignoredButton.style.display = 'none';
+           } else if (ignoredButton) {
+               ignoredButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('ignoredToggle'));
+               });
+           }
+
+           const traitImplsButton = document.getElementById('toggle-trait-impls');
+           if (traitImplsButton && !hasTraitImpls) {
+               traitImplsButton.style.display = 'none';
+           } else if (traitImplsButton) {
+               traitImplsButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('traitImplsToggle'));
+               });
+           }
+
+           const directoriesButton = document.getElementById('toggle-directories');
+           if (directoriesButton && !moduleGroupsData.length) {
+               directoriesButton.style.display = 'none';
+           } else if (directoriesButton) {
+               directoriesButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('directoriesToggle'));
+               });
+           }
+
+           const colorModeButton = document.getElementById('toggle-color-mode');
+           if (colorModeButton && !hasLayers) {
+               colorModeButton.style.display = 'none';
+           } else if (colorModeButton) {
+               colorModeButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('colorModeToggle'));
+               });
+           }
+
+           const robustnessButton = document.getElementById('toggle-robustness');
+           if (robustnessButton) {
+               robustnessButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('robustnessToggle'));
+               });
+           }
+
+           const dataAccessButton = document.getElementById('toggle-data-access');
+           if (dataAccessButton && !nodesData.some((node) => node.dataAccessRole)) {
+               dataAccessButton.style.display = 'none';
+           } else if (dataAccessButton) {
+               dataAccessButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('dataAccessToggle'));
+               });
+           }
+
+           const lintsButton = document.getElementById('toggle-lints');
+           if (lintsButton && !nodesData.some((node) => node.clippyLints)) {
+               lintsButton.style.display = 'none';
+           } else if (lintsButton) {
+               lintsButton.addEventListener('click', () => {
+                   window.dispatchEvent(new CustomEvent('lintsToggle'));
+               });
+           }
+
+           // The server ranks search matches; exported pages without one match names and paths
+           const searchInput = document.getElementById('module-search');
+           const searchResults = document.getElementById('search-results');
+           if (searchInput && searchResults) {
+               let searchTimer = null;
+               let searchRequest = 0;
+               const localMatches = (query) => {
+                   const terms = query.toLowerCase().split(/\s+/).filter(Boolean);
+                   return nodesData
+                       .filter((node) => terms.every((term) => node.name.toLowerCase().includes(term) || (node.filePath || '').toLowerCase().includes(term)))
+                       .slice(0, 20)
+                       .map((node) => ({ id: node.id, name: node.name, file_path: node.filePath, matched_field: 'name' }));
+               };
+               const showMatches = (results) => {
+                   searchResults.innerHTML = results.map((result) => {
+                       const where = result.matched_field === 'name' || result.matched_field === 'file_path'
+                           ? result.file_path
+                           : `${result.matched_field} ${result.matched_text} · ${result.file_path}`;
+                       return `<li><button type="button" class="search-result" data-node-id="${escapeHtml(result.id)}"><strong>${escapeHtml(result.name)}</strong><span>${escapeHtml(where)}</span></button></li>`;
+                   }).join('');
+                   window.dispatchEvent(new CustomEvent('searchMatches', { detail: results.map((result) => result.id) }));
+               };
+               const runSearch = async () => {
+                   const query = searchInput.value.trim();
+                   const request = ++searchRequest;
+                   if (!query) {
+                       showMatches([]);
+                       return;
+                   }
+                   let results;
+                   try {
+                       const response = await fetch(`${apiBase}/api/search?q=${encodeURIComponent(query)}&limit=20`);
+                       if (!response.ok) throw new Error(`HTTP ${response.status}`);
+                       results = (await response.json()).results;
+                   } catch (error) {
+                       results = localMatches(query);
+                   }
+                   // A slower earlier request must not replace newer results
+                   if (request === searchRequest) showMatches(results);
+               };
+               searchInput.addEventListener('input', () => {
+                   clearTimeout(searchTimer);
+                   searchTimer = setTimeout(runSearch, 200);
+               });
+               searchInput.addEventListener('keydown', (event) => {
+                   if (event.key === 'Enter') {
+                       searchResults.querySelector('[data-node-id]')?.click();
+                   } else if (event.key === 'Escape') {
+                       searchInput.value = '';
+                       runSearch();
+                   }
+               });
+               searchResults.addEventListener('click', (event) => {
+                   const result = event.target.closest('[data-node-id]');
+                   if (!result) return;
+                   window.dispatchEvent(new CustomEvent('moduleSelect', { detail: result.dataset.nodeId }));
+                   window.dispatchEvent(new CustomEvent('searchFocus', { detail: result.dataset.nodeId }));
+               });
+           }
+
+           // Entry points to trace from, libraries first
+           const traceSelect = document.getElementById('trace-entry-point');
+           const entryPoints = nodesData
+               .filter((node) => node.entryPoint)
+               .sort((a, b) => b.entryPoint.localeCompare(a.entryPoint) || a.name.localeCompare(b.name));
+           if (traceSelect && !entryPoints.length) {
+               document.getElementById('trace-controls').style.display = 'none';
+           } else if (traceSelect) {
+               entryPoints.forEach((node) => {
+                   const option = document.createElement('option');
+                   option.value = node.id;
+                   option.textContent = `${node.entryPoint === 'Library' ? '📚' : '▶'} ${node.name} (${node.filePath})`;
+                   traceSelect.appendChild(option);
+               });
+               traceSelect.value = urlState.get('trace') || '';
+               traceSelect.addEventListener('change', () => {
+                   window.dispatchEvent(new CustomEvent('traceChange', { detail: traceSelect.value }));
+               });
+           }
+
+           // Feature sets and targets from `scanning.variants`
+           const variantSelect = document.getElementById('variant-select');
+           if (variantSelect && !variantsData.length) {
+               document.getElementById('variant-controls').style.display = 'none';
+           } else if (variantSelect) {
+               variantsData.forEach((variant) => {
+                   const option = document.createElement('option');
+                   const described = [variant.features.join(', '), variant.target].filter(Boolean).join(' @ ');
+                   option.value = variant.name;
+                   option.textContent = described ? `${variant.name} (${described})` : variant.name;
+                   variantSelect.appendChild(option);
+               });
+               variantSelect.addEventListener('change', () => {
+                   window.dispatchEvent(new CustomEvent('variantChange', { detail: variantSelect.value }));
+               });
+           }
+
            // Legend toggle handler
            const legendToggle = document.getElementById('legend-toggle');
            const legend = document.querySelector('.legend');
@@ -1019,10 +3785,7 @@ document.addEventListener('DOMContentLoaded', () => {
 
     document.addEventListener('keydown', (event) => {
         if (event.key === 'Escape') {
-            const detailsPanel = document.getElementById('details-panel');
-            const detailsContent = document.getElementById('details-content');
-            if (detailsPanel) detailsPanel.classList.remove('open');
-            if (detailsContent) detailsContent.innerHTML = '<p class="details-placeholder">Click on a module to see details</p>';
+            closeDetails();
         }
     });
 
@@ -1031,11 +3794,222 @@ document.addEventListener('DOMContentLoaded', () => {
     if (rootElement) {
         const root = createRoot(rootElement);
         root.render(e(ReactFlowProvider, null, e(FlowApp, null)));
+        connectLiveUpdates();
+        initHistoryTimeline();
+        renderDataAge();
+        setInterval(renderDataAge, 30000);
+        renderAnomalies(architectureData.anomalies || []);
+        renderOrphans(architectureData.orphans || []);
+        document.getElementById('orphan-panel')?.addEventListener('click', (event) => {
+            const button = event.target.closest('[data-orphan-id]');
+            if (button) window.dispatchEvent(new CustomEvent('moduleSelect', { detail: button.dataset.orphanId }));
+        });
+        renderCycles(architectureData.cycles || []);
+        document.getElementById('cycle-panel')?.addEventListener('click', (event) => {
+            const module = event.target.closest('[data-cycle-module]');
+            if (module) window.dispatchEvent(new CustomEvent('moduleSelect', { detail: module.dataset.cycleModule }));
+            const focus = event.target.closest('[data-cycle-focus]');
+            const cycle = focus && event.currentTarget.cycles?.[Number(focus.dataset.cycleFocus)];
+            if (cycle) window.dispatchEvent(new CustomEvent('cycleFocus', { detail: cycle.modules.map((module) => module.id) }));
+        });
+        if (liveUpdates) renderBuildStatus(architectureData.build);
     } else {
         console.error('React Flow root element not found');
     }
 });
 "#;
-        Ok(template.replace("__ARCHITECTURE_DATA__", &serialized))
+        template.replace("__ARCHITECTURE_DATA__", data_source)
+    }
+}
+
+/// Render an optional page section, or nothing when it is disabled
+fn section(enabled: bool, render: impl FnOnce() -> String) -> String {
+    if enabled {
+        render()
+    } else {
+        String::new()
+    }
+}
+
+/// Value and label of one stats card; module counts skip ignored modules and external crates
+fn stat_card(
+    card: StatCard,
+    architecture: &ArchitectureMap,
+    counted: &HashMap<String, ArchitectureNode>,
+) -> (String, &'static str) {
+    let robustness_total = |count: fn(&ArchitectureNode) -> usize| counted.values().map(count).sum::<usize>();
+    match card {
+        StatCard::TotalModules => (architecture.total_modules.to_string(), "Total Modules"),
+        StatCard::ActiveModules => (
+            counted.values().filter(|n| matches!(n.status, NodeStatus::Active)).count().to_string(),
+            "Active Modules",
+        ),
+        StatCard::LinesOfCode => (architecture.total_lines.to_string(), "Lines of Code"),
+        StatCard::AverageComplexity => (format!("{:.1}", architecture.average_complexity), "Avg Complexity"),
+        StatCard::MaxComplexity => (format!("{:.1}", architecture.metrics.max_complexity), "Max Complexity"),
+        StatCard::Functions => (architecture.metrics.total_functions.to_string(), "Functions"),
+        StatCard::Dependencies => (architecture.edges.len().to_string(), "Dependencies"),
+        StatCard::CircularDependencies => (architecture.circular_dependencies.len().to_string(), "Circular Deps"),
+        StatCard::Modularity => (format!("{:.2}", architecture.metrics.modularity_score), "Modularity"),
+        StatCard::HealthScore => (format!("{:.0}", architecture.metrics.health_score), "Health Score"),
+        StatCard::DocCoverage => (
+            format!("{:.0}%", doc_coverage::project_ratio(architecture) * 100.0),
+            "Doc Coverage",
+        ),
+        StatCard::UnsafeBlocks => (robustness_total(|n| n.robustness.unsafe_blocks).to_string(), "Unsafe Blocks"),
+        StatCard::PanicPoints => (
+            robustness_total(|n| n.robustness.unwraps + n.robustness.expects + n.robustness.panics).to_string(),
+            "Panic Points",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LayerDefinition;
+    use crate::types::ScanProvenance;
+
+    fn visualizer(config: ProjectConfig) -> ArchitectureVisualizer {
+        ArchitectureVisualizer::new(ArchitectureScanner::new(std::env::temp_dir(), config))
+    }
+
+    fn architecture() -> ArchitectureMap {
+        serde_json::from_value(json!({
+            "nodes": {},
+            "edges": [],
+            "last_scan": "2024-05-06T07:08:09Z",
+            "total_modules": 12,
+            "total_lines": 3400,
+            "average_complexity": 2.5,
+            "circular_dependencies": [],
+            "metrics": {
+                "total_functions": 87,
+                "total_structs": 0,
+                "total_enums": 0,
+                "total_traits": 0,
+                "max_complexity": 9.0,
+                "min_complexity": 1.0,
+                "dependency_density": 0.0,
+                "modularity_score": 0.0,
+                "maintainability_index": 0.0
+            }
+        }))
+        .unwrap()
+    }
+
+    fn provenance(git_commit: Option<&str>, git_dirty: Option<bool>, host: Option<&str>) -> ScanProvenance {
+        ScanProvenance {
+            tool_version: "1.2.3".to_string(),
+            git_commit: git_commit.map(str::to_string),
+            git_dirty,
+            scan_duration_ms: 42,
+            config_hash: "abc".to_string(),
+            host: host.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn header_has_the_global_actions() {
+        let header = visualizer(ProjectConfig::default()).generate_header_html();
+        assert!(header.starts_with(r#"<div class="header">"#));
+        for id in ["refresh-btn", "theme-btn", "fullscreen-btn", "rules-link"] {
+            assert!(header.contains(&format!(r#"id="{}""#, id)), "missing {}", id);
+        }
+    }
+
+    #[test]
+    fn controls_offer_the_legend_toggle_only_with_a_legend() {
+        let visualizer = visualizer(ProjectConfig::default());
+        let with_legend = visualizer.generate_controls_html(&PageSections::default());
+        assert!(with_legend.starts_with(r#"<div class="visualization-controls">"#));
+        assert!(with_legend.contains(r#"id="legend-toggle""#));
+
+        let without_legend = visualizer.generate_controls_html(&PageSections { legend: false, ..PageSections::default() });
+        assert!(!without_legend.contains("legend-toggle"));
+        assert!(without_legend.contains(r#"id="layout-grid""#));
+    }
+
+    #[test]
+    fn details_panel_has_the_elements_the_script_fills() {
+        let panel = visualizer(ProjectConfig::default()).generate_details_panel_html();
+        assert!(panel.starts_with(r#"<aside class="details-panel" id="details-panel">"#));
+        assert!(panel.contains(r#"id="close-details""#));
+        assert!(panel.contains(r#"id="details-content""#));
+    }
+
+    #[test]
+    fn footer_reports_the_scan() {
+        let footer = visualizer(ProjectConfig::default()).generate_footer_html(&architecture());
+        assert!(footer.contains("Last updated: 2024-05-06 07:08:09 UTC"));
+        assert!(footer.contains("Total modules: 12"));
+        assert!(footer.contains("Dependencies: 0"));
+        assert!(!footer.contains("rust-arch-viz"));
+    }
+
+    #[test]
+    fn footer_shows_the_shortened_revision_and_escaped_host() {
+        let visualizer = visualizer(ProjectConfig::default());
+        let mut architecture = architecture();
+
+        architecture.provenance = Some(provenance(Some("0123456789abcdef0123"), Some(true), Some("ci<1>")));
+        let footer = visualizer.generate_footer_html(&architecture);
+        assert!(footer.contains("rust-arch-viz 1.2.3 · 0123456789ab (dirty) · 42 ms · ci&lt;1&gt;"));
+        assert!(footer.contains(r#"title="Config hash abc""#));
+
+        architecture.provenance = Some(provenance(None, None, None));
+        assert!(visualizer.generate_footer_html(&architecture).contains("rust-arch-viz 1.2.3 · no git · 42 ms</span>"));
+    }
+
+    #[test]
+    fn stats_show_the_configured_cards_in_order() {
+        let mut config = ProjectConfig::default();
+        config.visualization.stat_cards = vec![StatCard::Functions, StatCard::TotalModules];
+        let stats = visualizer(config).generate_stats_html(&architecture());
+
+        assert!(stats.starts_with(r#"<div class="stats">"#));
+        assert_eq!(stats.matches(r#"class="stat-card""#).count(), 2);
+        let functions = stats.find("Functions").unwrap();
+        let modules = stats.find("Total Modules").unwrap();
+        assert!(functions < modules);
+        assert!(stats.contains(r#"<div class="stat-number">87</div>"#));
+        assert!(stats.contains(r#"<div class="stat-number">12</div>"#));
+    }
+
+    #[test]
+    fn legend_lists_module_types_and_escaped_layers() {
+        let mut config = ProjectConfig::default();
+        config.rules.layers = vec![LayerDefinition {
+            name: "Web & API".to_string(),
+            patterns: Vec::new(),
+            module_types: Vec::new(),
+            groups: Vec::new(),
+            color: Some("#123456".to_string()),
+        }];
+        let legend = visualizer(config).generate_legend_html();
+
+        assert!(legend.contains("<span>Core</span>"));
+        assert!(legend.contains("<span>Integration Tests</span>"));
+        assert!(legend.contains("<span>Web &amp; API</span>"));
+        assert!(legend.contains("background-color: #123456;"));
+    }
+
+    #[test]
+    fn disabled_sections_are_left_out_of_the_page() {
+        let mut config = ProjectConfig::default();
+        config.visualization.sections = PageSections {
+            controls: false,
+            legend: true,
+            stats: false,
+            details_panel: false,
+            footer: true,
+        };
+        let html = visualizer(config).generate_html(&architecture()).unwrap();
+
+        assert!(!html.contains(r#"class="visualization-controls""#));
+        assert!(!html.contains(r#"id="details-panel""#));
+        assert!(!html.contains(r#"<div class="stats">"#));
+        assert!(html.contains(r#"class="footer""#));
+        assert_eq!(html.matches(r#"id="react-flow-root""#).count(), 1);
     }
 }
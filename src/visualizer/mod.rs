@@ -1,14 +1,93 @@
+pub mod component_view;
+pub mod crate_view;
+pub mod doc_coverage;
+pub mod feature_view;
 pub mod html_generator;
+pub mod settings_page;
+pub mod snapshot;
+pub mod static_page;
 pub mod svg_renderer;
+pub mod vendor_assets;
 
 use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use crate::{
     config::ProjectConfig,
-    scanner::ArchitectureScanner,
+    scanner::{git_analyzer::hotspot_score, ArchitectureScanner},
+    types::{ArchitectureMap, ArchitectureNode, NodeSizeMetric},
 };
 
-pub use html_generator::ArchitectureVisualizer;
+pub use component_view::{build_component_view, ComponentEdge, ComponentSummary, ComponentView};
+pub use crate_view::{build_crate_view, CrateEdge, CrateSummary, CrateView};
+pub use feature_view::{build_feature_view, FeatureEdge, FeatureEdgeKind, FeatureSummary, FeatureView, OptionalDependency};
+pub use html_generator::{ArchitectureVisualizer, HtmlOptions};
+pub use snapshot::Snapshot;
+pub use svg_renderer::SvgRenderer;
+
+/// Relative size (0.0 - 1.0) of a node according to the configured sizing metric.
+///
+/// Each metric saturates at a fixed reference value so that sizes stay comparable
+/// between renderers and between scans.
+pub fn node_size_factor(metric: NodeSizeMetric, node: &ArchitectureNode) -> f64 {
+    let (value, saturation) = match metric {
+        NodeSizeMetric::LinesOfCode => (node.metrics.lines_of_code as f64, 1000.0),
+        NodeSizeMetric::Complexity => (node.metrics.complexity_score, 10.0),
+        NodeSizeMetric::FanIn => (node.metrics.dependent_count as f64, 10.0),
+        NodeSizeMetric::Churn => (node.metrics.recent_churn as f64, 500.0),
+        NodeSizeMetric::Hotspot => (hotspot_score(&node.metrics), 5000.0),
+    };
+    
+    (value / saturation).clamp(0.0, 1.0)
+}
+
+type MetricValue = fn(&ArchitectureNode) -> f64;
+
+/// Module metrics given a percentile, by their key in the flow data's `metrics`
+const RANKED_METRICS: [(&str, MetricValue); 5] = [
+    ("lines_of_code", |node| node.metrics.lines_of_code as f64),
+    ("function_count", |node| node.metrics.function_count as f64),
+    ("complexity_score", |node| node.metrics.complexity_score),
+    ("dependency_count", |node| node.metrics.dependency_count as f64),
+    ("recent_churn", |node| node.metrics.recent_churn as f64),
+];
+
+/// Fewer modules than this have no distribution worth ranking against
+const MIN_RANKED_MODULES: usize = 10;
+
+/// Percentile (0 - 100) of each module's metrics among the modules of the project, by node id.
+///
+/// Tied modules share the middle of their ranks, so a value most modules have
+/// is not reported as the top of the range. Ignored modules and test targets
+/// are neither ranked nor ranked against.
+pub fn metric_percentiles(architecture: &ArchitectureMap) -> HashMap<String, BTreeMap<&'static str, u8>> {
+    let ranked: Vec<&ArchitectureNode> = architecture
+        .nodes
+        .values()
+        .filter(|node| !node.ignored && !node.module_type.is_test_target())
+        .collect();
+    let mut percentiles: HashMap<String, BTreeMap<&'static str, u8>> = HashMap::new();
+    if ranked.len() < MIN_RANKED_MODULES {
+        return percentiles;
+    }
+
+    for (key, value) in RANKED_METRICS {
+        let mut sorted: Vec<f64> = ranked.iter().map(|node| value(node)).collect();
+        sorted.sort_by(f64::total_cmp);
+        // Nothing to rank when every module has the same value, e.g. churn without git history
+        if sorted.first() == sorted.last() {
+            continue;
+        }
+        for node in &ranked {
+            let value = value(node);
+            let below = sorted.partition_point(|other| *other < value);
+            let tied = sorted.partition_point(|other| *other <= value) - below;
+            let percentile = (below as f64 + tied as f64 / 2.0) / sorted.len() as f64 * 100.0;
+            percentiles.entry(node.id.clone()).or_default().insert(key, percentile.round() as u8);
+        }
+    }
+    percentiles
+}
 
 /// Create a new architecture visualizer
 pub fn create_visualizer<P: AsRef<Path>>(
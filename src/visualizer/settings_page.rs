@@ -0,0 +1,245 @@
+//! Browser editor for the `[rules]` and `[check]` sections, with a live preview of what `check` would report.
+//!
+//! The page loads the current sections from `api/config`, posts every edit to
+//! `api/rules/preview` and writes the sections back through `api/config`.
+//! Delta thresholds and overrides are not editable here and are saved unchanged.
+
+use crate::types::ModuleType;
+
+/// Milliseconds an edit waits for the next one before the preview is requested
+const PREVIEW_DELAY_MS: u32 = 300;
+
+/// Body of the settings page, after the header
+pub fn render_body() -> String {
+    let module_types: Vec<String> = ModuleType::BUILTIN
+        .iter()
+        .map(|module_type| format!("<code>{:?}</code>", module_type))
+        .collect();
+
+    format!(
+        r#"<main class="static-content rule-editor">
+            <section>
+                <h2>Thresholds</h2>
+                <div class="rule-editor__grid">
+                    <label>Max average complexity <input type="number" step="0.1" min="0" data-check="max_average_complexity"></label>
+                    <label>Max module complexity <input type="number" step="0.1" min="0" data-check="max_module_complexity"></label>
+                    <label>Max circular dependencies <input type="number" step="1" min="0" data-check="max_circular_dependencies"></label>
+                </div>
+                <label><input type="checkbox" data-rule="enforce_layers"> Fail on dependencies that point up the layering</label>
+                <label><input type="checkbox" data-rule="forbid_api_leaks"> Fail on public APIs exposing other workspace crates' types</label>
+            </section>
+            <section>
+                <h2>Layers</h2>
+                <p class="rule-editor__hint">From the top to the bottom; a module belongs to the first layer it matches. Patterns and module types are comma separated.</p>
+                <table class="module-table">
+                    <thead><tr><th>Name</th><th>Patterns</th><th>Module types</th><th>Color</th><th></th></tr></thead>
+                    <tbody id="layer-rows"></tbody>
+                </table>
+                <button type="button" id="add-layer" class="btn btn-secondary">＋ Layer</button>
+                <label>Module type order, used when there are no layers <input type="text" id="module-type-order" placeholder="API, Core, Utilities"></label>
+            </section>
+            <section>
+                <h2>Forbidden dependencies</h2>
+                <table class="module-table">
+                    <thead><tr><th>Name</th><th>From patterns</th><th>From types</th><th>To patterns</th><th>To types</th><th></th></tr></thead>
+                    <tbody id="forbidden-rows"></tbody>
+                </table>
+                <button type="button" id="add-forbidden" class="btn btn-secondary">＋ Rule</button>
+            </section>
+            <p class="rule-editor__hint">Module types: {}</p>
+            <div class="controls">
+                <button type="button" id="save-rules" class="btn btn-primary">💾 Save to config file</button>
+                <button type="button" id="reset-rules" class="btn btn-secondary">↺ Reset</button>
+                <span id="rule-status" role="status"></span>
+            </div>
+            <section>
+                <h2>Preview</h2>
+                <div id="rule-preview">Loading…</div>
+            </section>
+        </main>
+        <script>{}</script>"#,
+        module_types.join(", "),
+        SCRIPT.replace("__PREVIEW_DELAY__", &PREVIEW_DELAY_MS.to_string()),
+    )
+}
+
+/// Page-specific styles, added after the shared stylesheet
+pub const STYLE: &str = r#"
+.rule-editor section{margin-bottom:1.6rem;}
+.rule-editor label{display:block;margin:.4rem 0;}
+.rule-editor input[type=text],.rule-editor input[type=number]{padding:.3rem .45rem;border:1px solid #cbd5e1;border-radius:6px;font:inherit;}
+.rule-editor td input[type=text]{width:100%;}
+.rule-editor__grid{display:grid;grid-template-columns:repeat(auto-fit,minmax(230px,1fr));gap:.4rem 1rem;}
+.rule-editor__hint{font-size:.85rem;color:#64748b;}
+.rule-editor__passed{color:#15803d;font-weight:600;}
+.rule-editor__failed{color:#b91c1c;font-weight:600;}
+"#;
+
+const SCRIPT: &str = r#"
+(() => {
+    const state = { saved: null, rules: null, check: null };
+    const status = document.getElementById('rule-status');
+    const preview = document.getElementById('rule-preview');
+    const escapeHtml = (value) => String(value).replace(/[&<>"']/g, (c) => ({ '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c]));
+    const splitList = (value) => value.split(',').map((part) => part.trim()).filter(Boolean);
+    const joinList = (list) => (list || []).join(', ');
+
+    const textCell = (value, onInput) => {
+        const cell = document.createElement('td');
+        const input = document.createElement('input');
+        input.type = 'text';
+        input.value = value;
+        input.addEventListener('input', () => { onInput(input.value); changed(); });
+        cell.appendChild(input);
+        return cell;
+    };
+    const actionCell = (list, index, render) => {
+        const cell = document.createElement('td');
+        const actions = [['↑', index - 1], ['↓', index + 1], ['✕', null]];
+        actions.forEach(([label, target]) => {
+            if (target !== null && (target < 0 || target >= list.length)) return;
+            const button = document.createElement('button');
+            button.type = 'button';
+            button.className = 'btn btn-secondary';
+            button.textContent = label;
+            button.addEventListener('click', () => {
+                const [item] = list.splice(index, 1);
+                if (target !== null) list.splice(target, 0, item);
+                render();
+                changed();
+            });
+            cell.appendChild(button);
+        });
+        return cell;
+    };
+
+    const renderLayers = () => {
+        const body = document.getElementById('layer-rows');
+        body.innerHTML = '';
+        state.rules.layers.forEach((layer, index) => {
+            const row = document.createElement('tr');
+            row.appendChild(textCell(layer.name, (value) => { layer.name = value; }));
+            row.appendChild(textCell(joinList(layer.patterns), (value) => { layer.patterns = splitList(value); }));
+            row.appendChild(textCell(joinList(layer.module_types), (value) => { layer.module_types = splitList(value); }));
+            row.appendChild(textCell(layer.color || '', (value) => { layer.color = value.trim() || null; }));
+            row.appendChild(actionCell(state.rules.layers, index, renderLayers));
+            body.appendChild(row);
+        });
+    };
+    const renderForbidden = () => {
+        const body = document.getElementById('forbidden-rows');
+        body.innerHTML = '';
+        state.rules.forbidden_dependencies.forEach((rule, index) => {
+            const row = document.createElement('tr');
+            row.appendChild(textCell(rule.name || '', (value) => { rule.name = value.trim() || null; }));
+            row.appendChild(textCell(joinList(rule.from.patterns), (value) => { rule.from.patterns = splitList(value); }));
+            row.appendChild(textCell(joinList(rule.from.module_types), (value) => { rule.from.module_types = splitList(value); }));
+            row.appendChild(textCell(joinList(rule.to.patterns), (value) => { rule.to.patterns = splitList(value); }));
+            row.appendChild(textCell(joinList(rule.to.module_types), (value) => { rule.to.module_types = splitList(value); }));
+            row.appendChild(actionCell(state.rules.forbidden_dependencies, index, renderForbidden));
+            body.appendChild(row);
+        });
+    };
+
+    const renderForm = () => {
+        document.querySelectorAll('[data-check]').forEach((input) => {
+            const value = state.check[input.dataset.check];
+            input.value = value === null || value === undefined ? '' : value;
+        });
+        document.querySelectorAll('[data-rule]').forEach((input) => {
+            input.checked = state.rules[input.dataset.rule] === true;
+        });
+        document.getElementById('module-type-order').value = joinList(state.rules.module_type_order);
+        renderLayers();
+        renderForbidden();
+    };
+
+    const renderPreview = (result) => {
+        const report = result.report;
+        const verdict = report.passed
+            ? '<p class="rule-editor__passed">✓ check passes</p>'
+            : `<p class="rule-editor__failed">✗ check fails with ${report.violations.length} violation(s)</p>`;
+        const violations = report.violations.length === 0 ? '' : `<ul>${report.violations.map((violation) => `<li><code>${escapeHtml(violation.metric)}</code> ${escapeHtml(violation.message)}</li>`).join('')}</ul>`;
+        const layers = result.layers.length === 0
+            ? '<p class="rule-editor__hint">No layers configured.</p>'
+            : `<table class="module-table"><thead><tr><th>Layer</th><th>Modules</th></tr></thead><tbody>${result.layers.map((layer) => `<tr><td><span class="legend-color" style="background:${escapeHtml(layer.color)}"></span> ${escapeHtml(layer.name)}</td><td>${layer.modules}</td></tr>`).join('')}<tr><td><em>No layer</em></td><td>${result.unlayered}</td></tr></tbody></table>`;
+        preview.innerHTML = verdict + violations + layers;
+    };
+
+    const draft = () => ({ rules: state.rules, check: state.check });
+    const post = (path, body) => fetch(path, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify(body)
+    }).then(async (response) => {
+        if (!response.ok) throw new Error(await response.text());
+        return response.json();
+    });
+
+    let pending = null;
+    const requestPreview = () => {
+        post('api/rules/preview', draft())
+            .then(renderPreview)
+            .catch((error) => { preview.innerHTML = `<p class="rule-editor__failed">${escapeHtml(error.message)}</p>`; });
+    };
+    const changed = () => {
+        status.textContent = 'Unsaved changes';
+        clearTimeout(pending);
+        pending = setTimeout(requestPreview, __PREVIEW_DELAY__);
+    };
+
+    document.querySelectorAll('[data-check]').forEach((input) => {
+        input.addEventListener('input', () => {
+            const value = input.value.trim();
+            state.check[input.dataset.check] = value === '' ? null : Number(value);
+            changed();
+        });
+    });
+    document.querySelectorAll('[data-rule]').forEach((input) => {
+        input.addEventListener('change', () => { state.rules[input.dataset.rule] = input.checked; changed(); });
+    });
+    document.getElementById('module-type-order').addEventListener('input', (event) => {
+        state.rules.module_type_order = splitList(event.target.value);
+        changed();
+    });
+    document.getElementById('add-layer').addEventListener('click', () => {
+        state.rules.layers.push({ name: `layer ${state.rules.layers.length + 1}`, patterns: [], module_types: [], color: null });
+        renderLayers();
+        changed();
+    });
+    document.getElementById('add-forbidden').addEventListener('click', () => {
+        state.rules.forbidden_dependencies.push({ name: null, from: { patterns: [], module_types: [] }, to: { patterns: [], module_types: [] } });
+        renderForbidden();
+        changed();
+    });
+    document.getElementById('reset-rules').addEventListener('click', () => {
+        state.rules = structuredClone(state.saved.rules);
+        state.check = structuredClone(state.saved.check);
+        renderForm();
+        requestPreview();
+        status.textContent = '';
+    });
+    document.getElementById('save-rules').addEventListener('click', () => {
+        status.textContent = 'Saving…';
+        post('api/config', draft())
+            .then((config) => {
+                state.saved = { rules: structuredClone(config.rules), check: structuredClone(config.check) };
+                status.textContent = 'Saved to the config file';
+                requestPreview();
+            })
+            .catch((error) => { status.textContent = `Not saved: ${error.message}`; });
+    });
+
+    fetch('api/config')
+        .then((response) => response.json())
+        .then((config) => {
+            state.saved = { rules: config.rules, check: config.check };
+            state.rules = structuredClone(config.rules);
+            state.check = structuredClone(config.check);
+            renderForm();
+            requestPreview();
+        })
+        .catch((error) => { preview.innerHTML = `<p class="rule-editor__failed">${escapeHtml(error.message)}</p>`; });
+})();
+"#;
+
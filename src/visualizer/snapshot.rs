@@ -0,0 +1,35 @@
+//! Latest value shared between tasks, swapped in whole once it is ready.
+
+use arc_swap::ArcSwapOption;
+use std::sync::Arc;
+
+/// Holds the most recent value behind an `Arc`.
+///
+/// Loading and replacing the pointer are atomic, so readers never wait for a
+/// writer, not even while it swaps in the next value.
+#[derive(Debug)]
+pub struct Snapshot<T> {
+    current: ArcSwapOption<T>,
+}
+
+impl<T> Default for Snapshot<T> {
+    fn default() -> Self {
+        Self {
+            current: ArcSwapOption::empty(),
+        }
+    }
+}
+
+impl<T> Snapshot<T> {
+    /// The current value, if one was stored
+    pub fn load(&self) -> Option<Arc<T>> {
+        self.current.load_full()
+    }
+
+    /// Replace the current value, returning the stored pointer
+    pub fn store(&self, value: T) -> Arc<T> {
+        let value = Arc::new(value);
+        self.current.store(Some(value.clone()));
+        value
+    }
+}
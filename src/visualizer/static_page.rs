@@ -0,0 +1,405 @@
+//! Pieces of the script-free fallback page: a pre-laid-out SVG and plain tables.
+
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    types::{ArchitectureMap, LayoutType, Position, VisualizationSettings},
+    visualizer::{crate_view::toolchain_inconsistencies, SvgRenderer},
+};
+
+const COLUMN_SPACING: f64 = 220.0;
+const ROW_SPACING: f64 = 140.0;
+const MARGIN: f64 = 110.0;
+
+/// User agents of text-mode browsers that cannot run the interactive page
+const TEXT_BROWSERS: &[&str] = &["lynx", "links", "elinks", "w3m", "browsh"];
+
+/// Whether a request with this `User-Agent` should get the static page
+pub fn prefers_static_page(user_agent: &str) -> bool {
+    let user_agent = user_agent.to_lowercase();
+    TEXT_BROWSERS.iter().any(|browser| user_agent.contains(browser))
+}
+
+/// Dependency graph as SVG, with modules without a stored position laid out in
+/// layers for the hierarchical layout and on a grid for every other one
+pub fn render_graph_svg(architecture: &ArchitectureMap, settings: &VisualizationSettings) -> Result<String> {
+    let (laid_out, width, height) = lay_out(architecture, settings);
+    graph_renderer(width, height, settings).render_architecture(&laid_out)
+}
+
+/// Renderer of the graph SVG, whose geometry the raster exports share
+pub fn graph_renderer(width: f64, height: f64, settings: &VisualizationSettings) -> SvgRenderer {
+    SvgRenderer::new(width, height)
+        .with_node_size_metric(settings.node_size_metric)
+        .with_edge_labels(settings.edge_labels)
+}
+
+/// Copy of `architecture` with a position for every module, and the width and
+/// height of the drawing
+pub fn lay_out(architecture: &ArchitectureMap, settings: &VisualizationSettings) -> (ArchitectureMap, f64, f64) {
+    let mut laid_out = architecture.clone();
+    let mut ids: Vec<String> = laid_out.nodes.keys().cloned().collect();
+    ids.sort_by(|a, b| laid_out.nodes[a].name.cmp(&laid_out.nodes[b].name).then(a.cmp(b)));
+
+    let cells = match settings.layout {
+        LayoutType::Hierarchical => layer_cells(architecture, &ids),
+        _ => grid_cells(ids.len()),
+    };
+    let columns = cells.iter().map(|(column, _)| column + 1).max().unwrap_or(1);
+    let rows = cells.iter().map(|(_, row)| row + 1).max().unwrap_or(1);
+    for (id, (column, row)) in ids.iter().zip(cells) {
+        if let Some(node) = laid_out.nodes.get_mut(id) {
+            if node.position.is_none() {
+                node.position = Some(Position {
+                    x: MARGIN + column as f64 * COLUMN_SPACING,
+                    y: MARGIN + row as f64 * ROW_SPACING,
+                    z: 0.0,
+                });
+            }
+        }
+    }
+
+    let (width, height) = laid_out
+        .nodes
+        .values()
+        .filter_map(|node| node.position.as_ref())
+        .fold(
+            (2.0 * MARGIN + (columns - 1) as f64 * COLUMN_SPACING, 2.0 * MARGIN + (rows - 1) as f64 * ROW_SPACING),
+            |(width, height), position| (width.max(position.x + MARGIN), height.max(position.y + MARGIN)),
+        );
+    (laid_out, width, height)
+}
+
+/// Column and row of each of `count` modules on a square grid, row by row
+fn grid_cells(count: usize) -> Vec<(usize, usize)> {
+    let columns = (count as f64).sqrt().ceil().max(1.0) as usize;
+    (0..count).map(|index| (index % columns, index / columns)).collect()
+}
+
+/// Column and row of each module in `ids`, with every module above the ones it
+/// depends on; modules depending on nothing share the bottom row. Edges of
+/// cycles are left out, so each cycle is laid out as if it were broken.
+fn layer_cells(architecture: &ArchitectureMap, ids: &[String]) -> Vec<(usize, usize)> {
+    let mut dependencies: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in architecture.edges.iter().filter(|edge| !edge.is_circular && edge.from != edge.to) {
+        dependencies.entry(edge.from.as_str()).or_default().push(edge.to.as_str());
+    }
+
+    // Longest chain of dependencies below each module
+    fn height<'a>(
+        id: &'a str,
+        dependencies: &HashMap<&'a str, Vec<&'a str>>,
+        heights: &mut HashMap<&'a str, usize>,
+        visiting: &mut HashSet<&'a str>,
+    ) -> usize {
+        if let Some(&height) = heights.get(id) {
+            return height;
+        }
+        // Cycles the circular flags missed end here
+        if !visiting.insert(id) {
+            return 0;
+        }
+        let height = dependencies
+            .get(id)
+            .into_iter()
+            .flatten()
+            .map(|dependency| height(dependency, dependencies, heights, visiting) + 1)
+            .max()
+            .unwrap_or(0);
+        visiting.remove(id);
+        heights.insert(id, height);
+        height
+    }
+    let mut heights = HashMap::new();
+    let mut visiting = HashSet::new();
+    let node_heights: Vec<usize> = ids
+        .iter()
+        .map(|id| height(id, &dependencies, &mut heights, &mut visiting))
+        .collect();
+
+    let top = node_heights.iter().copied().max().unwrap_or(0);
+    let mut filled: HashMap<usize, usize> = HashMap::new();
+    node_heights
+        .into_iter()
+        .map(|height| {
+            let row = top - height;
+            let column = filled.entry(row).or_default();
+            *column += 1;
+            (*column - 1, row)
+        })
+        .collect()
+}
+
+/// Table of every module, sorted by file path
+pub fn render_module_table(architecture: &ArchitectureMap) -> String {
+    let mut nodes: Vec<_> = architecture.nodes.values().collect();
+    nodes.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+
+    let rows: String = nodes
+        .iter()
+        .map(|node| {
+            format!(
+                "<tr><th scope=\"row\">{}</th><td>{}</td><td><code>{}</code></td><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&node.name),
+                node.module_type.display_name(),
+                escape_html(&node.file_path),
+                escape_html(node.crate_name.as_deref().unwrap_or("")),
+                node.metrics.lines_of_code,
+                node.metrics.complexity_score,
+                node.metrics.dependency_count,
+                node.metrics.dependent_count
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>Modules ({})</caption>
+            <thead><tr><th scope="col">Module</th><th scope="col">Type</th><th scope="col">File</th><th scope="col">Crate</th><th scope="col">Lines</th><th scope="col">Complexity</th><th scope="col">Dependencies</th><th scope="col">Dependents</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        nodes.len(),
+        rows
+    )
+}
+
+/// Table of crate versions, editions and MSRVs; empty for single-crate projects
+pub fn render_crate_table(architecture: &ArchitectureMap) -> String {
+    if architecture.crates.len() < 2 {
+        return String::new();
+    }
+
+    let inconsistencies = toolchain_inconsistencies(&architecture.crates);
+    let rows: String = architecture
+        .crates
+        .iter()
+        .map(|metadata| {
+            format!(
+                "<tr><th scope=\"row\">{}</th><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&metadata.name),
+                escape_html(metadata.version.as_deref().unwrap_or("")),
+                escape_html(&metadata.edition),
+                escape_html(metadata.rust_version.as_deref().unwrap_or("")),
+                inconsistencies
+                    .get(&metadata.name)
+                    .map(|messages| escape_html(&messages.join("; ")))
+                    .unwrap_or_default()
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>Crates ({})</caption>
+            <thead><tr><th scope="col">Crate</th><th scope="col">Version</th><th scope="col">Edition</th><th scope="col">MSRV</th><th scope="col">Inconsistencies</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        architecture.crates.len(),
+        rows
+    )
+}
+
+/// Table of complex modules with a bus factor of 1; empty when ownership was not scanned
+pub fn render_risk_table(architecture: &ArchitectureMap) -> String {
+    let mut risks: Vec<_> = architecture
+        .nodes
+        .values()
+        .filter_map(|node| Some((node, node.ownership.as_ref().filter(|ownership| ownership.organizational_risk)?)))
+        .collect();
+    if risks.is_empty() {
+        return String::new();
+    }
+    risks.sort_by(|(a, _), (b, _)| b.metrics.complexity_score.total_cmp(&a.metrics.complexity_score));
+
+    let rows: String = risks
+        .iter()
+        .map(|(node, ownership)| {
+            format!(
+                "<tr><th scope=\"row\">{}</th><td>{}</td><td>{:.0}%</td><td>{:.1}</td></tr>\n",
+                escape_html(&node.name),
+                escape_html(&ownership.top_author),
+                ownership.top_author_share * 100.0,
+                node.metrics.complexity_score
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>Organizational risks ({})</caption>
+            <thead><tr><th scope="col">Module</th><th scope="col">Top author</th><th scope="col">Share of lines</th><th scope="col">Complexity</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        risks.len(),
+        rows
+    )
+}
+
+/// HTTP surface of the project: every detected route, sorted by path; empty when there are none
+pub fn render_route_table(architecture: &ArchitectureMap) -> String {
+    let mut routes: Vec<_> = architecture
+        .nodes
+        .values()
+        .flat_map(|node| node.routes.iter().map(move |route| (node, route)))
+        .collect();
+    if routes.is_empty() {
+        return String::new();
+    }
+    routes.sort_by(|(a, first), (b, second)| {
+        first
+            .path
+            .cmp(&second.path)
+            .then_with(|| first.method.cmp(&second.method))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+
+    let rows: String = routes
+        .iter()
+        .map(|(node, route)| {
+            format!(
+                "<tr><th scope=\"row\"><code>{}</code></th><td>{}</td><td>{}</td><td>{}</td><td><code>{}:{}</code></td></tr>\n",
+                escape_html(&route.path),
+                escape_html(&route.method),
+                escape_html(route.handler.as_deref().unwrap_or("(closure)")),
+                escape_html(&node.name),
+                escape_html(&node.file_path),
+                route.line
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>HTTP surface ({})</caption>
+            <thead><tr><th scope="col">Path</th><th scope="col">Method</th><th scope="col">Handler</th><th scope="col">Module</th><th scope="col">Registered at</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        routes.len(),
+        rows
+    )
+}
+
+/// Table of dependencies pointing up the layering, with ways to invert them; empty when there are none
+pub fn render_inversion_table(architecture: &ArchitectureMap) -> String {
+    let mut upward: Vec<_> = architecture.edges.iter().filter(|edge| edge.layer_violation).collect();
+    if upward.is_empty() {
+        return String::new();
+    }
+    let name_of = |id: &String| architecture.nodes.get(id).map_or_else(|| id.clone(), |node| node.name.clone());
+    upward.sort_by_key(|edge| (name_of(&edge.from), name_of(&edge.to)));
+
+    let describe = |id: &String| {
+        architecture.nodes.get(id).map_or_else(
+            || escape_html(id),
+            |node| format!("{} <small>({})</small>", escape_html(&node.name), escape_html(node.layer.as_deref().unwrap_or(""))),
+        )
+    };
+    let rows: String = upward
+        .iter()
+        .map(|edge| {
+            let suggestions: Vec<String> = edge.suggestions.iter().map(|suggestion| escape_html(suggestion)).collect();
+            format!(
+                "<tr><th scope=\"row\">{}</th><td>{}</td><td>{}</td></tr>\n",
+                describe(&edge.from),
+                describe(&edge.to),
+                suggestions.join("<br>")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>Inversion candidates ({})</caption>
+            <thead><tr><th scope="col">From (lower layer)</th><th scope="col">To (higher layer)</th><th scope="col">Suggestions</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        upward.len(),
+        rows
+    )
+}
+
+/// Table of the modules no target reaches; empty when there are none
+pub fn render_orphan_table(architecture: &ArchitectureMap) -> String {
+    let orphans: Vec<_> = architecture.orphans.iter().filter_map(|id| architecture.nodes.get(id)).collect();
+    if orphans.is_empty() {
+        return String::new();
+    }
+    let rows: String = orphans
+        .iter()
+        .map(|node| {
+            format!(
+                "<tr><th scope=\"row\">{}</th><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&node.name),
+                escape_html(&node.file_path),
+                node.metrics.lines_of_code
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>Orphan modules ({}): no library, binary, example or build script reaches them</caption>
+            <thead><tr><th scope="col">Module</th><th scope="col">File</th><th scope="col">Lines</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        orphans.len(),
+        rows
+    )
+}
+
+/// Table of circular dependencies, or a note that there are none
+pub fn render_cycle_table(architecture: &ArchitectureMap) -> String {
+    if architecture.circular_dependencies.is_empty() {
+        return r#"<p class="static-note">No circular dependencies.</p>"#.to_string();
+    }
+
+    let name_of = |id: &String| {
+        architecture
+            .nodes
+            .get(id)
+            .map_or_else(|| escape_html(id), |node| escape_html(&node.name))
+    };
+    let rows: String = architecture
+        .circular_dependencies
+        .iter()
+        .enumerate()
+        .map(|(index, cycle)| {
+            let mut path: Vec<String> = cycle.iter().map(name_of).collect();
+            if let Some(first) = cycle.first() {
+                path.push(name_of(first));
+            }
+            format!(
+                "<tr><th scope=\"row\">{}</th><td>{}</td><td>{}</td></tr>\n",
+                index + 1,
+                cycle.len(),
+                path.join(" → ")
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<table class="static-table">
+            <caption>Circular dependencies ({})</caption>
+            <thead><tr><th scope="col">#</th><th scope="col">Modules</th><th scope="col">Cycle</th></tr></thead>
+            <tbody>
+{}</tbody>
+        </table>"#,
+        architecture.circular_dependencies.len(),
+        rows
+    )
+}
+
+pub(crate) fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,15 +1,41 @@
 use anyhow::Result;
-use crate::types::{ArchitectureMap, DependencyEdge, ArchitectureNode, Position};
+use crate::types::{ArchitectureMap, DependencyEdge, ArchitectureNode, EdgeLabels, NodeSizeMetric, Position};
+use crate::visualizer::{node_size_factor, static_page::escape_html};
+use std::collections::BTreeMap;
+
+/// Colors of `[groups]` frames in name order, as on the interactive page
+const GROUP_PALETTE: [&str; 8] = ["#6366f1", "#0ea5e9", "#10b981", "#f59e0b", "#ec4899", "#8b5cf6", "#14b8a6", "#ef4444"];
+/// Space between a group's frame and its outermost modules
+const GROUP_PADDING: f64 = 20.0;
 
 /// Renders SVG elements for the architecture visualization
 pub struct SvgRenderer {
     width: f64,
     height: f64,
+    node_size_metric: NodeSizeMetric,
+    edge_labels: EdgeLabels,
 }
 
 impl SvgRenderer {
     pub fn new(width: f64, height: f64) -> Self {
-        Self { width, height }
+        Self {
+            width,
+            height,
+            node_size_metric: NodeSizeMetric::default(),
+            edge_labels: EdgeLabels::default(),
+        }
+    }
+
+    /// Choose the metric that controls node size
+    pub fn with_node_size_metric(mut self, metric: NodeSizeMetric) -> Self {
+        self.node_size_metric = metric;
+        self
+    }
+
+    /// Choose which edges are labelled with their relationship
+    pub fn with_edge_labels(mut self, edge_labels: EdgeLabels) -> Self {
+        self.edge_labels = edge_labels;
+        self
     }
 
     /// Render the complete SVG for the architecture
@@ -25,6 +51,9 @@ impl SvgRenderer {
         // Add definitions for markers and gradients
         svg.push_str(&self.render_definitions());
         
+        // Frame the modules of each `[groups]` group behind everything else
+        svg.push_str(&self.render_groups(&architecture.nodes));
+        
         // Render dependency arrows
         svg.push_str(&self.render_dependencies(&architecture.edges, &architecture.nodes)?);
         
@@ -89,6 +118,8 @@ impl SvgRenderer {
         nodes: &std::collections::HashMap<String, ArchitectureNode>,
     ) -> Result<String> {
         let mut svg = String::new();
+        let mut edges: Vec<&DependencyEdge> = edges.iter().collect();
+        edges.sort_by(|a, b| (&a.from, &a.to, &a.relationship).cmp(&(&b.from, &b.to, &b.relationship)));
         
         for edge in edges {
             if let (Some(from_node), Some(to_node)) = (
@@ -113,12 +144,27 @@ impl SvgRenderer {
                 let stroke_width = (edge.strength * 3.0).max(1.0);
                 
                 // Calculate arrow path with curve for better visualization
-                let path = self.calculate_arrow_path(from_pos, to_pos);
+                let path = self.calculate_arrow_path(&from_pos, &to_pos);
+                let label = escape_html(&format!("{:?}", edge.relationship));
+                // Without a visible label the relationship still shows as a tooltip
+                let title = if self.edge_labels == EdgeLabels::OnHover {
+                    format!("<title>{}</title>", label)
+                } else {
+                    String::new()
+                };
                 
                 svg.push_str(&format!(
-                    r#"<path d="{}" stroke="{}" stroke-width="{}" fill="none" marker-end="url(#{})" opacity="0.7" class="dependency-arrow" data-from="{}" data-to="{}"/>"#,
-                    path, color, stroke_width, arrow_id, edge.from, edge.to
+                    r#"<path d="{}" stroke="{}" stroke-width="{}" fill="none" marker-end="url(#{})" opacity="0.7" class="dependency-arrow" data-from="{}" data-to="{}">{}</path>"#,
+                    path, color, stroke_width, arrow_id, edge.from, edge.to, title
                 ));
+                
+                if self.is_labelled(edge) {
+                    let position = self.calculate_label_position(&from_pos, &to_pos);
+                    svg.push_str(&format!(
+                        r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" font-size="10" fill="{}" class="dependency-label">{}</text>"#,
+                        position.x, position.y - 4.0, color, label
+                    ));
+                }
             }
         }
         
@@ -131,8 +177,11 @@ impl SvgRenderer {
         nodes: &std::collections::HashMap<String, ArchitectureNode>,
     ) -> Result<String> {
         let mut svg = String::new();
+        // In id order, so the same scan always draws the same document
+        let mut nodes: Vec<&ArchitectureNode> = nodes.values().collect();
+        nodes.sort_by(|a, b| a.id.cmp(&b.id));
         
-        for node in nodes.values() {
+        for node in nodes {
             let position = self.get_node_position(node);
             let size = self.calculate_node_size(node);
             
@@ -161,6 +210,19 @@ impl SvgRenderer {
                 node.id
             ));
             
+            // Entry points get a second, square-cornered frame
+            if node.entry_point.is_some() {
+                svg.push_str(&format!(
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="{}" stroke-width="2" class="module-entry-point" data-module-id="{}"/>"#,
+                    position.x - size.width / 2.0 - 5.0,
+                    position.y - size.height / 2.0 - 5.0,
+                    size.width + 10.0,
+                    size.height + 10.0,
+                    color,
+                    node.id
+                ));
+            }
+            
             // Module title
             svg.push_str(&format!(
                 r#"<text x="{}" y="{}" text-anchor="middle" fill="white" font-family="Arial, sans-serif" font-size="14" font-weight="bold" class="module-title" data-module-id="{}">{}</text>"#,
@@ -194,8 +256,56 @@ impl SvgRenderer {
         Ok(svg)
     }
 
+    /// A labelled frame around the modules of each group
+    fn render_groups(&self, nodes: &std::collections::HashMap<String, ArchitectureNode>) -> String {
+        let mut bounds: BTreeMap<&str, (f64, f64, f64, f64)> = BTreeMap::new();
+        for node in nodes.values() {
+            let position = self.get_node_position(node);
+            let size = self.calculate_node_size(node);
+            let (left, top) = (position.x - size.width / 2.0, position.y - size.height / 2.0);
+            let (right, bottom) = (left + size.width, top + size.height);
+            for group in &node.groups {
+                let entry = bounds.entry(group).or_insert((left, top, right, bottom));
+                *entry = (entry.0.min(left), entry.1.min(top), entry.2.max(right), entry.3.max(bottom));
+            }
+        }
+        
+        let mut svg = String::new();
+        for (index, (group, (left, top, right, bottom))) in bounds.into_iter().enumerate() {
+            let color = GROUP_PALETTE[index % GROUP_PALETTE.len()];
+            // Twice the padding above, for the label
+            let (x, y) = (left - GROUP_PADDING, top - GROUP_PADDING * 2.0);
+            svg.push_str(&format!(
+                r#"<rect x="{}" y="{}" width="{}" height="{}" rx="14" ry="14" fill="{}" fill-opacity="0.07" stroke="{}" stroke-width="2" stroke-dasharray="6 4" class="group-frame"/>"#,
+                x,
+                y,
+                right - left + GROUP_PADDING * 2.0,
+                bottom - top + GROUP_PADDING * 3.0,
+                color,
+                color
+            ));
+            svg.push_str(&format!(
+                r#"<text x="{}" y="{}" fill="{}" font-family="Arial, sans-serif" font-size="12" font-weight="bold" class="group-label">{}</text>"#,
+                x + 12.0,
+                y + 18.0,
+                color,
+                escape_html(group)
+            ));
+        }
+        svg
+    }
+
+    /// Whether `edge` shows its relationship next to it, without hovering
+    pub(crate) fn is_labelled(&self, edge: &DependencyEdge) -> bool {
+        match self.edge_labels {
+            EdgeLabels::Always => true,
+            EdgeLabels::CircularOnly => edge.is_circular || edge.layer_violation,
+            EdgeLabels::OnHover | EdgeLabels::Never => false,
+        }
+    }
+
     /// Get node position (simplified layout algorithm)
-    fn get_node_position(&self, node: &ArchitectureNode) -> Position {
+    pub(crate) fn get_node_position(&self, node: &ArchitectureNode) -> Position {
         if let Some(pos) = &node.position {
             pos.clone()
         } else {
@@ -214,16 +324,15 @@ impl SvgRenderer {
     }
 
     /// Calculate node size based on metrics
-    fn calculate_node_size(&self, node: &ArchitectureNode) -> NodeSize {
+    pub(crate) fn calculate_node_size(&self, node: &ArchitectureNode) -> NodeSize {
         let base_width = 150.0;
         let base_height = 80.0;
         
-        // Adjust size based on complexity and lines of code
-        let complexity_factor = (node.metrics.complexity_score / 10.0).min(1.0);
-        let lines_factor = (node.metrics.lines_of_code as f64 / 1000.0).min(1.0);
+        // Adjust size based on the configured sizing metric
+        let factor = node_size_factor(self.node_size_metric, node);
         
-        let width = base_width + (complexity_factor * 50.0);
-        let height = base_height + (lines_factor * 30.0);
+        let width = base_width + (factor * 50.0);
+        let height = base_height + (factor * 30.0);
         
         NodeSize { width, height }
     }
@@ -231,38 +340,49 @@ impl SvgRenderer {
     /// Get gradient ID for module type
     fn get_gradient_id(&self, module_type: &crate::types::ModuleType) -> &'static str {
         match module_type {
-            crate::types::ModuleType::Core => "core-gradient",
-            crate::types::ModuleType::API => "api-gradient",
-            crate::types::ModuleType::DataProcessing => "data-gradient",
-            _ => "core-gradient", // Default
+            crate::types::ModuleType::Core => "core_gradient",
+            crate::types::ModuleType::API => "api_gradient",
+            crate::types::ModuleType::DataProcessing => "data_gradient",
+            _ => "core_gradient", // Default
         }
     }
 
     /// Calculate arrow path with curve
-    fn calculate_arrow_path(&self, from: Position, to: Position) -> String {
-        let dx = to.x - from.x;
-        let dy = to.y - from.y;
-        let distance = (dx * dx + dy * dy).sqrt();
-        
-        if distance < 50.0 {
-            // Straight line for close nodes
-            format!("M {} {} L {} {}", from.x, from.y, to.x, to.y)
-        } else {
+    fn calculate_arrow_path(&self, from: &Position, to: &Position) -> String {
+        match Self::control_point(from, to) {
             // Curved path for distant nodes
-            let control_x = (from.x + to.x) / 2.0;
-            let control_y = (from.y + to.y) / 2.0 - (distance * 0.2);
-            
-            format!(
+            Some((control_x, control_y)) => format!(
                 "M {} {} Q {} {} {} {}",
                 from.x, from.y, control_x, control_y, to.x, to.y
-            )
+            ),
+            // Straight line for close nodes
+            None => format!("M {} {} L {} {}", from.x, from.y, to.x, to.y),
         }
     }
+
+    /// Midpoint of the arrow path between `from` and `to`
+    pub(crate) fn calculate_label_position(&self, from: &Position, to: &Position) -> Position {
+        let (x, y) = match Self::control_point(from, to) {
+            // Point of the quadratic curve halfway along it
+            Some((control_x, control_y)) => ((from.x + 2.0 * control_x + to.x) / 4.0, (from.y + 2.0 * control_y + to.y) / 4.0),
+            None => ((from.x + to.x) / 2.0, (from.y + to.y) / 2.0),
+        };
+        Position { x, y, z: 0.0 }
+    }
+
+    /// Control point curving the path between distant nodes, `None` for close ones
+    pub(crate) fn control_point(from: &Position, to: &Position) -> Option<(f64, f64)> {
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        let distance = (dx * dx + dy * dy).sqrt();
+        
+        (distance >= 50.0).then(|| ((from.x + to.x) / 2.0, (from.y + to.y) / 2.0 - (distance * 0.2)))
+    }
 }
 
 /// Node size information
 #[derive(Debug, Clone)]
-struct NodeSize {
-    width: f64,
-    height: f64,
+pub(crate) struct NodeSize {
+    pub(crate) width: f64,
+    pub(crate) height: f64,
 }
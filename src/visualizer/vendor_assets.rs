@@ -0,0 +1,200 @@
+//! React, React Flow and the React Flow stylesheet the interactive page loads.
+//!
+//! By default they come from CDNs. With `offline_assets` the page loads nothing
+//! from the network: the vendored copies in `vendor_dir` are embedded as data
+//! URLs, or referenced under a path the web server or a bundle provides.
+//! Bundles always carry vendored copies, downloading them into `vendor_dir`
+//! when they are missing there.
+
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+/// One ES module the page imports by a bare specifier
+pub struct VendorModule {
+    /// Name the page script imports, resolved through the page's import map
+    pub specifier: &'static str,
+    /// File holding the vendored copy inside `vendor_dir`
+    pub file_name: &'static str,
+    cdn_url: &'static str,
+    /// Single-file build importing the other modules by their bare specifiers
+    #[cfg_attr(not(feature = "cli"), allow(dead_code))]
+    download_url: &'static str,
+}
+
+/// Modules of the page; the vendored copies must import each other by these bare specifiers
+pub const MODULES: &[VendorModule] = &[
+    VendorModule {
+        specifier: "react",
+        file_name: "react.js",
+        cdn_url: "https://esm.sh/react@18.2.0",
+        download_url: "https://esm.sh/react@18.2.0?bundle",
+    },
+    VendorModule {
+        specifier: "react-dom",
+        file_name: "react-dom.js",
+        cdn_url: "https://esm.sh/react-dom@18.2.0",
+        download_url: "https://esm.sh/react-dom@18.2.0?bundle&external=react",
+    },
+    VendorModule {
+        specifier: "react-dom/client",
+        file_name: "react-dom-client.js",
+        cdn_url: "https://esm.sh/react-dom@18.2.0/client",
+        download_url: "https://esm.sh/react-dom@18.2.0/client?bundle&external=react,react-dom",
+    },
+    VendorModule {
+        specifier: "reactflow",
+        file_name: "reactflow.js",
+        cdn_url: "https://esm.sh/reactflow@11.6.0?deps=react@18.2.0,react-dom@18.2.0",
+        download_url: "https://esm.sh/reactflow@11.6.0?bundle&external=react,react-dom",
+    },
+];
+
+/// File holding the vendored React Flow stylesheet inside `vendor_dir`
+pub const STYLESHEET_FILE: &str = "reactflow.css";
+const STYLESHEET_CDN: &str = "https://unpkg.com/reactflow@11.7.4/dist/style.css";
+/// Host of every `download_url`, which the builds they point to are relative to
+#[cfg(feature = "cli")]
+const DOWNLOAD_ORIGIN: &str = "https://esm.sh";
+
+/// Where the page loads its libraries from
+#[derive(Debug, Clone, Copy)]
+pub enum AssetSource<'a> {
+    Cdn,
+    /// Vendored files read from this directory and embedded into the page
+    Embedded(&'a Path),
+    /// Vendored files served under this URL path
+    Served(&'a str),
+}
+
+/// Stylesheet and import map for the page head
+pub fn head_html(source: AssetSource) -> Result<String> {
+    let mut imports = Map::new();
+    let stylesheet = match source {
+        AssetSource::Cdn => {
+            for module in MODULES {
+                imports.insert(module.specifier.to_string(), Value::from(module.cdn_url));
+            }
+            format!(r#"<link rel="stylesheet" href="{}">"#, STYLESHEET_CDN)
+        }
+        AssetSource::Embedded(dir) => {
+            for module in MODULES {
+                let script = read(dir, module.file_name)?;
+                imports.insert(
+                    module.specifier.to_string(),
+                    Value::from(format!("data:text/javascript;base64,{}", STANDARD.encode(script))),
+                );
+            }
+            let css = String::from_utf8(read(dir, STYLESHEET_FILE)?)
+                .with_context(|| format!("Vendored stylesheet is not UTF-8: {}", STYLESHEET_FILE))?;
+            format!("<style>\n{}\n    </style>", css.replace("</style", "<\\/style"))
+        }
+        AssetSource::Served(base) => {
+            let base = base.trim_end_matches('/');
+            for module in MODULES {
+                imports.insert(module.specifier.to_string(), Value::from(format!("{}/{}", base, module.file_name)));
+            }
+            format!(r#"<link rel="stylesheet" href="{}/{}">"#, base, STYLESHEET_FILE)
+        }
+    };
+    let import_map = serde_json::json!({ "imports": imports });
+
+    Ok(format!(
+        "{}\n    <script type=\"importmap\">{}</script>",
+        stylesheet,
+        import_map.to_string().replace("</", "<\\/")
+    ))
+}
+
+/// Names of every vendored file, scripts first
+pub fn file_names() -> impl Iterator<Item = &'static str> {
+    MODULES.iter().map(|module| module.file_name).chain(std::iter::once(STYLESHEET_FILE))
+}
+
+/// Content type of a vendored file, or `None` for names that are not vendored files
+pub fn content_type(file_name: &str) -> Option<&'static str> {
+    if file_name == STYLESHEET_FILE {
+        Some("text/css; charset=utf-8")
+    } else if MODULES.iter().any(|module| module.file_name == file_name) {
+        Some("text/javascript; charset=utf-8")
+    } else {
+        None
+    }
+}
+
+/// Whether `dir` holds every vendored file
+pub fn is_complete(dir: &Path) -> bool {
+    file_names().all(|file_name| dir.join(file_name).is_file())
+}
+
+/// Download every vendored file into `dir`, writing nothing unless all of them arrive
+#[cfg(feature = "cli")]
+pub fn download(dir: &Path) -> Result<()> {
+    let mut files = Vec::new();
+    for module in MODULES {
+        files.push((module.file_name, download_module(module.download_url)?));
+    }
+    files.push((STYLESHEET_FILE, fetch(STYLESHEET_CDN)?));
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    for (file_name, content) in files {
+        std::fs::write(dir.join(file_name), content)
+            .with_context(|| format!("Failed to write vendored asset {}", file_name))?;
+    }
+    Ok(())
+}
+
+/// One module as a single file; esm.sh answers package URLs with a stub re-exporting the build
+#[cfg(feature = "cli")]
+fn download_module(url: &str) -> Result<String> {
+    let mut script = fetch(url)?;
+    if let Some(build) = stub_target(&script) {
+        script = fetch(&format!("{}{}", DOWNLOAD_ORIGIN, build))?;
+    }
+    if let Some(path) = absolute_imports(&script).first() {
+        anyhow::bail!("{} still imports {} from the CDN", url, path);
+    }
+    Ok(script)
+}
+
+#[cfg(feature = "cli")]
+fn fetch(url: &str) -> Result<String> {
+    ureq::get(url)
+        .call()
+        .context("Failed to download")?
+        .into_string()
+        .with_context(|| format!("Failed to read {}", url))
+}
+
+/// Path of the build `script` re-exports, if it is nothing but such a stub
+#[cfg(feature = "cli")]
+fn stub_target(script: &str) -> Option<&str> {
+    let only_exports = script
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("/*") && !line.starts_with("//"))
+        .all(|line| line.starts_with("export "));
+    let imports = absolute_imports(script);
+    let target = *imports.first()?;
+    (only_exports && imports.iter().all(|&path| path == target)).then_some(target)
+}
+
+/// Paths on the CDN host that `script` imports from
+#[cfg(feature = "cli")]
+fn absolute_imports(script: &str) -> Vec<&str> {
+    let import_regex = regex::Regex::new(r#"\b(?:from|import)\s*\(?\s*"(/[^"]*)""#).unwrap();
+    import_regex
+        .captures_iter(script)
+        .filter_map(|captures| captures.get(1))
+        .map(|path| path.as_str())
+        .collect()
+}
+
+/// Contents of one vendored file
+pub fn read(dir: &Path, file_name: &str) -> Result<Vec<u8>> {
+    let path = dir.join(file_name);
+    std::fs::read(&path).with_context(|| {
+        format!("Failed to read vendored asset {} (needed by `offline_assets`)", path.display())
+    })
+}
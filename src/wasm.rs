@@ -0,0 +1,28 @@
+//! JavaScript bindings for analyzing a source tree in the browser.
+//!
+//! Build with `--target wasm32-unknown-unknown --no-default-features --features wasm`
+//! and run `wasm-bindgen` on the output. The page passes the files a user pasted
+//! or dropped in; nothing is uploaded.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use wasm_bindgen::prelude::*;
+
+use crate::{config::ProjectConfig, scanner::ArchitectureScanner};
+
+/// Scan `files`, a JSON object of contents by path below the project root such as
+/// `{"Cargo.toml": "...", "src/lib.rs": "..."}`, and return the architecture as JSON.
+///
+/// `config` is the content of an `architecture.toml`, or empty for the defaults.
+#[wasm_bindgen(js_name = analyzeSources)]
+pub fn analyze_sources(files: &str, config: &str) -> Result<String, JsError> {
+    analyze(files, config).map_err(|e| JsError::new(&format!("{:#}", e)))
+}
+
+fn analyze(files: &str, config: &str) -> Result<String> {
+    let files: BTreeMap<PathBuf, String> = serde_json::from_str(files).context("Failed to parse the files")?;
+    let config: ProjectConfig = toml::from_str(config).context("Failed to parse TOML config")?;
+    let architecture = ArchitectureScanner::new("", config).scan_sources(&files)?;
+    Ok(serde_json::to_string(&architecture)?)
+}
@@ -0,0 +1,123 @@
+//! Endpoints remote agents push their scans to.
+//!
+//! Both take the token the server was started with as a bearer credential and
+//! refuse everything when no token is set. A pushed map replaces the served
+//! scan and reaches connected pages as a live update.
+
+use anyhow::Result;
+use axum::{
+    body::Bytes,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::{Json, Response},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::{
+    agent::AgentReply,
+    schema,
+    types::ArchitectureMap,
+    web::WebState,
+};
+
+/// Largest scan an agent may push, in bytes
+pub const MAX_SCAN_SIZE: usize = 128 * 1024 * 1024;
+
+/// WebSocket an agent keeps open, pushing a scan after every change
+pub async fn agent_websocket_handler(
+    ws: WebSocketUpgrade,
+    State(state): State<WebState>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    authorize(&state, &headers)?;
+    Ok(ws.max_message_size(MAX_SCAN_SIZE).on_upgrade(move |socket| receive_scans(socket, state)))
+}
+
+async fn receive_scans(mut socket: WebSocket, state: WebState) {
+    while let Some(Ok(message)) = socket.recv().await {
+        let reply = match message {
+            Message::Text(text) => match serde_json::from_str(&text) {
+                Ok(PushedMessage::Architecture { architecture }) => match parse_scan(architecture) {
+                    Ok(architecture) => accept(&state, architecture).await,
+                    Err(e) => AgentReply::Rejected { message: format!("Invalid architecture map: {:#}", e) },
+                },
+                Err(e) => AgentReply::Rejected { message: format!("Invalid message: {}", e) },
+            },
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let text = match serde_json::to_string(&reply) {
+            Ok(text) => text,
+            Err(e) => {
+                warn!("Failed to serialize agent reply: {}", e);
+                continue;
+            }
+        };
+        if socket.send(Message::Text(text)).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// One scan pushed over plain HTTP, e.g. from a CI job
+pub async fn agent_push_handler(
+    State(state): State<WebState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<AgentReply>, (StatusCode, String)> {
+    // Checked before parsing, so unauthenticated clients cannot make the server parse large bodies
+    authorize(&state, &headers)?;
+    let architecture = serde_json::from_slice(&body)
+        .map_err(anyhow::Error::from)
+        .and_then(parse_scan)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid architecture map: {:#}", e)))?;
+
+    Ok(Json(accept(&state, architecture).await))
+}
+
+/// An [`AgentMessage`](crate::agent::AgentMessage) with the map left as JSON, so it goes through [`parse_scan`]
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum PushedMessage {
+    Architecture { architecture: Value },
+}
+
+/// A pushed map of any schema version, refused when its nodes and edges disagree
+fn parse_scan(json: Value) -> Result<ArchitectureMap> {
+    let architecture = schema::upgrade(json)?;
+    architecture.validate()?;
+    Ok(architecture)
+}
+
+async fn accept(state: &WebState, architecture: ArchitectureMap) -> AgentReply {
+    let architecture = state.accept_agent_scan(architecture).await;
+    info!("📡 Agent pushed a scan of {} modules", architecture.total_modules);
+    AgentReply::Accepted {
+        last_scan: architecture.last_scan,
+        total_modules: architecture.total_modules,
+    }
+}
+
+fn authorize(state: &WebState, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let Some(expected) = state.agent_token.as_deref() else {
+        return Err((StatusCode::FORBIDDEN, "Agent pushes are disabled; start the server with --agent-token".to_string()));
+    };
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match given {
+        Some(given) if tokens_match(given, expected) => Ok(()),
+        _ => Err((StatusCode::UNAUTHORIZED, "Missing or wrong agent token".to_string())),
+    }
+}
+
+/// Compare without stopping at the first difference, so timing does not reveal how much matched
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
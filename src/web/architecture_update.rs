@@ -0,0 +1,128 @@
+//! Architecture map changes pushed over `/ws/architecture`, for clients working with the scan data itself.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{
+    Anomaly, ArchitectureMap, ArchitectureMetrics, ArchitectureNode, DependencyEdge, HookOutcome,
+};
+
+/// Share of modules a delta may touch before the whole map is sent instead
+const FULL_UPDATE_SHARE: f64 = 0.5;
+
+/// Message sent to clients connected to `/ws/architecture`
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArchitectureUpdate<'a> {
+    /// The whole map, on connect and after refreshes that change most of it
+    Full { architecture: &'a ArchitectureMap },
+    Delta(Box<ArchitectureDelta<'a>>),
+}
+
+/// A dependency edge with the id it keeps between scans
+#[derive(Debug, Serialize)]
+pub struct EdgeEntry<'a> {
+    pub id: String,
+    #[serde(flatten)]
+    pub edge: &'a DependencyEdge,
+}
+
+/// What a refresh changed in the map.
+///
+/// Changed edges are listed as removed and added under the same id, so
+/// clients apply removals first. Totals, metrics and cycles are resent whole.
+#[derive(Debug, Serialize)]
+pub struct ArchitectureDelta<'a> {
+    pub last_scan: DateTime<Utc>,
+    pub added_nodes: Vec<&'a ArchitectureNode>,
+    pub updated_nodes: Vec<&'a ArchitectureNode>,
+    pub removed_nodes: Vec<String>,
+    pub added_edges: Vec<EdgeEntry<'a>>,
+    pub removed_edges: Vec<String>,
+    pub total_modules: usize,
+    pub total_lines: usize,
+    pub average_complexity: f64,
+    pub circular_dependencies: &'a [Vec<String>],
+    pub metrics: &'a ArchitectureMetrics,
+    pub hook_outcome: Option<&'a HookOutcome>,
+    pub anomalies: &'a [Anomaly],
+}
+
+impl<'a> ArchitectureUpdate<'a> {
+    /// The change from `previous`, the map the client last received, to `current`
+    pub fn between(previous: Option<&ArchitectureMap>, current: &'a ArchitectureMap) -> Self {
+        let Some(previous) = previous else {
+            return ArchitectureUpdate::Full { architecture: current };
+        };
+
+        let previous_nodes: HashMap<&str, serde_json::Value> = previous
+            .nodes
+            .iter()
+            .map(|(id, node)| (id.as_str(), serde_json::to_value(node).unwrap_or_default()))
+            .collect();
+        let mut added_nodes = Vec::new();
+        let mut updated_nodes = Vec::new();
+        for (id, node) in &current.nodes {
+            match previous_nodes.get(id.as_str()) {
+                Some(old) if *old == serde_json::to_value(node).unwrap_or_default() => {}
+                Some(_) => updated_nodes.push(node),
+                None => added_nodes.push(node),
+            }
+        }
+        let mut removed_nodes: Vec<String> = previous
+            .nodes
+            .keys()
+            .filter(|id| !current.nodes.contains_key(*id))
+            .cloned()
+            .collect();
+        removed_nodes.sort();
+
+        let touched = added_nodes.len() + updated_nodes.len() + removed_nodes.len();
+        if touched as f64 > current.nodes.len().max(1) as f64 * FULL_UPDATE_SHARE {
+            return ArchitectureUpdate::Full { architecture: current };
+        }
+
+        let previous_edges: HashMap<String, serde_json::Value> = previous
+            .edge_ids()
+            .into_iter()
+            .zip(&previous.edges)
+            .map(|(id, edge)| (id, serde_json::to_value(edge).unwrap_or_default()))
+            .collect();
+        let current_edges: Vec<(String, &DependencyEdge)> = current.edge_ids().into_iter().zip(&current.edges).collect();
+        let current_ids: HashSet<&str> = current_edges.iter().map(|(id, _)| id.as_str()).collect();
+        let mut removed_edges: Vec<String> = previous_edges
+            .keys()
+            .filter(|id| !current_ids.contains(id.as_str()))
+            .cloned()
+            .collect();
+        let mut added_edges = Vec::new();
+        for &(ref id, edge) in &current_edges {
+            match previous_edges.get(id) {
+                Some(old) if *old == serde_json::to_value(edge).unwrap_or_default() => {}
+                Some(_) => {
+                    removed_edges.push(id.clone());
+                    added_edges.push(EdgeEntry { id: id.clone(), edge });
+                }
+                None => added_edges.push(EdgeEntry { id: id.clone(), edge }),
+            }
+        }
+        removed_edges.sort();
+
+        ArchitectureUpdate::Delta(Box::new(ArchitectureDelta {
+            last_scan: current.last_scan,
+            added_nodes,
+            updated_nodes,
+            removed_nodes,
+            added_edges,
+            removed_edges,
+            total_modules: current.total_modules,
+            total_lines: current.total_lines,
+            average_complexity: current.average_complexity,
+            circular_dependencies: &current.circular_dependencies,
+            metrics: &current.metrics,
+            hook_outcome: current.hook_outcome.as_ref(),
+            anomalies: &current.anomalies,
+        }))
+    }
+}
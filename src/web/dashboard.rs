@@ -0,0 +1,198 @@
+//! Landing page for a server hosting several projects.
+//!
+//! Each project is mounted under `/projects/<slug>` with the usual routes; the
+//! root lists them with their headline metrics.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{Html, Json},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::{collections::HashSet, sync::Arc};
+
+use crate::{visualizer::static_page::escape_html, web::WebState};
+
+/// A project mounted by the dashboard
+#[derive(Clone)]
+pub struct ProjectEntry {
+    /// URL segment under `/projects/`
+    pub slug: String,
+    pub name: String,
+    pub state: WebState,
+}
+
+/// State shared by the landing page handlers
+#[derive(Clone)]
+pub struct DashboardState {
+    pub projects: Arc<Vec<ProjectEntry>>,
+}
+
+/// Headline metrics of one project
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectSummary {
+    pub slug: String,
+    pub name: String,
+    pub path: String,
+    pub url: String,
+    pub total_modules: usize,
+    pub circular_dependencies: usize,
+    pub health_score: f64,
+    pub last_scan: Option<DateTime<Utc>>,
+    /// Why the project could not be scanned, if it failed
+    pub error: Option<String>,
+}
+
+/// URL-safe slug for `name` that does not collide with any in `taken`
+pub fn unique_slug(name: &str, taken: &mut HashSet<String>) -> String {
+    let mut base: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if base.is_empty() {
+        base = "project".to_string();
+    }
+
+    let mut slug = base.clone();
+    let mut suffix = 2;
+    while !taken.insert(slug.clone()) {
+        slug = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    slug
+}
+
+/// Scan results of every project, scanning those that have not been scanned yet
+async fn summarize(projects: &[ProjectEntry]) -> Vec<ProjectSummary> {
+    let mut summaries = Vec::new();
+    for project in projects {
+        let (path, cached) = {
+            let visualizer = project.state.visualizer.read().await;
+            (visualizer.project_path().display().to_string(), visualizer.cached_architecture())
+        };
+        let architecture = match cached {
+            Some(architecture) => Ok(architecture),
+            None => project.state.visualizer.read().await.refresh().await,
+        };
+
+        let mut summary = ProjectSummary {
+            slug: project.slug.clone(),
+            name: project.name.clone(),
+            path,
+            url: format!("/projects/{}", project.slug),
+            total_modules: 0,
+            circular_dependencies: 0,
+            health_score: 0.0,
+            last_scan: None,
+            error: None,
+        };
+        match architecture {
+            Ok(architecture) => {
+                summary.total_modules = architecture.total_modules;
+                summary.circular_dependencies = architecture.circular_dependencies.len();
+                summary.health_score = architecture.metrics.health_score;
+                summary.last_scan = Some(architecture.last_scan);
+            }
+            Err(e) => summary.error = Some(format!("{:#}", e)),
+        }
+        summaries.push(summary);
+    }
+    summaries
+}
+
+/// Headline metrics of all projects as JSON
+pub async fn projects_handler(State(state): State<DashboardState>) -> Result<Json<Vec<ProjectSummary>>, StatusCode> {
+    Ok(Json(summarize(&state.projects).await))
+}
+
+/// Landing page listing every project
+pub async fn landing_handler(State(state): State<DashboardState>) -> Result<Html<String>, StatusCode> {
+    let summaries = summarize(&state.projects).await;
+    Ok(Html(render_landing_page(&summaries)))
+}
+
+fn render_landing_page(summaries: &[ProjectSummary]) -> String {
+    let cards: String = summaries.iter().map(render_project_card).collect();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>Architecture Visualizer · Projects</title>
+    <style>
+*{{margin:0;padding:0;box-sizing:border-box;}}
+body{{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',Roboto,sans-serif;min-height:100vh;background:#f4f5ff;color:#1f2937;padding:2rem;}}
+.container{{max-width:1320px;margin:0 auto;background:#fff;border-radius:20px;box-shadow:0 12px 26px rgba(15,23,42,.12);overflow:hidden;}}
+.header{{background:linear-gradient(135deg,#667eea,#764ba2);color:#fff;text-align:center;padding:2.2rem 2rem;}}
+.header h1{{font-size:2.4rem;margin-bottom:.6rem;}}
+.header p{{opacity:.85;}}
+.projects{{display:grid;grid-template-columns:repeat(auto-fill,minmax(280px,1fr));gap:1.4rem;padding:1.8rem;}}
+.project-card{{display:block;background:#fff;border-radius:16px;padding:1.4rem;box-shadow:0 10px 24px rgba(15,23,42,.12);color:inherit;text-decoration:none;border:1px solid transparent;}}
+.project-card:hover{{border-color:#667eea;}}
+.project-card h2{{font-size:1.2rem;margin-bottom:.2rem;}}
+.project-path{{font-size:.8rem;color:#64748b;word-break:break-all;margin-bottom:1rem;}}
+.project-metrics{{display:grid;grid-template-columns:repeat(3,1fr);gap:.5rem;text-align:center;}}
+.metric-value{{font-size:1.5rem;font-weight:700;color:#667eea;}}
+.metric-value.warn{{color:#ef4444;}}
+.metric-label{{text-transform:uppercase;font-size:.7rem;letter-spacing:.08em;color:#64748b;}}
+.project-scan{{margin-top:1rem;font-size:.8rem;color:#64748b;}}
+.project-error{{margin-top:1rem;font-size:.85rem;color:#ef4444;}}
+    </style>
+</head>
+<body>
+    <div class="container">
+        <div class="header">
+            <h1>🏗️ Architecture Visualizer</h1>
+            <p>{} project{}</p>
+        </div>
+        <div class="projects">{}</div>
+    </div>
+</body>
+</html>"#,
+        summaries.len(),
+        if summaries.len() == 1 { "" } else { "s" },
+        cards
+    )
+}
+
+fn render_project_card(summary: &ProjectSummary) -> String {
+    let body = match &summary.error {
+        Some(error) => format!(r#"<div class="project-error">Scan failed: {}</div>"#, escape_html(error)),
+        None => format!(
+            r#"<div class="project-metrics">
+                <div><div class="metric-value">{}</div><div class="metric-label">Modules</div></div>
+                <div><div class="metric-value{}">{}</div><div class="metric-label">Cycles</div></div>
+                <div><div class="metric-value">{:.0}</div><div class="metric-label">Health</div></div>
+            </div>
+            <div class="project-scan">Last scan: {}</div>"#,
+            summary.total_modules,
+            if summary.circular_dependencies > 0 { " warn" } else { "" },
+            summary.circular_dependencies,
+            summary.health_score,
+            summary
+                .last_scan
+                .map(|time| time.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                .unwrap_or_else(|| "never".to_string())
+        ),
+    };
+
+    format!(
+        r#"<a class="project-card" href="{}">
+            <h2>{}</h2>
+            <div class="project-path">{}</div>
+            {}
+        </a>"#,
+        escape_html(&summary.url),
+        escape_html(&summary.name),
+        escape_html(&summary.path),
+        body
+    )
+}
@@ -0,0 +1,277 @@
+//! gRPC service following `proto/service.proto`, served next to the web server.
+//!
+//! Requests are decoded with prost; responses are the messages the protobuf
+//! export writes, so the service adds no generated code and no `protoc` step.
+
+use anyhow::Result;
+use prost::bytes::{Buf, BufMut};
+use std::{convert::Infallible, future::Future, marker::PhantomData, net::SocketAddr, sync::Arc};
+use tokio::sync::{broadcast::error::RecvError, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{
+    body::BoxBody,
+    codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+    codegen::{http, Body, BoxFuture, Context, Poll, Service, StdError},
+    server::{Grpc, NamedService, ServerStreamingService, UnaryService},
+    Status,
+};
+use tracing::{debug, info, warn};
+
+use crate::{
+    diff,
+    export::protobuf,
+    history::HistoryStore,
+    scanner::git_analyzer,
+    types::ArchitectureMap,
+    visualizer::ArchitectureVisualizer,
+    web::WebState,
+};
+
+/// Refreshed maps a `Watch` call may fall behind by before it skips to the newest
+const WATCH_BUFFER: usize = 4;
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScanRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GetArchitectureRequest {
+    /// Empty for the current scan
+    #[prost(string, tag = "1")]
+    pub snapshot: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct DiffRequest {
+    #[prost(string, tag = "1")]
+    pub from_snapshot: String,
+    /// Empty for the current scan
+    #[prost(string, tag = "2")]
+    pub to_snapshot: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct WatchRequest {}
+
+/// Serve the project of `state` over gRPC on `addr` until the server fails
+pub async fn serve(state: WebState, addr: SocketAddr) -> Result<()> {
+    info!("🔌 gRPC service listening on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(ArchitectureService { state })
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+/// `rust_arch_viz.v1.ArchitectureService`
+#[derive(Clone)]
+pub struct ArchitectureService {
+    state: WebState,
+}
+
+impl NamedService for ArchitectureService {
+    const NAME: &'static str = "rust_arch_viz.v1.ArchitectureService";
+}
+
+impl<B> Service<http::Request<B>> for ArchitectureService
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let state = self.state.clone();
+        let method = request.uri().path().rsplit('/').next().unwrap_or_default().to_string();
+        Box::pin(async move {
+            let response = match method.as_str() {
+                "Scan" => Grpc::new(RequestCodec::default()).unary(Unary(|_: ScanRequest| scan(state.clone())), request).await,
+                "GetArchitecture" => {
+                    let call = |request: GetArchitectureRequest| get_architecture(state.clone(), request);
+                    Grpc::new(RequestCodec::default()).unary(Unary(call), request).await
+                }
+                "Diff" => Grpc::new(RequestCodec::default()).unary(Unary(|request: DiffRequest| diff(state.clone(), request)), request).await,
+                "Watch" => Grpc::new(RequestCodec::default()).server_streaming(Watch(state), request).await,
+                _ => Status::unimplemented(format!("No method {}", method)).into_http(),
+            };
+            Ok(response)
+        })
+    }
+}
+
+async fn scan(state: WebState) -> Result<Vec<u8>, Status> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.refresh().await.map_err(internal)?;
+    Ok(protobuf::encode(&architecture))
+}
+
+async fn get_architecture(state: WebState, request: GetArchitectureRequest) -> Result<Vec<u8>, Status> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = architecture(&visualizer, &request.snapshot).await?;
+    Ok(protobuf::encode(&architecture))
+}
+
+async fn diff(state: WebState, request: DiffRequest) -> Result<Vec<u8>, Status> {
+    if request.from_snapshot.is_empty() {
+        return Err(Status::invalid_argument("from_snapshot is required"));
+    }
+    let visualizer = state.visualizer.read().await;
+    let before = architecture(&visualizer, &request.from_snapshot).await?;
+    let after = architecture(&visualizer, &request.to_snapshot).await?;
+
+    let mut report = diff::compare(&before, &after);
+    let git_commit = |architecture: &ArchitectureMap| {
+        architecture.provenance.as_ref().and_then(|provenance| provenance.git_commit.clone())
+    };
+    if let (Some(from), Some(to)) = (git_commit(&before), git_commit(&after)) {
+        if from != to {
+            diff::attach_commits(&mut report, &git_analyzer::commits_between(visualizer.project_path(), &from, &to));
+        }
+    }
+    Ok(protobuf::encode_diff(&report))
+}
+
+/// The stored snapshot `id`, `latest` for the newest one, or the current scan when `id` is empty
+async fn architecture(visualizer: &ArchitectureVisualizer, id: &str) -> Result<Arc<ArchitectureMap>, Status> {
+    if id.is_empty() {
+        return visualizer.get_architecture().await.map_err(internal);
+    }
+    let store = HistoryStore::existing_for_project(visualizer.project_path(), visualizer.get_config())
+        .ok_or_else(|| Status::not_found("The project has no stored snapshots"))?;
+    let id = if id == "latest" {
+        store.list().map_err(internal)?.pop().map(|snapshot| snapshot.id)
+            .ok_or_else(|| Status::not_found("The project has no stored snapshots"))?
+    } else if store.contains(id) {
+        id.to_string()
+    } else {
+        return Err(Status::not_found(format!("Snapshot not found: {}", id)));
+    };
+    store.load(&id).map(Arc::new).map_err(internal)
+}
+
+fn internal(e: anyhow::Error) -> Status {
+    Status::internal(format!("{:#}", e))
+}
+
+/// A unary method from its request message to its encoded response
+struct Unary<F>(F);
+
+impl<R, F, Fut> UnaryService<R> for Unary<F>
+where
+    F: FnMut(R) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>, Status>> + Send + 'static,
+{
+    type Response = Vec<u8>;
+    type Future = BoxFuture<tonic::Response<Vec<u8>>, Status>;
+
+    fn call(&mut self, request: tonic::Request<R>) -> Self::Future {
+        let response = (self.0)(request.into_inner());
+        Box::pin(async move { response.await.map(tonic::Response::new) })
+    }
+}
+
+/// The `Watch` method: the current map, then every refresh the served project sees
+struct Watch(WebState);
+
+impl ServerStreamingService<WatchRequest> for Watch {
+    type Response = Vec<u8>;
+    type ResponseStream = ReceiverStream<Result<Vec<u8>, Status>>;
+    type Future = BoxFuture<tonic::Response<Self::ResponseStream>, Status>;
+
+    fn call(&mut self, _request: tonic::Request<WatchRequest>) -> Self::Future {
+        let state = self.0.clone();
+        Box::pin(async move {
+            let visualizer = state.visualizer.read().await;
+            // Subscribe first so no refresh falls between loading the map and listening
+            let mut refreshes = visualizer.subscribe_refreshes();
+            let current = match visualizer.cached_architecture() {
+                Some(architecture) => architecture,
+                None => visualizer.refresh().await.map_err(internal)?,
+            };
+            drop(visualizer);
+
+            let (sender, receiver) = mpsc::channel(WATCH_BUFFER);
+            tokio::spawn(async move {
+                if sender.send(Ok(protobuf::encode(&current))).await.is_err() {
+                    return;
+                }
+                let mut sent = current;
+                loop {
+                    let architecture = match refreshes.recv().await {
+                        Ok(architecture) => architecture,
+                        Err(RecvError::Lagged(skipped)) => {
+                            debug!("gRPC watch skipped {} refreshes", skipped);
+                            continue;
+                        }
+                        Err(RecvError::Closed) => break,
+                    };
+                    if Arc::ptr_eq(&sent, &architecture) {
+                        continue;
+                    }
+                    // The client hung up
+                    if sender.send(Ok(protobuf::encode(&architecture))).await.is_err() {
+                        break;
+                    }
+                    sent = architecture;
+                }
+            });
+            Ok(tonic::Response::new(ReceiverStream::new(receiver)))
+        })
+    }
+}
+
+/// Decodes requests with prost and passes responses through, as they are already encoded
+struct RequestCodec<R>(PhantomData<R>);
+
+impl<R> Default for RequestCodec<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R: prost::Message + Default + Send + 'static> Codec for RequestCodec<R> {
+    type Encode = Vec<u8>;
+    type Decode = R;
+    type Encoder = EncodedEncoder;
+    type Decoder = RequestDecoder<R>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        EncodedEncoder
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RequestDecoder(PhantomData)
+    }
+}
+
+struct EncodedEncoder;
+
+impl Encoder for EncodedEncoder {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
+
+struct RequestDecoder<R>(PhantomData<R>);
+
+impl<R: prost::Message + Default> Decoder for RequestDecoder<R> {
+    type Item = R;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<R>, Status> {
+        let request = R::decode(src.copy_to_bytes(src.remaining())).map_err(|e| {
+            warn!("Undecodable gRPC request: {}", e);
+            Status::invalid_argument(e.to_string())
+        })?;
+        Ok(Some(request))
+    }
+}
@@ -1,29 +1,102 @@
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{OriginalUri, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
     response::{Html, Json},
 };
+use serde::Deserialize;
 use serde_json::json;
+use std::sync::Arc;
 
 use crate::{
-    web::WebState,
-    types::ArchitectureMap,
+    check::{self, RulePreview},
+    config::{CheckSettings, ProjectConfig, RuleSettings, SettingsUpdate},
+    curation::Curation,
+    history::{window, HistoryStore},
+    rules::cycles,
+    scanner::{classification, documentation, ignored},
+    schema,
+    simulation::{self, SimulationReport, SimulationRequest},
+    web::{search, WebState},
+    types::{ArchitectureMap, ArchitectureNode, ModuleType, NodeStatus},
+    visualizer::{doc_coverage, static_page, vendor_assets, HtmlOptions},
 };
 
+/// Query parameters of the index page
+#[derive(Debug, Deserialize)]
+pub struct IndexQuery {
+    /// `1` forces the script-free page, `0` the interactive one
+    pub nojs: Option<String>,
+}
+
 /// Main index page handler
-pub async fn index_handler(State(state): State<WebState>) -> Result<Html<String>, StatusCode> {
+pub async fn index_handler(
+    State(state): State<WebState>,
+    Query(query): Query<IndexQuery>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<Html<String>, StatusCode> {
     let visualizer = state.visualizer.read().await;
     let architecture = visualizer.get_architecture().await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    let html = visualizer.generate_html(&architecture)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let use_static_page = match query.nojs.as_deref() {
+        Some("1") | Some("true") => true,
+        Some(_) => false,
+        None => headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(static_page::prefers_static_page),
+    };
+    let html = if use_static_page {
+        visualizer.generate_static_html(&architecture)
+    } else {
+        let options = HtmlOptions {
+            live_updates: state.watch_mode,
+            // Under the dashboard the page lives below `/projects/<slug>`
+            asset_base: visualizer
+                .vendor_dir()
+                .map(|_| format!("{}/static", uri.path().trim_end_matches('/'))),
+            ..HtmlOptions::default()
+        };
+        visualizer.generate_html_with_options(&architecture, &options)
+    }
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     Ok(Html(html))
 }
 
+/// Rule editor page
+pub async fn settings_page_handler(State(state): State<WebState>) -> Html<String> {
+    let visualizer = state.visualizer.read().await;
+    
+    Html(visualizer.generate_settings_html())
+}
+
+/// Documentation coverage page
+pub async fn doc_coverage_handler(State(state): State<WebState>) -> Result<Html<String>, StatusCode> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Html(visualizer.generate_doc_coverage_html(&architecture)))
+}
+
+/// Documentation coverage badge, for READMEs
+pub async fn doc_coverage_badge_handler(
+    State(state): State<WebState>,
+) -> Result<([(header::HeaderName, &'static str); 2], String), StatusCode> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok((
+        [(header::CONTENT_TYPE, "image/svg+xml"), (header::CACHE_CONTROL, "no-cache")],
+        doc_coverage::render_badge(&architecture),
+    ))
+}
+
 /// Architecture data API handler
-pub async fn architecture_handler(State(state): State<WebState>) -> Result<Json<ArchitectureMap>, StatusCode> {
+pub async fn architecture_handler(State(state): State<WebState>) -> Result<Json<Arc<ArchitectureMap>>, StatusCode> {
     let visualizer = state.visualizer.read().await;
     let architecture = visualizer.get_architecture().await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -31,9 +104,141 @@ pub async fn architecture_handler(State(state): State<WebState>) -> Result<Json<
     Ok(Json(architecture))
 }
 
+/// Modules returned per page when the query sets no `limit`
+const DEFAULT_MODULES_LIMIT: usize = 100;
+/// Most modules returned per page
+const MAX_MODULES_LIMIT: usize = 1000;
+
+/// Query parameters of the module list
+#[derive(Debug, Deserialize)]
+pub struct ModulesQuery {
+    /// Only modules of this type, e.g. `Core`
+    #[serde(rename = "type")]
+    pub module_type: Option<ModuleType>,
+    /// Only modules with this status, e.g. `Error`
+    pub status: Option<NodeStatus>,
+    /// Only modules with at least this complexity score
+    pub min_complexity: Option<f64>,
+    /// Only modules with at most this complexity score
+    pub max_complexity: Option<f64>,
+    /// Modules to skip, in file path order
+    pub offset: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// One page of the modules matching the query, without their items
+pub async fn modules_handler(
+    State(state): State<WebState>,
+    Query(query): Query<ModulesQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let mut modules: Vec<&ArchitectureNode> = architecture.nodes.values()
+        .filter(|node| passes_filters(node, &query.module_type, &query.status, query.min_complexity, query.max_complexity))
+        .collect();
+    modules.sort_by(|a, b| a.file_path.cmp(&b.file_path).then_with(|| a.id.cmp(&b.id)));
+    let offset = query.offset.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_MODULES_LIMIT).min(MAX_MODULES_LIMIT);
+    let page: Vec<serde_json::Value> = modules.iter()
+        .skip(offset)
+        .take(limit)
+        .map(|node| json!({
+            "id": node.id,
+            "name": node.name,
+            "module_type": node.module_type,
+            "status": node.status,
+            "file_path": node.file_path,
+            "crate_name": node.crate_name,
+            "module_path": node.module_path,
+            "layer": node.layer,
+            "ignored": node.ignored,
+            "metrics": node.metrics,
+        }))
+        .collect();
+    
+    Ok(Json(json!({
+        "total": modules.len(),
+        "offset": offset,
+        "limit": limit,
+        "modules": page,
+    })))
+}
+
+/// One module with its items, dependencies and dependents
+pub async fn module_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+) -> Result<Json<ArchitectureNode>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    architecture.nodes.get(&id)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown module: {}", id)))
+}
+
+/// Whether `node` has the type, status and complexity range a query asks for
+fn passes_filters(
+    node: &ArchitectureNode,
+    module_type: &Option<ModuleType>,
+    status: &Option<NodeStatus>,
+    min_complexity: Option<f64>,
+    max_complexity: Option<f64>,
+) -> bool {
+    module_type.as_ref().is_none_or(|module_type| node.module_type == *module_type)
+        && status.as_ref().is_none_or(|status| node.status == *status)
+        && min_complexity.is_none_or(|min| node.metrics.complexity_score >= min)
+        && max_complexity.is_none_or(|max| node.metrics.complexity_score <= max)
+}
+
+/// Results returned when the query sets no `limit`
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Query parameters of the module search
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    /// Terms to find in module names, file paths and item names
+    pub q: String,
+    #[serde(rename = "type")]
+    pub module_type: Option<ModuleType>,
+    pub status: Option<NodeStatus>,
+    pub min_complexity: Option<f64>,
+    pub max_complexity: Option<f64>,
+    pub limit: Option<usize>,
+}
+
+/// Modules fuzzily matching the query, best first, among those passing the filters
+pub async fn search_handler(
+    State(state): State<WebState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    if query.q.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Missing search query `q`".to_string()));
+    }
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let candidates = architecture.nodes.values()
+        .filter(|node| passes_filters(node, &query.module_type, &query.status, query.min_complexity, query.max_complexity));
+    let mut results = search::search(candidates, &query.q);
+    let total = results.len();
+    results.truncate(query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_MODULES_LIMIT));
+    
+    Ok(Json(json!({
+        "query": query.q,
+        "total": total,
+        "results": results,
+    })))
+}
+
 /// Refresh architecture data handler
 pub async fn refresh_handler(State(state): State<WebState>) -> Result<Json<serde_json::Value>, StatusCode> {
-    let mut visualizer = state.visualizer.write().await;
+    let visualizer = state.visualizer.read().await;
     let result = visualizer.refresh().await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
@@ -47,9 +252,50 @@ pub async fn refresh_handler(State(state): State<WebState>) -> Result<Json<serde
 /// Configuration handler
 pub async fn config_handler(State(state): State<WebState>) -> Result<Json<serde_json::Value>, StatusCode> {
     let visualizer = state.visualizer.read().await;
-    let config = visualizer.get_config();
     
-    Ok(Json(json!({
+    Ok(Json(config_json(visualizer.get_config(), state.watch_mode)))
+}
+
+/// Update settings, persist them to the project's config file and rescan
+pub async fn update_config_handler(
+    State(state): State<WebState>,
+    Json(update): Json<SettingsUpdate>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    // Every section is validated before anything is written
+    {
+        let visualizer = state.visualizer.read().await;
+        if let Some(classification) = &update.classification {
+            classification::validate(classification)
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+        }
+        if let Some(ignore) = &update.ignore {
+            ignored::validate(ignore)
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+        }
+        if update.rules.is_some() || update.check.is_some() {
+            let config = visualizer.get_config();
+            let rules = update.rules.as_ref().unwrap_or(&config.rules);
+            let check = update.check.as_ref().unwrap_or(&config.check);
+            // Evaluating the rules once rejects invalid globs and empty selectors
+            let architecture = visualizer.get_architecture().await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+            check::preview(&architecture, check, rules)
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+        }
+    }
+
+    state.visualizer.write().await.update_settings(update)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+
+    // Rescan once for every changed section, without keeping readers waiting on the write lock
+    state.refresh().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    let visualizer = state.visualizer.read().await;
+    Ok(Json(config_json(visualizer.get_config(), state.watch_mode)))
+}
+
+fn config_json(config: &ProjectConfig, watch_mode: bool) -> serde_json::Value {
+    json!({
         "project": {
             "name": config.project.name,
             "description": config.project.description,
@@ -66,16 +312,43 @@ pub async fn config_handler(State(state): State<WebState>) -> Result<Json<serde_
         "server": {
             "port": config.server.port,
             "host": config.server.host,
-            "watch_mode": state.watch_mode
-        }
-    })))
+            "watch_mode": watch_mode
+        },
+        "classification": config.classification,
+        "ignore": config.ignore,
+        "rules": config.rules,
+        "check": config.check
+    })
+}
+
+/// Query parameters of the metrics endpoint
+#[derive(Debug, Deserialize)]
+pub struct MetricsQuery {
+    /// Start of the history window to aggregate, e.g. `30d` or `2024-05-01`
+    pub since: Option<String>,
 }
 
 /// Metrics handler
-pub async fn metrics_handler(State(state): State<WebState>) -> Result<Json<serde_json::Value>, StatusCode> {
+pub async fn metrics_handler(
+    State(state): State<WebState>,
+    Query(query): Query<MetricsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let visualizer = state.visualizer.read().await;
     let architecture = visualizer.get_architecture().await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let window = match query.since {
+        Some(since) => {
+            let since = window::parse_since(&since, chrono::Utc::now())
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+            let snapshots = match HistoryStore::existing_for_project(visualizer.project_path(), visualizer.get_config()) {
+                Some(store) => store.list().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?,
+                None => Vec::new(),
+            };
+            Some(window::aggregate(&snapshots, since, Some(&architecture)))
+        }
+        None => None,
+    };
     
     Ok(Json(json!({
         "total_modules": architecture.total_modules,
@@ -84,15 +357,319 @@ pub async fn metrics_handler(State(state): State<WebState>) -> Result<Json<serde
         "total_dependencies": architecture.edges.len(),
         "circular_dependencies": architecture.circular_dependencies.len(),
         "metrics": architecture.metrics,
-        "last_scan": architecture.last_scan
+        "last_scan": architecture.last_scan,
+        "provenance": architecture.provenance,
+        "window": window,
+        "metadata": {
+            "formulas": visualizer.metric_formulas(),
+            "complexity_weights": visualizer.get_config().metrics.complexity,
+        }
+    })))
+}
+
+/// Stored scan snapshots, oldest first; empty when the project has no history yet
+pub async fn history_handler(State(state): State<WebState>) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let snapshots = match HistoryStore::existing_for_project(visualizer.project_path(), visualizer.get_config()) {
+        Some(store) => store.list().map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?,
+        None => Vec::new(),
+    };
+    
+    Ok(Json(json!({
+        "total": snapshots.len(),
+        "snapshots": snapshots,
+    })))
+}
+
+/// The full architecture map of one stored snapshot
+pub async fn history_snapshot_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+) -> Result<Json<ArchitectureMap>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = load_snapshot(visualizer.project_path(), visualizer.get_config(), &id)?;
+    
+    Ok(Json(architecture))
+}
+
+/// The PNG preview stored with a snapshot
+pub async fn history_thumbnail_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+) -> Result<([(header::HeaderName, &'static str); 2], Vec<u8>), (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let thumbnail = HistoryStore::existing_for_project(visualizer.project_path(), visualizer.get_config())
+        .map(|store| store.thumbnail(&id))
+        .transpose()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?
+        .flatten()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No thumbnail for snapshot {}", id)))?;
+    
+    // Snapshots never change once saved
+    Ok(([(header::CONTENT_TYPE, "image/png"), (header::CACHE_CONTROL, "max-age=86400")], thumbnail))
+}
+
+/// Query parameters of the graph data endpoint
+#[derive(Debug, Deserialize)]
+pub struct FlowQuery {
+    /// Id of a stored snapshot to return instead of the current scan
+    pub snapshot: Option<String>,
+}
+
+/// Graph data as the interactive page renders it, of the current scan or a stored snapshot
+pub async fn flow_handler(
+    State(state): State<WebState>,
+    Query(query): Query<FlowQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = match query.snapshot {
+        Some(id) => Arc::new(load_snapshot(visualizer.project_path(), visualizer.get_config(), &id)?),
+        None => visualizer.get_architecture().await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+    
+    Ok(Json(visualizer.flow_data(&architecture)))
+}
+
+/// Query parameters of the SVG export
+#[derive(Debug, Deserialize)]
+pub struct SvgExportQuery {
+    /// `hierarchical` or `grid`; the configured layout when left out
+    pub layout: Option<String>,
+    /// Id of a stored snapshot to draw instead of the current scan
+    pub snapshot: Option<String>,
+}
+
+/// The module graph as an SVG diagram, laid out on the server
+pub async fn svg_export_handler(
+    State(state): State<WebState>,
+    Query(query): Query<SvgExportQuery>,
+) -> Result<([(header::HeaderName, &'static str); 2], String), (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let mut settings = visualizer.get_config().visualization.clone();
+    if let Some(layout) = query.layout {
+        settings.layout = layout.parse().map_err(|e: anyhow::Error| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
+    let architecture = match query.snapshot {
+        Some(id) => Arc::new(load_snapshot(visualizer.project_path(), visualizer.get_config(), &id)?),
+        None => visualizer.get_architecture().await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+    };
+    let svg = static_page::render_graph_svg(&architecture, &settings)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml"), (header::CACHE_CONTROL, "no-cache")], svg))
+}
+
+fn load_snapshot(project: &std::path::Path, config: &ProjectConfig, id: &str) -> Result<ArchitectureMap, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, format!("Snapshot not found: {}", id));
+    let store = HistoryStore::existing_for_project(project, config)
+        .filter(|store| store.contains(id))
+        .ok_or_else(not_found)?;
+    store.load(id).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))
+}
+
+/// Pinned positions and manual groups
+pub async fn layout_handler(State(state): State<WebState>) -> Result<Json<Curation>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let curation = visualizer.load_curation()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    
+    Ok(Json(curation))
+}
+
+/// Replace the pinned positions and manual groups, persisting them to the layout file
+pub async fn update_layout_handler(
+    State(state): State<WebState>,
+    Json(curation): Json<Curation>,
+) -> Result<Json<Curation>, (StatusCode, String)> {
+    curation.validate()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    // Hold the write lock so concurrent updates are written one at a time
+    let visualizer = state.visualizer.write().await;
+    curation.save(&visualizer.curation_path())
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e)))?;
+    
+    Ok(Json(curation))
+}
+
+/// Refactor simulation handler: project the graph after hypothetical changes
+pub async fn simulate_handler(
+    State(state): State<WebState>,
+    Json(request): Json<SimulationRequest>,
+) -> Result<Json<SimulationReport>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let report = simulation::simulate(&architecture, &request.changes)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    Ok(Json(report))
+}
+
+/// A dependency edge with the source locations that create it
+pub async fn edge_handler(
+    State(state): State<WebState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let edge = architecture.edge_ids().iter().position(|edge_id| *edge_id == id)
+        .map(|index| &architecture.edges[index])
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Unknown edge: {}", id)))?;
+    let name_of = |node_id: &str| architecture.nodes.get(node_id).map(|node| node.name.clone());
+    
+    Ok(Json(json!({
+        "id": id,
+        "from": edge.from,
+        "to": edge.to,
+        "from_name": name_of(&edge.from),
+        "to_name": name_of(&edge.to),
+        "relationship": edge.relationship,
+        "strength": edge.strength,
+        "is_circular": edge.is_circular,
+        "layer_violation": edge.layer_violation,
+        "locations": edge.locations,
+        "suggestions": edge.suggestions,
+    })))
+}
+
+/// Draft rules and `check` thresholds; sections left out are the configured ones
+#[derive(Debug, Deserialize)]
+pub struct RulesDraft {
+    pub rules: Option<RuleSettings>,
+    pub check: Option<CheckSettings>,
+}
+
+/// What `check` would report with draft rules, without saving or rescanning
+pub async fn rules_preview_handler(
+    State(state): State<WebState>,
+    Json(draft): Json<RulesDraft>,
+) -> Result<Json<RulePreview>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let config = visualizer.get_config();
+    let rules = draft.rules.unwrap_or_else(|| config.rules.clone());
+    let check = draft.check.unwrap_or_else(|| config.check.clone());
+    
+    check::preview(&architecture, &check, &rules)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))
+}
+
+/// Strongly connected components of the module graph, with the `use` statements to remove to break each
+pub async fn cycles_handler(
+    State(state): State<WebState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let cycles = cycles::analyze(&architecture);
+    
+    Ok(Json(json!({
+        "total": cycles.len(),
+        "cycles": cycles,
+    })))
+}
+
+/// Edges that break the configured layering, with suggestions for removing each, and the
+/// public signatures that expose another workspace crate's types
+pub async fn rule_violations_handler(
+    State(state): State<WebState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let node = |id: &str| architecture.nodes.get(id);
+    let violations: Vec<serde_json::Value> = architecture.edges.iter()
+        .zip(architecture.edge_ids())
+        .filter(|(edge, _)| edge.layer_violation)
+        .map(|(edge, id)| json!({
+            "id": id,
+            "from": edge.from,
+            "to": edge.to,
+            "from_name": node(&edge.from).map(|node| &node.name),
+            "to_name": node(&edge.to).map(|node| &node.name),
+            "from_layer": node(&edge.from).and_then(|node| node.layer.as_ref()),
+            "to_layer": node(&edge.to).and_then(|node| node.layer.as_ref()),
+            "locations": edge.locations,
+            "suggestions": edge.suggestions,
+        }))
+        .collect();
+    let mut api_leaks: Vec<serde_json::Value> = architecture.nodes.values()
+        .flat_map(|node| node.api_leaks.iter().map(move |leak| json!({
+            "module": node.id,
+            "module_name": node.name,
+            "file_path": node.file_path,
+            "item": leak.item,
+            "signature": leak.signature,
+            "crate": leak.crate_name,
+            "type_path": leak.type_path,
+            "line": leak.line,
+        })))
+        .collect();
+    api_leaks.sort_by(|a, b| (a["file_path"].as_str(), a["line"].as_u64()).cmp(&(b["file_path"].as_str(), b["line"].as_u64())));
+    
+    Ok(Json(json!({
+        "total": violations.len(),
+        "violations": violations,
+        "api_leaks": api_leaks,
     })))
 }
 
-/// Static file handler (for serving assets)
-pub async fn static_handler() -> Result<Html<&'static str>, StatusCode> {
-    // For now, return a simple message
-    // In a real implementation, you'd serve static files
-    Ok(Html("<h1>Static files not implemented yet</h1>"))
+/// Project file linked as module documentation, served as plain text
+pub async fn docs_handler(
+    State(state): State<WebState>,
+    Path(path): Path<String>,
+) -> Result<([(header::HeaderName, &'static str); 1], String), (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let architecture = visualizer.get_architecture().await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    // Only files some module links to are served, never arbitrary project files
+    if !documentation::is_linked_file(architecture.nodes.values(), &path) {
+        return Err((StatusCode::NOT_FOUND, format!("No module links to {}", path)));
+    }
+    let project_dir = visualizer.project_path().canonicalize()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let file = project_dir.join(&path).canonicalize()
+        .map_err(|_| (StatusCode::NOT_FOUND, format!("Documentation file not found: {}", path)))?;
+    if !file.starts_with(&project_dir) {
+        return Err((StatusCode::NOT_FOUND, format!("Documentation file not found: {}", path)));
+    }
+    let content = tokio::fs::read_to_string(&file).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to read {}: {}", path, e)))?;
+    
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], content))
+}
+
+/// Vendored libraries of the interactive page, when `offline_assets` is enabled
+pub async fn static_handler(
+    State(state): State<WebState>,
+    Path(path): Path<String>,
+) -> Result<([(header::HeaderName, &'static str); 1], Vec<u8>), (StatusCode, String)> {
+    let visualizer = state.visualizer.read().await;
+    let Some(vendor_dir) = visualizer.vendor_dir() else {
+        return Err((StatusCode::NOT_FOUND, "Offline assets are disabled".to_string()));
+    };
+    // Only the known file names are served, never other files of the directory
+    let content_type = vendor_assets::content_type(&path)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("No vendored asset named {}", path)))?;
+    let content = vendor_assets::read(&vendor_dir, &path)
+        .map_err(|e| (StatusCode::NOT_FOUND, format!("{:#}", e)))?;
+    
+    Ok(([(header::CONTENT_TYPE, content_type)], content))
+}
+
+/// JSON Schema of the architecture data, e.g. of `/api/architecture`
+pub async fn schema_handler() -> Json<schemars::schema::RootSchema> {
+    Json(schema::json_schema())
 }
 
 /// Health check handler
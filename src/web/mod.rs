@@ -1,18 +1,40 @@
 pub mod server;
 pub mod handlers;
 pub mod websocket;
+pub mod dashboard;
+pub mod patch;
+pub mod search;
+pub mod topics;
+pub mod architecture_update;
+pub mod agent;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 
+use anyhow::Result;
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use crate::visualizer::ArchitectureVisualizer;
+use tokio::sync::{broadcast, RwLock};
+use crate::{
+    types::{ArchitectureMap, BuildStatus},
+    visualizer::ArchitectureVisualizer,
+};
 
+pub use architecture_update::ArchitectureUpdate;
+pub use patch::{diff_flow_data, ChangeSummary, FlowPatch, LiveUpdate, MetricsUpdate};
+pub use topics::{Subscription, Topic};
 pub use server::WebServer;
 
+/// Undelivered updates kept per subscriber before it starts skipping; each refresh sends one per topic
+const UPDATE_BUFFER: usize = 48;
+
 /// Web server state
 #[derive(Clone)]
 pub struct WebState {
     pub visualizer: Arc<RwLock<ArchitectureVisualizer>>,
     pub watch_mode: bool,
+    /// Changes pushed to pages connected to `/ws`
+    pub updates: broadcast::Sender<LiveUpdate>,
+    /// Token remote agents authenticate with; agent pushes are refused without one
+    pub agent_token: Option<Arc<str>>,
 }
 
 impl WebState {
@@ -20,10 +42,83 @@ impl WebState {
         Self {
             visualizer: Arc::new(RwLock::new(visualizer)),
             watch_mode: false,
+            updates: broadcast::channel(UPDATE_BUFFER).0,
+            agent_token: None,
         }
     }
     
     pub fn set_watch_mode(&mut self, enabled: bool) {
         self.watch_mode = enabled;
     }
+
+    /// Refresh after a source change and push what changed to connected pages
+    pub async fn refresh_after_change(&self) -> Result<()> {
+        let (hook, project_path) = {
+            let visualizer = self.visualizer.read().await;
+            (visualizer.watch_command(), visualizer.project_path().to_path_buf())
+        };
+        // Run the command unlocked, so settings saves waiting to write do not stall every reader
+        let outcome = match hook {
+            Some(hook) => {
+                let _ = self.updates.send(LiveUpdate::Build(BuildStatus::Building { command: hook.command().to_string() }));
+                match hook.run(&project_path).await {
+                    Ok(outcome) => Some(outcome),
+                    Err(e) => {
+                        tracing::warn!("Watch command failed: {:#}", e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let visualizer = self.visualizer.read().await;
+        visualizer
+            .refresh_with(outcome, |previous, architecture| {
+                if let Some(outcome) = &architecture.hook_outcome {
+                    let _ = self.updates.send(LiveUpdate::Build(BuildStatus::from_outcome(outcome)));
+                }
+                self.publish(&visualizer, previous, architecture);
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Rescan without the watch command and push what changed to connected pages
+    pub async fn refresh(&self) -> Result<()> {
+        let visualizer = self.visualizer.read().await;
+        visualizer
+            .refresh_with(None, |previous, architecture| self.publish(&visualizer, previous, architecture))
+            .await?;
+        Ok(())
+    }
+
+    /// Swap in a scan pushed by a remote agent and push what changed to connected pages
+    pub async fn accept_agent_scan(&self, architecture: ArchitectureMap) -> Arc<ArchitectureMap> {
+        let visualizer = self.visualizer.read().await;
+        visualizer
+            .accept_remote_scan(architecture, |previous, architecture| self.publish(&visualizer, previous, architecture))
+            .await
+    }
+
+    /// Push what changed from `previous` to `architecture`; called while the swap is serialized
+    fn publish(&self, visualizer: &ArchitectureVisualizer, previous: Option<&ArchitectureMap>, architecture: &ArchitectureMap) {
+        let known_anomalies = previous.map(|previous| previous.anomalies.as_slice()).unwrap_or_default();
+        for anomaly in architecture.anomalies.iter().filter(|anomaly| !known_anomalies.contains(anomaly)) {
+            tracing::warn!("⚠️  Anomaly since the latest snapshot: {}", anomaly.message);
+        }
+
+        let _ = self.updates.send(LiveUpdate::Scanned { last_scan: architecture.last_scan });
+        if let Some(previous) = previous {
+            let mut patch = diff_flow_data(&visualizer.flow_data(previous), &visualizer.flow_data(architecture));
+            if !patch.is_empty() {
+                patch.last_scan = Some(architecture.last_scan);
+                // Every topic goes out; each connection picks what it subscribed to.
+                // Nobody listening is not an error
+                let _ = self.updates.send(LiveUpdate::Diff(patch.summary()));
+                let _ = self.updates.send(LiveUpdate::Metrics(Box::new(MetricsUpdate::from_architecture(architecture))));
+                let _ = self.updates.send(LiveUpdate::Patch(Box::new(patch)));
+            }
+        }
+    }
 }
@@ -0,0 +1,350 @@
+//! Node-level changes between two renderings of the graph, pushed to open pages in watch mode.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{types::{ArchitectureMap, BuildStatus}, visualizer::crate_view::UNOWNED_CRATE, web::Subscription};
+
+/// Module nodes that changed, keyed by node id
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NodeChanges {
+    pub updated: Vec<Value>,
+    pub added: Vec<Value>,
+    pub removed: Vec<String>,
+}
+
+/// Dependency edges that appeared or disappeared, keyed by edge id
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EdgeChanges {
+    pub added: Vec<Value>,
+    pub removed: Vec<String>,
+}
+
+/// Difference between two [`flow_data`](crate::visualizer::ArchitectureVisualizer::flow_data) values.
+///
+/// Node and edge entries have the shape the page renders; the crate,
+/// component and feature graphs, the anomaly notices and the orphan list are small and are resent whole
+/// when they change.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlowPatch {
+    pub nodes: NodeChanges,
+    pub edges: EdgeChanges,
+    pub crates: Option<Value>,
+    pub components: Option<Value>,
+    pub features: Option<Value>,
+    pub anomalies: Option<Value>,
+    pub orphans: Option<Value>,
+    pub cycles: Option<Value>,
+    pub variants: Option<Value>,
+    pub last_scan: Option<DateTime<Utc>>,
+    /// Crate of every node the patch touches, from either rendering
+    #[serde(skip)]
+    node_crates: HashMap<String, String>,
+    /// Names of touched nodes, so removed ones can still be reported by name
+    #[serde(skip)]
+    node_names: HashMap<String, String>,
+    /// Endpoints of every edge the patch touches
+    #[serde(skip)]
+    edge_endpoints: HashMap<String, (String, String)>,
+}
+
+impl FlowPatch {
+    pub fn is_empty(&self) -> bool {
+        self.nodes.updated.is_empty()
+            && self.nodes.added.is_empty()
+            && self.nodes.removed.is_empty()
+            && self.edges.added.is_empty()
+            && self.edges.removed.is_empty()
+            && self.crates.is_none()
+            && self.components.is_none()
+            && self.features.is_none()
+            && self.anomalies.is_none()
+            && self.orphans.is_none()
+            && self.cycles.is_none()
+            && self.variants.is_none()
+    }
+
+    /// The part of the patch about modules of `crate_name` and edges touching them.
+    ///
+    /// The crate, component and feature graphs span the workspace and are left out.
+    pub fn for_crate(&self, crate_name: &str) -> FlowPatch {
+        let in_crate = |id: &str| self.node_crates.get(id).is_some_and(|name| name == crate_name);
+        let edge_in_crate = |id: &str| {
+            self.edge_endpoints
+                .get(id)
+                .is_some_and(|(source, target)| in_crate(source) || in_crate(target))
+        };
+        let entry_id = |entry: &Value| entry["id"].as_str().unwrap_or_default().to_string();
+
+        FlowPatch {
+            nodes: NodeChanges {
+                updated: self.nodes.updated.iter().filter(|entry| in_crate(&entry_id(entry))).cloned().collect(),
+                added: self.nodes.added.iter().filter(|entry| in_crate(&entry_id(entry))).cloned().collect(),
+                removed: self.nodes.removed.iter().filter(|id| in_crate(id)).cloned().collect(),
+            },
+            edges: EdgeChanges {
+                added: self.edges.added.iter().filter(|entry| edge_in_crate(&entry_id(entry))).cloned().collect(),
+                removed: self.edges.removed.iter().filter(|id| edge_in_crate(id)).cloned().collect(),
+            },
+            crates: None,
+            components: None,
+            features: None,
+            anomalies: self.anomalies.clone(),
+            // Ids of other crates' modules are skipped by the page
+            orphans: self.orphans.clone(),
+            cycles: self.cycles.clone(),
+            variants: self.variants.clone(),
+            last_scan: self.last_scan,
+            node_crates: self.node_crates.clone(),
+            node_names: self.node_names.clone(),
+            edge_endpoints: self.edge_endpoints.clone(),
+        }
+    }
+
+    /// Which modules changed, without their rendered entries
+    pub fn summary(&self) -> ChangeSummary {
+        let module = |id: String| ChangedModule {
+            name: self.node_names.get(&id).cloned().unwrap_or_else(|| id.clone()),
+            crate_name: self.node_crates.get(&id).cloned().unwrap_or_else(|| UNOWNED_CRATE.to_string()),
+            id,
+        };
+        let entry_ids = |entries: &[Value]| -> Vec<String> {
+            entries.iter().filter_map(|entry| entry["id"].as_str().map(str::to_string)).collect()
+        };
+        let replaced = self.edges.added.iter().filter(|entry| {
+            entry["id"].as_str().is_some_and(|id| self.edges.removed.iter().any(|removed| removed == id))
+        });
+        let edges_changed = replaced.count();
+
+        ChangeSummary {
+            added: entry_ids(&self.nodes.added).into_iter().map(module).collect(),
+            updated: entry_ids(&self.nodes.updated).into_iter().map(module).collect(),
+            removed: self.nodes.removed.iter().cloned().map(module).collect(),
+            edges_added: self.edges.added.len() - edges_changed,
+            edges_changed,
+            edges_removed: self.edges.removed.len() - edges_changed,
+            last_scan: self.last_scan,
+        }
+    }
+}
+
+/// A module named in a [`ChangeSummary`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangedModule {
+    pub id: String,
+    pub name: String,
+    pub crate_name: String,
+}
+
+/// Modules that were added, changed or removed by a refresh, for the `diff` topic
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeSummary {
+    pub added: Vec<ChangedModule>,
+    pub updated: Vec<ChangedModule>,
+    pub removed: Vec<ChangedModule>,
+    pub edges_added: usize,
+    pub edges_changed: usize,
+    pub edges_removed: usize,
+    pub last_scan: Option<DateTime<Utc>>,
+}
+
+impl ChangeSummary {
+    /// Only the modules of `crate_name`; edge counts stay workspace-wide
+    pub fn for_crate(&self, crate_name: &str) -> ChangeSummary {
+        let keep = |modules: &[ChangedModule]| -> Vec<ChangedModule> {
+            modules.iter().filter(|module| module.crate_name == crate_name).cloned().collect()
+        };
+        ChangeSummary {
+            added: keep(&self.added),
+            updated: keep(&self.updated),
+            removed: keep(&self.removed),
+            ..self.clone()
+        }
+    }
+}
+
+/// Headline numbers of one crate
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrateTotals {
+    pub total_modules: usize,
+    pub total_lines: usize,
+    pub average_complexity: f64,
+}
+
+/// Headline numbers after a refresh, for the `metrics` topic
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsUpdate {
+    pub total_modules: usize,
+    pub total_lines: usize,
+    pub average_complexity: f64,
+    pub circular_dependencies: usize,
+    pub health_score: f64,
+    pub crates: BTreeMap<String, CrateTotals>,
+    pub last_scan: DateTime<Utc>,
+}
+
+impl MetricsUpdate {
+    pub fn from_architecture(architecture: &ArchitectureMap) -> Self {
+        let mut by_crate: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+        for node in architecture.nodes.values() {
+            by_crate.entry(node.crate_name.as_deref().unwrap_or(UNOWNED_CRATE)).or_default().push(node);
+        }
+        let crates = by_crate
+            .into_iter()
+            .map(|(name, nodes)| {
+                // Generated modules stay out of the average, as in the project-wide number
+                let complexities: Vec<f64> = nodes
+                    .iter()
+                    .filter(|node| !node.module_type.is_generated())
+                    .map(|node| node.metrics.complexity_score)
+                    .collect();
+                let totals = CrateTotals {
+                    total_modules: nodes.len(),
+                    total_lines: nodes.iter().map(|node| node.metrics.lines_of_code).sum(),
+                    average_complexity: if complexities.is_empty() {
+                        0.0
+                    } else {
+                        complexities.iter().sum::<f64>() / complexities.len() as f64
+                    },
+                };
+                (name.to_string(), totals)
+            })
+            .collect();
+
+        Self {
+            total_modules: architecture.total_modules,
+            total_lines: architecture.total_lines,
+            average_complexity: architecture.average_complexity,
+            circular_dependencies: architecture.circular_dependencies.len(),
+            health_score: architecture.metrics.health_score,
+            crates,
+            last_scan: architecture.last_scan,
+        }
+    }
+
+    /// Only the totals of `crate_name`; the project-wide numbers stay
+    pub fn for_crate(&self, crate_name: &str) -> MetricsUpdate {
+        MetricsUpdate {
+            crates: self.crates.iter().filter(|(name, _)| *name == crate_name).map(|(name, totals)| (name.clone(), totals.clone())).collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Message sent to clients connected to `/ws`
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveUpdate {
+    Patch(Box<FlowPatch>),
+    Diff(ChangeSummary),
+    Metrics(Box<MetricsUpdate>),
+    /// The watch command started or finished
+    Build(BuildStatus),
+    /// Updates were dropped for a slow subscriber, so its page has to reload
+    Resync,
+    /// A refresh finished, whether or not it changed anything
+    Scanned { last_scan: DateTime<Utc> },
+    /// Acknowledges a `subscribe` request
+    Subscribed(Subscription),
+    /// A client message could not be understood
+    Error { message: String },
+}
+
+/// Compare two renderings of the graph
+pub fn diff_flow_data(previous: &Value, current: &Value) -> FlowPatch {
+    let mut patch = FlowPatch::default();
+
+    for entry in [&previous["nodes"], &current["nodes"]].into_iter().filter_map(Value::as_array).flatten() {
+        if let Some(id) = entry["id"].as_str() {
+            patch.node_crates.insert(id.to_string(), entry["crateName"].as_str().unwrap_or(UNOWNED_CRATE).to_string());
+            patch.node_names.insert(id.to_string(), entry["name"].as_str().unwrap_or(id).to_string());
+        }
+    }
+    for entry in [&previous["edges"], &current["edges"]].into_iter().filter_map(Value::as_array).flatten() {
+        if let (Some(id), Some(source), Some(target)) = (entry["id"].as_str(), entry["source"].as_str(), entry["target"].as_str()) {
+            patch.edge_endpoints.insert(id.to_string(), (source.to_string(), target.to_string()));
+        }
+    }
+
+    let previous_nodes = entries_by_id(&previous["nodes"]);
+    let current_nodes = entries_by_id(&current["nodes"]);
+    for (id, entry) in &current_nodes {
+        match previous_nodes.get(id) {
+            // The sort position alone shifts whenever a module is added
+            Some(old) if without_order(old) == without_order(entry) => {}
+            Some(_) => patch.nodes.updated.push((*entry).clone()),
+            None => patch.nodes.added.push((*entry).clone()),
+        }
+    }
+    patch.nodes.removed = removed_ids(&previous_nodes, &current_nodes);
+
+    let previous_edges = entries_by_id(&previous["edges"]);
+    let current_edges = entries_by_id(&current["edges"]);
+    for (id, entry) in &current_edges {
+        if previous_edges.get(id) != Some(entry) {
+            if previous_edges.contains_key(id) {
+                patch.edges.removed.push(id.to_string());
+            }
+            patch.edges.added.push((*entry).clone());
+        }
+    }
+    patch.edges.removed.extend(removed_ids(&previous_edges, &current_edges));
+
+    if previous["crates"] != current["crates"] {
+        patch.crates = Some(current["crates"].clone());
+    }
+    if previous["components"] != current["components"] {
+        patch.components = Some(current["components"].clone());
+    }
+    if previous["features"] != current["features"] {
+        patch.features = Some(current["features"].clone());
+    }
+    if previous["anomalies"] != current["anomalies"] {
+        patch.anomalies = Some(current["anomalies"].clone());
+    }
+    if previous["orphans"] != current["orphans"] {
+        patch.orphans = Some(current["orphans"].clone());
+    }
+    if previous["cycles"] != current["cycles"] {
+        patch.cycles = Some(current["cycles"].clone());
+    }
+    if previous["variants"] != current["variants"] {
+        patch.variants = Some(current["variants"].clone());
+    }
+
+    patch
+}
+
+fn entries_by_id(entries: &Value) -> HashMap<&str, &Value> {
+    entries
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry["id"].as_str().map(|id| (id, entry)))
+        .collect()
+}
+
+fn removed_ids(previous: &HashMap<&str, &Value>, current: &HashMap<&str, &Value>) -> Vec<String> {
+    let mut removed: Vec<String> = previous
+        .keys()
+        .filter(|id| !current.contains_key(*id))
+        .map(|id| id.to_string())
+        .collect();
+    removed.sort();
+    removed
+}
+
+fn without_order(entry: &Value) -> Value {
+    let mut entry = entry.clone();
+    if let Some(object) = entry.as_object_mut() {
+        object.remove("order");
+    }
+    entry
+}
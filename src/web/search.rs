@@ -0,0 +1,146 @@
+//! Fuzzy module search behind `/api/search` and the page's search box.
+//!
+//! A query term matches a text when its characters appear in it in order,
+//! ignoring case. Whole substrings score above scattered matches, and matches
+//! at the start of the text or of a word above those in the middle. A module
+//! scores each term by its best field, weighted so its name counts most, then
+//! the items it contains, then its file path; every term has to match.
+
+use serde::Serialize;
+
+use crate::types::{ArchitectureNode, ModuleType};
+
+/// Weight of a match in the module name
+const NAME_WEIGHT: f64 = 1.0;
+/// Weight of a match in the name of a function, struct, enum or trait of the module
+const ITEM_WEIGHT: f64 = 0.8;
+/// Weight of a match in the file path
+const PATH_WEIGHT: f64 = 0.6;
+
+/// A module matching a search, with the field that matched best
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub id: String,
+    pub name: String,
+    pub file_path: String,
+    pub module_type: ModuleType,
+    pub score: f64,
+    /// `name`, `file_path`, `function`, `struct`, `enum` or `trait`
+    pub matched_field: &'static str,
+    pub matched_text: String,
+}
+
+/// Modules of `nodes` matching every whitespace-separated term of `query`, best first
+pub fn search<'a>(nodes: impl Iterator<Item = &'a ArchitectureNode>, query: &str) -> Vec<SearchResult> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut results: Vec<SearchResult> = nodes.filter_map(|node| score_node(node, &terms)).collect();
+    results.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.file_path.cmp(&b.file_path)));
+    results
+}
+
+fn score_node(node: &ArchitectureNode, terms: &[String]) -> Option<SearchResult> {
+    let items = node
+        .functions
+        .iter()
+        .map(|item| ("function", &item.name))
+        .chain(node.structs.iter().map(|item| ("struct", &item.name)))
+        .chain(node.enums.iter().map(|item| ("enum", &item.name)))
+        .chain(node.traits.iter().map(|item| ("trait", &item.name)));
+    let fields: Vec<(&'static str, &str, f64)> = [("name", node.name.as_str(), NAME_WEIGHT), ("file_path", node.file_path.as_str(), PATH_WEIGHT)]
+        .into_iter()
+        .chain(items.map(|(kind, name)| (kind, name.as_str(), ITEM_WEIGHT)))
+        .collect();
+
+    let mut total = 0.0;
+    let mut best: Option<(f64, &'static str, &str)> = None;
+    for term in terms {
+        let (score, field, text) = fields
+            .iter()
+            .filter_map(|&(field, text, weight)| Some((fuzzy_score(term, text)? * weight, field, text)))
+            .max_by(|a, b| a.0.total_cmp(&b.0))?;
+        total += score;
+        if best.is_none_or(|(best_score, _, _)| score > best_score) {
+            best = Some((score, field, text));
+        }
+    }
+    let (_, matched_field, matched_text) = best?;
+    Some(SearchResult {
+        id: node.id.clone(),
+        name: node.name.clone(),
+        file_path: node.file_path.clone(),
+        module_type: node.module_type.clone(),
+        score: total / terms.len() as f64,
+        matched_field,
+        matched_text: matched_text.to_string(),
+    })
+}
+
+/// How well lowercase `term` matches `text`, up to 100 for an exact match; `None` when it does not
+fn fuzzy_score(term: &str, text: &str) -> Option<f64> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text.to_lowercase().chars().collect();
+    let term: Vec<char> = term.chars().collect();
+    // Lowercasing keeps the length of the identifiers and paths searched here; fall back to no match otherwise
+    if term.is_empty() || lower.len() != chars.len() {
+        return None;
+    }
+    let coverage = term.len() as f64 / lower.len() as f64;
+    if lower == term {
+        return Some(100.0);
+    }
+    if let Some(start) = lower.windows(term.len()).position(|window| window == term.as_slice()) {
+        let position_bonus = if start == 0 {
+            20.0
+        } else if lower
+            .windows(term.len())
+            .enumerate()
+            .any(|(index, window)| window == term.as_slice() && is_word_start(&chars, index))
+        {
+            10.0
+        } else {
+            0.0
+        };
+        return Some(60.0 + position_bonus + 10.0 * coverage);
+    }
+
+    // Scattered in order; consecutive characters and word starts count extra, long gaps less
+    let mut points = 0.0;
+    let mut next = 0;
+    let mut first = None;
+    let mut previous: Option<usize> = None;
+    for &wanted in &term {
+        // The next character if it follows on, else the next word starting with it, else its next occurrence
+        let rest = next..lower.len();
+        let index = if lower.get(next) == Some(&wanted) {
+            next
+        } else {
+            let mut candidates = rest.filter(|&index| lower[index] == wanted);
+            let first = candidates.next()?;
+            std::iter::once(first).chain(candidates).find(|&index| is_word_start(&chars, index)).unwrap_or(first)
+        };
+        points += 1.0;
+        if previous == Some(index.wrapping_sub(1)) {
+            points += 2.0;
+        }
+        if is_word_start(&chars, index) {
+            points += 2.0;
+        }
+        first.get_or_insert(index);
+        previous = Some(index);
+        next = index + 1;
+    }
+    let span = next - first.unwrap_or(0);
+    let density = term.len() as f64 / span as f64;
+    Some(30.0 * points / (5.0 * term.len() as f64) + 10.0 * density + 10.0 * coverage)
+}
+
+/// Whether a word begins at `index`: after a separator, or at a lowercase-to-uppercase step
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    let Some(&previous) = index.checked_sub(1).and_then(|previous| chars.get(previous)) else {
+        return true;
+    };
+    !previous.is_alphanumeric() || (previous.is_lowercase() && chars[index].is_uppercase())
+}
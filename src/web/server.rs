@@ -1,5 +1,9 @@
 use anyhow::Result;
 use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::{header, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     routing::{get, post},
     Router,
 };
@@ -9,39 +13,87 @@ use tower_http::{
     compression::CompressionLayer,
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{info, warn};
+
+use std::{collections::HashSet, sync::Arc};
 
 use crate::{
-    web::{handlers, websocket, WebState},
+    config::StaleDataPolicy,
+    export,
+    web::{
+        dashboard::{self, DashboardState, ProjectEntry},
+        agent, handlers, websocket, WebState,
+    },
     visualizer::ArchitectureVisualizer,
 };
 
 /// Web server for the architecture visualizer
 pub struct WebServer {
-    visualizer: ArchitectureVisualizer,
+    projects: Vec<ArchitectureVisualizer>,
     watch_mode: bool,
+    agent_token: Option<String>,
+    grpc_port: Option<u16>,
 }
 
 impl WebServer {
     pub fn new(visualizer: ArchitectureVisualizer) -> Self {
         Self {
-            visualizer,
+            projects: vec![visualizer],
             watch_mode: false,
+            agent_token: None,
+            grpc_port: None,
         }
     }
     
+    /// Serve another project; with more than one, `/` becomes a landing page listing them
+    pub fn with_project(mut self, visualizer: ArchitectureVisualizer) -> Self {
+        self.projects.push(visualizer);
+        self
+    }
+    
     pub fn watch_mode(mut self, enabled: bool) -> Self {
         self.watch_mode = enabled;
         self
     }
     
+    /// Accept scans pushed by remote agents that present `token`
+    pub fn agent_token(mut self, token: Option<String>) -> Self {
+        self.agent_token = token;
+        self
+    }
+    
+    /// Also serve the gRPC service on `port` of the same host; needs the `grpc` feature
+    pub fn grpc_port(mut self, port: Option<u16>) -> Self {
+        self.grpc_port = port;
+        self
+    }
+    
     /// Start the web server
     pub async fn serve(self, host: &str, port: u16) -> Result<()> {
         let watch_mode = self.watch_mode;
-        let mut state = WebState::new(self.visualizer);
-        state.set_watch_mode(watch_mode);
+        let agent_token: Option<Arc<str>> = self.agent_token.map(Arc::from);
+        let mut states = Vec::new();
+        for visualizer in self.projects {
+            let mut state = WebState::new(visualizer);
+            state.set_watch_mode(watch_mode);
+            state.agent_token = agent_token.clone();
+            if watch_mode {
+                Self::spawn_watch_loop(state.clone()).await;
+            }
+            Self::spawn_export_loop(state.clone()).await;
+            Self::spawn_freshness_loop(state.clone()).await;
+            states.push(state);
+        }
+        if let Some(grpc_port) = self.grpc_port {
+            Self::spawn_grpc(&states, host, grpc_port).await?;
+        }
         
-        let app = Self::create_router_static(state);
+        let app = if states.len() == 1 {
+            Self::project_routes(states.remove(0))
+        } else {
+            Self::dashboard_routes(states).await
+        };
+        let app = Self::with_middleware(app);
         
         let listener = tokio::net::TcpListener::bind(&format!("{}:{}", host, port)).await?;
         
@@ -51,33 +103,272 @@ impl WebServer {
         if watch_mode {
             info!("👀 Watch mode enabled - auto-refreshing on file changes");
         }
+        if agent_token.is_some() {
+            info!("📡 Accepting scans from remote agents");
+        }
         
         axum::serve(listener, app).await?;
         
         Ok(())
     }
     
-    /// Create the router with all routes
-    fn create_router_static(state: WebState) -> Router {
+    #[cfg(feature = "grpc")]
+    async fn spawn_grpc(states: &[WebState], host: &str, port: u16) -> Result<()> {
+        let [state] = states else {
+            anyhow::bail!("The gRPC service serves a single project, not {}", states.len());
+        };
+        let addr = tokio::net::lookup_host((host, port))
+            .await?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("Cannot resolve {}", host))?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(e) = crate::web::grpc::serve(state, addr).await {
+                warn!("gRPC service stopped: {:#}", e);
+            }
+        });
+        Ok(())
+    }
+    
+    #[cfg(not(feature = "grpc"))]
+    async fn spawn_grpc(_states: &[WebState], _host: &str, _port: u16) -> Result<()> {
+        anyhow::bail!("This build has no gRPC service; rebuild with `--features grpc`")
+    }
+    
+    /// Refresh (running the watch command) whenever the project's sources change.
+    ///
+    /// Uses file events when the `watch` feature is on and the platform provides
+    /// them, and polls the sources otherwise.
+    async fn spawn_watch_loop(state: WebState) {
+        #[cfg(feature = "watch")]
+        {
+            let (project_path, debounce) = {
+                let visualizer = state.visualizer.read().await;
+                let debounce = std::time::Duration::from_millis(visualizer.get_config().watch.debounce_ms);
+                (visualizer.project_path().to_path_buf(), debounce)
+            };
+            match crate::scanner::watcher::SourceWatcher::new(&project_path, debounce) {
+                Ok(watcher) => {
+                    Self::spawn_event_loop(state, watcher);
+                    return;
+                }
+                Err(e) => warn!("File events unavailable, polling for changes instead: {:#}", e),
+            }
+        }
+        
+        let poll_interval = {
+            let visualizer = state.visualizer.read().await;
+            std::time::Duration::from_millis(visualizer.get_config().watch.poll_interval_ms.max(100))
+        };
+        
+        tokio::spawn(async move {
+            let mut last_fingerprint = None;
+            let mut ticker = tokio::time::interval(poll_interval);
+            
+            loop {
+                ticker.tick().await;
+                
+                let fingerprint = match state.visualizer.read().await.source_fingerprint() {
+                    Ok(fingerprint) => fingerprint,
+                    Err(e) => {
+                        warn!("Failed to check project for changes: {:#}", e);
+                        continue;
+                    }
+                };
+                
+                if last_fingerprint == Some(fingerprint) {
+                    continue;
+                }
+                
+                if last_fingerprint.is_some() {
+                    info!("🔁 Change detected, refreshing architecture");
+                }
+                last_fingerprint = Some(fingerprint);
+                
+                if let Err(e) = state.refresh_after_change().await {
+                    warn!("Watch refresh failed: {:#}", e);
+                }
+            }
+        });
+    }
+    
+    /// Refresh once, then after every debounced burst of file events
+    #[cfg(feature = "watch")]
+    fn spawn_event_loop(state: WebState, mut watcher: crate::scanner::watcher::SourceWatcher) {
+        tokio::spawn(async move {
+            if let Err(e) = state.refresh_after_change().await {
+                warn!("Watch refresh failed: {:#}", e);
+            }
+            
+            while let Some(paths) = watcher.changed().await {
+                info!("🔁 {} file(s) changed, refreshing architecture", paths.len());
+                if let Err(e) = state.refresh_after_change().await {
+                    warn!("Watch refresh failed: {:#}", e);
+                }
+            }
+        });
+    }
+    
+    /// Rescan whenever the served scan gets older than `server.max_data_age`
+    async fn spawn_freshness_loop(state: WebState) {
+        let max_age = {
+            let visualizer = state.visualizer.read().await;
+            match visualizer.get_config().server.max_data_age {
+                Some(max_age) if visualizer.get_config().server.on_stale_data == StaleDataPolicy::Rescan => max_age.max(1),
+                _ => return,
+            }
+        };
+        
+        tokio::spawn(async move {
+            // Often enough that no scan outlives the limit by more than a tenth of it
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs((max_age / 10).clamp(1, 60)));
+            
+            loop {
+                ticker.tick().await;
+                
+                let age = {
+                    let visualizer = state.visualizer.read().await;
+                    // Pushed scans can only be replaced by the agents that push them
+                    if visualizer.serves_remote_scans() {
+                        continue;
+                    }
+                    // Until the first request nothing is cached, and every request scans afresh
+                    match visualizer.cached_architecture() {
+                        Some(architecture) => (chrono::Utc::now() - architecture.last_scan).num_seconds(),
+                        None => continue,
+                    }
+                };
+                if age < max_age as i64 {
+                    continue;
+                }
+                
+                info!("⏰ Scan is {}s old, over the {}s limit; rescanning", age, max_age);
+                if let Err(e) = state.refresh().await {
+                    warn!("Rescan of stale data failed: {:#}", e);
+                }
+            }
+        });
+    }
+    
+    /// Write the configured export artifacts on startup and then every `exports.interval` seconds
+    async fn spawn_export_loop(state: WebState) {
+        let interval = {
+            let visualizer = state.visualizer.read().await;
+            let Some(directory) = export::schedule::export_directory(&visualizer) else {
+                return;
+            };
+            info!("🗂️  Writing exports to {} every {}s", directory.display(), visualizer.get_config().exports.interval);
+            std::time::Duration::from_secs(visualizer.get_config().exports.interval.max(1))
+        };
+        
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            
+            loop {
+                ticker.tick().await;
+                
+                // Watch mode keeps the cached scan current; otherwise rescan for every export
+                let refreshed = if state.watch_mode { Ok(()) } else { state.refresh().await };
+                let written = match refreshed {
+                    Ok(()) => {
+                        let visualizer = state.visualizer.read().await;
+                        visualizer
+                            .get_architecture()
+                            .await
+                            .and_then(|architecture| export::schedule::write_artifacts(&visualizer, &architecture))
+                    }
+                    Err(e) => Err(e),
+                };
+                match written {
+                    Ok(paths) => info!("🗂️  Wrote {} export artifacts", paths.len()),
+                    Err(e) => warn!("Scheduled export failed: {:#}", e),
+                }
+            }
+        });
+    }
+    
+    /// Landing page at `/` and each project under `/projects/<slug>`
+    async fn dashboard_routes(states: Vec<WebState>) -> Router {
+        let mut taken = HashSet::new();
+        let mut projects = Vec::new();
+        for state in states {
+            let name = {
+                let visualizer = state.visualizer.read().await;
+                visualizer.get_config().project.name.clone().unwrap_or_else(|| {
+                    visualizer
+                        .project_path()
+                        .canonicalize()
+                        .ok()
+                        .and_then(|path| path.file_name().map(|name| name.to_string_lossy().to_string()))
+                        .unwrap_or_else(|| visualizer.project_path().display().to_string())
+                })
+            };
+            let slug = dashboard::unique_slug(&name, &mut taken);
+            info!("📁 Serving {} at /projects/{}", name, slug);
+            projects.push(ProjectEntry { slug, name, state });
+        }
+        
+        let mut router = Router::new()
+            .route("/", get(dashboard::landing_handler))
+            .route("/api/projects", get(dashboard::projects_handler))
+            .route("/health", get(handlers::health_handler))
+            .with_state(DashboardState { projects: Arc::new(projects.clone()) });
+        for project in projects {
+            router = router.nest(&format!("/projects/{}", project.slug), Self::project_routes(project.state));
+        }
+        router
+    }
+    
+    /// Routes of a single project
+    fn project_routes(state: WebState) -> Router {
         Router::new()
             // Main routes
             .route("/", get(handlers::index_handler))
+            .route("/settings", get(handlers::settings_page_handler))
+            .route("/docs-coverage", get(handlers::doc_coverage_handler))
+            .route("/docs-coverage/badge.svg", get(handlers::doc_coverage_badge_handler))
             .route("/api/architecture", get(handlers::architecture_handler))
+            .route("/api/modules", get(handlers::modules_handler))
+            .route("/api/modules/:id", get(handlers::module_handler))
+            .route("/api/search", get(handlers::search_handler))
+            .route("/api/schema", get(handlers::schema_handler))
             .route("/api/refresh", post(handlers::refresh_handler))
-            .route("/api/config", get(handlers::config_handler))
+            .route("/api/config", get(handlers::config_handler).post(handlers::update_config_handler))
             .route("/api/metrics", get(handlers::metrics_handler))
+            .route("/api/history", get(handlers::history_handler))
+            .route("/api/history/:id", get(handlers::history_snapshot_handler))
+            .route("/api/history/:id/thumbnail", get(handlers::history_thumbnail_handler))
+            .route("/api/flow", get(handlers::flow_handler))
+            .route("/api/export/svg", get(handlers::svg_export_handler))
+            .route("/api/simulate", post(handlers::simulate_handler))
+            .route("/api/layout", get(handlers::layout_handler).put(handlers::update_layout_handler))
+            .route("/api/edges/:id", get(handlers::edge_handler))
+            .route("/api/cycles", get(handlers::cycles_handler))
+            .route("/api/rules/violations", get(handlers::rule_violations_handler))
+            .route("/api/rules/preview", post(handlers::rules_preview_handler))
+            .route("/api/docs/*path", get(handlers::docs_handler))
             
             // WebSocket routes
             .route("/ws", get(websocket::websocket_handler))
             .route("/ws/architecture", get(websocket::architecture_websocket_handler))
             
-            // Static files (if needed)
+            // Scans pushed by remote agents
+            .route("/ws/agent", get(agent::agent_websocket_handler))
+            .route(
+                "/api/agent/architecture",
+                post(agent::agent_push_handler).layer(DefaultBodyLimit::max(agent::MAX_SCAN_SIZE)),
+            )
+            
+            // Vendored libraries for `offline_assets`
             .route("/static/*path", get(handlers::static_handler))
             
             // Health check
             .route("/health", get(handlers::health_handler))
-            
-            // Add middleware
+            .with_state(state)
+    }
+    
+    fn with_middleware(router: Router) -> Router {
+        router
             .layer(
                 ServiceBuilder::new()
                     .layer(TraceLayer::new_for_http())
@@ -85,10 +376,27 @@ impl WebServer {
                     .layer(
                         CorsLayer::new()
                             .allow_origin(Any)
-                            .allow_methods(Any)
+                            .allow_methods([Method::GET, Method::HEAD])
                             .allow_headers(Any)
                     )
+                    .layer(middleware::from_fn(reject_cross_origin_writes))
             )
-            .with_state(state)
     }
 }
+
+/// Refuse requests that change state, like rewriting the config file, when a page of another origin sends them
+///
+/// Browsers name the sending page's origin on every such request; clients without
+/// one, like the agent or `curl`, are not pages another site can drive.
+async fn reject_cross_origin_writes(request: Request, next: Next) -> Response {
+    if !matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        if let Some(origin) = request.headers().get(header::ORIGIN) {
+            let origin_host = origin.to_str().ok().and_then(|origin| origin.split_once("://")).map(|(_, host)| host);
+            let host = request.headers().get(header::HOST).and_then(|host| host.to_str().ok());
+            if origin_host.is_none() || origin_host != host {
+                return (StatusCode::FORBIDDEN, "Requests from other origins may only read").into_response();
+            }
+        }
+    }
+    next.run(request).await
+}
@@ -0,0 +1,82 @@
+//! Topics a `/ws` client subscribes to, so small widgets only receive what they show.
+
+use serde::{Deserialize, Serialize};
+
+use crate::web::LiveUpdate;
+
+/// Kind of update a client can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Topic {
+    /// Node-level patches of the rendered graph
+    Graph,
+    /// Headline numbers after each refresh
+    Metrics,
+    /// Names of the modules a refresh added, changed or removed
+    Diff,
+    /// Start and result of the watch command, e.g. `cargo check`
+    Build,
+}
+
+/// What one client receives; new connections get graph patches for every crate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Subscription {
+    pub topics: Vec<Topic>,
+    /// Limit updates to the modules of one crate
+    #[serde(rename = "crate", skip_serializing_if = "Option::is_none")]
+    pub crate_name: Option<String>,
+}
+
+impl Default for Subscription {
+    fn default() -> Self {
+        Self {
+            topics: vec![Topic::Graph],
+            crate_name: None,
+        }
+    }
+}
+
+impl Subscription {
+    /// The form of `update` this subscriber should receive, if any
+    pub fn select(&self, update: &LiveUpdate) -> Option<LiveUpdate> {
+        let crate_name = self.crate_name.as_deref();
+        match update {
+            LiveUpdate::Patch(patch) if self.topics.contains(&Topic::Graph) => {
+                let patch = match crate_name {
+                    Some(name) => patch.for_crate(name),
+                    None => patch.as_ref().clone(),
+                };
+                (!patch.is_empty()).then(|| LiveUpdate::Patch(Box::new(patch)))
+            }
+            LiveUpdate::Diff(summary) if self.topics.contains(&Topic::Diff) => {
+                let summary = match crate_name {
+                    Some(name) => summary.for_crate(name),
+                    None => summary.clone(),
+                };
+                let changed = !summary.added.is_empty() || !summary.updated.is_empty() || !summary.removed.is_empty();
+                (crate_name.is_none() || changed).then_some(LiveUpdate::Diff(summary))
+            }
+            LiveUpdate::Metrics(metrics) if self.topics.contains(&Topic::Metrics) => Some(LiveUpdate::Metrics(Box::new(
+                match crate_name {
+                    Some(name) => metrics.for_crate(name),
+                    None => metrics.as_ref().clone(),
+                },
+            ))),
+            // The build covers the whole project, whatever crate was asked for
+            LiveUpdate::Build(status) if self.topics.contains(&Topic::Build) => Some(LiveUpdate::Build(status.clone())),
+            // Tells graph pages how old what they show is
+            LiveUpdate::Scanned { last_scan } if self.topics.contains(&Topic::Graph) => Some(LiveUpdate::Scanned { last_scan: *last_scan }),
+            LiveUpdate::Resync => Some(LiveUpdate::Resync),
+            _ => None,
+        }
+    }
+}
+
+/// Message a client sends over `/ws`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Replace the client's subscription
+    Subscribe(Subscription),
+}
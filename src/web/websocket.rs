@@ -1,29 +1,149 @@
 use axum::{
-    extract::State,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
     response::Response,
-    http::StatusCode,
 };
+use std::sync::Arc;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{debug, warn};
 
-use crate::web::WebState;
+use crate::{
+    types::ArchitectureMap,
+    web::{topics::ClientMessage, ArchitectureUpdate, LiveUpdate, Subscription, WebState},
+};
 
-/// WebSocket handler for real-time updates (placeholder)
+/// WebSocket pushing updates after each watch refresh, by default node-level patches for the page
 pub async fn websocket_handler(
-    State(_state): State<WebState>,
-) -> Result<Response, StatusCode> {
-    // For now, return a simple message indicating WebSocket is not implemented
-    Ok(Response::builder()
-        .status(StatusCode::NOT_IMPLEMENTED)
-        .body("WebSocket support not yet implemented".into())
-        .unwrap())
+    ws: WebSocketUpgrade,
+    State(state): State<WebState>,
+) -> Response {
+    let updates = state.updates.subscribe();
+    ws.on_upgrade(move |socket| forward_updates(socket, updates))
+}
+
+async fn forward_updates(mut socket: WebSocket, mut updates: Receiver<LiveUpdate>) {
+    let mut subscription = Subscription::default();
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let update = match update {
+                    Ok(update) => update,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("WebSocket client skipped {} updates", skipped);
+                        LiveUpdate::Resync
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if let Some(update) = subscription.select(&update) {
+                    if !send(&mut socket, &update).await {
+                        break;
+                    }
+                }
+            }
+            incoming = socket.recv() => {
+                let reply = match incoming {
+                    Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                        Ok(ClientMessage::Subscribe(requested)) => {
+                            subscription = requested;
+                            LiveUpdate::Subscribed(subscription.clone())
+                        }
+                        Err(e) => LiveUpdate::Error { message: format!("Invalid message: {}", e) },
+                    },
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue,
+                };
+                if !send(&mut socket, &reply).await {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Send `update` as JSON, returning whether the socket is still open
+async fn send(socket: &mut WebSocket, update: &LiveUpdate) -> bool {
+    match serde_json::to_string(update) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(e) => {
+            warn!("Failed to serialize live update: {}", e);
+            true
+        }
+    }
 }
 
-/// WebSocket handler specifically for architecture updates (placeholder)
+/// WebSocket streaming the architecture map: the whole map on connect, then
+/// what each refresh changed (or the whole map again after large changes)
 pub async fn architecture_websocket_handler(
-    State(_state): State<WebState>,
-) -> Result<Response, StatusCode> {
-    // For now, return a simple message indicating WebSocket is not implemented
-    Ok(Response::builder()
-        .status(StatusCode::NOT_IMPLEMENTED)
-        .body("WebSocket support not yet implemented".into())
-        .unwrap())
+    ws: WebSocketUpgrade,
+    State(state): State<WebState>,
+) -> Response {
+    let visualizer = state.visualizer.read().await;
+    // Subscribe first so no refresh falls between loading the map and listening
+    let refreshes = visualizer.subscribe_refreshes();
+    let current = match visualizer.cached_architecture() {
+        Some(architecture) => Some(architecture),
+        None => match visualizer.refresh().await {
+            Ok(architecture) => Some(architecture),
+            Err(e) => {
+                warn!("Initial scan for architecture stream failed: {:#}", e);
+                None
+            }
+        },
+    };
+    drop(visualizer);
+    ws.on_upgrade(move |socket| stream_architecture(socket, current, refreshes))
+}
+
+async fn stream_architecture(
+    mut socket: WebSocket,
+    current: Option<Arc<ArchitectureMap>>,
+    mut refreshes: Receiver<Arc<ArchitectureMap>>,
+) {
+    // The map the client holds; deltas are taken against it, so skipped refreshes lose nothing
+    let mut sent: Option<Arc<ArchitectureMap>> = None;
+    if let Some(current) = current {
+        if !send_update(&mut socket, &ArchitectureUpdate::between(None, &current)).await {
+            return;
+        }
+        sent = Some(current);
+    }
+    loop {
+        tokio::select! {
+            refreshed = refreshes.recv() => {
+                let architecture = match refreshed {
+                    Ok(architecture) => architecture,
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!("Architecture stream skipped {} refreshes", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                if sent.as_ref().is_some_and(|sent| Arc::ptr_eq(sent, &architecture)) {
+                    continue;
+                }
+                let update = ArchitectureUpdate::between(sent.as_deref(), &architecture);
+                if !send_update(&mut socket, &update).await {
+                    break;
+                }
+                sent = Some(architecture);
+            }
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+        }
+    }
+}
+
+/// Send `update` as JSON, returning whether the socket is still open
+async fn send_update(socket: &mut WebSocket, update: &ArchitectureUpdate<'_>) -> bool {
+    match serde_json::to_string(update) {
+        Ok(text) => socket.send(Message::Text(text)).await.is_ok(),
+        Err(e) => {
+            warn!("Failed to serialize architecture update: {}", e);
+            true
+        }
+    }
 }